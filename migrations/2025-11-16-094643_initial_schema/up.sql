@@ -249,27 +249,8 @@ FROM productos p
 LEFT JOIN inventarios i ON p.id = i.id_producto
 WHERE p.activo = TRUE;
 
--- ===== DATOS DE PRUEBA =====
-
--- Personas
-INSERT INTO personas (nombre, documento, perfil, email) VALUES
-('Juan Pérez', '12345678', 'CLIENTE', 'juan@email.com'),
-('María González', '87654321', 'VENDEDOR', 'maria@email.com'),
-('Proveedor ABC', '11111111', 'PROVEEDOR', 'proveedor@abc.com');
-
--- Productos
-INSERT INTO productos (nombre, cantidad, unidad_venta, precio_unitario) VALUES
-('Laptop Dell', 10, 'Unidad', 1200.00),
-('Mouse Logitech', 50, 'Unidad', 25.00),
-('Teclado Mecánico', 30, 'Unidad', 80.00);
-
--- Inicializar inventarios
-INSERT INTO inventarios (id_producto, id_persona, cantidad_disponible)
-SELECT
-    p.id,
-    (SELECT id FROM personas WHERE perfil = 'VENDEDOR' LIMIT 1),
-    p.cantidad
-FROM productos p;
+-- Los datos de demostración no forman parte del esquema: ver seeds/demo.sql
+-- (polimarket-admin seed --profile demo)
 
 -- ===== COMENTARIOS =====
 COMMENT ON VIEW vista_ventas_completas IS 'Ventas con todos sus detalles';
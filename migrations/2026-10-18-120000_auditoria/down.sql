@@ -0,0 +1,16 @@
+-- ===== ELIMINAR TRIGGERS =====
+DROP TRIGGER IF EXISTS trg_detalle_ventas_auditoria ON detalle_ventas;
+DROP TRIGGER IF EXISTS trg_ventas_auditoria ON ventas;
+DROP TRIGGER IF EXISTS trg_detalle_inventarios_auditoria ON detalle_inventarios;
+DROP TRIGGER IF EXISTS trg_inventarios_auditoria ON inventarios;
+DROP TRIGGER IF EXISTS trg_productos_auditoria ON productos;
+DROP TRIGGER IF EXISTS trg_personas_auditoria ON personas;
+
+-- ===== ELIMINAR FUNCIONES =====
+DROP FUNCTION IF EXISTS registrar_auditoria();
+
+-- ===== ELIMINAR TABLAS =====
+DROP TABLE IF EXISTS auditoria;
+
+-- ===== ELIMINAR TIPOS ENUM =====
+DROP TYPE IF EXISTS accion_auditoria;
@@ -0,0 +1,117 @@
+-- ===== AUDITORÍA =====
+-- Registro de cada creación, actualización o desactivación en las tablas de negocio.
+-- Lo escriben triggers, de modo que también quedan registrados los cambios hechos por
+-- otros triggers (p.ej. el descuento de inventario al insertar un detalle de venta).
+--
+-- El actor y el request id los fija la aplicación al inicio de cada transacción con
+-- set_config('polimarket.actor', ..., true) y set_config('polimarket.request_id', ..., true).
+
+CREATE TYPE accion_auditoria AS ENUM ('CREAR', 'ACTUALIZAR', 'DESACTIVAR');
+
+CREATE TABLE auditoria (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    fecha TIMESTAMP NOT NULL DEFAULT NOW(),
+    id_actor UUID,
+    accion accion_auditoria NOT NULL,
+    entidad VARCHAR(50) NOT NULL,
+    id_entidad UUID NOT NULL,
+    datos_anteriores JSONB,
+    datos_nuevos JSONB,
+    cambios JSONB,
+    request_id VARCHAR(128)
+);
+
+CREATE INDEX idx_auditoria_entidad ON auditoria(entidad, id_entidad);
+CREATE INDEX idx_auditoria_actor ON auditoria(id_actor);
+CREATE INDEX idx_auditoria_fecha ON auditoria(fecha);
+
+-- ===== FUNCIÓN: Registrar cambios en auditoria =====
+CREATE OR REPLACE FUNCTION registrar_auditoria()
+RETURNS TRIGGER AS $$
+DECLARE
+    v_anterior JSONB;
+    v_nuevo JSONB;
+    v_cambios JSONB;
+    v_accion accion_auditoria;
+BEGIN
+    v_nuevo := to_jsonb(NEW);
+
+    IF TG_OP = 'INSERT' THEN
+        v_accion := 'CREAR';
+    ELSE
+        v_anterior := to_jsonb(OLD);
+
+        -- Diferencia campo a campo; fecha_actualizacion cambia siempre y no aporta
+        SELECT jsonb_object_agg(n.key, jsonb_build_object('antes', v_anterior -> n.key, 'despues', n.value))
+        INTO v_cambios
+        FROM jsonb_each(v_nuevo) n
+        WHERE n.key <> 'fecha_actualizacion'
+          AND (v_anterior -> n.key) IS DISTINCT FROM n.value;
+
+        IF v_cambios IS NULL THEN
+            RETURN NEW;
+        END IF;
+
+        IF OLD.activo AND NOT NEW.activo THEN
+            v_accion := 'DESACTIVAR';
+        ELSE
+            v_accion := 'ACTUALIZAR';
+        END IF;
+    END IF;
+
+    INSERT INTO auditoria (
+        fecha,
+        id_actor,
+        accion,
+        entidad,
+        id_entidad,
+        datos_anteriores,
+        datos_nuevos,
+        cambios,
+        request_id
+    ) VALUES (
+        clock_timestamp(),
+        NULLIF(current_setting('polimarket.actor', true), '')::UUID,
+        v_accion,
+        TG_TABLE_NAME,
+        NEW.id,
+        v_anterior,
+        v_nuevo,
+        v_cambios,
+        NULLIF(current_setting('polimarket.request_id', true), '')
+    );
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+-- ===== TRIGGERS de auditoría =====
+CREATE TRIGGER trg_personas_auditoria
+    AFTER INSERT OR UPDATE ON personas
+    FOR EACH ROW
+    EXECUTE FUNCTION registrar_auditoria();
+
+CREATE TRIGGER trg_productos_auditoria
+    AFTER INSERT OR UPDATE ON productos
+    FOR EACH ROW
+    EXECUTE FUNCTION registrar_auditoria();
+
+CREATE TRIGGER trg_inventarios_auditoria
+    AFTER INSERT OR UPDATE ON inventarios
+    FOR EACH ROW
+    EXECUTE FUNCTION registrar_auditoria();
+
+CREATE TRIGGER trg_detalle_inventarios_auditoria
+    AFTER INSERT OR UPDATE ON detalle_inventarios
+    FOR EACH ROW
+    EXECUTE FUNCTION registrar_auditoria();
+
+CREATE TRIGGER trg_ventas_auditoria
+    AFTER INSERT OR UPDATE ON ventas
+    FOR EACH ROW
+    EXECUTE FUNCTION registrar_auditoria();
+
+CREATE TRIGGER trg_detalle_ventas_auditoria
+    AFTER INSERT OR UPDATE ON detalle_ventas
+    FOR EACH ROW
+    EXECUTE FUNCTION registrar_auditoria();
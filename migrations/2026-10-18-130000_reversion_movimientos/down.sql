@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_detalle_inventarios_revertido;
+
+ALTER TABLE detalle_inventarios DROP COLUMN IF EXISTS id_movimiento_revertido;
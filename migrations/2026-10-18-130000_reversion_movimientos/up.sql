@@ -0,0 +1,10 @@
+-- ===== REVERSIÓN DE MOVIMIENTOS =====
+-- Un movimiento se revierte registrando otro de efecto contrario que lo referencia.
+-- El índice único impide revertir dos veces el mismo movimiento.
+
+ALTER TABLE detalle_inventarios
+    ADD COLUMN id_movimiento_revertido UUID REFERENCES detalle_inventarios(id);
+
+CREATE UNIQUE INDEX idx_detalle_inventarios_revertido
+    ON detalle_inventarios(id_movimiento_revertido)
+    WHERE id_movimiento_revertido IS NOT NULL;
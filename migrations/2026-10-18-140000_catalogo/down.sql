@@ -0,0 +1,28 @@
+-- ===== ELIMINAR TRIGGERS =====
+DROP TRIGGER IF EXISTS trg_codigos_barra_auditoria ON codigos_barra;
+DROP TRIGGER IF EXISTS trg_marcas_auditoria ON marcas;
+DROP TRIGGER IF EXISTS trg_categorias_auditoria ON categorias;
+DROP TRIGGER IF EXISTS trg_codigos_barra_actualizacion ON codigos_barra;
+DROP TRIGGER IF EXISTS trg_marcas_actualizacion ON marcas;
+DROP TRIGGER IF EXISTS trg_categorias_actualizacion ON categorias;
+DROP TRIGGER IF EXISTS trg_productos_sku ON productos;
+
+-- ===== ELIMINAR FUNCIONES =====
+DROP FUNCTION IF EXISTS asignar_sku_producto();
+
+-- ===== ELIMINAR TABLAS Y COLUMNAS =====
+DROP TABLE IF EXISTS codigos_barra;
+
+DROP INDEX IF EXISTS idx_productos_marca;
+DROP INDEX IF EXISTS idx_productos_categoria;
+DROP INDEX IF EXISTS idx_productos_sku;
+ALTER TABLE productos
+    DROP COLUMN IF EXISTS id_marca,
+    DROP COLUMN IF EXISTS id_categoria,
+    DROP COLUMN IF EXISTS sku;
+
+DROP TABLE IF EXISTS marcas;
+DROP TABLE IF EXISTS categorias;
+
+-- ===== ELIMINAR TIPOS ENUM =====
+DROP TYPE IF EXISTS tipo_codigo_barras;
@@ -0,0 +1,117 @@
+-- ===== CATÁLOGO: categorías, marcas, SKU y códigos de barras =====
+
+-- ===== TIPOS ENUM =====
+CREATE TYPE tipo_codigo_barras AS ENUM ('EAN8', 'UPCA', 'EAN13');
+
+-- ===== TABLA: categorias =====
+-- Jerárquica: id_padre apunta a la categoría que la contiene (NULL en las raíces)
+CREATE TABLE categorias (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    nombre VARCHAR(100) NOT NULL,
+    descripcion TEXT,
+    id_padre UUID REFERENCES categorias(id),
+    fecha_creacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    fecha_actualizacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    activo BOOLEAN NOT NULL DEFAULT TRUE,
+
+    CONSTRAINT chk_nombre_categoria CHECK (LENGTH(TRIM(nombre)) >= 2),
+    CONSTRAINT chk_categoria_padre CHECK (id_padre IS NULL OR id_padre <> id)
+);
+
+-- El nombre no se repite entre las categorías activas de un mismo padre
+CREATE UNIQUE INDEX idx_categorias_nombre
+    ON categorias (COALESCE(id_padre, '00000000-0000-0000-0000-000000000000'::UUID), LOWER(nombre))
+    WHERE activo = TRUE;
+CREATE INDEX idx_categorias_padre ON categorias(id_padre);
+
+-- ===== TABLA: marcas =====
+CREATE TABLE marcas (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    nombre VARCHAR(100) NOT NULL,
+    fecha_creacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    fecha_actualizacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    activo BOOLEAN NOT NULL DEFAULT TRUE,
+
+    CONSTRAINT chk_nombre_marca CHECK (LENGTH(TRIM(nombre)) >= 2)
+);
+
+CREATE UNIQUE INDEX idx_marcas_nombre ON marcas (LOWER(nombre)) WHERE activo = TRUE;
+
+-- ===== PRODUCTOS: SKU, categoría y marca =====
+ALTER TABLE productos
+    ADD COLUMN sku VARCHAR(64),
+    ADD COLUMN id_categoria UUID REFERENCES categorias(id),
+    ADD COLUMN id_marca UUID REFERENCES marcas(id);
+
+-- SKU por defecto a partir del id, para los productos existentes y los que se creen sin SKU
+CREATE OR REPLACE FUNCTION asignar_sku_producto()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.sku IS NULL OR TRIM(NEW.sku) = '' THEN
+        NEW.sku := 'PM-' || UPPER(SUBSTRING(REPLACE(NEW.id::TEXT, '-', '') FROM 1 FOR 12));
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trg_productos_sku
+    BEFORE INSERT ON productos
+    FOR EACH ROW
+    EXECUTE FUNCTION asignar_sku_producto();
+
+UPDATE productos SET sku = 'PM-' || UPPER(SUBSTRING(REPLACE(id::TEXT, '-', '') FROM 1 FOR 12));
+
+ALTER TABLE productos ALTER COLUMN sku SET NOT NULL;
+
+CREATE UNIQUE INDEX idx_productos_sku ON productos (UPPER(sku));
+CREATE INDEX idx_productos_categoria ON productos(id_categoria);
+CREATE INDEX idx_productos_marca ON productos(id_marca);
+
+-- ===== TABLA: codigos_barra =====
+-- codigo guarda el GTIN-14 (el código leído completado con ceros a la izquierda), de modo que
+-- un UPC-A y su EAN-13 equivalente (con 0 delante) son el mismo código
+CREATE TABLE codigos_barra (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    id_producto UUID NOT NULL REFERENCES productos(id),
+    codigo VARCHAR(14) NOT NULL,
+    tipo tipo_codigo_barras NOT NULL,
+    fecha_creacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    fecha_actualizacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    activo BOOLEAN NOT NULL DEFAULT TRUE,
+
+    CONSTRAINT chk_codigo_barras CHECK (codigo ~ '^[0-9]{14}$')
+);
+
+CREATE UNIQUE INDEX idx_codigos_barra_codigo ON codigos_barra(codigo) WHERE activo = TRUE;
+CREATE INDEX idx_codigos_barra_producto ON codigos_barra(id_producto);
+
+-- ===== TRIGGERS =====
+CREATE TRIGGER trg_categorias_actualizacion
+    BEFORE UPDATE ON categorias
+    FOR EACH ROW
+    EXECUTE FUNCTION actualizar_fecha_modificacion();
+
+CREATE TRIGGER trg_marcas_actualizacion
+    BEFORE UPDATE ON marcas
+    FOR EACH ROW
+    EXECUTE FUNCTION actualizar_fecha_modificacion();
+
+CREATE TRIGGER trg_codigos_barra_actualizacion
+    BEFORE UPDATE ON codigos_barra
+    FOR EACH ROW
+    EXECUTE FUNCTION actualizar_fecha_modificacion();
+
+CREATE TRIGGER trg_categorias_auditoria
+    AFTER INSERT OR UPDATE ON categorias
+    FOR EACH ROW
+    EXECUTE FUNCTION registrar_auditoria();
+
+CREATE TRIGGER trg_marcas_auditoria
+    AFTER INSERT OR UPDATE ON marcas
+    FOR EACH ROW
+    EXECUTE FUNCTION registrar_auditoria();
+
+CREATE TRIGGER trg_codigos_barra_auditoria
+    AFTER INSERT OR UPDATE ON codigos_barra
+    FOR EACH ROW
+    EXECUTE FUNCTION registrar_auditoria();
@@ -0,0 +1,12 @@
+-- ===== ELIMINAR ÍNDICES =====
+DROP INDEX IF EXISTS idx_personas_documento_prefijo;
+DROP INDEX IF EXISTS idx_personas_nombre_trgm;
+DROP INDEX IF EXISTS idx_personas_nombre_fts;
+DROP INDEX IF EXISTS idx_productos_nombre_trgm;
+DROP INDEX IF EXISTS idx_productos_nombre_fts;
+
+-- ===== ELIMINAR CONFIGURACIÓN Y FUNCIONES =====
+DROP TEXT SEARCH CONFIGURATION IF EXISTS espanol_sin_acentos;
+DROP FUNCTION IF EXISTS sin_acentos(TEXT);
+
+-- Las extensiones se conservan por si otros objetos de la base de datos dependen de ellas
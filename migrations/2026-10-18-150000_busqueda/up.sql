@@ -0,0 +1,34 @@
+-- ============================================
+-- BÚSQUEDA DE PRODUCTOS Y PERSONAS
+-- Texto completo en español sin distinguir acentos (unaccent) y similitud de
+-- trigramas (pg_trgm) para tolerar errores de escritura
+-- ============================================
+
+CREATE EXTENSION IF NOT EXISTS unaccent;
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+-- unaccent() es STABLE porque depende del search_path; fijando el diccionario se puede
+-- declarar IMMUTABLE y usar en índices
+CREATE OR REPLACE FUNCTION sin_acentos(texto TEXT)
+RETURNS TEXT AS $$
+    SELECT public.unaccent('public.unaccent'::regdictionary, texto)
+$$ LANGUAGE sql IMMUTABLE PARALLEL SAFE STRICT;
+
+-- Configuración 'spanish' que además quita los acentos antes de extraer la raíz
+CREATE TEXT SEARCH CONFIGURATION espanol_sin_acentos (COPY = pg_catalog.spanish);
+ALTER TEXT SEARCH CONFIGURATION espanol_sin_acentos
+    ALTER MAPPING FOR hword, hword_part, word WITH unaccent, spanish_stem;
+
+-- Las consultas deben usar exactamente estas expresiones para aprovechar los índices
+CREATE INDEX idx_productos_nombre_fts ON productos
+    USING GIN (to_tsvector('espanol_sin_acentos', nombre));
+CREATE INDEX idx_productos_nombre_trgm ON productos
+    USING GIN (sin_acentos(LOWER(nombre)) gin_trgm_ops);
+
+CREATE INDEX idx_personas_nombre_fts ON personas
+    USING GIN (to_tsvector('espanol_sin_acentos', nombre));
+CREATE INDEX idx_personas_nombre_trgm ON personas
+    USING GIN (sin_acentos(LOWER(nombre)) gin_trgm_ops);
+
+-- Búsqueda por prefijo de documento (LIKE '123%') con cualquier collation
+CREATE INDEX idx_personas_documento_prefijo ON personas (documento varchar_pattern_ops);
@@ -0,0 +1,22 @@
+-- ===== ELIMINAR TRIGGERS =====
+DROP TRIGGER IF EXISTS trg_unidades_producto_auditoria ON unidades_producto;
+DROP TRIGGER IF EXISTS trg_unidades_producto_actualizacion ON unidades_producto;
+DROP TRIGGER IF EXISTS trg_detalle_ventas_unidad ON detalle_ventas;
+
+-- ===== ELIMINAR FUNCIONES =====
+DROP FUNCTION IF EXISTS validar_unidad_detalle_venta();
+
+-- ===== ELIMINAR TABLAS Y COLUMNAS =====
+ALTER TABLE detalle_ventas
+    DROP COLUMN IF EXISTS cantidad_unidad,
+    DROP COLUMN IF EXISTS id_unidad;
+
+DROP TABLE IF EXISTS unidades_producto;
+
+DROP INDEX IF EXISTS idx_productos_variante;
+DROP INDEX IF EXISTS idx_productos_padre;
+ALTER TABLE productos
+    DROP CONSTRAINT IF EXISTS chk_atributos_producto,
+    DROP CONSTRAINT IF EXISTS chk_producto_padre,
+    DROP COLUMN IF EXISTS atributos,
+    DROP COLUMN IF EXISTS id_producto_padre;
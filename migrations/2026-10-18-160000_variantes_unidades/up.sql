@@ -0,0 +1,102 @@
+-- ===== VARIANTES Y UNIDADES DE MEDIDA =====
+
+-- ===== PRODUCTOS: variantes =====
+-- Una variante (talla, color...) es un producto con su propio SKU, precio e inventario que
+-- cuelga de un producto padre. atributos describe la variante: {"talla": "M", "color": "Rojo"}
+ALTER TABLE productos
+    ADD COLUMN id_producto_padre UUID REFERENCES productos(id),
+    ADD COLUMN atributos JSONB NOT NULL DEFAULT '{}'::JSONB,
+    ADD CONSTRAINT chk_producto_padre CHECK (id_producto_padre IS NULL OR id_producto_padre <> id),
+    ADD CONSTRAINT chk_atributos_producto CHECK (jsonb_typeof(atributos) = 'object');
+
+CREATE INDEX idx_productos_padre ON productos(id_producto_padre);
+
+-- Un mismo padre no puede tener dos variantes activas con los mismos atributos
+CREATE UNIQUE INDEX idx_productos_variante
+    ON productos (id_producto_padre, atributos)
+    WHERE activo = TRUE AND id_producto_padre IS NOT NULL;
+
+-- ===== TABLA: unidades_producto =====
+-- Unidades alternativas de venta. La unidad base es productos.unidad_venta: el inventario
+-- siempre se lleva en ella y factor indica cuántas unidades base contiene esta unidad
+-- ("Caja x12" -> 12). Sin precio_unitario, el precio es el del producto multiplicado por factor.
+CREATE TABLE unidades_producto (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    id_producto UUID NOT NULL REFERENCES productos(id),
+    nombre VARCHAR(50) NOT NULL,
+    factor INT NOT NULL,
+    precio_unitario NUMERIC(12, 2),
+    fecha_creacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    fecha_actualizacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    activo BOOLEAN NOT NULL DEFAULT TRUE,
+
+    CONSTRAINT chk_nombre_unidad CHECK (LENGTH(TRIM(nombre)) >= 1),
+    CONSTRAINT chk_factor_unidad CHECK (factor > 0),
+    CONSTRAINT chk_precio_unidad CHECK (precio_unitario IS NULL OR precio_unitario > 0)
+);
+
+CREATE UNIQUE INDEX idx_unidades_producto_nombre
+    ON unidades_producto (id_producto, LOWER(nombre))
+    WHERE activo = TRUE;
+
+-- ===== DETALLE_VENTAS: unidad vendida =====
+-- cantidad sigue en la unidad base (es la que validan y descuentan los triggers de inventario);
+-- cantidad_unidad es la cantidad en la unidad vendida, id_unidad NULL si fue la base
+ALTER TABLE detalle_ventas
+    ADD COLUMN id_unidad UUID REFERENCES unidades_producto(id),
+    ADD COLUMN cantidad_unidad INT;
+
+UPDATE detalle_ventas SET cantidad_unidad = cantidad;
+
+ALTER TABLE detalle_ventas
+    ALTER COLUMN cantidad_unidad SET NOT NULL,
+    ADD CONSTRAINT chk_detalle_cantidad_unidad CHECK (cantidad_unidad > 0);
+
+-- Sin unidad, cantidad_unidad es la misma cantidad base. Con unidad, comprueba que sea del
+-- producto y que cantidad sea la conversión a la unidad base.
+CREATE OR REPLACE FUNCTION validar_unidad_detalle_venta()
+RETURNS TRIGGER AS $$
+DECLARE
+    factor_unidad INT;
+BEGIN
+    IF NEW.id_unidad IS NULL THEN
+        NEW.cantidad_unidad := COALESCE(NEW.cantidad_unidad, NEW.cantidad);
+        IF NEW.cantidad_unidad <> NEW.cantidad THEN
+            RAISE EXCEPTION 'Sin unidad, cantidad_unidad debe ser igual a cantidad';
+        END IF;
+        RETURN NEW;
+    END IF;
+
+    SELECT factor INTO factor_unidad
+    FROM unidades_producto
+    WHERE id = NEW.id_unidad AND id_producto = NEW.id_producto;
+
+    IF factor_unidad IS NULL THEN
+        RAISE EXCEPTION 'La unidad % no pertenece al producto %', NEW.id_unidad, NEW.id_producto;
+    END IF;
+
+    IF NEW.cantidad_unidad IS NULL OR NEW.cantidad <> NEW.cantidad_unidad * factor_unidad THEN
+        RAISE EXCEPTION 'La cantidad % no corresponde a % unidades de factor %',
+            NEW.cantidad, NEW.cantidad_unidad, factor_unidad;
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Los triggers BEFORE se ejecutan por orden alfabético: este va antes de trg_validar_stock_detalle_venta
+CREATE TRIGGER trg_detalle_ventas_unidad
+    BEFORE INSERT ON detalle_ventas
+    FOR EACH ROW
+    EXECUTE FUNCTION validar_unidad_detalle_venta();
+
+-- ===== TRIGGERS =====
+CREATE TRIGGER trg_unidades_producto_actualizacion
+    BEFORE UPDATE ON unidades_producto
+    FOR EACH ROW
+    EXECUTE FUNCTION actualizar_fecha_modificacion();
+
+CREATE TRIGGER trg_unidades_producto_auditoria
+    AFTER INSERT OR UPDATE ON unidades_producto
+    FOR EACH ROW
+    EXECUTE FUNCTION registrar_auditoria();
@@ -0,0 +1,13 @@
+-- ===== ELIMINAR TRIGGERS =====
+DROP TRIGGER IF EXISTS trg_detalle_ventas_lotes_auditoria ON detalle_ventas_lotes;
+DROP TRIGGER IF EXISTS trg_lotes_auditoria ON lotes;
+DROP TRIGGER IF EXISTS trg_detalle_ventas_lotes_actualizacion ON detalle_ventas_lotes;
+DROP TRIGGER IF EXISTS trg_lotes_actualizacion ON lotes;
+
+-- ===== ELIMINAR TABLAS Y COLUMNAS =====
+DROP TABLE IF EXISTS detalle_ventas_lotes;
+
+DROP INDEX IF EXISTS idx_detalle_inventarios_lote;
+ALTER TABLE detalle_inventarios DROP COLUMN IF EXISTS id_lote;
+
+DROP TABLE IF EXISTS lotes;
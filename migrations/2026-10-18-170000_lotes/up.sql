@@ -0,0 +1,71 @@
+-- ===== LOTES Y VENCIMIENTOS =====
+
+-- ===== TABLA: lotes =====
+-- Existencias de un producto que comparten fecha de vencimiento. La suma de los lotes de un
+-- producto nunca supera inventarios.cantidad_disponible: la diferencia es stock sin lote
+-- (el anterior a esta migración o el que entra sin indicar lote).
+CREATE TABLE lotes (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    id_producto UUID NOT NULL REFERENCES productos(id),
+    codigo VARCHAR(50) NOT NULL,
+    fecha_vencimiento DATE NOT NULL,
+    cantidad_disponible INT NOT NULL DEFAULT 0,
+    fecha_creacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    fecha_actualizacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    activo BOOLEAN NOT NULL DEFAULT TRUE,
+
+    CONSTRAINT chk_codigo_lote CHECK (LENGTH(TRIM(codigo)) >= 1),
+    CONSTRAINT chk_cantidad_lote CHECK (cantidad_disponible >= 0)
+);
+
+-- Los códigos se guardan en mayúsculas y son únicos por producto
+CREATE UNIQUE INDEX idx_lotes_producto_codigo ON lotes (id_producto, codigo) WHERE activo = TRUE;
+
+-- Asignación FEFO y consulta de próximos vencimientos
+CREATE INDEX idx_lotes_vencimiento
+    ON lotes (fecha_vencimiento, id_producto)
+    WHERE activo = TRUE AND cantidad_disponible > 0;
+
+-- ===== DETALLE_INVENTARIOS: lote afectado =====
+ALTER TABLE detalle_inventarios ADD COLUMN id_lote UUID REFERENCES lotes(id);
+
+CREATE INDEX idx_detalle_inventarios_lote ON detalle_inventarios(id_lote);
+
+-- ===== TABLA: detalle_ventas_lotes =====
+-- Lotes de los que salió cada línea de venta; la cantidad que no aparece aquí salió del stock
+-- sin lote
+CREATE TABLE detalle_ventas_lotes (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    id_detalle_venta UUID NOT NULL REFERENCES detalle_ventas(id),
+    id_lote UUID NOT NULL REFERENCES lotes(id),
+    cantidad INT NOT NULL,
+    fecha_creacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    fecha_actualizacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    activo BOOLEAN NOT NULL DEFAULT TRUE,
+
+    CONSTRAINT chk_cantidad_detalle_lote CHECK (cantidad > 0)
+);
+
+CREATE INDEX idx_detalle_ventas_lotes_detalle ON detalle_ventas_lotes(id_detalle_venta);
+CREATE INDEX idx_detalle_ventas_lotes_lote ON detalle_ventas_lotes(id_lote);
+
+-- ===== TRIGGERS =====
+CREATE TRIGGER trg_lotes_actualizacion
+    BEFORE UPDATE ON lotes
+    FOR EACH ROW
+    EXECUTE FUNCTION actualizar_fecha_modificacion();
+
+CREATE TRIGGER trg_detalle_ventas_lotes_actualizacion
+    BEFORE UPDATE ON detalle_ventas_lotes
+    FOR EACH ROW
+    EXECUTE FUNCTION actualizar_fecha_modificacion();
+
+CREATE TRIGGER trg_lotes_auditoria
+    AFTER INSERT OR UPDATE ON lotes
+    FOR EACH ROW
+    EXECUTE FUNCTION registrar_auditoria();
+
+CREATE TRIGGER trg_detalle_ventas_lotes_auditoria
+    AFTER INSERT OR UPDATE ON detalle_ventas_lotes
+    FOR EACH ROW
+    EXECUTE FUNCTION registrar_auditoria();
@@ -0,0 +1,13 @@
+-- ===== ELIMINAR TRIGGERS =====
+DROP TRIGGER IF EXISTS trg_detalle_ventas_series_auditoria ON detalle_ventas_series;
+DROP TRIGGER IF EXISTS trg_numeros_serie_auditoria ON numeros_serie;
+DROP TRIGGER IF EXISTS trg_detalle_ventas_series_actualizacion ON detalle_ventas_series;
+DROP TRIGGER IF EXISTS trg_numeros_serie_actualizacion ON numeros_serie;
+
+-- ===== ELIMINAR TABLAS Y COLUMNAS =====
+DROP TABLE IF EXISTS detalle_ventas_series;
+DROP TABLE IF EXISTS numeros_serie;
+
+ALTER TABLE productos DROP COLUMN IF EXISTS serializado;
+
+DROP TYPE IF EXISTS estado_serie;
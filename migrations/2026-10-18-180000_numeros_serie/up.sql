@@ -0,0 +1,65 @@
+-- ===== NÚMEROS DE SERIE =====
+
+CREATE TYPE estado_serie AS ENUM ('EN_STOCK', 'VENDIDO', 'DEVUELTO');
+
+-- ===== PRODUCTOS: seguimiento por número de serie =====
+-- En un producto serializado cada unidad en stock tiene su número de serie: el stock es el
+-- número de series activas EN_STOCK o DEVUELTO
+ALTER TABLE productos ADD COLUMN serializado BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- ===== TABLA: numeros_serie =====
+-- Ciclo de vida: EN_STOCK al entrar, VENDIDO al venderse y DEVUELTO si vuelve a entrar después
+-- de vendido (se puede vender de nuevo). Una SALIDA manual lo da de baja (activo = FALSE).
+CREATE TABLE numeros_serie (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    id_producto UUID NOT NULL REFERENCES productos(id),
+    numero VARCHAR(100) NOT NULL,
+    estado estado_serie NOT NULL DEFAULT 'EN_STOCK',
+    -- Último movimiento de inventario que lo dio de alta, lo devolvió o lo dio de baja
+    id_movimiento UUID REFERENCES detalle_inventarios(id),
+    fecha_creacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    fecha_actualizacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    activo BOOLEAN NOT NULL DEFAULT TRUE,
+
+    CONSTRAINT chk_numero_serie CHECK (LENGTH(TRIM(numero)) >= 1)
+);
+
+-- Los números se guardan en mayúsculas; un número activo no se repite en el mismo producto
+CREATE UNIQUE INDEX idx_numeros_serie_producto ON numeros_serie (id_producto, numero) WHERE activo = TRUE;
+CREATE INDEX idx_numeros_serie_numero ON numeros_serie (numero);
+
+-- ===== TABLA: detalle_ventas_series =====
+-- Números de serie vendidos en cada línea de venta; conserva el historial si una unidad
+-- devuelta se vuelve a vender
+CREATE TABLE detalle_ventas_series (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    id_detalle_venta UUID NOT NULL REFERENCES detalle_ventas(id),
+    id_serie UUID NOT NULL REFERENCES numeros_serie(id),
+    fecha_creacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    fecha_actualizacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    activo BOOLEAN NOT NULL DEFAULT TRUE
+);
+
+CREATE INDEX idx_detalle_ventas_series_detalle ON detalle_ventas_series(id_detalle_venta);
+CREATE INDEX idx_detalle_ventas_series_serie ON detalle_ventas_series(id_serie);
+
+-- ===== TRIGGERS =====
+CREATE TRIGGER trg_numeros_serie_actualizacion
+    BEFORE UPDATE ON numeros_serie
+    FOR EACH ROW
+    EXECUTE FUNCTION actualizar_fecha_modificacion();
+
+CREATE TRIGGER trg_detalle_ventas_series_actualizacion
+    BEFORE UPDATE ON detalle_ventas_series
+    FOR EACH ROW
+    EXECUTE FUNCTION actualizar_fecha_modificacion();
+
+CREATE TRIGGER trg_numeros_serie_auditoria
+    AFTER INSERT OR UPDATE ON numeros_serie
+    FOR EACH ROW
+    EXECUTE FUNCTION registrar_auditoria();
+
+CREATE TRIGGER trg_detalle_ventas_series_auditoria
+    AFTER INSERT OR UPDATE ON detalle_ventas_series
+    FOR EACH ROW
+    EXECUTE FUNCTION registrar_auditoria();
@@ -0,0 +1,44 @@
+-- ===== ELIMINAR TRIGGERS =====
+DROP TRIGGER IF EXISTS trg_capas_costo_auditoria ON capas_costo;
+DROP TRIGGER IF EXISTS trg_capas_costo_actualizacion ON capas_costo;
+
+-- ===== RESTAURAR LA SALIDA DE LAS VENTAS SIN COSTO =====
+CREATE OR REPLACE FUNCTION actualizar_inventario_venta()
+RETURNS TRIGGER AS $$
+BEGIN
+    -- Reducir stock
+    UPDATE inventarios
+    SET cantidad_disponible = cantidad_disponible - NEW.cantidad
+    WHERE id_producto = NEW.id_producto;
+
+    -- Registrar movimiento
+    INSERT INTO detalle_inventarios (
+        id_producto,
+        tipo_movimiento,
+        fecha,
+        id_persona,
+        cantidad,
+        observaciones
+    )
+    SELECT
+        NEW.id_producto,
+        'SALIDA'::tipo_movimiento,
+        NOW(),
+        v.id_persona,
+        -NEW.cantidad,
+        'Venta ID: ' || NEW.id_venta
+    FROM ventas v
+    WHERE v.id = NEW.id_venta;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+-- ===== ELIMINAR TABLAS Y COLUMNAS =====
+DROP TABLE IF EXISTS capas_costo;
+
+ALTER TABLE detalle_ventas DROP CONSTRAINT IF EXISTS chk_detalle_costo;
+ALTER TABLE detalle_ventas DROP COLUMN IF EXISTS costo;
+
+ALTER TABLE detalle_inventarios DROP CONSTRAINT IF EXISTS chk_movimiento_costo;
+ALTER TABLE detalle_inventarios DROP COLUMN IF EXISTS costo_unitario;
@@ -0,0 +1,84 @@
+-- ===== COSTO DE LOS MOVIMIENTOS =====
+-- Costo unitario con el que cada movimiento entró o salió del inventario: el de compra en las
+-- ENTRADAS y el de las capas consumidas en las salidas. La valorización a una fecha es la suma
+-- de los movimientos hasta ella por su costo. NULL en los movimientos anteriores a los costos.
+ALTER TABLE detalle_inventarios ADD COLUMN costo_unitario NUMERIC(14, 4);
+
+ALTER TABLE detalle_inventarios ADD CONSTRAINT chk_movimiento_costo
+    CHECK (costo_unitario IS NULL OR costo_unitario >= 0);
+
+-- ===== VENTAS: costo de la mercancía vendida =====
+-- Se fija al vender, con el costo de las capas que consumió la línea
+ALTER TABLE detalle_ventas ADD COLUMN costo NUMERIC(14, 2) NOT NULL DEFAULT 0;
+
+ALTER TABLE detalle_ventas ADD CONSTRAINT chk_detalle_costo CHECK (costo >= 0);
+
+-- ===== TABLA: capas_costo =====
+-- Unidades en stock agrupadas por costo, de la más antigua a la más reciente. Cada ENTRADA
+-- crea una capa y las salidas las consumen en orden FIFO. Con el método de promedio ponderado,
+-- cada ENTRADA lleva todas las capas al nuevo costo promedio. El stock que no cubre ninguna
+-- capa (anterior a los costos) vale 0 y es lo primero que sale.
+CREATE TABLE capas_costo (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    id_producto UUID NOT NULL REFERENCES productos(id),
+    -- Movimiento que creó la capa
+    id_movimiento UUID REFERENCES detalle_inventarios(id),
+    costo_unitario NUMERIC(14, 4) NOT NULL,
+    cantidad_inicial INTEGER NOT NULL,
+    cantidad_disponible INTEGER NOT NULL,
+    fecha_creacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    fecha_actualizacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    activo BOOLEAN NOT NULL DEFAULT TRUE,
+
+    CONSTRAINT chk_capa_costo CHECK (costo_unitario >= 0),
+    CONSTRAINT chk_capa_cantidad_inicial CHECK (cantidad_inicial > 0),
+    CONSTRAINT chk_capa_cantidad_disponible CHECK (cantidad_disponible >= 0)
+);
+
+CREATE INDEX idx_capas_costo_producto ON capas_costo (id_producto, fecha_creacion)
+    WHERE activo = TRUE AND cantidad_disponible > 0;
+
+-- ===== FUNCIÓN: la SALIDA de una venta lleva el costo de la línea =====
+CREATE OR REPLACE FUNCTION actualizar_inventario_venta()
+RETURNS TRIGGER AS $$
+BEGIN
+    -- Reducir stock
+    UPDATE inventarios
+    SET cantidad_disponible = cantidad_disponible - NEW.cantidad
+    WHERE id_producto = NEW.id_producto;
+
+    -- Registrar movimiento
+    INSERT INTO detalle_inventarios (
+        id_producto,
+        tipo_movimiento,
+        fecha,
+        id_persona,
+        cantidad,
+        observaciones,
+        costo_unitario
+    )
+    SELECT
+        NEW.id_producto,
+        'SALIDA'::tipo_movimiento,
+        NOW(),
+        v.id_persona,
+        -NEW.cantidad,
+        'Venta ID: ' || NEW.id_venta,
+        ROUND(NEW.costo / NULLIF(NEW.cantidad, 0), 4)
+    FROM ventas v
+    WHERE v.id = NEW.id_venta;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+-- ===== TRIGGERS =====
+CREATE TRIGGER trg_capas_costo_actualizacion
+    BEFORE UPDATE ON capas_costo
+    FOR EACH ROW
+    EXECUTE FUNCTION actualizar_fecha_modificacion();
+
+CREATE TRIGGER trg_capas_costo_auditoria
+    AFTER INSERT OR UPDATE ON capas_costo
+    FOR EACH ROW
+    EXECUTE FUNCTION registrar_auditoria();
@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_ventas_vendedor;
+
+ALTER TABLE ventas DROP COLUMN IF EXISTS id_vendedor;
@@ -0,0 +1,6 @@
+-- ===== VENDEDOR DE CADA VENTA =====
+
+-- Vendedor que atendió la venta; NULL en las ventas anteriores a esta migración
+ALTER TABLE ventas ADD COLUMN id_vendedor UUID REFERENCES personas(id);
+
+CREATE INDEX idx_ventas_vendedor ON ventas(id_vendedor);
@@ -0,0 +1,44 @@
+-- ===== ELIMINAR TRIGGERS =====
+DROP TRIGGER IF EXISTS trg_reglas_comision_auditoria ON reglas_comision;
+DROP TRIGGER IF EXISTS trg_reglas_comision_actualizacion ON reglas_comision;
+
+-- ===== RESTAURAR LA SALIDA DE LAS VENTAS A CARGO DEL CLIENTE =====
+CREATE OR REPLACE FUNCTION actualizar_inventario_venta()
+RETURNS TRIGGER AS $$
+BEGIN
+    -- Reducir stock
+    UPDATE inventarios
+    SET cantidad_disponible = cantidad_disponible - NEW.cantidad
+    WHERE id_producto = NEW.id_producto;
+
+    -- Registrar movimiento
+    INSERT INTO detalle_inventarios (
+        id_producto,
+        tipo_movimiento,
+        fecha,
+        id_persona,
+        cantidad,
+        observaciones,
+        costo_unitario
+    )
+    SELECT
+        NEW.id_producto,
+        'SALIDA'::tipo_movimiento,
+        NOW(),
+        v.id_persona,
+        -NEW.cantidad,
+        'Venta ID: ' || NEW.id_venta,
+        ROUND(NEW.costo / NULLIF(NEW.cantidad, 0), 4)
+    FROM ventas v
+    WHERE v.id = NEW.id_venta;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+-- ===== ELIMINAR TABLAS Y COLUMNAS =====
+ALTER TABLE detalle_ventas DROP CONSTRAINT IF EXISTS chk_detalle_comision;
+ALTER TABLE detalle_ventas DROP COLUMN IF EXISTS id_regla_comision;
+ALTER TABLE detalle_ventas DROP COLUMN IF EXISTS comision;
+
+DROP TABLE IF EXISTS reglas_comision;
@@ -0,0 +1,81 @@
+-- ===== COMISIONES DE LOS VENDEDORES =====
+
+-- ===== TABLA: reglas_comision =====
+-- Porcentaje de comisión sobre el subtotal de las líneas vendidas. Una regla puede limitarse
+-- a un vendedor, a una categoría (incluidas sus subcategorías) o a ambos; sin ninguno de los
+-- dos es la regla general. Para cada línea se aplica la más específica.
+CREATE TABLE reglas_comision (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    id_vendedor UUID REFERENCES personas(id),
+    id_categoria UUID REFERENCES categorias(id),
+    porcentaje NUMERIC(5, 2) NOT NULL,
+    fecha_creacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    fecha_actualizacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    activo BOOLEAN NOT NULL DEFAULT TRUE,
+
+    CONSTRAINT chk_regla_comision_porcentaje CHECK (porcentaje >= 0 AND porcentaje <= 100)
+);
+
+-- Una sola regla activa por combinación de vendedor y categoría
+CREATE UNIQUE INDEX idx_reglas_comision_alcance ON reglas_comision (
+    COALESCE(id_vendedor, '00000000-0000-0000-0000-000000000000'::UUID),
+    COALESCE(id_categoria, '00000000-0000-0000-0000-000000000000'::UUID)
+) WHERE activo = TRUE;
+
+-- ===== DETALLE_VENTAS: comisión de la línea =====
+-- Se fija al vender con la regla vigente, así que cambiar las reglas no altera lo ya liquidado
+ALTER TABLE detalle_ventas ADD COLUMN comision NUMERIC(14, 2) NOT NULL DEFAULT 0;
+ALTER TABLE detalle_ventas ADD COLUMN id_regla_comision UUID REFERENCES reglas_comision(id);
+
+ALTER TABLE detalle_ventas ADD CONSTRAINT chk_detalle_comision CHECK (comision >= 0);
+
+-- ===== FUNCIÓN: la SALIDA de una venta queda a cargo del vendedor =====
+-- Antes se registraba al cliente (ventas.id_persona). Las ventas sin vendedor quedan a cargo
+-- del responsable del inventario del producto.
+CREATE OR REPLACE FUNCTION actualizar_inventario_venta()
+RETURNS TRIGGER AS $$
+BEGIN
+    -- Reducir stock
+    UPDATE inventarios
+    SET cantidad_disponible = cantidad_disponible - NEW.cantidad
+    WHERE id_producto = NEW.id_producto;
+
+    -- Registrar movimiento
+    INSERT INTO detalle_inventarios (
+        id_producto,
+        tipo_movimiento,
+        fecha,
+        id_persona,
+        cantidad,
+        observaciones,
+        costo_unitario
+    )
+    SELECT
+        NEW.id_producto,
+        'SALIDA'::tipo_movimiento,
+        NOW(),
+        COALESCE(
+            v.id_vendedor,
+            (SELECT i.id_persona FROM inventarios i WHERE i.id_producto = NEW.id_producto ORDER BY i.activo DESC LIMIT 1),
+            v.id_persona
+        ),
+        -NEW.cantidad,
+        'Venta ID: ' || NEW.id_venta,
+        ROUND(NEW.costo / NULLIF(NEW.cantidad, 0), 4)
+    FROM ventas v
+    WHERE v.id = NEW.id_venta;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+-- ===== TRIGGERS =====
+CREATE TRIGGER trg_reglas_comision_actualizacion
+    BEFORE UPDATE ON reglas_comision
+    FOR EACH ROW
+    EXECUTE FUNCTION actualizar_fecha_modificacion();
+
+CREATE TRIGGER trg_reglas_comision_auditoria
+    AFTER INSERT OR UPDATE ON reglas_comision
+    FOR EACH ROW
+    EXECUTE FUNCTION registrar_auditoria();
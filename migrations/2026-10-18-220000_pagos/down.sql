@@ -0,0 +1,12 @@
+-- ===== ELIMINAR TRIGGERS =====
+DROP TRIGGER IF EXISTS trg_pagos_auditoria ON pagos;
+DROP TRIGGER IF EXISTS trg_pagos_actualizacion ON pagos;
+
+-- ===== ELIMINAR TABLAS Y COLUMNAS =====
+DROP TABLE IF EXISTS pagos;
+
+DROP INDEX IF EXISTS idx_ventas_estado;
+ALTER TABLE ventas DROP COLUMN IF EXISTS estado;
+
+DROP TYPE IF EXISTS estado_venta;
+DROP TYPE IF EXISTS metodo_pago;
@@ -0,0 +1,48 @@
+-- ===== PAGOS DE LAS VENTAS =====
+
+CREATE TYPE metodo_pago AS ENUM ('EFECTIVO', 'TARJETA', 'TRANSFERENCIA');
+
+CREATE TYPE estado_venta AS ENUM ('PENDIENTE_PAGO', 'PAGADA', 'ANULADA');
+
+-- ===== VENTAS: estado del cobro =====
+-- Las ventas anteriores a esta migración se cobraron en caja sin registrar el pago, así que
+-- quedan como PAGADA; las nuevas empiezan PENDIENTE_PAGO hasta que sus pagos cubren el total
+ALTER TABLE ventas ADD COLUMN estado estado_venta NOT NULL DEFAULT 'PAGADA';
+ALTER TABLE ventas ALTER COLUMN estado SET DEFAULT 'PENDIENTE_PAGO';
+
+CREATE INDEX idx_ventas_estado ON ventas(estado);
+
+-- ===== TABLA: pagos =====
+-- Una venta se puede pagar combinando métodos. monto es lo que el pago abona a la venta y
+-- cambio lo que se devolvió al cliente: solo el efectivo puede superar lo pendiente.
+-- Al anular la venta sus pagos se desactivan (se reembolsan).
+CREATE TABLE pagos (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    id_venta UUID NOT NULL REFERENCES ventas(id),
+    metodo metodo_pago NOT NULL,
+    monto NUMERIC(14, 2) NOT NULL,
+    cambio NUMERIC(14, 2) NOT NULL DEFAULT 0,
+    -- Autorización de la tarjeta, número de la transferencia...
+    referencia VARCHAR(100),
+    fecha TIMESTAMP NOT NULL DEFAULT NOW(),
+    fecha_creacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    fecha_actualizacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    activo BOOLEAN NOT NULL DEFAULT TRUE,
+
+    CONSTRAINT chk_pago_monto CHECK (monto > 0),
+    CONSTRAINT chk_pago_cambio CHECK (cambio >= 0 AND (metodo = 'EFECTIVO' OR cambio = 0))
+);
+
+CREATE INDEX idx_pagos_venta ON pagos(id_venta);
+CREATE INDEX idx_pagos_metodo ON pagos(metodo);
+
+-- ===== TRIGGERS =====
+CREATE TRIGGER trg_pagos_actualizacion
+    BEFORE UPDATE ON pagos
+    FOR EACH ROW
+    EXECUTE FUNCTION actualizar_fecha_modificacion();
+
+CREATE TRIGGER trg_pagos_auditoria
+    AFTER INSERT OR UPDATE ON pagos
+    FOR EACH ROW
+    EXECUTE FUNCTION registrar_auditoria();
@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS idx_ventas_cliente_pendientes;
+
+ALTER TABLE ventas DROP COLUMN IF EXISTS credito;
+ALTER TABLE personas DROP COLUMN IF EXISTS limite_credito;
@@ -0,0 +1,12 @@
+-- ===== CRÉDITO DE LOS CLIENTES =====
+
+-- Cuánto puede deber el cliente entre todas sus ventas sin pagar; NULL si no compra a crédito
+ALTER TABLE personas ADD COLUMN limite_credito NUMERIC(14, 2)
+    CONSTRAINT chk_limite_credito CHECK (limite_credito IS NULL OR limite_credito >= 0);
+
+-- Las ventas a crédito admiten abonos parciales: lo que queda sin pagar es saldo del cliente
+-- y forma parte de su estado de cuenta
+ALTER TABLE ventas ADD COLUMN credito BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Saldo pendiente de cada cliente
+CREATE INDEX idx_ventas_cliente_pendientes ON ventas(id_persona) WHERE estado = 'PENDIENTE_PAGO';
@@ -0,0 +1,13 @@
+-- ===== ELIMINAR TRIGGERS =====
+DROP TRIGGER IF EXISTS trg_sesiones_caja_auditoria ON sesiones_caja;
+DROP TRIGGER IF EXISTS trg_sesiones_caja_actualizacion ON sesiones_caja;
+
+-- ===== ELIMINAR TABLAS Y COLUMNAS =====
+DROP INDEX IF EXISTS idx_pagos_sesion_caja;
+DROP INDEX IF EXISTS idx_ventas_sesion_caja;
+ALTER TABLE pagos DROP COLUMN IF EXISTS id_sesion_caja;
+ALTER TABLE ventas DROP COLUMN IF EXISTS id_sesion_caja;
+
+DROP TABLE IF EXISTS sesiones_caja;
+
+DROP TYPE IF EXISTS estado_sesion_caja;
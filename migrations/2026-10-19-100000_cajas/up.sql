@@ -0,0 +1,57 @@
+-- ===== SESIONES DE CAJA =====
+
+CREATE TYPE estado_sesion_caja AS ENUM ('ABIERTA', 'CERRADA');
+
+-- ===== TABLA: sesiones_caja =====
+-- Turno de un operador en una terminal de una sucursal. Al cerrarla se guarda el arqueo: el
+-- efectivo esperado (apertura más los pagos en efectivo cobrados en la sesión), el contado y su
+-- diferencia. Una sesión cerrada ya no admite ventas.
+CREATE TABLE sesiones_caja (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    sucursal VARCHAR(100) NOT NULL,
+    terminal VARCHAR(50) NOT NULL,
+    id_operador UUID NOT NULL REFERENCES personas(id),
+    monto_apertura NUMERIC(14, 2) NOT NULL,
+    fecha_apertura TIMESTAMP NOT NULL DEFAULT NOW(),
+    estado estado_sesion_caja NOT NULL DEFAULT 'ABIERTA',
+    fecha_cierre TIMESTAMP,
+    efectivo_esperado NUMERIC(14, 2),
+    efectivo_contado NUMERIC(14, 2),
+    diferencia NUMERIC(14, 2),
+    observaciones TEXT,
+    fecha_creacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    fecha_actualizacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    activo BOOLEAN NOT NULL DEFAULT TRUE,
+
+    CONSTRAINT chk_sesion_monto_apertura CHECK (monto_apertura >= 0),
+    CONSTRAINT chk_sesion_efectivo_contado CHECK (efectivo_contado IS NULL OR efectivo_contado >= 0),
+    CONSTRAINT chk_sesion_cierre CHECK (
+        (estado = 'ABIERTA' AND fecha_cierre IS NULL AND efectivo_contado IS NULL)
+        OR (estado = 'CERRADA' AND fecha_cierre IS NOT NULL AND efectivo_esperado IS NOT NULL
+            AND efectivo_contado IS NOT NULL AND diferencia IS NOT NULL)
+    )
+);
+
+-- Una sola sesión abierta por terminal
+CREATE UNIQUE INDEX idx_sesiones_caja_terminal_abierta ON sesiones_caja(sucursal, terminal)
+    WHERE estado = 'ABIERTA' AND activo = TRUE;
+CREATE INDEX idx_sesiones_caja_operador ON sesiones_caja(id_operador);
+
+-- ===== VENTAS Y PAGOS: sesión en que se registraron =====
+-- NULL en los registrados antes de esta migración y en los pagos cobrados fuera de caja
+ALTER TABLE ventas ADD COLUMN id_sesion_caja UUID REFERENCES sesiones_caja(id);
+ALTER TABLE pagos ADD COLUMN id_sesion_caja UUID REFERENCES sesiones_caja(id);
+
+CREATE INDEX idx_ventas_sesion_caja ON ventas(id_sesion_caja);
+CREATE INDEX idx_pagos_sesion_caja ON pagos(id_sesion_caja);
+
+-- ===== TRIGGERS =====
+CREATE TRIGGER trg_sesiones_caja_actualizacion
+    BEFORE UPDATE ON sesiones_caja
+    FOR EACH ROW
+    EXECUTE FUNCTION actualizar_fecha_modificacion();
+
+CREATE TRIGGER trg_sesiones_caja_auditoria
+    AFTER INSERT OR UPDATE ON sesiones_caja
+    FOR EACH ROW
+    EXECUTE FUNCTION registrar_auditoria();
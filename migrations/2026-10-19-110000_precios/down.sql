@@ -0,0 +1,22 @@
+-- ===== ELIMINAR TRIGGERS =====
+DROP TRIGGER IF EXISTS trg_promociones_auditoria ON promociones;
+DROP TRIGGER IF EXISTS trg_promociones_actualizacion ON promociones;
+DROP TRIGGER IF EXISTS trg_precios_lista_auditoria ON precios_lista;
+DROP TRIGGER IF EXISTS trg_precios_lista_actualizacion ON precios_lista;
+DROP TRIGGER IF EXISTS trg_listas_precios_auditoria ON listas_precios;
+DROP TRIGGER IF EXISTS trg_listas_precios_actualizacion ON listas_precios;
+
+-- ===== ELIMINAR TABLAS Y COLUMNAS =====
+ALTER TABLE detalle_ventas DROP CONSTRAINT IF EXISTS chk_detalle_descuento;
+ALTER TABLE detalle_ventas DROP COLUMN IF EXISTS descuento;
+ALTER TABLE detalle_ventas DROP COLUMN IF EXISTS id_promocion;
+ALTER TABLE detalle_ventas DROP COLUMN IF EXISTS id_lista_precios;
+
+DROP TABLE IF EXISTS promociones;
+
+ALTER TABLE personas DROP COLUMN IF EXISTS id_lista_precios;
+
+DROP TABLE IF EXISTS precios_lista;
+DROP TABLE IF EXISTS listas_precios;
+
+DROP TYPE IF EXISTS tipo_promocion;
@@ -0,0 +1,109 @@
+-- ===== LISTAS DE PRECIOS Y PROMOCIONES =====
+
+CREATE TYPE tipo_promocion AS ENUM ('PORCENTAJE', 'LLEVA_PAGA', 'PRECIO_PAQUETE');
+
+-- ===== TABLA: listas_precios =====
+-- Precios alternativos al de catálogo (minorista, mayorista, VIP...). Cada cliente puede tener
+-- una asignada; los productos que no estén en su lista se venden al precio de catálogo.
+CREATE TABLE listas_precios (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    nombre VARCHAR(100) NOT NULL,
+    descripcion TEXT,
+    fecha_creacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    fecha_actualizacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    activo BOOLEAN NOT NULL DEFAULT TRUE
+);
+
+CREATE UNIQUE INDEX idx_listas_precios_nombre ON listas_precios(LOWER(nombre)) WHERE activo = TRUE;
+
+-- ===== TABLA: precios_lista =====
+-- Precio de la unidad base de un producto en una lista
+CREATE TABLE precios_lista (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    id_lista_precios UUID NOT NULL REFERENCES listas_precios(id),
+    id_producto UUID NOT NULL REFERENCES productos(id),
+    precio_unitario NUMERIC(12, 2) NOT NULL,
+    fecha_creacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    fecha_actualizacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    activo BOOLEAN NOT NULL DEFAULT TRUE,
+
+    CONSTRAINT chk_precio_lista CHECK (precio_unitario >= 0)
+);
+
+CREATE UNIQUE INDEX idx_precios_lista_producto ON precios_lista(id_lista_precios, id_producto) WHERE activo = TRUE;
+
+ALTER TABLE personas ADD COLUMN id_lista_precios UUID REFERENCES listas_precios(id);
+
+-- ===== TABLA: promociones =====
+-- Descuento sobre un producto durante [fecha_inicio, fecha_fin):
+--   PORCENTAJE      porcentaje sobre el subtotal de la línea
+--   LLEVA_PAGA      por cada cantidad_lleva unidades se pagan cantidad_paga (3x2)
+--   PRECIO_PAQUETE  cada cantidad_lleva unidades cuestan precio_paquete
+-- Las promociones no se acumulan: en cada línea se aplica la de mayor descuento.
+CREATE TABLE promociones (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    nombre VARCHAR(100) NOT NULL,
+    tipo tipo_promocion NOT NULL,
+    id_producto UUID NOT NULL REFERENCES productos(id),
+    fecha_inicio TIMESTAMP NOT NULL,
+    fecha_fin TIMESTAMP NOT NULL,
+    porcentaje NUMERIC(5, 2),
+    cantidad_lleva INTEGER,
+    cantidad_paga INTEGER,
+    precio_paquete NUMERIC(12, 2),
+    fecha_creacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    fecha_actualizacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    activo BOOLEAN NOT NULL DEFAULT TRUE,
+
+    CONSTRAINT chk_promocion_vigencia CHECK (fecha_fin > fecha_inicio),
+    CONSTRAINT chk_promocion_tipo CHECK (
+        (tipo = 'PORCENTAJE' AND porcentaje > 0 AND porcentaje <= 100
+            AND cantidad_lleva IS NULL AND cantidad_paga IS NULL AND precio_paquete IS NULL)
+        OR (tipo = 'LLEVA_PAGA' AND porcentaje IS NULL AND cantidad_paga >= 1
+            AND cantidad_lleva > cantidad_paga AND precio_paquete IS NULL)
+        OR (tipo = 'PRECIO_PAQUETE' AND porcentaje IS NULL AND cantidad_lleva >= 2
+            AND cantidad_paga IS NULL AND precio_paquete >= 0)
+    )
+);
+
+CREATE INDEX idx_promociones_producto ON promociones(id_producto, fecha_inicio) WHERE activo = TRUE;
+
+-- ===== DETALLE_VENTAS: origen del precio de la línea =====
+-- Lista de la que salió el precio (NULL: precio de catálogo) y promoción aplicada con su
+-- descuento. El monto de la línea ya descuenta la promoción.
+ALTER TABLE detalle_ventas ADD COLUMN id_lista_precios UUID REFERENCES listas_precios(id);
+ALTER TABLE detalle_ventas ADD COLUMN id_promocion UUID REFERENCES promociones(id);
+ALTER TABLE detalle_ventas ADD COLUMN descuento NUMERIC(14, 2) NOT NULL DEFAULT 0;
+
+ALTER TABLE detalle_ventas ADD CONSTRAINT chk_detalle_descuento CHECK (descuento >= 0);
+
+-- ===== TRIGGERS =====
+CREATE TRIGGER trg_listas_precios_actualizacion
+    BEFORE UPDATE ON listas_precios
+    FOR EACH ROW
+    EXECUTE FUNCTION actualizar_fecha_modificacion();
+
+CREATE TRIGGER trg_listas_precios_auditoria
+    AFTER INSERT OR UPDATE ON listas_precios
+    FOR EACH ROW
+    EXECUTE FUNCTION registrar_auditoria();
+
+CREATE TRIGGER trg_precios_lista_actualizacion
+    BEFORE UPDATE ON precios_lista
+    FOR EACH ROW
+    EXECUTE FUNCTION actualizar_fecha_modificacion();
+
+CREATE TRIGGER trg_precios_lista_auditoria
+    AFTER INSERT OR UPDATE ON precios_lista
+    FOR EACH ROW
+    EXECUTE FUNCTION registrar_auditoria();
+
+CREATE TRIGGER trg_promociones_actualizacion
+    BEFORE UPDATE ON promociones
+    FOR EACH ROW
+    EXECUTE FUNCTION actualizar_fecha_modificacion();
+
+CREATE TRIGGER trg_promociones_auditoria
+    AFTER INSERT OR UPDATE ON promociones
+    FOR EACH ROW
+    EXECUTE FUNCTION registrar_auditoria();
@@ -0,0 +1,14 @@
+-- ===== ELIMINAR TRIGGERS =====
+DROP TRIGGER IF EXISTS trg_detalle_cotizaciones_auditoria ON detalle_cotizaciones;
+DROP TRIGGER IF EXISTS trg_detalle_cotizaciones_actualizacion ON detalle_cotizaciones;
+DROP TRIGGER IF EXISTS trg_cotizaciones_auditoria ON cotizaciones;
+DROP TRIGGER IF EXISTS trg_cotizaciones_actualizacion ON cotizaciones;
+
+-- ===== ELIMINAR TABLAS Y COLUMNAS =====
+DROP INDEX IF EXISTS idx_ventas_cotizacion;
+ALTER TABLE ventas DROP COLUMN IF EXISTS id_cotizacion;
+
+DROP TABLE IF EXISTS detalle_cotizaciones;
+DROP TABLE IF EXISTS cotizaciones;
+
+DROP TYPE IF EXISTS estado_cotizacion;
@@ -0,0 +1,83 @@
+-- ===== COTIZACIONES =====
+
+CREATE TYPE estado_cotizacion AS ENUM ('BORRADOR', 'ENVIADA', 'ACEPTADA', 'VENCIDA', 'CONVERTIDA');
+
+-- ===== TABLA: cotizaciones =====
+-- Precios ofrecidos a un cliente hasta fecha_vencimiento (incluida), sin reservar stock. Pasa
+-- de BORRADOR a ENVIADA y ACEPTADA; al convertirla en venta queda CONVERTIDA con id_venta, y
+-- si vence antes queda VENCIDA.
+CREATE TABLE cotizaciones (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    id_persona UUID NOT NULL REFERENCES personas(id),
+    id_vendedor UUID REFERENCES personas(id),
+    sucursal VARCHAR(100),
+    estado estado_cotizacion NOT NULL DEFAULT 'BORRADOR',
+    fecha_vencimiento DATE NOT NULL,
+    monto NUMERIC(14, 2) NOT NULL,
+    observaciones TEXT,
+    id_venta UUID REFERENCES ventas(id),
+    fecha_creacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    fecha_actualizacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    activo BOOLEAN NOT NULL DEFAULT TRUE,
+
+    CONSTRAINT chk_cotizacion_monto CHECK (monto >= 0),
+    CONSTRAINT chk_cotizacion_venta CHECK ((estado = 'CONVERTIDA') = (id_venta IS NOT NULL))
+);
+
+CREATE INDEX idx_cotizaciones_persona ON cotizaciones(id_persona);
+CREATE INDEX idx_cotizaciones_vencimiento ON cotizaciones(fecha_vencimiento)
+    WHERE estado IN ('BORRADOR', 'ENVIADA', 'ACEPTADA');
+
+-- ===== TABLA: detalle_cotizaciones =====
+-- Mismas columnas de precio que detalle_ventas: el monto ya descuenta la promoción. linea es
+-- la posición en la cotización, la que tendrá en la venta.
+CREATE TABLE detalle_cotizaciones (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    id_cotizacion UUID NOT NULL REFERENCES cotizaciones(id),
+    linea INTEGER NOT NULL,
+    id_producto UUID NOT NULL REFERENCES productos(id),
+    id_unidad UUID REFERENCES unidades_producto(id),
+    cantidad INTEGER NOT NULL,
+    cantidad_unidad INTEGER NOT NULL,
+    precio_unitario NUMERIC(12, 2) NOT NULL,
+    descuento NUMERIC(14, 2) NOT NULL DEFAULT 0,
+    monto NUMERIC(14, 2) NOT NULL,
+    id_lista_precios UUID REFERENCES listas_precios(id),
+    id_promocion UUID REFERENCES promociones(id),
+    fecha_creacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    fecha_actualizacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    activo BOOLEAN NOT NULL DEFAULT TRUE,
+
+    CONSTRAINT chk_detalle_cotizacion_cantidad CHECK (cantidad > 0 AND cantidad_unidad > 0),
+    CONSTRAINT chk_detalle_cotizacion_importes CHECK (precio_unitario >= 0 AND descuento >= 0 AND monto >= 0)
+);
+
+CREATE UNIQUE INDEX idx_detalle_cotizaciones_linea ON detalle_cotizaciones(id_cotizacion, linea)
+    WHERE activo = TRUE;
+
+-- ===== VENTAS: cotización de la que salió =====
+-- Una cotización se convierte en una sola venta
+ALTER TABLE ventas ADD COLUMN id_cotizacion UUID REFERENCES cotizaciones(id);
+
+CREATE UNIQUE INDEX idx_ventas_cotizacion ON ventas(id_cotizacion) WHERE id_cotizacion IS NOT NULL;
+
+-- ===== TRIGGERS =====
+CREATE TRIGGER trg_cotizaciones_actualizacion
+    BEFORE UPDATE ON cotizaciones
+    FOR EACH ROW
+    EXECUTE FUNCTION actualizar_fecha_modificacion();
+
+CREATE TRIGGER trg_cotizaciones_auditoria
+    AFTER INSERT OR UPDATE ON cotizaciones
+    FOR EACH ROW
+    EXECUTE FUNCTION registrar_auditoria();
+
+CREATE TRIGGER trg_detalle_cotizaciones_actualizacion
+    BEFORE UPDATE ON detalle_cotizaciones
+    FOR EACH ROW
+    EXECUTE FUNCTION actualizar_fecha_modificacion();
+
+CREATE TRIGGER trg_detalle_cotizaciones_auditoria
+    AFTER INSERT OR UPDATE ON detalle_cotizaciones
+    FOR EACH ROW
+    EXECUTE FUNCTION registrar_auditoria();
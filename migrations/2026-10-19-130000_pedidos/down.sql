@@ -0,0 +1,75 @@
+-- ===== ELIMINAR TRIGGERS =====
+DROP TRIGGER IF EXISTS trg_detalle_pedidos_auditoria ON detalle_pedidos;
+DROP TRIGGER IF EXISTS trg_detalle_pedidos_actualizacion ON detalle_pedidos;
+DROP TRIGGER IF EXISTS trg_pedidos_auditoria ON pedidos;
+DROP TRIGGER IF EXISTS trg_pedidos_actualizacion ON pedidos;
+
+-- ===== RESTAURAR LAS FUNCIONES DE STOCK DE LAS VENTAS =====
+CREATE OR REPLACE FUNCTION validar_stock_venta()
+RETURNS TRIGGER AS $$
+DECLARE
+    stock_actual INT;
+BEGIN
+    SELECT cantidad_disponible INTO stock_actual
+    FROM inventarios
+    WHERE id_producto = NEW.id_producto;
+
+    IF stock_actual IS NULL OR stock_actual < NEW.cantidad THEN
+        RAISE EXCEPTION 'Stock insuficiente para el producto %', NEW.id_producto;
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION actualizar_inventario_venta()
+RETURNS TRIGGER AS $$
+BEGIN
+    -- Reducir stock
+    UPDATE inventarios
+    SET cantidad_disponible = cantidad_disponible - NEW.cantidad
+    WHERE id_producto = NEW.id_producto;
+
+    -- Registrar movimiento
+    INSERT INTO detalle_inventarios (
+        id_producto,
+        tipo_movimiento,
+        fecha,
+        id_persona,
+        cantidad,
+        observaciones,
+        costo_unitario
+    )
+    SELECT
+        NEW.id_producto,
+        'SALIDA'::tipo_movimiento,
+        NOW(),
+        COALESCE(
+            v.id_vendedor,
+            (SELECT i.id_persona FROM inventarios i WHERE i.id_producto = NEW.id_producto ORDER BY i.activo DESC LIMIT 1),
+            v.id_persona
+        ),
+        -NEW.cantidad,
+        'Venta ID: ' || NEW.id_venta,
+        ROUND(NEW.costo / NULLIF(NEW.cantidad, 0), 4)
+    FROM ventas v
+    WHERE v.id = NEW.id_venta;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+-- ===== ELIMINAR TABLAS Y COLUMNAS =====
+DROP INDEX IF EXISTS idx_ventas_pedido;
+ALTER TABLE ventas DROP COLUMN IF EXISTS id_pedido;
+
+DROP INDEX IF EXISTS idx_detalle_inventarios_detalle_pedido;
+ALTER TABLE detalle_inventarios DROP COLUMN IF EXISTS id_detalle_pedido;
+
+DROP TABLE IF EXISTS detalle_pedidos;
+DROP TABLE IF EXISTS pedidos;
+
+ALTER TABLE inventarios DROP CONSTRAINT IF EXISTS chk_inventario_reservada;
+ALTER TABLE inventarios DROP COLUMN IF EXISTS cantidad_reservada;
+
+DROP TYPE IF EXISTS estado_pedido;
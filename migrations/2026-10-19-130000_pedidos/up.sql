@@ -0,0 +1,169 @@
+-- ===== PEDIDOS =====
+
+CREATE TYPE estado_pedido AS ENUM ('PENDIENTE', 'EN_PREPARACION', 'DESPACHADO', 'ENTREGADO');
+
+-- ===== INVENTARIOS: stock reservado para pedidos =====
+-- Unidades asignadas a pedidos que todavía no se despacharon. Siguen en cantidad_disponible,
+-- pero ninguna venta ni SALIDA puede llevárselas.
+ALTER TABLE inventarios ADD COLUMN cantidad_reservada INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE inventarios ADD CONSTRAINT chk_inventario_reservada
+    CHECK (cantidad_reservada >= 0 AND cantidad_reservada <= cantidad_disponible);
+
+-- ===== TABLA: pedidos =====
+-- Pedido de un cliente (teléfono, web) que se prepara y despacha por partes. Pasa de PENDIENTE
+-- a EN_PREPARACION, a DESPACHADO cuando salió todo y a ENTREGADO con la venta final (id_venta).
+CREATE TABLE pedidos (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    id_persona UUID NOT NULL REFERENCES personas(id),
+    id_vendedor UUID REFERENCES personas(id),
+    sucursal VARCHAR(100),
+    estado estado_pedido NOT NULL DEFAULT 'PENDIENTE',
+    monto NUMERIC(14, 2) NOT NULL,
+    observaciones TEXT,
+    id_venta UUID REFERENCES ventas(id),
+    fecha_creacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    fecha_actualizacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    activo BOOLEAN NOT NULL DEFAULT TRUE,
+
+    CONSTRAINT chk_pedido_monto CHECK (monto >= 0),
+    CONSTRAINT chk_pedido_venta CHECK ((estado = 'ENTREGADO') = (id_venta IS NOT NULL))
+);
+
+CREATE INDEX idx_pedidos_persona ON pedidos(id_persona);
+CREATE INDEX idx_pedidos_estado ON pedidos(estado);
+
+-- ===== TABLA: detalle_pedidos =====
+-- Cantidades en la unidad base: cantidad_asignada está reservada en el inventario y
+-- cantidad_despachada ya salió con una SALIDA; el resto está pendiente de stock (backorder).
+-- Los precios se fijan al crear el pedido, como en detalle_ventas.
+CREATE TABLE detalle_pedidos (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    id_pedido UUID NOT NULL REFERENCES pedidos(id),
+    linea INTEGER NOT NULL,
+    id_producto UUID NOT NULL REFERENCES productos(id),
+    id_unidad UUID REFERENCES unidades_producto(id),
+    cantidad INTEGER NOT NULL,
+    cantidad_unidad INTEGER NOT NULL,
+    cantidad_asignada INTEGER NOT NULL DEFAULT 0,
+    cantidad_despachada INTEGER NOT NULL DEFAULT 0,
+    precio_unitario NUMERIC(12, 2) NOT NULL,
+    descuento NUMERIC(14, 2) NOT NULL DEFAULT 0,
+    monto NUMERIC(14, 2) NOT NULL,
+    id_lista_precios UUID REFERENCES listas_precios(id),
+    id_promocion UUID REFERENCES promociones(id),
+    fecha_creacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    fecha_actualizacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    activo BOOLEAN NOT NULL DEFAULT TRUE,
+
+    CONSTRAINT chk_detalle_pedido_cantidad CHECK (cantidad > 0 AND cantidad_unidad > 0),
+    CONSTRAINT chk_detalle_pedido_asignacion CHECK (
+        cantidad_asignada >= 0 AND cantidad_despachada >= 0
+        AND cantidad_asignada + cantidad_despachada <= cantidad
+    ),
+    CONSTRAINT chk_detalle_pedido_importes CHECK (precio_unitario >= 0 AND descuento >= 0 AND monto >= 0)
+);
+
+CREATE UNIQUE INDEX idx_detalle_pedidos_linea ON detalle_pedidos(id_pedido, linea) WHERE activo = TRUE;
+CREATE INDEX idx_detalle_pedidos_pendientes ON detalle_pedidos(id_producto)
+    WHERE activo = TRUE AND cantidad_asignada + cantidad_despachada < cantidad;
+
+-- ===== DETALLE_INVENTARIOS: línea de pedido despachada =====
+-- Las SALIDAS de un despacho apuntan a su línea; hay una por lote consumido
+ALTER TABLE detalle_inventarios ADD COLUMN id_detalle_pedido UUID REFERENCES detalle_pedidos(id);
+
+CREATE INDEX idx_detalle_inventarios_detalle_pedido ON detalle_inventarios(id_detalle_pedido)
+    WHERE id_detalle_pedido IS NOT NULL;
+
+-- ===== VENTAS: pedido del que salió =====
+-- Un pedido se entrega con una sola venta
+ALTER TABLE ventas ADD COLUMN id_pedido UUID REFERENCES pedidos(id);
+
+CREATE UNIQUE INDEX idx_ventas_pedido ON ventas(id_pedido) WHERE id_pedido IS NOT NULL;
+
+-- ===== FUNCIÓN: validar stock de una venta =====
+-- Lo reservado para pedidos no se vende. Las líneas de la venta de un pedido ya salieron del
+-- stock con sus despachos.
+CREATE OR REPLACE FUNCTION validar_stock_venta()
+RETURNS TRIGGER AS $$
+DECLARE
+    stock_libre INT;
+BEGIN
+    IF EXISTS (SELECT 1 FROM ventas v WHERE v.id = NEW.id_venta AND v.id_pedido IS NOT NULL) THEN
+        RETURN NEW;
+    END IF;
+
+    SELECT cantidad_disponible - cantidad_reservada INTO stock_libre
+    FROM inventarios
+    WHERE id_producto = NEW.id_producto;
+
+    IF stock_libre IS NULL OR stock_libre < NEW.cantidad THEN
+        RAISE EXCEPTION 'Stock insuficiente para el producto %', NEW.id_producto;
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+-- ===== FUNCIÓN: la venta de un pedido no vuelve a descontar stock =====
+CREATE OR REPLACE FUNCTION actualizar_inventario_venta()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF EXISTS (SELECT 1 FROM ventas v WHERE v.id = NEW.id_venta AND v.id_pedido IS NOT NULL) THEN
+        RETURN NEW;
+    END IF;
+
+    -- Reducir stock
+    UPDATE inventarios
+    SET cantidad_disponible = cantidad_disponible - NEW.cantidad
+    WHERE id_producto = NEW.id_producto;
+
+    -- Registrar movimiento
+    INSERT INTO detalle_inventarios (
+        id_producto,
+        tipo_movimiento,
+        fecha,
+        id_persona,
+        cantidad,
+        observaciones,
+        costo_unitario
+    )
+    SELECT
+        NEW.id_producto,
+        'SALIDA'::tipo_movimiento,
+        NOW(),
+        COALESCE(
+            v.id_vendedor,
+            (SELECT i.id_persona FROM inventarios i WHERE i.id_producto = NEW.id_producto ORDER BY i.activo DESC LIMIT 1),
+            v.id_persona
+        ),
+        -NEW.cantidad,
+        'Venta ID: ' || NEW.id_venta,
+        ROUND(NEW.costo / NULLIF(NEW.cantidad, 0), 4)
+    FROM ventas v
+    WHERE v.id = NEW.id_venta;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+-- ===== TRIGGERS =====
+CREATE TRIGGER trg_pedidos_actualizacion
+    BEFORE UPDATE ON pedidos
+    FOR EACH ROW
+    EXECUTE FUNCTION actualizar_fecha_modificacion();
+
+CREATE TRIGGER trg_pedidos_auditoria
+    AFTER INSERT OR UPDATE ON pedidos
+    FOR EACH ROW
+    EXECUTE FUNCTION registrar_auditoria();
+
+CREATE TRIGGER trg_detalle_pedidos_actualizacion
+    BEFORE UPDATE ON detalle_pedidos
+    FOR EACH ROW
+    EXECUTE FUNCTION actualizar_fecha_modificacion();
+
+CREATE TRIGGER trg_detalle_pedidos_auditoria
+    AFTER INSERT OR UPDATE ON detalle_pedidos
+    FOR EACH ROW
+    EXECUTE FUNCTION registrar_auditoria();
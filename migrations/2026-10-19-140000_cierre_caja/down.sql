@@ -0,0 +1,11 @@
+-- ===== ELIMINAR TRIGGERS =====
+DROP TRIGGER IF EXISTS trg_totales_cierre_caja_auditoria ON totales_cierre_caja;
+DROP TRIGGER IF EXISTS trg_totales_cierre_caja_actualizacion ON totales_cierre_caja;
+
+-- ===== ELIMINAR TABLAS Y COLUMNAS =====
+DROP TABLE IF EXISTS totales_cierre_caja;
+
+ALTER TABLE sesiones_caja DROP CONSTRAINT IF EXISTS chk_sesion_resumen_cierre;
+ALTER TABLE sesiones_caja DROP COLUMN IF EXISTS total_vendido;
+ALTER TABLE sesiones_caja DROP COLUMN IF EXISTS ventas_anuladas;
+ALTER TABLE sesiones_caja DROP COLUMN IF EXISTS ventas;
@@ -0,0 +1,64 @@
+-- ===== CIERRE DE CAJA: resumen del reporte Z =====
+
+-- ===== SESIONES DE CAJA: ventas fijadas al cerrar =====
+-- Al cerrar la sesión se guarda lo vendido en ella, así que el reporte Z no cambia después
+ALTER TABLE sesiones_caja ADD COLUMN ventas BIGINT;
+ALTER TABLE sesiones_caja ADD COLUMN ventas_anuladas BIGINT;
+ALTER TABLE sesiones_caja ADD COLUMN total_vendido NUMERIC(14, 2);
+
+-- ===== TABLA: totales_cierre_caja =====
+-- Lo cobrado en la sesión por cada método de pago, fijado al cerrarla
+CREATE TABLE totales_cierre_caja (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    id_sesion_caja UUID NOT NULL REFERENCES sesiones_caja(id),
+    metodo metodo_pago NOT NULL,
+    pagos BIGINT NOT NULL,
+    monto NUMERIC(14, 2) NOT NULL,
+    cambio NUMERIC(14, 2) NOT NULL,
+    fecha_creacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    fecha_actualizacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    activo BOOLEAN NOT NULL DEFAULT TRUE,
+
+    CONSTRAINT uq_total_cierre_caja_metodo UNIQUE (id_sesion_caja, metodo)
+);
+
+-- ===== SESIONES YA CERRADAS =====
+-- Se fija su resumen con las ventas y los pagos vigentes al aplicar esta migración
+UPDATE sesiones_caja s
+SET ventas = r.ventas,
+    ventas_anuladas = r.ventas_anuladas,
+    total_vendido = r.total_vendido
+FROM (
+    SELECT s2.id,
+           COUNT(v.id) FILTER (WHERE v.estado <> 'ANULADA') AS ventas,
+           COUNT(v.id) FILTER (WHERE v.estado = 'ANULADA') AS ventas_anuladas,
+           COALESCE(SUM(v.monto) FILTER (WHERE v.estado <> 'ANULADA'), 0) AS total_vendido
+    FROM sesiones_caja s2
+    LEFT JOIN ventas v ON v.id_sesion_caja = s2.id AND v.activo = TRUE
+    WHERE s2.estado = 'CERRADA'
+    GROUP BY s2.id
+) r
+WHERE s.id = r.id;
+
+INSERT INTO totales_cierre_caja (id_sesion_caja, metodo, pagos, monto, cambio)
+SELECT p.id_sesion_caja, p.metodo, COUNT(*), SUM(p.monto), SUM(p.cambio)
+FROM pagos p
+JOIN sesiones_caja s ON s.id = p.id_sesion_caja
+WHERE s.estado = 'CERRADA' AND p.activo = TRUE
+GROUP BY p.id_sesion_caja, p.metodo;
+
+ALTER TABLE sesiones_caja ADD CONSTRAINT chk_sesion_resumen_cierre CHECK (
+    estado = 'ABIERTA'
+    OR (ventas IS NOT NULL AND ventas_anuladas IS NOT NULL AND total_vendido IS NOT NULL)
+);
+
+-- ===== TRIGGERS =====
+CREATE TRIGGER trg_totales_cierre_caja_actualizacion
+    BEFORE UPDATE ON totales_cierre_caja
+    FOR EACH ROW
+    EXECUTE FUNCTION actualizar_fecha_modificacion();
+
+CREATE TRIGGER trg_totales_cierre_caja_auditoria
+    AFTER INSERT OR UPDATE ON totales_cierre_caja
+    FOR EACH ROW
+    EXECUTE FUNCTION registrar_auditoria();
@@ -0,0 +1,8 @@
+-- ===== ELIMINAR LOS PRECIOS POR UNIDAD =====
+DROP INDEX IF EXISTS idx_precios_lista_unidad;
+DELETE FROM precios_lista WHERE id_unidad IS NOT NULL;
+
+DROP INDEX IF EXISTS idx_precios_lista_producto;
+CREATE UNIQUE INDEX idx_precios_lista_producto ON precios_lista(id_lista_precios, id_producto) WHERE activo = TRUE;
+
+ALTER TABLE precios_lista DROP COLUMN IF EXISTS id_unidad;
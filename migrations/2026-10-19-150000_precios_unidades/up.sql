@@ -0,0 +1,12 @@
+-- ===== PRECIOS DE LISTA POR UNIDAD DE VENTA =====
+
+-- ===== PRECIOS_LISTA: unidad del precio =====
+-- NULL es la unidad base. Una unidad sin precio propio en la lista se vende al precio base de
+-- la lista por su factor.
+ALTER TABLE precios_lista ADD COLUMN id_unidad UUID REFERENCES unidades_producto(id);
+
+DROP INDEX IF EXISTS idx_precios_lista_producto;
+CREATE UNIQUE INDEX idx_precios_lista_producto ON precios_lista(id_lista_precios, id_producto)
+    WHERE activo = TRUE AND id_unidad IS NULL;
+CREATE UNIQUE INDEX idx_precios_lista_unidad ON precios_lista(id_lista_precios, id_unidad)
+    WHERE activo = TRUE AND id_unidad IS NOT NULL;
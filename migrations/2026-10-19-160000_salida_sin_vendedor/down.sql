@@ -0,0 +1,41 @@
+-- ===== RESTAURAR: la SALIDA sin vendedor queda a cargo del responsable del inventario =====
+CREATE OR REPLACE FUNCTION actualizar_inventario_venta()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF EXISTS (SELECT 1 FROM ventas v WHERE v.id = NEW.id_venta AND v.id_pedido IS NOT NULL) THEN
+        RETURN NEW;
+    END IF;
+
+    -- Reducir stock
+    UPDATE inventarios
+    SET cantidad_disponible = cantidad_disponible - NEW.cantidad
+    WHERE id_producto = NEW.id_producto;
+
+    -- Registrar movimiento
+    INSERT INTO detalle_inventarios (
+        id_producto,
+        tipo_movimiento,
+        fecha,
+        id_persona,
+        cantidad,
+        observaciones,
+        costo_unitario
+    )
+    SELECT
+        NEW.id_producto,
+        'SALIDA'::tipo_movimiento,
+        NOW(),
+        COALESCE(
+            v.id_vendedor,
+            (SELECT i.id_persona FROM inventarios i WHERE i.id_producto = NEW.id_producto ORDER BY i.activo DESC LIMIT 1),
+            v.id_persona
+        ),
+        -NEW.cantidad,
+        'Venta ID: ' || NEW.id_venta,
+        ROUND(NEW.costo / NULLIF(NEW.cantidad, 0), 4)
+    FROM ventas v
+    WHERE v.id = NEW.id_venta;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
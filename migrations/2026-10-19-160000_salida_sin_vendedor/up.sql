@@ -0,0 +1,42 @@
+-- ===== FUNCIÓN: la SALIDA de una venta sin vendedor queda a cargo del operador de la caja =====
+-- Antes recaía en el responsable del inventario del producto, que no intervino en la venta.
+CREATE OR REPLACE FUNCTION actualizar_inventario_venta()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF EXISTS (SELECT 1 FROM ventas v WHERE v.id = NEW.id_venta AND v.id_pedido IS NOT NULL) THEN
+        RETURN NEW;
+    END IF;
+
+    -- Reducir stock
+    UPDATE inventarios
+    SET cantidad_disponible = cantidad_disponible - NEW.cantidad
+    WHERE id_producto = NEW.id_producto;
+
+    -- Registrar movimiento
+    INSERT INTO detalle_inventarios (
+        id_producto,
+        tipo_movimiento,
+        fecha,
+        id_persona,
+        cantidad,
+        observaciones,
+        costo_unitario
+    )
+    SELECT
+        NEW.id_producto,
+        'SALIDA'::tipo_movimiento,
+        NOW(),
+        COALESCE(
+            v.id_vendedor,
+            (SELECT s.id_operador FROM sesiones_caja s WHERE s.id = v.id_sesion_caja),
+            v.id_persona
+        ),
+        -NEW.cantidad,
+        'Venta ID: ' || NEW.id_venta,
+        ROUND(NEW.costo / NULLIF(NEW.cantidad, 0), 4)
+    FROM ventas v
+    WHERE v.id = NEW.id_venta;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,36 @@
+-- ===== DATOS DE DEMOSTRACIÓN =====
+-- Se cargan con `polimarket-admin seed --profile demo`; nunca se aplican con las migraciones.
+-- Es idempotente: volver a ejecutarlo no duplica personas, productos ni inventarios.
+
+-- Personas
+INSERT INTO personas (nombre, documento, perfil, email) VALUES
+('Juan Pérez', '12345678', 'CLIENTE', 'juan@email.com'),
+('María González', '87654321', 'VENDEDOR', 'maria@email.com'),
+('Proveedor ABC', '11111111', 'PROVEEDOR', 'proveedor@abc.com')
+ON CONFLICT (documento) DO NOTHING;
+
+-- Productos
+INSERT INTO productos (nombre, cantidad, unidad_venta, precio_unitario)
+SELECT s.nombre, s.cantidad, 'Unidad', s.precio_unitario
+FROM (VALUES
+    ('Laptop Dell', 10, 1200.00),
+    ('Mouse Logitech', 50, 25.00),
+    ('Teclado Mecánico', 30, 80.00)
+) AS s(nombre, cantidad, precio_unitario)
+WHERE NOT EXISTS (SELECT 1 FROM productos p WHERE p.nombre = s.nombre);
+
+-- Inventarios a cargo de la vendedora, con su ENTRADA inicial para que el stock
+-- cuadre con la suma de movimientos
+WITH nuevos AS (
+    INSERT INTO inventarios (id_producto, id_persona, cantidad_disponible)
+    SELECT p.id, v.id, p.cantidad
+    FROM productos p
+    CROSS JOIN (SELECT id FROM personas WHERE documento = '87654321') v
+    WHERE p.nombre IN ('Laptop Dell', 'Mouse Logitech', 'Teclado Mecánico')
+    ON CONFLICT (id_producto) DO NOTHING
+    RETURNING id_producto, id_persona, cantidad_disponible
+)
+INSERT INTO detalle_inventarios (id_producto, tipo_movimiento, fecha, id_persona, cantidad, observaciones)
+SELECT id_producto, 'ENTRADA', NOW(), id_persona, cantidad_disponible, 'Inventario inicial'
+FROM nuevos
+WHERE cantidad_disponible > 0;
@@ -0,0 +1,184 @@
+//! Tareas de administración de la base de datos que usa el binario `polimarket-admin`:
+//! migraciones, datos semilla y mantenimiento del stock.
+
+use diesel::pg::Pg;
+use diesel::prelude::*;
+use diesel::sql_types::Uuid as SqlUuid;
+use diesel::connection::SimpleConnection;
+use diesel::migration::MigrationSource;
+use diesel_migrations::MigrationHarness;
+use uuid::Uuid;
+
+use crate::modules::auditoria;
+use crate::modules::common::types::EstadoSerie;
+use crate::modules::consistencia::model::DescuadreStock;
+use crate::modules::consistencia::repository::{self as consistencia_db, SUMA_MOVIMIENTOS};
+use crate::schema::{capas_costo, inventarios, lotes, numeros_serie, productos};
+use crate::MIGRATIONS;
+
+pub type AdminResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// Datos de demostración; se cargan a mano y nunca con las migraciones
+const SEMILLA_DEMO: &str = include_str!("../../seeds/demo.sql");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum PerfilSemilla {
+    /// Tres personas (cliente, vendedora y proveedor) y tres productos con inventario
+    Demo,
+}
+
+impl PerfilSemilla {
+    fn sql(self) -> &'static str {
+        match self {
+            PerfilSemilla::Demo => SEMILLA_DEMO,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EstadoMigracion {
+    pub version: String,
+    pub nombre: String,
+    pub aplicada: bool,
+}
+
+/// Aplica las migraciones pendientes y devuelve las versiones aplicadas
+pub fn migrar(conn: &mut PgConnection) -> AdminResult<Vec<String>> {
+    let versiones = conn.run_pending_migrations(MIGRATIONS)?;
+    Ok(versiones.iter().map(ToString::to_string).collect())
+}
+
+/// Revierte las últimas `pasos` migraciones y devuelve sus versiones, de la más reciente a la más antigua
+pub fn revertir(conn: &mut PgConnection, pasos: usize) -> AdminResult<Vec<String>> {
+    let mut revertidas = Vec::with_capacity(pasos);
+    for _ in 0..pasos {
+        revertidas.push(conn.revert_last_migration(MIGRATIONS)?.to_string());
+    }
+    Ok(revertidas)
+}
+
+/// Todas las migraciones embebidas, en orden, indicando si ya están aplicadas
+pub fn estado_migraciones(conn: &mut PgConnection) -> AdminResult<Vec<EstadoMigracion>> {
+    let aplicadas = conn.applied_migrations()?;
+    let mut migraciones = MigrationSource::<Pg>::migrations(&MIGRATIONS)?;
+    migraciones.sort_by_key(|migracion| migracion.name().version().as_owned());
+
+    Ok(migraciones
+        .iter()
+        .map(|migracion| EstadoMigracion {
+            version: migracion.name().version().to_string(),
+            nombre: migracion.name().to_string(),
+            aplicada: aplicadas.contains(&migracion.name().version()),
+        })
+        .collect())
+}
+
+/// Carga los datos del perfil indicado en una transacción (es idempotente)
+pub fn sembrar(conn: &mut PgConnection, perfil: PerfilSemilla) -> QueryResult<()> {
+    conn.transaction(|conn| conn.batch_execute(perfil.sql()))
+}
+
+/// Producto que `recalcular_stock` deja como está y por qué
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecalculoRechazado {
+    pub descuadre: DescuadreStock,
+    pub motivo: String,
+}
+
+/// Resultado de `recalcular_stock`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecalculoStock {
+    /// Productos corregidos, con el stock que tenían antes
+    pub corregidos: Vec<DescuadreStock>,
+    /// Productos cuyo nuevo stock no cubriría sus reservas, lotes, capas de costo o números de serie
+    pub rechazados: Vec<RecalculoRechazado>,
+}
+
+/// Recalcula `cantidad_disponible` a partir de los movimientos (lo contrario de
+/// `ConsistenciaService::reparar`, que da el stock por bueno).
+///
+/// Un producto sólo se corrige si el nuevo stock sigue cubriendo lo que depende de él: lo
+/// reservado, sus lotes y capas de costo activos y, si es serializado, sus números de serie en
+/// stock. Si no, se deja como está y se informa en `rechazados`.
+pub fn recalcular_stock(conn: &mut PgConnection) -> QueryResult<RecalculoStock> {
+    conn.transaction(|conn| {
+        auditoria::contexto::aplicar(conn)?;
+        inventarios::table
+            .filter(inventarios::activo.eq(true))
+            .select(inventarios::id)
+            .for_update()
+            .load::<Uuid>(conn)?;
+
+        let mut resultado = RecalculoStock::default();
+        for descuadre in consistencia_db::descuadres(conn, None)? {
+            match motivo_rechazo(conn, &descuadre)? {
+                Some(motivo) => resultado.rechazados.push(RecalculoRechazado { descuadre, motivo }),
+                None => {
+                    diesel::sql_query(format!(
+                        "UPDATE inventarios i SET cantidad_disponible = ({}) WHERE i.id_producto = $1",
+                        SUMA_MOVIMIENTOS
+                    ))
+                    .bind::<SqlUuid, _>(descuadre.id_producto)
+                    .execute(conn)?;
+                    resultado.corregidos.push(descuadre);
+                }
+            }
+        }
+
+        Ok(resultado)
+    })
+}
+
+/// Por qué el stock de `descuadre` no puede pasar a ser la suma de sus movimientos, si no puede
+fn motivo_rechazo(conn: &mut PgConnection, descuadre: &DescuadreStock) -> QueryResult<Option<String>> {
+    let id_producto = descuadre.id_producto;
+    let nuevo = i64::from(descuadre.suma_movimientos);
+    if nuevo < 0 {
+        return Ok(Some(format!("los movimientos suman {} unidades", nuevo)));
+    }
+
+    let reservada: i32 = inventarios::table
+        .filter(inventarios::id_producto.eq(id_producto))
+        .filter(inventarios::activo.eq(true))
+        .select(inventarios::cantidad_reservada)
+        .first(conn)?;
+    if nuevo < i64::from(reservada) {
+        return Ok(Some(format!("hay {} unidades reservadas", reservada)));
+    }
+
+    let en_lotes: Option<i64> = lotes::table
+        .filter(lotes::id_producto.eq(id_producto))
+        .filter(lotes::activo.eq(true))
+        .select(diesel::dsl::sum(lotes::cantidad_disponible))
+        .first(conn)?;
+    if let Some(en_lotes) = en_lotes.filter(|en_lotes| nuevo < *en_lotes) {
+        return Ok(Some(format!("sus lotes activos suman {} unidades", en_lotes)));
+    }
+
+    let en_capas: Option<i64> = capas_costo::table
+        .filter(capas_costo::id_producto.eq(id_producto))
+        .filter(capas_costo::activo.eq(true))
+        .select(diesel::dsl::sum(capas_costo::cantidad_disponible))
+        .first(conn)?;
+    if let Some(en_capas) = en_capas.filter(|en_capas| nuevo < *en_capas) {
+        return Ok(Some(format!("sus capas de costo activas suman {} unidades", en_capas)));
+    }
+
+    let serializado: bool = productos::table
+        .find(id_producto)
+        .select(productos::serializado)
+        .first(conn)?;
+    if serializado {
+        let en_stock: i64 = numeros_serie::table
+            .filter(numeros_serie::id_producto.eq(id_producto))
+            .filter(numeros_serie::activo.eq(true))
+            .filter(numeros_serie::estado.eq_any([EstadoSerie::EnStock, EstadoSerie::Devuelto]))
+            .count()
+            .get_result(conn)?;
+        if nuevo != en_stock {
+            return Ok(Some(format!("tiene {} números de serie en stock", en_stock)));
+        }
+    }
+
+    Ok(None)
+}
@@ -0,0 +1,166 @@
+use std::collections::BTreeMap;
+use std::time::Instant;
+use actix_web::{web, HttpResponse, Result};
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel_migrations::MigrationHarness;
+use crate::health::model::{
+    ComponentHealth, HealthStatus, LivenessResponse, ReadinessResponse, GIT_SHA, SERVICE_NAME, VERSION,
+};
+use crate::state::app_state::{AppState, DbPool};
+use crate::MIGRATIONS;
+
+/// Porcentaje de conexiones en uso a partir del cual el pool se reporta como degradado
+const POOL_DEGRADED_RATIO: f64 = 0.8;
+
+/// GET /v1/health/live - El proceso está vivo y atiende peticiones
+#[utoipa::path(
+    get,
+    path = "/v1/health/live",
+    tag = "Health",
+    responses(
+        (status = 200, description = "El proceso está vivo", body = LivenessResponse)
+    )
+)]
+pub async fn liveness() -> HttpResponse {
+    HttpResponse::Ok().json(LivenessResponse {
+        status: HealthStatus::Healthy,
+        service: SERVICE_NAME,
+        version: VERSION,
+        git_sha: GIT_SHA,
+    })
+}
+
+/// GET /v1/health/ready - El servicio puede atender tráfico (base de datos, migraciones y pool)
+#[utoipa::path(
+    get,
+    path = "/v1/health/ready",
+    tag = "Health",
+    responses(
+        (status = 200, description = "Servicio listo (healthy o degraded)", body = ReadinessResponse),
+        (status = 503, description = "Algún componente no está operativo", body = ReadinessResponse)
+    )
+)]
+pub async fn readiness(state: web::Data<AppState>) -> Result<HttpResponse> {
+    let mut components = BTreeMap::new();
+
+    let (database, migrations) = verificar_base_de_datos(&state.pool);
+    components.insert("database".to_string(), database);
+    components.insert("migrations".to_string(), migrations);
+    components.insert("pool".to_string(), verificar_pool(&state.pool));
+
+    let status = components
+        .values()
+        .map(|c| c.status)
+        .max()
+        .unwrap_or(HealthStatus::Healthy);
+
+    let response = ReadinessResponse {
+        status,
+        service: SERVICE_NAME,
+        version: VERSION,
+        git_sha: GIT_SHA,
+        components,
+    };
+
+    if status == HealthStatus::Unhealthy {
+        Ok(HttpResponse::ServiceUnavailable().json(response))
+    } else {
+        Ok(HttpResponse::Ok().json(response))
+    }
+}
+
+/// Ejecuta `SELECT 1` sobre una conexión del pool y revisa si quedan migraciones pendientes
+fn verificar_base_de_datos(pool: &DbPool) -> (ComponentHealth, ComponentHealth) {
+    let inicio = Instant::now();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            let detalle = format!("No se pudo obtener una conexión del pool: {}", e);
+            return (
+                ComponentHealth {
+                    status: HealthStatus::Unhealthy,
+                    detalle: detalle.clone(),
+                    latencia_ms: Some(inicio.elapsed().as_millis() as u64),
+                },
+                ComponentHealth {
+                    status: HealthStatus::Unhealthy,
+                    detalle: "No verificable sin conexión a la base de datos".to_string(),
+                    latencia_ms: None,
+                },
+            );
+        }
+    };
+
+    let database = match sql_query("SELECT 1").execute(&mut conn) {
+        Ok(_) => ComponentHealth {
+            status: HealthStatus::Healthy,
+            detalle: "SELECT 1 ejecutado correctamente".to_string(),
+            latencia_ms: Some(inicio.elapsed().as_millis() as u64),
+        },
+        Err(e) => ComponentHealth {
+            status: HealthStatus::Unhealthy,
+            detalle: format!("SELECT 1 falló: {}", e),
+            latencia_ms: Some(inicio.elapsed().as_millis() as u64),
+        },
+    };
+
+    let migrations = match conn.pending_migrations(MIGRATIONS) {
+        Ok(pendientes) if pendientes.is_empty() => ComponentHealth {
+            status: HealthStatus::Healthy,
+            detalle: "Esquema al día".to_string(),
+            latencia_ms: None,
+        },
+        Ok(pendientes) => ComponentHealth {
+            status: HealthStatus::Unhealthy,
+            detalle: format!(
+                "{} migración(es) pendiente(s): {}",
+                pendientes.len(),
+                pendientes.iter().map(|m| m.name().to_string()).collect::<Vec<_>>().join(", ")
+            ),
+            latencia_ms: None,
+        },
+        Err(e) => ComponentHealth {
+            status: HealthStatus::Unhealthy,
+            detalle: format!("No se pudo consultar el estado de las migraciones: {}", e),
+            latencia_ms: None,
+        },
+    };
+
+    (database, migrations)
+}
+
+/// Evalúa la saturación del pool a partir de su estado actual
+fn verificar_pool(pool: &DbPool) -> ComponentHealth {
+    let estado = pool.state();
+    let max = pool.max_size();
+    let en_uso = estado.connections.saturating_sub(estado.idle_connections);
+    let ratio = en_uso as f64 / max as f64;
+
+    let status = if en_uso >= max {
+        HealthStatus::Unhealthy
+    } else if ratio >= POOL_DEGRADED_RATIO {
+        HealthStatus::Degraded
+    } else {
+        HealthStatus::Healthy
+    };
+
+    ComponentHealth {
+        status,
+        detalle: format!(
+            "{} de {} conexiones en uso, {} libres",
+            en_uso, max, estado.idle_connections
+        ),
+        latencia_ms: None,
+    }
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/health")
+            .route("", web::get().to(readiness))
+            .route("/live", web::get().to(liveness))
+            .route("/ready", web::get().to(readiness))
+    );
+}
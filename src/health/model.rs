@@ -0,0 +1,58 @@
+use std::collections::BTreeMap;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+pub const SERVICE_NAME: &str = "PoliMarket API";
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+pub const GIT_SHA: &str = env!("GIT_SHA");
+
+/// Estado de un componente o del servicio completo
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    /// Operativo
+    Healthy,
+    /// Operativo pero cerca de su límite (no retira la instancia del balanceador)
+    Degraded,
+    /// No operativo: la instancia no debe recibir tráfico
+    Unhealthy,
+}
+
+// DTO for a single dependency check
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ComponentHealth {
+    #[schema(example = "healthy")]
+    pub status: HealthStatus,
+    #[schema(example = "SELECT 1 ejecutado correctamente")]
+    pub detalle: String,
+    #[schema(example = 3)]
+    pub latencia_ms: Option<u64>,
+}
+
+// DTO for liveness response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LivenessResponse {
+    #[schema(example = "healthy")]
+    pub status: HealthStatus,
+    #[schema(example = "PoliMarket API")]
+    pub service: &'static str,
+    #[schema(example = "0.1.0")]
+    pub version: &'static str,
+    #[schema(example = "3b93262a1f0c")]
+    pub git_sha: &'static str,
+}
+
+// DTO for readiness response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReadinessResponse {
+    #[schema(example = "healthy")]
+    pub status: HealthStatus,
+    #[schema(example = "PoliMarket API")]
+    pub service: &'static str,
+    #[schema(example = "0.1.0")]
+    pub version: &'static str,
+    #[schema(example = "3b93262a1f0c")]
+    pub git_sha: &'static str,
+    /// Estado por componente: database, migrations, pool
+    pub components: BTreeMap<String, ComponentHealth>,
+}
@@ -0,0 +1,107 @@
+pub mod schema;
+pub mod modules;
+pub mod config;
+pub mod state;
+pub mod metrics;
+pub mod health;
+pub mod telemetry;
+pub mod security;
+pub mod openapi;
+pub mod admin;
+
+use actix_cors::Cors;
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceFactory, ServiceRequest, ServiceResponse};
+use actix_web::http::header::{self, HeaderName};
+use actix_web::middleware::from_fn;
+use actix_web::{web, App};
+use diesel_migrations::{embed_migrations, EmbeddedMigrations};
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::config::Config;
+use crate::openapi::ApiDoc;
+use crate::security::rate_limit::RateLimiter;
+use crate::state::app_state::AppState;
+
+// Embed migrations into the binary
+pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
+
+/// Construye la aplicación completa: middlewares, Swagger UI, /metrics y las rutas de /v1.
+///
+/// La usan tanto el servidor como los tests de integración, para probar exactamente lo mismo.
+pub fn crear_app(
+    app_state: web::Data<AppState>,
+    rate_limiter: web::Data<RateLimiter>,
+    config: &Config,
+) -> App<
+    impl ServiceFactory<
+        ServiceRequest,
+        Config = (),
+        Response = ServiceResponse<impl MessageBody>,
+        Error = actix_web::Error,
+        InitError = (),
+    >,
+> {
+    // Configure CORS ('*' permite cualquier origen)
+    let cors = config
+        .cors_allowed_origins
+        .iter()
+        .fold(Cors::default(), |cors, origen| {
+            if origen == "*" {
+                cors.allow_any_origin()
+            } else {
+                cors.allowed_origin(origen)
+            }
+        })
+        .allowed_methods(["GET", "POST", "PUT", "DELETE"])
+        .allowed_headers([
+            header::ACCEPT,
+            header::CONTENT_TYPE,
+            HeaderName::from_static(telemetry::request_id::REQUEST_ID_HEADER),
+            HeaderName::from_static(security::PERSONA_ID_HEADER),
+        ])
+        .expose_headers([
+            HeaderName::from_static(telemetry::request_id::REQUEST_ID_HEADER),
+            header::RETRY_AFTER,
+        ])
+        .max_age(3600);
+
+    App::new()
+        .app_data(app_state)
+        .app_data(rate_limiter)
+        .app_data(security::json_config(config.max_body_bytes))
+        .wrap(from_fn(modules::auditoria::contexto::establecer_contexto))
+        .wrap(from_fn(security::rate_limit::limitar_peticiones))
+        .wrap(from_fn(metrics::middleware::registrar_latencia))
+        .wrap(security::headers::cabeceras_seguridad(config.hsts_max_age_seconds))
+        .wrap(cors)
+        .wrap(from_fn(telemetry::request_id::trazar_peticion))
+        // Swagger UI
+        .service(
+            SwaggerUi::new("/swagger-ui/{_:.*}")
+                .url("/api-docs/openapi.json", ApiDoc::openapi())
+        )
+        // Prometheus metrics
+        .configure(metrics::handler::configure)
+        .service(
+            web::scope("/v1")
+                // Health check endpoints
+                .configure(health::handler::configure)
+                // Module routes
+                .configure(modules::cuentas::handler::configure)
+                .configure(modules::precios::handler::configure)
+                .configure(modules::cotizaciones::handler::configure)
+                .configure(modules::pedidos::handler::configure)
+                .configure(modules::personas::handler::configure)
+                .configure(modules::productos::handler::configure)
+                .configure(modules::catalogo::handler::configure)
+                .configure(modules::inventarios::handler::configure)
+                .configure(modules::ventas::handler::configure)
+                .configure(modules::cajas::handler::configure)
+                .configure(modules::comisiones::handler::configure)
+                .configure(modules::auditoria::handler::configure)
+                .configure(modules::consistencia::handler::configure)
+                .configure(modules::reportes::handler::configure)
+        )
+}
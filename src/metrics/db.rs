@@ -0,0 +1,78 @@
+use std::time::Instant;
+use diesel::connection::{Instrumentation, InstrumentationEvent};
+use diesel::r2d2::event::{CheckoutEvent, TimeoutEvent};
+use diesel::r2d2::{CustomizeConnection, HandleEvent};
+use diesel::{Connection, PgConnection};
+
+use crate::metrics::metrics;
+
+/// Event handler del pool r2d2: registra el tiempo de espera de cada checkout
+#[derive(Debug)]
+pub struct PoolMetrics;
+
+impl HandleEvent for PoolMetrics {
+    fn handle_checkout(&self, event: CheckoutEvent) {
+        metrics().db_pool_wait.observe(event.duration().as_secs_f64());
+    }
+
+    fn handle_timeout(&self, event: TimeoutEvent) {
+        metrics().db_pool_wait.observe(event.timeout().as_secs_f64());
+        metrics().db_pool_timeouts.inc();
+    }
+}
+
+/// Customizer del pool que instala el cronómetro de consultas en cada conexión nueva
+#[derive(Debug)]
+pub struct QueryMetrics;
+
+impl CustomizeConnection<PgConnection, diesel::r2d2::Error> for QueryMetrics {
+    fn on_acquire(&self, conn: &mut PgConnection) -> Result<(), diesel::r2d2::Error> {
+        conn.set_instrumentation(QueryTimer::default());
+        Ok(())
+    }
+}
+
+/// Mide la duración de cada consulta ejecutada sobre una conexión
+#[derive(Default)]
+struct QueryTimer {
+    inicio: Option<Instant>,
+}
+
+impl Instrumentation for QueryTimer {
+    fn on_connection_event(&mut self, event: InstrumentationEvent<'_>) {
+        match event {
+            InstrumentationEvent::StartQuery { .. } => {
+                self.inicio = Some(Instant::now());
+            }
+            InstrumentationEvent::FinishQuery { query, error, .. } => {
+                if let Some(inicio) = self.inicio.take() {
+                    let sql = query.to_string();
+                    let resultado = if error.is_some() { "error" } else { "ok" };
+                    metrics()
+                        .db_query_duration
+                        .with_label_values(&[tipo_sentencia(&sql), resultado])
+                        .observe(inicio.elapsed().as_secs_f64());
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Reduce una sentencia SQL a su verbo principal para mantener acotada la cardinalidad
+fn tipo_sentencia(sql: &str) -> &'static str {
+    let verbo = sql
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_ascii_uppercase();
+
+    match verbo.as_str() {
+        "SELECT" | "WITH" => "select",
+        "INSERT" => "insert",
+        "UPDATE" => "update",
+        "DELETE" => "delete",
+        "BEGIN" | "COMMIT" | "ROLLBACK" | "SAVEPOINT" | "RELEASE" => "transaction",
+        _ => "other",
+    }
+}
@@ -0,0 +1,29 @@
+use actix_web::{web, HttpResponse, Result};
+use crate::metrics::metrics;
+use crate::state::app_state::AppState;
+
+/// GET /metrics - Métricas en formato de texto de Prometheus
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "Observabilidad",
+    responses(
+        (status = 200, description = "Métricas HTTP, del pool de conexiones, de consultas SQL y de negocio", body = String, content_type = "text/plain"),
+        (status = 500, description = "Error al serializar las métricas", body = String)
+    )
+)]
+pub async fn exportar_metricas(state: web::Data<AppState>) -> Result<HttpResponse> {
+    let metrics = metrics();
+    metrics.actualizar_pool(&state.pool);
+
+    match metrics.exportar() {
+        Ok(cuerpo) => Ok(HttpResponse::Ok()
+            .content_type("text/plain; version=0.0.4")
+            .body(cuerpo)),
+        Err(e) => Ok(HttpResponse::InternalServerError().body(e.to_string())),
+    }
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.route("/metrics", web::get().to(exportar_metricas));
+}
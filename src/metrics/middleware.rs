@@ -0,0 +1,32 @@
+use std::time::Instant;
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::Error;
+
+use crate::metrics::metrics;
+
+/// Middleware que registra la latencia de cada petición por método, ruta y estado
+pub async fn registrar_latencia(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let inicio = Instant::now();
+    let method = req.method().to_string();
+    // Se usa el patrón de la ruta (p.ej. /v1/ventas/{id}) y no la URL concreta
+    let route = req.match_pattern().unwrap_or_else(|| "unmatched".to_string());
+
+    let resultado = next.call(req).await;
+
+    let status = match &resultado {
+        Ok(res) => res.status(),
+        Err(e) => e.as_response_error().status_code(),
+    };
+
+    metrics()
+        .http_request_duration
+        .with_label_values(&[method.as_str(), route.as_str(), status.as_str()])
+        .observe(inicio.elapsed().as_secs_f64());
+
+    resultado
+}
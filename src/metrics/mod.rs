@@ -0,0 +1,148 @@
+pub mod db;
+pub mod handler;
+pub mod middleware;
+
+use std::sync::LazyLock;
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, Histogram, IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
+    Opts, Registry, TextEncoder,
+};
+
+use crate::state::app_state::DbPool;
+
+/// Buckets for request/query latencies, from 1ms to 10s
+const LATENCY_BUCKETS: &[f64] = &[
+    0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+/// Acceso global a las métricas de la aplicación
+pub fn metrics() -> &'static Metrics {
+    &METRICS
+}
+
+pub struct Metrics {
+    registry: Registry,
+
+    // HTTP
+    pub http_request_duration: HistogramVec,
+    pub peticiones_limitadas: IntCounterVec,
+
+    // Pool de conexiones (r2d2)
+    pub db_pool_connections: IntGaugeVec,
+    pub db_pool_max_size: IntGauge,
+    pub db_pool_wait: Histogram,
+    pub db_pool_timeouts: IntCounter,
+
+    // Consultas a la base de datos
+    pub db_query_duration: HistogramVec,
+
+    // Negocio
+    pub ventas_creadas: IntCounter,
+    pub unidades_vendidas: IntCounterVec,
+    pub movimientos_inventario: IntCounterVec,
+    pub rechazos_stock_insuficiente: IntCounterVec,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new_custom(Some("polimarket".to_string()), None)
+            .expect("invalid metrics registry prefix");
+
+        let http_request_duration = HistogramVec::new(
+            HistogramOpts::new("http_request_duration_seconds", "Latencia de las peticiones HTTP por ruta")
+                .buckets(LATENCY_BUCKETS.to_vec()),
+            &["method", "route", "status"],
+        ).expect("invalid metric definition");
+
+        let peticiones_limitadas = IntCounterVec::new(
+            Opts::new("rate_limited_requests_total", "Peticiones rechazadas por límite de peticiones"),
+            &["politica", "alcance"],
+        ).expect("invalid metric definition");
+
+        let db_pool_connections = IntGaugeVec::new(
+            Opts::new("db_pool_connections", "Conexiones del pool por estado (in_use, idle)"),
+            &["state"],
+        ).expect("invalid metric definition");
+
+        let db_pool_max_size = IntGauge::new("db_pool_max_size", "Tamaño máximo configurado del pool (POOL_MAX_SIZE)")
+            .expect("invalid metric definition");
+
+        let db_pool_wait = Histogram::with_opts(
+            HistogramOpts::new("db_pool_wait_seconds", "Tiempo de espera para obtener una conexión del pool")
+                .buckets(LATENCY_BUCKETS.to_vec()),
+        ).expect("invalid metric definition");
+
+        let db_pool_timeouts = IntCounter::new("db_pool_timeouts_total", "Esperas de conexión que agotaron el timeout")
+            .expect("invalid metric definition");
+
+        let db_query_duration = HistogramVec::new(
+            HistogramOpts::new("db_query_duration_seconds", "Duración de las consultas SQL por tipo de sentencia")
+                .buckets(LATENCY_BUCKETS.to_vec()),
+            &["statement", "result"],
+        ).expect("invalid metric definition");
+
+        let ventas_creadas = IntCounter::new("sales_created_total", "Ventas creadas exitosamente")
+            .expect("invalid metric definition");
+
+        let unidades_vendidas = IntCounterVec::new(
+            Opts::new("units_sold_total", "Unidades vendidas por producto"),
+            &["id_producto"],
+        ).expect("invalid metric definition");
+
+        let movimientos_inventario = IntCounterVec::new(
+            Opts::new("stock_movements_total", "Movimientos de inventario por tipo de movimiento"),
+            &["tipo_movimiento"],
+        ).expect("invalid metric definition");
+
+        let rechazos_stock_insuficiente = IntCounterVec::new(
+            Opts::new("insufficient_stock_rejections_total", "Operaciones rechazadas por stock insuficiente"),
+            &["origen"],
+        ).expect("invalid metric definition");
+
+        registry.register(Box::new(http_request_duration.clone())).expect("duplicate metric");
+        registry.register(Box::new(peticiones_limitadas.clone())).expect("duplicate metric");
+        registry.register(Box::new(db_pool_connections.clone())).expect("duplicate metric");
+        registry.register(Box::new(db_pool_max_size.clone())).expect("duplicate metric");
+        registry.register(Box::new(db_pool_wait.clone())).expect("duplicate metric");
+        registry.register(Box::new(db_pool_timeouts.clone())).expect("duplicate metric");
+        registry.register(Box::new(db_query_duration.clone())).expect("duplicate metric");
+        registry.register(Box::new(ventas_creadas.clone())).expect("duplicate metric");
+        registry.register(Box::new(unidades_vendidas.clone())).expect("duplicate metric");
+        registry.register(Box::new(movimientos_inventario.clone())).expect("duplicate metric");
+        registry.register(Box::new(rechazos_stock_insuficiente.clone())).expect("duplicate metric");
+
+        Metrics {
+            registry,
+            http_request_duration,
+            peticiones_limitadas,
+            db_pool_connections,
+            db_pool_max_size,
+            db_pool_wait,
+            db_pool_timeouts,
+            db_query_duration,
+            ventas_creadas,
+            unidades_vendidas,
+            movimientos_inventario,
+            rechazos_stock_insuficiente,
+        }
+    }
+
+    /// Actualiza los gauges del pool con su estado actual
+    pub fn actualizar_pool(&self, pool: &DbPool) {
+        let estado = pool.state();
+        let en_uso = estado.connections.saturating_sub(estado.idle_connections);
+
+        self.db_pool_connections.with_label_values(&["in_use"]).set(en_uso as i64);
+        self.db_pool_connections.with_label_values(&["idle"]).set(estado.idle_connections as i64);
+        self.db_pool_max_size.set(pool.max_size() as i64);
+    }
+
+    /// Serializa todas las métricas en el formato de texto de Prometheus
+    pub fn exportar(&self) -> Result<String, prometheus::Error> {
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+        String::from_utf8(buffer).map_err(|e| prometheus::Error::Msg(e.to_string()))
+    }
+}
@@ -0,0 +1,58 @@
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::{Error, HttpMessage};
+use diesel::prelude::*;
+use diesel::sql_types::Text;
+use uuid::Uuid;
+use crate::security::PERSONA_ID_HEADER;
+use crate::telemetry::request_id::RequestId;
+
+/// Quién origina los cambios de la petición en curso
+#[derive(Debug, Clone, Default)]
+pub struct ContextoAuditoria {
+    pub id_actor: Option<Uuid>,
+    pub request_id: Option<String>,
+}
+
+tokio::task_local! {
+    static CONTEXTO: ContextoAuditoria;
+}
+
+/// Contexto de la petición en curso, si la hay
+pub fn actual() -> Option<ContextoAuditoria> {
+    CONTEXTO.try_with(Clone::clone).ok()
+}
+
+/// Traslada el contexto a la transacción actual para que lo lean los triggers de auditoría.
+///
+/// Debe llamarse dentro de la transacción: `set_config(..., true)` sólo dura hasta su fin.
+pub fn aplicar(conn: &mut PgConnection) -> QueryResult<()> {
+    let contexto = actual().unwrap_or_default();
+
+    diesel::sql_query(
+        "SELECT set_config('polimarket.actor', $1, true), set_config('polimarket.request_id', $2, true)"
+    )
+    .bind::<Text, _>(contexto.id_actor.map(|id| id.to_string()).unwrap_or_default())
+    .bind::<Text, _>(contexto.request_id.unwrap_or_default())
+    .execute(conn)?;
+
+    Ok(())
+}
+
+/// Middleware que fija el contexto de auditoría (actor `X-Persona-Id` y request id) de cada petición
+pub async fn establecer_contexto(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let contexto = ContextoAuditoria {
+        id_actor: req
+            .headers()
+            .get(PERSONA_ID_HEADER)
+            .and_then(|valor| valor.to_str().ok())
+            .and_then(|valor| Uuid::parse_str(valor.trim()).ok()),
+        request_id: req.extensions().get::<RequestId>().map(|id| id.0.clone()),
+    };
+
+    CONTEXTO.scope(contexto, next.call(req)).await
+}
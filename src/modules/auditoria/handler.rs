@@ -0,0 +1,37 @@
+use actix_web::{web, HttpResponse, ResponseError, Result};
+use crate::modules::auditoria::model::{AuditoriaQueryParams, AuditoriaResponse};
+use crate::modules::common::errors::ErrorResponse;
+use crate::state::app_state::AppState;
+
+/// GET /v1/auditoria - Consultar el registro de auditoría
+#[utoipa::path(
+    get,
+    path = "/v1/auditoria",
+    tag = "Auditoria",
+    params(
+        AuditoriaQueryParams
+    ),
+    responses(
+        (status = 200, description = "Cambios registrados, del más reciente al más antiguo", body = Vec<AuditoriaResponse>),
+        (status = 400, description = "Filtros inválidos", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn listar_auditoria(
+    state: web::Data<AppState>,
+    query: web::Query<AuditoriaQueryParams>,
+) -> Result<HttpResponse> {
+    let service = &state.auditoria_service;
+
+    match service.listar(query.into_inner()) {
+        Ok(registros) => Ok(HttpResponse::Ok().json(registros)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/auditoria")
+            .route("", web::get().to(listar_auditoria))
+    );
+}
@@ -0,0 +1,5 @@
+pub mod contexto;
+pub mod model;
+pub mod repository;
+pub mod service;
+pub mod handler;
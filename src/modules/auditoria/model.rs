@@ -0,0 +1,116 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use utoipa::{IntoParams, ToSchema};
+use crate::modules::common::types::AccionAuditoria;
+use crate::schema::auditoria;
+
+/// Entidades auditadas (nombre de la tabla)
+pub const ENTIDADES_AUDITADAS: &[&str] = &[
+    "personas",
+    "productos",
+    "inventarios",
+    "detalle_inventarios",
+    "ventas",
+    "detalle_ventas",
+    "categorias",
+    "marcas",
+    "codigos_barra",
+    "unidades_producto",
+    "lotes",
+    "detalle_ventas_lotes",
+    "numeros_serie",
+    "detalle_ventas_series",
+    "capas_costo",
+    "reglas_comision",
+    "pagos",
+    "sesiones_caja",
+    "listas_precios",
+    "precios_lista",
+    "promociones",
+    "cotizaciones",
+    "detalle_cotizaciones",
+    "pedidos",
+    "detalle_pedidos",
+];
+
+// Domain Model (Database Entity)
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = auditoria)]
+pub struct RegistroAuditoria {
+    pub id: Uuid,
+    pub fecha: NaiveDateTime,
+    pub id_actor: Option<Uuid>,
+    pub accion: AccionAuditoria,
+    pub entidad: String,
+    pub id_entidad: Uuid,
+    pub datos_anteriores: Option<serde_json::Value>,
+    pub datos_nuevos: Option<serde_json::Value>,
+    pub cambios: Option<serde_json::Value>,
+    pub request_id: Option<String>,
+}
+
+// DTO for API Response
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct AuditoriaResponse {
+    #[schema(example = "9b2f6c1e-3a4d-4e5f-8a9b-0c1d2e3f4a5b")]
+    pub id: String,
+    #[schema(example = "2025-11-16T10:30:00")]
+    pub fecha: String,
+    /// Persona que originó el cambio (cabecera `X-Persona-Id`); null si no se indicó
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000")]
+    pub id_actor: Option<String>,
+    pub accion: AccionAuditoria,
+    #[schema(example = "inventarios")]
+    pub entidad: String,
+    #[schema(example = "660e8400-e29b-41d4-a716-446655440000")]
+    pub id_entidad: String,
+    #[schema(value_type = Option<Object>)]
+    pub datos_anteriores: Option<serde_json::Value>,
+    #[schema(value_type = Option<Object>)]
+    pub datos_nuevos: Option<serde_json::Value>,
+    /// Campos modificados: `{"campo": {"antes": ..., "despues": ...}}`
+    #[schema(value_type = Option<Object>, example = json!({"cantidad_disponible": {"antes": 45, "despues": 40}}))]
+    pub cambios: Option<serde_json::Value>,
+    #[schema(example = "3f2c8a9e-1b7d-4c6a-9e2f-5d4b3a2c1e0f")]
+    pub request_id: Option<String>,
+}
+
+impl From<RegistroAuditoria> for AuditoriaResponse {
+    fn from(registro: RegistroAuditoria) -> Self {
+        AuditoriaResponse {
+            id: registro.id.to_string(),
+            fecha: registro.fecha.format("%Y-%m-%dT%H:%M:%S%.3f").to_string(),
+            id_actor: registro.id_actor.map(|id| id.to_string()),
+            accion: registro.accion,
+            entidad: registro.entidad,
+            id_entidad: registro.id_entidad.to_string(),
+            datos_anteriores: registro.datos_anteriores,
+            datos_nuevos: registro.datos_nuevos,
+            cambios: registro.cambios,
+            request_id: registro.request_id,
+        }
+    }
+}
+
+// Query parameters for filtering the audit log
+#[derive(Debug, Deserialize, ToSchema, IntoParams)]
+pub struct AuditoriaQueryParams {
+    /// personas, productos, inventarios, detalle_inventarios, ventas o detalle_ventas
+    #[schema(example = "inventarios")]
+    pub entidad: Option<String>,
+    #[schema(example = "660e8400-e29b-41d4-a716-446655440000")]
+    pub id_entidad: Option<String>,
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000")]
+    pub id_actor: Option<String>,
+    /// `YYYY-MM-DD` o `YYYY-MM-DD HH:MM:SS`
+    #[schema(example = "2025-11-01")]
+    pub fecha_desde: Option<String>,
+    /// `YYYY-MM-DD` (día completo) o `YYYY-MM-DD HH:MM:SS`
+    #[schema(example = "2025-11-30")]
+    pub fecha_hasta: Option<String>,
+    /// Máximo de registros (por defecto 100, máximo 1000)
+    #[schema(example = 100)]
+    pub limite: Option<i64>,
+}
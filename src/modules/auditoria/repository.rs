@@ -0,0 +1,72 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use diesel::r2d2::{ConnectionManager, Pool, PooledConnection};
+use uuid::Uuid;
+use tracing::instrument;
+use crate::modules::auditoria::model::RegistroAuditoria;
+use crate::modules::common::errors::{ApiError, ApiResult};
+use crate::schema::auditoria;
+
+pub type DbPool = Pool<ConnectionManager<PgConnection>>;
+pub type DbConnection = PooledConnection<ConnectionManager<PgConnection>>;
+
+/// Filtros de consulta del registro de auditoría
+#[derive(Debug, Default)]
+pub struct FiltroAuditoria {
+    pub entidad: Option<String>,
+    pub id_entidad: Option<Uuid>,
+    pub id_actor: Option<Uuid>,
+    pub fecha_desde: Option<NaiveDateTime>,
+    pub fecha_hasta: Option<NaiveDateTime>,
+    pub limite: i64,
+}
+
+pub struct AuditoriaRepository {
+    pool: DbPool,
+}
+
+impl AuditoriaRepository {
+    pub fn new(pool: DbPool) -> Self {
+        AuditoriaRepository { pool }
+    }
+
+    fn get_connection(&self) -> ApiResult<DbConnection> {
+        self.pool
+            .get()
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+    }
+
+    #[instrument(skip(self))]
+    pub fn listar(&self, filtro: FiltroAuditoria) -> ApiResult<Vec<RegistroAuditoria>> {
+        let mut conn = self.get_connection()?;
+
+        let mut query = auditoria::table.into_boxed();
+
+        if let Some(entidad) = filtro.entidad {
+            query = query.filter(auditoria::entidad.eq(entidad));
+        }
+
+        if let Some(id_entidad) = filtro.id_entidad {
+            query = query.filter(auditoria::id_entidad.eq(id_entidad));
+        }
+
+        if let Some(id_actor) = filtro.id_actor {
+            query = query.filter(auditoria::id_actor.eq(id_actor));
+        }
+
+        if let Some(desde) = filtro.fecha_desde {
+            query = query.filter(auditoria::fecha.ge(desde));
+        }
+
+        if let Some(hasta) = filtro.fecha_hasta {
+            query = query.filter(auditoria::fecha.le(hasta));
+        }
+
+        query
+            .order(auditoria::fecha.desc())
+            .limit(filtro.limite)
+            .select(RegistroAuditoria::as_select())
+            .load(&mut conn)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+    }
+}
@@ -0,0 +1,83 @@
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use uuid::Uuid;
+use tracing::instrument;
+use crate::modules::auditoria::model::{AuditoriaQueryParams, AuditoriaResponse, ENTIDADES_AUDITADAS};
+use crate::modules::auditoria::repository::{AuditoriaRepository, FiltroAuditoria};
+use crate::modules::common::errors::{ApiError, ApiResult};
+
+const LIMITE_POR_DEFECTO: i64 = 100;
+const LIMITE_MAXIMO: i64 = 1000;
+
+pub struct AuditoriaService {
+    repository: AuditoriaRepository,
+}
+
+impl AuditoriaService {
+    pub fn new(repository: AuditoriaRepository) -> Self {
+        AuditoriaService { repository }
+    }
+
+    /// Consultar el registro de auditoría, del cambio más reciente al más antiguo
+    #[instrument(skip(self))]
+    pub fn listar(&self, query: AuditoriaQueryParams) -> ApiResult<Vec<AuditoriaResponse>> {
+        let entidad = match query.entidad {
+            Some(entidad) => {
+                let entidad = entidad.trim().to_lowercase();
+                if !ENTIDADES_AUDITADAS.contains(&entidad.as_str()) {
+                    return Err(ApiError::InvalidInput(format!(
+                        "Entidad inválida. Valores permitidos: {}",
+                        ENTIDADES_AUDITADAS.join(", ")
+                    )));
+                }
+                Some(entidad)
+            }
+            None => None,
+        };
+
+        let id_entidad = parse_uuid(query.id_entidad, "ID de entidad inválido")?;
+        let id_actor = parse_uuid(query.id_actor, "ID de actor inválido")?;
+        let fecha_desde = parse_fecha(query.fecha_desde, NaiveTime::MIN)?;
+        let fecha_hasta = parse_fecha(
+            query.fecha_hasta,
+            NaiveTime::from_hms_milli_opt(23, 59, 59, 999).unwrap_or(NaiveTime::MIN),
+        )?;
+
+        let limite = query.limite.unwrap_or(LIMITE_POR_DEFECTO);
+        if !(1..=LIMITE_MAXIMO).contains(&limite) {
+            return Err(ApiError::InvalidInput(format!(
+                "El límite debe estar entre 1 y {}", LIMITE_MAXIMO
+            )));
+        }
+
+        let registros = self.repository.listar(FiltroAuditoria {
+            entidad,
+            id_entidad,
+            id_actor,
+            fecha_desde,
+            fecha_hasta,
+            limite,
+        })?;
+
+        Ok(registros.into_iter().map(AuditoriaResponse::from).collect())
+    }
+}
+
+fn parse_uuid(valor: Option<String>, mensaje: &str) -> ApiResult<Option<Uuid>> {
+    valor
+        .map(|id| Uuid::parse_str(id.trim()).map_err(|_| ApiError::InvalidInput(mensaje.to_string())))
+        .transpose()
+}
+
+/// Acepta `YYYY-MM-DD HH:MM:SS` o sólo la fecha, completada con `hora_por_defecto`
+fn parse_fecha(valor: Option<String>, hora_por_defecto: NaiveTime) -> ApiResult<Option<NaiveDateTime>> {
+    let Some(valor) = valor else {
+        return Ok(None);
+    };
+
+    NaiveDateTime::parse_from_str(valor.trim(), "%Y-%m-%d %H:%M:%S")
+        .or_else(|_| NaiveDate::parse_from_str(valor.trim(), "%Y-%m-%d").map(|fecha| fecha.and_time(hora_por_defecto)))
+        .map(Some)
+        .map_err(|_| ApiError::InvalidInput(
+            "Formato de fecha inválido. Use YYYY-MM-DD o YYYY-MM-DD HH:MM:SS".to_string()
+        ))
+}
@@ -0,0 +1,29 @@
+//! Arqueo de una sesión de caja.
+//!
+//! En la caja debería haber el efectivo con que se abrió más lo cobrado en efectivo durante la
+//! sesión. El monto de un pago ya descuenta el cambio entregado, así que basta con sumarlos. Los
+//! pagos de ventas anuladas se reembolsan y no cuentan.
+
+use bigdecimal::BigDecimal;
+use crate::modules::cajas::model::{ResumenSesion, TotalMetodoPago};
+use crate::modules::common::types::MetodoPago;
+
+/// Apertura más lo cobrado en efectivo
+pub fn efectivo_esperado(monto_apertura: &BigDecimal, resumen: &ResumenSesion) -> BigDecimal {
+    monto_apertura
+        + resumen
+            .pagos
+            .iter()
+            .filter(|total| total.metodo == MetodoPago::Efectivo)
+            .map(|total| &total.monto)
+            .sum::<BigDecimal>()
+}
+
+/// Contado menos esperado: negativo si falta efectivo, positivo si sobra
+pub fn diferencia(efectivo_contado: &BigDecimal, efectivo_esperado: &BigDecimal) -> BigDecimal {
+    efectivo_contado - efectivo_esperado
+}
+
+pub fn cambio_entregado(pagos: &[TotalMetodoPago]) -> BigDecimal {
+    pagos.iter().map(|total| &total.cambio).sum()
+}
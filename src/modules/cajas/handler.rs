@@ -0,0 +1,182 @@
+use actix_web::{web, HttpResponse, ResponseError, Result};
+use crate::modules::cajas::model::{
+    AbrirSesionCajaRequest, CerrarSesionCajaRequest, ReporteCajaResponse, SesionCajaResponse, SesionesCajaQuery,
+};
+use crate::modules::common::errors::ErrorResponse;
+use crate::state::app_state::AppState;
+
+/// POST /v1/cajas/sesiones - Abrir una sesión de caja
+#[utoipa::path(
+    post,
+    path = "/v1/cajas/sesiones",
+    tag = "Cajas",
+    request_body = AbrirSesionCajaRequest,
+    responses(
+        (status = 201, description = "Sesión abierta", body = SesionCajaResponse),
+        (status = 400, description = "Datos inválidos, el operador no es vendedor o la terminal ya tiene una sesión abierta", body = ErrorResponse),
+        (status = 404, description = "Operador no encontrado", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn abrir_sesion(
+    state: web::Data<AppState>,
+    body: web::Json<AbrirSesionCajaRequest>,
+) -> Result<HttpResponse> {
+    let service = &state.caja_service;
+
+    match service.abrir_sesion(body.into_inner()) {
+        Ok(response) => Ok(HttpResponse::Created().json(response)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// GET /v1/cajas/sesiones - Listar sesiones de caja
+#[utoipa::path(
+    get,
+    path = "/v1/cajas/sesiones",
+    tag = "Cajas",
+    params(SesionesCajaQuery),
+    responses(
+        (status = 200, description = "Sesiones de caja, de la más reciente a la más antigua", body = Vec<SesionCajaResponse>),
+        (status = 400, description = "Filtro inválido", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn listar_sesiones(
+    state: web::Data<AppState>,
+    query: web::Query<SesionesCajaQuery>,
+) -> Result<HttpResponse> {
+    let service = &state.caja_service;
+    let query = query.into_inner();
+
+    match service.listar_sesiones(query.sucursal, query.estado) {
+        Ok(sesiones) => Ok(HttpResponse::Ok().json(sesiones)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// GET /v1/cajas/sesiones/:id - Obtener una sesión de caja
+#[utoipa::path(
+    get,
+    path = "/v1/cajas/sesiones/{id}",
+    tag = "Cajas",
+    params(
+        ("id" = String, Path, description = "ID de la sesión (UUID)")
+    ),
+    responses(
+        (status = 200, description = "Sesión de caja", body = SesionCajaResponse),
+        (status = 400, description = "ID inválido", body = ErrorResponse),
+        (status = 404, description = "Sesión no encontrada", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn obtener_sesion(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    let service = &state.caja_service;
+
+    match service.obtener_sesion(&id) {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// GET /v1/cajas/sesiones/:id/reporte-x - Reporte X de una sesión abierta
+#[utoipa::path(
+    get,
+    path = "/v1/cajas/sesiones/{id}/reporte-x",
+    tag = "Cajas",
+    params(
+        ("id" = String, Path, description = "ID de la sesión (UUID)")
+    ),
+    responses(
+        (status = 200, description = "Ventas, pagos por método y efectivo esperado hasta ahora", body = ReporteCajaResponse),
+        (status = 400, description = "ID inválido o sesión cerrada", body = ErrorResponse),
+        (status = 404, description = "Sesión no encontrada", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn reporte_x(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    let service = &state.caja_service;
+
+    match service.reporte_x(&id) {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// POST /v1/cajas/sesiones/:id/cierre - Cerrar una sesión de caja con el efectivo contado
+#[utoipa::path(
+    post,
+    path = "/v1/cajas/sesiones/{id}/cierre",
+    tag = "Cajas",
+    params(
+        ("id" = String, Path, description = "ID de la sesión (UUID)")
+    ),
+    request_body = CerrarSesionCajaRequest,
+    responses(
+        (status = 200, description = "Sesión cerrada; reporte Z con el arqueo", body = ReporteCajaResponse),
+        (status = 400, description = "Datos inválidos o sesión ya cerrada", body = ErrorResponse),
+        (status = 404, description = "Sesión no encontrada", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn cerrar_sesion(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<CerrarSesionCajaRequest>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    let service = &state.caja_service;
+
+    match service.cerrar_sesion(&id, body.into_inner()) {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// GET /v1/cajas/sesiones/:id/reporte-z - Reporte Z de una sesión cerrada
+#[utoipa::path(
+    get,
+    path = "/v1/cajas/sesiones/{id}/reporte-z",
+    tag = "Cajas",
+    params(
+        ("id" = String, Path, description = "ID de la sesión (UUID)")
+    ),
+    responses(
+        (status = 200, description = "Reporte de cierre con el arqueo de la sesión", body = ReporteCajaResponse),
+        (status = 400, description = "ID inválido o sesión abierta", body = ErrorResponse),
+        (status = 404, description = "Sesión no encontrada", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn reporte_z(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    let service = &state.caja_service;
+
+    match service.reporte_z(&id) {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/cajas")
+            .route("/sesiones", web::post().to(abrir_sesion))
+            .route("/sesiones", web::get().to(listar_sesiones))
+            .route("/sesiones/{id}", web::get().to(obtener_sesion))
+            .route("/sesiones/{id}/reporte-x", web::get().to(reporte_x))
+            .route("/sesiones/{id}/cierre", web::post().to(cerrar_sesion))
+            .route("/sesiones/{id}/reporte-z", web::get().to(reporte_z))
+    );
+}
@@ -0,0 +1,162 @@
+use std::cmp::Reverse;
+use bigdecimal::BigDecimal;
+use chrono::Utc;
+use uuid::Uuid;
+use crate::modules::cajas::arqueo;
+use crate::modules::cajas::model::{NuevaSesionCaja, ResumenSesion, SesionCaja, TotalCierreCaja, TotalMetodoPago};
+use crate::modules::cajas::repository::{self as caja_repo, CajaRepository};
+use crate::modules::common::errors::ApiResult;
+use crate::modules::common::memoria::{MemoriaRepository, Tablas};
+use crate::modules::common::types::{EstadoSesionCaja, EstadoVenta, MetodoPago};
+use crate::modules::ventas::model::Pago;
+
+impl Tablas {
+    pub(crate) fn sesion_caja(&self, id: Uuid) -> ApiResult<&SesionCaja> {
+        self.sesiones_caja
+            .iter()
+            .find(|sesion| sesion.id == id && sesion.activo)
+            .ok_or_else(|| caja_repo::sesion_no_encontrada(id))
+    }
+
+    fn resumen_sesion(&self, id: Uuid) -> ResumenSesion {
+        let mut resumen = ResumenSesion::default();
+        for venta in self.ventas.iter().filter(|venta| venta.id_sesion_caja == Some(id) && venta.activo) {
+            if venta.estado == EstadoVenta::Anulada {
+                resumen.ventas_anuladas += 1;
+            } else {
+                resumen.ventas += 1;
+                resumen.total_vendido += &venta.monto;
+            }
+        }
+        // En el orden de metodo_pago, como el ORDER BY de PostgreSQL
+        for metodo in [MetodoPago::Efectivo, MetodoPago::Tarjeta, MetodoPago::Transferencia] {
+            let cobrados: Vec<&Pago> = self
+                .pagos
+                .iter()
+                .filter(|pago| pago.id_sesion_caja == Some(id) && pago.activo && pago.metodo == metodo)
+                .collect();
+            if !cobrados.is_empty() {
+                resumen.pagos.push(TotalMetodoPago {
+                    metodo,
+                    pagos: cobrados.len() as i64,
+                    monto: cobrados.iter().map(|pago| &pago.monto).sum(),
+                    cambio: cobrados.iter().map(|pago| &pago.cambio).sum(),
+                });
+            }
+        }
+        resumen
+    }
+}
+
+impl CajaRepository for MemoriaRepository {
+    fn abrir_sesion(&self, nueva_sesion: NuevaSesionCaja) -> ApiResult<Uuid> {
+        self.transaccion(|tablas| {
+            // Misma regla que idx_sesiones_caja_terminal_abierta
+            if tablas.sesiones_caja.iter().any(|sesion| {
+                sesion.activo
+                    && sesion.estado == EstadoSesionCaja::Abierta
+                    && sesion.sucursal == nueva_sesion.sucursal
+                    && sesion.terminal == nueva_sesion.terminal
+            }) {
+                return Err(caja_repo::terminal_con_sesion_abierta(&nueva_sesion.sucursal, &nueva_sesion.terminal));
+            }
+
+            let ahora = Utc::now().naive_utc();
+            let id = Uuid::new_v4();
+            tablas.sesiones_caja.push(SesionCaja {
+                id,
+                sucursal: nueva_sesion.sucursal,
+                terminal: nueva_sesion.terminal,
+                id_operador: nueva_sesion.id_operador,
+                monto_apertura: nueva_sesion.monto_apertura,
+                fecha_apertura: ahora,
+                estado: EstadoSesionCaja::Abierta,
+                fecha_cierre: None,
+                efectivo_esperado: None,
+                efectivo_contado: None,
+                diferencia: None,
+                observaciones: None,
+                fecha_creacion: ahora,
+                fecha_actualizacion: ahora,
+                activo: true,
+                ventas: None,
+                ventas_anuladas: None,
+                total_vendido: None,
+            });
+            Ok(id)
+        })
+    }
+
+    fn buscar_sesion(&self, id: Uuid) -> ApiResult<SesionCaja> {
+        self.tablas().sesion_caja(id).cloned()
+    }
+
+    fn listar_sesiones(&self, sucursal: Option<String>, estado: Option<EstadoSesionCaja>) -> ApiResult<Vec<SesionCaja>> {
+        let mut sesiones: Vec<SesionCaja> = self
+            .tablas()
+            .sesiones_caja
+            .iter()
+            .filter(|sesion| sesion.activo)
+            .filter(|sesion| sucursal.as_ref().is_none_or(|sucursal| &sesion.sucursal == sucursal))
+            .filter(|sesion| estado.is_none_or(|estado| sesion.estado == estado))
+            .cloned()
+            .collect();
+        sesiones.sort_by_key(|sesion| Reverse(sesion.fecha_apertura));
+        Ok(sesiones)
+    }
+
+    fn resumen(&self, id: Uuid) -> ApiResult<ResumenSesion> {
+        Ok(self.tablas().resumen_sesion(id))
+    }
+
+    fn resumen_cierre(&self, id: Uuid) -> ApiResult<ResumenSesion> {
+        let tablas = self.tablas();
+        let sesion = tablas.sesion_caja(id)?.clone();
+        let totales = tablas
+            .totales_cierre_caja
+            .iter()
+            .filter(|total| total.id_sesion_caja == id && total.activo)
+            .cloned()
+            .collect();
+        caja_repo::resumen_guardado(sesion, totales)
+    }
+
+    fn cerrar_sesion(&self, id: Uuid, efectivo_contado: BigDecimal, observaciones: Option<String>) -> ApiResult<SesionCaja> {
+        self.transaccion(|tablas| {
+            let resumen = tablas.resumen_sesion(id);
+            let sesion = tablas
+                .sesiones_caja
+                .iter_mut()
+                .find(|sesion| sesion.id == id && sesion.activo)
+                .ok_or_else(|| caja_repo::sesion_no_encontrada(id))?;
+            caja_repo::validar_abierta(sesion)?;
+
+            let ahora = Utc::now().naive_utc();
+            let esperado = arqueo::efectivo_esperado(&sesion.monto_apertura, &resumen);
+            sesion.estado = EstadoSesionCaja::Cerrada;
+            sesion.fecha_cierre = Some(ahora);
+            sesion.diferencia = Some(arqueo::diferencia(&efectivo_contado, &esperado));
+            sesion.efectivo_esperado = Some(esperado);
+            sesion.efectivo_contado = Some(efectivo_contado);
+            sesion.observaciones = observaciones;
+            sesion.ventas = Some(resumen.ventas);
+            sesion.ventas_anuladas = Some(resumen.ventas_anuladas);
+            sesion.total_vendido = Some(resumen.total_vendido);
+            sesion.fecha_actualizacion = ahora;
+            let cerrada = sesion.clone();
+
+            tablas.totales_cierre_caja.extend(resumen.pagos.into_iter().map(|total| TotalCierreCaja {
+                id: Uuid::new_v4(),
+                id_sesion_caja: id,
+                metodo: total.metodo,
+                pagos: total.pagos,
+                monto: total.monto,
+                cambio: total.cambio,
+                fecha_creacion: ahora,
+                fecha_actualizacion: ahora,
+                activo: true,
+            }));
+            Ok(cerrada)
+        })
+    }
+}
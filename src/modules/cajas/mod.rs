@@ -0,0 +1,6 @@
+pub mod model;
+pub mod arqueo;
+pub mod repository;
+pub mod service;
+pub mod handler;
+mod memoria;
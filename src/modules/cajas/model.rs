@@ -0,0 +1,219 @@
+use bigdecimal::{BigDecimal, ToPrimitive};
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use utoipa::{IntoParams, ToSchema};
+use crate::modules::common::types::{EstadoSesionCaja, MetodoPago};
+use crate::schema::{sesiones_caja, totales_cierre_caja};
+
+// Domain Model for SesionCaja
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = sesiones_caja)]
+pub struct SesionCaja {
+    pub id: Uuid,
+    pub sucursal: String,
+    pub terminal: String,
+    /// Persona con perfil VENDEDOR que atiende la caja
+    pub id_operador: Uuid,
+    /// Efectivo con que se abrió la caja
+    pub monto_apertura: BigDecimal,
+    pub fecha_apertura: NaiveDateTime,
+    pub estado: EstadoSesionCaja,
+    pub fecha_cierre: Option<NaiveDateTime>,
+    /// Arqueo, fijado al cerrar la sesión
+    pub efectivo_esperado: Option<BigDecimal>,
+    pub efectivo_contado: Option<BigDecimal>,
+    /// Contado menos esperado: negativo si falta efectivo
+    pub diferencia: Option<BigDecimal>,
+    pub observaciones: Option<String>,
+    pub fecha_creacion: NaiveDateTime,
+    pub fecha_actualizacion: NaiveDateTime,
+    pub activo: bool,
+    /// Resumen del reporte Z, fijado al cerrar la sesión
+    pub ventas: Option<i64>,
+    pub ventas_anuladas: Option<i64>,
+    pub total_vendido: Option<BigDecimal>,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = sesiones_caja)]
+pub struct NuevaSesionCaja {
+    pub sucursal: String,
+    pub terminal: String,
+    pub id_operador: Uuid,
+    pub monto_apertura: BigDecimal,
+}
+
+// Sales and payments registered in a cash session
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ResumenSesion {
+    /// Ventas vigentes (sin contar las anuladas)
+    pub ventas: i64,
+    pub ventas_anuladas: i64,
+    pub total_vendido: BigDecimal,
+    /// Pagos vigentes cobrados en la sesión, por método
+    pub pagos: Vec<TotalMetodoPago>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TotalMetodoPago {
+    pub metodo: MetodoPago,
+    pub pagos: i64,
+    pub monto: BigDecimal,
+    pub cambio: BigDecimal,
+}
+
+// Domain Model for the payments of a closed cash session, by method
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable, Associations)]
+#[diesel(belongs_to(SesionCaja, foreign_key = id_sesion_caja))]
+#[diesel(table_name = totales_cierre_caja)]
+pub struct TotalCierreCaja {
+    pub id: Uuid,
+    pub id_sesion_caja: Uuid,
+    pub metodo: MetodoPago,
+    pub pagos: i64,
+    pub monto: BigDecimal,
+    pub cambio: BigDecimal,
+    pub fecha_creacion: NaiveDateTime,
+    pub fecha_actualizacion: NaiveDateTime,
+    pub activo: bool,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = totales_cierre_caja)]
+pub struct NuevoTotalCierreCaja {
+    pub id_sesion_caja: Uuid,
+    pub metodo: MetodoPago,
+    pub pagos: i64,
+    pub monto: BigDecimal,
+    pub cambio: BigDecimal,
+}
+
+// DTO for opening a cash session
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AbrirSesionCajaRequest {
+    #[schema(example = "Bogotá Centro")]
+    pub sucursal: String,
+    #[schema(example = "CAJA-01")]
+    pub terminal: String,
+    /// Persona con perfil VENDEDOR que atiende la caja
+    #[schema(example = "440e8400-e29b-41d4-a716-446655440000")]
+    pub id_operador: String,
+    /// Efectivo inicial de la caja
+    #[schema(example = 200000.0)]
+    pub monto_apertura: f64,
+}
+
+// DTO for closing a cash session
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CerrarSesionCajaRequest {
+    /// Efectivo contado en la caja al cerrar
+    #[schema(example = 1450000.0)]
+    pub efectivo_contado: f64,
+    #[schema(example = "Faltante por cambio mal entregado")]
+    pub observaciones: Option<String>,
+}
+
+// Query parameters for listing cash sessions
+#[derive(Debug, Deserialize, IntoParams, ToSchema)]
+pub struct SesionesCajaQuery {
+    #[schema(example = "Bogotá Centro")]
+    pub sucursal: Option<String>,
+    /// ABIERTA o CERRADA
+    #[schema(example = "ABIERTA")]
+    pub estado: Option<String>,
+}
+
+// DTO for cash session response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SesionCajaResponse {
+    #[schema(example = "bb0e8400-e29b-41d4-a716-446655440000")]
+    pub id: String,
+    #[schema(example = "Bogotá Centro")]
+    pub sucursal: String,
+    #[schema(example = "CAJA-01")]
+    pub terminal: String,
+    #[schema(example = "440e8400-e29b-41d4-a716-446655440000")]
+    pub id_operador: String,
+    #[schema(example = 200000.0)]
+    pub monto_apertura: f64,
+    #[schema(example = "2025-11-17 08:00:00")]
+    pub fecha_apertura: String,
+    pub estado: EstadoSesionCaja,
+    #[schema(example = "2025-11-17 18:05:00")]
+    pub fecha_cierre: Option<String>,
+    #[schema(example = 1452500.0)]
+    pub efectivo_esperado: Option<f64>,
+    #[schema(example = 1450000.0)]
+    pub efectivo_contado: Option<f64>,
+    #[schema(example = -2500.0)]
+    pub diferencia: Option<f64>,
+    pub observaciones: Option<String>,
+}
+
+impl From<SesionCaja> for SesionCajaResponse {
+    fn from(sesion: SesionCaja) -> Self {
+        let importe = |valor: Option<BigDecimal>| valor.map(|valor| valor.to_f64().unwrap_or(0.0));
+        SesionCajaResponse {
+            id: sesion.id.to_string(),
+            sucursal: sesion.sucursal,
+            terminal: sesion.terminal,
+            id_operador: sesion.id_operador.to_string(),
+            monto_apertura: sesion.monto_apertura.to_f64().unwrap_or(0.0),
+            fecha_apertura: sesion.fecha_apertura.format("%Y-%m-%d %H:%M:%S").to_string(),
+            estado: sesion.estado,
+            fecha_cierre: sesion.fecha_cierre.map(|fecha| fecha.format("%Y-%m-%d %H:%M:%S").to_string()),
+            efectivo_esperado: importe(sesion.efectivo_esperado),
+            efectivo_contado: importe(sesion.efectivo_contado),
+            diferencia: importe(sesion.diferencia),
+            observaciones: sesion.observaciones,
+        }
+    }
+}
+
+/// X: lectura parcial de una sesión abierta. Z: cierre de la sesión, con su arqueo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+pub enum TipoReporteCaja {
+    X,
+    Z,
+}
+
+// DTO for X and Z cash reports
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReporteCajaResponse {
+    pub tipo: TipoReporteCaja,
+    #[schema(example = "2025-11-17 18:05:00")]
+    pub fecha_generacion: String,
+    pub sesion: SesionCajaResponse,
+    /// Ventas vigentes registradas en la sesión
+    #[schema(example = 42)]
+    pub ventas: i64,
+    #[schema(example = 1)]
+    pub ventas_anuladas: i64,
+    #[schema(example = 3870000.0)]
+    pub total_vendido: f64,
+    /// Cobrado en la sesión por cada método de pago
+    pub pagos: Vec<TotalMetodoPagoResponse>,
+    #[schema(example = 37500.0)]
+    pub cambio_entregado: f64,
+    /// Apertura más lo cobrado en efectivo (ya descontado el cambio)
+    #[schema(example = 1452500.0)]
+    pub efectivo_esperado: f64,
+    /// Solo en el reporte Z
+    #[schema(example = 1450000.0)]
+    pub efectivo_contado: Option<f64>,
+    #[schema(example = -2500.0)]
+    pub diferencia: Option<f64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TotalMetodoPagoResponse {
+    pub metodo: MetodoPago,
+    #[schema(example = 30)]
+    pub pagos: i64,
+    #[schema(example = 1252500.0)]
+    pub monto: f64,
+    #[schema(example = 37500.0)]
+    pub cambio: f64,
+}
@@ -0,0 +1,307 @@
+use bigdecimal::BigDecimal;
+use chrono::Utc;
+use diesel::dsl::{count_star, exists};
+use diesel::prelude::*;
+use diesel::r2d2::{ConnectionManager, Pool, PooledConnection};
+use uuid::Uuid;
+use tracing::instrument;
+use crate::modules::auditoria;
+use crate::modules::cajas::arqueo;
+use crate::modules::cajas::model::{NuevaSesionCaja, NuevoTotalCierreCaja, ResumenSesion, SesionCaja, TotalCierreCaja, TotalMetodoPago};
+use crate::modules::common::errors::{ApiError, ApiResult};
+use crate::modules::common::types::{EstadoSesionCaja, EstadoVenta, MetodoPago};
+use crate::schema::{pagos, sesiones_caja, totales_cierre_caja, ventas};
+
+pub type DbPool = Pool<ConnectionManager<PgConnection>>;
+pub type DbConnection = PooledConnection<ConnectionManager<PgConnection>>;
+
+/// Acceso a datos de las sesiones de caja (ver `PersonaRepository` para las implementaciones)
+pub trait CajaRepository: Send + Sync {
+    /// Rechaza la apertura si la terminal ya tiene una sesión abierta
+    fn abrir_sesion(&self, nueva_sesion: NuevaSesionCaja) -> ApiResult<Uuid>;
+
+    fn buscar_sesion(&self, id: Uuid) -> ApiResult<SesionCaja>;
+
+    /// Sesiones activas, de la más reciente a la más antigua
+    fn listar_sesiones(&self, sucursal: Option<String>, estado: Option<EstadoSesionCaja>) -> ApiResult<Vec<SesionCaja>>;
+
+    /// Ventas y pagos vigentes registrados en la sesión
+    fn resumen(&self, id: Uuid) -> ApiResult<ResumenSesion>;
+
+    /// Resumen guardado al cerrar la sesión, el de su reporte Z
+    fn resumen_cierre(&self, id: Uuid) -> ApiResult<ResumenSesion>;
+
+    /// Cierra la sesión con su arqueo y guarda su resumen. Ambos se calculan con la sesión
+    /// bloqueada, así que incluyen las ventas que se estuvieran guardando en ella.
+    fn cerrar_sesion(&self, id: Uuid, efectivo_contado: BigDecimal, observaciones: Option<String>) -> ApiResult<SesionCaja>;
+}
+
+pub struct PgCajaRepository {
+    pool: DbPool,
+}
+
+impl PgCajaRepository {
+    pub fn new(pool: DbPool) -> Self {
+        PgCajaRepository { pool }
+    }
+
+    fn get_connection(&self) -> ApiResult<DbConnection> {
+        self.pool
+            .get()
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+    }
+}
+
+impl CajaRepository for PgCajaRepository {
+    #[instrument(skip(self))]
+    fn abrir_sesion(&self, nueva_sesion: NuevaSesionCaja) -> ApiResult<Uuid> {
+        let mut conn = self.get_connection()?;
+
+        conn.transaction::<Uuid, ApiError, _>(|conn| {
+            auditoria::contexto::aplicar(conn)?;
+
+            // Misma regla que idx_sesiones_caja_terminal_abierta
+            let ocupada: bool = diesel::select(exists(
+                sesiones_caja::table
+                    .filter(sesiones_caja::activo.eq(true))
+                    .filter(sesiones_caja::estado.eq(EstadoSesionCaja::Abierta))
+                    .filter(sesiones_caja::sucursal.eq(&nueva_sesion.sucursal))
+                    .filter(sesiones_caja::terminal.eq(&nueva_sesion.terminal)),
+            ))
+            .get_result(conn)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+            if ocupada {
+                return Err(terminal_con_sesion_abierta(&nueva_sesion.sucursal, &nueva_sesion.terminal));
+            }
+
+            diesel::insert_into(sesiones_caja::table)
+                .values(&nueva_sesion)
+                .returning(sesiones_caja::id)
+                .get_result(conn)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))
+        })
+    }
+
+    #[instrument(skip(self))]
+    fn buscar_sesion(&self, id: Uuid) -> ApiResult<SesionCaja> {
+        let mut conn = self.get_connection()?;
+
+        sesiones_caja::table
+            .find(id)
+            .filter(sesiones_caja::activo.eq(true))
+            .select(SesionCaja::as_select())
+            .first(&mut conn)
+            .map_err(|e| match e {
+                diesel::result::Error::NotFound => sesion_no_encontrada(id),
+                _ => ApiError::DatabaseError(e.to_string()),
+            })
+    }
+
+    #[instrument(skip(self))]
+    fn listar_sesiones(&self, sucursal: Option<String>, estado: Option<EstadoSesionCaja>) -> ApiResult<Vec<SesionCaja>> {
+        let mut conn = self.get_connection()?;
+
+        let mut query = sesiones_caja::table
+            .filter(sesiones_caja::activo.eq(true))
+            .into_boxed();
+        if let Some(sucursal) = sucursal {
+            query = query.filter(sesiones_caja::sucursal.eq(sucursal));
+        }
+        if let Some(estado) = estado {
+            query = query.filter(sesiones_caja::estado.eq(estado));
+        }
+
+        query
+            .order(sesiones_caja::fecha_apertura.desc())
+            .select(SesionCaja::as_select())
+            .load(&mut conn)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+    }
+
+    #[instrument(skip(self))]
+    fn resumen(&self, id: Uuid) -> ApiResult<ResumenSesion> {
+        let mut conn = self.get_connection()?;
+        resumen_de(&mut conn, id)
+    }
+
+    #[instrument(skip(self))]
+    fn resumen_cierre(&self, id: Uuid) -> ApiResult<ResumenSesion> {
+        let mut conn = self.get_connection()?;
+
+        let sesion = sesiones_caja::table
+            .find(id)
+            .filter(sesiones_caja::activo.eq(true))
+            .select(SesionCaja::as_select())
+            .first(&mut conn)
+            .map_err(|e| match e {
+                diesel::result::Error::NotFound => sesion_no_encontrada(id),
+                _ => ApiError::DatabaseError(e.to_string()),
+            })?;
+        let totales = totales_cierre_caja::table
+            .filter(totales_cierre_caja::id_sesion_caja.eq(id))
+            .filter(totales_cierre_caja::activo.eq(true))
+            .order(totales_cierre_caja::metodo.asc())
+            .select(TotalCierreCaja::as_select())
+            .load(&mut conn)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+        resumen_guardado(sesion, totales)
+    }
+
+    #[instrument(skip(self, observaciones))]
+    fn cerrar_sesion(&self, id: Uuid, efectivo_contado: BigDecimal, observaciones: Option<String>) -> ApiResult<SesionCaja> {
+        let mut conn = self.get_connection()?;
+
+        conn.transaction::<SesionCaja, ApiError, _>(|conn| {
+            auditoria::contexto::aplicar(conn)?;
+
+            // Bloquear la sesión: espera a las ventas en curso y rechaza las que lleguen después
+            let sesion = sesiones_caja::table
+                .find(id)
+                .filter(sesiones_caja::activo.eq(true))
+                .select(SesionCaja::as_select())
+                .for_update()
+                .first(conn)
+                .map_err(|e| match e {
+                    diesel::result::Error::NotFound => sesion_no_encontrada(id),
+                    _ => ApiError::DatabaseError(e.to_string()),
+                })?;
+            validar_abierta(&sesion)?;
+
+            let resumen = resumen_de(conn, id)?;
+            let esperado = arqueo::efectivo_esperado(&sesion.monto_apertura, &resumen);
+            let totales: Vec<NuevoTotalCierreCaja> = resumen
+                .pagos
+                .into_iter()
+                .map(|total| NuevoTotalCierreCaja {
+                    id_sesion_caja: id,
+                    metodo: total.metodo,
+                    pagos: total.pagos,
+                    monto: total.monto,
+                    cambio: total.cambio,
+                })
+                .collect();
+            diesel::insert_into(totales_cierre_caja::table)
+                .values(&totales)
+                .execute(conn)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+            diesel::update(sesiones_caja::table.find(id))
+                .set((
+                    sesiones_caja::estado.eq(EstadoSesionCaja::Cerrada),
+                    sesiones_caja::fecha_cierre.eq(Utc::now().naive_utc()),
+                    sesiones_caja::diferencia.eq(arqueo::diferencia(&efectivo_contado, &esperado)),
+                    sesiones_caja::efectivo_esperado.eq(esperado),
+                    sesiones_caja::efectivo_contado.eq(efectivo_contado),
+                    sesiones_caja::observaciones.eq(observaciones),
+                    sesiones_caja::ventas.eq(resumen.ventas),
+                    sesiones_caja::ventas_anuladas.eq(resumen.ventas_anuladas),
+                    sesiones_caja::total_vendido.eq(resumen.total_vendido),
+                ))
+                .returning(SesionCaja::as_returning())
+                .get_result(conn)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))
+        })
+    }
+}
+
+/// Sesión activa y abierta, bloqueada en modo compartido hasta el final de la transacción: otras
+/// ventas pueden registrarse a la vez, pero el cierre espera a que terminen
+pub(crate) fn sesion_abierta_compartida(conn: &mut PgConnection, id: Uuid) -> ApiResult<SesionCaja> {
+    let sesion = sesiones_caja::table
+        .find(id)
+        .filter(sesiones_caja::activo.eq(true))
+        .select(SesionCaja::as_select())
+        .for_share()
+        .first(conn)
+        .map_err(|e| match e {
+            diesel::result::Error::NotFound => sesion_no_encontrada(id),
+            _ => ApiError::DatabaseError(e.to_string()),
+        })?;
+    validar_abierta(&sesion)?;
+    Ok(sesion)
+}
+
+fn resumen_de(conn: &mut PgConnection, id: Uuid) -> ApiResult<ResumenSesion> {
+    let por_estado: Vec<(EstadoVenta, i64, Option<BigDecimal>)> = ventas::table
+        .filter(ventas::id_sesion_caja.eq(id))
+        .filter(ventas::activo.eq(true))
+        .group_by(ventas::estado)
+        .select((ventas::estado, count_star(), diesel::dsl::sum(ventas::monto)))
+        .load(conn)
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+    let por_metodo: Vec<(MetodoPago, i64, Option<BigDecimal>, Option<BigDecimal>)> = pagos::table
+        .filter(pagos::id_sesion_caja.eq(id))
+        .filter(pagos::activo.eq(true))
+        .group_by(pagos::metodo)
+        .select((pagos::metodo, count_star(), diesel::dsl::sum(pagos::monto), diesel::dsl::sum(pagos::cambio)))
+        .order(pagos::metodo.asc())
+        .load(conn)
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+    let mut resumen = ResumenSesion::default();
+    for (estado, cantidad, monto) in por_estado {
+        if estado == EstadoVenta::Anulada {
+            resumen.ventas_anuladas += cantidad;
+        } else {
+            resumen.ventas += cantidad;
+            resumen.total_vendido += monto.unwrap_or_default();
+        }
+    }
+    resumen.pagos = por_metodo
+        .into_iter()
+        .map(|(metodo, pagos, monto, cambio)| TotalMetodoPago {
+            metodo,
+            pagos,
+            monto: monto.unwrap_or_default(),
+            cambio: cambio.unwrap_or_default(),
+        })
+        .collect();
+    Ok(resumen)
+}
+
+/// Resumen fijado al cerrar la sesión, con sus totales por método de pago
+pub(crate) fn resumen_guardado(sesion: SesionCaja, totales: Vec<TotalCierreCaja>) -> ApiResult<ResumenSesion> {
+    match (sesion.ventas, sesion.ventas_anuladas, sesion.total_vendido) {
+        (Some(ventas), Some(ventas_anuladas), Some(total_vendido)) => Ok(ResumenSesion {
+            ventas,
+            ventas_anuladas,
+            total_vendido,
+            pagos: totales
+                .into_iter()
+                .map(|total| TotalMetodoPago {
+                    metodo: total.metodo,
+                    pagos: total.pagos,
+                    monto: total.monto,
+                    cambio: total.cambio,
+                })
+                .collect(),
+        }),
+        _ => Err(ApiError::BusinessRuleViolation(format!(
+            "La sesión de caja {} sigue abierta; ciérrela para obtener su reporte Z",
+            sesion.id
+        ))),
+    }
+}
+
+/// Solo se vende, se cobra y se anula en una sesión abierta
+pub(crate) fn validar_abierta(sesion: &SesionCaja) -> ApiResult<()> {
+    match sesion.estado {
+        EstadoSesionCaja::Abierta => Ok(()),
+        EstadoSesionCaja::Cerrada => Err(ApiError::BusinessRuleViolation(format!(
+            "La sesión de caja {} está cerrada",
+            sesion.id
+        ))),
+    }
+}
+
+pub(crate) fn terminal_con_sesion_abierta(sucursal: &str, terminal: &str) -> ApiError {
+    ApiError::BusinessRuleViolation(format!(
+        "La terminal '{}' de la sucursal '{}' ya tiene una sesión de caja abierta",
+        terminal, sucursal
+    ))
+}
+
+pub(crate) fn sesion_no_encontrada(id: Uuid) -> ApiError {
+    ApiError::NotFound(format!("Sesión de caja con ID {} no encontrada", id))
+}
@@ -0,0 +1,167 @@
+use std::sync::Arc;
+use bigdecimal::ToPrimitive;
+use chrono::Utc;
+use uuid::Uuid;
+use tracing::instrument;
+use crate::modules::cajas::arqueo;
+use crate::modules::cajas::model::{
+    AbrirSesionCajaRequest, CerrarSesionCajaRequest, NuevaSesionCaja, ReporteCajaResponse, ResumenSesion, SesionCaja,
+    SesionCajaResponse, TipoReporteCaja, TotalMetodoPagoResponse,
+};
+use crate::modules::cajas::repository::{self as caja_repo, CajaRepository};
+use crate::modules::common::errors::{ApiError, ApiResult};
+use crate::modules::common::validacion::{importe, parse_id};
+use crate::modules::common::types::EstadoSesionCaja;
+use crate::modules::personas::repository::PersonaRepository;
+
+pub struct CajaService {
+    repository: Arc<dyn CajaRepository>,
+    persona_repo: Arc<dyn PersonaRepository>,
+}
+
+impl CajaService {
+    pub fn new(repository: Arc<dyn CajaRepository>, persona_repo: Arc<dyn PersonaRepository>) -> Self {
+        CajaService { repository, persona_repo }
+    }
+
+    /// Abrir una sesión en una terminal libre, con el efectivo inicial de la caja
+    #[instrument(skip(self, request))]
+    pub fn abrir_sesion(&self, request: AbrirSesionCajaRequest) -> ApiResult<SesionCajaResponse> {
+        let sucursal = texto_requerido(&request.sucursal, "sucursal", 100)?;
+        let terminal = texto_requerido(&request.terminal, "terminal", 50)?;
+        let id_operador = Uuid::parse_str(&request.id_operador)
+            .map_err(|_| ApiError::InvalidInput("ID de operador inválido".to_string()))?;
+        let monto_apertura = importe(request.monto_apertura, "monto de apertura")?;
+
+        self.persona_repo.buscar_vendedor_activo(id_operador)?;
+
+        let id = self.repository.abrir_sesion(NuevaSesionCaja { sucursal, terminal, id_operador, monto_apertura })?;
+        Ok(self.repository.buscar_sesion(id)?.into())
+    }
+
+    #[instrument(skip(self))]
+    pub fn obtener_sesion(&self, id_str: &str) -> ApiResult<SesionCajaResponse> {
+        Ok(self.repository.buscar_sesion(parse_id(id_str, "sesión de caja")?)?.into())
+    }
+
+    /// Sesiones de caja, filtradas por sucursal y estado
+    #[instrument(skip(self))]
+    pub fn listar_sesiones(&self, sucursal: Option<String>, estado: Option<String>) -> ApiResult<Vec<SesionCajaResponse>> {
+        let estado = match estado {
+            Some(estado) => Some(match estado.to_uppercase().as_str() {
+                "ABIERTA" => EstadoSesionCaja::Abierta,
+                "CERRADA" => EstadoSesionCaja::Cerrada,
+                _ => return Err(ApiError::InvalidInput(
+                    "Estado de sesión inválido. Valores permitidos: ABIERTA, CERRADA".to_string()
+                )),
+            }),
+            None => None,
+        };
+
+        Ok(self
+            .repository
+            .listar_sesiones(sucursal, estado)?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    /// Reporte X: lo vendido y cobrado hasta ahora en una sesión abierta, sin cerrarla
+    #[instrument(skip(self))]
+    pub fn reporte_x(&self, id_str: &str) -> ApiResult<ReporteCajaResponse> {
+        let sesion = self.repository.buscar_sesion(parse_id(id_str, "sesión de caja")?)?;
+        if sesion.estado != EstadoSesionCaja::Abierta {
+            return Err(ApiError::BusinessRuleViolation(format!(
+                "La sesión de caja {} está cerrada; consulte su reporte Z",
+                sesion.id
+            )));
+        }
+
+        let resumen = self.repository.resumen(sesion.id)?;
+        Ok(reporte(TipoReporteCaja::X, sesion, resumen))
+    }
+
+    /// Cerrar la sesión con el efectivo contado. Devuelve el reporte Z.
+    #[instrument(skip(self, request))]
+    pub fn cerrar_sesion(&self, id_str: &str, request: CerrarSesionCajaRequest) -> ApiResult<ReporteCajaResponse> {
+        let id = parse_id(id_str, "sesión de caja")?;
+        let efectivo_contado = importe(request.efectivo_contado, "efectivo contado")?;
+        let observaciones = request
+            .observaciones
+            .map(|observaciones| observaciones.trim().to_string())
+            .filter(|observaciones| !observaciones.is_empty());
+
+        let sesion = self.repository.cerrar_sesion(id, efectivo_contado, observaciones)?;
+        let resumen = self.repository.resumen_cierre(id)?;
+        Ok(reporte(TipoReporteCaja::Z, sesion, resumen))
+    }
+
+    /// Reporte Z de una sesión cerrada, con el arqueo y el resumen fijados al cerrarla
+    #[instrument(skip(self))]
+    pub fn reporte_z(&self, id_str: &str) -> ApiResult<ReporteCajaResponse> {
+        let sesion = self.repository.buscar_sesion(parse_id(id_str, "sesión de caja")?)?;
+        if sesion.estado != EstadoSesionCaja::Cerrada {
+            return Err(ApiError::BusinessRuleViolation(format!(
+                "La sesión de caja {} sigue abierta; ciérrela para obtener su reporte Z",
+                sesion.id
+            )));
+        }
+
+        let resumen = self.repository.resumen_cierre(sesion.id)?;
+        Ok(reporte(TipoReporteCaja::Z, sesion, resumen))
+    }
+}
+
+/// En el reporte Z el arqueo es el que quedó guardado al cerrar; en el X se calcula al momento
+fn reporte(tipo: TipoReporteCaja, sesion: SesionCaja, resumen: ResumenSesion) -> ReporteCajaResponse {
+    let efectivo_esperado = sesion
+        .efectivo_esperado
+        .clone()
+        .unwrap_or_else(|| arqueo::efectivo_esperado(&sesion.monto_apertura, &resumen));
+    let efectivo_contado = sesion.efectivo_contado.as_ref().map(|contado| contado.to_f64().unwrap_or(0.0));
+    let diferencia = sesion.diferencia.as_ref().map(|diferencia| diferencia.to_f64().unwrap_or(0.0));
+
+    ReporteCajaResponse {
+        tipo,
+        fecha_generacion: Utc::now().naive_utc().format("%Y-%m-%d %H:%M:%S").to_string(),
+        ventas: resumen.ventas,
+        ventas_anuladas: resumen.ventas_anuladas,
+        total_vendido: resumen.total_vendido.to_f64().unwrap_or(0.0),
+        cambio_entregado: arqueo::cambio_entregado(&resumen.pagos).to_f64().unwrap_or(0.0),
+        pagos: resumen
+            .pagos
+            .into_iter()
+            .map(|total| TotalMetodoPagoResponse {
+                metodo: total.metodo,
+                pagos: total.pagos,
+                monto: total.monto.to_f64().unwrap_or(0.0),
+                cambio: total.cambio.to_f64().unwrap_or(0.0),
+            })
+            .collect(),
+        efectivo_esperado: efectivo_esperado.to_f64().unwrap_or(0.0),
+        efectivo_contado,
+        diferencia,
+        sesion: sesion.into(),
+    }
+}
+
+fn texto_requerido(valor: &str, campo: &str, longitud_maxima: usize) -> ApiResult<String> {
+    let valor = valor.trim();
+    if valor.is_empty() {
+        return Err(ApiError::InvalidInput(format!("La {} es obligatoria", campo)));
+    }
+    if valor.chars().count() > longitud_maxima {
+        return Err(ApiError::InvalidInput(format!(
+            "La {} no puede superar {} caracteres",
+            campo, longitud_maxima
+        )));
+    }
+    Ok(valor.to_string())
+}
+
+/// Sesión en la que se registra una venta o un cobro: debe existir y estar abierta
+pub(crate) fn sesion_para_cobrar(repository: &dyn CajaRepository, id_str: &str) -> ApiResult<SesionCaja> {
+    let sesion = repository.buscar_sesion(parse_id(id_str, "sesión de caja")?)?;
+    caja_repo::validar_abierta(&sesion)?;
+    Ok(sesion)
+}
@@ -0,0 +1,282 @@
+use actix_web::{web, HttpResponse, ResponseError, Result};
+use crate::modules::catalogo::model::{
+    CatalogoCreadoResponse, CategoriaRequest, CategoriaResponse, MarcaRequest, MarcaResponse,
+};
+use crate::modules::common::errors::ErrorResponse;
+use crate::state::app_state::AppState;
+
+/// POST /v1/categorias - Crear categoría
+#[utoipa::path(
+    post,
+    path = "/v1/categorias",
+    tag = "Catalogo",
+    request_body = CategoriaRequest,
+    responses(
+        (status = 201, description = "Categoría creada exitosamente", body = CatalogoCreadoResponse),
+        (status = 400, description = "Datos de entrada inválidos o nombre repetido", body = ErrorResponse),
+        (status = 404, description = "Categoría padre no encontrada", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn crear_categoria(
+    state: web::Data<AppState>,
+    body: web::Json<CategoriaRequest>,
+) -> Result<HttpResponse> {
+    let service = &state.catalogo_service;
+
+    match service.crear_categoria(body.into_inner()) {
+        Ok(response) => Ok(HttpResponse::Created().json(response)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// GET /v1/categorias - Listar categorías
+#[utoipa::path(
+    get,
+    path = "/v1/categorias",
+    tag = "Catalogo",
+    responses(
+        (status = 200, description = "Categorías activas", body = Vec<CategoriaResponse>),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn listar_categorias(state: web::Data<AppState>) -> Result<HttpResponse> {
+    let service = &state.catalogo_service;
+
+    match service.listar_categorias() {
+        Ok(categorias) => Ok(HttpResponse::Ok().json(categorias)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// GET /v1/categorias/:id - Obtener categoría
+#[utoipa::path(
+    get,
+    path = "/v1/categorias/{id}",
+    tag = "Catalogo",
+    params(
+        ("id" = String, Path, description = "ID de la categoría (UUID)")
+    ),
+    responses(
+        (status = 200, description = "Categoría encontrada", body = CategoriaResponse),
+        (status = 404, description = "Categoría no encontrada", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn obtener_categoria(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    let service = &state.catalogo_service;
+
+    match service.obtener_categoria(&id) {
+        Ok(categoria) => Ok(HttpResponse::Ok().json(categoria)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// PUT /v1/categorias/:id - Actualizar categoría
+#[utoipa::path(
+    put,
+    path = "/v1/categorias/{id}",
+    tag = "Catalogo",
+    params(
+        ("id" = String, Path, description = "ID de la categoría (UUID)")
+    ),
+    request_body = CategoriaRequest,
+    responses(
+        (status = 200, description = "Categoría actualizada", body = CategoriaResponse),
+        (status = 400, description = "Datos inválidos, nombre repetido o jerarquía circular", body = ErrorResponse),
+        (status = 404, description = "Categoría no encontrada", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn actualizar_categoria(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<CategoriaRequest>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    let service = &state.catalogo_service;
+
+    match service.actualizar_categoria(&id, body.into_inner()) {
+        Ok(categoria) => Ok(HttpResponse::Ok().json(categoria)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// DELETE /v1/categorias/:id - Dar de baja una categoría
+#[utoipa::path(
+    delete,
+    path = "/v1/categorias/{id}",
+    tag = "Catalogo",
+    params(
+        ("id" = String, Path, description = "ID de la categoría (UUID)")
+    ),
+    responses(
+        (status = 204, description = "Categoría dada de baja"),
+        (status = 400, description = "La categoría tiene subcategorías o productos activos", body = ErrorResponse),
+        (status = 404, description = "Categoría no encontrada", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn desactivar_categoria(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    let service = &state.catalogo_service;
+
+    match service.desactivar_categoria(&id) {
+        Ok(()) => Ok(HttpResponse::NoContent().finish()),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// POST /v1/marcas - Crear marca
+#[utoipa::path(
+    post,
+    path = "/v1/marcas",
+    tag = "Catalogo",
+    request_body = MarcaRequest,
+    responses(
+        (status = 201, description = "Marca creada exitosamente", body = CatalogoCreadoResponse),
+        (status = 400, description = "Datos de entrada inválidos o nombre repetido", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn crear_marca(
+    state: web::Data<AppState>,
+    body: web::Json<MarcaRequest>,
+) -> Result<HttpResponse> {
+    let service = &state.catalogo_service;
+
+    match service.crear_marca(body.into_inner()) {
+        Ok(response) => Ok(HttpResponse::Created().json(response)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// GET /v1/marcas - Listar marcas
+#[utoipa::path(
+    get,
+    path = "/v1/marcas",
+    tag = "Catalogo",
+    responses(
+        (status = 200, description = "Marcas activas", body = Vec<MarcaResponse>),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn listar_marcas(state: web::Data<AppState>) -> Result<HttpResponse> {
+    let service = &state.catalogo_service;
+
+    match service.listar_marcas() {
+        Ok(marcas) => Ok(HttpResponse::Ok().json(marcas)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// GET /v1/marcas/:id - Obtener marca
+#[utoipa::path(
+    get,
+    path = "/v1/marcas/{id}",
+    tag = "Catalogo",
+    params(
+        ("id" = String, Path, description = "ID de la marca (UUID)")
+    ),
+    responses(
+        (status = 200, description = "Marca encontrada", body = MarcaResponse),
+        (status = 404, description = "Marca no encontrada", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn obtener_marca(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    let service = &state.catalogo_service;
+
+    match service.obtener_marca(&id) {
+        Ok(marca) => Ok(HttpResponse::Ok().json(marca)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// PUT /v1/marcas/:id - Renombrar marca
+#[utoipa::path(
+    put,
+    path = "/v1/marcas/{id}",
+    tag = "Catalogo",
+    params(
+        ("id" = String, Path, description = "ID de la marca (UUID)")
+    ),
+    request_body = MarcaRequest,
+    responses(
+        (status = 200, description = "Marca actualizada", body = MarcaResponse),
+        (status = 400, description = "Datos inválidos o nombre repetido", body = ErrorResponse),
+        (status = 404, description = "Marca no encontrada", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn actualizar_marca(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<MarcaRequest>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    let service = &state.catalogo_service;
+
+    match service.actualizar_marca(&id, body.into_inner()) {
+        Ok(marca) => Ok(HttpResponse::Ok().json(marca)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// DELETE /v1/marcas/:id - Dar de baja una marca
+#[utoipa::path(
+    delete,
+    path = "/v1/marcas/{id}",
+    tag = "Catalogo",
+    params(
+        ("id" = String, Path, description = "ID de la marca (UUID)")
+    ),
+    responses(
+        (status = 204, description = "Marca dada de baja"),
+        (status = 400, description = "La marca tiene productos activos", body = ErrorResponse),
+        (status = 404, description = "Marca no encontrada", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn desactivar_marca(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    let service = &state.catalogo_service;
+
+    match service.desactivar_marca(&id) {
+        Ok(()) => Ok(HttpResponse::NoContent().finish()),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/categorias")
+            .route("", web::post().to(crear_categoria))
+            .route("", web::get().to(listar_categorias))
+            .route("/{id}", web::get().to(obtener_categoria))
+            .route("/{id}", web::put().to(actualizar_categoria))
+            .route("/{id}", web::delete().to(desactivar_categoria))
+    );
+    cfg.service(
+        web::scope("/marcas")
+            .route("", web::post().to(crear_marca))
+            .route("", web::get().to(listar_marcas))
+            .route("/{id}", web::get().to(obtener_marca))
+            .route("/{id}", web::put().to(actualizar_marca))
+            .route("/{id}", web::delete().to(desactivar_marca))
+    );
+}
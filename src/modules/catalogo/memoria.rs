@@ -0,0 +1,160 @@
+use chrono::Utc;
+use uuid::Uuid;
+use crate::modules::catalogo::model::{Categoria, Marca, NuevaCategoria, NuevaMarca};
+use crate::modules::catalogo::repository::{self as catalogo_repo, CatalogoRepository};
+use crate::modules::common::errors::ApiResult;
+use crate::modules::common::memoria::{MemoriaRepository, Tablas};
+
+impl Tablas {
+    fn categoria_mut(&mut self, id: Uuid) -> ApiResult<&mut Categoria> {
+        self.categorias
+            .iter_mut()
+            .find(|categoria| categoria.id == id && categoria.activo)
+            .ok_or_else(|| catalogo_repo::categoria_no_encontrada(id))
+    }
+
+    fn marca_mut(&mut self, id: Uuid) -> ApiResult<&mut Marca> {
+        self.marcas
+            .iter_mut()
+            .find(|marca| marca.id == id && marca.activo)
+            .ok_or_else(|| catalogo_repo::marca_no_encontrada(id))
+    }
+
+    /// Misma regla que idx_categorias_nombre
+    fn existe_categoria_con_nombre(&self, categoria: &NuevaCategoria, excluir: Option<Uuid>) -> bool {
+        self.categorias.iter().any(|c| {
+            c.activo
+                && Some(c.id) != excluir
+                && c.id_padre == categoria.id_padre
+                && c.nombre.to_lowercase() == categoria.nombre.to_lowercase()
+        })
+    }
+
+    /// Misma regla que idx_marcas_nombre
+    fn existe_marca_con_nombre(&self, nombre: &str, excluir: Option<Uuid>) -> bool {
+        self.marcas
+            .iter()
+            .any(|m| m.activo && Some(m.id) != excluir && m.nombre.to_lowercase() == nombre.to_lowercase())
+    }
+}
+
+impl CatalogoRepository for MemoriaRepository {
+    fn buscar_categoria(&self, id: Uuid) -> ApiResult<Categoria> {
+        Ok(self.tablas().categoria_mut(id)?.clone())
+    }
+
+    fn listar_categorias(&self) -> ApiResult<Vec<Categoria>> {
+        let mut categorias: Vec<Categoria> = self.tablas().categorias.iter().filter(|c| c.activo).cloned().collect();
+        categorias.sort_by(|a, b| a.nombre.cmp(&b.nombre));
+        Ok(categorias)
+    }
+
+    fn crear_categoria(&self, nueva_categoria: NuevaCategoria) -> ApiResult<Uuid> {
+        self.transaccion(|tablas| {
+            if tablas.existe_categoria_con_nombre(&nueva_categoria, None) {
+                return Err(catalogo_repo::categoria_duplicada(&nueva_categoria.nombre));
+            }
+
+            let ahora = Utc::now().naive_utc();
+            let id = Uuid::new_v4();
+            tablas.categorias.push(Categoria {
+                id,
+                nombre: nueva_categoria.nombre,
+                descripcion: nueva_categoria.descripcion,
+                id_padre: nueva_categoria.id_padre,
+                fecha_creacion: ahora,
+                fecha_actualizacion: ahora,
+                activo: true,
+            });
+            Ok(id)
+        })
+    }
+
+    fn actualizar_categoria(&self, id: Uuid, cambios: NuevaCategoria) -> ApiResult<Categoria> {
+        self.transaccion(|tablas| {
+            tablas.categoria_mut(id)?;
+            if tablas.existe_categoria_con_nombre(&cambios, Some(id)) {
+                return Err(catalogo_repo::categoria_duplicada(&cambios.nombre));
+            }
+
+            let categoria = tablas.categoria_mut(id)?;
+            categoria.nombre = cambios.nombre;
+            categoria.descripcion = cambios.descripcion;
+            categoria.id_padre = cambios.id_padre;
+            categoria.fecha_actualizacion = Utc::now().naive_utc();
+            Ok(categoria.clone())
+        })
+    }
+
+    fn desactivar_categoria(&self, id: Uuid) -> ApiResult<()> {
+        self.transaccion(|tablas| {
+            tablas.categoria_mut(id)?;
+
+            let subcategorias = tablas.categorias.iter().filter(|c| c.activo && c.id_padre == Some(id)).count() as i64;
+            let productos = tablas.productos.iter().filter(|p| p.activo && p.id_categoria == Some(id)).count() as i64;
+            if subcategorias > 0 || productos > 0 {
+                return Err(catalogo_repo::categoria_en_uso(id, subcategorias, productos));
+            }
+
+            tablas.categoria_mut(id)?.activo = false;
+            Ok(())
+        })
+    }
+
+    fn buscar_marca(&self, id: Uuid) -> ApiResult<Marca> {
+        Ok(self.tablas().marca_mut(id)?.clone())
+    }
+
+    fn listar_marcas(&self) -> ApiResult<Vec<Marca>> {
+        let mut marcas: Vec<Marca> = self.tablas().marcas.iter().filter(|m| m.activo).cloned().collect();
+        marcas.sort_by(|a, b| a.nombre.cmp(&b.nombre));
+        Ok(marcas)
+    }
+
+    fn crear_marca(&self, nueva_marca: NuevaMarca) -> ApiResult<Uuid> {
+        self.transaccion(|tablas| {
+            if tablas.existe_marca_con_nombre(&nueva_marca.nombre, None) {
+                return Err(catalogo_repo::marca_duplicada(&nueva_marca.nombre));
+            }
+
+            let ahora = Utc::now().naive_utc();
+            let id = Uuid::new_v4();
+            tablas.marcas.push(Marca {
+                id,
+                nombre: nueva_marca.nombre,
+                fecha_creacion: ahora,
+                fecha_actualizacion: ahora,
+                activo: true,
+            });
+            Ok(id)
+        })
+    }
+
+    fn actualizar_marca(&self, id: Uuid, cambios: NuevaMarca) -> ApiResult<Marca> {
+        self.transaccion(|tablas| {
+            tablas.marca_mut(id)?;
+            if tablas.existe_marca_con_nombre(&cambios.nombre, Some(id)) {
+                return Err(catalogo_repo::marca_duplicada(&cambios.nombre));
+            }
+
+            let marca = tablas.marca_mut(id)?;
+            marca.nombre = cambios.nombre;
+            marca.fecha_actualizacion = Utc::now().naive_utc();
+            Ok(marca.clone())
+        })
+    }
+
+    fn desactivar_marca(&self, id: Uuid) -> ApiResult<()> {
+        self.transaccion(|tablas| {
+            tablas.marca_mut(id)?;
+
+            let productos = tablas.productos.iter().filter(|p| p.activo && p.id_marca == Some(id)).count() as i64;
+            if productos > 0 {
+                return Err(catalogo_repo::marca_en_uso(id, productos));
+            }
+
+            tablas.marca_mut(id)?.activo = false;
+            Ok(())
+        })
+    }
+}
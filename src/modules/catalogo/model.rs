@@ -0,0 +1,116 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use utoipa::ToSchema;
+use crate::schema::{categorias, marcas};
+
+// Domain Model for Categoria
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = categorias)]
+pub struct Categoria {
+    pub id: Uuid,
+    pub nombre: String,
+    pub descripcion: Option<String>,
+    pub id_padre: Option<Uuid>,
+    pub fecha_creacion: NaiveDateTime,
+    pub fecha_actualizacion: NaiveDateTime,
+    pub activo: bool,
+}
+
+// Domain Model for Marca
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = marcas)]
+pub struct Marca {
+    pub id: Uuid,
+    pub nombre: String,
+    pub fecha_creacion: NaiveDateTime,
+    pub fecha_actualizacion: NaiveDateTime,
+    pub activo: bool,
+}
+
+// DTO for creating or updating a Categoria (database insert/update)
+#[derive(Debug, Clone, Insertable, AsChangeset)]
+#[diesel(table_name = categorias, treat_none_as_null = true)]
+pub struct NuevaCategoria {
+    pub nombre: String,
+    pub descripcion: Option<String>,
+    pub id_padre: Option<Uuid>,
+}
+
+// DTO for creating or updating a Marca (database insert/update)
+#[derive(Debug, Clone, Insertable, AsChangeset)]
+#[diesel(table_name = marcas)]
+pub struct NuevaMarca {
+    pub nombre: String,
+}
+
+// DTO for categoria creation/update request
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CategoriaRequest {
+    #[schema(example = "Portátiles")]
+    pub nombre: String,
+    #[schema(example = "Computadores portátiles y ultrabooks")]
+    pub descripcion: Option<String>,
+    /// Categoría que contiene a esta; se omite en las categorías raíz
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000")]
+    pub id_padre: Option<String>,
+}
+
+// DTO for categoria response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CategoriaResponse {
+    #[schema(example = "660e8400-e29b-41d4-a716-446655440000")]
+    pub id: String,
+    #[schema(example = "Portátiles")]
+    pub nombre: String,
+    #[schema(example = "Computadores portátiles y ultrabooks")]
+    pub descripcion: Option<String>,
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000")]
+    pub id_padre: Option<String>,
+}
+
+impl From<Categoria> for CategoriaResponse {
+    fn from(categoria: Categoria) -> Self {
+        CategoriaResponse {
+            id: categoria.id.to_string(),
+            nombre: categoria.nombre,
+            descripcion: categoria.descripcion,
+            id_padre: categoria.id_padre.map(|id| id.to_string()),
+        }
+    }
+}
+
+// DTO for marca creation/update request
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct MarcaRequest {
+    #[schema(example = "Dell")]
+    pub nombre: String,
+}
+
+// DTO for marca response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MarcaResponse {
+    #[schema(example = "770e8400-e29b-41d4-a716-446655440000")]
+    pub id: String,
+    #[schema(example = "Dell")]
+    pub nombre: String,
+}
+
+impl From<Marca> for MarcaResponse {
+    fn from(marca: Marca) -> Self {
+        MarcaResponse {
+            id: marca.id.to_string(),
+            nombre: marca.nombre,
+        }
+    }
+}
+
+// DTO for catalog entry creation response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CatalogoCreadoResponse {
+    #[schema(example = "660e8400-e29b-41d4-a716-446655440000")]
+    pub id: String,
+    #[schema(example = "Categoría creada exitosamente")]
+    pub mensaje: String,
+}
@@ -0,0 +1,321 @@
+use diesel::dsl::exists;
+use diesel::prelude::*;
+use diesel::sql_types::Text;
+use diesel::r2d2::{ConnectionManager, Pool, PooledConnection};
+use uuid::Uuid;
+use tracing::instrument;
+use crate::modules::auditoria;
+use crate::modules::catalogo::model::{Categoria, Marca, NuevaCategoria, NuevaMarca};
+use crate::modules::common::errors::{ApiError, ApiResult};
+use crate::schema::{categorias, marcas, productos};
+
+pub type DbPool = Pool<ConnectionManager<PgConnection>>;
+pub type DbConnection = PooledConnection<ConnectionManager<PgConnection>>;
+
+define_sql_function!(fn lower(x: Text) -> Text);
+
+/// Acceso a datos de categorías y marcas (ver `PersonaRepository` para las implementaciones)
+pub trait CatalogoRepository: Send + Sync {
+    fn buscar_categoria(&self, id: Uuid) -> ApiResult<Categoria>;
+
+    /// Categorías activas ordenadas por nombre
+    fn listar_categorias(&self) -> ApiResult<Vec<Categoria>>;
+
+    /// Rechaza el nombre si ya lo usa otra categoría activa con el mismo padre
+    fn crear_categoria(&self, nueva_categoria: NuevaCategoria) -> ApiResult<Uuid>;
+
+    fn actualizar_categoria(&self, id: Uuid, cambios: NuevaCategoria) -> ApiResult<Categoria>;
+
+    /// Da de baja la categoría si no tiene subcategorías ni productos activos
+    fn desactivar_categoria(&self, id: Uuid) -> ApiResult<()>;
+
+    fn buscar_marca(&self, id: Uuid) -> ApiResult<Marca>;
+
+    /// Marcas activas ordenadas por nombre
+    fn listar_marcas(&self) -> ApiResult<Vec<Marca>>;
+
+    /// Rechaza el nombre si ya lo usa otra marca activa
+    fn crear_marca(&self, nueva_marca: NuevaMarca) -> ApiResult<Uuid>;
+
+    fn actualizar_marca(&self, id: Uuid, cambios: NuevaMarca) -> ApiResult<Marca>;
+
+    /// Da de baja la marca si no tiene productos activos
+    fn desactivar_marca(&self, id: Uuid) -> ApiResult<()>;
+}
+
+pub struct PgCatalogoRepository {
+    pool: DbPool,
+}
+
+impl PgCatalogoRepository {
+    pub fn new(pool: DbPool) -> Self {
+        PgCatalogoRepository { pool }
+    }
+
+    fn get_connection(&self) -> ApiResult<DbConnection> {
+        self.pool
+            .get()
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+    }
+}
+
+impl CatalogoRepository for PgCatalogoRepository {
+    #[instrument(skip(self))]
+    fn buscar_categoria(&self, id: Uuid) -> ApiResult<Categoria> {
+        let mut conn = self.get_connection()?;
+        buscar_categoria_activa(&mut conn, id)
+    }
+
+    #[instrument(skip(self))]
+    fn listar_categorias(&self) -> ApiResult<Vec<Categoria>> {
+        let mut conn = self.get_connection()?;
+
+        categorias::table
+            .filter(categorias::activo.eq(true))
+            .order(categorias::nombre.asc())
+            .select(Categoria::as_select())
+            .load(&mut conn)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+    }
+
+    #[instrument(skip(self, nueva_categoria))]
+    fn crear_categoria(&self, nueva_categoria: NuevaCategoria) -> ApiResult<Uuid> {
+        let mut conn = self.get_connection()?;
+
+        conn.transaction::<Uuid, ApiError, _>(|conn| {
+            auditoria::contexto::aplicar(conn)?;
+
+            if existe_categoria_con_nombre(conn, &nueva_categoria, None)? {
+                return Err(categoria_duplicada(&nueva_categoria.nombre));
+            }
+
+            diesel::insert_into(categorias::table)
+                .values(&nueva_categoria)
+                .returning(categorias::id)
+                .get_result(conn)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))
+        })
+    }
+
+    #[instrument(skip(self, cambios))]
+    fn actualizar_categoria(&self, id: Uuid, cambios: NuevaCategoria) -> ApiResult<Categoria> {
+        let mut conn = self.get_connection()?;
+
+        conn.transaction::<Categoria, ApiError, _>(|conn| {
+            auditoria::contexto::aplicar(conn)?;
+
+            buscar_categoria_activa(conn, id)?;
+            if existe_categoria_con_nombre(conn, &cambios, Some(id))? {
+                return Err(categoria_duplicada(&cambios.nombre));
+            }
+
+            diesel::update(categorias::table.find(id))
+                .set(&cambios)
+                .returning(Categoria::as_returning())
+                .get_result(conn)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))
+        })
+    }
+
+    #[instrument(skip(self))]
+    fn desactivar_categoria(&self, id: Uuid) -> ApiResult<()> {
+        let mut conn = self.get_connection()?;
+
+        conn.transaction::<(), ApiError, _>(|conn| {
+            auditoria::contexto::aplicar(conn)?;
+
+            buscar_categoria_activa(conn, id)?;
+
+            let subcategorias: i64 = categorias::table
+                .filter(categorias::id_padre.eq(id))
+                .filter(categorias::activo.eq(true))
+                .count()
+                .get_result(conn)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+            let productos: i64 = productos::table
+                .filter(productos::id_categoria.eq(id))
+                .filter(productos::activo.eq(true))
+                .count()
+                .get_result(conn)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+            if subcategorias > 0 || productos > 0 {
+                return Err(categoria_en_uso(id, subcategorias, productos));
+            }
+
+            diesel::update(categorias::table.find(id))
+                .set(categorias::activo.eq(false))
+                .execute(conn)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+            Ok(())
+        })
+    }
+
+    #[instrument(skip(self))]
+    fn buscar_marca(&self, id: Uuid) -> ApiResult<Marca> {
+        let mut conn = self.get_connection()?;
+        buscar_marca_activa(&mut conn, id)
+    }
+
+    #[instrument(skip(self))]
+    fn listar_marcas(&self) -> ApiResult<Vec<Marca>> {
+        let mut conn = self.get_connection()?;
+
+        marcas::table
+            .filter(marcas::activo.eq(true))
+            .order(marcas::nombre.asc())
+            .select(Marca::as_select())
+            .load(&mut conn)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+    }
+
+    #[instrument(skip(self, nueva_marca))]
+    fn crear_marca(&self, nueva_marca: NuevaMarca) -> ApiResult<Uuid> {
+        let mut conn = self.get_connection()?;
+
+        conn.transaction::<Uuid, ApiError, _>(|conn| {
+            auditoria::contexto::aplicar(conn)?;
+
+            if existe_marca_con_nombre(conn, &nueva_marca.nombre, None)? {
+                return Err(marca_duplicada(&nueva_marca.nombre));
+            }
+
+            diesel::insert_into(marcas::table)
+                .values(&nueva_marca)
+                .returning(marcas::id)
+                .get_result(conn)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))
+        })
+    }
+
+    #[instrument(skip(self, cambios))]
+    fn actualizar_marca(&self, id: Uuid, cambios: NuevaMarca) -> ApiResult<Marca> {
+        let mut conn = self.get_connection()?;
+
+        conn.transaction::<Marca, ApiError, _>(|conn| {
+            auditoria::contexto::aplicar(conn)?;
+
+            buscar_marca_activa(conn, id)?;
+            if existe_marca_con_nombre(conn, &cambios.nombre, Some(id))? {
+                return Err(marca_duplicada(&cambios.nombre));
+            }
+
+            diesel::update(marcas::table.find(id))
+                .set(&cambios)
+                .returning(Marca::as_returning())
+                .get_result(conn)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))
+        })
+    }
+
+    #[instrument(skip(self))]
+    fn desactivar_marca(&self, id: Uuid) -> ApiResult<()> {
+        let mut conn = self.get_connection()?;
+
+        conn.transaction::<(), ApiError, _>(|conn| {
+            auditoria::contexto::aplicar(conn)?;
+
+            buscar_marca_activa(conn, id)?;
+
+            let productos: i64 = productos::table
+                .filter(productos::id_marca.eq(id))
+                .filter(productos::activo.eq(true))
+                .count()
+                .get_result(conn)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+            if productos > 0 {
+                return Err(marca_en_uso(id, productos));
+            }
+
+            diesel::update(marcas::table.find(id))
+                .set(marcas::activo.eq(false))
+                .execute(conn)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+            Ok(())
+        })
+    }
+}
+
+fn buscar_categoria_activa(conn: &mut PgConnection, id: Uuid) -> ApiResult<Categoria> {
+    categorias::table
+        .find(id)
+        .filter(categorias::activo.eq(true))
+        .select(Categoria::as_select())
+        .first(conn)
+        .map_err(|e| match e {
+            diesel::result::Error::NotFound => categoria_no_encontrada(id),
+            _ => ApiError::DatabaseError(e.to_string()),
+        })
+}
+
+fn buscar_marca_activa(conn: &mut PgConnection, id: Uuid) -> ApiResult<Marca> {
+    marcas::table
+        .find(id)
+        .filter(marcas::activo.eq(true))
+        .select(Marca::as_select())
+        .first(conn)
+        .map_err(|e| match e {
+            diesel::result::Error::NotFound => marca_no_encontrada(id),
+            _ => ApiError::DatabaseError(e.to_string()),
+        })
+}
+
+/// Misma regla que idx_categorias_nombre: nombre único (sin distinguir mayúsculas) por padre
+fn existe_categoria_con_nombre(
+    conn: &mut PgConnection,
+    categoria: &NuevaCategoria,
+    excluir: Option<Uuid>,
+) -> ApiResult<bool> {
+    let mut query = categorias::table
+        .filter(categorias::activo.eq(true))
+        .filter(lower(categorias::nombre).eq(categoria.nombre.to_lowercase()))
+        .filter(categorias::id.ne(excluir.unwrap_or(Uuid::nil())))
+        .into_boxed();
+    query = match categoria.id_padre {
+        Some(id_padre) => query.filter(categorias::id_padre.eq(id_padre)),
+        None => query.filter(categorias::id_padre.is_null()),
+    };
+
+    diesel::select(exists(query))
+        .get_result(conn)
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))
+}
+
+fn existe_marca_con_nombre(conn: &mut PgConnection, nombre: &str, excluir: Option<Uuid>) -> ApiResult<bool> {
+    diesel::select(exists(
+        marcas::table
+            .filter(marcas::activo.eq(true))
+            .filter(lower(marcas::nombre).eq(nombre.to_lowercase()))
+            .filter(marcas::id.ne(excluir.unwrap_or(Uuid::nil()))),
+    ))
+    .get_result(conn)
+    .map_err(|e| ApiError::DatabaseError(e.to_string()))
+}
+
+pub(crate) fn categoria_no_encontrada(id: Uuid) -> ApiError {
+    ApiError::NotFound(format!("Categoría {} no encontrada", id))
+}
+
+pub(crate) fn marca_no_encontrada(id: Uuid) -> ApiError {
+    ApiError::NotFound(format!("Marca {} no encontrada", id))
+}
+
+pub(crate) fn categoria_duplicada(nombre: &str) -> ApiError {
+    ApiError::BusinessRuleViolation(format!("Ya existe la categoría '{}' en ese nivel", nombre))
+}
+
+pub(crate) fn marca_duplicada(nombre: &str) -> ApiError {
+    ApiError::BusinessRuleViolation(format!("Ya existe la marca '{}'", nombre))
+}
+
+pub(crate) fn categoria_en_uso(id: Uuid, subcategorias: i64, productos: i64) -> ApiError {
+    ApiError::BusinessRuleViolation(format!(
+        "La categoría {} tiene {} subcategorías y {} productos activos",
+        id, subcategorias, productos
+    ))
+}
+
+pub(crate) fn marca_en_uso(id: Uuid, productos: i64) -> ApiError {
+    ApiError::BusinessRuleViolation(format!("La marca {} tiene {} productos activos", id, productos))
+}
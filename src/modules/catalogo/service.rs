@@ -0,0 +1,169 @@
+use std::sync::Arc;
+use uuid::Uuid;
+use tracing::instrument;
+use crate::modules::catalogo::model::{
+    CatalogoCreadoResponse, Categoria, CategoriaRequest, CategoriaResponse, MarcaRequest, MarcaResponse, NuevaCategoria,
+    NuevaMarca,
+};
+use crate::modules::catalogo::repository::CatalogoRepository;
+use crate::modules::common::errors::{ApiError, ApiResult};
+use crate::modules::common::validacion::parse_id;
+
+pub struct CatalogoService {
+    repository: Arc<dyn CatalogoRepository>,
+}
+
+impl CatalogoService {
+    pub fn new(repository: Arc<dyn CatalogoRepository>) -> Self {
+        CatalogoService { repository }
+    }
+
+    /// Obtener una categoría activa por ID
+    #[instrument(skip(self))]
+    pub fn obtener_categoria(&self, id_str: &str) -> ApiResult<CategoriaResponse> {
+        let id = parse_id(id_str, "categoría")?;
+        Ok(self.repository.buscar_categoria(id)?.into())
+    }
+
+    /// Listar las categorías activas (el árbol se reconstruye con `id_padre`)
+    #[instrument(skip(self))]
+    pub fn listar_categorias(&self) -> ApiResult<Vec<CategoriaResponse>> {
+        Ok(self.repository.listar_categorias()?.into_iter().map(Into::into).collect())
+    }
+
+    /// Crear una categoría, raíz o dentro de `id_padre`
+    #[instrument(skip(self, request))]
+    pub fn crear_categoria(&self, request: CategoriaRequest) -> ApiResult<CatalogoCreadoResponse> {
+        let nueva_categoria = self.validar_categoria(request, None)?;
+        let id = self.repository.crear_categoria(nueva_categoria)?;
+
+        Ok(CatalogoCreadoResponse {
+            id: id.to_string(),
+            mensaje: "Categoría creada exitosamente".to_string(),
+        })
+    }
+
+    /// Reemplazar nombre, descripción y padre de una categoría
+    #[instrument(skip(self, request))]
+    pub fn actualizar_categoria(&self, id_str: &str, request: CategoriaRequest) -> ApiResult<CategoriaResponse> {
+        let id = parse_id(id_str, "categoría")?;
+        let cambios = self.validar_categoria(request, Some(id))?;
+        Ok(self.repository.actualizar_categoria(id, cambios)?.into())
+    }
+
+    /// Dar de baja una categoría sin subcategorías ni productos activos
+    #[instrument(skip(self))]
+    pub fn desactivar_categoria(&self, id_str: &str) -> ApiResult<()> {
+        let id = parse_id(id_str, "categoría")?;
+        self.repository.desactivar_categoria(id)
+    }
+
+    /// Obtener una marca activa por ID
+    #[instrument(skip(self))]
+    pub fn obtener_marca(&self, id_str: &str) -> ApiResult<MarcaResponse> {
+        let id = parse_id(id_str, "marca")?;
+        Ok(self.repository.buscar_marca(id)?.into())
+    }
+
+    /// Listar las marcas activas
+    #[instrument(skip(self))]
+    pub fn listar_marcas(&self) -> ApiResult<Vec<MarcaResponse>> {
+        Ok(self.repository.listar_marcas()?.into_iter().map(Into::into).collect())
+    }
+
+    /// Crear una marca
+    #[instrument(skip(self, request))]
+    pub fn crear_marca(&self, request: MarcaRequest) -> ApiResult<CatalogoCreadoResponse> {
+        let nueva_marca = NuevaMarca { nombre: validar_nombre(&request.nombre, "marca")? };
+        let id = self.repository.crear_marca(nueva_marca)?;
+
+        Ok(CatalogoCreadoResponse {
+            id: id.to_string(),
+            mensaje: "Marca creada exitosamente".to_string(),
+        })
+    }
+
+    /// Renombrar una marca
+    #[instrument(skip(self, request))]
+    pub fn actualizar_marca(&self, id_str: &str, request: MarcaRequest) -> ApiResult<MarcaResponse> {
+        let id = parse_id(id_str, "marca")?;
+        let cambios = NuevaMarca { nombre: validar_nombre(&request.nombre, "marca")? };
+        Ok(self.repository.actualizar_marca(id, cambios)?.into())
+    }
+
+    /// Dar de baja una marca sin productos activos
+    #[instrument(skip(self))]
+    pub fn desactivar_marca(&self, id_str: &str) -> ApiResult<()> {
+        let id = parse_id(id_str, "marca")?;
+        self.repository.desactivar_marca(id)
+    }
+
+    /// Valida la petición; `id` es la categoría que se actualiza, para no colgarla de sí misma
+    /// ni de una de sus subcategorías
+    fn validar_categoria(&self, request: CategoriaRequest, id: Option<Uuid>) -> ApiResult<NuevaCategoria> {
+        let nombre = validar_nombre(&request.nombre, "categoría")?;
+
+        let id_padre = request
+            .id_padre
+            .filter(|id_padre| !id_padre.trim().is_empty())
+            .map(|id_padre| parse_id(id_padre.trim(), "categoría padre"))
+            .transpose()?;
+
+        if let Some(id_padre) = id_padre {
+            self.repository.buscar_categoria(id_padre)?;
+
+            if let Some(id) = id {
+                let categorias = self.repository.listar_categorias()?;
+                if descendientes(&categorias, id).contains(&id_padre) {
+                    return Err(ApiError::BusinessRuleViolation(
+                        "Una categoría no puede estar dentro de sí misma ni de sus subcategorías".to_string()
+                    ));
+                }
+            }
+        }
+
+        Ok(NuevaCategoria {
+            nombre,
+            descripcion: request.descripcion.map(|d| d.trim().to_string()).filter(|d| !d.is_empty()),
+            id_padre,
+        })
+    }
+}
+
+/// `raiz` y todas las categorías que cuelgan de ella, a cualquier profundidad
+pub(crate) fn descendientes(categorias: &[Categoria], raiz: Uuid) -> Vec<Uuid> {
+    let mut encontradas = vec![raiz];
+    let mut pendientes = vec![raiz];
+
+    while let Some(actual) = pendientes.pop() {
+        for hija in categorias.iter().filter(|c| c.id_padre == Some(actual)) {
+            if !encontradas.contains(&hija.id) {
+                encontradas.push(hija.id);
+                pendientes.push(hija.id);
+            }
+        }
+    }
+
+    encontradas
+}
+
+/// Mismas reglas que chk_nombre_categoria / chk_nombre_marca, para responder 400 y no 500
+fn validar_nombre(nombre: &str, entidad: &str) -> ApiResult<String> {
+    let nombre = nombre.trim();
+    let longitud = nombre.chars().count();
+
+    if longitud < 2 {
+        return Err(ApiError::InvalidInput(format!(
+            "El nombre de la {} debe tener al menos 2 caracteres",
+            entidad
+        )));
+    }
+    if longitud > 100 {
+        return Err(ApiError::InvalidInput(format!(
+            "El nombre de la {} no puede superar los 100 caracteres",
+            entidad
+        )));
+    }
+
+    Ok(nombre.to_string())
+}
@@ -0,0 +1,111 @@
+use actix_web::{web, HttpResponse, ResponseError, Result};
+use crate::modules::comisiones::model::{
+    ComisionesQuery, LiquidacionResponse, ReglaComisionRequest, ReglaComisionResponse,
+};
+use crate::modules::common::errors::ErrorResponse;
+use crate::state::app_state::AppState;
+
+/// GET /v1/comisiones - Liquidación de comisiones de un mes
+#[utoipa::path(
+    get,
+    path = "/v1/comisiones",
+    tag = "Comisiones",
+    params(ComisionesQuery),
+    responses(
+        (status = 200, description = "Ventas y comisiones de cada vendedor en el periodo", body = LiquidacionResponse),
+        (status = 400, description = "Periodo inválido", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn liquidar_comisiones(
+    state: web::Data<AppState>,
+    query: web::Query<ComisionesQuery>,
+) -> Result<HttpResponse> {
+    let service = &state.comision_service;
+
+    match service.liquidacion(query.periodo.as_deref()) {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// POST /v1/comisiones/reglas - Crear regla de comisión
+#[utoipa::path(
+    post,
+    path = "/v1/comisiones/reglas",
+    tag = "Comisiones",
+    request_body = ReglaComisionRequest,
+    responses(
+        (status = 201, description = "Regla creada exitosamente", body = ReglaComisionResponse),
+        (status = 400, description = "Datos inválidos, la persona no es vendedor o la regla ya existe", body = ErrorResponse),
+        (status = 404, description = "Vendedor o categoría no encontrados", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn crear_regla(
+    state: web::Data<AppState>,
+    body: web::Json<ReglaComisionRequest>,
+) -> Result<HttpResponse> {
+    let service = &state.comision_service;
+
+    match service.crear_regla(body.into_inner()) {
+        Ok(response) => Ok(HttpResponse::Created().json(response)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// GET /v1/comisiones/reglas - Listar reglas de comisión
+#[utoipa::path(
+    get,
+    path = "/v1/comisiones/reglas",
+    tag = "Comisiones",
+    responses(
+        (status = 200, description = "Reglas activas", body = Vec<ReglaComisionResponse>),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn listar_reglas(state: web::Data<AppState>) -> Result<HttpResponse> {
+    let service = &state.comision_service;
+
+    match service.listar_reglas() {
+        Ok(reglas) => Ok(HttpResponse::Ok().json(reglas)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// DELETE /v1/comisiones/reglas/:id - Dar de baja una regla de comisión
+#[utoipa::path(
+    delete,
+    path = "/v1/comisiones/reglas/{id}",
+    tag = "Comisiones",
+    params(
+        ("id" = String, Path, description = "ID de la regla (UUID)")
+    ),
+    responses(
+        (status = 204, description = "Regla dada de baja"),
+        (status = 404, description = "Regla no encontrada", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn desactivar_regla(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    let service = &state.comision_service;
+
+    match service.desactivar_regla(&id) {
+        Ok(()) => Ok(HttpResponse::NoContent().finish()),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/comisiones")
+            .route("", web::get().to(liquidar_comisiones))
+            .route("/reglas", web::post().to(crear_regla))
+            .route("/reglas", web::get().to(listar_reglas))
+            .route("/reglas/{id}", web::delete().to(desactivar_regla))
+    );
+}
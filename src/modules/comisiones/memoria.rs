@@ -0,0 +1,98 @@
+use bigdecimal::BigDecimal;
+use chrono::{NaiveDateTime, Utc};
+use uuid::Uuid;
+use crate::modules::comisiones::model::{LiquidacionVendedor, NuevaReglaComision, ReglaComision};
+use crate::modules::comisiones::repository::{self as comision_repo, ComisionRepository};
+use crate::modules::common::errors::ApiResult;
+use crate::modules::common::memoria::MemoriaRepository;
+use crate::modules::common::types::EstadoVenta;
+
+impl ComisionRepository for MemoriaRepository {
+    fn buscar_regla(&self, id: Uuid) -> ApiResult<ReglaComision> {
+        self.tablas()
+            .reglas_comision
+            .iter()
+            .find(|regla| regla.id == id && regla.activo)
+            .cloned()
+            .ok_or_else(|| comision_repo::regla_no_encontrada(id))
+    }
+
+    fn listar_reglas(&self) -> ApiResult<Vec<ReglaComision>> {
+        let mut reglas: Vec<ReglaComision> =
+            self.tablas().reglas_comision.iter().filter(|regla| regla.activo).cloned().collect();
+        reglas.sort_by_key(|regla| (regla.id_vendedor.is_some(), regla.id_vendedor, regla.id_categoria.is_some(), regla.id_categoria));
+        Ok(reglas)
+    }
+
+    fn crear_regla(&self, nueva_regla: NuevaReglaComision) -> ApiResult<Uuid> {
+        self.transaccion(|tablas| {
+            // Misma regla que idx_reglas_comision_alcance
+            if tablas.reglas_comision.iter().any(|regla| {
+                regla.activo && regla.id_vendedor == nueva_regla.id_vendedor && regla.id_categoria == nueva_regla.id_categoria
+            }) {
+                return Err(comision_repo::regla_duplicada());
+            }
+
+            let ahora = Utc::now().naive_utc();
+            let id = Uuid::new_v4();
+            tablas.reglas_comision.push(ReglaComision {
+                id,
+                id_vendedor: nueva_regla.id_vendedor,
+                id_categoria: nueva_regla.id_categoria,
+                porcentaje: nueva_regla.porcentaje,
+                fecha_creacion: ahora,
+                fecha_actualizacion: ahora,
+                activo: true,
+            });
+            Ok(id)
+        })
+    }
+
+    fn desactivar_regla(&self, id: Uuid) -> ApiResult<()> {
+        self.transaccion(|tablas| {
+            let regla = tablas
+                .reglas_comision
+                .iter_mut()
+                .find(|regla| regla.id == id && regla.activo)
+                .ok_or_else(|| comision_repo::regla_no_encontrada(id))?;
+            regla.activo = false;
+            regla.fecha_actualizacion = Utc::now().naive_utc();
+            Ok(())
+        })
+    }
+
+    fn liquidacion(&self, desde: NaiveDateTime, hasta: NaiveDateTime) -> ApiResult<Vec<LiquidacionVendedor>> {
+        let tablas = self.tablas();
+        let mut liquidacion: Vec<LiquidacionVendedor> = Vec::new();
+
+        for venta in tablas.ventas.iter().filter(|venta| {
+            venta.activo && venta.estado != EstadoVenta::Anulada && venta.fecha >= desde && venta.fecha < hasta
+        }) {
+            let Some(id_vendedor) = venta.id_vendedor else { continue };
+            let Some(vendedor) = tablas.personas.iter().find(|persona| persona.id == id_vendedor) else { continue };
+
+            let posicion = match liquidacion.iter().position(|fila| fila.id_vendedor == id_vendedor) {
+                Some(posicion) => posicion,
+                None => {
+                    liquidacion.push(LiquidacionVendedor {
+                        id_vendedor,
+                        nombre: vendedor.nombre.clone(),
+                        ventas: 0,
+                        total_vendido: BigDecimal::from(0),
+                        comision: BigDecimal::from(0),
+                    });
+                    liquidacion.len() - 1
+                }
+            };
+            let fila = &mut liquidacion[posicion];
+            fila.ventas += 1;
+            for detalle in tablas.detalle_ventas.iter().filter(|detalle| detalle.id_venta == venta.id && detalle.activo) {
+                fila.total_vendido += &detalle.monto;
+                fila.comision += &detalle.comision;
+            }
+        }
+
+        liquidacion.sort_by(|a, b| b.comision.cmp(&a.comision).then_with(|| a.nombre.cmp(&b.nombre)));
+        Ok(liquidacion)
+    }
+}
@@ -0,0 +1,119 @@
+use bigdecimal::{BigDecimal, ToPrimitive};
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Numeric, Text, Uuid as SqlUuid};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use utoipa::{IntoParams, ToSchema};
+use crate::schema::reglas_comision;
+
+// Domain Model for ReglaComision
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = reglas_comision)]
+pub struct ReglaComision {
+    pub id: Uuid,
+    /// None: aplica a todos los vendedores
+    pub id_vendedor: Option<Uuid>,
+    /// None: aplica a todas las categorías; si no, también a sus subcategorías
+    pub id_categoria: Option<Uuid>,
+    /// Porcentaje sobre el subtotal de la línea
+    pub porcentaje: BigDecimal,
+    pub fecha_creacion: NaiveDateTime,
+    pub fecha_actualizacion: NaiveDateTime,
+    pub activo: bool,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = reglas_comision)]
+pub struct NuevaReglaComision {
+    pub id_vendedor: Option<Uuid>,
+    pub id_categoria: Option<Uuid>,
+    pub porcentaje: BigDecimal,
+}
+
+// Query result: commissions earned by one seller in a period
+#[derive(Debug, Clone, QueryableByName)]
+pub struct LiquidacionVendedor {
+    #[diesel(sql_type = SqlUuid)]
+    pub id_vendedor: Uuid,
+    #[diesel(sql_type = Text)]
+    pub nombre: String,
+    #[diesel(sql_type = BigInt)]
+    pub ventas: i64,
+    /// Subtotal de las líneas vendidas
+    #[diesel(sql_type = Numeric)]
+    pub total_vendido: BigDecimal,
+    #[diesel(sql_type = Numeric)]
+    pub comision: BigDecimal,
+}
+
+// DTO for commission rule creation request
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ReglaComisionRequest {
+    /// Persona con perfil VENDEDOR; se omite para todos los vendedores
+    #[schema(example = "440e8400-e29b-41d4-a716-446655440000")]
+    pub id_vendedor: Option<String>,
+    /// Se omite para todas las categorías
+    #[schema(example = "660e8400-e29b-41d4-a716-446655440000")]
+    pub id_categoria: Option<String>,
+    /// Entre 0 y 100, con hasta dos decimales
+    #[schema(example = 2.5)]
+    pub porcentaje: f64,
+}
+
+// DTO for commission rule response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReglaComisionResponse {
+    #[schema(example = "990e8400-e29b-41d4-a716-446655440000")]
+    pub id: String,
+    #[schema(example = "440e8400-e29b-41d4-a716-446655440000")]
+    pub id_vendedor: Option<String>,
+    #[schema(example = "660e8400-e29b-41d4-a716-446655440000")]
+    pub id_categoria: Option<String>,
+    #[schema(example = 2.5)]
+    pub porcentaje: f64,
+}
+
+impl From<ReglaComision> for ReglaComisionResponse {
+    fn from(regla: ReglaComision) -> Self {
+        ReglaComisionResponse {
+            id: regla.id.to_string(),
+            id_vendedor: regla.id_vendedor.map(|id| id.to_string()),
+            id_categoria: regla.id_categoria.map(|id| id.to_string()),
+            porcentaje: regla.porcentaje.to_f64().unwrap_or(0.0),
+        }
+    }
+}
+
+// Query parameters for the commission settlement
+#[derive(Debug, Deserialize, ToSchema, IntoParams)]
+pub struct ComisionesQuery {
+    /// Mes a liquidar (YYYY-MM); por defecto el mes en curso
+    #[schema(example = "2026-10")]
+    pub periodo: Option<String>,
+}
+
+// DTO for the commission settlement
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LiquidacionResponse {
+    #[schema(example = "2026-10")]
+    pub periodo: String,
+    #[schema(example = 385000.0)]
+    pub total_comisiones: f64,
+    /// Vendedores con ventas en el periodo, de mayor a menor comisión
+    pub vendedores: Vec<LiquidacionVendedorResponse>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LiquidacionVendedorResponse {
+    #[schema(example = "440e8400-e29b-41d4-a716-446655440000")]
+    pub id_vendedor: String,
+    #[schema(example = "Carlos Pérez")]
+    pub nombre_vendedor: String,
+    #[schema(example = 12)]
+    pub ventas: i64,
+    #[schema(example = 15400000.0)]
+    pub total_vendido: f64,
+    #[schema(example = 385000.0)]
+    pub comision: f64,
+}
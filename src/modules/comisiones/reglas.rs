@@ -0,0 +1,47 @@
+//! Elección de la regla de comisión de una línea de venta.
+
+use std::cmp::Reverse;
+use bigdecimal::BigDecimal;
+use uuid::Uuid;
+use crate::modules::catalogo::model::Categoria;
+use crate::modules::comisiones::model::ReglaComision;
+use crate::modules::inventarios::costos;
+
+/// Categoría `id_categoria` seguida de sus ancestros, de la más cercana a la raíz
+pub fn categoria_y_ancestros(id_categoria: Option<Uuid>, categorias: &[Categoria]) -> Vec<Uuid> {
+    let mut cadena = Vec::new();
+    let mut actual = id_categoria;
+    // El límite corta un ciclo que la API no permite crear pero la base de datos no impide
+    while let Some(id) = actual.filter(|_| cadena.len() <= categorias.len()) {
+        cadena.push(id);
+        actual = categorias.iter().find(|categoria| categoria.id == id).and_then(|categoria| categoria.id_padre);
+    }
+    cadena
+}
+
+/// Regla más específica para una línea del vendedor `id_vendedor` de un producto en
+/// `categorias` (ver `categoria_y_ancestros`): vendedor y categoría, solo vendedor, solo
+/// categoría y por último la regla general. Entre categorías gana la más cercana al producto.
+pub fn regla_aplicable<'a>(
+    reglas: &'a [ReglaComision],
+    id_vendedor: Uuid,
+    categorias: &[Uuid],
+) -> Option<&'a ReglaComision> {
+    reglas
+        .iter()
+        .filter(|regla| regla.activo && regla.id_vendedor.is_none_or(|id| id == id_vendedor))
+        .filter_map(|regla| {
+            let cercania = match regla.id_categoria {
+                Some(id_categoria) => Some(categorias.iter().position(|id| *id == id_categoria)?),
+                None => None,
+            };
+            Some((regla, cercania))
+        })
+        .max_by_key(|(regla, cercania)| (regla.id_vendedor.is_some(), cercania.is_some(), Reverse(*cercania)))
+        .map(|(regla, _)| regla)
+}
+
+/// Comisión en centavos de una línea de `subtotal` al `porcentaje` indicado
+pub fn calcular_comision(subtotal: &BigDecimal, porcentaje: &BigDecimal) -> BigDecimal {
+    costos::redondear_importe(&(subtotal * porcentaje / BigDecimal::from(100)))
+}
@@ -0,0 +1,158 @@
+use chrono::NaiveDateTime;
+use diesel::dsl::exists;
+use diesel::prelude::*;
+use diesel::r2d2::{ConnectionManager, Pool, PooledConnection};
+use diesel::sql_types::Timestamp;
+use uuid::Uuid;
+use tracing::instrument;
+use crate::modules::auditoria;
+use crate::modules::comisiones::model::{LiquidacionVendedor, NuevaReglaComision, ReglaComision};
+use crate::modules::common::errors::{ApiError, ApiResult};
+use crate::schema::reglas_comision;
+
+pub type DbPool = Pool<ConnectionManager<PgConnection>>;
+pub type DbConnection = PooledConnection<ConnectionManager<PgConnection>>;
+
+/// Acceso a datos de las reglas de comisión (ver `PersonaRepository` para las implementaciones)
+pub trait ComisionRepository: Send + Sync {
+    fn buscar_regla(&self, id: Uuid) -> ApiResult<ReglaComision>;
+
+    /// Reglas activas, las generales primero
+    fn listar_reglas(&self) -> ApiResult<Vec<ReglaComision>>;
+
+    /// Rechaza la regla si ya hay otra activa para el mismo vendedor y categoría
+    fn crear_regla(&self, nueva_regla: NuevaReglaComision) -> ApiResult<Uuid>;
+
+    fn desactivar_regla(&self, id: Uuid) -> ApiResult<()>;
+
+    /// Ventas, subtotal vendido y comisiones de cada vendedor con ventas en `[desde, hasta)`,
+    /// de mayor a menor comisión. Las ventas anuladas no cuentan.
+    fn liquidacion(&self, desde: NaiveDateTime, hasta: NaiveDateTime) -> ApiResult<Vec<LiquidacionVendedor>>;
+}
+
+pub struct PgComisionRepository {
+    pool: DbPool,
+}
+
+impl PgComisionRepository {
+    pub fn new(pool: DbPool) -> Self {
+        PgComisionRepository { pool }
+    }
+
+    fn get_connection(&self) -> ApiResult<DbConnection> {
+        self.pool
+            .get()
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+    }
+}
+
+impl ComisionRepository for PgComisionRepository {
+    #[instrument(skip(self))]
+    fn buscar_regla(&self, id: Uuid) -> ApiResult<ReglaComision> {
+        let mut conn = self.get_connection()?;
+        buscar_regla_activa(&mut conn, id)
+    }
+
+    #[instrument(skip(self))]
+    fn listar_reglas(&self) -> ApiResult<Vec<ReglaComision>> {
+        let mut conn = self.get_connection()?;
+
+        reglas_comision::table
+            .filter(reglas_comision::activo.eq(true))
+            .order((
+                reglas_comision::id_vendedor.asc().nulls_first(),
+                reglas_comision::id_categoria.asc().nulls_first(),
+            ))
+            .select(ReglaComision::as_select())
+            .load(&mut conn)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+    }
+
+    #[instrument(skip(self))]
+    fn crear_regla(&self, nueva_regla: NuevaReglaComision) -> ApiResult<Uuid> {
+        let mut conn = self.get_connection()?;
+
+        conn.transaction::<Uuid, ApiError, _>(|conn| {
+            auditoria::contexto::aplicar(conn)?;
+
+            // Misma regla que idx_reglas_comision_alcance
+            let duplicada: bool = diesel::select(exists(
+                reglas_comision::table
+                    .filter(reglas_comision::activo.eq(true))
+                    .filter(reglas_comision::id_vendedor.is_not_distinct_from(nueva_regla.id_vendedor))
+                    .filter(reglas_comision::id_categoria.is_not_distinct_from(nueva_regla.id_categoria)),
+            ))
+            .get_result(conn)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+            if duplicada {
+                return Err(regla_duplicada());
+            }
+
+            diesel::insert_into(reglas_comision::table)
+                .values(&nueva_regla)
+                .returning(reglas_comision::id)
+                .get_result(conn)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))
+        })
+    }
+
+    #[instrument(skip(self))]
+    fn desactivar_regla(&self, id: Uuid) -> ApiResult<()> {
+        let mut conn = self.get_connection()?;
+
+        conn.transaction::<(), ApiError, _>(|conn| {
+            auditoria::contexto::aplicar(conn)?;
+
+            buscar_regla_activa(conn, id)?;
+            diesel::update(reglas_comision::table.find(id))
+                .set(reglas_comision::activo.eq(false))
+                .execute(conn)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+            Ok(())
+        })
+    }
+
+    #[instrument(skip(self))]
+    fn liquidacion(&self, desde: NaiveDateTime, hasta: NaiveDateTime) -> ApiResult<Vec<LiquidacionVendedor>> {
+        let mut conn = self.get_connection()?;
+
+        diesel::sql_query(
+            "SELECT v.id_vendedor, p.nombre,
+                    COUNT(DISTINCT v.id) AS ventas,
+                    SUM(dv.monto) AS total_vendido,
+                    SUM(dv.comision) AS comision
+             FROM detalle_ventas dv
+             JOIN ventas v ON v.id = dv.id_venta
+             JOIN personas p ON p.id = v.id_vendedor
+             WHERE dv.activo = TRUE AND v.activo = TRUE AND v.estado <> 'ANULADA' AND v.fecha >= $1 AND v.fecha < $2
+             GROUP BY v.id_vendedor, p.nombre
+             ORDER BY comision DESC, p.nombre",
+        )
+        .bind::<Timestamp, _>(desde)
+        .bind::<Timestamp, _>(hasta)
+        .load(&mut conn)
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))
+    }
+}
+
+fn buscar_regla_activa(conn: &mut PgConnection, id: Uuid) -> ApiResult<ReglaComision> {
+    reglas_comision::table
+        .find(id)
+        .filter(reglas_comision::activo.eq(true))
+        .select(ReglaComision::as_select())
+        .first(conn)
+        .map_err(|e| match e {
+            diesel::result::Error::NotFound => regla_no_encontrada(id),
+            _ => ApiError::DatabaseError(e.to_string()),
+        })
+}
+
+pub(crate) fn regla_no_encontrada(id: Uuid) -> ApiError {
+    ApiError::NotFound(format!("Regla de comisión {} no encontrada", id))
+}
+
+pub(crate) fn regla_duplicada() -> ApiError {
+    ApiError::BusinessRuleViolation(
+        "Ya existe una regla de comisión activa para ese vendedor y categoría".to_string()
+    )
+}
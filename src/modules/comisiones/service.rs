@@ -0,0 +1,113 @@
+use std::sync::Arc;
+use bigdecimal::{BigDecimal, ToPrimitive};
+use chrono::{Datelike, Months, NaiveDate, Utc};
+use uuid::Uuid;
+use tracing::instrument;
+use crate::modules::catalogo::repository::CatalogoRepository;
+use crate::modules::comisiones::model::{
+    LiquidacionResponse, LiquidacionVendedorResponse, NuevaReglaComision, ReglaComisionRequest,
+    ReglaComisionResponse,
+};
+use crate::modules::comisiones::repository::ComisionRepository;
+use crate::modules::common::errors::{ApiError, ApiResult};
+use crate::modules::inventarios::costos;
+use crate::modules::personas::repository::PersonaRepository;
+
+pub struct ComisionService {
+    repository: Arc<dyn ComisionRepository>,
+    persona_repo: Arc<dyn PersonaRepository>,
+    catalogo_repo: Arc<dyn CatalogoRepository>,
+}
+
+impl ComisionService {
+    pub fn new(
+        repository: Arc<dyn ComisionRepository>,
+        persona_repo: Arc<dyn PersonaRepository>,
+        catalogo_repo: Arc<dyn CatalogoRepository>,
+    ) -> Self {
+        ComisionService { repository, persona_repo, catalogo_repo }
+    }
+
+    /// Reglas de comisión activas
+    #[instrument(skip(self))]
+    pub fn listar_reglas(&self) -> ApiResult<Vec<ReglaComisionResponse>> {
+        Ok(self.repository.listar_reglas()?.into_iter().map(Into::into).collect())
+    }
+
+    /// Crear una regla para un vendedor, una categoría, ambos o ninguno (la general)
+    #[instrument(skip(self, request))]
+    pub fn crear_regla(&self, request: ReglaComisionRequest) -> ApiResult<ReglaComisionResponse> {
+        let id_vendedor = parse_id_opcional(request.id_vendedor.as_deref(), "vendedor")?;
+        if let Some(id_vendedor) = id_vendedor {
+            self.persona_repo.buscar_vendedor_activo(id_vendedor)?;
+        }
+
+        let id_categoria = parse_id_opcional(request.id_categoria.as_deref(), "categoría")?;
+        if let Some(id_categoria) = id_categoria {
+            self.catalogo_repo.buscar_categoria(id_categoria)?;
+        }
+
+        if !request.porcentaje.is_finite() || !(0.0..=100.0).contains(&request.porcentaje) {
+            return Err(ApiError::InvalidInput("El porcentaje de comisión debe estar entre 0 y 100".to_string()));
+        }
+        let porcentaje = BigDecimal::try_from(request.porcentaje)
+            .map(|porcentaje| costos::redondear_importe(&porcentaje))
+            .map_err(|e| ApiError::InvalidInput(format!("Porcentaje inválido: {}", e)))?;
+
+        let id = self.repository.crear_regla(NuevaReglaComision { id_vendedor, id_categoria, porcentaje })?;
+        Ok(self.repository.buscar_regla(id)?.into())
+    }
+
+    /// Dar de baja una regla; las comisiones ya fijadas no cambian
+    #[instrument(skip(self))]
+    pub fn desactivar_regla(&self, id_str: &str) -> ApiResult<()> {
+        let id = Uuid::parse_str(id_str)
+            .map_err(|_| ApiError::InvalidInput("ID de regla inválido".to_string()))?;
+        self.repository.desactivar_regla(id)
+    }
+
+    /// Comisiones de cada vendedor en el mes `periodo` (YYYY-MM; por defecto el mes en curso)
+    #[instrument(skip(self))]
+    pub fn liquidacion(&self, periodo: Option<&str>) -> ApiResult<LiquidacionResponse> {
+        let desde = match periodo.map(str::trim).filter(|periodo| !periodo.is_empty()) {
+            Some(periodo) => NaiveDate::parse_from_str(&format!("{}-01", periodo), "%Y-%m-%d")
+                .map_err(|_| ApiError::InvalidInput("Formato de periodo inválido (YYYY-MM)".to_string()))?,
+            None => {
+                let hoy = Utc::now().date_naive();
+                hoy.with_day(1).unwrap_or(hoy)
+            }
+        };
+        let hasta = desde
+            .checked_add_months(Months::new(1))
+            .ok_or_else(|| ApiError::InvalidInput("Periodo fuera de rango".to_string()))?;
+
+        let vendedores = self.repository.liquidacion(
+            desde.and_hms_opt(0, 0, 0).expect("la medianoche siempre es válida"),
+            hasta.and_hms_opt(0, 0, 0).expect("la medianoche siempre es válida"),
+        )?;
+        let total: BigDecimal = vendedores.iter().map(|vendedor| &vendedor.comision).sum();
+
+        Ok(LiquidacionResponse {
+            periodo: desde.format("%Y-%m").to_string(),
+            total_comisiones: total.to_f64().unwrap_or(0.0),
+            vendedores: vendedores
+                .into_iter()
+                .map(|vendedor| LiquidacionVendedorResponse {
+                    id_vendedor: vendedor.id_vendedor.to_string(),
+                    nombre_vendedor: vendedor.nombre,
+                    ventas: vendedor.ventas,
+                    total_vendido: vendedor.total_vendido.to_f64().unwrap_or(0.0),
+                    comision: vendedor.comision.to_f64().unwrap_or(0.0),
+                })
+                .collect(),
+        })
+    }
+}
+
+/// Id opcional de la petición; vacío equivale a no indicarlo
+fn parse_id_opcional(id: Option<&str>, entidad: &str) -> ApiResult<Option<Uuid>> {
+    id.map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(|id| Uuid::parse_str(id).map_err(|_| ApiError::InvalidInput(format!("ID de {} inválido", entidad))))
+        .transpose()
+}
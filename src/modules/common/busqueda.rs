@@ -0,0 +1,126 @@
+//! Búsqueda por texto de los listados (`?q=`).
+//!
+//! En PostgreSQL combina texto completo con la configuración `espanol_sin_acentos` (cada palabra
+//! buscada se trata como prefijo, para que "teclado mec" encuentre "Teclado mecánico") y similitud
+//! de trigramas sobre `sin_acentos(LOWER(columna))`, que tolera errores de escritura. Ambas
+//! expresiones coinciden con los índices de la migración `busqueda`.
+
+use diesel::dsl::sql;
+use diesel::expression::BoxableExpression;
+use diesel::pg::Pg;
+use diesel::sql_types::{Bool, Float, Text};
+use crate::modules::common::errors::{ApiError, ApiResult};
+
+/// Longitud máxima del texto buscado
+pub const LONGITUD_MAXIMA: usize = 100;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Busqueda {
+    /// Texto buscado, sin espacios sobrantes
+    pub texto: String,
+    /// Palabras del texto (letras y dígitos), en el orden en que se escribieron
+    pub palabras: Vec<String>,
+}
+
+impl Busqueda {
+    /// `None` si no se buscó nada (parámetro ausente o en blanco)
+    pub fn parse(q: Option<String>) -> ApiResult<Option<Busqueda>> {
+        let Some(texto) = q.map(|q| q.split_whitespace().collect::<Vec<_>>().join(" ")) else {
+            return Ok(None);
+        };
+
+        if texto.chars().count() > LONGITUD_MAXIMA {
+            return Err(ApiError::InvalidInput(format!(
+                "El texto de búsqueda no puede superar los {} caracteres",
+                LONGITUD_MAXIMA
+            )));
+        }
+
+        let palabras: Vec<String> = texto
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|palabra| !palabra.is_empty())
+            .map(str::to_string)
+            .collect();
+
+        if palabras.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(Busqueda { texto, palabras }))
+    }
+
+    /// Solo dígitos: se busca como prefijo de documento en lugar de por nombre
+    pub fn es_documento(&self) -> bool {
+        self.texto.chars().all(|c| c.is_ascii_digit())
+    }
+
+    /// Consulta para `to_tsquery`: "teclado:* & mec:*". Las palabras solo contienen letras y
+    /// dígitos, así que no pueden alterar la sintaxis de la consulta.
+    fn consulta_prefijos(&self) -> String {
+        self.palabras
+            .iter()
+            .map(|palabra| format!("{}:*", palabra))
+            .collect::<Vec<_>>()
+            .join(" & ")
+    }
+
+    /// Condición sobre `columna` (nombre calificado, p.ej. "productos.nombre")
+    pub fn coincide<QS: 'static>(&self, columna: &str) -> Box<dyn BoxableExpression<QS, Pg, SqlType = Bool>> {
+        Box::new(
+            sql::<Bool>("(to_tsvector('espanol_sin_acentos', ")
+                .sql(columna)
+                .sql(") @@ to_tsquery('espanol_sin_acentos', ")
+                .bind::<Text, _>(self.consulta_prefijos())
+                .sql(") OR sin_acentos(LOWER(")
+                .bind::<Text, _>(self.texto.clone())
+                .sql(")) <% sin_acentos(LOWER(")
+                .sql(columna)
+                .sql(")))"),
+        )
+    }
+
+    /// Relevancia de cada fila para ordenar los resultados de mayor a menor
+    pub fn relevancia<QS: 'static>(&self, columna: &str) -> Box<dyn BoxableExpression<QS, Pg, SqlType = Float>> {
+        Box::new(
+            sql::<Float>("(ts_rank(to_tsvector('espanol_sin_acentos', ")
+                .sql(columna)
+                .sql("), to_tsquery('espanol_sin_acentos', ")
+                .bind::<Text, _>(self.consulta_prefijos())
+                .sql(")) + word_similarity(sin_acentos(LOWER(")
+                .bind::<Text, _>(self.texto.clone())
+                .sql(")), sin_acentos(LOWER(")
+                .sql(columna)
+                .sql("))))"),
+        )
+    }
+
+    /// Aproximación para el repositorio en memoria: cada palabra buscada es prefijo de alguna
+    /// palabra del texto, sin distinguir mayúsculas ni acentos
+    pub fn coincide_con(&self, texto: &str) -> bool {
+        let texto = normalizar(texto);
+        let palabras_texto: Vec<&str> = texto.split(|c: char| !c.is_alphanumeric()).collect();
+
+        self.palabras
+            .iter()
+            .map(|palabra| normalizar(palabra))
+            .all(|buscada| palabras_texto.iter().any(|palabra| palabra.starts_with(&buscada)))
+    }
+}
+
+/// Minúsculas y sin tildes ni diéresis, como `sin_acentos(LOWER(...))`
+pub fn normalizar(texto: &str) -> String {
+    texto
+        .to_lowercase()
+        .chars()
+        .map(|c| match c {
+            'á' | 'à' | 'â' | 'ä' | 'ã' => 'a',
+            'é' | 'è' | 'ê' | 'ë' => 'e',
+            'í' | 'ì' | 'î' | 'ï' => 'i',
+            'ó' | 'ò' | 'ô' | 'ö' | 'õ' => 'o',
+            'ú' | 'ù' | 'û' | 'ü' => 'u',
+            'ñ' => 'n',
+            'ç' => 'c',
+            otro => otro,
+        })
+        .collect()
+}
@@ -1,3 +1,4 @@
+use actix_web::http::{header, StatusCode};
 use actix_web::{HttpResponse, ResponseError};
 use thiserror::Error;
 use utoipa::ToSchema;
@@ -27,10 +28,37 @@ pub enum ApiError {
 
     #[error("Producto no encontrado o inactivo")]
     ProductNotFound,
+
+    #[error("El cuerpo de la petición supera el límite de {0} bytes")]
+    PayloadTooLarge(usize),
+
+    #[error("Demasiadas peticiones, reintente en {0} segundos")]
+    TooManyRequests(u64),
 }
 
 impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::NotFound(_) | ApiError::ProductNotFound => StatusCode::NOT_FOUND,
+            ApiError::InvalidInput(_)
+            | ApiError::BusinessRuleViolation(_)
+            | ApiError::InsufficientStock
+            | ApiError::InactiveClient => StatusCode::BAD_REQUEST,
+            ApiError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            ApiError::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::DatabaseError(_) | ApiError::InternalError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
     fn error_response(&self) -> HttpResponse {
+        // Se registra dentro del span de la petición, con su request_id
+        match self {
+            ApiError::DatabaseError(_) | ApiError::InternalError(_) => {
+                tracing::error!(error = %self, "Request failed with internal error")
+            }
+            _ => tracing::info!(error = %self, "Request rejected"),
+        }
+
         match self {
             ApiError::NotFound(_) => HttpResponse::NotFound().json(ErrorResponse {
                 error: self.to_string(),
@@ -56,6 +84,16 @@ impl ResponseError for ApiError {
                 error: self.to_string(),
                 code: "PRODUCT_NOT_FOUND",
             }),
+            ApiError::PayloadTooLarge(_) => HttpResponse::PayloadTooLarge().json(ErrorResponse {
+                error: self.to_string(),
+                code: "PAYLOAD_TOO_LARGE",
+            }),
+            ApiError::TooManyRequests(retry_after) => HttpResponse::TooManyRequests()
+                .insert_header((header::RETRY_AFTER, retry_after.to_string()))
+                .json(ErrorResponse {
+                    error: self.to_string(),
+                    code: "RATE_LIMITED",
+                }),
             _ => HttpResponse::InternalServerError().json(ErrorResponse {
                 error: self.to_string(),
                 code: "INTERNAL_ERROR",
@@ -0,0 +1,86 @@
+//! Implementación en memoria de los repositorios, para probar los servicios sin PostgreSQL.
+//!
+//! Un único `MemoriaRepository` implementa todos los traits sobre las mismas tablas, de modo que
+//! puede reproducir lo que en la base de datos hacen los triggers: al guardar una venta valida el
+//! stock (`validar_stock_venta`), lo descuenta y registra la SALIDA (`actualizar_inventario_venta`),
+//! salvo en la venta de un pedido, cuyo stock ya salió en los despachos. Aquí viven las tablas;
+//! cada módulo implementa su trait en su `memoria.rs`, junto a su repositorio de PostgreSQL.
+
+use std::sync::{Arc, Mutex, MutexGuard};
+use crate::modules::cajas::model::{SesionCaja, TotalCierreCaja};
+use crate::modules::catalogo::model::{Categoria, Marca};
+use crate::modules::comisiones::model::ReglaComision;
+use crate::modules::common::errors::ApiResult;
+use crate::modules::cotizaciones::model::{Cotizacion, DetalleCotizacion};
+use crate::modules::inventarios::costos::MetodoValorizacion;
+use crate::modules::inventarios::model::{CapaCosto, DetalleInventario, Inventario, Lote, NumeroSerie};
+use crate::modules::pedidos::model::{DetallePedido, Pedido};
+use crate::modules::personas::model::Persona;
+use crate::modules::precios::model::{ListaPrecios, PrecioLista, Promocion};
+use crate::modules::productos::model::{CodigoBarrasProducto, Producto, UnidadProducto};
+use crate::modules::ventas::model::{DetalleVenta, LoteDetalleVenta, Pago, SerieDetalleVenta, Venta};
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Tablas {
+    pub(crate) personas: Vec<Persona>,
+    pub(crate) productos: Vec<Producto>,
+    pub(crate) categorias: Vec<Categoria>,
+    pub(crate) marcas: Vec<Marca>,
+    pub(crate) codigos_barra: Vec<CodigoBarrasProducto>,
+    pub(crate) unidades_producto: Vec<UnidadProducto>,
+    pub(crate) inventarios: Vec<Inventario>,
+    pub(crate) detalle_inventarios: Vec<DetalleInventario>,
+    pub(crate) lotes: Vec<Lote>,
+    pub(crate) numeros_serie: Vec<NumeroSerie>,
+    pub(crate) capas_costo: Vec<CapaCosto>,
+    pub(crate) ventas: Vec<Venta>,
+    pub(crate) detalle_ventas: Vec<DetalleVenta>,
+    pub(crate) detalle_ventas_lotes: Vec<LoteDetalleVenta>,
+    pub(crate) detalle_ventas_series: Vec<SerieDetalleVenta>,
+    pub(crate) reglas_comision: Vec<ReglaComision>,
+    pub(crate) pagos: Vec<Pago>,
+    pub(crate) sesiones_caja: Vec<SesionCaja>,
+    pub(crate) totales_cierre_caja: Vec<TotalCierreCaja>,
+    pub(crate) listas_precios: Vec<ListaPrecios>,
+    pub(crate) precios_lista: Vec<PrecioLista>,
+    pub(crate) promociones: Vec<Promocion>,
+    pub(crate) cotizaciones: Vec<Cotizacion>,
+    pub(crate) detalle_cotizaciones: Vec<DetalleCotizacion>,
+    pub(crate) pedidos: Vec<Pedido>,
+    pub(crate) detalle_pedidos: Vec<DetallePedido>,
+}
+
+/// Repositorio en memoria. Es barato de clonar: los clones comparten las mismas tablas.
+#[derive(Debug, Clone, Default)]
+pub struct MemoriaRepository {
+    tablas: Arc<Mutex<Tablas>>,
+    pub(crate) metodo_valorizacion: MetodoValorizacion,
+}
+
+impl MemoriaRepository {
+    pub fn new() -> Self {
+        MemoriaRepository::default()
+    }
+
+    /// Repositorio vacío que costea las entradas con `metodo_valorizacion`
+    pub fn con_metodo_valorizacion(metodo_valorizacion: MetodoValorizacion) -> Self {
+        MemoriaRepository {
+            metodo_valorizacion,
+            ..MemoriaRepository::default()
+        }
+    }
+
+    pub(crate) fn tablas(&self) -> MutexGuard<'_, Tablas> {
+        self.tablas.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Ejecuta `operacion` sobre una copia de las tablas y solo la confirma si no falla,
+    /// igual que una transacción
+    pub(crate) fn transaccion<T>(&self, operacion: impl FnOnce(&mut Tablas) -> ApiResult<T>) -> ApiResult<T> {
+        let mut tablas = self.tablas();
+        let mut copia = tablas.clone();
+        let resultado = operacion(&mut copia)?;
+        *tablas = copia;
+        Ok(resultado)
+    }
+}
@@ -1,2 +1,5 @@
 pub mod types;
 pub mod errors;
+pub mod memoria;
+pub mod busqueda;
+pub mod validacion;
@@ -7,7 +7,11 @@ use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
 // Import SQL types from schema
-use crate::schema::sql_types::{TipoPerfil as TipoPerfilSql, TipoMovimiento as TipoMovimientoSql};
+use crate::schema::sql_types::{
+    AccionAuditoria as AccionAuditoriaSql, EstadoCotizacion as EstadoCotizacionSql, EstadoPedido as EstadoPedidoSql, EstadoSerie as EstadoSerieSql, EstadoSesionCaja as EstadoSesionCajaSql,
+    EstadoVenta as EstadoVentaSql, MetodoPago as MetodoPagoSql, TipoCodigoBarras as TipoCodigoBarrasSql, TipoPerfil as TipoPerfilSql,
+    TipoMovimiento as TipoMovimientoSql, TipoPromocion as TipoPromocionSql,
+};
 
 // Enum for TipoPerfil
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, AsExpression, FromSqlRow, ToSchema)]
@@ -57,6 +61,36 @@ pub enum TipoMovimiento {
     Ajuste,
 }
 
+impl TipoMovimiento {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TipoMovimiento::Entrada => "ENTRADA",
+            TipoMovimiento::Salida => "SALIDA",
+            TipoMovimiento::Ajuste => "AJUSTE",
+        }
+    }
+
+    /// Efecto de un movimiento sobre el stock, con el signo normalizado por tipo: ENTRADA suma y
+    /// SALIDA resta sea cual sea el signo guardado (la venta guarda la SALIDA negativa y el
+    /// movimiento manual positiva); AJUSTE conserva su signo.
+    pub fn efecto_en_stock(&self, cantidad: i32) -> i32 {
+        match self {
+            TipoMovimiento::Entrada => cantidad.abs(),
+            TipoMovimiento::Salida => -cantidad.abs(),
+            TipoMovimiento::Ajuste => cantidad,
+        }
+    }
+
+    /// Tipo del movimiento que compensa a uno de este tipo al revertirlo
+    pub fn compensatorio(&self) -> TipoMovimiento {
+        match self {
+            TipoMovimiento::Entrada => TipoMovimiento::Salida,
+            TipoMovimiento::Salida => TipoMovimiento::Entrada,
+            TipoMovimiento::Ajuste => TipoMovimiento::Ajuste,
+        }
+    }
+}
+
 impl ToSql<TipoMovimientoSql, Pg> for TipoMovimiento {
     fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
         match *self {
@@ -78,3 +112,397 @@ impl FromSql<TipoMovimientoSql, Pg> for TipoMovimiento {
         }
     }
 }
+
+// Enum for AccionAuditoria
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, AsExpression, FromSqlRow, ToSchema)]
+#[diesel(sql_type = AccionAuditoriaSql)]
+#[schema(example = "ACTUALIZAR")]
+pub enum AccionAuditoria {
+    #[serde(rename = "CREAR")]
+    Crear,
+    #[serde(rename = "ACTUALIZAR")]
+    Actualizar,
+    #[serde(rename = "DESACTIVAR")]
+    Desactivar,
+}
+
+impl ToSql<AccionAuditoriaSql, Pg> for AccionAuditoria {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        match *self {
+            AccionAuditoria::Crear => out.write_all(b"CREAR")?,
+            AccionAuditoria::Actualizar => out.write_all(b"ACTUALIZAR")?,
+            AccionAuditoria::Desactivar => out.write_all(b"DESACTIVAR")?,
+        }
+        Ok(serialize::IsNull::No)
+    }
+}
+
+impl FromSql<AccionAuditoriaSql, Pg> for AccionAuditoria {
+    fn from_sql(bytes: diesel::pg::PgValue) -> deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"CREAR" => Ok(AccionAuditoria::Crear),
+            b"ACTUALIZAR" => Ok(AccionAuditoria::Actualizar),
+            b"DESACTIVAR" => Ok(AccionAuditoria::Desactivar),
+            _ => Err("Unrecognized enum variant".into()),
+        }
+    }
+}
+
+// Enum for TipoCodigoBarras
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, AsExpression, FromSqlRow, ToSchema)]
+#[diesel(sql_type = TipoCodigoBarrasSql)]
+#[schema(example = "EAN13")]
+pub enum TipoCodigoBarras {
+    #[serde(rename = "EAN8")]
+    Ean8,
+    #[serde(rename = "UPCA")]
+    UpcA,
+    #[serde(rename = "EAN13")]
+    Ean13,
+}
+
+impl TipoCodigoBarras {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TipoCodigoBarras::Ean8 => "EAN8",
+            TipoCodigoBarras::UpcA => "UPCA",
+            TipoCodigoBarras::Ean13 => "EAN13",
+        }
+    }
+
+    /// Número de dígitos del código impreso, incluido el de control
+    pub fn longitud(&self) -> usize {
+        match self {
+            TipoCodigoBarras::Ean8 => 8,
+            TipoCodigoBarras::UpcA => 12,
+            TipoCodigoBarras::Ean13 => 13,
+        }
+    }
+
+    pub fn por_longitud(longitud: usize) -> Option<TipoCodigoBarras> {
+        match longitud {
+            8 => Some(TipoCodigoBarras::Ean8),
+            12 => Some(TipoCodigoBarras::UpcA),
+            13 => Some(TipoCodigoBarras::Ean13),
+            _ => None,
+        }
+    }
+}
+
+impl ToSql<TipoCodigoBarrasSql, Pg> for TipoCodigoBarras {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(self.as_str().as_bytes())?;
+        Ok(serialize::IsNull::No)
+    }
+}
+
+impl FromSql<TipoCodigoBarrasSql, Pg> for TipoCodigoBarras {
+    fn from_sql(bytes: diesel::pg::PgValue) -> deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"EAN8" => Ok(TipoCodigoBarras::Ean8),
+            b"UPCA" => Ok(TipoCodigoBarras::UpcA),
+            b"EAN13" => Ok(TipoCodigoBarras::Ean13),
+            _ => Err("Unrecognized enum variant".into()),
+        }
+    }
+}
+
+// Enum for EstadoSerie
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, AsExpression, FromSqlRow, ToSchema)]
+#[diesel(sql_type = EstadoSerieSql)]
+#[schema(example = "EN_STOCK")]
+pub enum EstadoSerie {
+    #[serde(rename = "EN_STOCK")]
+    EnStock,
+    #[serde(rename = "VENDIDO")]
+    Vendido,
+    #[serde(rename = "DEVUELTO")]
+    Devuelto,
+}
+
+impl EstadoSerie {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EstadoSerie::EnStock => "EN_STOCK",
+            EstadoSerie::Vendido => "VENDIDO",
+            EstadoSerie::Devuelto => "DEVUELTO",
+        }
+    }
+
+    /// La unidad está en el almacén y se puede vender
+    pub fn en_almacen(&self) -> bool {
+        matches!(self, EstadoSerie::EnStock | EstadoSerie::Devuelto)
+    }
+}
+
+impl ToSql<EstadoSerieSql, Pg> for EstadoSerie {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(self.as_str().as_bytes())?;
+        Ok(serialize::IsNull::No)
+    }
+}
+
+impl FromSql<EstadoSerieSql, Pg> for EstadoSerie {
+    fn from_sql(bytes: diesel::pg::PgValue) -> deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"EN_STOCK" => Ok(EstadoSerie::EnStock),
+            b"VENDIDO" => Ok(EstadoSerie::Vendido),
+            b"DEVUELTO" => Ok(EstadoSerie::Devuelto),
+            _ => Err("Unrecognized enum variant".into()),
+        }
+    }
+}
+
+// Enum for MetodoPago
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, AsExpression, FromSqlRow, ToSchema)]
+#[diesel(sql_type = MetodoPagoSql)]
+#[schema(example = "EFECTIVO")]
+pub enum MetodoPago {
+    #[serde(rename = "EFECTIVO")]
+    Efectivo,
+    #[serde(rename = "TARJETA")]
+    Tarjeta,
+    #[serde(rename = "TRANSFERENCIA")]
+    Transferencia,
+}
+
+impl MetodoPago {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MetodoPago::Efectivo => "EFECTIVO",
+            MetodoPago::Tarjeta => "TARJETA",
+            MetodoPago::Transferencia => "TRANSFERENCIA",
+        }
+    }
+}
+
+impl ToSql<MetodoPagoSql, Pg> for MetodoPago {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(self.as_str().as_bytes())?;
+        Ok(serialize::IsNull::No)
+    }
+}
+
+impl FromSql<MetodoPagoSql, Pg> for MetodoPago {
+    fn from_sql(bytes: diesel::pg::PgValue) -> deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"EFECTIVO" => Ok(MetodoPago::Efectivo),
+            b"TARJETA" => Ok(MetodoPago::Tarjeta),
+            b"TRANSFERENCIA" => Ok(MetodoPago::Transferencia),
+            _ => Err("Unrecognized enum variant".into()),
+        }
+    }
+}
+
+// Enum for EstadoVenta
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, AsExpression, FromSqlRow, ToSchema)]
+#[diesel(sql_type = EstadoVentaSql)]
+#[schema(example = "PAGADA")]
+pub enum EstadoVenta {
+    #[serde(rename = "PENDIENTE_PAGO")]
+    PendientePago,
+    #[serde(rename = "PAGADA")]
+    Pagada,
+    #[serde(rename = "ANULADA")]
+    Anulada,
+}
+
+impl EstadoVenta {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EstadoVenta::PendientePago => "PENDIENTE_PAGO",
+            EstadoVenta::Pagada => "PAGADA",
+            EstadoVenta::Anulada => "ANULADA",
+        }
+    }
+}
+
+impl ToSql<EstadoVentaSql, Pg> for EstadoVenta {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(self.as_str().as_bytes())?;
+        Ok(serialize::IsNull::No)
+    }
+}
+
+impl FromSql<EstadoVentaSql, Pg> for EstadoVenta {
+    fn from_sql(bytes: diesel::pg::PgValue) -> deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"PENDIENTE_PAGO" => Ok(EstadoVenta::PendientePago),
+            b"PAGADA" => Ok(EstadoVenta::Pagada),
+            b"ANULADA" => Ok(EstadoVenta::Anulada),
+            _ => Err("Unrecognized enum variant".into()),
+        }
+    }
+}
+
+// Enum for EstadoSesionCaja
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, AsExpression, FromSqlRow, ToSchema)]
+#[diesel(sql_type = EstadoSesionCajaSql)]
+#[schema(example = "ABIERTA")]
+pub enum EstadoSesionCaja {
+    #[serde(rename = "ABIERTA")]
+    Abierta,
+    #[serde(rename = "CERRADA")]
+    Cerrada,
+}
+
+impl EstadoSesionCaja {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EstadoSesionCaja::Abierta => "ABIERTA",
+            EstadoSesionCaja::Cerrada => "CERRADA",
+        }
+    }
+}
+
+impl ToSql<EstadoSesionCajaSql, Pg> for EstadoSesionCaja {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(self.as_str().as_bytes())?;
+        Ok(serialize::IsNull::No)
+    }
+}
+
+impl FromSql<EstadoSesionCajaSql, Pg> for EstadoSesionCaja {
+    fn from_sql(bytes: diesel::pg::PgValue) -> deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"ABIERTA" => Ok(EstadoSesionCaja::Abierta),
+            b"CERRADA" => Ok(EstadoSesionCaja::Cerrada),
+            _ => Err("Unrecognized enum variant".into()),
+        }
+    }
+}
+
+// Enum for TipoPromocion
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, AsExpression, FromSqlRow, ToSchema)]
+#[diesel(sql_type = TipoPromocionSql)]
+#[schema(example = "LLEVA_PAGA")]
+pub enum TipoPromocion {
+    #[serde(rename = "PORCENTAJE")]
+    Porcentaje,
+    #[serde(rename = "LLEVA_PAGA")]
+    LlevaPaga,
+    #[serde(rename = "PRECIO_PAQUETE")]
+    PrecioPaquete,
+}
+
+impl TipoPromocion {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TipoPromocion::Porcentaje => "PORCENTAJE",
+            TipoPromocion::LlevaPaga => "LLEVA_PAGA",
+            TipoPromocion::PrecioPaquete => "PRECIO_PAQUETE",
+        }
+    }
+}
+
+impl ToSql<TipoPromocionSql, Pg> for TipoPromocion {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(self.as_str().as_bytes())?;
+        Ok(serialize::IsNull::No)
+    }
+}
+
+impl FromSql<TipoPromocionSql, Pg> for TipoPromocion {
+    fn from_sql(bytes: diesel::pg::PgValue) -> deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"PORCENTAJE" => Ok(TipoPromocion::Porcentaje),
+            b"LLEVA_PAGA" => Ok(TipoPromocion::LlevaPaga),
+            b"PRECIO_PAQUETE" => Ok(TipoPromocion::PrecioPaquete),
+            _ => Err("Unrecognized enum variant".into()),
+        }
+    }
+}
+
+// Enum for EstadoCotizacion
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, AsExpression, FromSqlRow, ToSchema)]
+#[diesel(sql_type = EstadoCotizacionSql)]
+#[schema(example = "ENVIADA")]
+pub enum EstadoCotizacion {
+    #[serde(rename = "BORRADOR")]
+    Borrador,
+    #[serde(rename = "ENVIADA")]
+    Enviada,
+    #[serde(rename = "ACEPTADA")]
+    Aceptada,
+    #[serde(rename = "VENCIDA")]
+    Vencida,
+    #[serde(rename = "CONVERTIDA")]
+    Convertida,
+}
+
+impl EstadoCotizacion {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EstadoCotizacion::Borrador => "BORRADOR",
+            EstadoCotizacion::Enviada => "ENVIADA",
+            EstadoCotizacion::Aceptada => "ACEPTADA",
+            EstadoCotizacion::Vencida => "VENCIDA",
+            EstadoCotizacion::Convertida => "CONVERTIDA",
+        }
+    }
+}
+
+impl ToSql<EstadoCotizacionSql, Pg> for EstadoCotizacion {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(self.as_str().as_bytes())?;
+        Ok(serialize::IsNull::No)
+    }
+}
+
+impl FromSql<EstadoCotizacionSql, Pg> for EstadoCotizacion {
+    fn from_sql(bytes: diesel::pg::PgValue) -> deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"BORRADOR" => Ok(EstadoCotizacion::Borrador),
+            b"ENVIADA" => Ok(EstadoCotizacion::Enviada),
+            b"ACEPTADA" => Ok(EstadoCotizacion::Aceptada),
+            b"VENCIDA" => Ok(EstadoCotizacion::Vencida),
+            b"CONVERTIDA" => Ok(EstadoCotizacion::Convertida),
+            _ => Err("Unrecognized enum variant".into()),
+        }
+    }
+}
+
+// Enum for EstadoPedido
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, AsExpression, FromSqlRow, ToSchema)]
+#[diesel(sql_type = EstadoPedidoSql)]
+#[schema(example = "EN_PREPARACION")]
+pub enum EstadoPedido {
+    #[serde(rename = "PENDIENTE")]
+    Pendiente,
+    #[serde(rename = "EN_PREPARACION")]
+    EnPreparacion,
+    #[serde(rename = "DESPACHADO")]
+    Despachado,
+    #[serde(rename = "ENTREGADO")]
+    Entregado,
+}
+
+impl EstadoPedido {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EstadoPedido::Pendiente => "PENDIENTE",
+            EstadoPedido::EnPreparacion => "EN_PREPARACION",
+            EstadoPedido::Despachado => "DESPACHADO",
+            EstadoPedido::Entregado => "ENTREGADO",
+        }
+    }
+}
+
+impl ToSql<EstadoPedidoSql, Pg> for EstadoPedido {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(self.as_str().as_bytes())?;
+        Ok(serialize::IsNull::No)
+    }
+}
+
+impl FromSql<EstadoPedidoSql, Pg> for EstadoPedido {
+    fn from_sql(bytes: diesel::pg::PgValue) -> deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"PENDIENTE" => Ok(EstadoPedido::Pendiente),
+            b"EN_PREPARACION" => Ok(EstadoPedido::EnPreparacion),
+            b"DESPACHADO" => Ok(EstadoPedido::Despachado),
+            b"ENTREGADO" => Ok(EstadoPedido::Entregado),
+            _ => Err("Unrecognized enum variant".into()),
+        }
+    }
+}
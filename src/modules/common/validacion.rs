@@ -0,0 +1,21 @@
+//! Lectura de los datos de entrada que comparten los servicios: identificadores e importes.
+
+use bigdecimal::BigDecimal;
+use uuid::Uuid;
+use crate::modules::common::errors::{ApiError, ApiResult};
+use crate::modules::inventarios::costos;
+
+/// Id recibido como texto; `entidad` nombra lo que identifica en el mensaje de error
+pub fn parse_id(id_str: &str, entidad: &str) -> ApiResult<Uuid> {
+    Uuid::parse_str(id_str.trim()).map_err(|_| ApiError::InvalidInput(format!("ID de {} inválido", entidad)))
+}
+
+/// Importe no negativo, redondeado a centavos
+pub fn importe(valor: f64, campo: &str) -> ApiResult<BigDecimal> {
+    if !valor.is_finite() || valor < 0.0 {
+        return Err(ApiError::InvalidInput(format!("El {} no puede ser negativo", campo)));
+    }
+    BigDecimal::try_from(valor)
+        .map(|valor| costos::redondear_importe(&valor))
+        .map_err(|e| ApiError::InvalidInput(format!("El {} es inválido: {}", campo, e)))
+}
@@ -0,0 +1,56 @@
+use actix_web::{web, HttpResponse, ResponseError, Result};
+use crate::modules::common::errors::ErrorResponse;
+use crate::modules::consistencia::model::{ConsistenciaResponse, RepararStockRequest};
+use crate::state::app_state::AppState;
+
+/// GET /v1/consistencia/stock - Comparar el stock con la suma de movimientos
+#[utoipa::path(
+    get,
+    path = "/v1/consistencia/stock",
+    tag = "Inventario",
+    responses(
+        (status = 200, description = "Resultado de la verificación; lista los productos descuadrados", body = ConsistenciaResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn verificar_stock(state: web::Data<AppState>) -> Result<HttpResponse> {
+    let service = &state.consistencia_service;
+
+    match service.verificar() {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// POST /v1/consistencia/stock/reparar - Registrar AJUSTES correctivos
+#[utoipa::path(
+    post,
+    path = "/v1/consistencia/stock/reparar",
+    tag = "Inventario",
+    request_body = RepararStockRequest,
+    responses(
+        (status = 200, description = "Productos reparados, con el AJUSTE registrado para cada uno", body = ConsistenciaResponse),
+        (status = 400, description = "Datos de entrada inválidos", body = ErrorResponse),
+        (status = 404, description = "Persona no encontrada", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn reparar_stock(
+    state: web::Data<AppState>,
+    body: web::Json<RepararStockRequest>,
+) -> Result<HttpResponse> {
+    let service = &state.consistencia_service;
+
+    match service.reparar(body.into_inner()) {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/consistencia")
+            .route("/stock", web::get().to(verificar_stock))
+            .route("/stock/reparar", web::post().to(reparar_stock))
+    );
+}
@@ -0,0 +1,84 @@
+use diesel::prelude::*;
+use diesel::sql_types::{Integer, Text, Uuid as SqlUuid};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use utoipa::ToSchema;
+
+// Query result: product whose stock does not match its movements
+#[derive(Debug, Clone, PartialEq, Eq, QueryableByName)]
+pub struct DescuadreStock {
+    #[diesel(sql_type = SqlUuid)]
+    pub id_producto: Uuid,
+    #[diesel(sql_type = Text)]
+    pub nombre: String,
+    /// Responsable del inventario; se le atribuye el AJUSTE si no se indica otra persona
+    #[diesel(sql_type = SqlUuid)]
+    pub id_responsable: Uuid,
+    #[diesel(sql_type = Integer)]
+    pub cantidad_disponible: i32,
+    #[diesel(sql_type = Integer)]
+    pub suma_movimientos: i32,
+}
+
+impl DescuadreStock {
+    /// Lo que hay que sumar a los movimientos para que cuadren con el stock
+    pub fn diferencia(&self) -> i32 {
+        self.cantidad_disponible - self.suma_movimientos
+    }
+}
+
+// DTO for a product with drift
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct DescuadreStockResponse {
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000")]
+    pub id_producto: String,
+    #[schema(example = "Laptop Dell")]
+    pub nombre_producto: String,
+    #[schema(example = 12)]
+    pub cantidad_disponible: i32,
+    /// Suma de movimientos con el signo normalizado por tipo
+    #[schema(example = 10)]
+    pub suma_movimientos: i32,
+    #[schema(example = 2)]
+    pub diferencia: i32,
+    /// AJUSTE correctivo registrado (sólo al reparar)
+    #[schema(example = "770e8400-e29b-41d4-a716-446655440000")]
+    pub id_ajuste: Option<String>,
+}
+
+impl DescuadreStockResponse {
+    pub fn new(descuadre: DescuadreStock, id_ajuste: Option<Uuid>) -> Self {
+        DescuadreStockResponse {
+            id_producto: descuadre.id_producto.to_string(),
+            diferencia: descuadre.diferencia(),
+            nombre_producto: descuadre.nombre,
+            cantidad_disponible: descuadre.cantidad_disponible,
+            suma_movimientos: descuadre.suma_movimientos,
+            id_ajuste: id_ajuste.map(|id| id.to_string()),
+        }
+    }
+}
+
+// DTO for consistency check/repair response
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ConsistenciaResponse {
+    /// true si, tras la operación, todo el stock cuadra con los movimientos
+    #[schema(example = false)]
+    pub consistente: bool,
+    /// Productos descuadrados (o reparados, en el caso de la reparación)
+    pub descuadres: Vec<DescuadreStockResponse>,
+}
+
+// DTO for repair request
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct RepararStockRequest {
+    /// Limita la reparación a un producto
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000")]
+    pub id_producto: Option<String>,
+    /// Persona a la que se atribuyen los AJUSTES (por defecto, el responsable de cada inventario)
+    #[schema(example = "660e8400-e29b-41d4-a716-446655440000")]
+    pub id_persona: Option<String>,
+    /// Motivo que se añade a la explicación del AJUSTE
+    #[schema(example = "Conteo físico de fin de mes")]
+    pub motivo: Option<String>,
+}
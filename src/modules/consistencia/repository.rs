@@ -0,0 +1,131 @@
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::r2d2::{ConnectionManager, Pool, PooledConnection};
+use diesel::sql_types::{Nullable, Uuid as SqlUuid};
+use uuid::Uuid;
+use tracing::instrument;
+use crate::modules::auditoria;
+use crate::modules::common::errors::{ApiError, ApiResult};
+use crate::modules::common::types::TipoMovimiento;
+use crate::modules::consistencia::model::DescuadreStock;
+use crate::modules::inventarios::costos;
+use crate::modules::inventarios::repository as inventario_repo;
+use crate::schema::{detalle_inventarios, inventarios};
+
+pub type DbPool = Pool<ConnectionManager<PgConnection>>;
+pub type DbConnection = PooledConnection<ConnectionManager<PgConnection>>;
+
+/// Suma de los movimientos de `i.id_producto` con el signo normalizado igual que
+/// `TipoMovimiento::efecto_en_stock`
+pub(crate) const SUMA_MOVIMIENTOS: &str = "
+    SELECT COALESCE(SUM(CASE d.tipo_movimiento
+        WHEN 'ENTRADA' THEN ABS(d.cantidad)
+        WHEN 'SALIDA' THEN -ABS(d.cantidad)
+        ELSE d.cantidad
+    END), 0)::INT
+    FROM detalle_inventarios d
+    WHERE d.id_producto = i.id_producto AND d.activo = TRUE";
+
+pub struct ConsistenciaRepository {
+    pool: DbPool,
+}
+
+impl ConsistenciaRepository {
+    pub fn new(pool: DbPool) -> Self {
+        ConsistenciaRepository { pool }
+    }
+
+    fn get_connection(&self) -> ApiResult<DbConnection> {
+        self.pool
+            .get()
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+    }
+
+    #[instrument(skip(self))]
+    pub fn listar_descuadres(&self, id_producto: Option<Uuid>) -> ApiResult<Vec<DescuadreStock>> {
+        let mut conn = self.get_connection()?;
+        descuadres(&mut conn, id_producto).map_err(|e| ApiError::DatabaseError(e.to_string()))
+    }
+
+    /// Registra por cada producto descuadrado un AJUSTE por la diferencia, sin tocar
+    /// `cantidad_disponible`: el stock se da por bueno y se corrige el histórico. El AJUSTE se
+    /// valora al costo promedio del stock, que ya cubren las capas de costo.
+    /// Devuelve los descuadres junto con el id del AJUSTE registrado.
+    #[instrument(skip(self, motivo))]
+    pub fn reparar(
+        &self,
+        id_producto: Option<Uuid>,
+        id_persona: Option<Uuid>,
+        motivo: Option<&str>,
+    ) -> ApiResult<Vec<(DescuadreStock, Uuid)>> {
+        let mut conn = self.get_connection()?;
+
+        conn.transaction::<_, ApiError, _>(|conn| {
+            auditoria::contexto::aplicar(conn)?;
+
+            // Bloquear los inventarios para que ningún movimiento concurrente cambie el cálculo
+            let activos = inventarios::table.filter(inventarios::activo.eq(true));
+            match id_producto {
+                Some(id_producto) => activos
+                    .filter(inventarios::id_producto.eq(id_producto))
+                    .select(inventarios::id)
+                    .for_update()
+                    .load::<Uuid>(conn)?,
+                None => activos.select(inventarios::id).for_update().load::<Uuid>(conn)?,
+            };
+
+            let mut reparados = Vec::new();
+            for descuadre in descuadres(conn, id_producto)? {
+                let mut observaciones = format!(
+                    "Conciliación de stock: stock {}, suma de movimientos {}, diferencia {:+}",
+                    descuadre.cantidad_disponible,
+                    descuadre.suma_movimientos,
+                    descuadre.diferencia()
+                );
+                if let Some(motivo) = motivo.map(str::trim).filter(|m| !m.is_empty()) {
+                    observaciones.push_str(". ");
+                    observaciones.push_str(motivo);
+                }
+
+                let capas = inventario_repo::capas_bloqueadas(conn, descuadre.id_producto)?;
+                let costo_unitario = costos::costo_promedio(descuadre.cantidad_disponible, &capas);
+
+                let id_ajuste = diesel::insert_into(detalle_inventarios::table)
+                    .values((
+                        detalle_inventarios::id_producto.eq(descuadre.id_producto),
+                        detalle_inventarios::tipo_movimiento.eq(TipoMovimiento::Ajuste),
+                        detalle_inventarios::fecha.eq(Utc::now().naive_utc()),
+                        detalle_inventarios::id_persona.eq(id_persona.unwrap_or(descuadre.id_responsable)),
+                        detalle_inventarios::cantidad.eq(descuadre.diferencia()),
+                        detalle_inventarios::observaciones.eq(observaciones),
+                        detalle_inventarios::costo_unitario.eq(costo_unitario),
+                    ))
+                    .returning(detalle_inventarios::id)
+                    .get_result::<Uuid>(conn)?;
+
+                reparados.push((descuadre, id_ajuste));
+            }
+
+            Ok(reparados)
+        })
+    }
+}
+
+/// Productos activos (o sólo `id_producto`) cuyo stock no cuadra con la suma de sus movimientos
+pub(crate) fn descuadres(conn: &mut PgConnection, id_producto: Option<Uuid>) -> QueryResult<Vec<DescuadreStock>> {
+    diesel::sql_query(format!(
+        "SELECT * FROM (
+            SELECT i.id_producto, p.nombre, i.id_persona AS id_responsable,
+                   i.cantidad_disponible, ({}) AS suma_movimientos
+            FROM inventarios i
+            JOIN productos p ON p.id = i.id_producto
+            WHERE i.activo = TRUE AND p.activo = TRUE
+              AND ($1 IS NULL OR i.id_producto = $1)
+        ) s
+        WHERE s.cantidad_disponible <> s.suma_movimientos
+        ORDER BY s.nombre",
+        SUMA_MOVIMIENTOS
+    ))
+    .bind::<Nullable<SqlUuid>, _>(id_producto)
+    .load(conn)
+}
@@ -0,0 +1,72 @@
+use std::sync::Arc;
+use uuid::Uuid;
+use tracing::{instrument, warn};
+use crate::modules::common::errors::{ApiError, ApiResult};
+use crate::modules::consistencia::model::{ConsistenciaResponse, DescuadreStockResponse, RepararStockRequest};
+use crate::modules::consistencia::repository::ConsistenciaRepository;
+use crate::modules::personas::repository::PersonaRepository;
+
+pub struct ConsistenciaService {
+    repository: ConsistenciaRepository,
+    persona_repo: Arc<dyn PersonaRepository>,
+}
+
+impl ConsistenciaService {
+    pub fn new(repository: ConsistenciaRepository, persona_repo: Arc<dyn PersonaRepository>) -> Self {
+        ConsistenciaService { repository, persona_repo }
+    }
+
+    /// Compara el stock de cada producto con la suma de sus movimientos de inventario
+    #[instrument(skip(self))]
+    pub fn verificar(&self) -> ApiResult<ConsistenciaResponse> {
+        let descuadres = self.repository.listar_descuadres(None)?;
+
+        for descuadre in &descuadres {
+            warn!(
+                id_producto = %descuadre.id_producto,
+                cantidad_disponible = descuadre.cantidad_disponible,
+                suma_movimientos = descuadre.suma_movimientos,
+                "Stock drift detected"
+            );
+        }
+
+        Ok(ConsistenciaResponse {
+            consistente: descuadres.is_empty(),
+            descuadres: descuadres
+                .into_iter()
+                .map(|descuadre| DescuadreStockResponse::new(descuadre, None))
+                .collect(),
+        })
+    }
+
+    /// Registra un AJUSTE correctivo por cada producto descuadrado
+    #[instrument(skip(self, request))]
+    pub fn reparar(&self, request: RepararStockRequest) -> ApiResult<ConsistenciaResponse> {
+        let id_producto = request
+            .id_producto
+            .map(|id| Uuid::parse_str(id.trim()))
+            .transpose()
+            .map_err(|_| ApiError::InvalidInput("ID de producto inválido".to_string()))?;
+
+        let id_persona = request
+            .id_persona
+            .map(|id| Uuid::parse_str(id.trim()))
+            .transpose()
+            .map_err(|_| ApiError::InvalidInput("ID de persona inválido".to_string()))?;
+
+        // La persona indicada debe existir y estar activa
+        if let Some(id_persona) = id_persona {
+            self.persona_repo.buscar_por_id(id_persona)?;
+        }
+
+        let reparados = self.repository.reparar(id_producto, id_persona, request.motivo.as_deref())?;
+
+        Ok(ConsistenciaResponse {
+            consistente: true,
+            descuadres: reparados
+                .into_iter()
+                .map(|(descuadre, id_ajuste)| DescuadreStockResponse::new(descuadre, Some(id_ajuste)))
+                .collect(),
+        })
+    }
+}
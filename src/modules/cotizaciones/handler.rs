@@ -0,0 +1,213 @@
+use actix_web::{web, HttpResponse, ResponseError, Result};
+use crate::modules::common::errors::ErrorResponse;
+use crate::modules::cotizaciones::model::{
+    ConvertirCotizacionRequest, CotizacionRequest, CotizacionResponse, CotizacionesQuery,
+};
+use crate::modules::ventas::model::VentaCreadaResponse;
+use crate::state::app_state::AppState;
+
+/// POST /v1/cotizaciones - Crear cotización
+#[utoipa::path(
+    post,
+    path = "/v1/cotizaciones",
+    tag = "Cotizaciones",
+    request_body = CotizacionRequest,
+    responses(
+        (status = 201, description = "Cotización creada en BORRADOR con los precios vigentes", body = CotizacionResponse),
+        (status = 400, description = "Datos inválidos o cliente inactivo", body = ErrorResponse),
+        (status = 404, description = "Cliente, vendedor o producto no encontrado", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn crear_cotizacion(
+    state: web::Data<AppState>,
+    body: web::Json<CotizacionRequest>,
+) -> Result<HttpResponse> {
+    let service = &state.cotizacion_service;
+
+    match service.crear(body.into_inner()) {
+        Ok(response) => Ok(HttpResponse::Created().json(response)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// GET /v1/cotizaciones - Listar cotizaciones
+#[utoipa::path(
+    get,
+    path = "/v1/cotizaciones",
+    tag = "Cotizaciones",
+    params(CotizacionesQuery),
+    responses(
+        (status = 200, description = "Cotizaciones de la más reciente a la más antigua", body = Vec<CotizacionResponse>),
+        (status = 400, description = "Filtro inválido", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn listar_cotizaciones(
+    state: web::Data<AppState>,
+    query: web::Query<CotizacionesQuery>,
+) -> Result<HttpResponse> {
+    let service = &state.cotizacion_service;
+
+    match service.listar(query.into_inner()) {
+        Ok(cotizaciones) => Ok(HttpResponse::Ok().json(cotizaciones)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// GET /v1/cotizaciones/:id - Obtener cotización
+#[utoipa::path(
+    get,
+    path = "/v1/cotizaciones/{id}",
+    tag = "Cotizaciones",
+    params(
+        ("id" = String, Path, description = "ID de la cotización (UUID)")
+    ),
+    responses(
+        (status = 200, description = "Cotización con sus líneas", body = CotizacionResponse),
+        (status = 400, description = "ID inválido", body = ErrorResponse),
+        (status = 404, description = "Cotización no encontrada", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn obtener_cotizacion(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    let service = &state.cotizacion_service;
+
+    match service.obtener(&id) {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// PUT /v1/cotizaciones/:id - Editar una cotización en BORRADOR
+#[utoipa::path(
+    put,
+    path = "/v1/cotizaciones/{id}",
+    tag = "Cotizaciones",
+    params(
+        ("id" = String, Path, description = "ID de la cotización (UUID)")
+    ),
+    request_body = CotizacionRequest,
+    responses(
+        (status = 200, description = "Cotización actualizada y vuelta a cotizar", body = CotizacionResponse),
+        (status = 400, description = "Datos inválidos, cotización vencida o que ya no está en BORRADOR", body = ErrorResponse),
+        (status = 404, description = "Cotización, cliente o producto no encontrado", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn actualizar_cotizacion(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<CotizacionRequest>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    let service = &state.cotizacion_service;
+
+    match service.actualizar(&id, body.into_inner()) {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// POST /v1/cotizaciones/:id/enviar - Enviar la cotización al cliente
+#[utoipa::path(
+    post,
+    path = "/v1/cotizaciones/{id}/enviar",
+    tag = "Cotizaciones",
+    params(
+        ("id" = String, Path, description = "ID de la cotización (UUID)")
+    ),
+    responses(
+        (status = 200, description = "Cotización ENVIADA", body = CotizacionResponse),
+        (status = 400, description = "Cotización vencida o que no está en BORRADOR", body = ErrorResponse),
+        (status = 404, description = "Cotización no encontrada", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn enviar_cotizacion(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    let service = &state.cotizacion_service;
+
+    match service.enviar(&id) {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// POST /v1/cotizaciones/:id/aceptar - Registrar que el cliente aceptó la cotización
+#[utoipa::path(
+    post,
+    path = "/v1/cotizaciones/{id}/aceptar",
+    tag = "Cotizaciones",
+    params(
+        ("id" = String, Path, description = "ID de la cotización (UUID)")
+    ),
+    responses(
+        (status = 200, description = "Cotización ACEPTADA", body = CotizacionResponse),
+        (status = 400, description = "Cotización vencida o que no está ENVIADA", body = ErrorResponse),
+        (status = 404, description = "Cotización no encontrada", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn aceptar_cotizacion(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    let service = &state.cotizacion_service;
+
+    match service.aceptar(&id) {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// POST /v1/cotizaciones/:id/convertir - Convertir una cotización aceptada en venta
+#[utoipa::path(
+    post,
+    path = "/v1/cotizaciones/{id}/convertir",
+    tag = "Cotizaciones",
+    params(
+        ("id" = String, Path, description = "ID de la cotización (UUID)")
+    ),
+    request_body = ConvertirCotizacionRequest,
+    responses(
+        (status = 201, description = "Venta creada a partir de la cotización", body = VentaCreadaResponse),
+        (status = 400, description = "Cotización vencida, no ACEPTADA o ya convertida, stock insuficiente o pagos inválidos", body = ErrorResponse),
+        (status = 404, description = "Cotización, caja o producto no encontrado", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn convertir_cotizacion(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<ConvertirCotizacionRequest>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    let service = &state.cotizacion_service;
+
+    match service.convertir(&id, body.into_inner()) {
+        Ok(response) => Ok(HttpResponse::Created().json(response)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/cotizaciones")
+            .route("", web::post().to(crear_cotizacion))
+            .route("", web::get().to(listar_cotizaciones))
+            .route("/{id}", web::get().to(obtener_cotizacion))
+            .route("/{id}", web::put().to(actualizar_cotizacion))
+            .route("/{id}/enviar", web::post().to(enviar_cotizacion))
+            .route("/{id}/aceptar", web::post().to(aceptar_cotizacion))
+            .route("/{id}/convertir", web::post().to(convertir_cotizacion))
+    );
+}
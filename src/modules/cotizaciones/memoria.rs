@@ -0,0 +1,153 @@
+use std::cmp::Reverse;
+use chrono::{NaiveDate, NaiveDateTime, Utc};
+use uuid::Uuid;
+use crate::modules::common::errors::ApiResult;
+use crate::modules::common::memoria::{MemoriaRepository, Tablas};
+use crate::modules::common::types::EstadoCotizacion;
+use crate::modules::cotizaciones::model::{Cotizacion, DetalleCotizacion, NuevaCotizacion, NuevoDetalleCotizacion};
+use crate::modules::cotizaciones::repository::{self as cotizacion_repo, CotizacionRepository};
+
+impl Tablas {
+    pub(crate) fn cotizacion_mut(&mut self, id: Uuid) -> ApiResult<&mut Cotizacion> {
+        self.cotizaciones
+            .iter_mut()
+            .find(|cotizacion| cotizacion.id == id && cotizacion.activo)
+            .ok_or_else(|| cotizacion_repo::cotizacion_no_encontrada(id))
+    }
+
+    fn insertar_detalles_cotizacion(&mut self, detalles: Vec<NuevoDetalleCotizacion>, ahora: NaiveDateTime) {
+        self.detalle_cotizaciones.extend(detalles.into_iter().map(|detalle| DetalleCotizacion {
+            id: Uuid::new_v4(),
+            id_cotizacion: detalle.id_cotizacion,
+            linea: detalle.linea,
+            id_producto: detalle.id_producto,
+            id_unidad: detalle.id_unidad,
+            cantidad: detalle.cantidad,
+            cantidad_unidad: detalle.cantidad_unidad,
+            precio_unitario: detalle.precio_unitario,
+            descuento: detalle.descuento,
+            monto: detalle.monto,
+            id_lista_precios: detalle.id_lista_precios,
+            id_promocion: detalle.id_promocion,
+            fecha_creacion: ahora,
+            fecha_actualizacion: ahora,
+            activo: true,
+        }));
+    }
+}
+
+impl CotizacionRepository for MemoriaRepository {
+    fn crear(&self, nueva_cotizacion: NuevaCotizacion, detalles: Vec<NuevoDetalleCotizacion>) -> ApiResult<Uuid> {
+        self.transaccion(|tablas| {
+            let ahora = Utc::now().naive_utc();
+            let id = nueva_cotizacion.id;
+            tablas.cotizaciones.push(Cotizacion {
+                id,
+                id_persona: nueva_cotizacion.id_persona,
+                id_vendedor: nueva_cotizacion.id_vendedor,
+                sucursal: nueva_cotizacion.sucursal,
+                estado: EstadoCotizacion::Borrador,
+                fecha_vencimiento: nueva_cotizacion.fecha_vencimiento,
+                monto: nueva_cotizacion.monto,
+                observaciones: nueva_cotizacion.observaciones,
+                id_venta: None,
+                fecha_creacion: ahora,
+                fecha_actualizacion: ahora,
+                activo: true,
+            });
+            tablas.insertar_detalles_cotizacion(detalles, ahora);
+            Ok(id)
+        })
+    }
+
+    fn buscar(&self, id: Uuid) -> ApiResult<(Cotizacion, Vec<DetalleCotizacion>)> {
+        let mut tablas = self.tablas();
+        let cotizacion = tablas.cotizacion_mut(id)?.clone();
+        let mut detalles: Vec<DetalleCotizacion> = tablas
+            .detalle_cotizaciones
+            .iter()
+            .filter(|detalle| detalle.id_cotizacion == id && detalle.activo)
+            .cloned()
+            .collect();
+        detalles.sort_by_key(|detalle| detalle.linea);
+        Ok((cotizacion, detalles))
+    }
+
+    fn listar(&self, id_cliente: Option<Uuid>, estado: Option<EstadoCotizacion>, hoy: NaiveDate) -> ApiResult<Vec<Cotizacion>> {
+        let mut cotizaciones: Vec<Cotizacion> = self
+            .tablas()
+            .cotizaciones
+            .iter()
+            .filter(|cotizacion| cotizacion.activo)
+            .filter(|cotizacion| id_cliente.is_none_or(|id| cotizacion.id_persona == id))
+            .filter(|cotizacion| estado.is_none_or(|estado| cotizacion_repo::estado_vigente(cotizacion, hoy) == estado))
+            .cloned()
+            .collect();
+        cotizaciones.sort_by_key(|cotizacion| Reverse(cotizacion.fecha_creacion));
+        Ok(cotizaciones)
+    }
+
+    fn reemplazar(
+        &self,
+        cotizacion: NuevaCotizacion,
+        detalles: Vec<NuevoDetalleCotizacion>,
+        hoy: NaiveDate,
+    ) -> ApiResult<()> {
+        self.transaccion(|tablas| {
+            let ahora = Utc::now().naive_utc();
+            let actual = tablas.cotizacion_mut(cotizacion.id)?;
+            cotizacion_repo::validar_estado(actual, EstadoCotizacion::Borrador, hoy)?;
+
+            actual.id_persona = cotizacion.id_persona;
+            actual.id_vendedor = cotizacion.id_vendedor;
+            actual.sucursal = cotizacion.sucursal;
+            actual.fecha_vencimiento = cotizacion.fecha_vencimiento;
+            actual.monto = cotizacion.monto;
+            actual.observaciones = cotizacion.observaciones;
+            actual.fecha_actualizacion = ahora;
+            for detalle in tablas
+                .detalle_cotizaciones
+                .iter_mut()
+                .filter(|detalle| detalle.id_cotizacion == cotizacion.id && detalle.activo)
+            {
+                detalle.activo = false;
+                detalle.fecha_actualizacion = ahora;
+            }
+            tablas.insertar_detalles_cotizacion(detalles, ahora);
+            Ok(())
+        })
+    }
+
+    fn cambiar_estado(
+        &self,
+        id: Uuid,
+        desde: EstadoCotizacion,
+        hacia: EstadoCotizacion,
+        hoy: NaiveDate,
+    ) -> ApiResult<Cotizacion> {
+        self.transaccion(|tablas| {
+            let cotizacion = tablas.cotizacion_mut(id)?;
+            cotizacion_repo::validar_estado(cotizacion, desde, hoy)?;
+            cotizacion.estado = hacia;
+            cotizacion.fecha_actualizacion = Utc::now().naive_utc();
+            Ok(cotizacion.clone())
+        })
+    }
+
+    fn vencer(&self, hoy: NaiveDate) -> ApiResult<usize> {
+        self.transaccion(|tablas| {
+            let ahora = Utc::now().naive_utc();
+            let mut vencidas = 0;
+            for cotizacion in tablas.cotizaciones.iter_mut().filter(|cotizacion| cotizacion.activo) {
+                let estado = cotizacion_repo::estado_vigente(cotizacion, hoy);
+                if estado == cotizacion.estado {
+                    continue;
+                }
+                cotizacion.estado = estado;
+                cotizacion.fecha_actualizacion = ahora;
+                vencidas += 1;
+            }
+            Ok(vencidas)
+        })
+    }
+}
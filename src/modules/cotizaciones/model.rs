@@ -0,0 +1,233 @@
+use std::fmt;
+use std::str::FromStr;
+use bigdecimal::BigDecimal;
+use chrono::{NaiveDate, NaiveDateTime};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use utoipa::{IntoParams, ToSchema};
+use crate::modules::common::types::EstadoCotizacion;
+use crate::modules::precios::model::{LineaPrecioRequest, OrigenPrecio};
+use crate::modules::ventas::model::PagoRequest;
+use crate::schema::{cotizaciones, detalle_cotizaciones};
+
+/// Precios con que se convierte una cotización en venta
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PoliticaPreciosCotizacion {
+    /// Los cotizados, aunque la lista o las promociones hayan cambiado desde entonces
+    #[default]
+    Mantener,
+    /// Los del momento de la venta, como en cualquier otra venta
+    Actualizar,
+}
+
+impl PoliticaPreciosCotizacion {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PoliticaPreciosCotizacion::Mantener => "MANTENER",
+            PoliticaPreciosCotizacion::Actualizar => "ACTUALIZAR",
+        }
+    }
+}
+
+impl fmt::Display for PoliticaPreciosCotizacion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for PoliticaPreciosCotizacion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "mantener" | "keep" => Ok(PoliticaPreciosCotizacion::Mantener),
+            "actualizar" | "refresh" => Ok(PoliticaPreciosCotizacion::Actualizar),
+            otro => Err(format!(
+                "Política de precios de cotización inválida '{}'. Valores permitidos: mantener, actualizar",
+                otro
+            )),
+        }
+    }
+}
+
+// Domain Model for Cotizacion
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = cotizaciones)]
+pub struct Cotizacion {
+    pub id: Uuid,
+    /// Cliente al que se cotiza
+    pub id_persona: Uuid,
+    pub id_vendedor: Option<Uuid>,
+    /// Si se indica, la venta debe registrarse en una caja de esa sucursal
+    pub sucursal: Option<String>,
+    pub estado: EstadoCotizacion,
+    /// Último día en que se puede aceptar y convertir
+    pub fecha_vencimiento: NaiveDate,
+    /// Suma de los montos de las líneas
+    pub monto: BigDecimal,
+    pub observaciones: Option<String>,
+    /// Venta en que se convirtió
+    pub id_venta: Option<Uuid>,
+    pub fecha_creacion: NaiveDateTime,
+    pub fecha_actualizacion: NaiveDateTime,
+    pub activo: bool,
+}
+
+// Domain Model for DetalleCotizacion
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable, Associations)]
+#[diesel(belongs_to(Cotizacion, foreign_key = id_cotizacion))]
+#[diesel(table_name = detalle_cotizaciones)]
+pub struct DetalleCotizacion {
+    pub id: Uuid,
+    pub id_cotizacion: Uuid,
+    /// Posición de la línea en la cotización, desde 1
+    pub linea: i32,
+    pub id_producto: Uuid,
+    /// Unidad cotizada; None para la unidad base
+    pub id_unidad: Option<Uuid>,
+    /// Cantidad en la unidad base del producto
+    pub cantidad: i32,
+    /// Cantidad en la unidad cotizada
+    pub cantidad_unidad: i32,
+    /// Precio de una unidad de la cotizada, antes del descuento de la promoción
+    pub precio_unitario: BigDecimal,
+    pub descuento: BigDecimal,
+    /// Monto de la línea, ya descontada la promoción
+    pub monto: BigDecimal,
+    pub id_lista_precios: Option<Uuid>,
+    pub id_promocion: Option<Uuid>,
+    pub fecha_creacion: NaiveDateTime,
+    pub fecha_actualizacion: NaiveDateTime,
+    pub activo: bool,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = cotizaciones)]
+pub struct NuevaCotizacion {
+    pub id: Uuid,
+    pub id_persona: Uuid,
+    pub id_vendedor: Option<Uuid>,
+    pub sucursal: Option<String>,
+    pub fecha_vencimiento: NaiveDate,
+    pub monto: BigDecimal,
+    pub observaciones: Option<String>,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = detalle_cotizaciones)]
+pub struct NuevoDetalleCotizacion {
+    pub id_cotizacion: Uuid,
+    pub linea: i32,
+    pub id_producto: Uuid,
+    pub id_unidad: Option<Uuid>,
+    pub cantidad: i32,
+    pub cantidad_unidad: i32,
+    pub precio_unitario: BigDecimal,
+    pub descuento: BigDecimal,
+    pub monto: BigDecimal,
+    pub id_lista_precios: Option<Uuid>,
+    pub id_promocion: Option<Uuid>,
+}
+
+// DTO for creating or editing a quotation
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CotizacionRequest {
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000")]
+    pub id_cliente: String,
+    /// Persona con perfil VENDEDOR que prepara la cotización
+    #[schema(example = "440e8400-e29b-41d4-a716-446655440000")]
+    pub id_vendedor: Option<String>,
+    /// Si se indica, la venta debe registrarse en una caja de esa sucursal
+    #[schema(example = "Bogotá Centro")]
+    pub sucursal: Option<String>,
+    /// Último día de validez (YYYY-MM-DD); por defecto, dentro de 15 días
+    #[schema(example = "2026-11-30")]
+    pub fecha_vencimiento: Option<String>,
+    #[schema(example = "Entrega en la bodega del cliente")]
+    pub observaciones: Option<String>,
+    pub detalles: Vec<LineaPrecioRequest>,
+}
+
+// DTO for converting an accepted quotation into a sale
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ConvertirCotizacionRequest {
+    /// Sesión de caja abierta en la que se registra la venta
+    #[schema(example = "bb0e8400-e29b-41d4-a716-446655440000")]
+    pub id_sesion_caja: String,
+    /// Igual que en `POST /v1/ventas`: sin pagos, la venta queda PENDIENTE_PAGO
+    #[serde(default)]
+    pub pagos: Vec<PagoRequest>,
+    #[serde(default)]
+    pub credito: bool,
+    /// Números de serie de cada línea, en el orden de la cotización; solo en productos
+    /// serializados
+    #[serde(default)]
+    #[schema(example = json!([[], ["SN-5CD1234XYZ"]]))]
+    pub numeros_serie: Vec<Vec<String>>,
+}
+
+// Query parameters for listing quotations
+#[derive(Debug, Deserialize, IntoParams, ToSchema)]
+pub struct CotizacionesQuery {
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000")]
+    pub id_cliente: Option<String>,
+    /// BORRADOR, ENVIADA, ACEPTADA, VENCIDA o CONVERTIDA
+    #[schema(example = "ENVIADA")]
+    pub estado: Option<String>,
+}
+
+// DTO for quotation response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CotizacionResponse {
+    #[schema(example = "cc0e8400-e29b-41d4-a716-446655440000")]
+    pub id: String,
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000")]
+    pub id_cliente: String,
+    #[schema(example = "440e8400-e29b-41d4-a716-446655440000")]
+    pub id_vendedor: Option<String>,
+    #[schema(example = "Bogotá Centro")]
+    pub sucursal: Option<String>,
+    pub estado: EstadoCotizacion,
+    #[schema(example = "2026-11-30")]
+    pub fecha_vencimiento: String,
+    /// Descuento de las promociones, ya restado del total
+    #[schema(example = 80000.0)]
+    pub descuento: f64,
+    #[schema(example = 2320000.0)]
+    pub total: f64,
+    #[schema(example = "Entrega en la bodega del cliente")]
+    pub observaciones: Option<String>,
+    /// Venta en que se convirtió
+    #[schema(example = "770e8400-e29b-41d4-a716-446655440000")]
+    pub id_venta: Option<String>,
+    #[schema(example = "2026-11-15 10:30:00")]
+    pub fecha_creacion: String,
+    pub detalles: Vec<DetalleCotizacionResponse>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DetalleCotizacionResponse {
+    #[schema(example = "660e8400-e29b-41d4-a716-446655440000")]
+    pub id_producto: String,
+    #[schema(example = "Laptop HP Pavilion")]
+    pub nombre_producto: String,
+    #[schema(example = "unidad")]
+    pub unidad: String,
+    #[schema(example = 2)]
+    pub cantidad: i32,
+    /// Precio de una unidad de la cotizada, antes del descuento de la promoción
+    #[schema(example = 1200000.0)]
+    pub precio_unitario: f64,
+    #[schema(example = 0.0)]
+    pub descuento: f64,
+    #[schema(example = 2400000.0)]
+    pub subtotal: f64,
+    /// Regla que fijó el precio de la línea
+    pub origen_precio: OrigenPrecio,
+    #[schema(example = "aa0e8400-e29b-41d4-a716-446655440000")]
+    pub id_lista_precios: Option<String>,
+    #[schema(example = "ab0e8400-e29b-41d4-a716-446655440000")]
+    pub id_promocion: Option<String>,
+}
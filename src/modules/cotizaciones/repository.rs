@@ -0,0 +1,306 @@
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use diesel::r2d2::{ConnectionManager, Pool, PooledConnection};
+use uuid::Uuid;
+use tracing::instrument;
+use crate::modules::auditoria;
+use crate::modules::common::errors::{ApiError, ApiResult};
+use crate::modules::common::types::EstadoCotizacion;
+use crate::modules::cotizaciones::model::{Cotizacion, DetalleCotizacion, NuevaCotizacion, NuevoDetalleCotizacion};
+use crate::schema::{cotizaciones, detalle_cotizaciones};
+
+pub type DbPool = Pool<ConnectionManager<PgConnection>>;
+pub type DbConnection = PooledConnection<ConnectionManager<PgConnection>>;
+
+/// Estados en que una cotización todavía puede vencer
+pub const ESTADOS_ABIERTOS: [EstadoCotizacion; 3] =
+    [EstadoCotizacion::Borrador, EstadoCotizacion::Enviada, EstadoCotizacion::Aceptada];
+
+/// Acceso a datos de las cotizaciones (ver `PersonaRepository` para las implementaciones)
+pub trait CotizacionRepository: Send + Sync {
+    fn crear(&self, nueva_cotizacion: NuevaCotizacion, detalles: Vec<NuevoDetalleCotizacion>) -> ApiResult<Uuid>;
+
+    /// Cotización activa con sus líneas, en orden
+    fn buscar(&self, id: Uuid) -> ApiResult<(Cotizacion, Vec<DetalleCotizacion>)>;
+
+    /// Cotizaciones activas, de la más reciente a la más antigua. El filtro de estado usa el
+    /// estado a `hoy` (ver `estado_vigente`)
+    fn listar(&self, id_cliente: Option<Uuid>, estado: Option<EstadoCotizacion>, hoy: NaiveDate) -> ApiResult<Vec<Cotizacion>>;
+
+    /// Reemplaza los datos y las líneas de una cotización en BORRADOR
+    fn reemplazar(
+        &self,
+        cotizacion: NuevaCotizacion,
+        detalles: Vec<NuevoDetalleCotizacion>,
+        hoy: NaiveDate,
+    ) -> ApiResult<()>;
+
+    /// Pasa la cotización de `desde` a `hacia`, si sigue en `desde` y no ha vencido
+    fn cambiar_estado(
+        &self,
+        id: Uuid,
+        desde: EstadoCotizacion,
+        hacia: EstadoCotizacion,
+        hoy: NaiveDate,
+    ) -> ApiResult<Cotizacion>;
+
+    /// Marca VENCIDA las cotizaciones abiertas cuyo último día fue anterior a `hoy`. Solo lo
+    /// hacen las operaciones que cambian cotizaciones; las consultas calculan el estado al leer
+    fn vencer(&self, hoy: NaiveDate) -> ApiResult<usize>;
+}
+
+pub struct PgCotizacionRepository {
+    pool: DbPool,
+}
+
+impl PgCotizacionRepository {
+    pub fn new(pool: DbPool) -> Self {
+        PgCotizacionRepository { pool }
+    }
+
+    fn get_connection(&self) -> ApiResult<DbConnection> {
+        self.pool
+            .get()
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+    }
+}
+
+impl CotizacionRepository for PgCotizacionRepository {
+    #[instrument(skip(self, nueva_cotizacion, detalles))]
+    fn crear(&self, nueva_cotizacion: NuevaCotizacion, detalles: Vec<NuevoDetalleCotizacion>) -> ApiResult<Uuid> {
+        let mut conn = self.get_connection()?;
+
+        conn.transaction::<Uuid, ApiError, _>(|conn| {
+            auditoria::contexto::aplicar(conn)?;
+
+            diesel::insert_into(cotizaciones::table)
+                .values(&nueva_cotizacion)
+                .execute(conn)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+            diesel::insert_into(detalle_cotizaciones::table)
+                .values(&detalles)
+                .execute(conn)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+            Ok(nueva_cotizacion.id)
+        })
+    }
+
+    #[instrument(skip(self))]
+    fn buscar(&self, id: Uuid) -> ApiResult<(Cotizacion, Vec<DetalleCotizacion>)> {
+        let mut conn = self.get_connection()?;
+
+        let cotizacion = cotizaciones::table
+            .find(id)
+            .filter(cotizaciones::activo.eq(true))
+            .select(Cotizacion::as_select())
+            .first(&mut conn)
+            .map_err(|e| match e {
+                diesel::result::Error::NotFound => cotizacion_no_encontrada(id),
+                _ => ApiError::DatabaseError(e.to_string()),
+            })?;
+        let detalles = detalles_de(&mut conn, id)?;
+
+        Ok((cotizacion, detalles))
+    }
+
+    #[instrument(skip(self))]
+    fn listar(&self, id_cliente: Option<Uuid>, estado: Option<EstadoCotizacion>, hoy: NaiveDate) -> ApiResult<Vec<Cotizacion>> {
+        let mut conn = self.get_connection()?;
+
+        let mut query = cotizaciones::table
+            .filter(cotizaciones::activo.eq(true))
+            .into_boxed();
+        if let Some(id_cliente) = id_cliente {
+            query = query.filter(cotizaciones::id_persona.eq(id_cliente));
+        }
+        // Las abiertas que ya vencieron cuentan como VENCIDA aunque no se hayan marcado
+        match estado {
+            Some(EstadoCotizacion::Vencida) => {
+                query = query.filter(
+                    cotizaciones::estado.eq(EstadoCotizacion::Vencida).or(cotizaciones::estado
+                        .eq_any(ESTADOS_ABIERTOS)
+                        .and(cotizaciones::fecha_vencimiento.lt(hoy))),
+                );
+            }
+            Some(estado) if ESTADOS_ABIERTOS.contains(&estado) => {
+                query = query
+                    .filter(cotizaciones::estado.eq(estado))
+                    .filter(cotizaciones::fecha_vencimiento.ge(hoy));
+            }
+            Some(estado) => query = query.filter(cotizaciones::estado.eq(estado)),
+            None => {}
+        }
+
+        query
+            .order(cotizaciones::fecha_creacion.desc())
+            .select(Cotizacion::as_select())
+            .load(&mut conn)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+    }
+
+    #[instrument(skip(self, cotizacion, detalles))]
+    fn reemplazar(
+        &self,
+        cotizacion: NuevaCotizacion,
+        detalles: Vec<NuevoDetalleCotizacion>,
+        hoy: NaiveDate,
+    ) -> ApiResult<()> {
+        let mut conn = self.get_connection()?;
+
+        conn.transaction::<(), ApiError, _>(|conn| {
+            auditoria::contexto::aplicar(conn)?;
+
+            let actual = cotizacion_bloqueada(conn, cotizacion.id)?;
+            validar_estado(&actual, EstadoCotizacion::Borrador, hoy)?;
+
+            diesel::update(cotizaciones::table.find(cotizacion.id))
+                .set((
+                    cotizaciones::id_persona.eq(cotizacion.id_persona),
+                    cotizaciones::id_vendedor.eq(cotizacion.id_vendedor),
+                    cotizaciones::sucursal.eq(&cotizacion.sucursal),
+                    cotizaciones::fecha_vencimiento.eq(cotizacion.fecha_vencimiento),
+                    cotizaciones::monto.eq(&cotizacion.monto),
+                    cotizaciones::observaciones.eq(&cotizacion.observaciones),
+                ))
+                .execute(conn)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+            diesel::update(
+                detalle_cotizaciones::table
+                    .filter(detalle_cotizaciones::id_cotizacion.eq(cotizacion.id))
+                    .filter(detalle_cotizaciones::activo.eq(true)),
+            )
+            .set(detalle_cotizaciones::activo.eq(false))
+            .execute(conn)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+            diesel::insert_into(detalle_cotizaciones::table)
+                .values(&detalles)
+                .execute(conn)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+            Ok(())
+        })
+    }
+
+    #[instrument(skip(self))]
+    fn cambiar_estado(
+        &self,
+        id: Uuid,
+        desde: EstadoCotizacion,
+        hacia: EstadoCotizacion,
+        hoy: NaiveDate,
+    ) -> ApiResult<Cotizacion> {
+        let mut conn = self.get_connection()?;
+
+        conn.transaction::<Cotizacion, ApiError, _>(|conn| {
+            auditoria::contexto::aplicar(conn)?;
+
+            let cotizacion = cotizacion_bloqueada(conn, id)?;
+            validar_estado(&cotizacion, desde, hoy)?;
+
+            diesel::update(cotizaciones::table.find(id))
+                .set(cotizaciones::estado.eq(hacia))
+                .returning(Cotizacion::as_returning())
+                .get_result(conn)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))
+        })
+    }
+
+    #[instrument(skip(self))]
+    fn vencer(&self, hoy: NaiveDate) -> ApiResult<usize> {
+        let mut conn = self.get_connection()?;
+
+        conn.transaction::<usize, ApiError, _>(|conn| {
+            auditoria::contexto::aplicar(conn)?;
+
+            diesel::update(
+                cotizaciones::table
+                    .filter(cotizaciones::activo.eq(true))
+                    .filter(cotizaciones::estado.eq_any(ESTADOS_ABIERTOS))
+                    .filter(cotizaciones::fecha_vencimiento.lt(hoy)),
+            )
+            .set(cotizaciones::estado.eq(EstadoCotizacion::Vencida))
+            .execute(conn)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+        })
+    }
+}
+
+/// Marca CONVERTIDA en la venta `id_venta` una cotización ACEPTADA y vigente, dentro de la
+/// transacción que guarda la venta. La cotización queda bloqueada, así que dos conversiones
+/// simultáneas no generan dos ventas.
+pub(crate) fn convertir_en_venta(
+    conn: &mut PgConnection,
+    id: Uuid,
+    id_venta: Uuid,
+    hoy: NaiveDate,
+) -> ApiResult<()> {
+    let cotizacion = cotizacion_bloqueada(conn, id)?;
+    validar_estado(&cotizacion, EstadoCotizacion::Aceptada, hoy)?;
+
+    diesel::update(cotizaciones::table.find(id))
+        .set((
+            cotizaciones::estado.eq(EstadoCotizacion::Convertida),
+            cotizaciones::id_venta.eq(id_venta),
+        ))
+        .execute(conn)
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+    Ok(())
+}
+
+fn cotizacion_bloqueada(conn: &mut PgConnection, id: Uuid) -> ApiResult<Cotizacion> {
+    cotizaciones::table
+        .find(id)
+        .filter(cotizaciones::activo.eq(true))
+        .select(Cotizacion::as_select())
+        .for_update()
+        .first(conn)
+        .map_err(|e| match e {
+            diesel::result::Error::NotFound => cotizacion_no_encontrada(id),
+            _ => ApiError::DatabaseError(e.to_string()),
+        })
+}
+
+fn detalles_de(conn: &mut PgConnection, id_cotizacion: Uuid) -> ApiResult<Vec<DetalleCotizacion>> {
+    detalle_cotizaciones::table
+        .filter(detalle_cotizaciones::id_cotizacion.eq(id_cotizacion))
+        .filter(detalle_cotizaciones::activo.eq(true))
+        .order(detalle_cotizaciones::linea.asc())
+        .select(DetalleCotizacion::as_select())
+        .load(conn)
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))
+}
+
+/// Estado de la cotización a `hoy`: VENCIDA si sigue abierta y su último día ya pasó, aunque
+/// todavía no se haya marcado
+pub(crate) fn estado_vigente(cotizacion: &Cotizacion, hoy: NaiveDate) -> EstadoCotizacion {
+    if ESTADOS_ABIERTOS.contains(&cotizacion.estado) && cotizacion.fecha_vencimiento < hoy {
+        EstadoCotizacion::Vencida
+    } else {
+        cotizacion.estado
+    }
+}
+
+/// La cotización debe estar en `esperado` y, si sigue abierta, no haber vencido: una cotización
+/// vencida se rechaza aunque todavía no se haya marcado VENCIDA
+pub(crate) fn validar_estado(cotizacion: &Cotizacion, esperado: EstadoCotizacion, hoy: NaiveDate) -> ApiResult<()> {
+    if estado_vigente(cotizacion, hoy) != cotizacion.estado {
+        return Err(ApiError::BusinessRuleViolation(format!(
+            "La cotización {} venció el {}",
+            cotizacion.id,
+            cotizacion.fecha_vencimiento.format("%Y-%m-%d")
+        )));
+    }
+    if cotizacion.estado != esperado {
+        return Err(ApiError::BusinessRuleViolation(format!(
+            "La cotización {} está {} y debe estar {}",
+            cotizacion.id,
+            cotizacion.estado.as_str(),
+            esperado.as_str()
+        )));
+    }
+    Ok(())
+}
+
+pub(crate) fn cotizacion_no_encontrada(id: Uuid) -> ApiError {
+    ApiError::NotFound(format!("Cotización con ID {} no encontrada", id))
+}
@@ -0,0 +1,333 @@
+use std::sync::Arc;
+use bigdecimal::{BigDecimal, ToPrimitive};
+use chrono::{Days, NaiveDate, Utc};
+use uuid::Uuid;
+use tracing::instrument;
+use crate::modules::common::errors::{ApiError, ApiResult};
+use crate::modules::common::validacion::parse_id;
+use crate::modules::common::types::EstadoCotizacion;
+use crate::modules::cotizaciones::model::{
+    ConvertirCotizacionRequest, Cotizacion, CotizacionRequest, CotizacionResponse, CotizacionesQuery,
+    DetalleCotizacion, DetalleCotizacionResponse, NuevaCotizacion, NuevoDetalleCotizacion, PoliticaPreciosCotizacion,
+};
+use crate::modules::cotizaciones::repository::{self as cotizacion_repo, CotizacionRepository};
+use crate::modules::personas::repository::PersonaRepository;
+use crate::modules::precios::model::{OrigenPrecio, PrecioLinea};
+use crate::modules::precios::repository::PrecioRepository;
+use crate::modules::precios::lineas::{self, LineaConPrecio};
+use crate::modules::productos::repository::ProductoRepository;
+use crate::modules::ventas::model::{CrearVentaRequest, DetalleVentaRequest, VentaCreadaResponse};
+use crate::modules::ventas::service::{OrigenCotizacion, VentaService};
+
+/// Días de validez de una cotización que no indica su vencimiento
+pub const DIAS_VIGENCIA: u64 = 15;
+
+pub struct CotizacionService {
+    repository: Arc<dyn CotizacionRepository>,
+    persona_repo: Arc<dyn PersonaRepository>,
+    producto_repo: Arc<dyn ProductoRepository>,
+    precio_repo: Arc<dyn PrecioRepository>,
+    venta_service: Arc<VentaService>,
+    politica_precios: PoliticaPreciosCotizacion,
+}
+
+impl CotizacionService {
+    pub fn new(
+        repository: Arc<dyn CotizacionRepository>,
+        persona_repo: Arc<dyn PersonaRepository>,
+        producto_repo: Arc<dyn ProductoRepository>,
+        precio_repo: Arc<dyn PrecioRepository>,
+        venta_service: Arc<VentaService>,
+        politica_precios: PoliticaPreciosCotizacion,
+    ) -> Self {
+        CotizacionService {
+            repository,
+            persona_repo,
+            producto_repo,
+            precio_repo,
+            venta_service,
+            politica_precios,
+        }
+    }
+
+    /// Crear una cotización en BORRADOR, con los precios que tendría la venta ahora. No
+    /// comprueba ni reserva stock
+    #[instrument(skip(self, request), fields(id_cliente = %request.id_cliente))]
+    pub fn crear(&self, request: CotizacionRequest) -> ApiResult<CotizacionResponse> {
+        let (cotizacion, detalles) = self.preparar(Uuid::new_v4(), request, hoy())?;
+        let id = self.repository.crear(cotizacion, detalles)?;
+        self.respuesta(id)
+    }
+
+    /// Reemplazar los datos y las líneas de una cotización en BORRADOR, que se vuelven a cotizar
+    #[instrument(skip(self, request))]
+    pub fn actualizar(&self, id_str: &str, request: CotizacionRequest) -> ApiResult<CotizacionResponse> {
+        let id = parse_id(id_str, "cotización")?;
+        let hoy = hoy();
+        self.repository.vencer(hoy)?;
+
+        let (cotizacion, detalles) = self.preparar(id, request, hoy)?;
+        self.repository.reemplazar(cotizacion, detalles, hoy)?;
+        self.respuesta(id)
+    }
+
+    #[instrument(skip(self))]
+    pub fn obtener(&self, id_str: &str) -> ApiResult<CotizacionResponse> {
+        self.respuesta(parse_id(id_str, "cotización")?)
+    }
+
+    /// Cotizaciones, filtradas por cliente y estado. Una cotización abierta cuyo último día ya
+    /// pasó se muestra VENCIDA aunque todavía no se haya marcado
+    #[instrument(skip(self))]
+    pub fn listar(&self, query: CotizacionesQuery) -> ApiResult<Vec<CotizacionResponse>> {
+        let id_cliente = match query.id_cliente.as_deref() {
+            Some(id) => Some(parse_id(id, "cliente")?),
+            None => None,
+        };
+        let estado = match query.estado {
+            Some(estado) => Some(match estado.to_uppercase().as_str() {
+                "BORRADOR" => EstadoCotizacion::Borrador,
+                "ENVIADA" => EstadoCotizacion::Enviada,
+                "ACEPTADA" => EstadoCotizacion::Aceptada,
+                "VENCIDA" => EstadoCotizacion::Vencida,
+                "CONVERTIDA" => EstadoCotizacion::Convertida,
+                _ => return Err(ApiError::InvalidInput(
+                    "Estado de cotización inválido. Valores permitidos: BORRADOR, ENVIADA, ACEPTADA, VENCIDA, CONVERTIDA"
+                        .to_string(),
+                )),
+            }),
+            None => None,
+        };
+
+        self.repository
+            .listar(id_cliente, estado, hoy())?
+            .into_iter()
+            .map(|cotizacion| {
+                let (cotizacion, detalles) = self.repository.buscar(cotizacion.id)?;
+                self.cotizacion_response(cotizacion, detalles)
+            })
+            .collect()
+    }
+
+    /// BORRADOR → ENVIADA: la cotización ya no se edita
+    #[instrument(skip(self))]
+    pub fn enviar(&self, id_str: &str) -> ApiResult<CotizacionResponse> {
+        self.cambiar_estado(id_str, EstadoCotizacion::Borrador, EstadoCotizacion::Enviada)
+    }
+
+    /// ENVIADA → ACEPTADA: el cliente aceptó la cotización y puede convertirse en venta
+    #[instrument(skip(self))]
+    pub fn aceptar(&self, id_str: &str) -> ApiResult<CotizacionResponse> {
+        self.cambiar_estado(id_str, EstadoCotizacion::Enviada, EstadoCotizacion::Aceptada)
+    }
+
+    /// Convertir una cotización ACEPTADA y vigente en venta por el camino de `procesar_venta`,
+    /// que vuelve a validar el stock. Los precios se mantienen o se recalculan según la
+    /// política configurada
+    #[instrument(skip(self, request))]
+    pub fn convertir(&self, id_str: &str, request: ConvertirCotizacionRequest) -> ApiResult<VentaCreadaResponse> {
+        let id = parse_id(id_str, "cotización")?;
+        let hoy = hoy();
+        self.repository.vencer(hoy)?;
+
+        let (cotizacion, detalles) = self.repository.buscar(id)?;
+        cotizacion_repo::validar_estado(&cotizacion, EstadoCotizacion::Aceptada, hoy)?;
+        if request.numeros_serie.len() > detalles.len() {
+            return Err(ApiError::InvalidInput(format!(
+                "La cotización tiene {} líneas y se indicaron números de serie para {}",
+                detalles.len(),
+                request.numeros_serie.len()
+            )));
+        }
+
+        let mut numeros_serie = request.numeros_serie.into_iter();
+        let lineas = detalles
+            .iter()
+            .map(|detalle| {
+                let unidad = match detalle.id_unidad {
+                    Some(id_unidad) => Some(self.producto_repo.buscar_unidad(id_unidad)?.nombre),
+                    None => None,
+                };
+                Ok(DetalleVentaRequest {
+                    id_producto: detalle.id_producto.to_string(),
+                    cantidad: detalle.cantidad_unidad,
+                    unidad,
+                    numeros_serie: numeros_serie.next().unwrap_or_default(),
+                })
+            })
+            .collect::<ApiResult<Vec<_>>>()?;
+        let precios = match self.politica_precios {
+            PoliticaPreciosCotizacion::Mantener => Some(detalles.iter().map(precio_cotizado).collect()),
+            PoliticaPreciosCotizacion::Actualizar => None,
+        };
+
+        self.venta_service.convertir_cotizacion(
+            CrearVentaRequest {
+                id_cliente: cotizacion.id_persona.to_string(),
+                id_sesion_caja: request.id_sesion_caja,
+                sucursal: cotizacion.sucursal,
+                id_vendedor: cotizacion.id_vendedor.map(|id| id.to_string()),
+                detalles: lineas,
+                pagos: request.pagos,
+                credito: request.credito,
+            },
+            OrigenCotizacion { id_cotizacion: cotizacion.id, precios },
+        )
+    }
+
+    fn cambiar_estado(
+        &self,
+        id_str: &str,
+        desde: EstadoCotizacion,
+        hacia: EstadoCotizacion,
+    ) -> ApiResult<CotizacionResponse> {
+        let id = parse_id(id_str, "cotización")?;
+        let hoy = hoy();
+        self.repository.vencer(hoy)?;
+
+        self.repository.cambiar_estado(id, desde, hacia, hoy)?;
+        self.respuesta(id)
+    }
+
+    /// Valida la cotización y fija el precio de cada línea con la lista del cliente y las
+    /// promociones vigentes
+    fn preparar(
+        &self,
+        id: Uuid,
+        request: CotizacionRequest,
+        hoy: NaiveDate,
+    ) -> ApiResult<(NuevaCotizacion, Vec<NuevoDetalleCotizacion>)> {
+        let encabezado = lineas::validar_encabezado(
+            self.persona_repo.as_ref(),
+            &request.id_cliente,
+            request.id_vendedor.as_deref(),
+            request.sucursal.as_deref(),
+        )?;
+        let fecha_vencimiento = match request.fecha_vencimiento.as_deref() {
+            Some(fecha) => NaiveDate::parse_from_str(fecha.trim(), "%Y-%m-%d")
+                .map_err(|_| ApiError::InvalidInput("Formato de fecha inválido (YYYY-MM-DD)".to_string()))?,
+            None => hoy + Days::new(DIAS_VIGENCIA),
+        };
+        if fecha_vencimiento < hoy {
+            return Err(ApiError::InvalidInput(
+                "La fecha de vencimiento no puede ser anterior a hoy".to_string(),
+            ));
+        }
+        if request.detalles.is_empty() {
+            return Err(ApiError::InvalidInput("La cotización debe tener al menos un detalle".to_string()));
+        }
+
+        let fecha = Utc::now().naive_utc();
+        let mut monto = BigDecimal::from(0);
+        let mut detalles = Vec::with_capacity(request.detalles.len());
+        for (linea, detalle_req) in (1..).zip(&request.detalles) {
+            let LineaConPrecio { producto, unidad, cantidad, cantidad_base, precio } = lineas::linea_con_precio(
+                self.producto_repo.as_ref(),
+                self.precio_repo.as_ref(),
+                encabezado.cliente.id_lista_precios,
+                &detalle_req.id_producto,
+                detalle_req.unidad.as_deref(),
+                detalle_req.cantidad,
+                None,
+                fecha,
+            )?;
+
+            monto += &precio.subtotal;
+            detalles.push(NuevoDetalleCotizacion {
+                id_cotizacion: id,
+                linea,
+                id_producto: producto.id,
+                id_unidad: unidad.id,
+                cantidad: cantidad_base,
+                cantidad_unidad: cantidad,
+                precio_unitario: precio.precio_unitario,
+                descuento: precio.descuento,
+                monto: precio.subtotal,
+                id_lista_precios: precio.id_lista_precios,
+                id_promocion: precio.id_promocion,
+            });
+        }
+
+        let cotizacion = NuevaCotizacion {
+            id,
+            id_persona: encabezado.cliente.id,
+            id_vendedor: encabezado.id_vendedor,
+            sucursal: encabezado.sucursal,
+            fecha_vencimiento,
+            monto,
+            observaciones: request
+                .observaciones
+                .map(|observaciones| observaciones.trim().to_string())
+                .filter(|observaciones| !observaciones.is_empty()),
+        };
+        Ok((cotizacion, detalles))
+    }
+
+    fn respuesta(&self, id: Uuid) -> ApiResult<CotizacionResponse> {
+        let (cotizacion, detalles) = self.repository.buscar(id)?;
+        self.cotizacion_response(cotizacion, detalles)
+    }
+
+    fn cotizacion_response(
+        &self,
+        cotizacion: Cotizacion,
+        detalles: Vec<DetalleCotizacion>,
+    ) -> ApiResult<CotizacionResponse> {
+        let descuento: BigDecimal = detalles.iter().map(|detalle| &detalle.descuento).sum();
+        let detalles = detalles
+            .into_iter()
+            .map(|detalle| {
+                let producto = self.producto_repo.buscar_por_id(detalle.id_producto)?;
+                let unidad = match detalle.id_unidad {
+                    Some(id_unidad) => self.producto_repo.buscar_unidad(id_unidad)?.nombre,
+                    None => producto.unidad_venta,
+                };
+                Ok(DetalleCotizacionResponse {
+                    id_producto: detalle.id_producto.to_string(),
+                    nombre_producto: producto.nombre,
+                    unidad,
+                    cantidad: detalle.cantidad_unidad,
+                    precio_unitario: detalle.precio_unitario.to_f64().unwrap_or(0.0),
+                    descuento: detalle.descuento.to_f64().unwrap_or(0.0),
+                    subtotal: detalle.monto.to_f64().unwrap_or(0.0),
+                    origen_precio: OrigenPrecio::de(detalle.id_lista_precios, detalle.id_promocion),
+                    id_lista_precios: detalle.id_lista_precios.map(|id| id.to_string()),
+                    id_promocion: detalle.id_promocion.map(|id| id.to_string()),
+                })
+            })
+            .collect::<ApiResult<Vec<_>>>()?;
+        let estado = cotizacion_repo::estado_vigente(&cotizacion, hoy());
+
+        Ok(CotizacionResponse {
+            id: cotizacion.id.to_string(),
+            id_cliente: cotizacion.id_persona.to_string(),
+            id_vendedor: cotizacion.id_vendedor.map(|id| id.to_string()),
+            sucursal: cotizacion.sucursal,
+            estado,
+            fecha_vencimiento: cotizacion.fecha_vencimiento.format("%Y-%m-%d").to_string(),
+            descuento: descuento.to_f64().unwrap_or(0.0),
+            total: cotizacion.monto.to_f64().unwrap_or(0.0),
+            observaciones: cotizacion.observaciones,
+            id_venta: cotizacion.id_venta.map(|id| id.to_string()),
+            fecha_creacion: cotizacion.fecha_creacion.format("%Y-%m-%d %H:%M:%S").to_string(),
+            detalles,
+        })
+    }
+}
+
+/// Precio guardado en la línea de la cotización, para venderla sin recalcularlo
+fn precio_cotizado(detalle: &DetalleCotizacion) -> PrecioLinea {
+    PrecioLinea::fijado(
+        &detalle.precio_unitario,
+        detalle.cantidad_unidad,
+        &detalle.descuento,
+        &detalle.monto,
+        detalle.id_lista_precios,
+        detalle.id_promocion,
+    )
+}
+
+fn hoy() -> NaiveDate {
+    Utc::now().date_naive()
+}
+
@@ -0,0 +1,67 @@
+//! Reglas del crédito de los clientes y de su cuenta por cobrar.
+//!
+//! La cuenta de un cliente la forman sus ventas a crédito y las que aún esperan su pago; lo que
+//! falta por pagar de ellas es su saldo. Una venta que queda con saldo, a crédito o pendiente de
+//! pago, solo se acepta si ese saldo cabe, junto al que ya tenía, en su límite de crédito.
+
+use bigdecimal::{BigDecimal, Zero};
+use crate::modules::common::errors::{ApiError, ApiResult};
+use crate::modules::common::types::EstadoVenta;
+use crate::modules::personas::model::Persona;
+use crate::modules::ventas::model::{Pago, Venta};
+
+/// Falla si el cliente no compra a crédito o si `a_credito` no cabe en lo que le queda
+pub fn validar_credito(cliente: &Persona, saldo: &BigDecimal, a_credito: &BigDecimal) -> ApiResult<()> {
+    let limite = cliente.limite_credito.as_ref().ok_or_else(|| {
+        ApiError::BusinessRuleViolation(format!("El cliente '{}' no tiene crédito aprobado", cliente.nombre))
+    })?;
+
+    let disponible = credito_disponible(limite, saldo);
+    if *a_credito > disponible {
+        return Err(ApiError::BusinessRuleViolation(format!(
+            "Crédito insuficiente para el cliente '{}'. Disponible: {}, Requerido: {}",
+            cliente.nombre, disponible, a_credito
+        )));
+    }
+    Ok(())
+}
+
+/// Lo que el cliente aún puede deber; nunca negativo aunque el límite se haya bajado
+pub fn credito_disponible(limite: &BigDecimal, saldo: &BigDecimal) -> BigDecimal {
+    (limite - saldo).max(BigDecimal::zero())
+}
+
+/// La venta cuenta en el estado de cuenta del cliente
+pub fn es_de_la_cuenta(venta: &Venta) -> bool {
+    venta.estado != EstadoVenta::Anulada && (venta.credito || venta.estado == EstadoVenta::PendientePago)
+}
+
+/// Lo que falta por pagar de la venta, dados sus pagos vigentes
+pub fn saldo_de(venta: &Venta, pagos: &[Pago]) -> BigDecimal {
+    if venta.estado == EstadoVenta::Anulada {
+        return BigDecimal::zero();
+    }
+    let pagado: BigDecimal = pagos.iter().map(|pago| &pago.monto).sum();
+    (&venta.monto - pagado).max(BigDecimal::zero())
+}
+
+/// Saldo repartido por días transcurridos desde la venta
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AntiguedadSaldos {
+    pub dias_0_30: BigDecimal,
+    pub dias_31_60: BigDecimal,
+    pub dias_61_90: BigDecimal,
+    pub mas_de_90: BigDecimal,
+}
+
+impl AntiguedadSaldos {
+    pub fn sumar(&mut self, dias: i64, saldo: &BigDecimal) {
+        let tramo = match dias {
+            ..=30 => &mut self.dias_0_30,
+            31..=60 => &mut self.dias_31_60,
+            61..=90 => &mut self.dias_61_90,
+            _ => &mut self.mas_de_90,
+        };
+        *tramo += saldo;
+    }
+}
@@ -0,0 +1,68 @@
+use actix_web::{web, HttpResponse, ResponseError, Result};
+use crate::modules::common::errors::ErrorResponse;
+use crate::modules::cuentas::model::{EstadoCuentaResponse, LimiteCreditoRequest};
+use crate::modules::personas::model::PersonaResponse;
+use crate::state::app_state::AppState;
+
+/// GET /v1/personas/{id}/estado-cuenta - Estado de cuenta del cliente
+#[utoipa::path(
+    get,
+    path = "/v1/personas/{id}/estado-cuenta",
+    tag = "Cuentas por cobrar",
+    params(
+        ("id" = String, Path, description = "ID del cliente (UUID)")
+    ),
+    responses(
+        (status = 200, description = "Saldo, antigüedad, ventas con saldo y movimientos de la cuenta", body = EstadoCuentaResponse),
+        (status = 400, description = "ID inválido", body = ErrorResponse),
+        (status = 404, description = "Persona no encontrada", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn estado_cuenta(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+) -> Result<HttpResponse> {
+    let service = &state.cuenta_service;
+
+    match service.estado_cuenta(&path.into_inner()) {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// PUT /v1/personas/{id}/credito - Fijar o retirar el límite de crédito de un cliente
+#[utoipa::path(
+    put,
+    path = "/v1/personas/{id}/credito",
+    tag = "Cuentas por cobrar",
+    params(
+        ("id" = String, Path, description = "ID del cliente (UUID)")
+    ),
+    request_body = LimiteCreditoRequest,
+    responses(
+        (status = 200, description = "Límite actualizado", body = PersonaResponse),
+        (status = 400, description = "Límite inválido o la persona no es cliente", body = ErrorResponse),
+        (status = 404, description = "Persona no encontrada", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn asignar_limite_credito(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<LimiteCreditoRequest>,
+) -> Result<HttpResponse> {
+    let service = &state.cuenta_service;
+
+    match service.asignar_limite_credito(&path.into_inner(), body.into_inner()) {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// Se registra antes que `personas::handler::configure`, para que el scope `/personas` no
+/// tome estas rutas
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.route("/personas/{id}/estado-cuenta", web::get().to(estado_cuenta))
+        .route("/personas/{id}/credito", web::put().to(asignar_limite_credito));
+}
@@ -0,0 +1,4 @@
+pub mod model;
+pub mod credito;
+pub mod service;
+pub mod handler;
@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use crate::modules::common::types::MetodoPago;
+
+// DTO for setting a client's credit limit
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct LimiteCreditoRequest {
+    /// Con hasta dos decimales; null retira el crédito al cliente
+    #[schema(example = 500000.0)]
+    pub limite_credito: Option<f64>,
+}
+
+// DTO for a client's account statement
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EstadoCuentaResponse {
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000")]
+    pub id_cliente: String,
+    #[schema(example = "Juan Pérez")]
+    pub nombre_cliente: String,
+    #[schema(example = 500000.0)]
+    pub limite_credito: Option<f64>,
+    /// Lo que el cliente debe entre todas sus ventas sin pagar
+    #[schema(example = 180000.0)]
+    pub saldo: f64,
+    /// Lo que aún puede comprar a crédito; null si no tiene crédito
+    #[schema(example = 320000.0)]
+    pub credito_disponible: Option<f64>,
+    /// Fecha a la que se calcula la antigüedad de los saldos
+    #[schema(example = "2025-11-17")]
+    pub fecha_corte: String,
+    pub antiguedad: AntiguedadSaldosResponse,
+    /// Ventas con saldo, de la más antigua a la más reciente
+    pub documentos: Vec<DocumentoPendienteResponse>,
+    /// Cargos (ventas) y abonos (pagos) de la cuenta en orden cronológico
+    pub movimientos: Vec<MovimientoCuentaResponse>,
+}
+
+// DTO for the aging buckets of a client's balance
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AntiguedadSaldosResponse {
+    #[schema(example = 120000.0)]
+    pub dias_0_30: f64,
+    #[schema(example = 60000.0)]
+    pub dias_31_60: f64,
+    #[schema(example = 0.0)]
+    pub dias_61_90: f64,
+    #[schema(example = 0.0)]
+    pub mas_de_90: f64,
+}
+
+// DTO for a sale with an outstanding balance
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DocumentoPendienteResponse {
+    #[schema(example = "770e8400-e29b-41d4-a716-446655440000")]
+    pub id_venta: String,
+    #[schema(example = "2025-10-02 16:45:00")]
+    pub fecha: String,
+    /// Días transcurridos desde la venta hasta la fecha de corte
+    #[schema(example = 46)]
+    pub dias: i64,
+    #[schema(example = 100000.0)]
+    pub total: f64,
+    #[schema(example = 40000.0)]
+    pub pagado: f64,
+    #[schema(example = 60000.0)]
+    pub saldo: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum TipoMovimientoCuenta {
+    Cargo,
+    Abono,
+}
+
+// DTO for a ledger entry of a client's account
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MovimientoCuentaResponse {
+    #[schema(example = "2025-10-02 16:45:00")]
+    pub fecha: String,
+    pub tipo: TipoMovimientoCuenta,
+    #[schema(example = "770e8400-e29b-41d4-a716-446655440000")]
+    pub id_venta: String,
+    /// Solo en los abonos
+    pub metodo: Option<MetodoPago>,
+    #[schema(example = 40000.0)]
+    pub monto: f64,
+    /// Saldo de la cuenta tras el movimiento
+    #[schema(example = 60000.0)]
+    pub saldo: f64,
+}
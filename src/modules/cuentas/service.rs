@@ -0,0 +1,143 @@
+use std::sync::Arc;
+use bigdecimal::{BigDecimal, ToPrimitive, Zero};
+use chrono::Utc;
+use uuid::Uuid;
+use tracing::instrument;
+use crate::modules::common::errors::{ApiError, ApiResult};
+use crate::modules::common::types::TipoPerfil;
+use crate::modules::cuentas::credito::{self, AntiguedadSaldos};
+use crate::modules::cuentas::model::{
+    AntiguedadSaldosResponse, DocumentoPendienteResponse, EstadoCuentaResponse, LimiteCreditoRequest,
+    MovimientoCuentaResponse, TipoMovimientoCuenta,
+};
+use crate::modules::inventarios::costos;
+use crate::modules::personas::model::PersonaResponse;
+use crate::modules::personas::repository::PersonaRepository;
+use crate::modules::ventas::repository::VentaRepository;
+
+pub struct CuentaService {
+    venta_repo: Arc<dyn VentaRepository>,
+    persona_repo: Arc<dyn PersonaRepository>,
+}
+
+impl CuentaService {
+    pub fn new(venta_repo: Arc<dyn VentaRepository>, persona_repo: Arc<dyn PersonaRepository>) -> Self {
+        CuentaService { venta_repo, persona_repo }
+    }
+
+    /// Fijar o retirar el límite de crédito de un cliente. Bajarlo por debajo de lo que ya debe
+    /// solo impide nuevas ventas a crédito.
+    #[instrument(skip(self, request))]
+    pub fn asignar_limite_credito(&self, id_str: &str, request: LimiteCreditoRequest) -> ApiResult<PersonaResponse> {
+        let id = Uuid::parse_str(id_str)
+            .map_err(|_| ApiError::InvalidInput("ID de persona inválido".to_string()))?;
+
+        let limite = match request.limite_credito {
+            Some(limite) if !limite.is_finite() || limite < 0.0 => {
+                return Err(ApiError::InvalidInput("El límite de crédito no puede ser negativo".to_string()))
+            }
+            Some(limite) => Some(
+                BigDecimal::try_from(limite)
+                    .map(|limite| costos::redondear_importe(&limite))
+                    .map_err(|e| ApiError::InvalidInput(format!("Límite de crédito inválido: {}", e)))?,
+            ),
+            None => None,
+        };
+
+        let persona = self.persona_repo.buscar_por_id(id)?;
+        if persona.perfil != TipoPerfil::Cliente {
+            return Err(ApiError::BusinessRuleViolation(format!(
+                "La persona '{}' no tiene perfil CLIENTE",
+                persona.nombre
+            )));
+        }
+
+        let persona = self.persona_repo.actualizar_limite_credito(id, limite)?;
+        Ok(PersonaResponse::from(persona))
+    }
+
+    /// Estado de cuenta del cliente: saldo, crédito disponible, antigüedad de los saldos a hoy,
+    /// ventas con saldo y cargos y abonos de su cuenta
+    #[instrument(skip(self))]
+    pub fn estado_cuenta(&self, id_str: &str) -> ApiResult<EstadoCuentaResponse> {
+        let id = Uuid::parse_str(id_str)
+            .map_err(|_| ApiError::InvalidInput("ID de persona inválido".to_string()))?;
+        let cliente = self.persona_repo.buscar_por_id(id)?;
+        let fecha_corte = Utc::now().date_naive();
+
+        let mut ventas = self.venta_repo.listar(Some(id), None, None, None, None, None)?;
+        ventas.retain(credito::es_de_la_cuenta);
+        ventas.sort_by_key(|venta| venta.fecha);
+
+        let mut saldo = BigDecimal::zero();
+        let mut antiguedad = AntiguedadSaldos::default();
+        let mut documentos = Vec::new();
+        let mut asientos = Vec::new();
+        for venta in ventas {
+            let pagos = self.venta_repo.obtener_pagos(venta.id)?;
+            let saldo_venta = credito::saldo_de(&venta, &pagos);
+            let dias = (fecha_corte - venta.fecha.date()).num_days().max(0);
+
+            if saldo_venta > BigDecimal::zero() {
+                saldo += &saldo_venta;
+                antiguedad.sumar(dias, &saldo_venta);
+                documentos.push(DocumentoPendienteResponse {
+                    id_venta: venta.id.to_string(),
+                    fecha: venta.fecha.format("%Y-%m-%d %H:%M:%S").to_string(),
+                    dias,
+                    total: venta.monto.to_f64().unwrap_or(0.0),
+                    pagado: (&venta.monto - &saldo_venta).to_f64().unwrap_or(0.0),
+                    saldo: saldo_venta.to_f64().unwrap_or(0.0),
+                });
+            }
+
+            asientos.push((venta.fecha, TipoMovimientoCuenta::Cargo, venta.id, None, venta.monto.clone()));
+            asientos.extend(
+                pagos
+                    .into_iter()
+                    .map(|pago| (pago.fecha, TipoMovimientoCuenta::Abono, venta.id, Some(pago.metodo), pago.monto)),
+            );
+        }
+
+        // Orden cronológico; a la misma hora, la venta antes que sus pagos
+        asientos.sort_by_key(|(fecha, tipo, _, _, _)| (*fecha, *tipo == TipoMovimientoCuenta::Abono));
+        let mut saldo_corrido = BigDecimal::zero();
+        let movimientos = asientos
+            .into_iter()
+            .map(|(fecha, tipo, id_venta, metodo, monto)| {
+                match tipo {
+                    TipoMovimientoCuenta::Cargo => saldo_corrido += &monto,
+                    TipoMovimientoCuenta::Abono => saldo_corrido -= &monto,
+                }
+                MovimientoCuentaResponse {
+                    fecha: fecha.format("%Y-%m-%d %H:%M:%S").to_string(),
+                    tipo,
+                    id_venta: id_venta.to_string(),
+                    metodo,
+                    monto: monto.to_f64().unwrap_or(0.0),
+                    saldo: saldo_corrido.to_f64().unwrap_or(0.0),
+                }
+            })
+            .collect();
+
+        Ok(EstadoCuentaResponse {
+            id_cliente: cliente.id.to_string(),
+            nombre_cliente: cliente.nombre,
+            limite_credito: cliente.limite_credito.as_ref().map(|limite| limite.to_f64().unwrap_or(0.0)),
+            credito_disponible: cliente
+                .limite_credito
+                .as_ref()
+                .map(|limite| credito::credito_disponible(limite, &saldo).to_f64().unwrap_or(0.0)),
+            saldo: saldo.to_f64().unwrap_or(0.0),
+            fecha_corte: fecha_corte.format("%Y-%m-%d").to_string(),
+            antiguedad: AntiguedadSaldosResponse {
+                dias_0_30: antiguedad.dias_0_30.to_f64().unwrap_or(0.0),
+                dias_31_60: antiguedad.dias_31_60.to_f64().unwrap_or(0.0),
+                dias_61_90: antiguedad.dias_61_90.to_f64().unwrap_or(0.0),
+                mas_de_90: antiguedad.mas_de_90.to_f64().unwrap_or(0.0),
+            },
+            documentos,
+            movimientos,
+        })
+    }
+}
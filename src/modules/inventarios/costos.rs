@@ -0,0 +1,187 @@
+//! Costo del inventario por capas: cada ENTRADA crea una capa con su costo unitario y las
+//! salidas consumen las capas de la más antigua a la más reciente (FIFO).
+//!
+//! Con el método de promedio ponderado cada ENTRADA lleva todas las capas al nuevo costo
+//! promedio, así que consumirlas en cualquier orden cuesta lo mismo. El stock que no cubre
+//! ninguna capa (anterior a los costos) vale 0 y es lo primero que sale.
+
+use std::fmt;
+use std::str::FromStr;
+use bigdecimal::{BigDecimal, RoundingMode, Zero};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::modules::common::errors::{ApiError, ApiResult};
+use crate::modules::inventarios::model::CapaCosto;
+
+/// Decimales con los que se guardan los costos unitarios
+pub const DECIMALES_COSTO_UNITARIO: i64 = 4;
+
+/// Cómo se valoran las unidades que entran y salen del inventario
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MetodoValorizacion {
+    /// Promedio ponderado: todas las unidades en stock tienen el mismo costo
+    #[default]
+    Promedio,
+    /// Primero en entrar, primero en salir
+    Fifo,
+}
+
+impl MetodoValorizacion {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MetodoValorizacion::Promedio => "PROMEDIO",
+            MetodoValorizacion::Fifo => "FIFO",
+        }
+    }
+}
+
+impl fmt::Display for MetodoValorizacion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for MetodoValorizacion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "promedio" | "average" => Ok(MetodoValorizacion::Promedio),
+            "fifo" => Ok(MetodoValorizacion::Fifo),
+            otro => Err(format!("Método de valorización inválido '{}'. Valores permitidos: promedio, fifo", otro)),
+        }
+    }
+}
+
+/// Costo unitario recibido en una petición; no puede ser negativo
+pub fn costo_desde_f64(costo: f64) -> ApiResult<BigDecimal> {
+    if !costo.is_finite() || costo < 0.0 {
+        return Err(ApiError::InvalidInput("El costo unitario no puede ser negativo".to_string()));
+    }
+    BigDecimal::try_from(costo)
+        .map(|costo| redondear_costo(&costo))
+        .map_err(|e| ApiError::InvalidInput(format!("Costo inválido: {}", e)))
+}
+
+/// Redondea un costo unitario a los decimales con que se guarda
+pub fn redondear_costo(costo: &BigDecimal) -> BigDecimal {
+    costo.with_scale_round(DECIMALES_COSTO_UNITARIO, RoundingMode::HalfUp)
+}
+
+/// Redondea un importe a centavos
+pub fn redondear_importe(importe: &BigDecimal) -> BigDecimal {
+    importe.with_scale_round(2, RoundingMode::HalfUp)
+}
+
+/// Unidades del stock que no cubre ninguna capa activa
+pub fn stock_sin_capa(stock_actual: i32, capas: &[CapaCosto]) -> i32 {
+    stock_actual - capas.iter().filter(|capa| capa.activo).map(|capa| capa.cantidad_disponible).sum::<i32>()
+}
+
+/// Valor de las unidades en las capas activas
+pub fn valor_capas(capas: &[CapaCosto]) -> BigDecimal {
+    capas
+        .iter()
+        .filter(|capa| capa.activo)
+        .map(|capa| &capa.costo_unitario * BigDecimal::from(capa.cantidad_disponible))
+        .sum()
+}
+
+/// Costo promedio de una unidad en stock; 0 sin stock
+pub fn costo_promedio(stock_actual: i32, capas: &[CapaCosto]) -> BigDecimal {
+    if stock_actual <= 0 {
+        return BigDecimal::zero();
+    }
+    redondear_costo(&(valor_capas(capas) / BigDecimal::from(stock_actual)))
+}
+
+/// Capa que crea una ENTRADA
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntradaCosto {
+    /// Unidades de la capa; con promedio incluye el stock sin capa, que pasa a estar cubierto
+    pub cantidad: i32,
+    pub costo_unitario: BigDecimal,
+    /// Nuevo costo de las capas existentes (solo con promedio)
+    pub recostear: Option<BigDecimal>,
+}
+
+/// Decide la capa que crea la entrada de `cantidad` unidades a `costo_unitario`
+pub fn planificar_entrada(
+    metodo: MetodoValorizacion,
+    stock_actual: i32,
+    capas: &[CapaCosto],
+    cantidad: i32,
+    costo_unitario: &BigDecimal,
+) -> EntradaCosto {
+    let costo_unitario = redondear_costo(costo_unitario);
+    match metodo {
+        MetodoValorizacion::Fifo => EntradaCosto {
+            cantidad,
+            costo_unitario,
+            recostear: None,
+        },
+        MetodoValorizacion::Promedio => {
+            let unidades = stock_actual.max(0) + cantidad;
+            let valor = valor_capas(capas) + &costo_unitario * BigDecimal::from(cantidad);
+            let promedio = redondear_costo(&(valor / BigDecimal::from(unidades)));
+            EntradaCosto {
+                cantidad: cantidad + stock_sin_capa(stock_actual, capas).max(0),
+                costo_unitario: promedio.clone(),
+                recostear: Some(promedio),
+            }
+        }
+    }
+}
+
+/// Unidades que una salida toma de cada capa y lo que cuestan
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConsumoCosto {
+    /// (id de la capa, unidades tomadas)
+    pub capas: Vec<(Uuid, i32)>,
+    pub costo_total: BigDecimal,
+}
+
+impl ConsumoCosto {
+    /// Costo por unidad de la salida, con los decimales de los movimientos
+    pub fn costo_unitario(&self, cantidad: i32) -> BigDecimal {
+        if cantidad <= 0 {
+            return BigDecimal::zero();
+        }
+        redondear_costo(&(&self.costo_total / BigDecimal::from(cantidad)))
+    }
+}
+
+/// Reparte una salida de `cantidad` unidades: primero la capa `preferida` (la de la ENTRADA que
+/// se revierte), después el stock sin capa y luego las demás capas de la más antigua a la más
+/// reciente. El stock ya se validó, así que alcanzan.
+pub fn consumir(stock_actual: i32, capas: &[CapaCosto], cantidad: i32, preferida: Option<Uuid>) -> ConsumoCosto {
+    let mut disponibles: Vec<&CapaCosto> = capas
+        .iter()
+        .filter(|capa| capa.activo && capa.cantidad_disponible > 0)
+        .collect();
+    disponibles.sort_by_key(|capa| (Some(capa.id) != preferida, capa.fecha_creacion));
+
+    let mut sin_capa = stock_sin_capa(stock_actual, capas).max(0);
+    let mut pendiente = cantidad;
+    let mut consumo = ConsumoCosto {
+        capas: Vec::new(),
+        costo_total: BigDecimal::zero(),
+    };
+    for capa in disponibles {
+        if Some(capa.id) != preferida {
+            let tomada = pendiente.min(sin_capa);
+            pendiente -= tomada;
+            sin_capa = 0;
+        }
+        if pendiente <= 0 {
+            break;
+        }
+        let tomada = pendiente.min(capa.cantidad_disponible);
+        consumo.capas.push((capa.id, tomada));
+        consumo.costo_total += &capa.costo_unitario * BigDecimal::from(tomada);
+        pendiente -= tomada;
+    }
+
+    consumo
+}
@@ -1,5 +1,8 @@
 use actix_web::{web, HttpResponse, ResponseError, Result};
-use crate::modules::inventarios::model::{MovimientoRequest, MovimientoRegistradoResponse, DisponibilidadResponse};
+use crate::modules::inventarios::model::{
+    MovimientoRequest, MovimientoRegistradoResponse, MovimientoResponse, DisponibilidadResponse, RevertirMovimientoRequest,
+    LoteResponse, VencimientosQuery,
+};
 use crate::modules::common::errors::ErrorResponse;
 use crate::state::app_state::AppState;
 
@@ -54,10 +57,95 @@ pub async fn obtener_disponibilidad(
     }
 }
 
+/// GET /v1/inventario/movimientos/id/:id - Consultar un movimiento
+#[utoipa::path(
+    get,
+    path = "/v1/inventario/movimientos/id/{id}",
+    tag = "Inventario",
+    params(
+        ("id" = String, Path, description = "ID del movimiento (UUID)")
+    ),
+    responses(
+        (status = 200, description = "Movimiento encontrado", body = MovimientoResponse),
+        (status = 400, description = "ID inválido", body = ErrorResponse),
+        (status = 404, description = "Movimiento no encontrado", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn obtener_movimiento(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    let service = &state.inventario_service;
+
+    match service.obtener_movimiento(&id) {
+        Ok(movimiento) => Ok(HttpResponse::Ok().json(movimiento)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// POST /v1/inventario/movimientos/id/:id/reversion - Revertir un movimiento
+#[utoipa::path(
+    post,
+    path = "/v1/inventario/movimientos/id/{id}/reversion",
+    tag = "Inventario",
+    params(
+        ("id" = String, Path, description = "ID del movimiento a revertir (UUID)")
+    ),
+    request_body = RevertirMovimientoRequest,
+    responses(
+        (status = 201, description = "Movimiento compensatorio registrado", body = MovimientoRegistradoResponse),
+        (status = 400, description = "Datos inválidos, movimiento ya revertido o stock insuficiente", body = ErrorResponse),
+        (status = 404, description = "Movimiento o persona no encontrados", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn revertir_movimiento(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<RevertirMovimientoRequest>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    let service = &state.inventario_service;
+
+    match service.revertir_movimiento(&id, body.into_inner()) {
+        Ok(response) => Ok(HttpResponse::Created().json(response)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// GET /v1/inventario/vencimientos - Lotes próximos a vencer
+#[utoipa::path(
+    get,
+    path = "/v1/inventario/vencimientos",
+    tag = "Inventario",
+    params(VencimientosQuery),
+    responses(
+        (status = 200, description = "Lotes con existencias que vencen en el plazo, del primero al último", body = Vec<LoteResponse>),
+        (status = 400, description = "Número de días inválido", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn listar_vencimientos(
+    state: web::Data<AppState>,
+    query: web::Query<VencimientosQuery>,
+) -> Result<HttpResponse> {
+    let service = &state.inventario_service;
+
+    match service.listar_vencimientos(query.into_inner().dias) {
+        Ok(lotes) => Ok(HttpResponse::Ok().json(lotes)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
 pub fn configure(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/inventario")
             .route("/movimientos", web::post().to(registrar_movimiento))
+            .route("/movimientos/id/{id}", web::get().to(obtener_movimiento))
+            .route("/movimientos/id/{id}/reversion", web::post().to(revertir_movimiento))
             .route("/disponibilidad/{id}", web::get().to(obtener_disponibilidad))
+            .route("/vencimientos", web::get().to(listar_vencimientos))
     );
 }
@@ -0,0 +1,147 @@
+//! Lotes con fecha de vencimiento y asignación FEFO (primero en vencer, primero en salir).
+//!
+//! La suma de los lotes de un producto nunca supera su stock; la diferencia es stock sin lote.
+
+use chrono::NaiveDate;
+use uuid::Uuid;
+use crate::metrics::metrics;
+use crate::modules::common::errors::{ApiError, ApiResult};
+use crate::modules::inventarios::model::{Lote, LoteMovimiento};
+
+/// Longitud máxima del código de un lote
+pub const LONGITUD_MAXIMA_CODIGO: usize = 50;
+
+/// Los códigos se guardan sin espacios sobrantes y en mayúsculas
+pub fn normalizar_codigo(codigo: &str) -> ApiResult<String> {
+    let codigo = codigo.trim().to_uppercase();
+    if codigo.is_empty() {
+        return Err(ApiError::InvalidInput("El código de lote no puede estar vacío".to_string()));
+    }
+    if codigo.chars().count() > LONGITUD_MAXIMA_CODIGO {
+        return Err(ApiError::InvalidInput(format!(
+            "El código de lote no puede superar los {} caracteres",
+            LONGITUD_MAXIMA_CODIGO
+        )));
+    }
+    Ok(codigo)
+}
+
+/// Unidades del stock que no pertenecen a ningún lote activo
+pub fn stock_sin_lote(stock_actual: i32, lotes: &[Lote]) -> i32 {
+    stock_actual - lotes.iter().filter(|lote| lote.activo).map(|lote| lote.cantidad_disponible).sum::<i32>()
+}
+
+/// Qué hacer con el lote de un movimiento
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CambioLote {
+    /// Sumar el cambio a un lote existente
+    Existente(Uuid),
+    /// Crear el lote con el cambio como cantidad inicial
+    Nuevo(NaiveDate),
+}
+
+/// Decide cómo aplicar `cambio_stock` al lote indicado; `existente` es el lote activo del
+/// producto con ese código, si lo hay
+pub fn planificar_cambio_lote(
+    existente: Option<&Lote>,
+    lote: &LoteMovimiento,
+    cambio_stock: i32,
+) -> ApiResult<CambioLote> {
+    match existente {
+        Some(existente) => {
+            if let Some(fecha) = lote.fecha_vencimiento {
+                if fecha != existente.fecha_vencimiento {
+                    return Err(ApiError::BusinessRuleViolation(format!(
+                        "El lote {} vence el {}, no el {}",
+                        existente.codigo,
+                        existente.fecha_vencimiento.format("%Y-%m-%d"),
+                        fecha.format("%Y-%m-%d")
+                    )));
+                }
+            }
+            validar_cambio_lote(existente, cambio_stock)?;
+            Ok(CambioLote::Existente(existente.id))
+        }
+        None if cambio_stock <= 0 => Err(ApiError::NotFound(format!(
+            "El producto no tiene un lote {}",
+            lote.codigo
+        ))),
+        None => lote.fecha_vencimiento.map(CambioLote::Nuevo).ok_or_else(|| {
+            ApiError::InvalidInput(format!(
+                "El lote {} es nuevo: indique su fecha_vencimiento",
+                lote.codigo
+            ))
+        }),
+    }
+}
+
+/// Un lote nunca queda con cantidad negativa
+pub fn validar_cambio_lote(lote: &Lote, cambio_stock: i32) -> ApiResult<()> {
+    if lote.cantidad_disponible + cambio_stock < 0 {
+        metrics().rechazos_stock_insuficiente.with_label_values(&["movimiento"]).inc();
+        return Err(ApiError::BusinessRuleViolation(format!(
+            "Stock insuficiente en el lote {}. Disponible: {}, Cambio solicitado: {}",
+            lote.codigo, lote.cantidad_disponible, cambio_stock
+        )));
+    }
+    Ok(())
+}
+
+/// Un movimiento sin lote solo puede retirar stock sin lote
+pub fn validar_cambio_sin_lote(stock_actual: i32, lotes: &[Lote], cambio_stock: i32) -> ApiResult<()> {
+    let sin_lote = stock_sin_lote(stock_actual, lotes);
+    if cambio_stock < 0 && sin_lote + cambio_stock < 0 {
+        metrics().rechazos_stock_insuficiente.with_label_values(&["movimiento"]).inc();
+        return Err(ApiError::BusinessRuleViolation(format!(
+            "Solo hay {} unidades sin lote; indique el lote del que sale el resto",
+            sin_lote.max(0)
+        )));
+    }
+    Ok(())
+}
+
+/// Reparte `cantidad` entre los lotes vigentes (sin vencer a `hoy`) empezando por el que vence
+/// antes; lo que no cubren los lotes sale del stock sin lote. Los lotes vencidos no se venden.
+/// Devuelve (id del lote, cantidad tomada) por cada lote usado.
+pub fn asignar_fefo(
+    id_producto: Uuid,
+    lotes: &[Lote],
+    stock_actual: i32,
+    cantidad: i32,
+    hoy: NaiveDate,
+) -> ApiResult<Vec<(Uuid, i32)>> {
+    let mut vigentes: Vec<&Lote> = lotes
+        .iter()
+        .filter(|lote| lote.activo && lote.cantidad_disponible > 0 && lote.fecha_vencimiento >= hoy)
+        .collect();
+    vigentes.sort_by_key(|lote| (lote.fecha_vencimiento, lote.fecha_creacion));
+
+    let mut pendiente = cantidad;
+    let mut asignacion = Vec::new();
+    for lote in vigentes {
+        if pendiente == 0 {
+            break;
+        }
+        let tomada = pendiente.min(lote.cantidad_disponible);
+        asignacion.push((lote.id, tomada));
+        pendiente -= tomada;
+    }
+
+    let sin_lote = stock_sin_lote(stock_actual, lotes).max(0);
+    if pendiente > sin_lote {
+        let vencidas: i32 = lotes
+            .iter()
+            .filter(|lote| lote.activo && lote.fecha_vencimiento < hoy)
+            .map(|lote| lote.cantidad_disponible)
+            .sum();
+        metrics().rechazos_stock_insuficiente.with_label_values(&["venta"]).inc();
+        return Err(ApiError::BusinessRuleViolation(format!(
+            "Stock vendible insuficiente para el producto {}: faltan {} unidades ({} están en lotes vencidos)",
+            id_producto,
+            pendiente - sin_lote,
+            vencidas
+        )));
+    }
+
+    Ok(asignacion)
+}
@@ -0,0 +1,466 @@
+use std::cmp::Reverse;
+use bigdecimal::BigDecimal;
+use chrono::{NaiveDate, Utc};
+use uuid::Uuid;
+use crate::modules::common::errors::{ApiError, ApiResult};
+use crate::modules::common::memoria::{MemoriaRepository, Tablas};
+use crate::modules::common::types::{EstadoSerie, TipoMovimiento};
+use crate::modules::inventarios::costos::{self, ConsumoCosto, MetodoValorizacion};
+use crate::modules::inventarios::lotes::{self, CambioLote};
+use crate::modules::inventarios::model::{
+    CapaCosto, DetalleInventario, Inventario, Lote, LoteMovimiento, NuevoMovimiento, NumeroSerie, TrazabilidadMovimiento,
+};
+use crate::modules::inventarios::series::{self, CambioSerie};
+use crate::modules::inventarios::repository::{self as inventario_repo, InventarioRepository};
+
+impl Tablas {
+    pub(crate) fn inventario_mut(&mut self, id_producto: Uuid) -> ApiResult<&mut Inventario> {
+        self.inventarios
+            .iter_mut()
+            .find(|inventario| inventario.id_producto == id_producto && inventario.activo)
+            .ok_or_else(|| inventario_repo::inventario_no_encontrado(id_producto))
+    }
+
+    pub(crate) fn insertar_movimiento(&mut self, movimiento: NuevoMovimiento) -> Uuid {
+        let id = Uuid::new_v4();
+        self.detalle_inventarios.push(DetalleInventario {
+            id,
+            id_producto: movimiento.id_producto,
+            tipo_movimiento: movimiento.tipo_movimiento,
+            fecha: movimiento.fecha,
+            id_persona: movimiento.id_persona,
+            cantidad: movimiento.cantidad,
+            observaciones: movimiento.observaciones,
+            fecha_creacion: movimiento.fecha,
+            fecha_actualizacion: movimiento.fecha,
+            activo: true,
+            id_movimiento_revertido: movimiento.id_movimiento_revertido,
+            id_lote: movimiento.id_lote,
+            costo_unitario: movimiento.costo_unitario,
+            id_detalle_pedido: movimiento.id_detalle_pedido,
+        });
+        id
+    }
+
+    fn capas_de(&self, id_producto: Uuid) -> Vec<CapaCosto> {
+        self.capas_costo
+            .iter()
+            .filter(|capa| capa.id_producto == id_producto && capa.activo && capa.cantidad_disponible > 0)
+            .cloned()
+            .collect()
+    }
+
+    /// Como consumir_capas de PgInventarioRepository
+    pub(crate) fn consumir_capas(
+        &mut self,
+        id_producto: Uuid,
+        stock_actual: i32,
+        cantidad: i32,
+        id_movimiento_origen: Option<Uuid>,
+    ) -> ConsumoCosto {
+        let capas = self.capas_de(id_producto);
+        let preferida = id_movimiento_origen
+            .and_then(|id_movimiento| capas.iter().find(|capa| capa.id_movimiento == Some(id_movimiento)))
+            .map(|capa| capa.id);
+
+        let consumo = costos::consumir(stock_actual, &capas, cantidad, preferida);
+        let ahora = Utc::now().naive_utc();
+        for (id_capa, tomada) in &consumo.capas {
+            if let Some(capa) = self.capas_costo.iter_mut().find(|capa| capa.id == *id_capa) {
+                capa.cantidad_disponible -= tomada;
+                capa.fecha_actualizacion = ahora;
+            }
+        }
+        consumo
+    }
+
+    /// Como costear_movimiento de PgInventarioRepository
+    fn costear_movimiento(
+        &mut self,
+        id_producto: Uuid,
+        stock_actual: i32,
+        cambio_stock: i32,
+        costo_entrada: Option<BigDecimal>,
+        id_movimiento_origen: Option<Uuid>,
+    ) -> BigDecimal {
+        if cambio_stock >= 0 {
+            return match costo_entrada {
+                Some(costo) => costos::redondear_costo(&costo),
+                None => costos::costo_promedio(stock_actual, &self.capas_de(id_producto)),
+            };
+        }
+
+        self.consumir_capas(id_producto, stock_actual, -cambio_stock, id_movimiento_origen)
+            .costo_unitario(-cambio_stock)
+    }
+
+    /// Como registrar_capa de PgInventarioRepository
+    pub(crate) fn registrar_capa(
+        &mut self,
+        metodo: MetodoValorizacion,
+        id_producto: Uuid,
+        stock_previo: i32,
+        cantidad: i32,
+        costo_unitario: &BigDecimal,
+        id_movimiento: Uuid,
+    ) {
+        let capas = self.capas_de(id_producto);
+        let entrada = costos::planificar_entrada(metodo, stock_previo, &capas, cantidad, costo_unitario);
+        let ahora = Utc::now().naive_utc();
+
+        if let Some(promedio) = entrada.recostear {
+            for capa in self.capas_costo.iter_mut().filter(|capa| capas.iter().any(|c| c.id == capa.id)) {
+                capa.costo_unitario = promedio.clone();
+                capa.fecha_actualizacion = ahora;
+            }
+        }
+
+        self.capas_costo.push(CapaCosto {
+            id: Uuid::new_v4(),
+            id_producto,
+            id_movimiento: Some(id_movimiento),
+            costo_unitario: entrada.costo_unitario,
+            cantidad_inicial: entrada.cantidad,
+            cantidad_disponible: entrada.cantidad,
+            fecha_creacion: ahora,
+            fecha_actualizacion: ahora,
+            activo: true,
+        });
+    }
+
+    pub(crate) fn lotes_de(&self, id_producto: Uuid) -> Vec<Lote> {
+        self.lotes
+            .iter()
+            .filter(|lote| lote.id_producto == id_producto && lote.activo)
+            .cloned()
+            .collect()
+    }
+
+    fn lote_mut(&mut self, id_lote: Uuid) -> ApiResult<&mut Lote> {
+        self.lotes
+            .iter_mut()
+            .find(|lote| lote.id == id_lote)
+            .ok_or_else(|| inventario_repo::lote_no_encontrado(id_lote))
+    }
+
+    pub(crate) fn sumar_a_lote(&mut self, id_lote: Uuid, cambio_stock: i32) -> ApiResult<()> {
+        let lote = self.lote_mut(id_lote)?;
+        lotes::validar_cambio_lote(lote, cambio_stock)?;
+        lote.cantidad_disponible += cambio_stock;
+        lote.fecha_actualizacion = Utc::now().naive_utc();
+        Ok(())
+    }
+
+    /// Como aplicar_cambio_lote de PgInventarioRepository
+    fn aplicar_cambio_lote(&mut self, id_producto: Uuid, lote: &LoteMovimiento, cambio_stock: i32) -> ApiResult<Uuid> {
+        let lotes_producto = self.lotes_de(id_producto);
+        let existente = lotes_producto.iter().find(|l| l.codigo == lote.codigo);
+
+        match lotes::planificar_cambio_lote(existente, lote, cambio_stock)? {
+            CambioLote::Existente(id_lote) => {
+                self.sumar_a_lote(id_lote, cambio_stock)?;
+                Ok(id_lote)
+            }
+            CambioLote::Nuevo(fecha_vencimiento) => {
+                let ahora = Utc::now().naive_utc();
+                let id = Uuid::new_v4();
+                self.lotes.push(Lote {
+                    id,
+                    id_producto,
+                    codigo: lote.codigo.clone(),
+                    fecha_vencimiento,
+                    cantidad_disponible: cambio_stock,
+                    fecha_creacion: ahora,
+                    fecha_actualizacion: ahora,
+                    activo: true,
+                });
+                Ok(id)
+            }
+        }
+    }
+
+    pub(crate) fn insertar_serie(&mut self, id_producto: Uuid, numero: String, id_movimiento: Option<Uuid>) {
+        let ahora = Utc::now().naive_utc();
+        self.numeros_serie.push(NumeroSerie {
+            id: Uuid::new_v4(),
+            id_producto,
+            numero,
+            estado: EstadoSerie::EnStock,
+            id_movimiento,
+            fecha_creacion: ahora,
+            fecha_actualizacion: ahora,
+            activo: true,
+        });
+    }
+
+    fn serie_mut(&mut self, id_serie: Uuid) -> &mut NumeroSerie {
+        self.numeros_serie
+            .iter_mut()
+            .find(|serie| serie.id == id_serie)
+            .expect("planificar_series solo devuelve números existentes")
+    }
+
+    /// Como aplicar_series de PgInventarioRepository
+    pub(crate) fn aplicar_series(
+        &mut self,
+        id_producto: Uuid,
+        tipo_movimiento: TipoMovimiento,
+        numeros: &[String],
+        id_movimiento: Uuid,
+    ) -> ApiResult<()> {
+        let existentes: Vec<NumeroSerie> = self
+            .numeros_serie
+            .iter()
+            .filter(|serie| serie.id_producto == id_producto && serie.activo)
+            .cloned()
+            .collect();
+        let ahora = Utc::now().naive_utc();
+
+        for cambio in series::planificar_series(tipo_movimiento, &existentes, numeros)? {
+            match cambio {
+                CambioSerie::Alta(numero) => self.insertar_serie(id_producto, numero, Some(id_movimiento)),
+                CambioSerie::Devolucion(id_serie) => {
+                    let serie = self.serie_mut(id_serie);
+                    serie.estado = EstadoSerie::Devuelto;
+                    serie.id_movimiento = Some(id_movimiento);
+                    serie.fecha_actualizacion = ahora;
+                }
+                CambioSerie::Baja(id_serie) => {
+                    let serie = self.serie_mut(id_serie);
+                    serie.activo = false;
+                    serie.id_movimiento = Some(id_movimiento);
+                    serie.fecha_actualizacion = ahora;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Como devolver_al_stock de PgInventarioRepository
+    pub(crate) fn devolver_al_stock(&mut self, metodo: MetodoValorizacion, devolucion: NuevoMovimiento) -> ApiResult<Uuid> {
+        let id_producto = devolucion.id_producto;
+        let cantidad = devolucion.cantidad;
+        let costo_unitario = devolucion.costo_unitario.clone().unwrap_or_default();
+
+        let inventario = self.inventario_mut(id_producto)?;
+        let stock_actual = inventario.cantidad_disponible;
+        inventario.cantidad_disponible += cantidad;
+        inventario.fecha_actualizacion = Utc::now().naive_utc();
+        if let Some(id_lote) = devolucion.id_lote {
+            self.sumar_a_lote(id_lote, cantidad)?;
+        }
+
+        let id = self.insertar_movimiento(devolucion);
+        self.registrar_capa(metodo, id_producto, stock_actual, cantidad, &costo_unitario, id);
+        self.asignar_pendientes(id_producto)?;
+        Ok(id)
+    }
+
+    fn reversion_de(&self, id_movimiento: Uuid) -> Option<Uuid> {
+        self.detalle_inventarios
+            .iter()
+            .find(|movimiento| movimiento.id_movimiento_revertido == Some(id_movimiento))
+            .map(|movimiento| movimiento.id)
+    }
+}
+
+impl InventarioRepository for MemoriaRepository {
+    fn obtener_stock(&self, id_producto: Uuid) -> ApiResult<i32> {
+        Ok(self.tablas().inventario_mut(id_producto)?.cantidad_disponible)
+    }
+
+    fn obtener_reservado(&self, id_producto: Uuid) -> ApiResult<i32> {
+        Ok(self.tablas().inventario_mut(id_producto)?.cantidad_reservada)
+    }
+
+    fn listar_movimientos(&self, id_producto: Uuid) -> ApiResult<Vec<DetalleInventario>> {
+        let mut movimientos: Vec<DetalleInventario> = self
+            .tablas()
+            .detalle_inventarios
+            .iter()
+            .filter(|movimiento| movimiento.id_producto == id_producto && movimiento.activo)
+            .cloned()
+            .collect();
+        movimientos.sort_by_key(|movimiento| Reverse(movimiento.fecha));
+        Ok(movimientos)
+    }
+
+    fn registrar_movimiento_con_actualizacion(
+        &self,
+        id_producto: Uuid,
+        tipo_movimiento: TipoMovimiento,
+        id_persona: Uuid,
+        cantidad: i32,
+        observaciones: Option<String>,
+        trazabilidad: TrazabilidadMovimiento,
+    ) -> ApiResult<Uuid> {
+        self.transaccion(|tablas| {
+            let cambio_stock = tipo_movimiento.efecto_en_stock(cantidad);
+            let inventario = tablas
+                .inventario_mut(id_producto)
+                .map_err(|_| ApiError::NotFound(format!("No existe inventario para el producto {}", id_producto)))?;
+            let stock_actual = inventario.cantidad_disponible;
+            inventario_repo::validar_stock_resultante(stock_actual, inventario.cantidad_reservada, cambio_stock)?;
+
+            let id_lote = match trazabilidad.lote {
+                Some(lote) => Some(tablas.aplicar_cambio_lote(id_producto, &lote, cambio_stock)?),
+                None => {
+                    lotes::validar_cambio_sin_lote(stock_actual, &tablas.lotes_de(id_producto), cambio_stock)?;
+                    None
+                }
+            };
+
+            let costo_unitario =
+                tablas.costear_movimiento(id_producto, stock_actual, cambio_stock, trazabilidad.costo_unitario, None);
+
+            let inventario = tablas.inventario_mut(id_producto)?;
+            inventario.cantidad_disponible += cambio_stock;
+            inventario.fecha_actualizacion = Utc::now().naive_utc();
+
+            let id = tablas.insertar_movimiento(NuevoMovimiento {
+                id_producto,
+                tipo_movimiento,
+                fecha: Utc::now().naive_utc(),
+                id_persona,
+                cantidad,
+                observaciones,
+                id_movimiento_revertido: None,
+                id_lote,
+                costo_unitario: Some(costo_unitario.clone()),
+                id_detalle_pedido: None,
+            });
+            if cambio_stock > 0 {
+                tablas.registrar_capa(self.metodo_valorizacion, id_producto, stock_actual, cambio_stock, &costo_unitario, id);
+                tablas.asignar_pendientes(id_producto)?;
+            }
+
+            if !trazabilidad.numeros_serie.is_empty() {
+                tablas.aplicar_series(id_producto, tipo_movimiento, &trazabilidad.numeros_serie, id)?;
+            }
+
+            Ok(id)
+        })
+    }
+
+    fn buscar_movimiento(&self, id: Uuid) -> ApiResult<DetalleInventario> {
+        self.tablas()
+            .detalle_inventarios
+            .iter()
+            .find(|movimiento| movimiento.id == id && movimiento.activo)
+            .cloned()
+            .ok_or_else(|| inventario_repo::movimiento_no_encontrado(id))
+    }
+
+    fn buscar_reversion(&self, id_movimiento: Uuid) -> ApiResult<Option<Uuid>> {
+        Ok(self.tablas().reversion_de(id_movimiento))
+    }
+
+    fn revertir_movimiento(
+        &self,
+        id_movimiento: Uuid,
+        id_persona: Uuid,
+        observaciones: Option<String>,
+    ) -> ApiResult<Uuid> {
+        let original = self.buscar_movimiento(id_movimiento)?;
+
+        self.transaccion(|tablas| {
+            let mut reversion = inventario_repo::movimiento_compensatorio(
+                &original,
+                tablas.reversion_de(id_movimiento),
+                id_persona,
+                observaciones,
+            )?;
+
+            let cambio_stock = reversion.tipo_movimiento.efecto_en_stock(reversion.cantidad);
+            let inventario = tablas.inventario_mut(original.id_producto)?;
+            let stock_actual = inventario.cantidad_disponible;
+            inventario_repo::validar_stock_resultante(stock_actual, inventario.cantidad_reservada, cambio_stock)?;
+            match reversion.id_lote {
+                Some(id_lote) => tablas.sumar_a_lote(id_lote, cambio_stock)?,
+                None => lotes::validar_cambio_sin_lote(stock_actual, &tablas.lotes_de(original.id_producto), cambio_stock)?,
+            }
+
+            let costo_unitario = tablas.costear_movimiento(
+                original.id_producto,
+                stock_actual,
+                cambio_stock,
+                original.costo_unitario.clone(),
+                Some(original.id),
+            );
+            reversion.costo_unitario = Some(costo_unitario.clone());
+
+            let inventario = tablas.inventario_mut(original.id_producto)?;
+            inventario.cantidad_disponible += cambio_stock;
+            inventario.fecha_actualizacion = Utc::now().naive_utc();
+
+            let id = tablas.insertar_movimiento(reversion);
+            if cambio_stock > 0 {
+                tablas.registrar_capa(
+                    self.metodo_valorizacion,
+                    original.id_producto,
+                    stock_actual,
+                    cambio_stock,
+                    &costo_unitario,
+                    id,
+                );
+                tablas.asignar_pendientes(original.id_producto)?;
+            }
+
+            Ok(id)
+        })
+    }
+
+    fn listar_lotes(&self, id_producto: Uuid) -> ApiResult<Vec<Lote>> {
+        let mut lotes: Vec<Lote> = self
+            .tablas()
+            .lotes_de(id_producto)
+            .into_iter()
+            .filter(|lote| lote.cantidad_disponible > 0)
+            .collect();
+        lotes.sort_by_key(|lote| (lote.fecha_vencimiento, lote.fecha_creacion));
+        Ok(lotes)
+    }
+
+    fn listar_vencimientos(&self, hasta: NaiveDate) -> ApiResult<Vec<Lote>> {
+        let mut lotes: Vec<Lote> = self
+            .tablas()
+            .lotes
+            .iter()
+            .filter(|lote| lote.activo && lote.cantidad_disponible > 0 && lote.fecha_vencimiento <= hasta)
+            .cloned()
+            .collect();
+        lotes.sort_by(|a, b| a.fecha_vencimiento.cmp(&b.fecha_vencimiento).then_with(|| a.codigo.cmp(&b.codigo)));
+        Ok(lotes)
+    }
+
+    fn buscar_lote(&self, id: Uuid) -> ApiResult<Lote> {
+        self.tablas()
+            .lotes
+            .iter()
+            .find(|lote| lote.id == id)
+            .cloned()
+            .ok_or_else(|| inventario_repo::lote_no_encontrado(id))
+    }
+
+    fn buscar_serie(&self, id_producto: Uuid, numero: &str) -> ApiResult<Option<NumeroSerie>> {
+        Ok(self
+            .tablas()
+            .numeros_serie
+            .iter()
+            .find(|serie| serie.id_producto == id_producto && serie.numero == numero && serie.activo)
+            .cloned())
+    }
+
+    fn buscar_series_por_numero(&self, numero: &str) -> ApiResult<Vec<NumeroSerie>> {
+        let mut series: Vec<NumeroSerie> = self
+            .tablas()
+            .numeros_serie
+            .iter()
+            .filter(|serie| serie.numero == numero && serie.activo)
+            .cloned()
+            .collect();
+        series.sort_by_key(|serie| serie.fecha_creacion);
+        Ok(series)
+    }
+}
@@ -1,4 +1,8 @@
 pub mod model;
+pub mod lotes;
+pub mod series;
+pub mod costos;
 pub mod repository;
 pub mod service;
 pub mod handler;
+mod memoria;
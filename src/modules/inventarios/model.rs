@@ -1,10 +1,11 @@
-use chrono::NaiveDateTime;
+use bigdecimal::{BigDecimal, ToPrimitive};
+use chrono::{NaiveDate, NaiveDateTime};
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
-use utoipa::ToSchema;
-use crate::modules::common::types::TipoMovimiento;
-use crate::schema::{inventarios, detalle_inventarios};
+use utoipa::{IntoParams, ToSchema};
+use crate::modules::common::types::{EstadoSerie, TipoMovimiento};
+use crate::schema::{capas_costo, inventarios, detalle_inventarios, lotes, numeros_serie};
 
 // Domain Model for Inventario
 #[derive(Debug, Clone, Queryable, Identifiable, Selectable)]
@@ -17,6 +18,8 @@ pub struct Inventario {
     pub fecha_creacion: NaiveDateTime,
     pub fecha_actualizacion: NaiveDateTime,
     pub activo: bool,
+    /// Parte de cantidad_disponible asignada a pedidos sin despachar; no se vende
+    pub cantidad_reservada: i32,
 }
 
 // Domain Model for DetalleInventario
@@ -33,6 +36,106 @@ pub struct DetalleInventario {
     pub fecha_creacion: NaiveDateTime,
     pub fecha_actualizacion: NaiveDateTime,
     pub activo: bool,
+    /// Movimiento que este compensa, si es una reversión
+    pub id_movimiento_revertido: Option<Uuid>,
+    /// Lote afectado; None si el movimiento fue sobre el stock sin lote
+    pub id_lote: Option<Uuid>,
+    /// Costo por unidad con que entró o salió; None en los movimientos anteriores a los costos
+    pub costo_unitario: Option<BigDecimal>,
+    /// Línea de pedido cuyo despacho registró esta SALIDA
+    pub id_detalle_pedido: Option<Uuid>,
+}
+
+// Domain Model for Lote
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = lotes)]
+pub struct Lote {
+    pub id: Uuid,
+    pub id_producto: Uuid,
+    pub codigo: String,
+    pub fecha_vencimiento: NaiveDate,
+    pub cantidad_disponible: i32,
+    pub fecha_creacion: NaiveDateTime,
+    pub fecha_actualizacion: NaiveDateTime,
+    pub activo: bool,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = lotes)]
+pub struct NuevoLote {
+    pub id_producto: Uuid,
+    pub codigo: String,
+    pub fecha_vencimiento: NaiveDate,
+    pub cantidad_disponible: i32,
+}
+
+/// Lote indicado en un movimiento, con el código ya normalizado
+#[derive(Debug, Clone)]
+pub struct LoteMovimiento {
+    pub codigo: String,
+    /// Obligatoria para crear el lote; si el lote existe debe coincidir
+    pub fecha_vencimiento: Option<NaiveDate>,
+}
+
+// Domain Model for NumeroSerie
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = numeros_serie)]
+pub struct NumeroSerie {
+    pub id: Uuid,
+    pub id_producto: Uuid,
+    /// En mayúsculas y sin espacios sobrantes
+    pub numero: String,
+    pub estado: EstadoSerie,
+    /// Último movimiento de inventario que lo dio de alta, lo devolvió o lo dio de baja
+    pub id_movimiento: Option<Uuid>,
+    pub fecha_creacion: NaiveDateTime,
+    pub fecha_actualizacion: NaiveDateTime,
+    pub activo: bool,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = numeros_serie)]
+pub struct NuevoNumeroSerie {
+    pub id_producto: Uuid,
+    pub numero: String,
+    pub estado: EstadoSerie,
+    pub id_movimiento: Option<Uuid>,
+}
+
+/// Lote, números de serie y costo a los que se aplica un movimiento
+#[derive(Debug, Clone, Default)]
+pub struct TrazabilidadMovimiento {
+    pub lote: Option<LoteMovimiento>,
+    /// Ya normalizados; uno por unidad en los productos serializados, vacío en los demás
+    pub numeros_serie: Vec<String>,
+    /// Costo unitario de una ENTRADA; sin él entra al costo promedio del stock
+    pub costo_unitario: Option<BigDecimal>,
+}
+
+// Domain Model for CapaCosto
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = capas_costo)]
+pub struct CapaCosto {
+    pub id: Uuid,
+    pub id_producto: Uuid,
+    /// Movimiento que creó la capa
+    pub id_movimiento: Option<Uuid>,
+    pub costo_unitario: BigDecimal,
+    pub cantidad_inicial: i32,
+    pub cantidad_disponible: i32,
+    pub fecha_creacion: NaiveDateTime,
+    pub fecha_actualizacion: NaiveDateTime,
+    pub activo: bool,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = capas_costo)]
+pub struct NuevaCapaCosto {
+    pub id_producto: Uuid,
+    pub id_movimiento: Option<Uuid>,
+    pub costo_unitario: BigDecimal,
+    pub cantidad_inicial: i32,
+    pub cantidad_disponible: i32,
 }
 
 // DTO for stock availability response
@@ -42,6 +145,59 @@ pub struct DisponibilidadResponse {
     pub id_producto: String,
     #[schema(example = 45)]
     pub cantidad_disponible: i32,
+    /// Unidades de cantidad_disponible asignadas a pedidos, que no se pueden vender
+    #[schema(example = 5)]
+    pub cantidad_reservada: i32,
+    /// SIN_STOCK, STOCK_BAJO (por debajo del umbral configurado) o STOCK_OK
+    #[schema(example = "STOCK_OK")]
+    pub estado_stock: String,
+    /// Lotes con existencias, del primero al último en vencer
+    pub lotes: Vec<LoteResponse>,
+}
+
+// DTO for lot response
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct LoteResponse {
+    #[schema(example = "880e8400-e29b-41d4-a716-446655440000")]
+    pub id: String,
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000")]
+    pub id_producto: String,
+    #[schema(example = "Yogur natural 1L")]
+    pub nombre_producto: String,
+    #[schema(example = "L2026-101")]
+    pub codigo: String,
+    #[schema(example = "2026-11-05")]
+    pub fecha_vencimiento: String,
+    #[schema(example = 24)]
+    pub cantidad_disponible: i32,
+    /// Negativo si ya venció
+    #[schema(example = 18)]
+    pub dias_para_vencer: i64,
+    pub vencido: bool,
+}
+
+impl LoteResponse {
+    pub fn new(lote: Lote, nombre_producto: String, hoy: NaiveDate) -> Self {
+        let dias_para_vencer = (lote.fecha_vencimiento - hoy).num_days();
+        LoteResponse {
+            id: lote.id.to_string(),
+            id_producto: lote.id_producto.to_string(),
+            nombre_producto,
+            codigo: lote.codigo,
+            fecha_vencimiento: lote.fecha_vencimiento.format("%Y-%m-%d").to_string(),
+            cantidad_disponible: lote.cantidad_disponible,
+            dias_para_vencer,
+            vencido: dias_para_vencer < 0,
+        }
+    }
+}
+
+// Query parameters for lots close to expiry
+#[derive(Debug, Deserialize, ToSchema, IntoParams)]
+pub struct VencimientosQuery {
+    /// Lotes que vencen dentro de este número de días, incluidos los ya vencidos (por defecto 30)
+    #[schema(example = 30)]
+    pub dias: Option<i64>,
 }
 
 // DTO for creating a new inventory movement
@@ -54,6 +210,10 @@ pub struct NuevoMovimiento {
     pub id_persona: Uuid,
     pub cantidad: i32,
     pub observaciones: Option<String>,
+    pub id_movimiento_revertido: Option<Uuid>,
+    pub id_lote: Option<Uuid>,
+    pub costo_unitario: Option<BigDecimal>,
+    pub id_detalle_pedido: Option<Uuid>,
 }
 
 // DTO for movement request
@@ -69,6 +229,19 @@ pub struct MovimientoRequest {
     pub cantidad: i32,
     #[schema(example = "Compra de inventario mensual")]
     pub observaciones: Option<String>,
+    /// Código del lote: una ENTRADA lo crea o lo aumenta, una SALIDA o AJUSTE lo modifica
+    #[schema(example = "L2026-101")]
+    pub lote: Option<String>,
+    /// Vencimiento del lote (YYYY-MM-DD), obligatorio si el lote es nuevo
+    #[schema(example = "2026-11-05")]
+    pub fecha_vencimiento: Option<String>,
+    /// Obligatorios en productos serializados: uno por unidad que entra o sale
+    #[serde(default)]
+    #[schema(example = json!([]))]
+    pub numeros_serie: Vec<String>,
+    /// Costo de compra por unidad, solo en ENTRADAS; si se omite, entra al costo promedio del stock
+    #[schema(example = 950000.0)]
+    pub costo_unitario: Option<f64>,
 }
 
 // DTO for movement response
@@ -79,3 +252,66 @@ pub struct MovimientoRegistradoResponse {
     #[schema(example = "Movimiento registrado exitosamente")]
     pub mensaje: String,
 }
+
+// DTO for reversing a movement
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RevertirMovimientoRequest {
+    #[schema(example = "660e8400-e29b-41d4-a716-446655440000")]
+    pub id_persona: String,
+    #[schema(example = "Entrada registrada por error")]
+    pub observaciones: Option<String>,
+}
+
+// DTO for movement detail response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MovimientoResponse {
+    #[schema(example = "770e8400-e29b-41d4-a716-446655440000")]
+    pub id: String,
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000")]
+    pub id_producto: String,
+    #[schema(example = "ENTRADA")]
+    pub tipo_movimiento: String,
+    #[schema(example = "2025-11-17 10:30:00")]
+    pub fecha: String,
+    #[schema(example = "660e8400-e29b-41d4-a716-446655440000")]
+    pub id_persona: String,
+    /// Cantidad tal como se registró (las ventas guardan la SALIDA en negativo)
+    #[schema(example = 20)]
+    pub cantidad: i32,
+    /// Efecto sobre el stock, con el signo normalizado por tipo
+    #[schema(example = 20)]
+    pub efecto_en_stock: i32,
+    #[schema(example = "Compra de inventario mensual")]
+    pub observaciones: Option<String>,
+    /// Movimiento que este compensa, si es una reversión
+    pub id_movimiento_revertido: Option<String>,
+    /// Movimiento que revirtió a este, si lo hay
+    pub id_reversion: Option<String>,
+    /// Lote afectado, si lo hay
+    pub id_lote: Option<String>,
+    /// Costo por unidad con que entró o salió; null en movimientos anteriores a los costos
+    #[schema(example = 950000.0)]
+    pub costo_unitario: Option<f64>,
+    /// Línea de pedido despachada, si la SALIDA es de un despacho
+    pub id_detalle_pedido: Option<String>,
+}
+
+impl MovimientoResponse {
+    pub fn new(movimiento: DetalleInventario, id_reversion: Option<Uuid>) -> Self {
+        MovimientoResponse {
+            id: movimiento.id.to_string(),
+            id_producto: movimiento.id_producto.to_string(),
+            tipo_movimiento: movimiento.tipo_movimiento.as_str().to_string(),
+            fecha: movimiento.fecha.format("%Y-%m-%d %H:%M:%S").to_string(),
+            id_persona: movimiento.id_persona.to_string(),
+            cantidad: movimiento.cantidad,
+            efecto_en_stock: movimiento.tipo_movimiento.efecto_en_stock(movimiento.cantidad),
+            observaciones: movimiento.observaciones,
+            id_movimiento_revertido: movimiento.id_movimiento_revertido.map(|id| id.to_string()),
+            id_reversion: id_reversion.map(|id| id.to_string()),
+            id_lote: movimiento.id_lote.map(|id| id.to_string()),
+            costo_unitario: movimiento.costo_unitario.and_then(|costo| costo.to_f64()),
+            id_detalle_pedido: movimiento.id_detalle_pedido.map(|id| id.to_string()),
+        }
+    }
+}
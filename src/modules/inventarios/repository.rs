@@ -1,22 +1,98 @@
+use bigdecimal::BigDecimal;
 use diesel::prelude::*;
 use diesel::r2d2::{ConnectionManager, Pool, PooledConnection};
 use uuid::Uuid;
-use chrono::Utc;
+use chrono::{NaiveDate, Utc};
+use tracing::instrument;
+use crate::metrics::metrics;
+use crate::modules::auditoria;
 use crate::modules::common::errors::{ApiError, ApiResult};
-use crate::modules::common::types::TipoMovimiento;
-use crate::modules::inventarios::model::{Inventario, DetalleInventario, NuevoMovimiento};
-use crate::schema::{inventarios, detalle_inventarios};
+use crate::modules::common::types::{EstadoSerie, TipoMovimiento};
+use crate::modules::inventarios::costos::{self, ConsumoCosto, MetodoValorizacion};
+use crate::modules::inventarios::lotes::{self, CambioLote};
+use crate::modules::inventarios::model::{
+    CapaCosto, Inventario, DetalleInventario, Lote, LoteMovimiento, NuevaCapaCosto, NuevoLote, NuevoMovimiento,
+    NuevoNumeroSerie, NumeroSerie, TrazabilidadMovimiento,
+};
+use crate::modules::inventarios::series::{self, CambioSerie};
+use crate::modules::pedidos::repository as pedido_repo;
+use crate::schema::{capas_costo, inventarios, detalle_inventarios, lotes as lotes_tabla, numeros_serie};
 
 pub type DbPool = Pool<ConnectionManager<PgConnection>>;
 pub type DbConnection = PooledConnection<ConnectionManager<PgConnection>>;
 
-pub struct InventarioRepository {
+/// Acceso a datos de inventario (ver `PersonaRepository` para las implementaciones)
+pub trait InventarioRepository: Send + Sync {
+    fn obtener_stock(&self, id_producto: Uuid) -> ApiResult<i32>;
+
+    /// Parte del stock asignada a pedidos que todavía no se despacharon
+    fn obtener_reservado(&self, id_producto: Uuid) -> ApiResult<i32>;
+
+    fn validar_stock(&self, id_producto: Uuid, cantidad_requerida: i32) -> ApiResult<bool> {
+        let stock_actual = self.obtener_stock(id_producto)?;
+        Ok(stock_actual >= cantidad_requerida)
+    }
+
+    fn listar_movimientos(&self, id_producto: Uuid) -> ApiResult<Vec<DetalleInventario>>;
+
+    /// Registra un movimiento y actualiza el stock de forma atómica.
+    /// Rechaza el movimiento si dejaría el stock en negativo o por debajo de lo reservado para
+    /// pedidos. Con lote, el cambio se aplica
+    /// también a ese lote (una ENTRADA lo crea si no existe); sin él, solo puede retirar
+    /// stock sin lote. Los números de serie se dan de alta, se devuelven o se dan de baja
+    /// según el tipo (ver `series::planificar_series`). Lo que entra crea una capa de costo y
+    /// lo que sale consume las capas; el movimiento guarda su costo unitario (ver `costos`). Lo
+    /// que entra se asigna a los pedidos que esperaban stock (ver `pedido_repo::asignar_pendientes`).
+    fn registrar_movimiento_con_actualizacion(
+        &self,
+        id_producto: Uuid,
+        tipo_movimiento: TipoMovimiento,
+        id_persona: Uuid,
+        cantidad: i32,
+        observaciones: Option<String>,
+        trazabilidad: TrazabilidadMovimiento,
+    ) -> ApiResult<Uuid>;
+
+    fn buscar_movimiento(&self, id: Uuid) -> ApiResult<DetalleInventario>;
+
+    /// Movimiento que revirtió a `id_movimiento`, si existe
+    fn buscar_reversion(&self, id_movimiento: Uuid) -> ApiResult<Option<Uuid>>;
+
+    /// Registra el movimiento que compensa a `id_movimiento` y actualiza el stock de forma atómica.
+    /// Un movimiento sólo se revierte una vez y las reversiones y los despachos de pedidos no se
+    /// revierten. Revertir una
+    /// ENTRADA retira primero las unidades de la capa que creó; revertir una salida las devuelve
+    /// al costo con que salieron.
+    fn revertir_movimiento(
+        &self,
+        id_movimiento: Uuid,
+        id_persona: Uuid,
+        observaciones: Option<String>,
+    ) -> ApiResult<Uuid>;
+
+    /// Lotes activos del producto con existencias, del primero al último en vencer
+    fn listar_lotes(&self, id_producto: Uuid) -> ApiResult<Vec<Lote>>;
+
+    /// Lotes activos con existencias que vencen hasta `hasta` (incluidos los ya vencidos)
+    fn listar_vencimientos(&self, hasta: NaiveDate) -> ApiResult<Vec<Lote>>;
+
+    fn buscar_lote(&self, id: Uuid) -> ApiResult<Lote>;
+
+    /// Número de serie activo del producto, si está registrado
+    fn buscar_serie(&self, id_producto: Uuid, numero: &str) -> ApiResult<Option<NumeroSerie>>;
+
+    /// Números de serie activos con ese número, de cualquier producto
+    fn buscar_series_por_numero(&self, numero: &str) -> ApiResult<Vec<NumeroSerie>>;
+}
+
+pub struct PgInventarioRepository {
     pool: DbPool,
+    metodo_valorizacion: MetodoValorizacion,
 }
 
-impl InventarioRepository {
-    pub fn new(pool: DbPool) -> Self {
-        InventarioRepository { pool }
+impl PgInventarioRepository {
+    pub fn new(pool: DbPool, metodo_valorizacion: MetodoValorizacion) -> Self {
+        PgInventarioRepository { pool, metodo_valorizacion }
     }
 
     fn get_connection(&self) -> ApiResult<DbConnection> {
@@ -25,49 +101,58 @@ impl InventarioRepository {
             .map_err(|e| ApiError::DatabaseError(e.to_string()))
     }
 
-    pub fn obtener_stock(&self, id_producto: Uuid) -> ApiResult<i32> {
-        let mut conn = self.get_connection()?;
+    #[instrument(skip(self, conn))]
+    fn actualizar_stock(&self, conn: &mut PgConnection, id_producto: Uuid, cantidad: i32) -> ApiResult<()> {
+        diesel::update(inventarios::table)
+            .filter(inventarios::id_producto.eq(id_producto))
+            .set(inventarios::cantidad_disponible.eq(inventarios::cantidad_disponible + cantidad))
+            .execute(conn)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
 
+        Ok(())
+    }
+
+    /// Obtener stock usando una conexión existente (para uso en transacciones)
+    #[instrument(skip(self, conn))]
+    fn obtener_stock_con_conexion(&self, conn: &mut PgConnection, id_producto: Uuid) -> ApiResult<i32> {
         let inventario = inventarios::table
             .filter(inventarios::id_producto.eq(id_producto))
             .filter(inventarios::activo.eq(true))
             .select(Inventario::as_select())
-            .first(&mut conn)
+            .first(conn)
             .map_err(|e| match e {
-                diesel::result::Error::NotFound => ApiError::NotFound(format!("Inventario para producto {} no encontrado", id_producto)),
+                diesel::result::Error::NotFound => inventario_no_encontrado(id_producto),
                 _ => ApiError::DatabaseError(e.to_string()),
             })?;
 
         Ok(inventario.cantidad_disponible)
     }
+}
 
-    pub fn validar_stock(&self, id_producto: Uuid, cantidad_requerida: i32) -> ApiResult<bool> {
-        let stock_actual = self.obtener_stock(id_producto)?;
-        Ok(stock_actual >= cantidad_requerida)
-    }
-
-    pub fn actualizar_stock(&self, conn: &mut PgConnection, id_producto: Uuid, cantidad: i32) -> ApiResult<()> {
-        diesel::update(inventarios::table)
-            .filter(inventarios::id_producto.eq(id_producto))
-            .set(inventarios::cantidad_disponible.eq(inventarios::cantidad_disponible + cantidad))
-            .execute(conn)
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
-
-        Ok(())
+impl InventarioRepository for PgInventarioRepository {
+    #[instrument(skip(self))]
+    fn obtener_stock(&self, id_producto: Uuid) -> ApiResult<i32> {
+        let mut conn = self.get_connection()?;
+        self.obtener_stock_con_conexion(&mut conn, id_producto)
     }
 
-    pub fn registrar_movimiento(&self, conn: &mut PgConnection, movimiento: NuevoMovimiento) -> ApiResult<Uuid> {
-        let id = Uuid::new_v4();
-
-        diesel::insert_into(detalle_inventarios::table)
-            .values(&movimiento)
-            .execute(conn)
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+    #[instrument(skip(self))]
+    fn obtener_reservado(&self, id_producto: Uuid) -> ApiResult<i32> {
+        let mut conn = self.get_connection()?;
 
-        Ok(id)
+        inventarios::table
+            .filter(inventarios::id_producto.eq(id_producto))
+            .filter(inventarios::activo.eq(true))
+            .select(inventarios::cantidad_reservada)
+            .first(&mut conn)
+            .map_err(|e| match e {
+                diesel::result::Error::NotFound => inventario_no_encontrado(id_producto),
+                _ => ApiError::DatabaseError(e.to_string()),
+            })
     }
 
-    pub fn listar_movimientos(&self, id_producto: Uuid) -> ApiResult<Vec<DetalleInventario>> {
+    #[instrument(skip(self))]
+    fn listar_movimientos(&self, id_producto: Uuid) -> ApiResult<Vec<DetalleInventario>> {
         let mut conn = self.get_connection()?;
 
         detalle_inventarios::table
@@ -79,57 +164,22 @@ impl InventarioRepository {
             .map_err(|e| ApiError::DatabaseError(e.to_string()))
     }
 
-    /// Crear inventario inicial para un producto nuevo
-    pub fn crear_inventario_inicial(
-        &self,
-        conn: &mut PgConnection,
-        id_producto: Uuid,
-        id_persona: Uuid,
-        cantidad_inicial: i32
-    ) -> ApiResult<()> {
-        let id_inventario = Uuid::new_v4();
-
-        // Crear el registro en la tabla inventarios
-        diesel::insert_into(inventarios::table)
-            .values((
-                inventarios::id.eq(id_inventario),
-                inventarios::id_producto.eq(id_producto),
-                inventarios::id_persona.eq(id_persona),
-                inventarios::cantidad_disponible.eq(cantidad_inicial),
-            ))
-            .execute(conn)
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
-
-        // Registrar el movimiento inicial de entrada si la cantidad es mayor a 0
-        if cantidad_inicial > 0 {
-            let movimiento_inicial = NuevoMovimiento {
-                id_producto,
-                tipo_movimiento: TipoMovimiento::Entrada,
-                fecha: Utc::now().naive_utc(),
-                id_persona,
-                cantidad: cantidad_inicial,
-                observaciones: Some("Inventario inicial".to_string()),
-            };
-
-            self.registrar_movimiento(conn, movimiento_inicial)?;
-        }
-
-        Ok(())
-    }
-
-    /// Registrar un movimiento y actualizar el stock
-    pub fn registrar_movimiento_con_actualizacion(
+    #[instrument(skip(self))]
+    fn registrar_movimiento_con_actualizacion(
         &self,
         id_producto: Uuid,
         tipo_movimiento: TipoMovimiento,
         id_persona: Uuid,
         cantidad: i32,
-        observaciones: Option<String>
+        observaciones: Option<String>,
+        trazabilidad: TrazabilidadMovimiento,
     ) -> ApiResult<Uuid> {
         let mut conn = self.get_connection()?;
 
         // Iniciar transacción
         conn.transaction::<Uuid, ApiError, _>(|conn| {
+            auditoria::contexto::aplicar(conn)?;
+
             // Validar que el producto tiene inventario
             let inventario_existe = inventarios::table
                 .filter(inventarios::id_producto.eq(id_producto))
@@ -144,23 +194,28 @@ impl InventarioRepository {
                 ));
             }
 
-            // Calcular el cambio en el stock según el tipo de movimiento
-            let cambio_stock = match tipo_movimiento {
-                TipoMovimiento::Entrada => cantidad,
-                TipoMovimiento::Salida => -cantidad,
-                TipoMovimiento::Ajuste => cantidad, // El ajuste puede ser positivo o negativo
+            // Validar que no quede stock negativo ni se lleve lo reservado para pedidos
+            let cambio_stock = tipo_movimiento.efecto_en_stock(cantidad);
+            let inventario = inventario_bloqueado(conn, id_producto)?;
+            let stock_actual = inventario.cantidad_disponible;
+            validar_stock_resultante(stock_actual, inventario.cantidad_reservada, cambio_stock)?;
+
+            let id_lote = match trazabilidad.lote {
+                Some(lote) => Some(aplicar_cambio_lote(conn, id_producto, &lote, cambio_stock)?),
+                None => {
+                    lotes::validar_cambio_sin_lote(stock_actual, &lotes_bloqueados(conn, id_producto)?, cambio_stock)?;
+                    None
+                }
             };
 
-            // Validar que no quede stock negativo
-            let stock_actual = self.obtener_stock_con_conexion(conn, id_producto)?;
-            let nuevo_stock = stock_actual + cambio_stock;
-
-            if nuevo_stock < 0 {
-                return Err(ApiError::BusinessRuleViolation(
-                    format!("Stock insuficiente. Stock actual: {}, Cambio solicitado: {}",
-                            stock_actual, cambio_stock)
-                ));
-            }
+            let costo_unitario = costear_movimiento(
+                conn,
+                id_producto,
+                stock_actual,
+                cambio_stock,
+                trazabilidad.costo_unitario,
+                None,
+            )?;
 
             // Actualizar el stock
             self.actualizar_stock(conn, id_producto, cambio_stock)?;
@@ -173,28 +228,673 @@ impl InventarioRepository {
                 id_persona,
                 cantidad,
                 observaciones,
+                id_movimiento_revertido: None,
+                id_lote,
+                costo_unitario: Some(costo_unitario.clone()),
+                id_detalle_pedido: None,
             };
 
-            let id = self.registrar_movimiento(conn, movimiento)?;
+            let id = registrar_movimiento(conn, movimiento)?;
+            if cambio_stock > 0 {
+                registrar_capa(conn, self.metodo_valorizacion, id_producto, stock_actual, cambio_stock, &costo_unitario, Some(id))?;
+                pedido_repo::asignar_pendientes(conn, id_producto)?;
+            }
+
+            if !trazabilidad.numeros_serie.is_empty() {
+                aplicar_series(conn, id_producto, tipo_movimiento, &trazabilidad.numeros_serie, id)?;
+            }
 
             Ok(id)
         })
     }
 
-    /// Obtener stock usando una conexión existente (para uso en transacciones)
-    fn obtener_stock_con_conexion(&self, conn: &mut PgConnection, id_producto: Uuid) -> ApiResult<i32> {
-        let inventario = inventarios::table
-            .filter(inventarios::id_producto.eq(id_producto))
-            .filter(inventarios::activo.eq(true))
-            .select(Inventario::as_select())
-            .first(conn)
+    #[instrument(skip(self))]
+    fn buscar_movimiento(&self, id: Uuid) -> ApiResult<DetalleInventario> {
+        let mut conn = self.get_connection()?;
+
+        detalle_inventarios::table
+            .find(id)
+            .filter(detalle_inventarios::activo.eq(true))
+            .select(DetalleInventario::as_select())
+            .first(&mut conn)
             .map_err(|e| match e {
-                diesel::result::Error::NotFound => ApiError::NotFound(
-                    format!("Inventario para producto {} no encontrado", id_producto)
-                ),
+                diesel::result::Error::NotFound => movimiento_no_encontrado(id),
                 _ => ApiError::DatabaseError(e.to_string()),
-            })?;
+            })
+    }
 
-        Ok(inventario.cantidad_disponible)
+    #[instrument(skip(self))]
+    fn buscar_reversion(&self, id_movimiento: Uuid) -> ApiResult<Option<Uuid>> {
+        let mut conn = self.get_connection()?;
+        reversion_de(&mut conn, id_movimiento)
+    }
+
+    #[instrument(skip(self, observaciones))]
+    fn revertir_movimiento(
+        &self,
+        id_movimiento: Uuid,
+        id_persona: Uuid,
+        observaciones: Option<String>,
+    ) -> ApiResult<Uuid> {
+        let mut conn = self.get_connection()?;
+
+        conn.transaction::<Uuid, ApiError, _>(|conn| {
+            auditoria::contexto::aplicar(conn)?;
+
+            // Bloquear el original para que dos reversiones simultáneas no pasen la validación
+            let original = detalle_inventarios::table
+                .find(id_movimiento)
+                .filter(detalle_inventarios::activo.eq(true))
+                .select(DetalleInventario::as_select())
+                .for_update()
+                .first(conn)
+                .map_err(|e| match e {
+                    diesel::result::Error::NotFound => movimiento_no_encontrado(id_movimiento),
+                    _ => ApiError::DatabaseError(e.to_string()),
+                })?;
+
+            let id_reversion = reversion_de(conn, id_movimiento)?;
+            let mut reversion = movimiento_compensatorio(&original, id_reversion, id_persona, observaciones)?;
+
+            let cambio_stock = reversion.tipo_movimiento.efecto_en_stock(reversion.cantidad);
+            let inventario = inventario_bloqueado(conn, original.id_producto)?;
+            let stock_actual = inventario.cantidad_disponible;
+            validar_stock_resultante(stock_actual, inventario.cantidad_reservada, cambio_stock)?;
+            match reversion.id_lote {
+                Some(id_lote) => sumar_a_lote(conn, id_lote, cambio_stock)?,
+                None => lotes::validar_cambio_sin_lote(
+                    stock_actual,
+                    &lotes_bloqueados(conn, original.id_producto)?,
+                    cambio_stock,
+                )?,
+            }
+            let costo_unitario = costear_movimiento(
+                conn,
+                original.id_producto,
+                stock_actual,
+                cambio_stock,
+                original.costo_unitario.clone(),
+                Some(original.id),
+            )?;
+            reversion.costo_unitario = Some(costo_unitario.clone());
+            self.actualizar_stock(conn, original.id_producto, cambio_stock)?;
+
+            let id = registrar_movimiento(conn, reversion)?;
+            if cambio_stock > 0 {
+                registrar_capa(
+                    conn,
+                    self.metodo_valorizacion,
+                    original.id_producto,
+                    stock_actual,
+                    cambio_stock,
+                    &costo_unitario,
+                    Some(id),
+                )?;
+                pedido_repo::asignar_pendientes(conn, original.id_producto)?;
+            }
+
+            Ok(id)
+        })
+    }
+
+    #[instrument(skip(self))]
+    fn listar_lotes(&self, id_producto: Uuid) -> ApiResult<Vec<Lote>> {
+        let mut conn = self.get_connection()?;
+
+        lotes_tabla::table
+            .filter(lotes_tabla::id_producto.eq(id_producto))
+            .filter(lotes_tabla::activo.eq(true))
+            .filter(lotes_tabla::cantidad_disponible.gt(0))
+            .order((lotes_tabla::fecha_vencimiento.asc(), lotes_tabla::fecha_creacion.asc()))
+            .select(Lote::as_select())
+            .load(&mut conn)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))
     }
+
+    #[instrument(skip(self))]
+    fn listar_vencimientos(&self, hasta: NaiveDate) -> ApiResult<Vec<Lote>> {
+        let mut conn = self.get_connection()?;
+
+        lotes_tabla::table
+            .filter(lotes_tabla::activo.eq(true))
+            .filter(lotes_tabla::cantidad_disponible.gt(0))
+            .filter(lotes_tabla::fecha_vencimiento.le(hasta))
+            .order((lotes_tabla::fecha_vencimiento.asc(), lotes_tabla::codigo.asc()))
+            .select(Lote::as_select())
+            .load(&mut conn)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+    }
+
+    #[instrument(skip(self))]
+    fn buscar_lote(&self, id: Uuid) -> ApiResult<Lote> {
+        let mut conn = self.get_connection()?;
+
+        lotes_tabla::table
+            .find(id)
+            .select(Lote::as_select())
+            .first(&mut conn)
+            .map_err(|e| match e {
+                diesel::result::Error::NotFound => lote_no_encontrado(id),
+                _ => ApiError::DatabaseError(e.to_string()),
+            })
+    }
+
+    #[instrument(skip(self))]
+    fn buscar_serie(&self, id_producto: Uuid, numero: &str) -> ApiResult<Option<NumeroSerie>> {
+        let mut conn = self.get_connection()?;
+
+        numeros_serie::table
+            .filter(numeros_serie::id_producto.eq(id_producto))
+            .filter(numeros_serie::numero.eq(numero))
+            .filter(numeros_serie::activo.eq(true))
+            .select(NumeroSerie::as_select())
+            .first(&mut conn)
+            .optional()
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+    }
+
+    #[instrument(skip(self))]
+    fn buscar_series_por_numero(&self, numero: &str) -> ApiResult<Vec<NumeroSerie>> {
+        let mut conn = self.get_connection()?;
+
+        numeros_serie::table
+            .filter(numeros_serie::numero.eq(numero))
+            .filter(numeros_serie::activo.eq(true))
+            .order(numeros_serie::fecha_creacion.asc())
+            .select(NumeroSerie::as_select())
+            .load(&mut conn)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+    }
+}
+
+/// Lotes activos del producto, bloqueados hasta el final de la transacción
+fn lotes_bloqueados(conn: &mut PgConnection, id_producto: Uuid) -> ApiResult<Vec<Lote>> {
+    lotes_tabla::table
+        .filter(lotes_tabla::id_producto.eq(id_producto))
+        .filter(lotes_tabla::activo.eq(true))
+        .select(Lote::as_select())
+        .for_update()
+        .load(conn)
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))
+}
+
+/// Aplica el cambio de stock al lote con ese código, creándolo si hace falta. Devuelve su id.
+fn aplicar_cambio_lote(
+    conn: &mut PgConnection,
+    id_producto: Uuid,
+    lote: &LoteMovimiento,
+    cambio_stock: i32,
+) -> ApiResult<Uuid> {
+    let lotes_producto = lotes_bloqueados(conn, id_producto)?;
+    let existente = lotes_producto.iter().find(|l| l.codigo == lote.codigo);
+
+    match lotes::planificar_cambio_lote(existente, lote, cambio_stock)? {
+        CambioLote::Existente(id_lote) => {
+            actualizar_lote(conn, id_lote, cambio_stock)?;
+            Ok(id_lote)
+        }
+        CambioLote::Nuevo(fecha_vencimiento) => diesel::insert_into(lotes_tabla::table)
+            .values(&NuevoLote {
+                id_producto,
+                codigo: lote.codigo.clone(),
+                fecha_vencimiento,
+                cantidad_disponible: cambio_stock,
+            })
+            .returning(lotes_tabla::id)
+            .get_result(conn)
+            .map_err(|e| ApiError::DatabaseError(e.to_string())),
+    }
+}
+
+/// Suma el cambio a un lote concreto (reversiones), validando que no quede negativo
+fn sumar_a_lote(conn: &mut PgConnection, id_lote: Uuid, cambio_stock: i32) -> ApiResult<()> {
+    let lote = lotes_tabla::table
+        .find(id_lote)
+        .select(Lote::as_select())
+        .for_update()
+        .first(conn)
+        .map_err(|e| match e {
+            diesel::result::Error::NotFound => lote_no_encontrado(id_lote),
+            _ => ApiError::DatabaseError(e.to_string()),
+        })?;
+    lotes::validar_cambio_lote(&lote, cambio_stock)?;
+    actualizar_lote(conn, id_lote, cambio_stock)
+}
+
+fn actualizar_lote(conn: &mut PgConnection, id_lote: Uuid, cambio_stock: i32) -> ApiResult<()> {
+    diesel::update(lotes_tabla::table.find(id_lote))
+        .set(lotes_tabla::cantidad_disponible.eq(lotes_tabla::cantidad_disponible + cambio_stock))
+        .execute(conn)
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+    Ok(())
+}
+
+/// Da de alta, devuelve o da de baja los números de serie de un movimiento ya insertado
+pub(crate) fn aplicar_series(
+    conn: &mut PgConnection,
+    id_producto: Uuid,
+    tipo_movimiento: TipoMovimiento,
+    numeros: &[String],
+    id_movimiento: Uuid,
+) -> ApiResult<()> {
+    let existentes = numeros_serie::table
+        .filter(numeros_serie::id_producto.eq(id_producto))
+        .filter(numeros_serie::numero.eq_any(numeros))
+        .filter(numeros_serie::activo.eq(true))
+        .select(NumeroSerie::as_select())
+        .for_update()
+        .load(conn)
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+    for cambio in series::planificar_series(tipo_movimiento, &existentes, numeros)? {
+        match cambio {
+            CambioSerie::Alta(numero) => {
+                diesel::insert_into(numeros_serie::table)
+                    .values(&NuevoNumeroSerie {
+                        id_producto,
+                        numero,
+                        estado: EstadoSerie::EnStock,
+                        id_movimiento: Some(id_movimiento),
+                    })
+                    .execute(conn)
+                    .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+            }
+            CambioSerie::Devolucion(id_serie) => {
+                diesel::update(numeros_serie::table.find(id_serie))
+                    .set((
+                        numeros_serie::estado.eq(EstadoSerie::Devuelto),
+                        numeros_serie::id_movimiento.eq(id_movimiento),
+                    ))
+                    .execute(conn)
+                    .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+            }
+            CambioSerie::Baja(id_serie) => {
+                diesel::update(numeros_serie::table.find(id_serie))
+                    .set((
+                        numeros_serie::activo.eq(false),
+                        numeros_serie::id_movimiento.eq(id_movimiento),
+                    ))
+                    .execute(conn)
+                    .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Registra los números de serie de las unidades ya en stock de un producto que pasa a ser
+/// serializado, dentro de su transacción. Rechaza los que ya tenga registrados.
+pub(crate) fn registrar_series_existentes(conn: &mut PgConnection, id_producto: Uuid, numeros: &[String]) -> ApiResult<()> {
+    let registrado: Option<String> = numeros_serie::table
+        .filter(numeros_serie::id_producto.eq(id_producto))
+        .filter(numeros_serie::numero.eq_any(numeros))
+        .filter(numeros_serie::activo.eq(true))
+        .select(numeros_serie::numero)
+        .first(conn)
+        .optional()
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+    if let Some(numero) = registrado {
+        return Err(series::serie_duplicada(&numero));
+    }
+
+    let nuevos: Vec<NuevoNumeroSerie> = numeros
+        .iter()
+        .map(|numero| NuevoNumeroSerie {
+            id_producto,
+            numero: numero.clone(),
+            estado: EstadoSerie::EnStock,
+            id_movimiento: None,
+        })
+        .collect();
+    diesel::insert_into(numeros_serie::table)
+        .values(&nuevos)
+        .execute(conn)
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+    Ok(())
+}
+
+/// Inventario activo del producto, bloqueado hasta el final de la transacción
+pub(crate) fn inventario_bloqueado(conn: &mut PgConnection, id_producto: Uuid) -> ApiResult<Inventario> {
+    inventarios::table
+        .filter(inventarios::id_producto.eq(id_producto))
+        .filter(inventarios::activo.eq(true))
+        .select(Inventario::as_select())
+        .for_update()
+        .first(conn)
+        .map_err(|e| match e {
+            diesel::result::Error::NotFound => inventario_no_encontrado(id_producto),
+            _ => ApiError::DatabaseError(e.to_string()),
+        })
+}
+
+/// Stock del producto, bloqueando su inventario hasta el final de la transacción
+pub(crate) fn stock_bloqueado(conn: &mut PgConnection, id_producto: Uuid) -> ApiResult<i32> {
+    inventario_bloqueado(conn, id_producto).map(|inventario| inventario.cantidad_disponible)
+}
+
+/// Marca como vendidas las unidades de una venta. Falla si alguna ya no está en el almacén
+/// (otra venta o una SALIDA se la llevó después de validarla).
+pub(crate) fn vender_series(conn: &mut PgConnection, ids_serie: &[Uuid]) -> ApiResult<()> {
+    for id_serie in ids_serie {
+        let vendidas = diesel::update(
+            numeros_serie::table
+                .find(id_serie)
+                .filter(numeros_serie::activo.eq(true))
+                .filter(numeros_serie::estado.eq_any([EstadoSerie::EnStock, EstadoSerie::Devuelto])),
+        )
+        .set(numeros_serie::estado.eq(EstadoSerie::Vendido))
+        .execute(conn)
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+        if vendidas == 0 {
+            return Err(serie_no_disponible(*id_serie));
+        }
+    }
+
+    Ok(())
+}
+
+/// Descuenta `cantidad` de los lotes del producto en orden FEFO, dentro de la transacción de
+/// una venta y antes de insertar su detalle (el stock leído es el previo a la venta).
+/// Devuelve (id del lote, cantidad) por cada lote consumido.
+pub(crate) fn consumir_fefo(conn: &mut PgConnection, id_producto: Uuid, cantidad: i32) -> ApiResult<Vec<(Uuid, i32)>> {
+    let stock_actual = stock_bloqueado(conn, id_producto)?;
+    let lotes_producto = lotes_bloqueados(conn, id_producto)?;
+
+    let asignacion = lotes::asignar_fefo(
+        id_producto,
+        &lotes_producto,
+        stock_actual,
+        cantidad,
+        Utc::now().date_naive(),
+    )?;
+    for (id_lote, tomada) in &asignacion {
+        actualizar_lote(conn, *id_lote, -tomada)?;
+    }
+
+    Ok(asignacion)
+}
+
+/// Capas activas del producto con unidades, bloqueadas hasta el final de la transacción
+pub(crate) fn capas_bloqueadas(conn: &mut PgConnection, id_producto: Uuid) -> ApiResult<Vec<CapaCosto>> {
+    capas_costo::table
+        .filter(capas_costo::id_producto.eq(id_producto))
+        .filter(capas_costo::activo.eq(true))
+        .filter(capas_costo::cantidad_disponible.gt(0))
+        .order(capas_costo::fecha_creacion.asc())
+        .select(CapaCosto::as_select())
+        .for_update()
+        .load(conn)
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))
+}
+
+/// Descuenta una salida de `cantidad` unidades de las capas del producto (ver
+/// `costos::consumir`); `stock_actual` es el previo a la salida. Primero sale de la capa que
+/// creó `id_movimiento_origen`, si se indica.
+pub(crate) fn consumir_capas(
+    conn: &mut PgConnection,
+    id_producto: Uuid,
+    stock_actual: i32,
+    cantidad: i32,
+    id_movimiento_origen: Option<Uuid>,
+) -> ApiResult<ConsumoCosto> {
+    let capas = capas_bloqueadas(conn, id_producto)?;
+    let preferida = id_movimiento_origen
+        .and_then(|id_movimiento| capas.iter().find(|capa| capa.id_movimiento == Some(id_movimiento)))
+        .map(|capa| capa.id);
+
+    let consumo = costos::consumir(stock_actual, &capas, cantidad, preferida);
+    for (id_capa, tomada) in &consumo.capas {
+        diesel::update(capas_costo::table.find(id_capa))
+            .set(capas_costo::cantidad_disponible.eq(capas_costo::cantidad_disponible - tomada))
+            .execute(conn)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+    }
+
+    Ok(consumo)
+}
+
+/// Costo unitario de un movimiento que cambia el stock en `cambio_stock`. Lo que entra vale
+/// `costo_entrada` o, sin él, el costo promedio del stock; lo que sale, lo que cuestan las capas
+/// que consume, que se descuentan aquí.
+pub(crate) fn costear_movimiento(
+    conn: &mut PgConnection,
+    id_producto: Uuid,
+    stock_actual: i32,
+    cambio_stock: i32,
+    costo_entrada: Option<BigDecimal>,
+    id_movimiento_origen: Option<Uuid>,
+) -> ApiResult<BigDecimal> {
+    if cambio_stock >= 0 {
+        return Ok(match costo_entrada {
+            Some(costo) => costos::redondear_costo(&costo),
+            None => costos::costo_promedio(stock_actual, &capas_bloqueadas(conn, id_producto)?),
+        });
+    }
+
+    let consumo = consumir_capas(conn, id_producto, stock_actual, -cambio_stock, id_movimiento_origen)?;
+    Ok(consumo.costo_unitario(-cambio_stock))
+}
+
+/// Crea la capa de una entrada ya registrada como `id_movimiento`; con promedio, lleva las
+/// capas existentes al nuevo costo promedio. `stock_previo` es el anterior a la entrada.
+pub(crate) fn registrar_capa(
+    conn: &mut PgConnection,
+    metodo: MetodoValorizacion,
+    id_producto: Uuid,
+    stock_previo: i32,
+    cantidad: i32,
+    costo_unitario: &BigDecimal,
+    id_movimiento: Option<Uuid>,
+) -> ApiResult<()> {
+    let capas = capas_bloqueadas(conn, id_producto)?;
+    let entrada = costos::planificar_entrada(metodo, stock_previo, &capas, cantidad, costo_unitario);
+
+    if let Some(promedio) = entrada.recostear {
+        let ids: Vec<Uuid> = capas.iter().map(|capa| capa.id).collect();
+        diesel::update(capas_costo::table.filter(capas_costo::id.eq_any(ids)))
+            .set(capas_costo::costo_unitario.eq(promedio))
+            .execute(conn)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+    }
+
+    diesel::insert_into(capas_costo::table)
+        .values(&NuevaCapaCosto {
+            id_producto,
+            id_movimiento,
+            costo_unitario: entrada.costo_unitario,
+            cantidad_inicial: entrada.cantidad,
+            cantidad_disponible: entrada.cantidad,
+        })
+        .execute(conn)
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+    Ok(())
+}
+
+/// Devuelve al stock (y al lote, si se indica) unidades que salieron en una venta, con una
+/// ENTRADA al costo con que salieron que crea su capa de costo y que se asigna a los pedidos
+/// que esperaban stock. Devuelve el id del movimiento.
+pub(crate) fn devolver_al_stock(
+    conn: &mut PgConnection,
+    metodo: MetodoValorizacion,
+    devolucion: NuevoMovimiento,
+) -> ApiResult<Uuid> {
+    let id_producto = devolucion.id_producto;
+    let cantidad = devolucion.cantidad;
+    let costo_unitario = devolucion.costo_unitario.clone().unwrap_or_default();
+
+    let stock_actual = stock_bloqueado(conn, id_producto)?;
+    if let Some(id_lote) = devolucion.id_lote {
+        sumar_a_lote(conn, id_lote, cantidad)?;
+    }
+    diesel::update(inventarios::table)
+        .filter(inventarios::id_producto.eq(id_producto))
+        .filter(inventarios::activo.eq(true))
+        .set(inventarios::cantidad_disponible.eq(inventarios::cantidad_disponible + cantidad))
+        .execute(conn)
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+    let id = registrar_movimiento(conn, devolucion)?;
+    registrar_capa(conn, metodo, id_producto, stock_actual, cantidad, &costo_unitario, Some(id))?;
+    pedido_repo::asignar_pendientes(conn, id_producto)?;
+    Ok(id)
+}
+
+fn reversion_de(conn: &mut PgConnection, id_movimiento: Uuid) -> ApiResult<Option<Uuid>> {
+    detalle_inventarios::table
+        .filter(detalle_inventarios::id_movimiento_revertido.eq(id_movimiento))
+        .select(detalle_inventarios::id)
+        .first(conn)
+        .optional()
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))
+}
+
+#[instrument(skip(conn, movimiento))]
+pub(crate) fn registrar_movimiento(conn: &mut PgConnection, movimiento: NuevoMovimiento) -> ApiResult<Uuid> {
+    diesel::insert_into(detalle_inventarios::table)
+        .values(&movimiento)
+        .returning(detalle_inventarios::id)
+        .get_result(conn)
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))
+}
+
+/// Crear inventario inicial para un producto nuevo (dentro de la transacción del producto)
+#[instrument(skip(conn))]
+pub(crate) fn crear_inventario_inicial(
+    conn: &mut PgConnection,
+    id_producto: Uuid,
+    id_persona: Uuid,
+    cantidad_inicial: i32,
+    costo_inicial: BigDecimal,
+) -> ApiResult<()> {
+    let id_inventario = Uuid::new_v4();
+
+    // Crear el registro en la tabla inventarios
+    diesel::insert_into(inventarios::table)
+        .values((
+            inventarios::id.eq(id_inventario),
+            inventarios::id_producto.eq(id_producto),
+            inventarios::id_persona.eq(id_persona),
+            inventarios::cantidad_disponible.eq(cantidad_inicial),
+        ))
+        .execute(conn)
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+    // Registrar el movimiento inicial de entrada si la cantidad es mayor a 0, con su capa de costo
+    if let Some(movimiento_inicial) = movimiento_inicial(id_producto, id_persona, cantidad_inicial, costo_inicial) {
+        let costo_unitario = movimiento_inicial.costo_unitario.clone().unwrap_or_default();
+        let id = registrar_movimiento(conn, movimiento_inicial)?;
+        // Sin capas previas ambos métodos crean la misma capa
+        registrar_capa(conn, MetodoValorizacion::Fifo, id_producto, 0, cantidad_inicial, &costo_unitario, Some(id))?;
+    }
+
+    Ok(())
+}
+
+/// ENTRADA con la que se abre el inventario de un producto nuevo, si trae unidades
+pub(crate) fn movimiento_inicial(
+    id_producto: Uuid,
+    id_persona: Uuid,
+    cantidad_inicial: i32,
+    costo_inicial: BigDecimal,
+) -> Option<NuevoMovimiento> {
+    (cantidad_inicial > 0).then(|| NuevoMovimiento {
+        id_producto,
+        tipo_movimiento: TipoMovimiento::Entrada,
+        fecha: Utc::now().naive_utc(),
+        id_persona,
+        cantidad: cantidad_inicial,
+        observaciones: Some("Inventario inicial".to_string()),
+        id_movimiento_revertido: None,
+        id_lote: None,
+        costo_unitario: Some(costos::redondear_costo(&costo_inicial)),
+        id_detalle_pedido: None,
+    })
+}
+
+/// Movimiento que deshace el efecto de `original` sobre el stock. Las ENTRADAS y SALIDAS se
+/// compensan con el tipo contrario por la misma cantidad y los AJUSTES con el signo cambiado.
+/// Lo despachado de un pedido no vuelve al stock: ya va camino del cliente.
+pub(crate) fn movimiento_compensatorio(
+    original: &DetalleInventario,
+    id_reversion: Option<Uuid>,
+    id_persona: Uuid,
+    observaciones: Option<String>,
+) -> ApiResult<NuevoMovimiento> {
+    if let Some(id_original) = original.id_movimiento_revertido {
+        return Err(ApiError::BusinessRuleViolation(format!(
+            "El movimiento {} ya es la reversión de {} y no se puede revertir",
+            original.id, id_original
+        )));
+    }
+    if let Some(id_reversion) = id_reversion {
+        return Err(ApiError::BusinessRuleViolation(format!(
+            "El movimiento {} ya fue revertido por {}",
+            original.id, id_reversion
+        )));
+    }
+    if let Some(id_detalle_pedido) = original.id_detalle_pedido {
+        return Err(ApiError::BusinessRuleViolation(format!(
+            "El movimiento {} es el despacho de la línea de pedido {} y no se puede revertir",
+            original.id, id_detalle_pedido
+        )));
+    }
+
+    let tipo_movimiento = original.tipo_movimiento.compensatorio();
+    let cantidad = match original.tipo_movimiento {
+        TipoMovimiento::Ajuste => -original.cantidad,
+        TipoMovimiento::Entrada | TipoMovimiento::Salida => original.cantidad.abs(),
+    };
+    let mut detalle = format!("Reversión del movimiento {}", original.id);
+    if let Some(observaciones) = observaciones.as_deref().map(str::trim).filter(|o| !o.is_empty()) {
+        detalle.push_str(": ");
+        detalle.push_str(observaciones);
+    }
+
+    Ok(NuevoMovimiento {
+        id_producto: original.id_producto,
+        tipo_movimiento,
+        fecha: Utc::now().naive_utc(),
+        id_persona,
+        cantidad,
+        observaciones: Some(detalle),
+        id_movimiento_revertido: Some(original.id),
+        id_lote: original.id_lote,
+        costo_unitario: None,
+        id_detalle_pedido: None,
+    })
+}
+
+/// Regla de negocio compartida por todas las implementaciones: el stock nunca queda negativo ni
+/// por debajo de lo `reservado` para pedidos
+pub(crate) fn validar_stock_resultante(stock_actual: i32, reservado: i32, cambio_stock: i32) -> ApiResult<()> {
+    if stock_actual - reservado + cambio_stock < 0 {
+        metrics().rechazos_stock_insuficiente.with_label_values(&["movimiento"]).inc();
+        let mut mensaje = format!("Stock insuficiente. Stock actual: {}, Cambio solicitado: {}", stock_actual, cambio_stock);
+        if reservado > 0 {
+            mensaje.push_str(&format!(", Reservado para pedidos: {}", reservado));
+        }
+        return Err(ApiError::BusinessRuleViolation(mensaje));
+    }
+
+    Ok(())
+}
+
+pub(crate) fn inventario_no_encontrado(id_producto: Uuid) -> ApiError {
+    ApiError::NotFound(format!("Inventario para producto {} no encontrado", id_producto))
+}
+
+pub(crate) fn movimiento_no_encontrado(id: Uuid) -> ApiError {
+    ApiError::NotFound(format!("Movimiento de inventario {} no encontrado", id))
+}
+
+pub(crate) fn lote_no_encontrado(id: Uuid) -> ApiError {
+    ApiError::NotFound(format!("Lote {} no encontrado", id))
+}
+
+pub(crate) fn serie_no_disponible(id_serie: Uuid) -> ApiError {
+    ApiError::BusinessRuleViolation(format!("El número de serie {} ya no está en stock", id_serie))
 }
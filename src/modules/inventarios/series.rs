@@ -0,0 +1,151 @@
+//! Números de serie de los productos serializados.
+//!
+//! Cada unidad en stock de un producto serializado tiene su número: una ENTRADA lo da de alta
+//! (o lo marca DEVUELTO si vuelve tras venderse), una venta lo marca VENDIDO y una SALIDA manual
+//! lo da de baja.
+
+use std::collections::HashSet;
+use uuid::Uuid;
+use crate::modules::common::errors::{ApiError, ApiResult};
+use crate::modules::common::types::{EstadoSerie, TipoMovimiento};
+use crate::modules::inventarios::model::NumeroSerie;
+
+/// Longitud máxima de un número de serie
+pub const LONGITUD_MAXIMA_NUMERO: usize = 100;
+
+/// Los números se guardan sin espacios sobrantes y en mayúsculas
+pub fn normalizar_numero(numero: &str) -> ApiResult<String> {
+    let numero = numero.trim().to_uppercase();
+    if numero.is_empty() {
+        return Err(ApiError::InvalidInput("El número de serie no puede estar vacío".to_string()));
+    }
+    if numero.chars().count() > LONGITUD_MAXIMA_NUMERO {
+        return Err(ApiError::InvalidInput(format!(
+            "El número de serie no puede superar los {} caracteres",
+            LONGITUD_MAXIMA_NUMERO
+        )));
+    }
+    Ok(numero)
+}
+
+/// Normaliza una lista de números rechazando los repetidos
+pub fn normalizar_numeros(numeros: &[String]) -> ApiResult<Vec<String>> {
+    let mut normalizados = Vec::with_capacity(numeros.len());
+    let mut vistos = HashSet::new();
+    for numero in numeros {
+        let numero = normalizar_numero(numero)?;
+        if !vistos.insert(numero.clone()) {
+            return Err(ApiError::InvalidInput(format!("El número de serie {} está repetido", numero)));
+        }
+        normalizados.push(numero);
+    }
+    Ok(normalizados)
+}
+
+/// Un producto serializado se mueve con un número por unidad y nunca con AJUSTE; los demás
+/// no admiten números de serie
+pub fn validar_series_movimiento(
+    serializado: bool,
+    tipo_movimiento: TipoMovimiento,
+    cantidad: i32,
+    numeros: usize,
+) -> ApiResult<()> {
+    if !serializado {
+        if numeros > 0 {
+            return Err(ApiError::InvalidInput("El producto no lleva números de serie".to_string()));
+        }
+        return Ok(());
+    }
+    if tipo_movimiento == TipoMovimiento::Ajuste {
+        return Err(ApiError::BusinessRuleViolation(
+            "Los productos serializados no admiten AJUSTE: registre una ENTRADA o SALIDA con sus números de serie"
+                .to_string(),
+        ));
+    }
+    if numeros as i64 != cantidad as i64 {
+        return Err(ApiError::InvalidInput(format!(
+            "Indique un número de serie por unidad: {} unidades, {} números",
+            cantidad, numeros
+        )));
+    }
+    Ok(())
+}
+
+/// Al marcar un producto como serializado cada unidad en stock necesita su número; solo se
+/// puede desmarcar sin stock
+pub fn validar_cambio_serializado(serializado: bool, stock_actual: i32, numeros: usize) -> ApiResult<()> {
+    if serializado && numeros as i64 != stock_actual as i64 {
+        return Err(ApiError::BusinessRuleViolation(format!(
+            "El producto tiene {} unidades en stock: indique un número de serie por unidad ({} recibidos)",
+            stock_actual, numeros
+        )));
+    }
+    if !serializado && stock_actual != 0 {
+        return Err(ApiError::BusinessRuleViolation(format!(
+            "Solo se deja de seguir un producto por número de serie sin stock (hay {} unidades)",
+            stock_actual
+        )));
+    }
+    Ok(())
+}
+
+/// Qué hacer con cada número de serie de un movimiento
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CambioSerie {
+    /// Registrar el número, EN_STOCK
+    Alta(String),
+    /// Una unidad vendida que vuelve: pasa a DEVUELTO
+    Devolucion(Uuid),
+    /// Una unidad en el almacén que sale sin venderse: se da de baja
+    Baja(Uuid),
+}
+
+/// Decide cómo aplicar el movimiento a cada número; `existentes` son los números activos del
+/// producto
+pub fn planificar_series(
+    tipo_movimiento: TipoMovimiento,
+    existentes: &[NumeroSerie],
+    numeros: &[String],
+) -> ApiResult<Vec<CambioSerie>> {
+    numeros
+        .iter()
+        .map(|numero| {
+            let existente = existentes.iter().find(|serie| serie.activo && &serie.numero == numero);
+            match (tipo_movimiento, existente) {
+                (TipoMovimiento::Entrada, None) => Ok(CambioSerie::Alta(numero.clone())),
+                (TipoMovimiento::Entrada, Some(serie)) if serie.estado == EstadoSerie::Vendido => {
+                    Ok(CambioSerie::Devolucion(serie.id))
+                }
+                (TipoMovimiento::Entrada, Some(serie)) => Err(serie_duplicada(&serie.numero)),
+                (TipoMovimiento::Salida, Some(serie)) => {
+                    validar_serie_en_almacen(serie)?;
+                    Ok(CambioSerie::Baja(serie.id))
+                }
+                (TipoMovimiento::Salida, None) => Err(serie_no_encontrada(numero)),
+                (TipoMovimiento::Ajuste, _) => Err(ApiError::BusinessRuleViolation(
+                    "Los números de serie no se ajustan".to_string(),
+                )),
+            }
+        })
+        .collect()
+}
+
+/// Solo se vende o da de baja una unidad que está en el almacén
+pub fn validar_serie_en_almacen(serie: &NumeroSerie) -> ApiResult<()> {
+    if !serie.activo || !serie.estado.en_almacen() {
+        return Err(ApiError::BusinessRuleViolation(format!(
+            "El número de serie {} no está en stock ({})",
+            serie.numero,
+            serie.estado.as_str()
+        )));
+    }
+    Ok(())
+}
+
+pub fn serie_no_encontrada(numero: &str) -> ApiError {
+    ApiError::NotFound(format!("Número de serie {} no encontrado", numero))
+}
+
+pub fn serie_duplicada(numero: &str) -> ApiError {
+    ApiError::BusinessRuleViolation(format!("El número de serie {} ya está registrado", numero))
+}
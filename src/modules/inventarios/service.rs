@@ -1,47 +1,109 @@
+use std::sync::Arc;
+use chrono::{Duration, NaiveDate, Utc};
 use uuid::Uuid;
+use tracing::instrument;
+use crate::metrics::metrics;
 use crate::modules::common::errors::{ApiError, ApiResult};
 use crate::modules::common::types::TipoMovimiento;
-use crate::modules::inventarios::model::{DisponibilidadResponse, MovimientoRequest, MovimientoRegistradoResponse};
+use crate::modules::inventarios::{costos, lotes, series};
+use crate::modules::inventarios::model::{
+    DisponibilidadResponse, LoteMovimiento, LoteResponse, MovimientoRequest, MovimientoRegistradoResponse,
+    MovimientoResponse, RevertirMovimientoRequest, TrazabilidadMovimiento,
+};
 use crate::modules::inventarios::repository::InventarioRepository;
 use crate::modules::productos::repository::ProductoRepository;
 use crate::modules::personas::repository::PersonaRepository;
 
+/// Días por defecto y máximos de la consulta de vencimientos
+const DIAS_VENCIMIENTO_POR_DEFECTO: i64 = 30;
+const DIAS_VENCIMIENTO_MAXIMOS: i64 = 3650;
+
 pub struct InventarioService {
-    inventario_repo: InventarioRepository,
-    producto_repo: ProductoRepository,
-    persona_repo: PersonaRepository,
+    inventario_repo: Arc<dyn InventarioRepository>,
+    producto_repo: Arc<dyn ProductoRepository>,
+    persona_repo: Arc<dyn PersonaRepository>,
+    low_stock_threshold: i32,
 }
 
 impl InventarioService {
     pub fn new(
-        inventario_repo: InventarioRepository,
-        producto_repo: ProductoRepository,
-        persona_repo: PersonaRepository,
+        inventario_repo: Arc<dyn InventarioRepository>,
+        producto_repo: Arc<dyn ProductoRepository>,
+        persona_repo: Arc<dyn PersonaRepository>,
+        low_stock_threshold: i32,
     ) -> Self {
         InventarioService {
             inventario_repo,
             producto_repo,
             persona_repo,
+            low_stock_threshold,
         }
     }
 
     /// RF5: Consultar disponibilidad de inventario
+    #[instrument(skip(self))]
     pub fn obtener_disponibilidad(&self, id_producto_str: &str) -> ApiResult<DisponibilidadResponse> {
         let id_producto = Uuid::parse_str(id_producto_str)
             .map_err(|_| ApiError::InvalidInput("ID de producto inválido".to_string()))?;
 
         // Verificar que el producto existe
         self.producto_repo.verificar_existe_y_activo(id_producto)?;
+        let producto = self.producto_repo.buscar_por_id(id_producto)?;
 
         let cantidad_disponible = self.inventario_repo.obtener_stock(id_producto)?;
+        let cantidad_reservada = self.inventario_repo.obtener_reservado(id_producto)?;
+        let hoy = Utc::now().date_naive();
+        let lotes = self
+            .inventario_repo
+            .listar_lotes(id_producto)?
+            .into_iter()
+            .map(|lote| LoteResponse::new(lote, producto.nombre.clone(), hoy))
+            .collect();
 
         Ok(DisponibilidadResponse {
             id_producto: id_producto.to_string(),
             cantidad_disponible,
+            cantidad_reservada,
+            estado_stock: self.estado_stock(cantidad_disponible).to_string(),
+            lotes,
         })
     }
 
+    /// Lotes con existencias que vencen en los próximos `dias` días, incluidos los ya vencidos
+    #[instrument(skip(self))]
+    pub fn listar_vencimientos(&self, dias: Option<i64>) -> ApiResult<Vec<LoteResponse>> {
+        let dias = dias.unwrap_or(DIAS_VENCIMIENTO_POR_DEFECTO);
+        if !(0..=DIAS_VENCIMIENTO_MAXIMOS).contains(&dias) {
+            return Err(ApiError::InvalidInput(format!(
+                "dias debe estar entre 0 y {}",
+                DIAS_VENCIMIENTO_MAXIMOS
+            )));
+        }
+
+        let hoy = Utc::now().date_naive();
+        self.inventario_repo
+            .listar_vencimientos(hoy + Duration::days(dias))?
+            .into_iter()
+            .map(|lote| {
+                let producto = self.producto_repo.buscar_por_id(lote.id_producto)?;
+                Ok(LoteResponse::new(lote, producto.nombre, hoy))
+            })
+            .collect()
+    }
+
+    /// Clasifica el stock igual que `vista_stock_productos`, con el umbral configurado
+    fn estado_stock(&self, cantidad_disponible: i32) -> &'static str {
+        if cantidad_disponible <= 0 {
+            "SIN_STOCK"
+        } else if cantidad_disponible < self.low_stock_threshold {
+            "STOCK_BAJO"
+        } else {
+            "STOCK_OK"
+        }
+    }
+
     /// Validar que hay suficiente stock para una cantidad requerida
+    #[instrument(skip(self))]
     pub fn validar_stock(&self, id_producto: Uuid, cantidad_requerida: i32) -> ApiResult<()> {
         let stock_disponible = self.inventario_repo.obtener_stock(id_producto)?;
 
@@ -53,6 +115,7 @@ impl InventarioService {
     }
 
     /// Registrar un movimiento de inventario (ENTRADA, SALIDA, AJUSTE)
+    #[instrument(skip(self, request), fields(id_producto = %request.id_producto, tipo = %request.tipo_movimiento))]
     pub fn registrar_movimiento(&self, request: MovimientoRequest) -> ApiResult<MovimientoRegistradoResponse> {
         // Validar ID de producto
         let id_producto = Uuid::parse_str(&request.id_producto)
@@ -86,6 +149,27 @@ impl InventarioService {
             )),
         };
 
+        // El costo solo se indica al comprar; los demás movimientos se valoran con las capas
+        let costo_unitario = match request.costo_unitario {
+            Some(_) if tipo_movimiento != TipoMovimiento::Entrada => {
+                return Err(ApiError::InvalidInput("costo_unitario solo se indica en las ENTRADAS".to_string()));
+            }
+            Some(costo) => Some(costos::costo_desde_f64(costo)?),
+            None => None,
+        };
+
+        let trazabilidad = TrazabilidadMovimiento {
+            lote: lote_movimiento(request.lote.as_deref(), request.fecha_vencimiento.as_deref())?,
+            numeros_serie: series::normalizar_numeros(&request.numeros_serie)?,
+            costo_unitario,
+        };
+        series::validar_series_movimiento(
+            producto.serializado,
+            tipo_movimiento,
+            request.cantidad,
+            trazabilidad.numeros_serie.len(),
+        )?;
+
         // Registrar el movimiento con actualización de stock
         let id = self.inventario_repo.registrar_movimiento_con_actualizacion(
             id_producto,
@@ -93,8 +177,13 @@ impl InventarioService {
             id_persona,
             request.cantidad,
             request.observaciones,
+            trazabilidad,
         )?;
 
+        metrics().movimientos_inventario
+            .with_label_values(&[tipo_movimiento.as_str()])
+            .inc();
+
         let mensaje = match tipo_movimiento {
             TipoMovimiento::Entrada => format!(
                 "Entrada registrada: +{} unidades de '{}'. Stock actualizado.",
@@ -115,4 +204,86 @@ impl InventarioService {
             mensaje,
         })
     }
+
+    /// Consultar un movimiento de inventario por su ID
+    #[instrument(skip(self))]
+    pub fn obtener_movimiento(&self, id_str: &str) -> ApiResult<MovimientoResponse> {
+        let id = Uuid::parse_str(id_str)
+            .map_err(|_| ApiError::InvalidInput("ID de movimiento inválido".to_string()))?;
+
+        let movimiento = self.inventario_repo.buscar_movimiento(id)?;
+        let id_reversion = self.inventario_repo.buscar_reversion(id)?;
+
+        Ok(MovimientoResponse::new(movimiento, id_reversion))
+    }
+
+    /// Revertir un movimiento registrando otro de efecto contrario enlazado al original
+    #[instrument(skip(self, request))]
+    pub fn revertir_movimiento(
+        &self,
+        id_str: &str,
+        request: RevertirMovimientoRequest,
+    ) -> ApiResult<MovimientoRegistradoResponse> {
+        let id = Uuid::parse_str(id_str)
+            .map_err(|_| ApiError::InvalidInput("ID de movimiento inválido".to_string()))?;
+
+        let id_persona = Uuid::parse_str(&request.id_persona)
+            .map_err(|_| ApiError::InvalidInput("ID de persona inválido".to_string()))?;
+
+        let original = self.inventario_repo.buscar_movimiento(id)?;
+
+        // Validar que la persona existe y está activa
+        let persona = self.persona_repo.buscar_por_id(id_persona)?;
+        if !persona.activo {
+            return Err(ApiError::InactiveClient);
+        }
+
+        let producto = self.producto_repo.buscar_por_id(original.id_producto)?;
+        if producto.serializado {
+            return Err(ApiError::BusinessRuleViolation(
+                "Los movimientos de productos serializados no se revierten: registre el movimiento contrario con sus números de serie".to_string()
+            ));
+        }
+
+        let id_reversion = self.inventario_repo.revertir_movimiento(id, id_persona, request.observaciones)?;
+
+        let tipo_reversion = original.tipo_movimiento.compensatorio();
+        metrics().movimientos_inventario
+            .with_label_values(&[tipo_reversion.as_str()])
+            .inc();
+
+        Ok(MovimientoRegistradoResponse {
+            id: id_reversion.to_string(),
+            mensaje: format!(
+                "Movimiento revertido: {} de {:+} unidades de '{}'. Stock actualizado.",
+                tipo_reversion.as_str(),
+                -original.tipo_movimiento.efecto_en_stock(original.cantidad),
+                producto.nombre
+            ),
+        })
+    }
+}
+
+/// Lote indicado en una petición de movimiento; la fecha solo tiene sentido junto a un lote
+fn lote_movimiento(codigo: Option<&str>, fecha_vencimiento: Option<&str>) -> ApiResult<Option<LoteMovimiento>> {
+    let fecha_vencimiento = fecha_vencimiento
+        .map(str::trim)
+        .filter(|fecha| !fecha.is_empty())
+        .map(|fecha| {
+            NaiveDate::parse_from_str(fecha, "%Y-%m-%d").map_err(|_| {
+                ApiError::InvalidInput("Formato de fecha_vencimiento inválido (YYYY-MM-DD)".to_string())
+            })
+        })
+        .transpose()?;
+
+    match codigo.map(str::trim).filter(|codigo| !codigo.is_empty()) {
+        Some(codigo) => Ok(Some(LoteMovimiento {
+            codigo: lotes::normalizar_codigo(codigo)?,
+            fecha_vencimiento,
+        })),
+        None if fecha_vencimiento.is_some() => Err(ApiError::InvalidInput(
+            "fecha_vencimiento requiere indicar el lote".to_string(),
+        )),
+        None => Ok(None),
+    }
 }
@@ -1,5 +1,15 @@
 pub mod common;
 pub mod personas;
 pub mod productos;
+pub mod catalogo;
 pub mod inventarios;
 pub mod ventas;
+pub mod comisiones;
+pub mod cuentas;
+pub mod cajas;
+pub mod precios;
+pub mod cotizaciones;
+pub mod pedidos;
+pub mod auditoria;
+pub mod consistencia;
+pub mod reportes;
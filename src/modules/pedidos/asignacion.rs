@@ -0,0 +1,116 @@
+//! Asignación del stock a los pedidos y planificación de sus despachos.
+//!
+//! El stock libre de un producto (el disponible menos lo reservado) se asigna a las líneas que
+//! esperan stock por orden de llegada: primero los pedidos más antiguos y, dentro de cada uno,
+//! sus líneas en orden. Lo asignado queda reservado en el inventario hasta que se despacha.
+
+use std::collections::HashSet;
+use bigdecimal::BigDecimal;
+use uuid::Uuid;
+use crate::modules::common::errors::{ApiError, ApiResult};
+use crate::modules::common::types::{EstadoPedido, TipoMovimiento};
+use crate::modules::inventarios::costos;
+use crate::modules::inventarios::model::DetalleInventario;
+use crate::modules::pedidos::model::{DetallePedido, SalidaPedido};
+
+/// Estados en que un pedido todavía recibe stock
+pub const ESTADOS_CON_PENDIENTES: [EstadoPedido; 2] = [EstadoPedido::Pendiente, EstadoPedido::EnPreparacion];
+
+/// Reparte `libre` entre las líneas `pendientes`, ya en orden de llegada, hasta agotarlo.
+/// Devuelve (id de la línea, unidades asignadas) de las que reciben algo.
+pub fn repartir(libre: i32, pendientes: &[DetallePedido]) -> Vec<(Uuid, i32)> {
+    let mut restante = libre.max(0);
+    let mut asignacion = Vec::new();
+    for detalle in pendientes {
+        if restante == 0 {
+            break;
+        }
+        let asignada = restante.min(detalle.cantidad_pendiente());
+        if asignada > 0 {
+            asignacion.push((detalle.id, asignada));
+            restante -= asignada;
+        }
+    }
+    asignacion
+}
+
+/// Unidades a despachar de cada línea: las de `solicitado` (línea, cantidad) o, si viene
+/// vacío, todo lo asignado. No se despacha más de lo asignado a una línea.
+pub fn planificar_despacho<'a>(
+    detalles: &'a [DetallePedido],
+    solicitado: &[(i32, i32)],
+) -> ApiResult<Vec<(&'a DetallePedido, i32)>> {
+    let mut despacho = Vec::new();
+    if solicitado.is_empty() {
+        despacho.extend(
+            detalles
+                .iter()
+                .filter(|detalle| detalle.cantidad_asignada > 0)
+                .map(|detalle| (detalle, detalle.cantidad_asignada)),
+        );
+    } else {
+        let mut lineas = HashSet::new();
+        for (linea, cantidad) in solicitado {
+            let detalle = detalles
+                .iter()
+                .find(|detalle| detalle.linea == *linea)
+                .ok_or_else(|| ApiError::InvalidInput(format!("El pedido no tiene la línea {}", linea)))?;
+            if !lineas.insert(*linea) {
+                return Err(ApiError::InvalidInput(format!("La línea {} está repetida en el despacho", linea)));
+            }
+            if *cantidad <= 0 {
+                return Err(ApiError::InvalidInput("La cantidad debe ser mayor a 0".to_string()));
+            }
+            if *cantidad > detalle.cantidad_asignada {
+                return Err(ApiError::BusinessRuleViolation(format!(
+                    "La línea {} tiene {} unidades asignadas y se quieren despachar {}",
+                    linea, detalle.cantidad_asignada, cantidad
+                )));
+            }
+            despacho.push((detalle, *cantidad));
+        }
+        despacho.sort_by_key(|(detalle, _)| detalle.linea);
+    }
+
+    if despacho.is_empty() {
+        return Err(ApiError::BusinessRuleViolation(
+            "El pedido no tiene unidades asignadas para despachar".to_string(),
+        ));
+    }
+    Ok(despacho)
+}
+
+/// Un pedido está despachado cuando salió todo lo pedido en todas sus líneas
+pub fn despachado_por_completo(detalles: &[DetallePedido]) -> bool {
+    detalles.iter().all(|detalle| detalle.cantidad_despachada == detalle.cantidad)
+}
+
+/// Costo y lotes de lo despachado en cada línea, en su orden, a partir de las SALIDAS
+/// registradas al despacharlas
+pub fn salidas_por_linea(detalles: &[DetallePedido], movimientos: &[DetalleInventario]) -> Vec<SalidaPedido> {
+    detalles
+        .iter()
+        .map(|detalle| {
+            let salidas: Vec<&DetalleInventario> = movimientos
+                .iter()
+                .filter(|movimiento| {
+                    movimiento.id_detalle_pedido == Some(detalle.id) && movimiento.tipo_movimiento == TipoMovimiento::Salida
+                })
+                .collect();
+            let costo: BigDecimal = salidas
+                .iter()
+                .map(|salida| BigDecimal::from(salida.cantidad.abs()) * salida.costo_unitario.clone().unwrap_or_default())
+                .sum();
+
+            SalidaPedido {
+                id_producto: detalle.id_producto,
+                cantidad: detalle.cantidad_despachada,
+                costo: costos::redondear_importe(&costo),
+                lotes: salidas
+                    .iter()
+                    .filter_map(|salida| salida.id_lote.map(|id_lote| (id_lote, salida.cantidad.abs())))
+                    .collect(),
+            }
+        })
+        .collect()
+}
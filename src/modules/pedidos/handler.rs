@@ -0,0 +1,182 @@
+use actix_web::{web, HttpResponse, ResponseError, Result};
+use crate::modules::common::errors::ErrorResponse;
+use crate::modules::pedidos::model::{DespachoRequest, EntregarPedidoRequest, PedidoRequest, PedidoResponse, PedidosQuery};
+use crate::modules::ventas::model::VentaCreadaResponse;
+use crate::state::app_state::AppState;
+
+/// POST /v1/pedidos - Crear pedido
+#[utoipa::path(
+    post,
+    path = "/v1/pedidos",
+    tag = "Pedidos",
+    request_body = PedidoRequest,
+    responses(
+        (status = 201, description = "Pedido PENDIENTE con el stock libre asignado y el resto esperando stock", body = PedidoResponse),
+        (status = 400, description = "Datos inválidos, cliente inactivo o producto serializado", body = ErrorResponse),
+        (status = 404, description = "Cliente, vendedor o producto no encontrado", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn crear_pedido(
+    state: web::Data<AppState>,
+    body: web::Json<PedidoRequest>,
+) -> Result<HttpResponse> {
+    let service = &state.pedido_service;
+
+    match service.crear(body.into_inner()) {
+        Ok(response) => Ok(HttpResponse::Created().json(response)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// GET /v1/pedidos - Listar pedidos
+#[utoipa::path(
+    get,
+    path = "/v1/pedidos",
+    tag = "Pedidos",
+    params(PedidosQuery),
+    responses(
+        (status = 200, description = "Pedidos del más reciente al más antiguo", body = Vec<PedidoResponse>),
+        (status = 400, description = "Filtro inválido", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn listar_pedidos(
+    state: web::Data<AppState>,
+    query: web::Query<PedidosQuery>,
+) -> Result<HttpResponse> {
+    let service = &state.pedido_service;
+
+    match service.listar(query.into_inner()) {
+        Ok(pedidos) => Ok(HttpResponse::Ok().json(pedidos)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// GET /v1/pedidos/:id - Obtener pedido
+#[utoipa::path(
+    get,
+    path = "/v1/pedidos/{id}",
+    tag = "Pedidos",
+    params(
+        ("id" = String, Path, description = "ID del pedido (UUID)")
+    ),
+    responses(
+        (status = 200, description = "Pedido con lo asignado, despachado y pendiente de cada línea", body = PedidoResponse),
+        (status = 400, description = "ID inválido", body = ErrorResponse),
+        (status = 404, description = "Pedido no encontrado", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn obtener_pedido(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    let service = &state.pedido_service;
+
+    match service.obtener(&id) {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// POST /v1/pedidos/:id/preparar - Empezar a preparar el pedido
+#[utoipa::path(
+    post,
+    path = "/v1/pedidos/{id}/preparar",
+    tag = "Pedidos",
+    params(
+        ("id" = String, Path, description = "ID del pedido (UUID)")
+    ),
+    responses(
+        (status = 200, description = "Pedido EN_PREPARACION", body = PedidoResponse),
+        (status = 400, description = "El pedido no está PENDIENTE", body = ErrorResponse),
+        (status = 404, description = "Pedido no encontrado", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn preparar_pedido(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    let service = &state.pedido_service;
+
+    match service.preparar(&id) {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// POST /v1/pedidos/:id/despachos - Despachar lo asignado del pedido
+#[utoipa::path(
+    post,
+    path = "/v1/pedidos/{id}/despachos",
+    tag = "Pedidos",
+    params(
+        ("id" = String, Path, description = "ID del pedido (UUID)")
+    ),
+    request_body = DespachoRequest,
+    responses(
+        (status = 200, description = "Despacho registrado; el pedido pasa a DESPACHADO cuando salió todo", body = PedidoResponse),
+        (status = 400, description = "Pedido que no está EN_PREPARACION, línea inválida o más de lo asignado", body = ErrorResponse),
+        (status = 404, description = "Pedido o persona no encontrado", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn despachar_pedido(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<DespachoRequest>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    let service = &state.pedido_service;
+
+    match service.despachar(&id, body.into_inner()) {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// POST /v1/pedidos/:id/entregar - Entregar el pedido con su venta final
+#[utoipa::path(
+    post,
+    path = "/v1/pedidos/{id}/entregar",
+    tag = "Pedidos",
+    params(
+        ("id" = String, Path, description = "ID del pedido (UUID)")
+    ),
+    request_body = EntregarPedidoRequest,
+    responses(
+        (status = 201, description = "Venta creada con lo despachado; el pedido queda ENTREGADO", body = VentaCreadaResponse),
+        (status = 400, description = "Pedido no DESPACHADO o ya entregado, o pagos inválidos", body = ErrorResponse),
+        (status = 404, description = "Pedido o caja no encontrado", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn entregar_pedido(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<EntregarPedidoRequest>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    let service = &state.pedido_service;
+
+    match service.entregar(&id, body.into_inner()) {
+        Ok(response) => Ok(HttpResponse::Created().json(response)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/pedidos")
+            .route("", web::post().to(crear_pedido))
+            .route("", web::get().to(listar_pedidos))
+            .route("/{id}", web::get().to(obtener_pedido))
+            .route("/{id}/preparar", web::post().to(preparar_pedido))
+            .route("/{id}/despachos", web::post().to(despachar_pedido))
+            .route("/{id}/entregar", web::post().to(entregar_pedido))
+    );
+}
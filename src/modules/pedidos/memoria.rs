@@ -0,0 +1,237 @@
+use std::cmp::Reverse;
+use chrono::{NaiveDateTime, Utc};
+use uuid::Uuid;
+use crate::modules::common::errors::ApiResult;
+use crate::modules::common::memoria::{MemoriaRepository, Tablas};
+use crate::modules::common::types::EstadoPedido;
+use crate::modules::inventarios::lotes;
+use crate::modules::inventarios::model::DetalleInventario;
+use crate::modules::pedidos::asignacion;
+use crate::modules::pedidos::model::{DetallePedido, NuevoDetallePedido, NuevoPedido, Pedido, SalidaPedido};
+use crate::modules::pedidos::repository::{self as pedido_repo, PedidoRepository};
+
+impl Tablas {
+    fn pedido_mut(&mut self, id: Uuid) -> ApiResult<&mut Pedido> {
+        self.pedidos
+            .iter_mut()
+            .find(|pedido| pedido.id == id && pedido.activo)
+            .ok_or_else(|| pedido_repo::pedido_no_encontrado(id))
+    }
+
+    fn detalles_pedido(&self, id_pedido: Uuid) -> Vec<DetallePedido> {
+        let mut detalles: Vec<DetallePedido> = self
+            .detalle_pedidos
+            .iter()
+            .filter(|detalle| detalle.id_pedido == id_pedido && detalle.activo)
+            .cloned()
+            .collect();
+        detalles.sort_by_key(|detalle| detalle.linea);
+        detalles
+    }
+
+    fn detalle_pedido_mut(&mut self, id: Uuid) -> &mut DetallePedido {
+        self.detalle_pedidos
+            .iter_mut()
+            .find(|detalle| detalle.id == id)
+            .expect("las asignaciones solo nombran líneas existentes")
+    }
+
+    /// Como asignar_pendientes de PgPedidoRepository
+    pub(crate) fn asignar_pendientes(&mut self, id_producto: Uuid) -> ApiResult<()> {
+        let inventario = self.inventario_mut(id_producto)?;
+        let libre = inventario.cantidad_disponible - inventario.cantidad_reservada;
+        if libre <= 0 {
+            return Ok(());
+        }
+
+        let mut pendientes: Vec<(NaiveDateTime, Uuid, DetallePedido)> = self
+            .detalle_pedidos
+            .iter()
+            .filter(|detalle| detalle.id_producto == id_producto && detalle.activo && detalle.cantidad_pendiente() > 0)
+            .filter_map(|detalle| {
+                self.pedidos
+                    .iter()
+                    .find(|pedido| {
+                        pedido.id == detalle.id_pedido
+                            && pedido.activo
+                            && asignacion::ESTADOS_CON_PENDIENTES.contains(&pedido.estado)
+                    })
+                    .map(|pedido| (pedido.fecha_creacion, pedido.id, detalle.clone()))
+            })
+            .collect();
+        pendientes.sort_by_key(|(fecha, id_pedido, detalle)| (*fecha, *id_pedido, detalle.linea));
+        let pendientes: Vec<DetallePedido> = pendientes.into_iter().map(|(_, _, detalle)| detalle).collect();
+
+        let ahora = Utc::now().naive_utc();
+        let mut reservada = 0;
+        for (id_detalle, cantidad) in asignacion::repartir(libre, &pendientes) {
+            let detalle = self.detalle_pedido_mut(id_detalle);
+            detalle.cantidad_asignada += cantidad;
+            detalle.fecha_actualizacion = ahora;
+            reservada += cantidad;
+        }
+        if reservada > 0 {
+            let inventario = self.inventario_mut(id_producto)?;
+            inventario.cantidad_reservada += reservada;
+            inventario.fecha_actualizacion = ahora;
+        }
+
+        Ok(())
+    }
+
+    /// Como pedido_repo::entregar_en_venta
+    pub(crate) fn entregar_pedido(&mut self, id: Uuid, id_venta: Uuid, ahora: NaiveDateTime) -> ApiResult<Vec<SalidaPedido>> {
+        let pedido = self.pedido_mut(id)?;
+        pedido_repo::validar_estado(pedido, EstadoPedido::Despachado)?;
+        pedido.estado = EstadoPedido::Entregado;
+        pedido.id_venta = Some(id_venta);
+        pedido.fecha_actualizacion = ahora;
+
+        let detalles = self.detalles_pedido(id);
+        let movimientos: Vec<DetalleInventario> = self
+            .detalle_inventarios
+            .iter()
+            .filter(|movimiento| {
+                movimiento.activo
+                    && movimiento
+                        .id_detalle_pedido
+                        .is_some_and(|id_detalle| detalles.iter().any(|detalle| detalle.id == id_detalle))
+            })
+            .cloned()
+            .collect();
+
+        Ok(asignacion::salidas_por_linea(&detalles, &movimientos))
+    }
+}
+
+impl PedidoRepository for MemoriaRepository {
+    fn crear(&self, nuevo_pedido: NuevoPedido, detalles: Vec<NuevoDetallePedido>) -> ApiResult<Uuid> {
+        self.transaccion(|tablas| {
+            let ahora = Utc::now().naive_utc();
+            let id = nuevo_pedido.id;
+            tablas.pedidos.push(Pedido {
+                id,
+                id_persona: nuevo_pedido.id_persona,
+                id_vendedor: nuevo_pedido.id_vendedor,
+                sucursal: nuevo_pedido.sucursal,
+                estado: EstadoPedido::Pendiente,
+                monto: nuevo_pedido.monto,
+                observaciones: nuevo_pedido.observaciones,
+                id_venta: None,
+                fecha_creacion: ahora,
+                fecha_actualizacion: ahora,
+                activo: true,
+            });
+
+            let productos = pedido_repo::productos_en_orden(detalles.iter().map(|detalle| detalle.id_producto));
+            tablas.detalle_pedidos.extend(detalles.into_iter().map(|detalle| DetallePedido {
+                id: Uuid::new_v4(),
+                id_pedido: detalle.id_pedido,
+                linea: detalle.linea,
+                id_producto: detalle.id_producto,
+                id_unidad: detalle.id_unidad,
+                cantidad: detalle.cantidad,
+                cantidad_unidad: detalle.cantidad_unidad,
+                cantidad_asignada: 0,
+                cantidad_despachada: 0,
+                precio_unitario: detalle.precio_unitario,
+                descuento: detalle.descuento,
+                monto: detalle.monto,
+                id_lista_precios: detalle.id_lista_precios,
+                id_promocion: detalle.id_promocion,
+                fecha_creacion: ahora,
+                fecha_actualizacion: ahora,
+                activo: true,
+            }));
+            for id_producto in productos {
+                tablas.asignar_pendientes(id_producto)?;
+            }
+
+            Ok(id)
+        })
+    }
+
+    fn buscar(&self, id: Uuid) -> ApiResult<(Pedido, Vec<DetallePedido>)> {
+        let mut tablas = self.tablas();
+        let pedido = tablas.pedido_mut(id)?.clone();
+        Ok((pedido, tablas.detalles_pedido(id)))
+    }
+
+    fn listar(&self, id_cliente: Option<Uuid>, estado: Option<EstadoPedido>) -> ApiResult<Vec<Pedido>> {
+        let mut pedidos: Vec<Pedido> = self
+            .tablas()
+            .pedidos
+            .iter()
+            .filter(|pedido| pedido.activo)
+            .filter(|pedido| id_cliente.is_none_or(|id| pedido.id_persona == id))
+            .filter(|pedido| estado.is_none_or(|estado| pedido.estado == estado))
+            .cloned()
+            .collect();
+        pedidos.sort_by_key(|pedido| Reverse(pedido.fecha_creacion));
+        Ok(pedidos)
+    }
+
+    fn cambiar_estado(&self, id: Uuid, desde: EstadoPedido, hacia: EstadoPedido) -> ApiResult<Pedido> {
+        self.transaccion(|tablas| {
+            let pedido = tablas.pedido_mut(id)?;
+            pedido_repo::validar_estado(pedido, desde)?;
+            pedido.estado = hacia;
+            pedido.fecha_actualizacion = Utc::now().naive_utc();
+            Ok(pedido.clone())
+        })
+    }
+
+    fn despachar(&self, id: Uuid, solicitado: Vec<(i32, i32)>, id_persona: Uuid) -> ApiResult<Vec<Uuid>> {
+        self.transaccion(|tablas| {
+            let ahora = Utc::now().naive_utc();
+            pedido_repo::validar_estado(tablas.pedido_mut(id)?, EstadoPedido::EnPreparacion)?;
+            let detalles = tablas.detalles_pedido(id);
+            let despacho = asignacion::planificar_despacho(&detalles, &solicitado)?;
+
+            let mut salidas = Vec::new();
+            for (detalle, cantidad) in despacho {
+                // inventario_repo::consumir_fefo y consumir_capas, con el stock previo al despacho
+                let stock_actual = tablas.inventario_mut(detalle.id_producto)?.cantidad_disponible;
+                let lotes = lotes::asignar_fefo(
+                    detalle.id_producto,
+                    &tablas.lotes_de(detalle.id_producto),
+                    stock_actual,
+                    cantidad,
+                    ahora.date(),
+                )?;
+                for (id_lote, tomada) in &lotes {
+                    tablas.sumar_a_lote(*id_lote, -tomada)?;
+                }
+                let consumo = tablas.consumir_capas(detalle.id_producto, stock_actual, cantidad, None);
+
+                let inventario = tablas.inventario_mut(detalle.id_producto)?;
+                inventario.cantidad_disponible -= cantidad;
+                inventario.cantidad_reservada -= cantidad;
+                inventario.fecha_actualizacion = ahora;
+                let linea = tablas.detalle_pedido_mut(detalle.id);
+                linea.cantidad_asignada -= cantidad;
+                linea.cantidad_despachada += cantidad;
+                linea.fecha_actualizacion = ahora;
+
+                for salida in pedido_repo::salidas_de_despacho(
+                    detalle,
+                    cantidad,
+                    &lotes,
+                    consumo.costo_unitario(cantidad),
+                    id_persona,
+                    ahora,
+                ) {
+                    salidas.push(tablas.insertar_movimiento(salida));
+                }
+            }
+
+            if asignacion::despachado_por_completo(&tablas.detalles_pedido(id)) {
+                let pedido = tablas.pedido_mut(id)?;
+                pedido.estado = EstadoPedido::Despachado;
+                pedido.fecha_actualizacion = ahora;
+            }
+
+            Ok(salidas)
+        })
+    }
+}
@@ -0,0 +1,6 @@
+pub mod model;
+pub mod asignacion;
+pub mod repository;
+pub mod service;
+pub mod handler;
+mod memoria;
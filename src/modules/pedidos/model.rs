@@ -0,0 +1,235 @@
+use bigdecimal::BigDecimal;
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use utoipa::{IntoParams, ToSchema};
+use crate::modules::common::types::EstadoPedido;
+use crate::modules::precios::model::{LineaPrecioRequest, OrigenPrecio};
+use crate::modules::ventas::model::PagoRequest;
+use crate::schema::{detalle_pedidos, pedidos};
+
+// Domain Model for Pedido
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = pedidos)]
+pub struct Pedido {
+    pub id: Uuid,
+    /// Cliente que hizo el pedido
+    pub id_persona: Uuid,
+    pub id_vendedor: Option<Uuid>,
+    /// Si se indica, la venta final debe registrarse en una caja de esa sucursal
+    pub sucursal: Option<String>,
+    pub estado: EstadoPedido,
+    /// Suma de los montos de las líneas
+    pub monto: BigDecimal,
+    pub observaciones: Option<String>,
+    /// Venta con que se entregó
+    pub id_venta: Option<Uuid>,
+    pub fecha_creacion: NaiveDateTime,
+    pub fecha_actualizacion: NaiveDateTime,
+    pub activo: bool,
+}
+
+// Domain Model for DetallePedido
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable, Associations)]
+#[diesel(belongs_to(Pedido, foreign_key = id_pedido))]
+#[diesel(table_name = detalle_pedidos)]
+pub struct DetallePedido {
+    pub id: Uuid,
+    pub id_pedido: Uuid,
+    /// Posición de la línea en el pedido, desde 1
+    pub linea: i32,
+    pub id_producto: Uuid,
+    /// Unidad pedida; None para la unidad base
+    pub id_unidad: Option<Uuid>,
+    /// Cantidad en la unidad base del producto
+    pub cantidad: i32,
+    /// Cantidad en la unidad pedida
+    pub cantidad_unidad: i32,
+    /// Unidades base reservadas en el inventario y todavía sin despachar
+    pub cantidad_asignada: i32,
+    /// Unidades base que ya salieron del stock
+    pub cantidad_despachada: i32,
+    /// Precio de una unidad de la pedida, antes del descuento de la promoción
+    pub precio_unitario: BigDecimal,
+    pub descuento: BigDecimal,
+    /// Monto de la línea, ya descontada la promoción
+    pub monto: BigDecimal,
+    pub id_lista_precios: Option<Uuid>,
+    pub id_promocion: Option<Uuid>,
+    pub fecha_creacion: NaiveDateTime,
+    pub fecha_actualizacion: NaiveDateTime,
+    pub activo: bool,
+}
+
+impl DetallePedido {
+    /// Unidades base que esperan stock (backorder)
+    pub fn cantidad_pendiente(&self) -> i32 {
+        self.cantidad - self.cantidad_asignada - self.cantidad_despachada
+    }
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = pedidos)]
+pub struct NuevoPedido {
+    pub id: Uuid,
+    pub id_persona: Uuid,
+    pub id_vendedor: Option<Uuid>,
+    pub sucursal: Option<String>,
+    pub monto: BigDecimal,
+    pub observaciones: Option<String>,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = detalle_pedidos)]
+pub struct NuevoDetallePedido {
+    pub id_pedido: Uuid,
+    pub linea: i32,
+    pub id_producto: Uuid,
+    pub id_unidad: Option<Uuid>,
+    pub cantidad: i32,
+    pub cantidad_unidad: i32,
+    pub precio_unitario: BigDecimal,
+    pub descuento: BigDecimal,
+    pub monto: BigDecimal,
+    pub id_lista_precios: Option<Uuid>,
+    pub id_promocion: Option<Uuid>,
+}
+
+/// Lo que salió del stock en los despachos de una línea de pedido, para su venta final
+#[derive(Debug, Clone)]
+pub struct SalidaPedido {
+    pub id_producto: Uuid,
+    /// Unidades base despachadas
+    pub cantidad: i32,
+    /// Costo total de lo despachado
+    pub costo: BigDecimal,
+    /// (id del lote, cantidad) por cada lote del que salieron unidades
+    pub lotes: Vec<(Uuid, i32)>,
+}
+
+// DTO for creating a customer order
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PedidoRequest {
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000")]
+    pub id_cliente: String,
+    /// Persona con perfil VENDEDOR que toma el pedido
+    #[schema(example = "440e8400-e29b-41d4-a716-446655440000")]
+    pub id_vendedor: Option<String>,
+    /// Si se indica, la venta final debe registrarse en una caja de esa sucursal
+    #[schema(example = "Bogotá Centro")]
+    pub sucursal: Option<String>,
+    #[schema(example = "Pedido telefónico, entregar en la portería")]
+    pub observaciones: Option<String>,
+    pub detalles: Vec<LineaPrecioRequest>,
+}
+
+// DTO for shipping part of an order
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct DespachoRequest {
+    /// Persona que registra el despacho; queda como responsable de las SALIDAS
+    #[schema(example = "660e8400-e29b-41d4-a716-446655440000")]
+    pub id_persona: String,
+    /// Cantidades a despachar por línea; vacío para despachar todo lo asignado
+    #[serde(default)]
+    pub detalles: Vec<LineaDespachoRequest>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct LineaDespachoRequest {
+    /// Posición de la línea en el pedido, desde 1
+    #[schema(example = 1)]
+    pub linea: i32,
+    /// Unidades base a despachar, como mucho las asignadas a la línea
+    #[schema(example = 4)]
+    pub cantidad: i32,
+}
+
+// DTO for delivering a shipped order with its final sale
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct EntregarPedidoRequest {
+    /// Sesión de caja abierta en la que se registra la venta
+    #[schema(example = "bb0e8400-e29b-41d4-a716-446655440000")]
+    pub id_sesion_caja: String,
+    /// Igual que en `POST /v1/ventas`: sin pagos, la venta queda PENDIENTE_PAGO
+    #[serde(default)]
+    pub pagos: Vec<PagoRequest>,
+    #[serde(default)]
+    pub credito: bool,
+}
+
+// Query parameters for listing orders
+#[derive(Debug, Deserialize, IntoParams, ToSchema)]
+pub struct PedidosQuery {
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000")]
+    pub id_cliente: Option<String>,
+    /// PENDIENTE, EN_PREPARACION, DESPACHADO o ENTREGADO
+    #[schema(example = "EN_PREPARACION")]
+    pub estado: Option<String>,
+}
+
+// DTO for order response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PedidoResponse {
+    #[schema(example = "dd0e8400-e29b-41d4-a716-446655440000")]
+    pub id: String,
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000")]
+    pub id_cliente: String,
+    #[schema(example = "440e8400-e29b-41d4-a716-446655440000")]
+    pub id_vendedor: Option<String>,
+    #[schema(example = "Bogotá Centro")]
+    pub sucursal: Option<String>,
+    pub estado: EstadoPedido,
+    /// Descuento de las promociones, ya restado del total
+    #[schema(example = 0.0)]
+    pub descuento: f64,
+    #[schema(example = 2400000.0)]
+    pub total: f64,
+    #[schema(example = "Pedido telefónico, entregar en la portería")]
+    pub observaciones: Option<String>,
+    /// Venta con que se entregó
+    #[schema(example = "770e8400-e29b-41d4-a716-446655440000")]
+    pub id_venta: Option<String>,
+    #[schema(example = "2026-11-15 10:30:00")]
+    pub fecha_creacion: String,
+    pub detalles: Vec<DetallePedidoResponse>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DetallePedidoResponse {
+    #[schema(example = 1)]
+    pub linea: i32,
+    #[schema(example = "660e8400-e29b-41d4-a716-446655440000")]
+    pub id_producto: String,
+    #[schema(example = "Laptop HP Pavilion")]
+    pub nombre_producto: String,
+    #[schema(example = "unidad")]
+    pub unidad: String,
+    /// Cantidad en la unidad pedida
+    #[schema(example = 2)]
+    pub cantidad: i32,
+    /// Cantidad en la unidad base; las tres siguientes la reparten
+    #[schema(example = 2)]
+    pub cantidad_base: i32,
+    /// Reservada en el inventario, lista para despachar
+    #[schema(example = 1)]
+    pub cantidad_asignada: i32,
+    #[schema(example = 1)]
+    pub cantidad_despachada: i32,
+    /// Esperando stock (backorder)
+    #[schema(example = 0)]
+    pub cantidad_pendiente: i32,
+    /// Precio de una unidad de la pedida, antes del descuento de la promoción
+    #[schema(example = 1200000.0)]
+    pub precio_unitario: f64,
+    #[schema(example = 0.0)]
+    pub descuento: f64,
+    #[schema(example = 2400000.0)]
+    pub subtotal: f64,
+    /// Regla que fijó el precio de la línea
+    pub origen_precio: OrigenPrecio,
+    #[schema(example = "aa0e8400-e29b-41d4-a716-446655440000")]
+    pub id_lista_precios: Option<String>,
+    #[schema(example = "ab0e8400-e29b-41d4-a716-446655440000")]
+    pub id_promocion: Option<String>,
+}
@@ -0,0 +1,362 @@
+use bigdecimal::BigDecimal;
+use chrono::{NaiveDateTime, Utc};
+use diesel::prelude::*;
+use diesel::r2d2::{ConnectionManager, Pool, PooledConnection};
+use uuid::Uuid;
+use tracing::instrument;
+use crate::modules::auditoria;
+use crate::modules::common::errors::{ApiError, ApiResult};
+use crate::modules::common::types::{EstadoPedido, TipoMovimiento};
+use crate::modules::inventarios::model::{DetalleInventario, NuevoMovimiento};
+use crate::modules::inventarios::repository as inventario_repo;
+use crate::modules::pedidos::asignacion;
+use crate::modules::pedidos::model::{DetallePedido, NuevoDetallePedido, NuevoPedido, Pedido, SalidaPedido};
+use crate::modules::ventas::model::NuevoDetalleVenta;
+use crate::schema::{detalle_inventarios, detalle_pedidos, inventarios, pedidos};
+
+pub type DbPool = Pool<ConnectionManager<PgConnection>>;
+pub type DbConnection = PooledConnection<ConnectionManager<PgConnection>>;
+
+/// Acceso a datos de los pedidos (ver `PersonaRepository` para las implementaciones)
+pub trait PedidoRepository: Send + Sync {
+    /// Guarda el pedido y le asigna el stock libre de sus productos, detrás de los pedidos que
+    /// ya esperaban (ver `asignar_pendientes`)
+    fn crear(&self, nuevo_pedido: NuevoPedido, detalles: Vec<NuevoDetallePedido>) -> ApiResult<Uuid>;
+
+    /// Pedido activo con sus líneas, en orden
+    fn buscar(&self, id: Uuid) -> ApiResult<(Pedido, Vec<DetallePedido>)>;
+
+    /// Pedidos activos, del más reciente al más antiguo
+    fn listar(&self, id_cliente: Option<Uuid>, estado: Option<EstadoPedido>) -> ApiResult<Vec<Pedido>>;
+
+    /// Pasa el pedido de `desde` a `hacia`, si sigue en `desde`
+    fn cambiar_estado(&self, id: Uuid, desde: EstadoPedido, hacia: EstadoPedido) -> ApiResult<Pedido>;
+
+    /// Despacha unidades asignadas de un pedido EN_PREPARACION (ver
+    /// `asignacion::planificar_despacho`): salen del stock y de lo reservado con una SALIDA por
+    /// lote consumido en orden FEFO, costeada con las capas que consume. El pedido queda
+    /// DESPACHADO cuando sale todo. Devuelve los ids de las SALIDAS.
+    fn despachar(&self, id: Uuid, solicitado: Vec<(i32, i32)>, id_persona: Uuid) -> ApiResult<Vec<Uuid>>;
+}
+
+pub struct PgPedidoRepository {
+    pool: DbPool,
+}
+
+impl PgPedidoRepository {
+    pub fn new(pool: DbPool) -> Self {
+        PgPedidoRepository { pool }
+    }
+
+    fn get_connection(&self) -> ApiResult<DbConnection> {
+        self.pool
+            .get()
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+    }
+}
+
+impl PedidoRepository for PgPedidoRepository {
+    #[instrument(skip(self, nuevo_pedido, detalles))]
+    fn crear(&self, nuevo_pedido: NuevoPedido, detalles: Vec<NuevoDetallePedido>) -> ApiResult<Uuid> {
+        let mut conn = self.get_connection()?;
+
+        conn.transaction::<Uuid, ApiError, _>(|conn| {
+            auditoria::contexto::aplicar(conn)?;
+
+            diesel::insert_into(pedidos::table)
+                .values(&nuevo_pedido)
+                .execute(conn)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+            diesel::insert_into(detalle_pedidos::table)
+                .values(&detalles)
+                .execute(conn)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+            for id_producto in productos_en_orden(detalles.iter().map(|detalle| detalle.id_producto)) {
+                asignar_pendientes(conn, id_producto)?;
+            }
+
+            Ok(nuevo_pedido.id)
+        })
+    }
+
+    #[instrument(skip(self))]
+    fn buscar(&self, id: Uuid) -> ApiResult<(Pedido, Vec<DetallePedido>)> {
+        let mut conn = self.get_connection()?;
+
+        let pedido = pedidos::table
+            .find(id)
+            .filter(pedidos::activo.eq(true))
+            .select(Pedido::as_select())
+            .first(&mut conn)
+            .map_err(|e| match e {
+                diesel::result::Error::NotFound => pedido_no_encontrado(id),
+                _ => ApiError::DatabaseError(e.to_string()),
+            })?;
+        let detalles = detalles_de(&mut conn, id)?;
+
+        Ok((pedido, detalles))
+    }
+
+    #[instrument(skip(self))]
+    fn listar(&self, id_cliente: Option<Uuid>, estado: Option<EstadoPedido>) -> ApiResult<Vec<Pedido>> {
+        let mut conn = self.get_connection()?;
+
+        let mut query = pedidos::table
+            .filter(pedidos::activo.eq(true))
+            .into_boxed();
+        if let Some(id_cliente) = id_cliente {
+            query = query.filter(pedidos::id_persona.eq(id_cliente));
+        }
+        if let Some(estado) = estado {
+            query = query.filter(pedidos::estado.eq(estado));
+        }
+
+        query
+            .order(pedidos::fecha_creacion.desc())
+            .select(Pedido::as_select())
+            .load(&mut conn)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+    }
+
+    #[instrument(skip(self))]
+    fn cambiar_estado(&self, id: Uuid, desde: EstadoPedido, hacia: EstadoPedido) -> ApiResult<Pedido> {
+        let mut conn = self.get_connection()?;
+
+        conn.transaction::<Pedido, ApiError, _>(|conn| {
+            auditoria::contexto::aplicar(conn)?;
+
+            let pedido = pedido_bloqueado(conn, id)?;
+            validar_estado(&pedido, desde)?;
+
+            diesel::update(pedidos::table.find(id))
+                .set(pedidos::estado.eq(hacia))
+                .returning(Pedido::as_returning())
+                .get_result(conn)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))
+        })
+    }
+
+    #[instrument(skip(self, solicitado))]
+    fn despachar(&self, id: Uuid, solicitado: Vec<(i32, i32)>, id_persona: Uuid) -> ApiResult<Vec<Uuid>> {
+        let mut conn = self.get_connection()?;
+
+        conn.transaction::<Vec<Uuid>, ApiError, _>(|conn| {
+            auditoria::contexto::aplicar(conn)?;
+
+            let pedido = pedido_bloqueado(conn, id)?;
+            validar_estado(&pedido, EstadoPedido::EnPreparacion)?;
+            let detalles = detalles_de(conn, id)?;
+            let despacho = asignacion::planificar_despacho(&detalles, &solicitado)?;
+
+            // Bloquear los inventarios siempre en el mismo orden, para que dos despachos
+            // simultáneos no se esperen el uno al otro
+            for id_producto in productos_en_orden(despacho.iter().map(|(detalle, _)| detalle.id_producto)) {
+                inventario_repo::inventario_bloqueado(conn, id_producto)?;
+            }
+
+            let fecha = Utc::now().naive_utc();
+            let mut salidas = Vec::new();
+            for (detalle, cantidad) in despacho {
+                let lotes = inventario_repo::consumir_fefo(conn, detalle.id_producto, cantidad)?;
+                let stock_actual = inventario_repo::stock_bloqueado(conn, detalle.id_producto)?;
+                let consumo = inventario_repo::consumir_capas(conn, detalle.id_producto, stock_actual, cantidad, None)?;
+
+                diesel::update(inventarios::table)
+                    .filter(inventarios::id_producto.eq(detalle.id_producto))
+                    .filter(inventarios::activo.eq(true))
+                    .set((
+                        inventarios::cantidad_disponible.eq(inventarios::cantidad_disponible - cantidad),
+                        inventarios::cantidad_reservada.eq(inventarios::cantidad_reservada - cantidad),
+                    ))
+                    .execute(conn)
+                    .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+                diesel::update(detalle_pedidos::table.find(detalle.id))
+                    .set((
+                        detalle_pedidos::cantidad_asignada.eq(detalle_pedidos::cantidad_asignada - cantidad),
+                        detalle_pedidos::cantidad_despachada.eq(detalle_pedidos::cantidad_despachada + cantidad),
+                    ))
+                    .execute(conn)
+                    .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+                for salida in salidas_de_despacho(
+                    detalle,
+                    cantidad,
+                    &lotes,
+                    consumo.costo_unitario(cantidad),
+                    id_persona,
+                    fecha,
+                ) {
+                    salidas.push(inventario_repo::registrar_movimiento(conn, salida)?);
+                }
+            }
+
+            if asignacion::despachado_por_completo(&detalles_de(conn, id)?) {
+                diesel::update(pedidos::table.find(id))
+                    .set(pedidos::estado.eq(EstadoPedido::Despachado))
+                    .execute(conn)
+                    .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+            }
+
+            Ok(salidas)
+        })
+    }
+}
+
+/// Asigna el stock libre del producto a las líneas de pedidos PENDIENTES o EN_PREPARACION que
+/// esperan stock, de la más antigua a la más nueva (ver `asignacion::repartir`), y lo reserva
+/// en el inventario. Se ejecuta dentro de la transacción que liberó o creó stock, con el
+/// inventario bloqueado.
+pub(crate) fn asignar_pendientes(conn: &mut PgConnection, id_producto: Uuid) -> ApiResult<()> {
+    let inventario = inventario_repo::inventario_bloqueado(conn, id_producto)?;
+    let libre = inventario.cantidad_disponible - inventario.cantidad_reservada;
+    if libre <= 0 {
+        return Ok(());
+    }
+
+    let pendientes = detalle_pedidos::table
+        .inner_join(pedidos::table)
+        .filter(detalle_pedidos::id_producto.eq(id_producto))
+        .filter(detalle_pedidos::activo.eq(true))
+        .filter(pedidos::activo.eq(true))
+        .filter(pedidos::estado.eq_any(asignacion::ESTADOS_CON_PENDIENTES))
+        .filter((detalle_pedidos::cantidad_asignada + detalle_pedidos::cantidad_despachada).lt(detalle_pedidos::cantidad))
+        .order((pedidos::fecha_creacion.asc(), pedidos::id.asc(), detalle_pedidos::linea.asc()))
+        .select(DetallePedido::as_select())
+        .load(conn)
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+    let asignaciones = asignacion::repartir(libre, &pendientes);
+    for (id_detalle, cantidad) in &asignaciones {
+        diesel::update(detalle_pedidos::table.find(id_detalle))
+            .set(detalle_pedidos::cantidad_asignada.eq(detalle_pedidos::cantidad_asignada + cantidad))
+            .execute(conn)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+    }
+    let reservada: i32 = asignaciones.iter().map(|(_, cantidad)| cantidad).sum();
+    if reservada > 0 {
+        diesel::update(inventarios::table.find(inventario.id))
+            .set(inventarios::cantidad_reservada.eq(inventarios::cantidad_reservada + reservada))
+            .execute(conn)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+    }
+
+    Ok(())
+}
+
+/// Marca ENTREGADO en la venta `id_venta` un pedido DESPACHADO, dentro de la transacción que
+/// guarda la venta, y devuelve lo que salió en los despachos de cada línea, en su orden. El
+/// pedido queda bloqueado, así que no se entrega con dos ventas.
+pub(crate) fn entregar_en_venta(conn: &mut PgConnection, id: Uuid, id_venta: Uuid) -> ApiResult<Vec<SalidaPedido>> {
+    let pedido = pedido_bloqueado(conn, id)?;
+    validar_estado(&pedido, EstadoPedido::Despachado)?;
+
+    diesel::update(pedidos::table.find(id))
+        .set((
+            pedidos::estado.eq(EstadoPedido::Entregado),
+            pedidos::id_venta.eq(id_venta),
+        ))
+        .execute(conn)
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+    let detalles = detalles_de(conn, id)?;
+    let ids_detalle: Vec<Uuid> = detalles.iter().map(|detalle| detalle.id).collect();
+    let movimientos = detalle_inventarios::table
+        .filter(detalle_inventarios::id_detalle_pedido.eq_any(ids_detalle))
+        .filter(detalle_inventarios::activo.eq(true))
+        .select(DetalleInventario::as_select())
+        .load(conn)
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+    Ok(asignacion::salidas_por_linea(&detalles, &movimientos))
+}
+
+/// Lo que salió para el siguiente detalle de la venta de un pedido, que debe ser la misma línea
+pub(crate) fn salida_de_detalle(salida: Option<SalidaPedido>, detalle: &NuevoDetalleVenta) -> ApiResult<SalidaPedido> {
+    salida
+        .filter(|salida| salida.id_producto == detalle.id_producto && salida.cantidad == detalle.cantidad)
+        .ok_or_else(|| {
+            ApiError::InternalError(format!(
+                "La venta {} no corresponde a las líneas despachadas de su pedido",
+                detalle.id_venta
+            ))
+        })
+}
+
+/// SALIDAS de un despacho de `cantidad` unidades de la línea: una por cada lote del que salen
+/// unidades y otra por las que salen sin lote, todas al mismo costo unitario
+pub(crate) fn salidas_de_despacho(
+    detalle: &DetallePedido,
+    cantidad: i32,
+    lotes: &[(Uuid, i32)],
+    costo_unitario: BigDecimal,
+    id_persona: Uuid,
+    fecha: NaiveDateTime,
+) -> Vec<NuevoMovimiento> {
+    let sin_lote = cantidad - lotes.iter().map(|(_, tomada)| tomada).sum::<i32>();
+    lotes
+        .iter()
+        .map(|(id_lote, tomada)| (Some(*id_lote), *tomada))
+        .chain((sin_lote > 0).then_some((None, sin_lote)))
+        .map(|(id_lote, tomada)| NuevoMovimiento {
+            id_producto: detalle.id_producto,
+            tipo_movimiento: TipoMovimiento::Salida,
+            fecha,
+            id_persona,
+            cantidad: tomada,
+            observaciones: Some(format!("Despacho del pedido {}", detalle.id_pedido)),
+            id_movimiento_revertido: None,
+            id_lote,
+            costo_unitario: Some(costo_unitario.clone()),
+            id_detalle_pedido: Some(detalle.id),
+        })
+        .collect()
+}
+
+/// Productos distintos, ordenados por id
+pub(crate) fn productos_en_orden(ids: impl Iterator<Item = Uuid>) -> Vec<Uuid> {
+    let mut ids: Vec<Uuid> = ids.collect();
+    ids.sort();
+    ids.dedup();
+    ids
+}
+
+fn pedido_bloqueado(conn: &mut PgConnection, id: Uuid) -> ApiResult<Pedido> {
+    pedidos::table
+        .find(id)
+        .filter(pedidos::activo.eq(true))
+        .select(Pedido::as_select())
+        .for_update()
+        .first(conn)
+        .map_err(|e| match e {
+            diesel::result::Error::NotFound => pedido_no_encontrado(id),
+            _ => ApiError::DatabaseError(e.to_string()),
+        })
+}
+
+fn detalles_de(conn: &mut PgConnection, id_pedido: Uuid) -> ApiResult<Vec<DetallePedido>> {
+    detalle_pedidos::table
+        .filter(detalle_pedidos::id_pedido.eq(id_pedido))
+        .filter(detalle_pedidos::activo.eq(true))
+        .order(detalle_pedidos::linea.asc())
+        .select(DetallePedido::as_select())
+        .load(conn)
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))
+}
+
+/// El pedido debe estar en `esperado`
+pub(crate) fn validar_estado(pedido: &Pedido, esperado: EstadoPedido) -> ApiResult<()> {
+    if pedido.estado != esperado {
+        return Err(ApiError::BusinessRuleViolation(format!(
+            "El pedido {} está {} y debe estar {}",
+            pedido.id,
+            pedido.estado.as_str(),
+            esperado.as_str()
+        )));
+    }
+    Ok(())
+}
+
+pub(crate) fn pedido_no_encontrado(id: Uuid) -> ApiError {
+    ApiError::NotFound(format!("Pedido con ID {} no encontrado", id))
+}
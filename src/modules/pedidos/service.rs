@@ -0,0 +1,291 @@
+use std::sync::Arc;
+use bigdecimal::{BigDecimal, ToPrimitive};
+use chrono::Utc;
+use uuid::Uuid;
+use tracing::instrument;
+use crate::metrics::metrics;
+use crate::modules::common::errors::{ApiError, ApiResult};
+use crate::modules::common::validacion::parse_id;
+use crate::modules::common::types::{EstadoPedido, TipoMovimiento};
+use crate::modules::pedidos::model::{
+    DespachoRequest, DetallePedido, DetallePedidoResponse, EntregarPedidoRequest, NuevoDetallePedido, NuevoPedido,
+    Pedido, PedidoRequest, PedidoResponse, PedidosQuery,
+};
+use crate::modules::pedidos::repository::{self as pedido_repo, PedidoRepository};
+use crate::modules::personas::repository::PersonaRepository;
+use crate::modules::precios::model::{OrigenPrecio, PrecioLinea};
+use crate::modules::precios::repository::PrecioRepository;
+use crate::modules::precios::lineas::{self, LineaConPrecio};
+use crate::modules::productos::repository::ProductoRepository;
+use crate::modules::ventas::model::{CrearVentaRequest, DetalleVentaRequest, VentaCreadaResponse};
+use crate::modules::ventas::service::{OrigenPedido, VentaService};
+
+pub struct PedidoService {
+    repository: Arc<dyn PedidoRepository>,
+    persona_repo: Arc<dyn PersonaRepository>,
+    producto_repo: Arc<dyn ProductoRepository>,
+    precio_repo: Arc<dyn PrecioRepository>,
+    venta_service: Arc<VentaService>,
+}
+
+impl PedidoService {
+    pub fn new(
+        repository: Arc<dyn PedidoRepository>,
+        persona_repo: Arc<dyn PersonaRepository>,
+        producto_repo: Arc<dyn ProductoRepository>,
+        precio_repo: Arc<dyn PrecioRepository>,
+        venta_service: Arc<VentaService>,
+    ) -> Self {
+        PedidoService {
+            repository,
+            persona_repo,
+            producto_repo,
+            precio_repo,
+            venta_service,
+        }
+    }
+
+    /// Crear un pedido PENDIENTE con los precios vigentes. Se le asigna el stock libre de cada
+    /// producto y lo que falta queda esperando las próximas entradas
+    #[instrument(skip(self, request), fields(id_cliente = %request.id_cliente))]
+    pub fn crear(&self, request: PedidoRequest) -> ApiResult<PedidoResponse> {
+        let (pedido, detalles) = self.preparar_pedido(request)?;
+        let id = self.repository.crear(pedido, detalles)?;
+        self.respuesta(id)
+    }
+
+    #[instrument(skip(self))]
+    pub fn obtener(&self, id_str: &str) -> ApiResult<PedidoResponse> {
+        self.respuesta(parse_id(id_str, "pedido")?)
+    }
+
+    /// Pedidos, filtrados por cliente y estado
+    #[instrument(skip(self))]
+    pub fn listar(&self, query: PedidosQuery) -> ApiResult<Vec<PedidoResponse>> {
+        let id_cliente = match query.id_cliente.as_deref() {
+            Some(id) => Some(parse_id(id, "cliente")?),
+            None => None,
+        };
+        let estado = match query.estado {
+            Some(estado) => Some(match estado.to_uppercase().as_str() {
+                "PENDIENTE" => EstadoPedido::Pendiente,
+                "EN_PREPARACION" => EstadoPedido::EnPreparacion,
+                "DESPACHADO" => EstadoPedido::Despachado,
+                "ENTREGADO" => EstadoPedido::Entregado,
+                _ => return Err(ApiError::InvalidInput(
+                    "Estado de pedido inválido. Valores permitidos: PENDIENTE, EN_PREPARACION, DESPACHADO, ENTREGADO"
+                        .to_string(),
+                )),
+            }),
+            None => None,
+        };
+
+        self.repository
+            .listar(id_cliente, estado)?
+            .into_iter()
+            .map(|pedido| {
+                let (pedido, detalles) = self.repository.buscar(pedido.id)?;
+                self.pedido_response(pedido, detalles)
+            })
+            .collect()
+    }
+
+    /// PENDIENTE → EN_PREPARACION: el almacén empieza a preparar lo asignado
+    #[instrument(skip(self))]
+    pub fn preparar(&self, id_str: &str) -> ApiResult<PedidoResponse> {
+        let id = parse_id(id_str, "pedido")?;
+        self.repository.cambiar_estado(id, EstadoPedido::Pendiente, EstadoPedido::EnPreparacion)?;
+        self.respuesta(id)
+    }
+
+    /// Despachar lo asignado de un pedido EN_PREPARACION, todo o por líneas. Lo que sigue
+    /// pendiente de stock se despacha en envíos posteriores
+    #[instrument(skip(self, request))]
+    pub fn despachar(&self, id_str: &str, request: DespachoRequest) -> ApiResult<PedidoResponse> {
+        let id = parse_id(id_str, "pedido")?;
+        let persona = self.persona_repo.buscar_por_id(parse_id(&request.id_persona, "persona")?)?;
+        if !persona.activo {
+            return Err(ApiError::InactiveClient);
+        }
+
+        let solicitado = request
+            .detalles
+            .iter()
+            .map(|detalle| (detalle.linea, detalle.cantidad))
+            .collect();
+        let salidas = self.repository.despachar(id, solicitado, persona.id)?;
+        metrics().movimientos_inventario
+            .with_label_values(&[TipoMovimiento::Salida.as_str()])
+            .inc_by(salidas.len() as u64);
+
+        self.respuesta(id)
+    }
+
+    /// Entregar un pedido DESPACHADO con su venta final, a los precios del pedido, por el
+    /// camino de `procesar_venta` (caja, pagos y crédito)
+    #[instrument(skip(self, request))]
+    pub fn entregar(&self, id_str: &str, request: EntregarPedidoRequest) -> ApiResult<VentaCreadaResponse> {
+        let id = parse_id(id_str, "pedido")?;
+        let (pedido, detalles) = self.repository.buscar(id)?;
+        pedido_repo::validar_estado(&pedido, EstadoPedido::Despachado)?;
+
+        let lineas = detalles
+            .iter()
+            .map(|detalle| {
+                let unidad = match detalle.id_unidad {
+                    Some(id_unidad) => Some(self.producto_repo.buscar_unidad(id_unidad)?.nombre),
+                    None => None,
+                };
+                Ok(DetalleVentaRequest {
+                    id_producto: detalle.id_producto.to_string(),
+                    cantidad: detalle.cantidad_unidad,
+                    unidad,
+                    numeros_serie: Vec::new(),
+                })
+            })
+            .collect::<ApiResult<Vec<_>>>()?;
+        let precios = detalles.iter().map(precio_pedido).collect();
+
+        self.venta_service.entregar_pedido(
+            CrearVentaRequest {
+                id_cliente: pedido.id_persona.to_string(),
+                id_sesion_caja: request.id_sesion_caja,
+                sucursal: pedido.sucursal,
+                id_vendedor: pedido.id_vendedor.map(|id| id.to_string()),
+                detalles: lineas,
+                pagos: request.pagos,
+                credito: request.credito,
+            },
+            OrigenPedido { id_pedido: pedido.id, precios },
+        )
+    }
+
+    /// Valida el pedido y fija el precio de cada línea con la lista del cliente y las
+    /// promociones vigentes. Los productos serializados no se piden: se venden indicando sus
+    /// números de serie
+    fn preparar_pedido(&self, request: PedidoRequest) -> ApiResult<(NuevoPedido, Vec<NuevoDetallePedido>)> {
+        let encabezado = lineas::validar_encabezado(
+            self.persona_repo.as_ref(),
+            &request.id_cliente,
+            request.id_vendedor.as_deref(),
+            request.sucursal.as_deref(),
+        )?;
+        if request.detalles.is_empty() {
+            return Err(ApiError::InvalidInput("El pedido debe tener al menos un detalle".to_string()));
+        }
+
+        let id = Uuid::new_v4();
+        let fecha = Utc::now().naive_utc();
+        let mut monto = BigDecimal::from(0);
+        let mut detalles = Vec::with_capacity(request.detalles.len());
+        for (linea, detalle_req) in (1..).zip(&request.detalles) {
+            let LineaConPrecio { producto, unidad, cantidad, cantidad_base, precio } = lineas::linea_con_precio(
+                self.producto_repo.as_ref(),
+                self.precio_repo.as_ref(),
+                encabezado.cliente.id_lista_precios,
+                &detalle_req.id_producto,
+                detalle_req.unidad.as_deref(),
+                detalle_req.cantidad,
+                None,
+                fecha,
+            )?;
+            if producto.serializado {
+                return Err(ApiError::BusinessRuleViolation(format!(
+                    "El producto '{}' se sigue por número de serie y no se puede pedir: véndalo indicando sus números",
+                    producto.nombre
+                )));
+            }
+
+            monto += &precio.subtotal;
+            detalles.push(NuevoDetallePedido {
+                id_pedido: id,
+                linea,
+                id_producto: producto.id,
+                id_unidad: unidad.id,
+                cantidad: cantidad_base,
+                cantidad_unidad: cantidad,
+                precio_unitario: precio.precio_unitario,
+                descuento: precio.descuento,
+                monto: precio.subtotal,
+                id_lista_precios: precio.id_lista_precios,
+                id_promocion: precio.id_promocion,
+            });
+        }
+
+        let pedido = NuevoPedido {
+            id,
+            id_persona: encabezado.cliente.id,
+            id_vendedor: encabezado.id_vendedor,
+            sucursal: encabezado.sucursal,
+            monto,
+            observaciones: request
+                .observaciones
+                .map(|observaciones| observaciones.trim().to_string())
+                .filter(|observaciones| !observaciones.is_empty()),
+        };
+        Ok((pedido, detalles))
+    }
+
+    fn respuesta(&self, id: Uuid) -> ApiResult<PedidoResponse> {
+        let (pedido, detalles) = self.repository.buscar(id)?;
+        self.pedido_response(pedido, detalles)
+    }
+
+    fn pedido_response(&self, pedido: Pedido, detalles: Vec<DetallePedido>) -> ApiResult<PedidoResponse> {
+        let descuento: BigDecimal = detalles.iter().map(|detalle| &detalle.descuento).sum();
+        let detalles = detalles
+            .into_iter()
+            .map(|detalle| {
+                let producto = self.producto_repo.buscar_por_id(detalle.id_producto)?;
+                let unidad = match detalle.id_unidad {
+                    Some(id_unidad) => self.producto_repo.buscar_unidad(id_unidad)?.nombre,
+                    None => producto.unidad_venta,
+                };
+                Ok(DetallePedidoResponse {
+                    linea: detalle.linea,
+                    id_producto: detalle.id_producto.to_string(),
+                    nombre_producto: producto.nombre,
+                    unidad,
+                    cantidad: detalle.cantidad_unidad,
+                    cantidad_base: detalle.cantidad,
+                    cantidad_asignada: detalle.cantidad_asignada,
+                    cantidad_despachada: detalle.cantidad_despachada,
+                    cantidad_pendiente: detalle.cantidad_pendiente(),
+                    precio_unitario: detalle.precio_unitario.to_f64().unwrap_or(0.0),
+                    descuento: detalle.descuento.to_f64().unwrap_or(0.0),
+                    subtotal: detalle.monto.to_f64().unwrap_or(0.0),
+                    origen_precio: OrigenPrecio::de(detalle.id_lista_precios, detalle.id_promocion),
+                    id_lista_precios: detalle.id_lista_precios.map(|id| id.to_string()),
+                    id_promocion: detalle.id_promocion.map(|id| id.to_string()),
+                })
+            })
+            .collect::<ApiResult<Vec<_>>>()?;
+
+        Ok(PedidoResponse {
+            id: pedido.id.to_string(),
+            id_cliente: pedido.id_persona.to_string(),
+            id_vendedor: pedido.id_vendedor.map(|id| id.to_string()),
+            sucursal: pedido.sucursal,
+            estado: pedido.estado,
+            descuento: descuento.to_f64().unwrap_or(0.0),
+            total: pedido.monto.to_f64().unwrap_or(0.0),
+            observaciones: pedido.observaciones,
+            id_venta: pedido.id_venta.map(|id| id.to_string()),
+            fecha_creacion: pedido.fecha_creacion.format("%Y-%m-%d %H:%M:%S").to_string(),
+            detalles,
+        })
+    }
+}
+
+/// Precio fijado en la línea del pedido, para venderla sin recalcularlo
+fn precio_pedido(detalle: &DetallePedido) -> PrecioLinea {
+    PrecioLinea::fijado(
+        &detalle.precio_unitario,
+        detalle.cantidad_unidad,
+        &detalle.descuento,
+        &detalle.monto,
+        detalle.id_lista_precios,
+        detalle.id_promocion,
+    )
+}
+
@@ -9,6 +9,9 @@ use crate::state::app_state::AppState;
 pub struct PersonasQuery {
     #[schema(example = "CLIENTE")]
     pub tipo: Option<String>,
+    /// Nombre (sin distinguir acentos, tolera errores de escritura) o prefijo de documento
+    #[schema(example = "maria gonzalez")]
+    pub q: Option<String>,
 }
 
 /// POST /api/personas - Crear nueva persona
@@ -72,6 +75,7 @@ pub async fn obtener_persona(
     ),
     responses(
         (status = 200, description = "Lista de personas", body = Vec<PersonaResponse>),
+        (status = 400, description = "Filtros inválidos", body = ErrorResponse),
         (status = 500, description = "Error interno del servidor", body = ErrorResponse)
     )
 )]
@@ -81,7 +85,7 @@ pub async fn listar_personas(
 ) -> Result<HttpResponse> {
     let service = &state.persona_service;
 
-    match service.listar_personas(query.tipo.clone()) {
+    match service.listar_personas(query.tipo.clone(), query.q.clone()) {
         Ok(personas) => Ok(HttpResponse::Ok().json(personas)),
         Err(e) => Ok(e.error_response()),
     }
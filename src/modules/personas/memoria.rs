@@ -0,0 +1,94 @@
+use bigdecimal::BigDecimal;
+use chrono::Utc;
+use uuid::Uuid;
+use crate::modules::common::errors::ApiResult;
+use crate::modules::common::memoria::MemoriaRepository;
+use crate::modules::personas::model::{FiltroPersonas, NuevaPersona, Persona};
+use crate::modules::personas::repository::{self as persona_repo, PersonaRepository};
+
+impl MemoriaRepository {
+    /// Da de baja a una persona (activo = false), para preparar casos que la API no permite
+    pub fn desactivar_persona(&self, id: Uuid) {
+        if let Some(persona) = self.tablas().personas.iter_mut().find(|p| p.id == id) {
+            persona.activo = false;
+        }
+    }
+}
+
+impl PersonaRepository for MemoriaRepository {
+    fn buscar_por_id(&self, id: Uuid) -> ApiResult<Persona> {
+        self.tablas()
+            .personas
+            .iter()
+            .find(|persona| persona.id == id && persona.activo)
+            .cloned()
+            .ok_or_else(|| persona_repo::persona_no_encontrada(id))
+    }
+
+    fn listar(&self, filtro: &FiltroPersonas) -> ApiResult<Vec<Persona>> {
+        Ok(self
+            .tablas()
+            .personas
+            .iter()
+            .filter(|persona| persona.activo)
+            .filter(|persona| filtro.perfil.is_none_or(|perfil| persona.perfil == perfil))
+            .filter(|persona| match filtro.busqueda {
+                Some(ref busqueda) if busqueda.es_documento() => persona.documento.starts_with(&busqueda.texto),
+                Some(ref busqueda) => busqueda.coincide_con(&persona.nombre),
+                None => true,
+            })
+            .cloned()
+            .collect())
+    }
+
+    fn crear(&self, nueva_persona: NuevaPersona) -> ApiResult<Uuid> {
+        self.transaccion(|tablas| {
+            if tablas.personas.iter().any(|p| p.activo && p.documento == nueva_persona.documento) {
+                return Err(persona_repo::documento_duplicado(&nueva_persona.documento));
+            }
+
+            let ahora = Utc::now().naive_utc();
+            let id = Uuid::new_v4();
+            tablas.personas.push(Persona {
+                id,
+                nombre: nueva_persona.nombre,
+                documento: nueva_persona.documento,
+                perfil: nueva_persona.perfil,
+                email: nueva_persona.email,
+                telefono: nueva_persona.telefono,
+                fecha_creacion: ahora,
+                fecha_actualizacion: ahora,
+                activo: true,
+                limite_credito: None,
+                id_lista_precios: None,
+            });
+            Ok(id)
+        })
+    }
+
+    fn actualizar_limite_credito(&self, id: Uuid, limite_credito: Option<BigDecimal>) -> ApiResult<Persona> {
+        self.transaccion(|tablas| {
+            let persona = tablas
+                .personas
+                .iter_mut()
+                .find(|persona| persona.id == id && persona.activo)
+                .ok_or_else(|| persona_repo::persona_no_encontrada(id))?;
+            persona.limite_credito = limite_credito;
+            persona.fecha_actualizacion = Utc::now().naive_utc();
+            Ok(persona.clone())
+        })
+    }
+
+    fn asignar_lista_precios(&self, id: Uuid, id_lista_precios: Option<Uuid>) -> ApiResult<Persona> {
+        self.transaccion(|tablas| {
+            let persona = tablas
+                .personas
+                .iter_mut()
+                .find(|persona| persona.id == id && persona.activo)
+                .ok_or_else(|| persona_repo::persona_no_encontrada(id))?;
+            persona.id_lista_precios = id_lista_precios;
+            persona.fecha_actualizacion = Utc::now().naive_utc();
+            Ok(persona.clone())
+        })
+    }
+}
@@ -2,3 +2,4 @@ pub mod model;
 pub mod repository;
 pub mod service;
 pub mod handler;
+mod memoria;
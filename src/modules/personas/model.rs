@@ -1,8 +1,10 @@
+use bigdecimal::{BigDecimal, ToPrimitive};
 use chrono::NaiveDateTime;
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use utoipa::ToSchema;
+use crate::modules::common::busqueda::Busqueda;
 use crate::modules::common::types::TipoPerfil;
 use crate::schema::personas;
 
@@ -19,6 +21,18 @@ pub struct Persona {
     pub fecha_creacion: NaiveDateTime,
     pub fecha_actualizacion: NaiveDateTime,
     pub activo: bool,
+    /// Cuánto puede deber entre sus ventas sin pagar; None si no compra a crédito
+    pub limite_credito: Option<BigDecimal>,
+    /// Lista de precios con que se le vende; None para los precios de catálogo
+    pub id_lista_precios: Option<Uuid>,
+}
+
+// Filters for listing personas
+#[derive(Debug, Default)]
+pub struct FiltroPersonas {
+    pub perfil: Option<TipoPerfil>,
+    /// Por nombre o, si solo tiene dígitos, por prefijo de documento
+    pub busqueda: Option<Busqueda>,
 }
 
 // DTO for API Response
@@ -36,6 +50,12 @@ pub struct PersonaResponse {
     pub email: Option<String>,
     #[schema(example = "+57 300 123 4567")]
     pub telefono: Option<String>,
+    /// Solo los clientes con límite de crédito compran a crédito
+    #[schema(example = 500000.0)]
+    pub limite_credito: Option<f64>,
+    /// Lista de precios del cliente; null si compra a precios de catálogo
+    #[schema(example = "aa0e8400-e29b-41d4-a716-446655440000")]
+    pub id_lista_precios: Option<String>,
 }
 
 impl From<Persona> for PersonaResponse {
@@ -47,6 +67,8 @@ impl From<Persona> for PersonaResponse {
             perfil: format!("{:?}", persona.perfil).to_uppercase(),
             email: persona.email,
             telefono: persona.telefono,
+            limite_credito: persona.limite_credito.map(|limite| limite.to_f64().unwrap_or(0.0)),
+            id_lista_precios: persona.id_lista_precios.map(|id| id.to_string()),
         }
     }
 }
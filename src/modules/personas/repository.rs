@@ -1,21 +1,64 @@
+use bigdecimal::BigDecimal;
 use diesel::prelude::*;
 use diesel::r2d2::{ConnectionManager, Pool, PooledConnection};
 use uuid::Uuid;
+use tracing::instrument;
+use crate::modules::auditoria;
 use crate::modules::common::errors::{ApiError, ApiResult};
 use crate::modules::common::types::TipoPerfil;
-use crate::modules::personas::model::{Persona, NuevaPersona};
+use crate::modules::personas::model::{FiltroPersonas, Persona, NuevaPersona};
 use crate::schema::personas;
 
 pub type DbPool = Pool<ConnectionManager<PgConnection>>;
 pub type DbConnection = PooledConnection<ConnectionManager<PgConnection>>;
 
-pub struct PersonaRepository {
+/// Acceso a datos de personas.
+///
+/// `PgPersonaRepository` trabaja contra PostgreSQL; `MemoriaRepository` (en `common::memoria`)
+/// permite probar los servicios sin base de datos.
+pub trait PersonaRepository: Send + Sync {
+    fn buscar_por_id(&self, id: Uuid) -> ApiResult<Persona>;
+
+    fn listar(&self, filtro: &FiltroPersonas) -> ApiResult<Vec<Persona>>;
+
+    fn validar_activo(&self, id: Uuid) -> ApiResult<bool> {
+        let persona = self.buscar_por_id(id)?;
+        Ok(persona.activo)
+    }
+
+    /// La persona, si está activa y tiene perfil VENDEDOR
+    fn buscar_vendedor_activo(&self, id: Uuid) -> ApiResult<Persona> {
+        let vendedor = self.buscar_por_id(id)?;
+        if vendedor.perfil != TipoPerfil::Vendedor {
+            return Err(ApiError::BusinessRuleViolation(
+                format!("La persona '{}' no tiene perfil VENDEDOR", vendedor.nombre)
+            ));
+        }
+        if !vendedor.activo {
+            return Err(ApiError::BusinessRuleViolation(
+                format!("El vendedor '{}' está inactivo", vendedor.nombre)
+            ));
+        }
+        Ok(vendedor)
+    }
+
+    /// Inserta la persona; falla si ya existe otra activa con el mismo documento
+    fn crear(&self, nueva_persona: NuevaPersona) -> ApiResult<Uuid>;
+
+    /// Fija o retira (None) el límite de crédito de la persona
+    fn actualizar_limite_credito(&self, id: Uuid, limite_credito: Option<BigDecimal>) -> ApiResult<Persona>;
+
+    /// Asigna o retira (None) la lista de precios de la persona
+    fn asignar_lista_precios(&self, id: Uuid, id_lista_precios: Option<Uuid>) -> ApiResult<Persona>;
+}
+
+pub struct PgPersonaRepository {
     pool: DbPool,
 }
 
-impl PersonaRepository {
+impl PgPersonaRepository {
     pub fn new(pool: DbPool) -> Self {
-        PersonaRepository { pool }
+        PgPersonaRepository { pool }
     }
 
     fn get_connection(&self) -> ApiResult<DbConnection> {
@@ -24,7 +67,41 @@ impl PersonaRepository {
             .map_err(|e| ApiError::DatabaseError(e.to_string()))
     }
 
-    pub fn buscar_por_id(&self, id: Uuid) -> ApiResult<Persona> {
+    fn crear_con_conexion(&self, conn: &mut PgConnection, nueva_persona: NuevaPersona) -> ApiResult<Uuid> {
+        // Validar que el documento no exista ya
+        let existe = personas::table
+            .filter(personas::documento.eq(&nueva_persona.documento))
+            .filter(personas::activo.eq(true))
+            .count()
+            .get_result::<i64>(conn)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+        if existe > 0 {
+            return Err(documento_duplicado(&nueva_persona.documento));
+        }
+
+        let id = Uuid::new_v4();
+
+        // Insertar la nueva persona con ID generado
+        diesel::insert_into(personas::table)
+            .values((
+                personas::id.eq(id),
+                personas::nombre.eq(&nueva_persona.nombre),
+                personas::documento.eq(&nueva_persona.documento),
+                personas::perfil.eq(&nueva_persona.perfil),
+                personas::email.eq(&nueva_persona.email),
+                personas::telefono.eq(&nueva_persona.telefono),
+            ))
+            .execute(conn)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+        Ok(id)
+    }
+}
+
+impl PersonaRepository for PgPersonaRepository {
+    #[instrument(skip(self))]
+    fn buscar_por_id(&self, id: Uuid) -> ApiResult<Persona> {
         let mut conn = self.get_connection()?;
 
         personas::table
@@ -33,65 +110,94 @@ impl PersonaRepository {
             .select(Persona::as_select())
             .first(&mut conn)
             .map_err(|e| match e {
-                diesel::result::Error::NotFound => ApiError::NotFound(format!("Persona con ID {} no encontrada", id)),
+                diesel::result::Error::NotFound => persona_no_encontrada(id),
                 _ => ApiError::DatabaseError(e.to_string()),
             })
     }
 
-    pub fn listar(&self, perfil_filtro: Option<TipoPerfil>) -> ApiResult<Vec<Persona>> {
+    #[instrument(skip(self))]
+    fn listar(&self, filtro: &FiltroPersonas) -> ApiResult<Vec<Persona>> {
         let mut conn = self.get_connection()?;
 
         let mut query = personas::table
             .filter(personas::activo.eq(true))
             .into_boxed();
 
-        if let Some(perfil) = perfil_filtro {
+        if let Some(perfil) = filtro.perfil {
             query = query.filter(personas::perfil.eq(perfil));
         }
 
+        match filtro.busqueda {
+            Some(ref busqueda) if busqueda.es_documento() => {
+                query = query
+                    .filter(personas::documento.like(format!("{}%", busqueda.texto)))
+                    .order(personas::documento.asc());
+            }
+            Some(ref busqueda) => {
+                query = query
+                    .filter(busqueda.coincide("personas.nombre"))
+                    .order((busqueda.relevancia("personas.nombre").desc(), personas::nombre.asc()));
+            }
+            None => {}
+        }
+
         query
             .select(Persona::as_select())
             .load(&mut conn)
             .map_err(|e| ApiError::DatabaseError(e.to_string()))
     }
 
-    pub fn validar_activo(&self, id: Uuid) -> ApiResult<bool> {
-        let persona = self.buscar_por_id(id)?;
-        Ok(persona.activo)
+    #[instrument(skip(self, nueva_persona))]
+    fn crear(&self, nueva_persona: NuevaPersona) -> ApiResult<Uuid> {
+        let mut conn = self.get_connection()?;
+
+        conn.transaction::<Uuid, ApiError, _>(|conn| {
+            auditoria::contexto::aplicar(conn)?;
+            self.crear_con_conexion(conn, nueva_persona)
+        })
     }
 
-    pub fn crear(&self, nueva_persona: NuevaPersona) -> ApiResult<Uuid> {
+    #[instrument(skip(self))]
+    fn actualizar_limite_credito(&self, id: Uuid, limite_credito: Option<BigDecimal>) -> ApiResult<Persona> {
         let mut conn = self.get_connection()?;
 
-        // Validar que el documento no exista ya
-        let existe = personas::table
-            .filter(personas::documento.eq(&nueva_persona.documento))
-            .filter(personas::activo.eq(true))
-            .count()
-            .get_result::<i64>(&mut conn)
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        conn.transaction::<Persona, ApiError, _>(|conn| {
+            auditoria::contexto::aplicar(conn)?;
+
+            diesel::update(personas::table.find(id).filter(personas::activo.eq(true)))
+                .set(personas::limite_credito.eq(limite_credito))
+                .returning(Persona::as_returning())
+                .get_result(conn)
+                .map_err(|e| match e {
+                    diesel::result::Error::NotFound => persona_no_encontrada(id),
+                    _ => ApiError::DatabaseError(e.to_string()),
+                })
+        })
+    }
 
-        if existe > 0 {
-            return Err(ApiError::BusinessRuleViolation(
-                format!("Ya existe una persona con el documento {}", nueva_persona.documento)
-            ));
-        }
+    #[instrument(skip(self))]
+    fn asignar_lista_precios(&self, id: Uuid, id_lista_precios: Option<Uuid>) -> ApiResult<Persona> {
+        let mut conn = self.get_connection()?;
 
-        let id = Uuid::new_v4();
+        conn.transaction::<Persona, ApiError, _>(|conn| {
+            auditoria::contexto::aplicar(conn)?;
+
+            diesel::update(personas::table.find(id).filter(personas::activo.eq(true)))
+                .set(personas::id_lista_precios.eq(id_lista_precios))
+                .returning(Persona::as_returning())
+                .get_result(conn)
+                .map_err(|e| match e {
+                    diesel::result::Error::NotFound => persona_no_encontrada(id),
+                    _ => ApiError::DatabaseError(e.to_string()),
+                })
+        })
+    }
+}
 
-        // Insertar la nueva persona con ID generado
-        diesel::insert_into(personas::table)
-            .values((
-                personas::id.eq(id),
-                personas::nombre.eq(&nueva_persona.nombre),
-                personas::documento.eq(&nueva_persona.documento),
-                personas::perfil.eq(&nueva_persona.perfil),
-                personas::email.eq(&nueva_persona.email),
-                personas::telefono.eq(&nueva_persona.telefono),
-            ))
-            .execute(&mut conn)
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+pub(crate) fn persona_no_encontrada(id: Uuid) -> ApiError {
+    ApiError::NotFound(format!("Persona con ID {} no encontrada", id))
+}
 
-        Ok(id)
-    }
+pub(crate) fn documento_duplicado(documento: &str) -> ApiError {
+    ApiError::BusinessRuleViolation(format!("Ya existe una persona con el documento {}", documento))
 }
@@ -1,19 +1,23 @@
+use std::sync::Arc;
 use uuid::Uuid;
+use tracing::instrument;
+use crate::modules::common::busqueda::Busqueda;
 use crate::modules::common::errors::{ApiError, ApiResult};
 use crate::modules::common::types::TipoPerfil;
-use crate::modules::personas::model::{PersonaResponse, CrearPersonaRequest, PersonaCreadaResponse, NuevaPersona};
+use crate::modules::personas::model::{FiltroPersonas, PersonaResponse, CrearPersonaRequest, PersonaCreadaResponse, NuevaPersona};
 use crate::modules::personas::repository::PersonaRepository;
 
 pub struct PersonaService {
-    repository: PersonaRepository,
+    repository: Arc<dyn PersonaRepository>,
 }
 
 impl PersonaService {
-    pub fn new(repository: PersonaRepository) -> Self {
+    pub fn new(repository: Arc<dyn PersonaRepository>) -> Self {
         PersonaService { repository }
     }
 
     /// RF4: Obtener persona por ID
+    #[instrument(skip(self))]
     pub fn obtener_persona(&self, id_str: &str) -> ApiResult<PersonaResponse> {
         let id = Uuid::parse_str(id_str)
             .map_err(|_| ApiError::InvalidInput("ID de persona inválido".to_string()))?;
@@ -22,8 +26,9 @@ impl PersonaService {
         Ok(PersonaResponse::from(persona))
     }
 
-    /// Listar personas con filtro opcional por tipo de perfil
-    pub fn listar_personas(&self, tipo_perfil: Option<String>) -> ApiResult<Vec<PersonaResponse>> {
+    /// Listar personas con filtro opcional por tipo de perfil y búsqueda por nombre o documento
+    #[instrument(skip(self))]
+    pub fn listar_personas(&self, tipo_perfil: Option<String>, q: Option<String>) -> ApiResult<Vec<PersonaResponse>> {
         let perfil_filtro = match tipo_perfil {
             Some(tipo) => {
                 let perfil = match tipo.to_uppercase().as_str() {
@@ -37,11 +42,17 @@ impl PersonaService {
             None => None,
         };
 
-        let personas = self.repository.listar(perfil_filtro)?;
+        let filtro = FiltroPersonas {
+            perfil: perfil_filtro,
+            busqueda: Busqueda::parse(q)?,
+        };
+
+        let personas = self.repository.listar(&filtro)?;
         Ok(personas.into_iter().map(PersonaResponse::from).collect())
     }
 
     /// Validar que una persona existe y está activa
+    #[instrument(skip(self))]
     pub fn validar_persona_activa(&self, id: Uuid) -> ApiResult<()> {
         let es_activo = self.repository.validar_activo(id)?;
         if !es_activo {
@@ -51,6 +62,7 @@ impl PersonaService {
     }
 
     /// Crear una nueva persona
+    #[instrument(skip(self, request))]
     pub fn crear_persona(&self, request: CrearPersonaRequest) -> ApiResult<PersonaCreadaResponse> {
         // Validar campos requeridos
         if request.nombre.trim().is_empty() {
@@ -61,6 +73,15 @@ impl PersonaService {
             return Err(ApiError::InvalidInput("El documento es requerido".to_string()));
         }
 
+        // Mismas reglas que chk_nombre_valido y chk_documento_valido, para responder 400 y no 500
+        if request.nombre.trim().chars().count() < 2 {
+            return Err(ApiError::InvalidInput("El nombre debe tener al menos 2 caracteres".to_string()));
+        }
+
+        if request.documento.trim().chars().count() < 5 {
+            return Err(ApiError::InvalidInput("El documento debe tener al menos 5 caracteres".to_string()));
+        }
+
         // Parsear y validar tipo de perfil
         let perfil = match request.perfil.to_uppercase().as_str() {
             "VENDEDOR" => TipoPerfil::Vendedor,
@@ -0,0 +1,320 @@
+use actix_web::{web, HttpResponse, ResponseError, Result};
+use crate::modules::common::errors::ErrorResponse;
+use crate::modules::personas::model::PersonaResponse;
+use crate::modules::precios::model::{
+    CotizacionPreciosResponse, CotizarPreciosRequest, ListaPreciosClienteRequest, ListaPreciosRequest,
+    ListaPreciosResponse, PrecioListaQuery, PrecioListaRequest, PrecioListaResponse, PromocionRequest,
+    PromocionResponse, PromocionesQuery,
+};
+use crate::state::app_state::AppState;
+
+/// POST /v1/precios/listas - Crear lista de precios
+#[utoipa::path(
+    post,
+    path = "/v1/precios/listas",
+    tag = "Precios",
+    request_body = ListaPreciosRequest,
+    responses(
+        (status = 201, description = "Lista creada exitosamente", body = ListaPreciosResponse),
+        (status = 400, description = "Datos inválidos o ya existe una lista con ese nombre", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn crear_lista(
+    state: web::Data<AppState>,
+    body: web::Json<ListaPreciosRequest>,
+) -> Result<HttpResponse> {
+    let service = &state.precio_service;
+
+    match service.crear_lista(body.into_inner()) {
+        Ok(response) => Ok(HttpResponse::Created().json(response)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// GET /v1/precios/listas - Listar listas de precios
+#[utoipa::path(
+    get,
+    path = "/v1/precios/listas",
+    tag = "Precios",
+    responses(
+        (status = 200, description = "Listas activas por nombre", body = Vec<ListaPreciosResponse>),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn listar_listas(state: web::Data<AppState>) -> Result<HttpResponse> {
+    let service = &state.precio_service;
+
+    match service.listar_listas() {
+        Ok(listas) => Ok(HttpResponse::Ok().json(listas)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// GET /v1/precios/listas/:id - Obtener una lista de precios
+#[utoipa::path(
+    get,
+    path = "/v1/precios/listas/{id}",
+    tag = "Precios",
+    params(
+        ("id" = String, Path, description = "ID de la lista (UUID)")
+    ),
+    responses(
+        (status = 200, description = "Lista de precios", body = ListaPreciosResponse),
+        (status = 400, description = "ID inválido", body = ErrorResponse),
+        (status = 404, description = "Lista no encontrada", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn obtener_lista(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    let service = &state.precio_service;
+
+    match service.obtener_lista(&id) {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// GET /v1/precios/listas/:id/productos - Precios de una lista
+#[utoipa::path(
+    get,
+    path = "/v1/precios/listas/{id}/productos",
+    tag = "Precios",
+    params(
+        ("id" = String, Path, description = "ID de la lista (UUID)")
+    ),
+    responses(
+        (status = 200, description = "Productos incluidos en la lista y su precio", body = Vec<PrecioListaResponse>),
+        (status = 400, description = "ID inválido", body = ErrorResponse),
+        (status = 404, description = "Lista no encontrada", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn listar_precios(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    let service = &state.precio_service;
+
+    match service.listar_precios(&id) {
+        Ok(precios) => Ok(HttpResponse::Ok().json(precios)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// PUT /v1/precios/listas/:id/productos/:id_producto - Fijar el precio de un producto en una lista
+#[utoipa::path(
+    put,
+    path = "/v1/precios/listas/{id}/productos/{id_producto}",
+    tag = "Precios",
+    params(
+        ("id" = String, Path, description = "ID de la lista (UUID)"),
+        ("id_producto" = String, Path, description = "ID del producto (UUID)")
+    ),
+    request_body = PrecioListaRequest,
+    responses(
+        (status = 200, description = "Precio fijado", body = PrecioListaResponse),
+        (status = 400, description = "ID, precio o unidad inválidos", body = ErrorResponse),
+        (status = 404, description = "Lista o producto no encontrados", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn fijar_precio(
+    state: web::Data<AppState>,
+    path: web::Path<(String, String)>,
+    body: web::Json<PrecioListaRequest>,
+) -> Result<HttpResponse> {
+    let (id, id_producto) = path.into_inner();
+    let service = &state.precio_service;
+
+    match service.fijar_precio(&id, &id_producto, body.into_inner()) {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// DELETE /v1/precios/listas/:id/productos/:id_producto - Quitar un producto de una lista
+#[utoipa::path(
+    delete,
+    path = "/v1/precios/listas/{id}/productos/{id_producto}",
+    tag = "Precios",
+    params(
+        ("id" = String, Path, description = "ID de la lista (UUID)"),
+        ("id_producto" = String, Path, description = "ID del producto (UUID)"),
+        PrecioListaQuery
+    ),
+    responses(
+        (status = 204, description = "El producto vuelve a su precio de catálogo para la lista"),
+        (status = 400, description = "ID o unidad inválidos", body = ErrorResponse),
+        (status = 404, description = "El producto no tiene precio en la lista", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn quitar_precio(
+    state: web::Data<AppState>,
+    path: web::Path<(String, String)>,
+    query: web::Query<PrecioListaQuery>,
+) -> Result<HttpResponse> {
+    let (id, id_producto) = path.into_inner();
+    let service = &state.precio_service;
+
+    match service.quitar_precio(&id, &id_producto, query.into_inner()) {
+        Ok(()) => Ok(HttpResponse::NoContent().finish()),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// PUT /v1/personas/:id/lista-precios - Asignar o retirar la lista de precios de un cliente
+#[utoipa::path(
+    put,
+    path = "/v1/personas/{id}/lista-precios",
+    tag = "Precios",
+    params(
+        ("id" = String, Path, description = "ID del cliente (UUID)")
+    ),
+    request_body = ListaPreciosClienteRequest,
+    responses(
+        (status = 200, description = "Lista asignada", body = PersonaResponse),
+        (status = 400, description = "ID inválido o la persona no es cliente", body = ErrorResponse),
+        (status = 404, description = "Persona o lista no encontradas", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn asignar_lista_cliente(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<ListaPreciosClienteRequest>,
+) -> Result<HttpResponse> {
+    let service = &state.precio_service;
+
+    match service.asignar_lista_cliente(&path.into_inner(), body.into_inner()) {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// POST /v1/precios/promociones - Crear promoción
+#[utoipa::path(
+    post,
+    path = "/v1/precios/promociones",
+    tag = "Precios",
+    request_body = PromocionRequest,
+    responses(
+        (status = 201, description = "Promoción creada exitosamente", body = PromocionResponse),
+        (status = 400, description = "Datos inválidos para el tipo de promoción", body = ErrorResponse),
+        (status = 404, description = "Producto no encontrado", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn crear_promocion(
+    state: web::Data<AppState>,
+    body: web::Json<PromocionRequest>,
+) -> Result<HttpResponse> {
+    let service = &state.precio_service;
+
+    match service.crear_promocion(body.into_inner()) {
+        Ok(response) => Ok(HttpResponse::Created().json(response)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// GET /v1/precios/promociones - Listar promociones
+#[utoipa::path(
+    get,
+    path = "/v1/precios/promociones",
+    tag = "Precios",
+    params(PromocionesQuery),
+    responses(
+        (status = 200, description = "Promociones activas por fecha de inicio", body = Vec<PromocionResponse>),
+        (status = 400, description = "Filtro inválido", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn listar_promociones(
+    state: web::Data<AppState>,
+    query: web::Query<PromocionesQuery>,
+) -> Result<HttpResponse> {
+    let service = &state.precio_service;
+
+    match service.listar_promociones(query.into_inner()) {
+        Ok(promociones) => Ok(HttpResponse::Ok().json(promociones)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// DELETE /v1/precios/promociones/:id - Dar de baja una promoción
+#[utoipa::path(
+    delete,
+    path = "/v1/precios/promociones/{id}",
+    tag = "Precios",
+    params(
+        ("id" = String, Path, description = "ID de la promoción (UUID)")
+    ),
+    responses(
+        (status = 204, description = "Promoción dada de baja"),
+        (status = 400, description = "ID inválido", body = ErrorResponse),
+        (status = 404, description = "Promoción no encontrada", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn desactivar_promocion(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    let service = &state.precio_service;
+
+    match service.desactivar_promocion(&id) {
+        Ok(()) => Ok(HttpResponse::NoContent().finish()),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// POST /v1/precios/cotizar - Calcular precios sin registrar la venta
+#[utoipa::path(
+    post,
+    path = "/v1/precios/cotizar",
+    tag = "Precios",
+    request_body = CotizarPreciosRequest,
+    responses(
+        (status = 200, description = "Precio de cada línea con la regla que lo fijó", body = CotizacionPreciosResponse),
+        (status = 400, description = "Datos inválidos", body = ErrorResponse),
+        (status = 404, description = "Cliente o producto no encontrados", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn cotizar(
+    state: web::Data<AppState>,
+    body: web::Json<CotizarPreciosRequest>,
+) -> Result<HttpResponse> {
+    let service = &state.precio_service;
+
+    match service.cotizar(body.into_inner()) {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// Se registra antes que `personas::handler::configure`, para que el scope `/personas` no
+/// tome la ruta de la lista del cliente
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.route("/personas/{id}/lista-precios", web::put().to(asignar_lista_cliente))
+        .service(
+            web::scope("/precios")
+                .route("/listas", web::post().to(crear_lista))
+                .route("/listas", web::get().to(listar_listas))
+                .route("/listas/{id}", web::get().to(obtener_lista))
+                .route("/listas/{id}/productos", web::get().to(listar_precios))
+                .route("/listas/{id}/productos/{id_producto}", web::put().to(fijar_precio))
+                .route("/listas/{id}/productos/{id_producto}", web::delete().to(quitar_precio))
+                .route("/promociones", web::post().to(crear_promocion))
+                .route("/promociones", web::get().to(listar_promociones))
+                .route("/promociones/{id}", web::delete().to(desactivar_promocion))
+                .route("/cotizar", web::post().to(cotizar))
+        );
+}
@@ -0,0 +1,93 @@
+//! Encabezado y líneas con precio de los documentos de venta: cotizaciones, pedidos y ventas.
+//!
+//! Los tres validan igual a su cliente, su vendedor y su sucursal, y fijan el precio de cada
+//! línea con la lista del cliente y las promociones vigentes (ver `precio_de_linea`).
+
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+use crate::modules::common::errors::{ApiError, ApiResult};
+use crate::modules::common::validacion::parse_id;
+use crate::modules::personas::model::Persona;
+use crate::modules::personas::repository::PersonaRepository;
+use crate::modules::precios::model::PrecioLinea;
+use crate::modules::precios::repository::PrecioRepository;
+use crate::modules::precios::service::precio_de_linea;
+use crate::modules::productos::model::Producto;
+use crate::modules::productos::repository::ProductoRepository;
+use crate::modules::productos::unidades::UnidadVenta;
+
+/// Longitud máxima de la sucursal (columna VARCHAR(100))
+const LONGITUD_SUCURSAL: usize = 100;
+
+/// Cliente, vendedor y sucursal validados de un documento
+#[derive(Debug, Clone)]
+pub struct Encabezado {
+    pub cliente: Persona,
+    pub id_vendedor: Option<Uuid>,
+    /// Sin espacios sobrantes; None si viene vacía
+    pub sucursal: Option<String>,
+}
+
+/// Línea validada: su producto, su unidad, la cantidad en ella y en unidades base, y su precio
+#[derive(Debug, Clone)]
+pub struct LineaConPrecio {
+    pub producto: Producto,
+    pub unidad: UnidadVenta,
+    pub cantidad: i32,
+    pub cantidad_base: i32,
+    pub precio: PrecioLinea,
+}
+
+/// El cliente debe estar activo y el vendedor, si se indica, ser un VENDEDOR activo
+pub fn validar_encabezado(
+    persona_repo: &dyn PersonaRepository,
+    id_cliente: &str,
+    id_vendedor: Option<&str>,
+    sucursal: Option<&str>,
+) -> ApiResult<Encabezado> {
+    let cliente = persona_repo.buscar_por_id(parse_id(id_cliente, "cliente")?)?;
+    if !cliente.activo {
+        return Err(ApiError::InactiveClient);
+    }
+    let id_vendedor = match id_vendedor {
+        Some(id_vendedor) => Some(persona_repo.buscar_vendedor_activo(parse_id(id_vendedor, "vendedor")?)?.id),
+        None => None,
+    };
+    let sucursal = sucursal.map(str::trim).filter(|sucursal| !sucursal.is_empty());
+    if sucursal.is_some_and(|sucursal| sucursal.chars().count() > LONGITUD_SUCURSAL) {
+        return Err(ApiError::InvalidInput(format!(
+            "La sucursal no puede superar {} caracteres",
+            LONGITUD_SUCURSAL
+        )));
+    }
+
+    Ok(Encabezado { cliente, id_vendedor, sucursal: sucursal.map(str::to_string) })
+}
+
+/// Resuelve el producto y la unidad de la línea. Su precio es `precio_fijado` (el guardado
+/// en una cotización o un pedido) o, sin él, el de la lista `id_lista_precios` en `fecha`
+#[allow(clippy::too_many_arguments)]
+pub fn linea_con_precio(
+    producto_repo: &dyn ProductoRepository,
+    precio_repo: &dyn PrecioRepository,
+    id_lista_precios: Option<Uuid>,
+    id_producto: &str,
+    unidad: Option<&str>,
+    cantidad: i32,
+    precio_fijado: Option<PrecioLinea>,
+    fecha: NaiveDateTime,
+) -> ApiResult<LineaConPrecio> {
+    if cantidad <= 0 {
+        return Err(ApiError::InvalidInput("La cantidad debe ser mayor a 0".to_string()));
+    }
+    let producto = producto_repo.buscar_por_id(parse_id(id_producto, "producto")?)?;
+    let unidades = producto_repo.listar_unidades(producto.id)?;
+    let unidad = UnidadVenta::resolver(&producto, &unidades, unidad)?;
+    let cantidad_base = unidad.a_unidad_base(cantidad)?;
+    let precio = match precio_fijado {
+        Some(precio) => precio,
+        None => precio_de_linea(precio_repo, id_lista_precios, &producto, &unidad, cantidad, fecha)?,
+    };
+
+    Ok(LineaConPrecio { producto, unidad, cantidad, cantidad_base, precio })
+}
@@ -0,0 +1,195 @@
+use chrono::{NaiveDateTime, Utc};
+use uuid::Uuid;
+use crate::modules::common::errors::ApiResult;
+use crate::modules::common::memoria::{MemoriaRepository, Tablas};
+use crate::modules::precios::model::{
+    ListaPrecios, NuevaListaPrecios, NuevaPromocion, NuevoPrecioLista, PrecioLista, Promocion,
+};
+use crate::modules::precios::reglas as reglas_precio;
+use crate::modules::precios::repository::{self as precio_repo, PrecioRepository};
+
+impl Tablas {
+    fn lista_precios(&self, id: Uuid) -> ApiResult<&ListaPrecios> {
+        self.listas_precios
+            .iter()
+            .find(|lista| lista.id == id && lista.activo)
+            .ok_or_else(|| precio_repo::lista_no_encontrada(id))
+    }
+}
+
+impl PrecioRepository for MemoriaRepository {
+    fn buscar_lista(&self, id: Uuid) -> ApiResult<ListaPrecios> {
+        self.tablas().lista_precios(id).cloned()
+    }
+
+    fn listar_listas(&self) -> ApiResult<Vec<ListaPrecios>> {
+        let mut listas: Vec<ListaPrecios> =
+            self.tablas().listas_precios.iter().filter(|lista| lista.activo).cloned().collect();
+        listas.sort_by(|a, b| a.nombre.cmp(&b.nombre));
+        Ok(listas)
+    }
+
+    fn crear_lista(&self, nueva_lista: NuevaListaPrecios) -> ApiResult<Uuid> {
+        self.transaccion(|tablas| {
+            // Misma regla que idx_listas_precios_nombre
+            if tablas
+                .listas_precios
+                .iter()
+                .any(|lista| lista.activo && lista.nombre.to_lowercase() == nueva_lista.nombre.to_lowercase())
+            {
+                return Err(precio_repo::lista_duplicada(&nueva_lista.nombre));
+            }
+
+            let ahora = Utc::now().naive_utc();
+            let id = Uuid::new_v4();
+            tablas.listas_precios.push(ListaPrecios {
+                id,
+                nombre: nueva_lista.nombre,
+                descripcion: nueva_lista.descripcion,
+                fecha_creacion: ahora,
+                fecha_actualizacion: ahora,
+                activo: true,
+            });
+            Ok(id)
+        })
+    }
+
+    fn listar_precios(&self, id_lista_precios: Uuid) -> ApiResult<Vec<PrecioLista>> {
+        Ok(self
+            .tablas()
+            .precios_lista
+            .iter()
+            .filter(|precio| precio.id_lista_precios == id_lista_precios && precio.activo)
+            .cloned()
+            .collect())
+    }
+
+    fn precio_en_lista(&self, id_lista_precios: Uuid, id_producto: Uuid, id_unidad: Option<Uuid>) -> ApiResult<Option<PrecioLista>> {
+        let tablas = self.tablas();
+        if tablas.lista_precios(id_lista_precios).is_err() {
+            return Ok(None);
+        }
+        Ok(tablas
+            .precios_lista
+            .iter()
+            .find(|precio| {
+                precio.id_lista_precios == id_lista_precios
+                    && precio.id_producto == id_producto
+                    && precio.id_unidad == id_unidad
+                    && precio.activo
+            })
+            .cloned())
+    }
+
+    fn fijar_precio(&self, nuevo_precio: NuevoPrecioLista) -> ApiResult<PrecioLista> {
+        self.transaccion(|tablas| {
+            tablas.lista_precios(nuevo_precio.id_lista_precios)?;
+
+            let ahora = Utc::now().naive_utc();
+            if let Some(precio) = tablas.precios_lista.iter_mut().find(|precio| {
+                precio.id_lista_precios == nuevo_precio.id_lista_precios
+                    && precio.id_producto == nuevo_precio.id_producto
+                    && precio.id_unidad == nuevo_precio.id_unidad
+                    && precio.activo
+            }) {
+                precio.precio_unitario = nuevo_precio.precio_unitario;
+                precio.fecha_actualizacion = ahora;
+                return Ok(precio.clone());
+            }
+
+            let precio = PrecioLista {
+                id: Uuid::new_v4(),
+                id_lista_precios: nuevo_precio.id_lista_precios,
+                id_producto: nuevo_precio.id_producto,
+                precio_unitario: nuevo_precio.precio_unitario,
+                fecha_creacion: ahora,
+                fecha_actualizacion: ahora,
+                activo: true,
+                id_unidad: nuevo_precio.id_unidad,
+            };
+            tablas.precios_lista.push(precio.clone());
+            Ok(precio)
+        })
+    }
+
+    fn quitar_precio(&self, id_lista_precios: Uuid, id_producto: Uuid, id_unidad: Option<Uuid>) -> ApiResult<()> {
+        self.transaccion(|tablas| {
+            let precio = tablas
+                .precios_lista
+                .iter_mut()
+                .find(|precio| {
+                    precio.id_lista_precios == id_lista_precios
+                        && precio.id_producto == id_producto
+                        && precio.id_unidad == id_unidad
+                        && precio.activo
+                })
+                .ok_or_else(|| precio_repo::precio_no_encontrado(id_lista_precios, id_producto))?;
+            precio.activo = false;
+            precio.fecha_actualizacion = Utc::now().naive_utc();
+            Ok(())
+        })
+    }
+
+    fn buscar_promocion(&self, id: Uuid) -> ApiResult<Promocion> {
+        self.tablas()
+            .promociones
+            .iter()
+            .find(|promocion| promocion.id == id && promocion.activo)
+            .cloned()
+            .ok_or_else(|| precio_repo::promocion_no_encontrada(id))
+    }
+
+    fn listar_promociones(
+        &self,
+        id_producto: Option<Uuid>,
+        vigentes_en: Option<NaiveDateTime>,
+    ) -> ApiResult<Vec<Promocion>> {
+        let mut promociones: Vec<Promocion> = self
+            .tablas()
+            .promociones
+            .iter()
+            .filter(|promocion| promocion.activo)
+            .filter(|promocion| id_producto.is_none_or(|id| promocion.id_producto == id))
+            .filter(|promocion| vigentes_en.is_none_or(|fecha| reglas_precio::vigente(promocion, fecha)))
+            .cloned()
+            .collect();
+        promociones.sort_by_key(|promocion| (promocion.fecha_inicio, promocion.id));
+        Ok(promociones)
+    }
+
+    fn crear_promocion(&self, nueva_promocion: NuevaPromocion) -> ApiResult<Uuid> {
+        self.transaccion(|tablas| {
+            let ahora = Utc::now().naive_utc();
+            let id = Uuid::new_v4();
+            tablas.promociones.push(Promocion {
+                id,
+                nombre: nueva_promocion.nombre,
+                tipo: nueva_promocion.tipo,
+                id_producto: nueva_promocion.id_producto,
+                fecha_inicio: nueva_promocion.fecha_inicio,
+                fecha_fin: nueva_promocion.fecha_fin,
+                porcentaje: nueva_promocion.porcentaje,
+                cantidad_lleva: nueva_promocion.cantidad_lleva,
+                cantidad_paga: nueva_promocion.cantidad_paga,
+                precio_paquete: nueva_promocion.precio_paquete,
+                fecha_creacion: ahora,
+                fecha_actualizacion: ahora,
+                activo: true,
+            });
+            Ok(id)
+        })
+    }
+
+    fn desactivar_promocion(&self, id: Uuid) -> ApiResult<()> {
+        self.transaccion(|tablas| {
+            let promocion = tablas
+                .promociones
+                .iter_mut()
+                .find(|promocion| promocion.id == id && promocion.activo)
+                .ok_or_else(|| precio_repo::promocion_no_encontrada(id))?;
+            promocion.activo = false;
+            promocion.fecha_actualizacion = Utc::now().naive_utc();
+            Ok(())
+        })
+    }
+}
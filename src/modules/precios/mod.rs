@@ -0,0 +1,7 @@
+pub mod model;
+pub mod reglas;
+pub mod lineas;
+pub mod repository;
+pub mod service;
+pub mod handler;
+mod memoria;
@@ -0,0 +1,386 @@
+use bigdecimal::{BigDecimal, ToPrimitive};
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use utoipa::{IntoParams, ToSchema};
+use crate::modules::common::types::TipoPromocion;
+use crate::schema::{listas_precios, precios_lista, promociones};
+
+// Domain Model for ListaPrecios
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = listas_precios)]
+pub struct ListaPrecios {
+    pub id: Uuid,
+    pub nombre: String,
+    pub descripcion: Option<String>,
+    pub fecha_creacion: NaiveDateTime,
+    pub fecha_actualizacion: NaiveDateTime,
+    pub activo: bool,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = listas_precios)]
+pub struct NuevaListaPrecios {
+    pub nombre: String,
+    pub descripcion: Option<String>,
+}
+
+// Domain Model for the price of a product in a list
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = precios_lista)]
+pub struct PrecioLista {
+    pub id: Uuid,
+    pub id_lista_precios: Uuid,
+    pub id_producto: Uuid,
+    /// Precio de una unidad de `id_unidad`
+    pub precio_unitario: BigDecimal,
+    pub fecha_creacion: NaiveDateTime,
+    pub fecha_actualizacion: NaiveDateTime,
+    pub activo: bool,
+    /// Unidad de venta del precio; None para la unidad base
+    pub id_unidad: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = precios_lista)]
+pub struct NuevoPrecioLista {
+    pub id_lista_precios: Uuid,
+    pub id_producto: Uuid,
+    pub precio_unitario: BigDecimal,
+    pub id_unidad: Option<Uuid>,
+}
+
+// Domain Model for Promocion
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = promociones)]
+pub struct Promocion {
+    pub id: Uuid,
+    pub nombre: String,
+    pub tipo: TipoPromocion,
+    pub id_producto: Uuid,
+    /// Vigente desde esta fecha (incluida)
+    pub fecha_inicio: NaiveDateTime,
+    /// Vigente hasta esta fecha (excluida)
+    pub fecha_fin: NaiveDateTime,
+    /// Solo PORCENTAJE
+    pub porcentaje: Option<BigDecimal>,
+    /// LLEVA_PAGA y PRECIO_PAQUETE: unidades que forman un grupo
+    pub cantidad_lleva: Option<i32>,
+    /// Solo LLEVA_PAGA: unidades que se pagan de cada grupo
+    pub cantidad_paga: Option<i32>,
+    /// Solo PRECIO_PAQUETE: precio de cada grupo
+    pub precio_paquete: Option<BigDecimal>,
+    pub fecha_creacion: NaiveDateTime,
+    pub fecha_actualizacion: NaiveDateTime,
+    pub activo: bool,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = promociones)]
+pub struct NuevaPromocion {
+    pub nombre: String,
+    pub tipo: TipoPromocion,
+    pub id_producto: Uuid,
+    pub fecha_inicio: NaiveDateTime,
+    pub fecha_fin: NaiveDateTime,
+    pub porcentaje: Option<BigDecimal>,
+    pub cantidad_lleva: Option<i32>,
+    pub cantidad_paga: Option<i32>,
+    pub precio_paquete: Option<BigDecimal>,
+}
+
+// Computed price of a sale line
+#[derive(Debug, Clone)]
+pub struct PrecioLinea {
+    /// Precio de una unidad de la vendida, antes de promociones
+    pub precio_unitario: BigDecimal,
+    /// Precio unitario por cantidad
+    pub bruto: BigDecimal,
+    pub descuento: BigDecimal,
+    /// Bruto menos descuento: el monto de la línea
+    pub subtotal: BigDecimal,
+    /// Lista de la que salió el precio unitario; None si es el de catálogo
+    pub id_lista_precios: Option<Uuid>,
+    /// Promoción que fijó el descuento
+    pub id_promocion: Option<Uuid>,
+    /// Nombre de la promoción, para mostrarla; None también en los precios guardados en una
+    /// cotización o un pedido
+    pub promocion: Option<String>,
+}
+
+impl PrecioLinea {
+    /// Precio ya guardado en una línea de `cantidad` unidades, para venderla sin recalcularlo
+    pub fn fijado(
+        precio_unitario: &BigDecimal,
+        cantidad: i32,
+        descuento: &BigDecimal,
+        subtotal: &BigDecimal,
+        id_lista_precios: Option<Uuid>,
+        id_promocion: Option<Uuid>,
+    ) -> PrecioLinea {
+        PrecioLinea {
+            precio_unitario: precio_unitario.clone(),
+            bruto: precio_unitario * BigDecimal::from(cantidad),
+            descuento: descuento.clone(),
+            subtotal: subtotal.clone(),
+            id_lista_precios,
+            id_promocion,
+            promocion: None,
+        }
+    }
+}
+
+/// Regla que fijó el precio de una línea
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[schema(example = "PROMOCION")]
+pub enum OrigenPrecio {
+    /// Precio de catálogo del producto o de su unidad de venta
+    #[serde(rename = "CATALOGO")]
+    Catalogo,
+    /// Precio de la lista del cliente
+    #[serde(rename = "LISTA")]
+    Lista,
+    /// Promoción sobre el precio de catálogo o de lista
+    #[serde(rename = "PROMOCION")]
+    Promocion,
+}
+
+impl OrigenPrecio {
+    pub fn de(id_lista_precios: Option<Uuid>, id_promocion: Option<Uuid>) -> OrigenPrecio {
+        match (id_lista_precios, id_promocion) {
+            (_, Some(_)) => OrigenPrecio::Promocion,
+            (Some(_), None) => OrigenPrecio::Lista,
+            (None, None) => OrigenPrecio::Catalogo,
+        }
+    }
+}
+
+// DTO for price list creation request
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ListaPreciosRequest {
+    #[schema(example = "Mayorista")]
+    pub nombre: String,
+    #[schema(example = "Clientes con compras por volumen")]
+    pub descripcion: Option<String>,
+}
+
+// DTO for price list response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ListaPreciosResponse {
+    #[schema(example = "aa0e8400-e29b-41d4-a716-446655440000")]
+    pub id: String,
+    #[schema(example = "Mayorista")]
+    pub nombre: String,
+    #[schema(example = "Clientes con compras por volumen")]
+    pub descripcion: Option<String>,
+}
+
+impl From<ListaPrecios> for ListaPreciosResponse {
+    fn from(lista: ListaPrecios) -> Self {
+        ListaPreciosResponse {
+            id: lista.id.to_string(),
+            nombre: lista.nombre,
+            descripcion: lista.descripcion,
+        }
+    }
+}
+
+// DTO for setting the price of a product in a list
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PrecioListaRequest {
+    /// Precio de una unidad de `unidad`
+    #[schema(example = 1100000.0)]
+    pub precio_unitario: f64,
+    /// Unidad de venta del precio; por defecto la unidad base
+    #[schema(example = "Caja x12")]
+    pub unidad: Option<String>,
+}
+
+// Query parameters for removing the price of a product from a list
+#[derive(Debug, Deserialize, ToSchema, IntoParams)]
+pub struct PrecioListaQuery {
+    /// Unidad de venta del precio; por defecto la unidad base
+    #[schema(example = "Caja x12")]
+    pub unidad: Option<String>,
+}
+
+// DTO for the price of a product in a list
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PrecioListaResponse {
+    #[schema(example = "aa0e8400-e29b-41d4-a716-446655440000")]
+    pub id_lista_precios: String,
+    #[schema(example = "660e8400-e29b-41d4-a716-446655440000")]
+    pub id_producto: String,
+    #[schema(example = 1100000.0)]
+    pub precio_unitario: f64,
+    /// Unidad de venta del precio; null para la unidad base
+    #[schema(example = "ee0e8400-e29b-41d4-a716-446655440000")]
+    pub id_unidad: Option<String>,
+}
+
+impl From<PrecioLista> for PrecioListaResponse {
+    fn from(precio: PrecioLista) -> Self {
+        PrecioListaResponse {
+            id_lista_precios: precio.id_lista_precios.to_string(),
+            id_producto: precio.id_producto.to_string(),
+            precio_unitario: precio.precio_unitario.to_f64().unwrap_or(0.0),
+            id_unidad: precio.id_unidad.map(|id| id.to_string()),
+        }
+    }
+}
+
+// DTO for assigning a price list to a client
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ListaPreciosClienteRequest {
+    /// Se omite (o null) para volver a los precios de catálogo
+    #[schema(example = "aa0e8400-e29b-41d4-a716-446655440000")]
+    pub id_lista_precios: Option<String>,
+}
+
+// DTO for promotion creation request
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PromocionRequest {
+    #[schema(example = "3x2 en mouse")]
+    pub nombre: String,
+    /// PORCENTAJE, LLEVA_PAGA o PRECIO_PAQUETE
+    #[schema(example = "LLEVA_PAGA")]
+    pub tipo: String,
+    #[schema(example = "660e8400-e29b-41d4-a716-446655440000")]
+    pub id_producto: String,
+    /// Formato YYYY-MM-DD HH:MM:SS
+    #[schema(example = "2026-11-01 00:00:00")]
+    pub fecha_inicio: String,
+    /// Formato YYYY-MM-DD HH:MM:SS; la promoción deja de aplicar en esta fecha
+    #[schema(example = "2026-12-01 00:00:00")]
+    pub fecha_fin: String,
+    /// PORCENTAJE: descuento entre 0 y 100
+    #[schema(example = 10.0)]
+    pub porcentaje: Option<f64>,
+    /// LLEVA_PAGA y PRECIO_PAQUETE: unidades del grupo
+    #[schema(example = 3)]
+    pub cantidad_lleva: Option<i32>,
+    /// LLEVA_PAGA: unidades que se pagan de cada grupo
+    #[schema(example = 2)]
+    pub cantidad_paga: Option<i32>,
+    /// PRECIO_PAQUETE: precio de cada grupo
+    #[schema(example = 99000.0)]
+    pub precio_paquete: Option<f64>,
+}
+
+// DTO for promotion response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PromocionResponse {
+    #[schema(example = "bb0e8400-e29b-41d4-a716-446655440000")]
+    pub id: String,
+    #[schema(example = "3x2 en mouse")]
+    pub nombre: String,
+    pub tipo: TipoPromocion,
+    #[schema(example = "660e8400-e29b-41d4-a716-446655440000")]
+    pub id_producto: String,
+    #[schema(example = "2026-11-01 00:00:00")]
+    pub fecha_inicio: String,
+    #[schema(example = "2026-12-01 00:00:00")]
+    pub fecha_fin: String,
+    #[schema(example = json!(null))]
+    pub porcentaje: Option<f64>,
+    #[schema(example = 3)]
+    pub cantidad_lleva: Option<i32>,
+    #[schema(example = 2)]
+    pub cantidad_paga: Option<i32>,
+    #[schema(example = json!(null))]
+    pub precio_paquete: Option<f64>,
+}
+
+impl From<Promocion> for PromocionResponse {
+    fn from(promocion: Promocion) -> Self {
+        PromocionResponse {
+            id: promocion.id.to_string(),
+            nombre: promocion.nombre,
+            tipo: promocion.tipo,
+            id_producto: promocion.id_producto.to_string(),
+            fecha_inicio: promocion.fecha_inicio.format("%Y-%m-%d %H:%M:%S").to_string(),
+            fecha_fin: promocion.fecha_fin.format("%Y-%m-%d %H:%M:%S").to_string(),
+            porcentaje: promocion.porcentaje.map(|porcentaje| porcentaje.to_f64().unwrap_or(0.0)),
+            cantidad_lleva: promocion.cantidad_lleva,
+            cantidad_paga: promocion.cantidad_paga,
+            precio_paquete: promocion.precio_paquete.map(|precio| precio.to_f64().unwrap_or(0.0)),
+        }
+    }
+}
+
+// Query parameters for listing promotions
+#[derive(Debug, Deserialize, ToSchema, IntoParams)]
+pub struct PromocionesQuery {
+    #[schema(example = "660e8400-e29b-41d4-a716-446655440000")]
+    pub id_producto: Option<String>,
+    /// Solo las vigentes en este momento
+    #[schema(example = true)]
+    pub vigentes: Option<bool>,
+}
+
+// DTO for a price quote request
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CotizarPreciosRequest {
+    /// Cliente cuya lista de precios se aplica; sin cliente, precios de catálogo
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000")]
+    pub id_cliente: Option<String>,
+    pub detalles: Vec<LineaPrecioRequest>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct LineaPrecioRequest {
+    #[schema(example = "660e8400-e29b-41d4-a716-446655440000")]
+    pub id_producto: String,
+    /// Cantidad en la unidad indicada
+    #[schema(example = 3)]
+    pub cantidad: i32,
+    /// Nombre de la unidad de venta; si se omite, la unidad base del producto
+    #[schema(example = json!(null))]
+    pub unidad: Option<String>,
+}
+
+// DTO for a price quote
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CotizacionPreciosResponse {
+    #[schema(example = "aa0e8400-e29b-41d4-a716-446655440000")]
+    pub id_lista_precios: Option<String>,
+    /// Momento en que se evaluaron las promociones
+    #[schema(example = "2026-11-15 10:30:00")]
+    pub fecha: String,
+    #[schema(example = 150000.0)]
+    pub bruto: f64,
+    #[schema(example = 50000.0)]
+    pub descuento: f64,
+    #[schema(example = 100000.0)]
+    pub total: f64,
+    pub detalles: Vec<PrecioLineaResponse>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PrecioLineaResponse {
+    #[schema(example = "660e8400-e29b-41d4-a716-446655440000")]
+    pub id_producto: String,
+    #[schema(example = "Mouse inalámbrico")]
+    pub nombre_producto: String,
+    #[schema(example = "Unidad")]
+    pub unidad: String,
+    #[schema(example = 3)]
+    pub cantidad: i32,
+    /// Precio de una unidad antes de promociones
+    #[schema(example = 50000.0)]
+    pub precio_unitario: f64,
+    #[schema(example = 150000.0)]
+    pub bruto: f64,
+    #[schema(example = 50000.0)]
+    pub descuento: f64,
+    #[schema(example = 100000.0)]
+    pub subtotal: f64,
+    pub origen: OrigenPrecio,
+    #[schema(example = json!(null))]
+    pub id_lista_precios: Option<String>,
+    #[schema(example = "bb0e8400-e29b-41d4-a716-446655440000")]
+    pub id_promocion: Option<String>,
+    #[schema(example = "3x2 en mouse")]
+    pub promocion: Option<String>,
+}
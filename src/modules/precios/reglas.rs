@@ -0,0 +1,93 @@
+//! Precio de una línea de venta a partir de su precio unitario y las promociones del producto.
+//!
+//! Las promociones no se acumulan: de las vigentes se aplica la de mayor descuento y, a igual
+//! descuento, la que empezó antes (y después la de menor id), así que el resultado no depende
+//! del orden en que lleguen.
+
+use std::cmp::Reverse;
+use bigdecimal::{BigDecimal, Zero};
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+use crate::modules::common::types::TipoPromocion;
+use crate::modules::inventarios::costos;
+use crate::modules::precios::model::{PrecioLinea, Promocion};
+
+/// Activa y dentro de `[fecha_inicio, fecha_fin)`
+pub fn vigente(promocion: &Promocion, fecha: NaiveDateTime) -> bool {
+    promocion.activo && promocion.fecha_inicio <= fecha && fecha < promocion.fecha_fin
+}
+
+/// Descuento en centavos que la promoción hace sobre `cantidad` unidades a `precio_unitario`,
+/// sin superar el bruto de la línea. Las unidades que no completan un grupo van a precio lleno.
+pub fn descuento(promocion: &Promocion, precio_unitario: &BigDecimal, cantidad: i32) -> BigDecimal {
+    let bruto = precio_unitario * BigDecimal::from(cantidad);
+    let descuento = match promocion.tipo {
+        TipoPromocion::Porcentaje => match &promocion.porcentaje {
+            Some(porcentaje) => costos::redondear_importe(&(&bruto * porcentaje / BigDecimal::from(100))),
+            None => BigDecimal::zero(),
+        },
+        TipoPromocion::LlevaPaga => match (promocion.cantidad_lleva, promocion.cantidad_paga) {
+            (Some(lleva), Some(paga)) if lleva > 0 && paga < lleva => {
+                precio_unitario * BigDecimal::from((cantidad / lleva) * (lleva - paga))
+            }
+            _ => BigDecimal::zero(),
+        },
+        TipoPromocion::PrecioPaquete => match (promocion.cantidad_lleva, &promocion.precio_paquete) {
+            (Some(lleva), Some(precio_paquete)) if lleva > 0 => {
+                let ahorro = precio_unitario * BigDecimal::from(lleva) - precio_paquete;
+                if ahorro > BigDecimal::zero() {
+                    ahorro * BigDecimal::from(cantidad / lleva)
+                } else {
+                    BigDecimal::zero()
+                }
+            }
+            _ => BigDecimal::zero(),
+        },
+    };
+
+    descuento.max(BigDecimal::zero()).min(bruto)
+}
+
+/// Precio de `cantidad` unidades a `precio_unitario` con la mejor de `promociones` vigentes en
+/// `fecha`. Cada unidad vendida contiene `factor` unidades base: las promociones, que se definen
+/// sobre la unidad base, se evalúan con la cantidad en unidades base y su parte del precio.
+/// `id_lista_precios` es la lista de la que salió el precio unitario, si salió de una.
+pub fn precio_linea(
+    precio_unitario: BigDecimal,
+    cantidad: i32,
+    factor: i32,
+    id_lista_precios: Option<Uuid>,
+    promociones: &[Promocion],
+    fecha: NaiveDateTime,
+) -> PrecioLinea {
+    let bruto = &precio_unitario * BigDecimal::from(cantidad);
+    let precio_base = &precio_unitario / BigDecimal::from(factor);
+    let cantidad_base = cantidad * factor;
+    let mejor = promociones
+        .iter()
+        .filter(|promocion| vigente(promocion, fecha))
+        .map(|promocion| {
+            let descuento = costos::redondear_importe(&descuento(promocion, &precio_base, cantidad_base));
+            (promocion, descuento.min(bruto.clone()))
+        })
+        .filter(|(_, descuento)| *descuento > BigDecimal::zero())
+        .max_by(|(a, descuento_a), (b, descuento_b)| {
+            (descuento_a, Reverse(a.fecha_inicio), Reverse(a.id))
+                .cmp(&(descuento_b, Reverse(b.fecha_inicio), Reverse(b.id)))
+        });
+
+    let (descuento, promocion) = match mejor {
+        Some((promocion, descuento)) => (descuento, Some(promocion)),
+        None => (BigDecimal::zero(), None),
+    };
+
+    PrecioLinea {
+        subtotal: &bruto - &descuento,
+        precio_unitario,
+        bruto,
+        descuento,
+        id_lista_precios,
+        id_promocion: promocion.map(|promocion| promocion.id),
+        promocion: promocion.map(|promocion| promocion.nombre.clone()),
+    }
+}
@@ -0,0 +1,311 @@
+use chrono::NaiveDateTime;
+use diesel::dsl::exists;
+use diesel::prelude::*;
+use diesel::r2d2::{ConnectionManager, Pool, PooledConnection};
+use diesel::sql_types::Text;
+use uuid::Uuid;
+use tracing::instrument;
+use crate::modules::auditoria;
+use crate::modules::common::errors::{ApiError, ApiResult};
+use crate::modules::precios::model::{
+    ListaPrecios, NuevaListaPrecios, NuevaPromocion, NuevoPrecioLista, PrecioLista, Promocion,
+};
+use crate::schema::{listas_precios, precios_lista, promociones};
+
+pub type DbPool = Pool<ConnectionManager<PgConnection>>;
+pub type DbConnection = PooledConnection<ConnectionManager<PgConnection>>;
+
+define_sql_function!(fn lower(x: Text) -> Text);
+
+/// Acceso a datos de listas de precios y promociones (ver `PersonaRepository` para las
+/// implementaciones)
+pub trait PrecioRepository: Send + Sync {
+    fn buscar_lista(&self, id: Uuid) -> ApiResult<ListaPrecios>;
+
+    /// Listas activas por nombre
+    fn listar_listas(&self) -> ApiResult<Vec<ListaPrecios>>;
+
+    /// Rechaza la lista si ya hay otra activa con el mismo nombre (sin distinguir mayúsculas)
+    fn crear_lista(&self, nueva_lista: NuevaListaPrecios) -> ApiResult<Uuid>;
+
+    /// Precios activos de la lista
+    fn listar_precios(&self, id_lista_precios: Uuid) -> ApiResult<Vec<PrecioLista>>;
+
+    /// Precio del producto en la lista para la unidad `id_unidad` (None: la base); None si la
+    /// lista no lo incluye o ya no está activa
+    fn precio_en_lista(&self, id_lista_precios: Uuid, id_producto: Uuid, id_unidad: Option<Uuid>) -> ApiResult<Option<PrecioLista>>;
+
+    /// Fija el precio del producto y la unidad en la lista, reemplazando el que tuviera
+    fn fijar_precio(&self, nuevo_precio: NuevoPrecioLista) -> ApiResult<PrecioLista>;
+
+    fn quitar_precio(&self, id_lista_precios: Uuid, id_producto: Uuid, id_unidad: Option<Uuid>) -> ApiResult<()>;
+
+    fn buscar_promocion(&self, id: Uuid) -> ApiResult<Promocion>;
+
+    /// Promociones activas por fecha de inicio, opcionalmente de un producto y vigentes en una fecha
+    fn listar_promociones(
+        &self,
+        id_producto: Option<Uuid>,
+        vigentes_en: Option<NaiveDateTime>,
+    ) -> ApiResult<Vec<Promocion>>;
+
+    fn crear_promocion(&self, nueva_promocion: NuevaPromocion) -> ApiResult<Uuid>;
+
+    fn desactivar_promocion(&self, id: Uuid) -> ApiResult<()>;
+}
+
+pub struct PgPrecioRepository {
+    pool: DbPool,
+}
+
+impl PgPrecioRepository {
+    pub fn new(pool: DbPool) -> Self {
+        PgPrecioRepository { pool }
+    }
+
+    fn get_connection(&self) -> ApiResult<DbConnection> {
+        self.pool
+            .get()
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+    }
+}
+
+impl PrecioRepository for PgPrecioRepository {
+    #[instrument(skip(self))]
+    fn buscar_lista(&self, id: Uuid) -> ApiResult<ListaPrecios> {
+        let mut conn = self.get_connection()?;
+        buscar_lista_activa(&mut conn, id)
+    }
+
+    #[instrument(skip(self))]
+    fn listar_listas(&self) -> ApiResult<Vec<ListaPrecios>> {
+        let mut conn = self.get_connection()?;
+
+        listas_precios::table
+            .filter(listas_precios::activo.eq(true))
+            .order(listas_precios::nombre.asc())
+            .select(ListaPrecios::as_select())
+            .load(&mut conn)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+    }
+
+    #[instrument(skip(self))]
+    fn crear_lista(&self, nueva_lista: NuevaListaPrecios) -> ApiResult<Uuid> {
+        let mut conn = self.get_connection()?;
+
+        conn.transaction::<Uuid, ApiError, _>(|conn| {
+            auditoria::contexto::aplicar(conn)?;
+
+            // Misma regla que idx_listas_precios_nombre
+            let duplicada: bool = diesel::select(exists(
+                listas_precios::table
+                    .filter(listas_precios::activo.eq(true))
+                    .filter(lower(listas_precios::nombre).eq(nueva_lista.nombre.to_lowercase())),
+            ))
+            .get_result(conn)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+            if duplicada {
+                return Err(lista_duplicada(&nueva_lista.nombre));
+            }
+
+            diesel::insert_into(listas_precios::table)
+                .values(&nueva_lista)
+                .returning(listas_precios::id)
+                .get_result(conn)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))
+        })
+    }
+
+    #[instrument(skip(self))]
+    fn listar_precios(&self, id_lista_precios: Uuid) -> ApiResult<Vec<PrecioLista>> {
+        let mut conn = self.get_connection()?;
+
+        precios_lista::table
+            .filter(precios_lista::id_lista_precios.eq(id_lista_precios))
+            .filter(precios_lista::activo.eq(true))
+            .order(precios_lista::fecha_creacion.asc())
+            .select(PrecioLista::as_select())
+            .load(&mut conn)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+    }
+
+    #[instrument(skip(self))]
+    fn precio_en_lista(&self, id_lista_precios: Uuid, id_producto: Uuid, id_unidad: Option<Uuid>) -> ApiResult<Option<PrecioLista>> {
+        let mut conn = self.get_connection()?;
+
+        precios_lista::table
+            .inner_join(listas_precios::table)
+            .filter(listas_precios::activo.eq(true))
+            .filter(precios_lista::id_lista_precios.eq(id_lista_precios))
+            .filter(precios_lista::id_producto.eq(id_producto))
+            .filter(precios_lista::id_unidad.is_not_distinct_from(id_unidad))
+            .filter(precios_lista::activo.eq(true))
+            .select(PrecioLista::as_select())
+            .first(&mut conn)
+            .optional()
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+    }
+
+    #[instrument(skip(self))]
+    fn fijar_precio(&self, nuevo_precio: NuevoPrecioLista) -> ApiResult<PrecioLista> {
+        let mut conn = self.get_connection()?;
+
+        conn.transaction::<PrecioLista, ApiError, _>(|conn| {
+            auditoria::contexto::aplicar(conn)?;
+
+            buscar_lista_activa(conn, nuevo_precio.id_lista_precios)?;
+            let actualizado = diesel::update(
+                precios_lista::table
+                    .filter(precios_lista::id_lista_precios.eq(nuevo_precio.id_lista_precios))
+                    .filter(precios_lista::id_producto.eq(nuevo_precio.id_producto))
+                    .filter(precios_lista::id_unidad.is_not_distinct_from(nuevo_precio.id_unidad))
+                    .filter(precios_lista::activo.eq(true)),
+            )
+            .set(precios_lista::precio_unitario.eq(&nuevo_precio.precio_unitario))
+            .returning(PrecioLista::as_returning())
+            .get_result(conn)
+            .optional()
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+            if let Some(precio) = actualizado {
+                return Ok(precio);
+            }
+
+            diesel::insert_into(precios_lista::table)
+                .values(&nuevo_precio)
+                .returning(PrecioLista::as_returning())
+                .get_result(conn)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))
+        })
+    }
+
+    #[instrument(skip(self))]
+    fn quitar_precio(&self, id_lista_precios: Uuid, id_producto: Uuid, id_unidad: Option<Uuid>) -> ApiResult<()> {
+        let mut conn = self.get_connection()?;
+
+        conn.transaction::<(), ApiError, _>(|conn| {
+            auditoria::contexto::aplicar(conn)?;
+
+            let quitados = diesel::update(
+                precios_lista::table
+                    .filter(precios_lista::id_lista_precios.eq(id_lista_precios))
+                    .filter(precios_lista::id_producto.eq(id_producto))
+                    .filter(precios_lista::id_unidad.is_not_distinct_from(id_unidad))
+                    .filter(precios_lista::activo.eq(true)),
+            )
+            .set(precios_lista::activo.eq(false))
+            .execute(conn)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+            if quitados == 0 {
+                return Err(precio_no_encontrado(id_lista_precios, id_producto));
+            }
+            Ok(())
+        })
+    }
+
+    #[instrument(skip(self))]
+    fn buscar_promocion(&self, id: Uuid) -> ApiResult<Promocion> {
+        let mut conn = self.get_connection()?;
+        buscar_promocion_activa(&mut conn, id)
+    }
+
+    #[instrument(skip(self))]
+    fn listar_promociones(
+        &self,
+        id_producto: Option<Uuid>,
+        vigentes_en: Option<NaiveDateTime>,
+    ) -> ApiResult<Vec<Promocion>> {
+        let mut conn = self.get_connection()?;
+
+        let mut query = promociones::table
+            .filter(promociones::activo.eq(true))
+            .into_boxed();
+        if let Some(id_producto) = id_producto {
+            query = query.filter(promociones::id_producto.eq(id_producto));
+        }
+        if let Some(fecha) = vigentes_en {
+            query = query
+                .filter(promociones::fecha_inicio.le(fecha))
+                .filter(promociones::fecha_fin.gt(fecha));
+        }
+
+        query
+            .order((promociones::fecha_inicio.asc(), promociones::id.asc()))
+            .select(Promocion::as_select())
+            .load(&mut conn)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+    }
+
+    #[instrument(skip(self))]
+    fn crear_promocion(&self, nueva_promocion: NuevaPromocion) -> ApiResult<Uuid> {
+        let mut conn = self.get_connection()?;
+
+        conn.transaction::<Uuid, ApiError, _>(|conn| {
+            auditoria::contexto::aplicar(conn)?;
+
+            diesel::insert_into(promociones::table)
+                .values(&nueva_promocion)
+                .returning(promociones::id)
+                .get_result(conn)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))
+        })
+    }
+
+    #[instrument(skip(self))]
+    fn desactivar_promocion(&self, id: Uuid) -> ApiResult<()> {
+        let mut conn = self.get_connection()?;
+
+        conn.transaction::<(), ApiError, _>(|conn| {
+            auditoria::contexto::aplicar(conn)?;
+
+            buscar_promocion_activa(conn, id)?;
+            diesel::update(promociones::table.find(id))
+                .set(promociones::activo.eq(false))
+                .execute(conn)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+            Ok(())
+        })
+    }
+}
+
+fn buscar_lista_activa(conn: &mut PgConnection, id: Uuid) -> ApiResult<ListaPrecios> {
+    listas_precios::table
+        .find(id)
+        .filter(listas_precios::activo.eq(true))
+        .select(ListaPrecios::as_select())
+        .first(conn)
+        .map_err(|e| match e {
+            diesel::result::Error::NotFound => lista_no_encontrada(id),
+            _ => ApiError::DatabaseError(e.to_string()),
+        })
+}
+
+fn buscar_promocion_activa(conn: &mut PgConnection, id: Uuid) -> ApiResult<Promocion> {
+    promociones::table
+        .find(id)
+        .filter(promociones::activo.eq(true))
+        .select(Promocion::as_select())
+        .first(conn)
+        .map_err(|e| match e {
+            diesel::result::Error::NotFound => promocion_no_encontrada(id),
+            _ => ApiError::DatabaseError(e.to_string()),
+        })
+}
+
+pub(crate) fn lista_no_encontrada(id: Uuid) -> ApiError {
+    ApiError::NotFound(format!("Lista de precios {} no encontrada", id))
+}
+
+pub(crate) fn lista_duplicada(nombre: &str) -> ApiError {
+    ApiError::BusinessRuleViolation(format!("Ya existe una lista de precios llamada '{}'", nombre))
+}
+
+pub(crate) fn precio_no_encontrado(id_lista_precios: Uuid, id_producto: Uuid) -> ApiError {
+    ApiError::NotFound(format!(
+        "El producto {} no tiene precio en la lista {}",
+        id_producto, id_lista_precios
+    ))
+}
+
+pub(crate) fn promocion_no_encontrada(id: Uuid) -> ApiError {
+    ApiError::NotFound(format!("Promoción {} no encontrada", id))
+}
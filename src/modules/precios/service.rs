@@ -0,0 +1,326 @@
+use std::sync::Arc;
+use bigdecimal::{BigDecimal, ToPrimitive};
+use chrono::{NaiveDateTime, Utc};
+use uuid::Uuid;
+use tracing::instrument;
+use crate::modules::common::errors::{ApiError, ApiResult};
+use crate::modules::common::validacion::{importe, parse_id};
+use crate::modules::common::types::{TipoPerfil, TipoPromocion};
+use crate::modules::personas::model::PersonaResponse;
+use crate::modules::personas::repository::PersonaRepository;
+use crate::modules::precios::model::{
+    CotizacionPreciosResponse, CotizarPreciosRequest, ListaPreciosClienteRequest, ListaPreciosRequest,
+    ListaPreciosResponse, NuevaListaPrecios, NuevaPromocion, NuevoPrecioLista, OrigenPrecio, PrecioLinea,
+    PrecioLineaResponse, PrecioListaQuery, PrecioListaRequest, PrecioListaResponse, PromocionRequest, PromocionResponse,
+    PromocionesQuery,
+};
+use crate::modules::precios::lineas::{self, LineaConPrecio};
+use crate::modules::precios::reglas;
+use crate::modules::precios::repository::PrecioRepository;
+use crate::modules::productos::model::Producto;
+use crate::modules::productos::repository::ProductoRepository;
+use crate::modules::productos::unidades::UnidadVenta;
+
+pub struct PrecioService {
+    repository: Arc<dyn PrecioRepository>,
+    producto_repo: Arc<dyn ProductoRepository>,
+    persona_repo: Arc<dyn PersonaRepository>,
+}
+
+impl PrecioService {
+    pub fn new(
+        repository: Arc<dyn PrecioRepository>,
+        producto_repo: Arc<dyn ProductoRepository>,
+        persona_repo: Arc<dyn PersonaRepository>,
+    ) -> Self {
+        PrecioService { repository, producto_repo, persona_repo }
+    }
+
+    #[instrument(skip(self, request))]
+    pub fn crear_lista(&self, request: ListaPreciosRequest) -> ApiResult<ListaPreciosResponse> {
+        let nombre = request.nombre.trim();
+        if nombre.is_empty() {
+            return Err(ApiError::InvalidInput("El nombre de la lista es obligatorio".to_string()));
+        }
+        if nombre.chars().count() > 100 {
+            return Err(ApiError::InvalidInput("El nombre de la lista no puede superar 100 caracteres".to_string()));
+        }
+        let descripcion = request
+            .descripcion
+            .map(|descripcion| descripcion.trim().to_string())
+            .filter(|descripcion| !descripcion.is_empty());
+
+        let id = self.repository.crear_lista(NuevaListaPrecios { nombre: nombre.to_string(), descripcion })?;
+        Ok(self.repository.buscar_lista(id)?.into())
+    }
+
+    #[instrument(skip(self))]
+    pub fn listar_listas(&self) -> ApiResult<Vec<ListaPreciosResponse>> {
+        Ok(self.repository.listar_listas()?.into_iter().map(Into::into).collect())
+    }
+
+    #[instrument(skip(self))]
+    pub fn obtener_lista(&self, id_str: &str) -> ApiResult<ListaPreciosResponse> {
+        Ok(self.repository.buscar_lista(parse_id(id_str, "lista de precios")?)?.into())
+    }
+
+    /// Precios de los productos incluidos en la lista
+    #[instrument(skip(self))]
+    pub fn listar_precios(&self, id_str: &str) -> ApiResult<Vec<PrecioListaResponse>> {
+        let lista = self.repository.buscar_lista(parse_id(id_str, "lista de precios")?)?;
+        Ok(self.repository.listar_precios(lista.id)?.into_iter().map(Into::into).collect())
+    }
+
+    /// Fijar el precio de un producto en la lista, para su unidad base o para otra de sus
+    /// unidades de venta
+    #[instrument(skip(self, request))]
+    pub fn fijar_precio(
+        &self,
+        id_lista_str: &str,
+        id_producto_str: &str,
+        request: PrecioListaRequest,
+    ) -> ApiResult<PrecioListaResponse> {
+        let id_lista_precios = parse_id(id_lista_str, "lista de precios")?;
+        let id_producto = parse_id(id_producto_str, "producto")?;
+        let precio_unitario = importe(request.precio_unitario, "precio")?;
+        let id_unidad = self.unidad_de(id_producto, request.unidad.as_deref())?;
+
+        let precio = self.repository.fijar_precio(NuevoPrecioLista { id_lista_precios, id_producto, precio_unitario, id_unidad })?;
+        Ok(precio.into())
+    }
+
+    /// Quitar el precio de un producto (en una unidad) de la lista: vuelve a venderse a su precio
+    /// de catálogo, o al precio base de la lista por el factor de la unidad
+    #[instrument(skip(self))]
+    pub fn quitar_precio(&self, id_lista_str: &str, id_producto_str: &str, query: PrecioListaQuery) -> ApiResult<()> {
+        let id_lista_precios = parse_id(id_lista_str, "lista de precios")?;
+        let id_producto = parse_id(id_producto_str, "producto")?;
+        let id_unidad = match query.unidad.as_deref() {
+            Some(unidad) => self.unidad_de(id_producto, Some(unidad))?,
+            None => None,
+        };
+        self.repository.quitar_precio(id_lista_precios, id_producto, id_unidad)
+    }
+
+    /// Unidad de venta del producto con ese nombre; None para la unidad base
+    fn unidad_de(&self, id_producto: Uuid, nombre: Option<&str>) -> ApiResult<Option<Uuid>> {
+        let producto = self.producto_repo.buscar_por_id(id_producto)?;
+        let unidades = self.producto_repo.listar_unidades(producto.id)?;
+        Ok(UnidadVenta::resolver(&producto, &unidades, nombre)?.id)
+    }
+
+    /// Asignar o retirar (None) la lista de precios de un cliente
+    #[instrument(skip(self, request))]
+    pub fn asignar_lista_cliente(
+        &self,
+        id_str: &str,
+        request: ListaPreciosClienteRequest,
+    ) -> ApiResult<PersonaResponse> {
+        let id = parse_id(id_str, "persona")?;
+        let id_lista_precios = match request.id_lista_precios.as_deref().map(str::trim).filter(|id| !id.is_empty()) {
+            Some(id_lista) => Some(self.repository.buscar_lista(parse_id(id_lista, "lista de precios")?)?.id),
+            None => None,
+        };
+
+        let persona = self.persona_repo.buscar_por_id(id)?;
+        if persona.perfil != TipoPerfil::Cliente {
+            return Err(ApiError::BusinessRuleViolation(format!(
+                "La persona '{}' no tiene perfil CLIENTE",
+                persona.nombre
+            )));
+        }
+
+        Ok(self.persona_repo.asignar_lista_precios(id, id_lista_precios)?.into())
+    }
+
+    /// Crear una promoción sobre un producto; solo se guardan los campos de su tipo
+    #[instrument(skip(self, request))]
+    pub fn crear_promocion(&self, request: PromocionRequest) -> ApiResult<PromocionResponse> {
+        let nombre = request.nombre.trim();
+        if nombre.is_empty() {
+            return Err(ApiError::InvalidInput("El nombre de la promoción es obligatorio".to_string()));
+        }
+        if nombre.chars().count() > 100 {
+            return Err(ApiError::InvalidInput("El nombre de la promoción no puede superar 100 caracteres".to_string()));
+        }
+
+        let id_producto = parse_id(&request.id_producto, "producto")?;
+        self.producto_repo.buscar_por_id(id_producto)?;
+
+        let fecha_inicio = parse_fecha(&request.fecha_inicio)?;
+        let fecha_fin = parse_fecha(&request.fecha_fin)?;
+        if fecha_fin <= fecha_inicio {
+            return Err(ApiError::InvalidInput("La fecha de fin debe ser posterior a la de inicio".to_string()));
+        }
+
+        let mut nueva_promocion = NuevaPromocion {
+            nombre: nombre.to_string(),
+            tipo: TipoPromocion::Porcentaje,
+            id_producto,
+            fecha_inicio,
+            fecha_fin,
+            porcentaje: None,
+            cantidad_lleva: None,
+            cantidad_paga: None,
+            precio_paquete: None,
+        };
+        match request.tipo.to_uppercase().as_str() {
+            "PORCENTAJE" => {
+                let porcentaje = request
+                    .porcentaje
+                    .filter(|porcentaje| porcentaje.is_finite() && *porcentaje > 0.0 && *porcentaje <= 100.0)
+                    .ok_or_else(|| {
+                        ApiError::InvalidInput("El porcentaje debe ser mayor que 0 y hasta 100".to_string())
+                    })?;
+                nueva_promocion.porcentaje = Some(importe(porcentaje, "porcentaje")?);
+            }
+            "LLEVA_PAGA" => {
+                let (lleva, paga) = match (request.cantidad_lleva, request.cantidad_paga) {
+                    (Some(lleva), Some(paga)) if paga >= 1 && lleva > paga => (lleva, paga),
+                    _ => return Err(ApiError::InvalidInput(
+                        "Indique cantidad_lleva y cantidad_paga, con 1 <= cantidad_paga < cantidad_lleva".to_string()
+                    )),
+                };
+                nueva_promocion.tipo = TipoPromocion::LlevaPaga;
+                nueva_promocion.cantidad_lleva = Some(lleva);
+                nueva_promocion.cantidad_paga = Some(paga);
+            }
+            "PRECIO_PAQUETE" => {
+                let lleva = request.cantidad_lleva.filter(|lleva| *lleva >= 2).ok_or_else(|| {
+                    ApiError::InvalidInput("El paquete debe tener al menos 2 unidades (cantidad_lleva)".to_string())
+                })?;
+                let precio = request.precio_paquete.ok_or_else(|| {
+                    ApiError::InvalidInput("Indique el precio del paquete".to_string())
+                })?;
+                nueva_promocion.tipo = TipoPromocion::PrecioPaquete;
+                nueva_promocion.cantidad_lleva = Some(lleva);
+                nueva_promocion.precio_paquete = Some(importe(precio, "precio del paquete")?);
+            }
+            _ => return Err(ApiError::InvalidInput(
+                "Tipo de promoción inválido. Valores permitidos: PORCENTAJE, LLEVA_PAGA, PRECIO_PAQUETE".to_string()
+            )),
+        }
+
+        let id = self.repository.crear_promocion(nueva_promocion)?;
+        Ok(self.repository.buscar_promocion(id)?.into())
+    }
+
+    /// Promociones activas, por producto y solo las vigentes si se pide
+    #[instrument(skip(self))]
+    pub fn listar_promociones(&self, query: PromocionesQuery) -> ApiResult<Vec<PromocionResponse>> {
+        let id_producto = match query.id_producto.as_deref() {
+            Some(id) => Some(parse_id(id, "producto")?),
+            None => None,
+        };
+        let vigentes_en = query.vigentes.unwrap_or(false).then(|| Utc::now().naive_utc());
+
+        Ok(self
+            .repository
+            .listar_promociones(id_producto, vigentes_en)?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    /// Dar de baja una promoción; las ventas ya hechas conservan su descuento
+    #[instrument(skip(self))]
+    pub fn desactivar_promocion(&self, id_str: &str) -> ApiResult<()> {
+        self.repository.desactivar_promocion(parse_id(id_str, "promoción")?)
+    }
+
+    /// Precio que tendrían las líneas si se vendieran ahora al cliente, sin registrar la venta
+    /// ni comprobar el stock
+    #[instrument(skip(self, request))]
+    pub fn cotizar(&self, request: CotizarPreciosRequest) -> ApiResult<CotizacionPreciosResponse> {
+        let id_lista_precios = match request.id_cliente.as_deref() {
+            Some(id_cliente) => self.persona_repo.buscar_por_id(parse_id(id_cliente, "cliente")?)?.id_lista_precios,
+            None => None,
+        };
+        if request.detalles.is_empty() {
+            return Err(ApiError::InvalidInput("Indique al menos una línea".to_string()));
+        }
+
+        let fecha = Utc::now().naive_utc();
+        let mut bruto = BigDecimal::from(0);
+        let mut descuento = BigDecimal::from(0);
+        let mut detalles = Vec::with_capacity(request.detalles.len());
+        for linea in &request.detalles {
+            let LineaConPrecio { producto, unidad, cantidad, precio, .. } = lineas::linea_con_precio(
+                self.producto_repo.as_ref(),
+                self.repository.as_ref(),
+                id_lista_precios,
+                &linea.id_producto,
+                linea.unidad.as_deref(),
+                linea.cantidad,
+                None,
+                fecha,
+            )?;
+
+            bruto += &precio.bruto;
+            descuento += &precio.descuento;
+            detalles.push(PrecioLineaResponse {
+                id_producto: producto.id.to_string(),
+                nombre_producto: producto.nombre,
+                unidad: unidad.nombre,
+                cantidad,
+                precio_unitario: precio.precio_unitario.to_f64().unwrap_or(0.0),
+                bruto: precio.bruto.to_f64().unwrap_or(0.0),
+                descuento: precio.descuento.to_f64().unwrap_or(0.0),
+                subtotal: precio.subtotal.to_f64().unwrap_or(0.0),
+                origen: OrigenPrecio::de(precio.id_lista_precios, precio.id_promocion),
+                id_lista_precios: precio.id_lista_precios.map(|id| id.to_string()),
+                id_promocion: precio.id_promocion.map(|id| id.to_string()),
+                promocion: precio.promocion,
+            });
+        }
+
+        Ok(CotizacionPreciosResponse {
+            id_lista_precios: id_lista_precios.map(|id| id.to_string()),
+            fecha: fecha.format("%Y-%m-%d %H:%M:%S").to_string(),
+            total: (&bruto - &descuento).to_f64().unwrap_or(0.0),
+            bruto: bruto.to_f64().unwrap_or(0.0),
+            descuento: descuento.to_f64().unwrap_or(0.0),
+            detalles,
+        })
+    }
+}
+
+/// Precio de `cantidad` unidades de una línea en `fecha`, con la lista `id_lista_precios` del
+/// cliente. En una unidad distinta de la base (cajas, paquetes) la lista usa su precio para esa
+/// unidad o, si no lo tiene, su precio base por el factor; sin lista, el de catálogo de la
+/// unidad. Las promociones se evalúan sobre la cantidad en unidades base.
+pub(crate) fn precio_de_linea(
+    repository: &dyn PrecioRepository,
+    id_lista_precios: Option<Uuid>,
+    producto: &Producto,
+    unidad: &UnidadVenta,
+    cantidad: i32,
+    fecha: NaiveDateTime,
+) -> ApiResult<PrecioLinea> {
+    unidad.a_unidad_base(cantidad)?;
+
+    let mut precio_lista = None;
+    if let Some(id_lista_precios) = id_lista_precios {
+        precio_lista = repository
+            .precio_en_lista(id_lista_precios, producto.id, unidad.id)?
+            .map(|precio| precio.precio_unitario);
+        if precio_lista.is_none() && unidad.id.is_some() {
+            precio_lista = repository
+                .precio_en_lista(id_lista_precios, producto.id, None)?
+                .map(|precio| precio.precio_unitario * BigDecimal::from(unidad.factor));
+        }
+    }
+    let (precio_unitario, id_lista_precios) = match precio_lista {
+        Some(precio_unitario) => (precio_unitario, id_lista_precios),
+        None => (unidad.precio_unitario.clone(), None),
+    };
+    let promociones = repository.listar_promociones(Some(producto.id), Some(fecha))?;
+
+    Ok(reglas::precio_linea(precio_unitario, cantidad, unidad.factor, id_lista_precios, &promociones, fecha))
+}
+
+fn parse_fecha(valor: &str) -> ApiResult<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(valor.trim(), "%Y-%m-%d %H:%M:%S")
+        .map_err(|_| ApiError::InvalidInput("Formato de fecha inválido (YYYY-MM-DD HH:MM:SS)".to_string()))
+}
+
@@ -0,0 +1,70 @@
+//! Validación y normalización de códigos de barras EAN-8, UPC-A y EAN-13.
+//!
+//! Los tres son GTIN: se guardan completados con ceros a la izquierda hasta 14 dígitos, de modo
+//! que un UPC-A y el EAN-13 que lo representa (el mismo código con un 0 delante) coinciden.
+
+use crate::modules::common::errors::{ApiError, ApiResult};
+use crate::modules::common::types::TipoCodigoBarras;
+
+/// Longitud de un GTIN-14, la forma en que se guardan todos los códigos
+pub const LONGITUD_GTIN: usize = 14;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodigoBarras {
+    pub tipo: TipoCodigoBarras,
+    /// GTIN-14 normalizado
+    pub gtin: String,
+}
+
+impl CodigoBarras {
+    /// Valida el código leído (longitud y dígito de control) y lo normaliza.
+    /// Se ignoran los espacios y guiones que algunos lectores o etiquetas añaden.
+    pub fn parse(codigo: &str) -> ApiResult<CodigoBarras> {
+        let digitos: String = codigo.chars().filter(|c| !c.is_whitespace() && *c != '-').collect();
+
+        if digitos.is_empty() || !digitos.chars().all(|c| c.is_ascii_digit()) {
+            return Err(ApiError::InvalidInput(format!(
+                "Código de barras inválido '{}': solo puede contener dígitos",
+                codigo
+            )));
+        }
+
+        let tipo = TipoCodigoBarras::por_longitud(digitos.len()).ok_or_else(|| {
+            ApiError::InvalidInput(format!(
+                "Código de barras inválido '{}': debe tener 8 (EAN-8), 12 (UPC-A) o 13 (EAN-13) dígitos",
+                codigo
+            ))
+        })?;
+
+        let (cuerpo, control) = digitos.split_at(digitos.len() - 1);
+        let esperado = digito_control(cuerpo);
+        if control.as_bytes()[0] - b'0' != esperado {
+            return Err(ApiError::InvalidInput(format!(
+                "Código de barras inválido '{}': el dígito de control debería ser {}",
+                codigo, esperado
+            )));
+        }
+
+        Ok(CodigoBarras {
+            tipo,
+            gtin: format!("{:0>width$}", digitos, width = LONGITUD_GTIN),
+        })
+    }
+
+    /// Reconstruye el código impreso a partir del GTIN guardado
+    pub fn impreso(tipo: TipoCodigoBarras, gtin: &str) -> String {
+        gtin[gtin.len().saturating_sub(tipo.longitud())..].to_string()
+    }
+}
+
+/// Dígito de control GS1: desde la derecha, los dígitos pesan 3, 1, 3, 1...
+fn digito_control(cuerpo: &str) -> u8 {
+    let suma: u32 = cuerpo
+        .bytes()
+        .rev()
+        .enumerate()
+        .map(|(posicion, digito)| u32::from(digito - b'0') * if posicion % 2 == 0 { 3 } else { 1 })
+        .sum();
+
+    ((10 - suma % 10) % 10) as u8
+}
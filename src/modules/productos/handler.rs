@@ -1,5 +1,9 @@
 use actix_web::{web, HttpResponse, ResponseError, Result};
-use crate::modules::productos::model::{CrearProductoRequest, ProductoResponse, ProductoCreadoResponse};
+use crate::modules::productos::model::{
+    ActualizarCatalogoProductoRequest, CodigoBarrasRequest, CodigoBarrasResponse, CrearProductoRequest,
+    ProductoCreadoResponse, ProductoResponse, ProductosQueryParams, SerializadoRequest, UnidadProductoRequest,
+    UnidadProductoResponse,
+};
 use crate::modules::common::errors::ErrorResponse;
 use crate::state::app_state::AppState;
 
@@ -11,7 +15,8 @@ use crate::state::app_state::AppState;
     request_body = CrearProductoRequest,
     responses(
         (status = 201, description = "Producto creado exitosamente con inventario inicial", body = ProductoCreadoResponse),
-        (status = 400, description = "Datos de entrada inválidos", body = ErrorResponse),
+        (status = 400, description = "Datos de entrada inválidos, SKU o código de barras repetido", body = ErrorResponse),
+        (status = 404, description = "Categoría o marca no encontrada", body = ErrorResponse),
         (status = 500, description = "Error interno del servidor", body = ErrorResponse)
     )
 )]
@@ -59,27 +64,273 @@ pub async fn obtener_producto(
     get,
     path = "/v1/productos",
     tag = "Productos",
+    params(ProductosQueryParams),
     responses(
         (status = 200, description = "Lista de productos con stock disponible", body = Vec<ProductoResponse>),
+        (status = 400, description = "Filtros inválidos", body = ErrorResponse),
+        (status = 404, description = "Categoría o marca no encontrada", body = ErrorResponse),
         (status = 500, description = "Error interno del servidor", body = ErrorResponse)
     )
 )]
 pub async fn listar_productos(
     state: web::Data<AppState>,
+    query: web::Query<ProductosQueryParams>,
 ) -> Result<HttpResponse> {
     let service = &state.producto_service;
+    let query = query.into_inner();
 
-    match service.listar_productos() {
+    match service.listar_productos(query.id_categoria, query.id_marca, query.q) {
         Ok(productos) => Ok(HttpResponse::Ok().json(productos)),
         Err(e) => Ok(e.error_response()),
     }
 }
 
+/// GET /v1/productos/codigo/:codigo - Buscar producto por código de barras (venta por escáner)
+#[utoipa::path(
+    get,
+    path = "/v1/productos/codigo/{codigo}",
+    tag = "Productos",
+    params(
+        ("codigo" = String, Path, description = "Código EAN-8, UPC-A o EAN-13")
+    ),
+    responses(
+        (status = 200, description = "Producto al que pertenece el código", body = ProductoResponse),
+        (status = 400, description = "Código de barras inválido", body = ErrorResponse),
+        (status = 404, description = "Ningún producto tiene ese código", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn obtener_por_codigo_barras(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+) -> Result<HttpResponse> {
+    let codigo = path.into_inner();
+    let service = &state.producto_service;
+
+    match service.obtener_por_codigo_barras(&codigo) {
+        Ok(producto) => Ok(HttpResponse::Ok().json(producto)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// PUT /v1/productos/:id/catalogo - Cambiar SKU, categoría y marca
+#[utoipa::path(
+    put,
+    path = "/v1/productos/{id}/catalogo",
+    tag = "Productos",
+    params(
+        ("id" = String, Path, description = "ID del producto (UUID)")
+    ),
+    request_body = ActualizarCatalogoProductoRequest,
+    responses(
+        (status = 200, description = "Producto actualizado", body = ProductoResponse),
+        (status = 400, description = "Datos inválidos o SKU repetido", body = ErrorResponse),
+        (status = 404, description = "Producto, categoría o marca no encontrados", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn actualizar_catalogo(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<ActualizarCatalogoProductoRequest>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    let service = &state.producto_service;
+
+    match service.actualizar_catalogo(&id, body.into_inner()) {
+        Ok(producto) => Ok(HttpResponse::Ok().json(producto)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// POST /v1/productos/:id/codigos - Asignar un código de barras
+#[utoipa::path(
+    post,
+    path = "/v1/productos/{id}/codigos",
+    tag = "Productos",
+    params(
+        ("id" = String, Path, description = "ID del producto (UUID)")
+    ),
+    request_body = CodigoBarrasRequest,
+    responses(
+        (status = 201, description = "Código asignado", body = CodigoBarrasResponse),
+        (status = 400, description = "Código inválido o asignado a otro producto", body = ErrorResponse),
+        (status = 404, description = "Producto no encontrado", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn agregar_codigo_barras(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<CodigoBarrasRequest>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    let service = &state.producto_service;
+
+    match service.agregar_codigo_barras(&id, body.into_inner()) {
+        Ok(codigo) => Ok(HttpResponse::Created().json(codigo)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// DELETE /v1/productos/:id/codigos/:codigo - Quitar un código de barras
+#[utoipa::path(
+    delete,
+    path = "/v1/productos/{id}/codigos/{codigo}",
+    tag = "Productos",
+    params(
+        ("id" = String, Path, description = "ID del producto (UUID)"),
+        ("codigo" = String, Path, description = "Código EAN-8, UPC-A o EAN-13")
+    ),
+    responses(
+        (status = 204, description = "Código retirado"),
+        (status = 400, description = "Código inválido", body = ErrorResponse),
+        (status = 404, description = "El producto no tiene ese código", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn eliminar_codigo_barras(
+    state: web::Data<AppState>,
+    path: web::Path<(String, String)>,
+) -> Result<HttpResponse> {
+    let (id, codigo) = path.into_inner();
+    let service = &state.producto_service;
+
+    match service.eliminar_codigo_barras(&id, &codigo) {
+        Ok(()) => Ok(HttpResponse::NoContent().finish()),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// GET /v1/productos/:id/variantes - Listar las variantes de un producto
+#[utoipa::path(
+    get,
+    path = "/v1/productos/{id}/variantes",
+    tag = "Productos",
+    params(
+        ("id" = String, Path, description = "ID del producto padre (UUID)")
+    ),
+    responses(
+        (status = 200, description = "Variantes activas del producto", body = Vec<ProductoResponse>),
+        (status = 404, description = "Producto no encontrado", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn listar_variantes(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    let service = &state.producto_service;
+
+    match service.listar_variantes(&id) {
+        Ok(variantes) => Ok(HttpResponse::Ok().json(variantes)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// POST /v1/productos/:id/unidades - Agregar una unidad de venta
+#[utoipa::path(
+    post,
+    path = "/v1/productos/{id}/unidades",
+    tag = "Productos",
+    params(
+        ("id" = String, Path, description = "ID del producto (UUID)")
+    ),
+    request_body = UnidadProductoRequest,
+    responses(
+        (status = 201, description = "Unidad agregada", body = UnidadProductoResponse),
+        (status = 400, description = "Datos inválidos o unidad repetida", body = ErrorResponse),
+        (status = 404, description = "Producto no encontrado", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn agregar_unidad(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<UnidadProductoRequest>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    let service = &state.producto_service;
+
+    match service.agregar_unidad(&id, body.into_inner()) {
+        Ok(unidad) => Ok(HttpResponse::Created().json(unidad)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// DELETE /v1/productos/:id/unidades/:id_unidad - Retirar una unidad de venta
+#[utoipa::path(
+    delete,
+    path = "/v1/productos/{id}/unidades/{id_unidad}",
+    tag = "Productos",
+    params(
+        ("id" = String, Path, description = "ID del producto (UUID)"),
+        ("id_unidad" = String, Path, description = "ID de la unidad (UUID)")
+    ),
+    responses(
+        (status = 204, description = "Unidad retirada"),
+        (status = 400, description = "ID inválido", body = ErrorResponse),
+        (status = 404, description = "El producto no tiene esa unidad", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn eliminar_unidad(
+    state: web::Data<AppState>,
+    path: web::Path<(String, String)>,
+) -> Result<HttpResponse> {
+    let (id, id_unidad) = path.into_inner();
+    let service = &state.producto_service;
+
+    match service.eliminar_unidad(&id, &id_unidad) {
+        Ok(()) => Ok(HttpResponse::NoContent().finish()),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// PUT /v1/productos/:id/serializado - Activar o desactivar el seguimiento por número de serie
+#[utoipa::path(
+    put,
+    path = "/v1/productos/{id}/serializado",
+    tag = "Productos",
+    params(
+        ("id" = String, Path, description = "ID del producto (UUID)")
+    ),
+    request_body = SerializadoRequest,
+    responses(
+        (status = 200, description = "Producto actualizado", body = ProductoResponse),
+        (status = 400, description = "Números de serie inválidos, repetidos o que no cubren el stock, o producto con stock al desactivar", body = ErrorResponse),
+        (status = 404, description = "Producto no encontrado", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn cambiar_serializado(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<SerializadoRequest>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    let service = &state.producto_service;
+
+    match service.cambiar_serializado(&id, body.into_inner()) {
+        Ok(producto) => Ok(HttpResponse::Ok().json(producto)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
 pub fn configure(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/productos")
             .route("", web::post().to(crear_producto))
             .route("", web::get().to(listar_productos))
+            .route("/codigo/{codigo}", web::get().to(obtener_por_codigo_barras))
             .route("/{id}", web::get().to(obtener_producto))
+            .route("/{id}/catalogo", web::put().to(actualizar_catalogo))
+            .route("/{id}/codigos", web::post().to(agregar_codigo_barras))
+            .route("/{id}/codigos/{codigo}", web::delete().to(eliminar_codigo_barras))
+            .route("/{id}/variantes", web::get().to(listar_variantes))
+            .route("/{id}/unidades", web::post().to(agregar_unidad))
+            .route("/{id}/unidades/{id_unidad}", web::delete().to(eliminar_unidad))
+            .route("/{id}/serializado", web::put().to(cambiar_serializado))
     );
 }
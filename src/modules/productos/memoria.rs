@@ -0,0 +1,312 @@
+use bigdecimal::BigDecimal;
+use chrono::Utc;
+use uuid::Uuid;
+use crate::modules::common::errors::{ApiError, ApiResult};
+use crate::modules::common::memoria::{MemoriaRepository, Tablas};
+use crate::modules::inventarios::costos::MetodoValorizacion;
+use crate::modules::inventarios::model::Inventario;
+use crate::modules::inventarios::series;
+use crate::modules::inventarios::repository as inventario_repo;
+use crate::modules::productos::codigo_barras::CodigoBarras;
+use crate::modules::productos::model::{
+    CambiosCatalogoProducto, CodigoBarrasProducto, FiltroProductos, NuevaUnidadProducto, NuevoProducto, Producto,
+    UnidadProducto,
+};
+use crate::modules::productos::repository::{self as producto_repo, ProductoRepository};
+
+impl Tablas {
+    /// Misma regla que idx_productos_sku
+    fn validar_sku_libre(&self, sku: &str, excluir: Option<Uuid>) -> ApiResult<()> {
+        if self.productos.iter().any(|p| p.id != excluir.unwrap_or(Uuid::nil()) && p.sku.eq_ignore_ascii_case(sku)) {
+            return Err(producto_repo::sku_duplicado(sku));
+        }
+        Ok(())
+    }
+
+    fn insertar_codigo_barras(&mut self, id_producto: Uuid, codigo: CodigoBarras) -> ApiResult<CodigoBarrasProducto> {
+        if let Some(asignado) = self.codigos_barra.iter().find(|c| c.activo && c.codigo == codigo.gtin) {
+            return Err(producto_repo::codigo_barras_duplicado(&codigo, asignado.id_producto));
+        }
+
+        let ahora = Utc::now().naive_utc();
+        let codigo_barras = CodigoBarrasProducto {
+            id: Uuid::new_v4(),
+            id_producto,
+            codigo: codigo.gtin,
+            tipo: codigo.tipo,
+            fecha_creacion: ahora,
+            fecha_actualizacion: ahora,
+            activo: true,
+        };
+        self.codigos_barra.push(codigo_barras.clone());
+        Ok(codigo_barras)
+    }
+}
+
+impl ProductoRepository for MemoriaRepository {
+    fn buscar_por_id(&self, id: Uuid) -> ApiResult<Producto> {
+        self.tablas()
+            .productos
+            .iter()
+            .find(|producto| producto.id == id && producto.activo)
+            .cloned()
+            .ok_or(ApiError::ProductNotFound)
+    }
+
+    fn listar(&self, filtro: &FiltroProductos) -> ApiResult<Vec<Producto>> {
+        let mut productos: Vec<Producto> = self
+            .tablas()
+            .productos
+            .iter()
+            .filter(|producto| producto.activo)
+            .filter(|producto| {
+                filtro.categorias.as_ref().is_none_or(|categorias| {
+                    producto.id_categoria.is_some_and(|id| categorias.contains(&id))
+                })
+            })
+            .filter(|producto| filtro.id_marca.is_none_or(|id| producto.id_marca == Some(id)))
+            .filter(|producto| filtro.busqueda.as_ref().is_none_or(|busqueda| busqueda.coincide_con(&producto.nombre)))
+            .cloned()
+            .collect();
+        productos.sort_by(|a, b| a.nombre.cmp(&b.nombre));
+        Ok(productos)
+    }
+
+    fn verificar_existe_y_activo(&self, id: Uuid) -> ApiResult<bool> {
+        Ok(self.tablas().productos.iter().any(|p| p.id == id && p.activo))
+    }
+
+    fn crear_con_inventario(
+        &self,
+        nuevo_producto: NuevoProducto,
+        codigos: Vec<CodigoBarras>,
+        id_persona: Uuid,
+        costo_inicial: BigDecimal,
+    ) -> ApiResult<Uuid> {
+        self.transaccion(|tablas| {
+            if let Some(ref sku) = nuevo_producto.sku {
+                tablas.validar_sku_libre(sku, None)?;
+            }
+
+            if let Some(id_padre) = nuevo_producto.id_producto_padre {
+                if tablas.productos.iter().any(|p| {
+                    p.activo && p.id_producto_padre == Some(id_padre) && p.atributos == nuevo_producto.atributos
+                }) {
+                    return Err(producto_repo::variante_duplicada(id_padre));
+                }
+            }
+
+            let ahora = Utc::now().naive_utc();
+            let id_producto = Uuid::new_v4();
+            // Igual que el trigger asignar_sku_producto
+            let sku = nuevo_producto.sku.unwrap_or_else(|| {
+                format!("PM-{}", &id_producto.simple().to_string()[..12]).to_uppercase()
+            });
+
+            tablas.productos.push(Producto {
+                id: id_producto,
+                nombre: nuevo_producto.nombre,
+                cantidad: nuevo_producto.cantidad,
+                unidad_venta: nuevo_producto.unidad_venta,
+                precio_unitario: nuevo_producto.precio_unitario,
+                fecha_creacion: ahora,
+                fecha_actualizacion: ahora,
+                activo: true,
+                sku,
+                id_categoria: nuevo_producto.id_categoria,
+                id_marca: nuevo_producto.id_marca,
+                id_producto_padre: nuevo_producto.id_producto_padre,
+                atributos: nuevo_producto.atributos,
+                serializado: nuevo_producto.serializado,
+            });
+
+            for codigo in codigos {
+                tablas.insertar_codigo_barras(id_producto, codigo)?;
+            }
+
+            tablas.inventarios.push(Inventario {
+                id: Uuid::new_v4(),
+                id_producto,
+                id_persona,
+                cantidad_disponible: nuevo_producto.cantidad,
+                fecha_creacion: ahora,
+                fecha_actualizacion: ahora,
+                activo: true,
+                cantidad_reservada: 0,
+            });
+
+            if let Some(movimiento) =
+                inventario_repo::movimiento_inicial(id_producto, id_persona, nuevo_producto.cantidad, costo_inicial)
+            {
+                let costo_unitario = movimiento.costo_unitario.clone().unwrap_or_default();
+                let id = tablas.insertar_movimiento(movimiento);
+                tablas.registrar_capa(
+                    MetodoValorizacion::Fifo,
+                    id_producto,
+                    0,
+                    nuevo_producto.cantidad,
+                    &costo_unitario,
+                    id,
+                );
+            }
+
+            Ok(id_producto)
+        })
+    }
+
+    fn buscar_por_codigo_barras(&self, codigo: &CodigoBarras) -> ApiResult<Producto> {
+        let tablas = self.tablas();
+        tablas
+            .codigos_barra
+            .iter()
+            .filter(|c| c.activo && c.codigo == codigo.gtin)
+            .find_map(|c| tablas.productos.iter().find(|p| p.id == c.id_producto && p.activo))
+            .cloned()
+            .ok_or_else(|| producto_repo::codigo_barras_no_encontrado(codigo))
+    }
+
+    fn listar_codigos_barra(&self, id_producto: Uuid) -> ApiResult<Vec<CodigoBarrasProducto>> {
+        Ok(self
+            .tablas()
+            .codigos_barra
+            .iter()
+            .filter(|c| c.id_producto == id_producto && c.activo)
+            .cloned()
+            .collect())
+    }
+
+    fn agregar_codigo_barras(&self, id_producto: Uuid, codigo: CodigoBarras) -> ApiResult<CodigoBarrasProducto> {
+        self.transaccion(|tablas| tablas.insertar_codigo_barras(id_producto, codigo))
+    }
+
+    fn eliminar_codigo_barras(&self, id_producto: Uuid, codigo: &CodigoBarras) -> ApiResult<()> {
+        self.transaccion(|tablas| {
+            let codigo_barras = tablas
+                .codigos_barra
+                .iter_mut()
+                .find(|c| c.id_producto == id_producto && c.codigo == codigo.gtin && c.activo)
+                .ok_or_else(|| producto_repo::codigo_barras_no_encontrado(codigo))?;
+            codigo_barras.activo = false;
+            codigo_barras.fecha_actualizacion = Utc::now().naive_utc();
+            Ok(())
+        })
+    }
+
+    fn actualizar_catalogo(&self, id: Uuid, cambios: CambiosCatalogoProducto) -> ApiResult<Producto> {
+        self.transaccion(|tablas| {
+            tablas.validar_sku_libre(&cambios.sku, Some(id))?;
+
+            let producto = tablas
+                .productos
+                .iter_mut()
+                .find(|producto| producto.id == id && producto.activo)
+                .ok_or(ApiError::ProductNotFound)?;
+            producto.sku = cambios.sku;
+            producto.id_categoria = cambios.id_categoria;
+            producto.id_marca = cambios.id_marca;
+            producto.fecha_actualizacion = Utc::now().naive_utc();
+            Ok(producto.clone())
+        })
+    }
+
+    fn listar_variantes(&self, id_producto_padre: Uuid) -> ApiResult<Vec<Producto>> {
+        let mut variantes: Vec<Producto> = self
+            .tablas()
+            .productos
+            .iter()
+            .filter(|producto| producto.activo && producto.id_producto_padre == Some(id_producto_padre))
+            .cloned()
+            .collect();
+        variantes.sort_by(|a, b| a.nombre.cmp(&b.nombre));
+        Ok(variantes)
+    }
+
+    fn listar_unidades(&self, id_producto: Uuid) -> ApiResult<Vec<UnidadProducto>> {
+        let mut unidades: Vec<UnidadProducto> = self
+            .tablas()
+            .unidades_producto
+            .iter()
+            .filter(|unidad| unidad.activo && unidad.id_producto == id_producto)
+            .cloned()
+            .collect();
+        unidades.sort_by(|a, b| a.factor.cmp(&b.factor).then_with(|| a.nombre.cmp(&b.nombre)));
+        Ok(unidades)
+    }
+
+    fn buscar_unidad(&self, id: Uuid) -> ApiResult<UnidadProducto> {
+        self.tablas()
+            .unidades_producto
+            .iter()
+            .find(|unidad| unidad.id == id)
+            .cloned()
+            .ok_or_else(|| producto_repo::unidad_no_encontrada(id))
+    }
+
+    fn agregar_unidad(&self, nueva_unidad: NuevaUnidadProducto) -> ApiResult<UnidadProducto> {
+        self.transaccion(|tablas| {
+            if tablas.unidades_producto.iter().any(|u| {
+                u.activo
+                    && u.id_producto == nueva_unidad.id_producto
+                    && u.nombre.to_lowercase() == nueva_unidad.nombre.to_lowercase()
+            }) {
+                return Err(producto_repo::unidad_duplicada(&nueva_unidad.nombre));
+            }
+
+            let ahora = Utc::now().naive_utc();
+            let unidad = UnidadProducto {
+                id: Uuid::new_v4(),
+                id_producto: nueva_unidad.id_producto,
+                nombre: nueva_unidad.nombre,
+                factor: nueva_unidad.factor,
+                precio_unitario: nueva_unidad.precio_unitario,
+                fecha_creacion: ahora,
+                fecha_actualizacion: ahora,
+                activo: true,
+            };
+            tablas.unidades_producto.push(unidad.clone());
+            Ok(unidad)
+        })
+    }
+
+    fn eliminar_unidad(&self, id_producto: Uuid, id_unidad: Uuid) -> ApiResult<()> {
+        self.transaccion(|tablas| {
+            let unidad = tablas
+                .unidades_producto
+                .iter_mut()
+                .find(|u| u.id == id_unidad && u.id_producto == id_producto && u.activo)
+                .ok_or_else(|| producto_repo::unidad_no_encontrada(id_unidad))?;
+            unidad.activo = false;
+            unidad.fecha_actualizacion = Utc::now().naive_utc();
+            Ok(())
+        })
+    }
+
+    fn cambiar_serializado(&self, id: Uuid, serializado: bool, numeros: Vec<String>) -> ApiResult<Producto> {
+        self.transaccion(|tablas| {
+            let stock_actual = tablas.inventario_mut(id)?.cantidad_disponible;
+            series::validar_cambio_serializado(serializado, stock_actual, numeros.len())?;
+
+            if serializado {
+                if let Some(registrada) = tablas
+                    .numeros_serie
+                    .iter()
+                    .find(|serie| serie.id_producto == id && serie.activo && numeros.contains(&serie.numero))
+                {
+                    return Err(series::serie_duplicada(&registrada.numero));
+                }
+                for numero in numeros {
+                    tablas.insertar_serie(id, numero, None);
+                }
+            }
+
+            let producto = tablas
+                .productos
+                .iter_mut()
+                .find(|producto| producto.id == id && producto.activo)
+                .ok_or(ApiError::ProductNotFound)?;
+            producto.serializado = serializado;
+            producto.fecha_actualizacion = Utc::now().naive_utc();
+            Ok(producto.clone())
+        })
+    }
+}
@@ -1,4 +1,7 @@
 pub mod model;
+pub mod codigo_barras;
+pub mod unidades;
 pub mod repository;
 pub mod service;
 pub mod handler;
+mod memoria;
@@ -1,10 +1,13 @@
+use std::collections::BTreeMap;
 use bigdecimal::BigDecimal;
 use chrono::NaiveDateTime;
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
-use utoipa::ToSchema;
-use crate::schema::productos;
+use utoipa::{IntoParams, ToSchema};
+use crate::modules::common::busqueda::Busqueda;
+use crate::modules::common::types::TipoCodigoBarras;
+use crate::schema::{codigos_barra, productos, unidades_producto};
 
 // Domain Model (Database Entity)
 #[derive(Debug, Clone, Queryable, Identifiable, Selectable)]
@@ -18,6 +21,44 @@ pub struct Producto {
     pub fecha_creacion: NaiveDateTime,
     pub fecha_actualizacion: NaiveDateTime,
     pub activo: bool,
+    pub sku: String,
+    pub id_categoria: Option<Uuid>,
+    pub id_marca: Option<Uuid>,
+    pub id_producto_padre: Option<Uuid>,
+    /// Objeto JSON con los atributos de la variante ({"talla": "M"}); vacío si no es variante
+    pub atributos: serde_json::Value,
+    /// Cada unidad en stock tiene su número de serie (`numeros_serie`)
+    pub serializado: bool,
+}
+
+// Domain Model for UnidadProducto
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = unidades_producto)]
+pub struct UnidadProducto {
+    pub id: Uuid,
+    pub id_producto: Uuid,
+    pub nombre: String,
+    /// Unidades base (productos.unidad_venta) que contiene
+    pub factor: i32,
+    /// Si es None, precio del producto multiplicado por factor
+    pub precio_unitario: Option<BigDecimal>,
+    pub fecha_creacion: NaiveDateTime,
+    pub fecha_actualizacion: NaiveDateTime,
+    pub activo: bool,
+}
+
+// Domain Model for CodigoBarras
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = codigos_barra)]
+pub struct CodigoBarrasProducto {
+    pub id: Uuid,
+    pub id_producto: Uuid,
+    /// GTIN-14 normalizado (ver `codigo_barras`)
+    pub codigo: String,
+    pub tipo: TipoCodigoBarras,
+    pub fecha_creacion: NaiveDateTime,
+    pub fecha_actualizacion: NaiveDateTime,
+    pub activo: bool,
 }
 
 // DTO for API Response
@@ -33,6 +74,47 @@ pub struct ProductoResponse {
     pub unidad_venta: String,
     #[schema(example = 50)]
     pub stock_actual: i32,
+    #[schema(example = "LAP-DELL-I15")]
+    pub sku: String,
+    #[schema(example = "660e8400-e29b-41d4-a716-446655440000")]
+    pub id_categoria: Option<String>,
+    #[schema(example = "770e8400-e29b-41d4-a716-446655440000")]
+    pub id_marca: Option<String>,
+    pub codigos_barra: Vec<CodigoBarrasResponse>,
+    /// Producto del que es variante
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000")]
+    pub id_producto_padre: Option<String>,
+    #[schema(example = json!({"talla": "M", "color": "Rojo"}))]
+    pub atributos: BTreeMap<String, String>,
+    /// Unidades de venta además de la base (`unidad_venta`)
+    pub unidades: Vec<UnidadProductoResponse>,
+    /// Las entradas, salidas y ventas indican el número de serie de cada unidad
+    #[schema(example = true)]
+    pub serializado: bool,
+}
+
+// DTO for unit of measure response
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct UnidadProductoResponse {
+    #[schema(example = "880e8400-e29b-41d4-a716-446655440000")]
+    pub id: String,
+    #[schema(example = "Caja x12")]
+    pub nombre: String,
+    #[schema(example = 12)]
+    pub factor: i32,
+    /// Precio de una unidad de este tipo
+    #[schema(example = 110000.0)]
+    pub precio_unitario: f64,
+}
+
+// DTO for barcode response
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CodigoBarrasResponse {
+    /// Código tal como se imprime (8, 12 o 13 dígitos)
+    #[schema(example = "7702004003508")]
+    pub codigo: String,
+    #[schema(example = "EAN13")]
+    pub tipo: TipoCodigoBarras,
 }
 
 // DTO for creating a new Producto (database insert)
@@ -43,6 +125,42 @@ pub struct NuevoProducto {
     pub cantidad: i32,
     pub unidad_venta: String,
     pub precio_unitario: BigDecimal,
+    /// Si es None, trg_productos_sku asigna uno a partir del id
+    pub sku: Option<String>,
+    pub id_categoria: Option<Uuid>,
+    pub id_marca: Option<Uuid>,
+    pub id_producto_padre: Option<Uuid>,
+    pub atributos: serde_json::Value,
+    pub serializado: bool,
+}
+
+// DTO for creating a UnidadProducto (database insert)
+#[derive(Debug, Insertable)]
+#[diesel(table_name = unidades_producto)]
+pub struct NuevaUnidadProducto {
+    pub id_producto: Uuid,
+    pub nombre: String,
+    pub factor: i32,
+    pub precio_unitario: Option<BigDecimal>,
+}
+
+// DTO for changing a product's SKU, category and brand (database update)
+#[derive(Debug, AsChangeset)]
+#[diesel(table_name = productos, treat_none_as_null = true)]
+pub struct CambiosCatalogoProducto {
+    pub sku: String,
+    pub id_categoria: Option<Uuid>,
+    pub id_marca: Option<Uuid>,
+}
+
+// Filters for listing productos
+#[derive(Debug, Default)]
+pub struct FiltroProductos {
+    /// Categorías admitidas (la pedida y sus subcategorías)
+    pub categorias: Option<Vec<Uuid>>,
+    pub id_marca: Option<Uuid>,
+    /// Ordena además por relevancia
+    pub busqueda: Option<Busqueda>,
 }
 
 // DTO for producto creation request
@@ -56,6 +174,89 @@ pub struct CrearProductoRequest {
     pub unidad_venta: String,
     #[schema(example = 1200000.0)]
     pub precio_unitario: f64,
+    /// Único; si se omite se genera a partir del id (PM-XXXXXXXXXXXX)
+    #[schema(example = "LAP-DELL-I15")]
+    pub sku: Option<String>,
+    #[schema(example = "660e8400-e29b-41d4-a716-446655440000")]
+    pub id_categoria: Option<String>,
+    #[schema(example = "770e8400-e29b-41d4-a716-446655440000")]
+    pub id_marca: Option<String>,
+    /// Códigos EAN-8, UPC-A o EAN-13 con dígito de control válido
+    #[serde(default)]
+    #[schema(example = json!(["7702004003508"]))]
+    pub codigos_barra: Vec<String>,
+    /// Crea el producto como variante de este; sin categoría o marca, toma las del padre
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000")]
+    pub id_producto_padre: Option<String>,
+    /// Obligatorios en una variante y solo admitidos en ella
+    #[serde(default)]
+    #[schema(example = json!({}))]
+    pub atributos: BTreeMap<String, String>,
+    /// Seguir cada unidad por número de serie; exige crear el producto sin stock y registrarlo
+    /// con ENTRADAS que indiquen los números
+    #[serde(default)]
+    #[schema(example = false)]
+    pub serializado: bool,
+    /// Costo de compra por unidad del stock inicial (por defecto 0)
+    #[schema(example = 950000.0)]
+    pub costo_unitario: Option<f64>,
+}
+
+// DTO for adding a unit of measure to a producto
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UnidadProductoRequest {
+    #[schema(example = "Caja x12")]
+    pub nombre: String,
+    /// Unidades base que contiene
+    #[schema(example = 12)]
+    pub factor: i32,
+    /// Si se omite, precio del producto multiplicado por factor
+    #[schema(example = 110000.0)]
+    pub precio_unitario: Option<f64>,
+}
+
+// DTO for updating a product's SKU, category and brand
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ActualizarCatalogoProductoRequest {
+    #[schema(example = "LAP-DELL-I15")]
+    pub sku: String,
+    /// Se omite o es null para dejar el producto sin categoría
+    #[schema(example = "660e8400-e29b-41d4-a716-446655440000")]
+    pub id_categoria: Option<String>,
+    /// Se omite o es null para dejar el producto sin marca
+    #[schema(example = "770e8400-e29b-41d4-a716-446655440000")]
+    pub id_marca: Option<String>,
+}
+
+// DTO for turning serial number tracking on or off
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SerializadoRequest {
+    #[schema(example = true)]
+    pub serializado: bool,
+    /// Al activarlo, un número por unidad en stock
+    #[serde(default)]
+    #[schema(example = json!(["SN-5CD1234XYZ", "SN-5CD1234XZA"]))]
+    pub numeros_serie: Vec<String>,
+}
+
+// DTO for adding a barcode to a producto
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CodigoBarrasRequest {
+    #[schema(example = "7702004003508")]
+    pub codigo: String,
+}
+
+// Query parameters for filtering productos
+#[derive(Debug, Deserialize, ToSchema, IntoParams)]
+pub struct ProductosQueryParams {
+    /// Texto a buscar en el nombre, sin distinguir acentos y tolerando errores de escritura
+    #[schema(example = "teclado mec")]
+    pub q: Option<String>,
+    /// Incluye los productos de sus subcategorías
+    #[schema(example = "660e8400-e29b-41d4-a716-446655440000")]
+    pub id_categoria: Option<String>,
+    #[schema(example = "770e8400-e29b-41d4-a716-446655440000")]
+    pub id_marca: Option<String>,
 }
 
 // DTO for producto creation response
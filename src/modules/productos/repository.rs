@@ -1,20 +1,87 @@
+use bigdecimal::BigDecimal;
+use diesel::dsl::exists;
 use diesel::prelude::*;
 use diesel::r2d2::{ConnectionManager, Pool, PooledConnection};
+use diesel::sql_types::Text;
 use uuid::Uuid;
+use tracing::instrument;
+use crate::modules::auditoria;
 use crate::modules::common::errors::{ApiError, ApiResult};
-use crate::modules::productos::model::{Producto, NuevoProducto};
-use crate::schema::productos;
+use crate::modules::inventarios::repository as inventario_db;
+use crate::modules::inventarios::series;
+use crate::modules::productos::codigo_barras::CodigoBarras;
+use crate::modules::productos::model::{
+    CambiosCatalogoProducto, CodigoBarrasProducto, FiltroProductos, NuevaUnidadProducto, NuevoProducto, Producto,
+    UnidadProducto,
+};
+use crate::schema::{codigos_barra, productos, unidades_producto};
 
 pub type DbPool = Pool<ConnectionManager<PgConnection>>;
 pub type DbConnection = PooledConnection<ConnectionManager<PgConnection>>;
 
-pub struct ProductoRepository {
-    pub pool: DbPool,
+define_sql_function!(fn upper(x: Text) -> Text);
+define_sql_function!(fn lower(x: Text) -> Text);
+
+/// Acceso a datos de productos (ver `PersonaRepository` para las implementaciones)
+pub trait ProductoRepository: Send + Sync {
+    fn buscar_por_id(&self, id: Uuid) -> ApiResult<Producto>;
+
+    fn listar(&self, filtro: &FiltroProductos) -> ApiResult<Vec<Producto>>;
+
+    fn verificar_existe_y_activo(&self, id: Uuid) -> ApiResult<bool>;
+
+    /// Crea el producto, sus códigos de barras y su inventario inicial a cargo de `id_persona`,
+    /// en una sola transacción. Si la cantidad inicial es positiva registra además la ENTRADA,
+    /// con su capa de costo a `costo_inicial` por unidad.
+    /// Rechaza SKUs y códigos de barras ya asignados a otro producto, y variantes con los mismos
+    /// atributos que otra del mismo padre.
+    fn crear_con_inventario(
+        &self,
+        nuevo_producto: NuevoProducto,
+        codigos: Vec<CodigoBarras>,
+        id_persona: Uuid,
+        costo_inicial: BigDecimal,
+    ) -> ApiResult<Uuid>;
+
+    /// Producto activo al que está asignado el código de barras
+    fn buscar_por_codigo_barras(&self, codigo: &CodigoBarras) -> ApiResult<Producto>;
+
+    fn listar_codigos_barra(&self, id_producto: Uuid) -> ApiResult<Vec<CodigoBarrasProducto>>;
+
+    fn agregar_codigo_barras(&self, id_producto: Uuid, codigo: CodigoBarras) -> ApiResult<CodigoBarrasProducto>;
+
+    /// Da de baja el código; después puede asignarse a otro producto
+    fn eliminar_codigo_barras(&self, id_producto: Uuid, codigo: &CodigoBarras) -> ApiResult<()>;
+
+    /// Cambia SKU, categoría y marca. Rechaza un SKU que ya use otro producto.
+    fn actualizar_catalogo(&self, id: Uuid, cambios: CambiosCatalogoProducto) -> ApiResult<Producto>;
+
+    /// Variantes activas de `id_producto_padre`, ordenadas por nombre
+    fn listar_variantes(&self, id_producto_padre: Uuid) -> ApiResult<Vec<Producto>>;
+
+    /// Unidades activas del producto, de menor a mayor factor
+    fn listar_unidades(&self, id_producto: Uuid) -> ApiResult<Vec<UnidadProducto>>;
+
+    /// Unidad por ID aunque esté dada de baja (las ventas antiguas la siguen referenciando)
+    fn buscar_unidad(&self, id: Uuid) -> ApiResult<UnidadProducto>;
+
+    /// Rechaza el nombre si el producto ya tiene otra unidad activa con él
+    fn agregar_unidad(&self, nueva_unidad: NuevaUnidadProducto) -> ApiResult<UnidadProducto>;
+
+    fn eliminar_unidad(&self, id_producto: Uuid, id_unidad: Uuid) -> ApiResult<()>;
+
+    /// Activa o desactiva el seguimiento por número de serie. Al activarlo registra `numeros`
+    /// como las unidades en stock, que deben coincidir con él; solo se desactiva sin stock.
+    fn cambiar_serializado(&self, id: Uuid, serializado: bool, numeros: Vec<String>) -> ApiResult<Producto>;
+}
+
+pub struct PgProductoRepository {
+    pool: DbPool,
 }
 
-impl ProductoRepository {
+impl PgProductoRepository {
     pub fn new(pool: DbPool) -> Self {
-        ProductoRepository { pool }
+        PgProductoRepository { pool }
     }
 
     fn get_connection(&self) -> ApiResult<DbConnection> {
@@ -23,7 +90,34 @@ impl ProductoRepository {
             .map_err(|e| ApiError::DatabaseError(e.to_string()))
     }
 
-    pub fn buscar_por_id(&self, id: Uuid) -> ApiResult<Producto> {
+    /// Método usado internamente en transacciones
+    #[instrument(skip(self, conn, nuevo_producto))]
+    fn crear_con_conexion(&self, conn: &mut PgConnection, id: Uuid, nuevo_producto: &NuevoProducto) -> ApiResult<()> {
+        diesel::insert_into(productos::table)
+            .values((
+                productos::id.eq(id),
+                productos::nombre.eq(&nuevo_producto.nombre),
+                productos::cantidad.eq(&nuevo_producto.cantidad),
+                productos::unidad_venta.eq(&nuevo_producto.unidad_venta),
+                productos::precio_unitario.eq(&nuevo_producto.precio_unitario),
+                // Vacío: el trigger asignar_sku_producto genera uno a partir del id
+                productos::sku.eq(nuevo_producto.sku.as_deref().unwrap_or_default()),
+                productos::id_categoria.eq(&nuevo_producto.id_categoria),
+                productos::id_marca.eq(&nuevo_producto.id_marca),
+                productos::id_producto_padre.eq(&nuevo_producto.id_producto_padre),
+                productos::atributos.eq(&nuevo_producto.atributos),
+                productos::serializado.eq(nuevo_producto.serializado),
+            ))
+            .execute(conn)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+}
+
+impl ProductoRepository for PgProductoRepository {
+    #[instrument(skip(self))]
+    fn buscar_por_id(&self, id: Uuid) -> ApiResult<Producto> {
         let mut conn = self.get_connection()?;
 
         productos::table
@@ -37,18 +131,37 @@ impl ProductoRepository {
             })
     }
 
-    pub fn listar(&self) -> ApiResult<Vec<Producto>> {
+    #[instrument(skip(self))]
+    fn listar(&self, filtro: &FiltroProductos) -> ApiResult<Vec<Producto>> {
         let mut conn = self.get_connection()?;
 
-        productos::table
+        let mut query = productos::table
             .filter(productos::activo.eq(true))
-            .order(productos::nombre.asc())
+            .into_boxed();
+
+        if let Some(ref categorias) = filtro.categorias {
+            query = query.filter(productos::id_categoria.eq_any(categorias));
+        }
+
+        if let Some(id_marca) = filtro.id_marca {
+            query = query.filter(productos::id_marca.eq(id_marca));
+        }
+
+        query = match filtro.busqueda {
+            Some(ref busqueda) => query
+                .filter(busqueda.coincide("productos.nombre"))
+                .order((busqueda.relevancia("productos.nombre").desc(), productos::nombre.asc())),
+            None => query.order(productos::nombre.asc()),
+        };
+
+        query
             .select(Producto::as_select())
             .load(&mut conn)
             .map_err(|e| ApiError::DatabaseError(e.to_string()))
     }
 
-    pub fn verificar_existe_y_activo(&self, id: Uuid) -> ApiResult<bool> {
+    #[instrument(skip(self))]
+    fn verificar_existe_y_activo(&self, id: Uuid) -> ApiResult<bool> {
         let mut conn = self.get_connection()?;
 
         let count: i64 = productos::table
@@ -61,44 +174,337 @@ impl ProductoRepository {
         Ok(count > 0)
     }
 
-    pub fn crear(&self, nuevo_producto: NuevoProducto) -> ApiResult<Uuid> {
+    #[instrument(skip(self, nuevo_producto, codigos))]
+    fn crear_con_inventario(
+        &self,
+        nuevo_producto: NuevoProducto,
+        codigos: Vec<CodigoBarras>,
+        id_persona: Uuid,
+        costo_inicial: BigDecimal,
+    ) -> ApiResult<Uuid> {
         let mut conn = self.get_connection()?;
 
-        // Validar que el nombre no esté vacío
-        if nuevo_producto.nombre.trim().is_empty() {
-            return Err(ApiError::InvalidInput("El nombre del producto es requerido".to_string()));
-        }
+        conn.transaction::<Uuid, ApiError, _>(|conn| {
+            auditoria::contexto::aplicar(conn)?;
 
-        let id = Uuid::new_v4();
+            if let Some(ref sku) = nuevo_producto.sku {
+                validar_sku_libre(conn, sku, None)?;
+            }
 
-        // Insertar el nuevo producto con ID generado
-        diesel::insert_into(productos::table)
-            .values((
-                productos::id.eq(id),
-                productos::nombre.eq(&nuevo_producto.nombre),
-                productos::cantidad.eq(&nuevo_producto.cantidad),
-                productos::unidad_venta.eq(&nuevo_producto.unidad_venta),
-                productos::precio_unitario.eq(&nuevo_producto.precio_unitario),
-            ))
-            .execute(&mut conn)
+            if let Some(id_padre) = nuevo_producto.id_producto_padre {
+                let repetida: bool = diesel::select(exists(
+                    productos::table
+                        .filter(productos::id_producto_padre.eq(id_padre))
+                        .filter(productos::atributos.eq(&nuevo_producto.atributos))
+                        .filter(productos::activo.eq(true)),
+                ))
+                .get_result(conn)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+                if repetida {
+                    return Err(variante_duplicada(id_padre));
+                }
+            }
+
+            let id_producto = Uuid::new_v4();
+
+            self.crear_con_conexion(conn, id_producto, &nuevo_producto)?;
+
+            for codigo in codigos {
+                insertar_codigo_barras(conn, id_producto, codigo)?;
+            }
+
+            inventario_db::crear_inventario_inicial(
+                conn,
+                id_producto,
+                id_persona,
+                nuevo_producto.cantidad,
+                costo_inicial,
+            )?;
+
+            Ok(id_producto)
+        })
+    }
+
+    #[instrument(skip(self))]
+    fn buscar_por_codigo_barras(&self, codigo: &CodigoBarras) -> ApiResult<Producto> {
+        let mut conn = self.get_connection()?;
+
+        codigos_barra::table
+            .inner_join(productos::table)
+            .filter(codigos_barra::codigo.eq(&codigo.gtin))
+            .filter(codigos_barra::activo.eq(true))
+            .filter(productos::activo.eq(true))
+            .select(Producto::as_select())
+            .first(&mut conn)
+            .map_err(|e| match e {
+                diesel::result::Error::NotFound => codigo_barras_no_encontrado(codigo),
+                _ => ApiError::DatabaseError(e.to_string()),
+            })
+    }
+
+    #[instrument(skip(self))]
+    fn listar_codigos_barra(&self, id_producto: Uuid) -> ApiResult<Vec<CodigoBarrasProducto>> {
+        let mut conn = self.get_connection()?;
+
+        codigos_barra::table
+            .filter(codigos_barra::id_producto.eq(id_producto))
+            .filter(codigos_barra::activo.eq(true))
+            .order(codigos_barra::fecha_creacion.asc())
+            .select(CodigoBarrasProducto::as_select())
+            .load(&mut conn)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+    }
+
+    #[instrument(skip(self))]
+    fn agregar_codigo_barras(&self, id_producto: Uuid, codigo: CodigoBarras) -> ApiResult<CodigoBarrasProducto> {
+        let mut conn = self.get_connection()?;
+
+        conn.transaction::<CodigoBarrasProducto, ApiError, _>(|conn| {
+            auditoria::contexto::aplicar(conn)?;
+            insertar_codigo_barras(conn, id_producto, codigo)
+        })
+    }
+
+    #[instrument(skip(self))]
+    fn eliminar_codigo_barras(&self, id_producto: Uuid, codigo: &CodigoBarras) -> ApiResult<()> {
+        let mut conn = self.get_connection()?;
+
+        conn.transaction::<(), ApiError, _>(|conn| {
+            auditoria::contexto::aplicar(conn)?;
+
+            let eliminados = diesel::update(
+                codigos_barra::table
+                    .filter(codigos_barra::id_producto.eq(id_producto))
+                    .filter(codigos_barra::codigo.eq(&codigo.gtin))
+                    .filter(codigos_barra::activo.eq(true)),
+            )
+            .set(codigos_barra::activo.eq(false))
+            .execute(conn)
             .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
 
-        Ok(id)
+            if eliminados == 0 {
+                return Err(codigo_barras_no_encontrado(codigo));
+            }
+
+            Ok(())
+        })
     }
 
-    /// Método usado internamente en transacciones
-    pub fn crear_con_conexion(&self, conn: &mut PgConnection, id: Uuid, nuevo_producto: NuevoProducto) -> ApiResult<()> {
-        diesel::insert_into(productos::table)
-            .values((
-                productos::id.eq(id),
-                productos::nombre.eq(&nuevo_producto.nombre),
-                productos::cantidad.eq(&nuevo_producto.cantidad),
-                productos::unidad_venta.eq(&nuevo_producto.unidad_venta),
-                productos::precio_unitario.eq(&nuevo_producto.precio_unitario),
+    #[instrument(skip(self, cambios))]
+    fn actualizar_catalogo(&self, id: Uuid, cambios: CambiosCatalogoProducto) -> ApiResult<Producto> {
+        let mut conn = self.get_connection()?;
+
+        conn.transaction::<Producto, ApiError, _>(|conn| {
+            auditoria::contexto::aplicar(conn)?;
+
+            validar_sku_libre(conn, &cambios.sku, Some(id))?;
+
+            diesel::update(productos::table.find(id).filter(productos::activo.eq(true)))
+                .set(&cambios)
+                .returning(Producto::as_returning())
+                .get_result(conn)
+                .map_err(|e| match e {
+                    diesel::result::Error::NotFound => ApiError::ProductNotFound,
+                    _ => ApiError::DatabaseError(e.to_string()),
+                })
+        })
+    }
+    #[instrument(skip(self))]
+    fn listar_variantes(&self, id_producto_padre: Uuid) -> ApiResult<Vec<Producto>> {
+        let mut conn = self.get_connection()?;
+
+        productos::table
+            .filter(productos::id_producto_padre.eq(id_producto_padre))
+            .filter(productos::activo.eq(true))
+            .order(productos::nombre.asc())
+            .select(Producto::as_select())
+            .load(&mut conn)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+    }
+
+    #[instrument(skip(self))]
+    fn listar_unidades(&self, id_producto: Uuid) -> ApiResult<Vec<UnidadProducto>> {
+        let mut conn = self.get_connection()?;
+
+        unidades_producto::table
+            .filter(unidades_producto::id_producto.eq(id_producto))
+            .filter(unidades_producto::activo.eq(true))
+            .order((unidades_producto::factor.asc(), unidades_producto::nombre.asc()))
+            .select(UnidadProducto::as_select())
+            .load(&mut conn)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+    }
+
+    #[instrument(skip(self))]
+    fn buscar_unidad(&self, id: Uuid) -> ApiResult<UnidadProducto> {
+        let mut conn = self.get_connection()?;
+
+        unidades_producto::table
+            .find(id)
+            .select(UnidadProducto::as_select())
+            .first(&mut conn)
+            .map_err(|e| match e {
+                diesel::result::Error::NotFound => unidad_no_encontrada(id),
+                _ => ApiError::DatabaseError(e.to_string()),
+            })
+    }
+
+    #[instrument(skip(self, nueva_unidad))]
+    fn agregar_unidad(&self, nueva_unidad: NuevaUnidadProducto) -> ApiResult<UnidadProducto> {
+        let mut conn = self.get_connection()?;
+
+        conn.transaction::<UnidadProducto, ApiError, _>(|conn| {
+            auditoria::contexto::aplicar(conn)?;
+
+            let repetida: bool = diesel::select(exists(
+                unidades_producto::table
+                    .filter(unidades_producto::id_producto.eq(nueva_unidad.id_producto))
+                    .filter(lower(unidades_producto::nombre).eq(nueva_unidad.nombre.to_lowercase()))
+                    .filter(unidades_producto::activo.eq(true)),
             ))
+            .get_result(conn)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+            if repetida {
+                return Err(unidad_duplicada(&nueva_unidad.nombre));
+            }
+
+            diesel::insert_into(unidades_producto::table)
+                .values(&nueva_unidad)
+                .returning(UnidadProducto::as_returning())
+                .get_result(conn)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))
+        })
+    }
+
+    #[instrument(skip(self))]
+    fn eliminar_unidad(&self, id_producto: Uuid, id_unidad: Uuid) -> ApiResult<()> {
+        let mut conn = self.get_connection()?;
+
+        conn.transaction::<(), ApiError, _>(|conn| {
+            auditoria::contexto::aplicar(conn)?;
+
+            let eliminadas = diesel::update(
+                unidades_producto::table
+                    .find(id_unidad)
+                    .filter(unidades_producto::id_producto.eq(id_producto))
+                    .filter(unidades_producto::activo.eq(true)),
+            )
+            .set(unidades_producto::activo.eq(false))
             .execute(conn)
             .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
 
-        Ok(())
+            if eliminadas == 0 {
+                return Err(unidad_no_encontrada(id_unidad));
+            }
+
+            Ok(())
+        })
     }
+
+    #[instrument(skip(self, numeros))]
+    fn cambiar_serializado(&self, id: Uuid, serializado: bool, numeros: Vec<String>) -> ApiResult<Producto> {
+        let mut conn = self.get_connection()?;
+
+        conn.transaction::<Producto, ApiError, _>(|conn| {
+            auditoria::contexto::aplicar(conn)?;
+
+            // Con el inventario bloqueado ningún movimiento cambia el stock mientras tanto
+            let stock_actual = inventario_db::stock_bloqueado(conn, id)?;
+            series::validar_cambio_serializado(serializado, stock_actual, numeros.len())?;
+
+            if serializado {
+                inventario_db::registrar_series_existentes(conn, id, &numeros)?;
+            }
+
+            diesel::update(productos::table.find(id).filter(productos::activo.eq(true)))
+                .set(productos::serializado.eq(serializado))
+                .returning(Producto::as_returning())
+                .get_result(conn)
+                .map_err(|e| match e {
+                    diesel::result::Error::NotFound => ApiError::ProductNotFound,
+                    _ => ApiError::DatabaseError(e.to_string()),
+                })
+        })
+    }
+}
+
+/// Misma regla que idx_productos_sku: el SKU es único sin distinguir mayúsculas
+fn validar_sku_libre(conn: &mut PgConnection, sku: &str, excluir: Option<Uuid>) -> ApiResult<()> {
+    let ocupado: bool = diesel::select(exists(
+        productos::table
+            .filter(upper(productos::sku).eq(sku.to_uppercase()))
+            .filter(productos::id.ne(excluir.unwrap_or(Uuid::nil()))),
+    ))
+    .get_result(conn)
+    .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+    if ocupado {
+        return Err(sku_duplicado(sku));
+    }
+
+    Ok(())
+}
+
+fn insertar_codigo_barras(
+    conn: &mut PgConnection,
+    id_producto: Uuid,
+    codigo: CodigoBarras,
+) -> ApiResult<CodigoBarrasProducto> {
+    let asignado: Option<Uuid> = codigos_barra::table
+        .filter(codigos_barra::codigo.eq(&codigo.gtin))
+        .filter(codigos_barra::activo.eq(true))
+        .select(codigos_barra::id_producto)
+        .first(conn)
+        .optional()
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+    if let Some(id_asignado) = asignado {
+        return Err(codigo_barras_duplicado(&codigo, id_asignado));
+    }
+
+    diesel::insert_into(codigos_barra::table)
+        .values((
+            codigos_barra::id_producto.eq(id_producto),
+            codigos_barra::codigo.eq(&codigo.gtin),
+            codigos_barra::tipo.eq(codigo.tipo),
+        ))
+        .returning(CodigoBarrasProducto::as_returning())
+        .get_result(conn)
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))
+}
+
+pub(crate) fn sku_duplicado(sku: &str) -> ApiError {
+    ApiError::BusinessRuleViolation(format!("Ya existe un producto con el SKU {}", sku))
+}
+
+pub(crate) fn codigo_barras_duplicado(codigo: &CodigoBarras, id_producto: Uuid) -> ApiError {
+    ApiError::BusinessRuleViolation(format!(
+        "El código de barras {} ya está asignado al producto {}",
+        CodigoBarras::impreso(codigo.tipo, &codigo.gtin),
+        id_producto
+    ))
+}
+
+pub(crate) fn codigo_barras_no_encontrado(codigo: &CodigoBarras) -> ApiError {
+    ApiError::NotFound(format!(
+        "No hay ningún producto con el código de barras {}",
+        CodigoBarras::impreso(codigo.tipo, &codigo.gtin)
+    ))
+}
+
+pub(crate) fn variante_duplicada(id_producto_padre: Uuid) -> ApiError {
+    ApiError::BusinessRuleViolation(format!(
+        "El producto {} ya tiene una variante con esos atributos",
+        id_producto_padre
+    ))
+}
+
+pub(crate) fn unidad_duplicada(nombre: &str) -> ApiError {
+    ApiError::BusinessRuleViolation(format!("El producto ya tiene la unidad '{}'", nombre))
+}
+
+pub(crate) fn unidad_no_encontrada(id: Uuid) -> ApiError {
+    ApiError::NotFound(format!("Unidad {} no encontrada", id))
 }
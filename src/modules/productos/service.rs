@@ -1,38 +1,269 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
 use uuid::Uuid;
 use bigdecimal::{BigDecimal, ToPrimitive};
-use diesel::Connection;
+use tracing::instrument;
+use crate::metrics::metrics;
+use crate::modules::common::busqueda::Busqueda;
 use crate::modules::common::errors::{ApiError, ApiResult};
-use crate::modules::productos::model::{ProductoResponse, CrearProductoRequest, ProductoCreadoResponse, NuevoProducto};
+use crate::modules::common::types::{TipoMovimiento, TipoPerfil};
+use crate::modules::catalogo::repository::CatalogoRepository;
+use crate::modules::catalogo::service::descendientes;
+use crate::modules::productos::codigo_barras::CodigoBarras;
+use crate::modules::productos::model::{
+    ActualizarCatalogoProductoRequest, CambiosCatalogoProducto, CodigoBarrasRequest, CodigoBarrasResponse,
+    CrearProductoRequest, FiltroProductos, NuevaUnidadProducto, NuevoProducto, Producto, ProductoCreadoResponse,
+    ProductoResponse, SerializadoRequest, UnidadProductoRequest, UnidadProductoResponse,
+};
+use crate::modules::productos::unidades::UnidadVenta;
 use crate::modules::productos::repository::ProductoRepository;
 use crate::modules::inventarios::repository::InventarioRepository;
+use crate::modules::inventarios::{costos, series};
+use crate::modules::personas::model::FiltroPersonas;
 use crate::modules::personas::repository::PersonaRepository;
 
+/// Longitud máxima del SKU (productos.sku)
+const LONGITUD_MAXIMA_SKU: usize = 64;
+
+/// Longitud máxima del nombre de una unidad (unidades_producto.nombre)
+const LONGITUD_MAXIMA_UNIDAD: usize = 50;
+
 pub struct ProductoService {
-    producto_repo: ProductoRepository,
-    inventario_repo: InventarioRepository,
-    persona_repo: PersonaRepository,
+    producto_repo: Arc<dyn ProductoRepository>,
+    inventario_repo: Arc<dyn InventarioRepository>,
+    persona_repo: Arc<dyn PersonaRepository>,
+    catalogo_repo: Arc<dyn CatalogoRepository>,
 }
 
 impl ProductoService {
     pub fn new(
-        producto_repo: ProductoRepository,
-        inventario_repo: InventarioRepository,
-        persona_repo: PersonaRepository,
+        producto_repo: Arc<dyn ProductoRepository>,
+        inventario_repo: Arc<dyn InventarioRepository>,
+        persona_repo: Arc<dyn PersonaRepository>,
+        catalogo_repo: Arc<dyn CatalogoRepository>,
     ) -> Self {
         ProductoService {
             producto_repo,
             inventario_repo,
             persona_repo,
+            catalogo_repo,
         }
     }
 
     /// RF3: Obtener producto por ID con su stock actual
+    #[instrument(skip(self))]
     pub fn obtener_producto(&self, id_str: &str) -> ApiResult<ProductoResponse> {
-        let id = Uuid::parse_str(id_str)
-            .map_err(|_| ApiError::InvalidInput("ID de producto inválido".to_string()))?;
+        let id = parse_id_producto(id_str)?;
+        let producto = self.producto_repo.buscar_por_id(id)?;
+        self.respuesta(producto)
+    }
+
+    /// Obtener el producto al que pertenece un código de barras leído en caja
+    #[instrument(skip(self))]
+    pub fn obtener_por_codigo_barras(&self, codigo: &str) -> ApiResult<ProductoResponse> {
+        let codigo = CodigoBarras::parse(codigo)?;
+        let producto = self.producto_repo.buscar_por_codigo_barras(&codigo)?;
+        self.respuesta(producto)
+    }
+
+    /// Listar los productos activos con su stock, opcionalmente de una categoría
+    /// (incluidas sus subcategorías) y/o de una marca. Con `q` se ordenan por relevancia.
+    #[instrument(skip(self))]
+    pub fn listar_productos(
+        &self,
+        id_categoria: Option<String>,
+        id_marca: Option<String>,
+        q: Option<String>,
+    ) -> ApiResult<Vec<ProductoResponse>> {
+        let mut filtro = FiltroProductos {
+            busqueda: Busqueda::parse(q)?,
+            ..FiltroProductos::default()
+        };
+
+        if let Some(id_categoria) = id_categoria {
+            let id_categoria = Uuid::parse_str(&id_categoria)
+                .map_err(|_| ApiError::InvalidInput("ID de categoría inválido".to_string()))?;
+            self.catalogo_repo.buscar_categoria(id_categoria)?;
+            let categorias = self.catalogo_repo.listar_categorias()?;
+            filtro.categorias = Some(descendientes(&categorias, id_categoria));
+        }
+
+        if let Some(id_marca) = id_marca {
+            let id_marca = Uuid::parse_str(&id_marca)
+                .map_err(|_| ApiError::InvalidInput("ID de marca inválido".to_string()))?;
+            self.catalogo_repo.buscar_marca(id_marca)?;
+            filtro.id_marca = Some(id_marca);
+        }
+
+        let productos = self.producto_repo.listar(&filtro)?;
+
+        let mut respuestas = Vec::new();
+        for producto in productos {
+            respuestas.push(self.respuesta(producto)?);
+        }
+
+        Ok(respuestas)
+    }
+
+    /// Cambiar SKU, categoría y marca de un producto
+    #[instrument(skip(self, request))]
+    pub fn actualizar_catalogo(
+        &self,
+        id_str: &str,
+        request: ActualizarCatalogoProductoRequest,
+    ) -> ApiResult<ProductoResponse> {
+        let id = parse_id_producto(id_str)?;
+        self.producto_repo.buscar_por_id(id)?;
+
+        let cambios = CambiosCatalogoProducto {
+            sku: validar_sku(&request.sku)?,
+            id_categoria: self.validar_categoria(request.id_categoria)?,
+            id_marca: self.validar_marca(request.id_marca)?,
+        };
+
+        let producto = self.producto_repo.actualizar_catalogo(id, cambios)?;
+        self.respuesta(producto)
+    }
+
+    /// Asignar un código de barras a un producto
+    #[instrument(skip(self, request))]
+    pub fn agregar_codigo_barras(&self, id_str: &str, request: CodigoBarrasRequest) -> ApiResult<CodigoBarrasResponse> {
+        let id = parse_id_producto(id_str)?;
+        let codigo = CodigoBarras::parse(&request.codigo)?;
+        self.producto_repo.buscar_por_id(id)?;
+
+        let creado = self.producto_repo.agregar_codigo_barras(id, codigo)?;
+
+        Ok(CodigoBarrasResponse {
+            codigo: CodigoBarras::impreso(creado.tipo, &creado.codigo),
+            tipo: creado.tipo,
+        })
+    }
+
+    /// Quitar un código de barras de un producto
+    #[instrument(skip(self))]
+    pub fn eliminar_codigo_barras(&self, id_str: &str, codigo: &str) -> ApiResult<()> {
+        let id = parse_id_producto(id_str)?;
+        let codigo = CodigoBarras::parse(codigo)?;
+        self.producto_repo.buscar_por_id(id)?;
+
+        self.producto_repo.eliminar_codigo_barras(id, &codigo)
+    }
+
+    /// Listar las variantes activas de un producto
+    #[instrument(skip(self))]
+    pub fn listar_variantes(&self, id_str: &str) -> ApiResult<Vec<ProductoResponse>> {
+        let id = parse_id_producto(id_str)?;
+        self.producto_repo.buscar_por_id(id)?;
+
+        let mut respuestas = Vec::new();
+        for variante in self.producto_repo.listar_variantes(id)? {
+            respuestas.push(self.respuesta(variante)?);
+        }
+
+        Ok(respuestas)
+    }
+
+    /// Agregar una unidad de venta al producto (p.ej. "Caja x12" con factor 12)
+    #[instrument(skip(self, request))]
+    pub fn agregar_unidad(&self, id_str: &str, request: UnidadProductoRequest) -> ApiResult<UnidadProductoResponse> {
+        let id = parse_id_producto(id_str)?;
+        let producto = self.producto_repo.buscar_por_id(id)?;
+
+        let nombre = request.nombre.trim();
+        if nombre.is_empty() {
+            return Err(ApiError::InvalidInput("El nombre de la unidad es requerido".to_string()));
+        }
+        if nombre.chars().count() > LONGITUD_MAXIMA_UNIDAD {
+            return Err(ApiError::InvalidInput(format!(
+                "El nombre de la unidad no puede superar los {} caracteres",
+                LONGITUD_MAXIMA_UNIDAD
+            )));
+        }
+        if nombre.to_lowercase() == producto.unidad_venta.to_lowercase() {
+            return Err(ApiError::InvalidInput(format!(
+                "'{}' es la unidad base del producto",
+                producto.unidad_venta
+            )));
+        }
+        if request.factor <= 0 {
+            return Err(ApiError::InvalidInput("El factor de conversión debe ser mayor a 0".to_string()));
+        }
+
+        let precio_unitario = match request.precio_unitario {
+            Some(precio) if precio <= 0.0 => {
+                return Err(ApiError::InvalidInput("El precio unitario debe ser mayor a 0".to_string()));
+            }
+            Some(precio) => Some(
+                BigDecimal::try_from(precio)
+                    .map_err(|e| ApiError::InvalidInput(format!("Precio inválido: {}", e)))?,
+            ),
+            None => None,
+        };
+
+        let unidad = self.producto_repo.agregar_unidad(NuevaUnidadProducto {
+            id_producto: id,
+            nombre: nombre.to_string(),
+            factor: request.factor,
+            precio_unitario,
+        })?;
+
+        Ok(unidad_response(UnidadVenta::de(&producto, &unidad)))
+    }
+
+    /// Dar de baja una unidad de venta del producto
+    #[instrument(skip(self))]
+    pub fn eliminar_unidad(&self, id_str: &str, id_unidad_str: &str) -> ApiResult<()> {
+        let id = parse_id_producto(id_str)?;
+        let id_unidad = Uuid::parse_str(id_unidad_str)
+            .map_err(|_| ApiError::InvalidInput("ID de unidad inválido".to_string()))?;
+        self.producto_repo.buscar_por_id(id)?;
+
+        self.producto_repo.eliminar_unidad(id, id_unidad)
+    }
 
+    /// Activar o desactivar el seguimiento por número de serie de un producto
+    #[instrument(skip(self, request))]
+    pub fn cambiar_serializado(&self, id_str: &str, request: SerializadoRequest) -> ApiResult<ProductoResponse> {
+        let id = parse_id_producto(id_str)?;
         let producto = self.producto_repo.buscar_por_id(id)?;
-        let stock_actual = self.inventario_repo.obtener_stock(id)?;
+
+        let numeros = series::normalizar_numeros(&request.numeros_serie)?;
+        if !request.serializado && !numeros.is_empty() {
+            return Err(ApiError::InvalidInput(
+                "Los números de serie solo se indican al activar el seguimiento".to_string()
+            ));
+        }
+        if producto.serializado == request.serializado {
+            if numeros.is_empty() {
+                return self.respuesta(producto);
+            }
+            return Err(ApiError::BusinessRuleViolation(
+                "El producto ya se sigue por número de serie: registre las unidades nuevas con una ENTRADA".to_string()
+            ));
+        }
+
+        let producto = self.producto_repo.cambiar_serializado(id, request.serializado, numeros)?;
+        self.respuesta(producto)
+    }
+
+    fn respuesta(&self, producto: Producto) -> ApiResult<ProductoResponse> {
+        let stock_actual = self.inventario_repo.obtener_stock(producto.id)?;
+        let codigos_barra = self
+            .producto_repo
+            .listar_codigos_barra(producto.id)?
+            .into_iter()
+            .map(|codigo| CodigoBarrasResponse {
+                codigo: CodigoBarras::impreso(codigo.tipo, &codigo.codigo),
+                tipo: codigo.tipo,
+            })
+            .collect();
+        let unidades = self
+            .producto_repo
+            .listar_unidades(producto.id)?
+            .iter()
+            .map(|unidad| unidad_response(UnidadVenta::de(&producto, unidad)))
+            .collect();
 
         Ok(ProductoResponse {
             id: producto.id.to_string(),
@@ -40,35 +271,50 @@ impl ProductoService {
             precio_unitario: producto.precio_unitario.to_f64().unwrap_or(0.0),
             unidad_venta: producto.unidad_venta,
             stock_actual,
+            sku: producto.sku,
+            id_categoria: producto.id_categoria.map(|id| id.to_string()),
+            id_marca: producto.id_marca.map(|id| id.to_string()),
+            codigos_barra,
+            id_producto_padre: producto.id_producto_padre.map(|id| id.to_string()),
+            atributos: serde_json::from_value(producto.atributos).unwrap_or_default(),
+            unidades,
+            serializado: producto.serializado,
         })
     }
 
-    /// Listar todos los productos activos con su stock
-    pub fn listar_productos(&self) -> ApiResult<Vec<ProductoResponse>> {
-        let productos = self.producto_repo.listar()?;
-
-        let mut respuestas = Vec::new();
-        for producto in productos {
-            let stock_actual = self.inventario_repo.obtener_stock(producto.id)?;
-            respuestas.push(ProductoResponse {
-                id: producto.id.to_string(),
-                nombre: producto.nombre,
-                precio_unitario: producto.precio_unitario.to_f64().unwrap_or(0.0),
-                unidad_venta: producto.unidad_venta,
-                stock_actual,
-            });
-        }
+    fn validar_categoria(&self, id_categoria: Option<String>) -> ApiResult<Option<Uuid>> {
+        let Some(id_categoria) = id_categoria.filter(|id| !id.trim().is_empty()) else {
+            return Ok(None);
+        };
+        let id_categoria = Uuid::parse_str(id_categoria.trim())
+            .map_err(|_| ApiError::InvalidInput("ID de categoría inválido".to_string()))?;
+        self.catalogo_repo.buscar_categoria(id_categoria)?;
+        Ok(Some(id_categoria))
+    }
 
-        Ok(respuestas)
+    fn validar_marca(&self, id_marca: Option<String>) -> ApiResult<Option<Uuid>> {
+        let Some(id_marca) = id_marca.filter(|id| !id.trim().is_empty()) else {
+            return Ok(None);
+        };
+        let id_marca = Uuid::parse_str(id_marca.trim())
+            .map_err(|_| ApiError::InvalidInput("ID de marca inválido".to_string()))?;
+        self.catalogo_repo.buscar_marca(id_marca)?;
+        Ok(Some(id_marca))
     }
 
     /// Crear un nuevo producto con su inventario inicial
+    #[instrument(skip(self, request))]
     pub fn crear_producto(&self, request: CrearProductoRequest) -> ApiResult<ProductoCreadoResponse> {
         // Validar campos requeridos
         if request.nombre.trim().is_empty() {
             return Err(ApiError::InvalidInput("El nombre del producto es requerido".to_string()));
         }
 
+        // Misma regla que chk_nombre_producto, para responder 400 y no 500
+        if request.nombre.trim().chars().count() < 2 {
+            return Err(ApiError::InvalidInput("El nombre del producto debe tener al menos 2 caracteres".to_string()));
+        }
+
         if request.unidad_venta.trim().is_empty() {
             return Err(ApiError::InvalidInput("La unidad de venta es requerida".to_string()));
         }
@@ -78,49 +324,100 @@ impl ProductoService {
             return Err(ApiError::InvalidInput("La cantidad no puede ser negativa".to_string()));
         }
 
+        // Las unidades de un producto serializado entran con sus números de serie
+        if request.serializado && request.cantidad > 0 {
+            return Err(ApiError::InvalidInput(
+                "Un producto serializado se crea sin stock: registre las unidades con una ENTRADA y sus números de serie".to_string()
+            ));
+        }
+
         // Validar que el precio sea positivo
         if request.precio_unitario <= 0.0 {
             return Err(ApiError::InvalidInput("El precio unitario debe ser mayor a 0".to_string()));
         }
+        let costo_inicial = request.costo_unitario.map(costos::costo_desde_f64).transpose()?.unwrap_or_default();
+
+        let sku = request
+            .sku
+            .filter(|sku| !sku.trim().is_empty())
+            .map(|sku| validar_sku(&sku))
+            .transpose()?;
+        let mut id_categoria = self.validar_categoria(request.id_categoria)?;
+        let mut id_marca = self.validar_marca(request.id_marca)?;
+
+        let atributos = validar_atributos(request.atributos)?;
+        let id_producto_padre = match request.id_producto_padre.filter(|id| !id.trim().is_empty()) {
+            Some(id_padre) => {
+                let padre = self.producto_repo.buscar_por_id(parse_id_producto(id_padre.trim())?)?;
+                if padre.id_producto_padre.is_some() {
+                    return Err(ApiError::BusinessRuleViolation(
+                        "Una variante no puede tener variantes propias".to_string()
+                    ));
+                }
+                if atributos.is_empty() {
+                    return Err(ApiError::InvalidInput(
+                        "Una variante debe indicar al menos un atributo (p.ej. talla o color)".to_string()
+                    ));
+                }
+                id_categoria = id_categoria.or(padre.id_categoria);
+                id_marca = id_marca.or(padre.id_marca);
+                Some(padre.id)
+            }
+            None if !atributos.is_empty() => {
+                return Err(ApiError::InvalidInput(
+                    "Los atributos solo se admiten en variantes (id_producto_padre)".to_string()
+                ));
+            }
+            None => None,
+        };
+
+        let mut codigos: Vec<CodigoBarras> = Vec::with_capacity(request.codigos_barra.len());
+        for codigo in &request.codigos_barra {
+            let codigo = CodigoBarras::parse(codigo)?;
+            if codigos.contains(&codigo) {
+                return Err(ApiError::InvalidInput(format!(
+                    "El código de barras {} está repetido",
+                    CodigoBarras::impreso(codigo.tipo, &codigo.gtin)
+                )));
+            }
+            codigos.push(codigo);
+        }
 
         // Para el inventario inicial, necesitamos una persona responsable
         // Por ahora, usaremos el primer vendedor activo que encontremos
         // En un sistema real, esto vendría del contexto del usuario autenticado
-        let personas = self.persona_repo.listar(Some(crate::modules::common::types::TipoPerfil::Vendedor))?;
+        let personas = self.persona_repo.listar(&FiltroPersonas {
+            perfil: Some(TipoPerfil::Vendedor),
+            ..FiltroPersonas::default()
+        })?;
         let id_persona = personas.first()
             .ok_or_else(|| ApiError::BusinessRuleViolation(
                 "No hay vendedores registrados en el sistema. Debe crear al menos un vendedor primero".to_string()
             ))?
             .id;
 
-        // Crear el producto con transacción (producto + inventario inicial)
-        let mut conn = self.producto_repo.pool.get()
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        let nuevo_producto = NuevoProducto {
+            nombre: request.nombre.trim().to_string(),
+            cantidad: request.cantidad,
+            unidad_venta: request.unidad_venta.trim().to_string(),
+            precio_unitario: BigDecimal::try_from(request.precio_unitario)
+                .map_err(|e| ApiError::InvalidInput(format!("Precio inválido: {}", e)))?,
+            sku,
+            id_categoria,
+            id_marca,
+            id_producto_padre,
+            atributos: serde_json::to_value(atributos).unwrap_or_default(),
+            serializado: request.serializado,
+        };
 
-        let id_producto = conn.transaction::<Uuid, ApiError, _>(|conn| {
-            let id_producto = Uuid::new_v4();
+        // Crear el producto con transacción (producto + códigos de barras + inventario inicial)
+        let id_producto = self.producto_repo.crear_con_inventario(nuevo_producto, codigos, id_persona, costo_inicial)?;
 
-            // Crear el producto
-            let nuevo_producto = NuevoProducto {
-                nombre: request.nombre.trim().to_string(),
-                cantidad: request.cantidad,
-                unidad_venta: request.unidad_venta.trim().to_string(),
-                precio_unitario: BigDecimal::try_from(request.precio_unitario)
-                    .map_err(|e| ApiError::InvalidInput(format!("Precio inválido: {}", e)))?,
-            };
-
-            self.producto_repo.crear_con_conexion(conn, id_producto, nuevo_producto)?;
-
-            // Crear el inventario inicial
-            self.inventario_repo.crear_inventario_inicial(
-                conn,
-                id_producto,
-                id_persona,
-                request.cantidad
-            )?;
-
-            Ok(id_producto)
-        })?;
+        if request.cantidad > 0 {
+            metrics().movimientos_inventario
+                .with_label_values(&[TipoMovimiento::Entrada.as_str()])
+                .inc();
+        }
 
         Ok(ProductoCreadoResponse {
             id: id_producto.to_string(),
@@ -131,3 +428,57 @@ impl ProductoService {
         })
     }
 }
+
+fn unidad_response(unidad: UnidadVenta) -> UnidadProductoResponse {
+    UnidadProductoResponse {
+        id: unidad.id.map(|id| id.to_string()).unwrap_or_default(),
+        nombre: unidad.nombre,
+        factor: unidad.factor,
+        precio_unitario: unidad.precio_unitario.to_f64().unwrap_or(0.0),
+    }
+}
+
+fn parse_id_producto(id: &str) -> ApiResult<Uuid> {
+    Uuid::parse_str(id).map_err(|_| ApiError::InvalidInput("ID de producto inválido".to_string()))
+}
+
+/// Atributos de variante con nombre y valor sin espacios sobrantes y no vacíos
+fn validar_atributos(atributos: BTreeMap<String, String>) -> ApiResult<BTreeMap<String, String>> {
+    let mut validados = BTreeMap::new();
+
+    for (nombre, valor) in atributos {
+        let (nombre, valor) = (nombre.trim().to_lowercase(), valor.trim().to_string());
+        if nombre.is_empty() || valor.is_empty() {
+            return Err(ApiError::InvalidInput(
+                "Los atributos de la variante necesitan nombre y valor".to_string()
+            ));
+        }
+        if validados.insert(nombre.clone(), valor).is_some() {
+            return Err(ApiError::InvalidInput(format!("El atributo '{}' está repetido", nombre)));
+        }
+    }
+
+    Ok(validados)
+}
+
+/// SKU sin espacios de letras, dígitos, '-', '_' o '.', de hasta 64 caracteres
+fn validar_sku(sku: &str) -> ApiResult<String> {
+    let sku = sku.trim();
+
+    if sku.is_empty() {
+        return Err(ApiError::InvalidInput("El SKU es requerido".to_string()));
+    }
+    if sku.chars().count() > LONGITUD_MAXIMA_SKU {
+        return Err(ApiError::InvalidInput(format!(
+            "El SKU no puede superar los {} caracteres",
+            LONGITUD_MAXIMA_SKU
+        )));
+    }
+    if !sku.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')) {
+        return Err(ApiError::InvalidInput(
+            "El SKU solo puede contener letras, dígitos, '-', '_' y '.'".to_string()
+        ));
+    }
+
+    Ok(sku.to_string())
+}
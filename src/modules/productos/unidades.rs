@@ -0,0 +1,73 @@
+//! Conversión entre las unidades de venta de un producto y su unidad base.
+//!
+//! El inventario siempre se lleva en la unidad base (`productos.unidad_venta`); las demás
+//! unidades (`unidades_producto`) indican cuántas unidades base contienen.
+
+use bigdecimal::BigDecimal;
+use uuid::Uuid;
+use crate::modules::common::errors::{ApiError, ApiResult};
+use crate::modules::productos::model::{Producto, UnidadProducto};
+
+/// Unidad en la que se vende una línea, ya resuelta
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnidadVenta {
+    /// None para la unidad base
+    pub id: Option<Uuid>,
+    pub nombre: String,
+    pub factor: i32,
+    /// Precio de una unidad de este tipo
+    pub precio_unitario: BigDecimal,
+}
+
+impl UnidadVenta {
+    pub fn base(producto: &Producto) -> UnidadVenta {
+        UnidadVenta {
+            id: None,
+            nombre: producto.unidad_venta.clone(),
+            factor: 1,
+            precio_unitario: producto.precio_unitario.clone(),
+        }
+    }
+
+    pub fn de(producto: &Producto, unidad: &UnidadProducto) -> UnidadVenta {
+        UnidadVenta {
+            id: Some(unidad.id),
+            nombre: unidad.nombre.clone(),
+            factor: unidad.factor,
+            precio_unitario: unidad
+                .precio_unitario
+                .clone()
+                .unwrap_or_else(|| &producto.precio_unitario * BigDecimal::from(unidad.factor)),
+        }
+    }
+
+    /// Busca la unidad por nombre (sin distinguir mayúsculas) entre la base y las del producto.
+    /// Sin nombre, la base.
+    pub fn resolver(producto: &Producto, unidades: &[UnidadProducto], nombre: Option<&str>) -> ApiResult<UnidadVenta> {
+        let Some(nombre) = nombre.map(str::trim).filter(|nombre| !nombre.is_empty()) else {
+            return Ok(UnidadVenta::base(producto));
+        };
+
+        if producto.unidad_venta.to_lowercase() == nombre.to_lowercase() {
+            return Ok(UnidadVenta::base(producto));
+        }
+
+        unidades
+            .iter()
+            .find(|unidad| unidad.activo && unidad.nombre.to_lowercase() == nombre.to_lowercase())
+            .map(|unidad| UnidadVenta::de(producto, unidad))
+            .ok_or_else(|| {
+                ApiError::InvalidInput(format!(
+                    "El producto '{}' no se vende en la unidad '{}'",
+                    producto.nombre, nombre
+                ))
+            })
+    }
+
+    /// `cantidad` de esta unidad expresada en unidades base
+    pub fn a_unidad_base(&self, cantidad: i32) -> ApiResult<i32> {
+        cantidad.checked_mul(self.factor).ok_or_else(|| {
+            ApiError::InvalidInput(format!("La cantidad {} de '{}' es demasiado grande", cantidad, self.nombre))
+        })
+    }
+}
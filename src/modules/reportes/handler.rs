@@ -0,0 +1,65 @@
+use actix_web::{web, HttpResponse, ResponseError, Result};
+use crate::modules::common::errors::ErrorResponse;
+use crate::modules::reportes::model::{MargenesQuery, MargenesResponse, ValorizacionQuery, ValorizacionResponse};
+use crate::state::app_state::AppState;
+
+/// GET /v1/reportes/valorizacion - Valor del inventario a una fecha
+#[utoipa::path(
+    get,
+    path = "/v1/reportes/valorizacion",
+    tag = "Reportes",
+    params(ValorizacionQuery),
+    responses(
+        (status = 200, description = "Unidades y valor al costo del stock de cada producto a la fecha", body = ValorizacionResponse),
+        (status = 400, description = "Fecha inválida", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn valorizacion(
+    state: web::Data<AppState>,
+    query: web::Query<ValorizacionQuery>,
+) -> Result<HttpResponse> {
+    let service = &state.reporte_service;
+
+    match service.valorizacion(query.fecha.as_deref()) {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// GET /v1/reportes/margenes - Margen bruto por producto, vendedor, sucursal o periodo
+#[utoipa::path(
+    get,
+    path = "/v1/reportes/margenes",
+    tag = "Reportes",
+    params(MargenesQuery),
+    responses(
+        (status = 200, description = "Ingresos, costo de lo vendido y margen bruto de cada grupo", body = MargenesResponse),
+        (status = 400, description = "Agrupación, periodo o fechas inválidos", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn margenes(
+    state: web::Data<AppState>,
+    query: web::Query<MargenesQuery>,
+) -> Result<HttpResponse> {
+    let service = &state.reporte_service;
+
+    match service.margenes(
+        query.agrupar.as_deref(),
+        query.periodo.as_deref(),
+        query.fecha_desde.as_deref(),
+        query.fecha_hasta.as_deref(),
+    ) {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/reportes")
+            .route("/valorizacion", web::get().to(valorizacion))
+            .route("/margenes", web::get().to(margenes))
+    );
+}
@@ -0,0 +1,165 @@
+use bigdecimal::BigDecimal;
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Nullable, Numeric, Text, Uuid as SqlUuid};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use utoipa::{IntoParams, ToSchema};
+
+// Query result: units and value of a product's stock up to a date
+#[derive(Debug, Clone, QueryableByName)]
+pub struct ValorizacionProducto {
+    #[diesel(sql_type = SqlUuid)]
+    pub id_producto: Uuid,
+    #[diesel(sql_type = Text)]
+    pub nombre: String,
+    #[diesel(sql_type = BigInt)]
+    pub cantidad: i64,
+    /// Suma de los movimientos por su costo unitario, en centavos
+    #[diesel(sql_type = Numeric)]
+    pub valor: BigDecimal,
+}
+
+// Query parameters for the inventory valuation
+#[derive(Debug, Deserialize, ToSchema, IntoParams)]
+pub struct ValorizacionQuery {
+    /// Fecha de corte (YYYY-MM-DD), incluida; por defecto hoy
+    #[schema(example = "2026-10-31")]
+    pub fecha: Option<String>,
+}
+
+// DTO for the inventory valuation
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ValorizacionResponse {
+    #[schema(example = "2026-10-31")]
+    pub fecha: String,
+    /// Método con el que se costean las entradas y salidas (PROMEDIO o FIFO)
+    #[schema(example = "PROMEDIO")]
+    pub metodo_valorizacion: String,
+    #[schema(example = 1725.0)]
+    pub valor_total: f64,
+    /// Productos con stock a la fecha, por nombre
+    pub productos: Vec<ValorizacionProductoResponse>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ValorizacionProductoResponse {
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000")]
+    pub id_producto: String,
+    #[schema(example = "Laptop Dell Inspiron 15")]
+    pub nombre_producto: String,
+    /// Unidades en stock a la fecha según los movimientos
+    #[schema(example = 15)]
+    pub cantidad: i64,
+    #[schema(example = 115.0)]
+    pub costo_promedio: f64,
+    #[schema(example = 1725.0)]
+    pub valor: f64,
+}
+
+/// Criterio con que se agrupan las líneas de venta en el reporte de márgenes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AgrupacionMargen {
+    Producto,
+    Vendedor,
+    Sucursal,
+    Periodo(PeriodoMargen),
+}
+
+impl AgrupacionMargen {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AgrupacionMargen::Producto => "PRODUCTO",
+            AgrupacionMargen::Vendedor => "VENDEDOR",
+            AgrupacionMargen::Sucursal => "SUCURSAL",
+            AgrupacionMargen::Periodo(_) => "PERIODO",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeriodoMargen {
+    Dia,
+    Semana,
+    Mes,
+}
+
+impl PeriodoMargen {
+    /// Unidad de `date_trunc`; las semanas empiezan el lunes
+    pub fn unidad_sql(&self) -> &'static str {
+        match self {
+            PeriodoMargen::Dia => "day",
+            PeriodoMargen::Semana => "week",
+            PeriodoMargen::Mes => "month",
+        }
+    }
+}
+
+// Query result: revenue and cost of the sale lines of one group
+#[derive(Debug, Clone, QueryableByName)]
+pub struct MargenGrupo {
+    /// Id del producto o del vendedor, sucursal o inicio del periodo; None si la venta no lo tiene
+    #[diesel(sql_type = Nullable<Text>)]
+    pub clave: Option<String>,
+    #[diesel(sql_type = Text)]
+    pub nombre: String,
+    #[diesel(sql_type = BigInt)]
+    pub unidades: i64,
+    #[diesel(sql_type = Numeric)]
+    pub ingresos: BigDecimal,
+    #[diesel(sql_type = Numeric)]
+    pub costo: BigDecimal,
+}
+
+// Query parameters for the gross margin report
+#[derive(Debug, Deserialize, ToSchema, IntoParams)]
+pub struct MargenesQuery {
+    /// producto, vendedor, sucursal o periodo
+    #[schema(example = "producto")]
+    pub agrupar: Option<String>,
+    /// Con agrupar=periodo: dia, semana o mes (por defecto mes)
+    #[schema(example = "mes")]
+    pub periodo: Option<String>,
+    /// Primer día incluido (YYYY-MM-DD); por defecto el primero del mes en curso
+    #[schema(example = "2026-10-01")]
+    pub fecha_desde: Option<String>,
+    /// Último día incluido (YYYY-MM-DD); por defecto hoy
+    #[schema(example = "2026-10-31")]
+    pub fecha_hasta: Option<String>,
+}
+
+// DTO for the gross margin report
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MargenesResponse {
+    #[schema(example = "PRODUCTO")]
+    pub agrupacion: String,
+    #[schema(example = "2026-10-01")]
+    pub fecha_desde: String,
+    #[schema(example = "2026-10-31")]
+    pub fecha_hasta: String,
+    /// Todas las líneas vendidas en el rango
+    pub total: MargenResponse,
+    /// De mayor a menor margen; por periodo, en orden cronológico
+    pub grupos: Vec<MargenResponse>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MargenResponse {
+    /// Id del producto o vendedor, nombre de la sucursal o inicio del periodo (YYYY-MM-DD)
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000")]
+    pub clave: Option<String>,
+    #[schema(example = "Laptop Dell Inspiron 15")]
+    pub nombre: String,
+    /// Unidades base vendidas
+    #[schema(example = 12)]
+    pub unidades: i64,
+    #[schema(example = 14400000.0)]
+    pub ingresos: f64,
+    /// Costo de la mercancía vendida
+    #[schema(example = 11400000.0)]
+    pub costo: f64,
+    #[schema(example = 3000000.0)]
+    pub margen: f64,
+    /// Margen sobre los ingresos, en porcentaje; None sin ingresos
+    #[schema(example = 20.83)]
+    pub margen_porcentaje: Option<f64>,
+}
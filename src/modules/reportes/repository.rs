@@ -0,0 +1,119 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use diesel::r2d2::{ConnectionManager, Pool, PooledConnection};
+use diesel::sql_types::Timestamp;
+use tracing::instrument;
+use crate::modules::common::errors::{ApiError, ApiResult};
+use crate::modules::reportes::model::{AgrupacionMargen, MargenGrupo, ValorizacionProducto};
+
+pub type DbPool = Pool<ConnectionManager<PgConnection>>;
+pub type DbConnection = PooledConnection<ConnectionManager<PgConnection>>;
+
+/// Efecto de un movimiento `d` sobre el stock, con el signo normalizado igual que
+/// `TipoMovimiento::efecto_en_stock`
+const EFECTO_MOVIMIENTO: &str = "
+    CASE d.tipo_movimiento
+        WHEN 'ENTRADA' THEN ABS(d.cantidad)
+        WHEN 'SALIDA' THEN -ABS(d.cantidad)
+        ELSE d.cantidad
+    END";
+
+/// Consultas de solo lectura que agregan datos de varios módulos
+pub struct ReporteRepository {
+    pool: DbPool,
+}
+
+impl ReporteRepository {
+    pub fn new(pool: DbPool) -> Self {
+        ReporteRepository { pool }
+    }
+
+    fn get_connection(&self) -> ApiResult<DbConnection> {
+        self.pool
+            .get()
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+    }
+
+    /// Unidades y valor del stock de cada producto con los movimientos anteriores a `hasta`:
+    /// cada movimiento aporta su efecto en el stock por el costo unitario con que se registró
+    /// (0 si es anterior a los costos). Omite los productos sin unidades.
+    #[instrument(skip(self))]
+    pub fn valorizacion(&self, hasta: NaiveDateTime) -> ApiResult<Vec<ValorizacionProducto>> {
+        let mut conn = self.get_connection()?;
+
+        diesel::sql_query(format!(
+            "SELECT p.id AS id_producto, p.nombre,
+                    SUM(m.efecto)::BIGINT AS cantidad,
+                    ROUND(SUM(m.efecto * COALESCE(m.costo_unitario, 0)), 2) AS valor
+             FROM (
+                 SELECT d.id_producto, d.costo_unitario, ({}) AS efecto
+                 FROM detalle_inventarios d
+                 WHERE d.activo = TRUE AND d.fecha < $1
+             ) m
+             JOIN productos p ON p.id = m.id_producto
+             GROUP BY p.id, p.nombre
+             HAVING SUM(m.efecto) <> 0
+             ORDER BY p.nombre",
+            EFECTO_MOVIMIENTO
+        ))
+        .bind::<Timestamp, _>(hasta)
+        .load(&mut conn)
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))
+    }
+
+    /// Ingresos y costo de las líneas de las ventas no anuladas hechas en `[desde, hasta)`,
+    /// agrupados según `agrupacion`. Ordena de mayor a menor margen, salvo por periodo
+    /// (cronológico).
+    #[instrument(skip(self))]
+    pub fn margenes(
+        &self,
+        agrupacion: AgrupacionMargen,
+        desde: NaiveDateTime,
+        hasta: NaiveDateTime,
+    ) -> ApiResult<Vec<MargenGrupo>> {
+        let mut conn = self.get_connection()?;
+
+        // Solo se interpolan fragmentos fijos; las fechas van como parámetros
+        let (clave, nombre, join, orden) = match agrupacion {
+            AgrupacionMargen::Producto => (
+                "dv.id_producto::TEXT".to_string(),
+                "p.nombre".to_string(),
+                "JOIN productos p ON p.id = dv.id_producto",
+                "SUM(dv.monto) - SUM(dv.costo) DESC, 2",
+            ),
+            AgrupacionMargen::Vendedor => (
+                "v.id_vendedor::TEXT".to_string(),
+                "COALESCE(pe.nombre, 'Sin vendedor')".to_string(),
+                "LEFT JOIN personas pe ON pe.id = v.id_vendedor",
+                "SUM(dv.monto) - SUM(dv.costo) DESC, 2",
+            ),
+            AgrupacionMargen::Sucursal => (
+                "v.sucursal::TEXT".to_string(),
+                "COALESCE(v.sucursal, 'Sin sucursal')".to_string(),
+                "",
+                "SUM(dv.monto) - SUM(dv.costo) DESC, 2",
+            ),
+            AgrupacionMargen::Periodo(periodo) => {
+                let inicio = format!("TO_CHAR(DATE_TRUNC('{}', v.fecha), 'YYYY-MM-DD')", periodo.unidad_sql());
+                (inicio.clone(), inicio, "", "1")
+            }
+        };
+
+        diesel::sql_query(format!(
+            "SELECT {clave} AS clave, {nombre} AS nombre,
+                    SUM(dv.cantidad)::BIGINT AS unidades,
+                    SUM(dv.monto) AS ingresos,
+                    SUM(dv.costo) AS costo
+             FROM detalle_ventas dv
+             JOIN ventas v ON v.id = dv.id_venta
+             {join}
+             WHERE dv.activo = TRUE AND v.activo = TRUE AND v.estado <> 'ANULADA' AND v.fecha >= $1 AND v.fecha < $2
+             GROUP BY 1, 2
+             ORDER BY {orden}"
+        ))
+        .bind::<Timestamp, _>(desde)
+        .bind::<Timestamp, _>(hasta)
+        .load(&mut conn)
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))
+    }
+}
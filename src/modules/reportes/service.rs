@@ -0,0 +1,153 @@
+use bigdecimal::{BigDecimal, ToPrimitive, Zero};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Utc};
+use tracing::instrument;
+use crate::modules::common::errors::{ApiError, ApiResult};
+use crate::modules::inventarios::costos::{self, MetodoValorizacion};
+use crate::modules::reportes::model::{
+    AgrupacionMargen, MargenGrupo, MargenResponse, MargenesResponse, PeriodoMargen, ValorizacionProductoResponse,
+    ValorizacionResponse,
+};
+use crate::modules::reportes::repository::ReporteRepository;
+
+pub struct ReporteService {
+    repository: ReporteRepository,
+    metodo_valorizacion: MetodoValorizacion,
+}
+
+impl ReporteService {
+    pub fn new(repository: ReporteRepository, metodo_valorizacion: MetodoValorizacion) -> Self {
+        ReporteService { repository, metodo_valorizacion }
+    }
+
+    /// Valor del inventario al final del día `fecha` (por defecto hoy), producto a producto
+    #[instrument(skip(self))]
+    pub fn valorizacion(&self, fecha: Option<&str>) -> ApiResult<ValorizacionResponse> {
+        let fecha = parsear_fecha(fecha)?.unwrap_or_else(|| Utc::now().date_naive());
+
+        let mut valor_total = BigDecimal::zero();
+        let productos = self
+            .repository
+            .valorizacion(fin_del_dia(fecha))?
+            .into_iter()
+            .map(|producto| {
+                valor_total += &producto.valor;
+                let costo_promedio = costos::redondear_costo(&(&producto.valor / BigDecimal::from(producto.cantidad)));
+                ValorizacionProductoResponse {
+                    id_producto: producto.id_producto.to_string(),
+                    nombre_producto: producto.nombre,
+                    cantidad: producto.cantidad,
+                    costo_promedio: costo_promedio.to_f64().unwrap_or(0.0),
+                    valor: producto.valor.to_f64().unwrap_or(0.0),
+                }
+            })
+            .collect();
+
+        Ok(ValorizacionResponse {
+            fecha: fecha.format("%Y-%m-%d").to_string(),
+            metodo_valorizacion: self.metodo_valorizacion.as_str().to_string(),
+            valor_total: valor_total.to_f64().unwrap_or(0.0),
+            productos,
+        })
+    }
+
+    /// Ingresos, costo de lo vendido y margen bruto de las ventas entre `fecha_desde` (por
+    /// defecto el primer día del mes) y `fecha_hasta` (por defecto hoy), ambos incluidos
+    #[instrument(skip(self))]
+    pub fn margenes(
+        &self,
+        agrupar: Option<&str>,
+        periodo: Option<&str>,
+        fecha_desde: Option<&str>,
+        fecha_hasta: Option<&str>,
+    ) -> ApiResult<MargenesResponse> {
+        let agrupacion = parsear_agrupacion(agrupar, periodo)?;
+        let hoy = Utc::now().date_naive();
+        let hasta = parsear_fecha(fecha_hasta)?.unwrap_or(hoy);
+        let desde = parsear_fecha(fecha_desde)?.unwrap_or_else(|| hasta.with_day(1).unwrap_or(hasta));
+        if desde > hasta {
+            return Err(ApiError::InvalidInput("fecha_desde no puede ser posterior a fecha_hasta".to_string()));
+        }
+
+        let grupos = self.repository.margenes(
+            agrupacion,
+            desde.and_hms_opt(0, 0, 0).expect("la medianoche siempre es válida"),
+            fin_del_dia(hasta),
+        )?;
+
+        let mut total = MargenGrupo {
+            clave: None,
+            nombre: "Total".to_string(),
+            unidades: 0,
+            ingresos: BigDecimal::zero(),
+            costo: BigDecimal::zero(),
+        };
+        for grupo in &grupos {
+            total.unidades += grupo.unidades;
+            total.ingresos += &grupo.ingresos;
+            total.costo += &grupo.costo;
+        }
+
+        Ok(MargenesResponse {
+            agrupacion: agrupacion.as_str().to_string(),
+            fecha_desde: desde.format("%Y-%m-%d").to_string(),
+            fecha_hasta: hasta.format("%Y-%m-%d").to_string(),
+            total: margen_response(total),
+            grupos: grupos.into_iter().map(margen_response).collect(),
+        })
+    }
+}
+
+/// Fecha opcional en formato YYYY-MM-DD; vacía equivale a no indicarla
+fn parsear_fecha(fecha: Option<&str>) -> ApiResult<Option<NaiveDate>> {
+    fecha
+        .map(str::trim)
+        .filter(|fecha| !fecha.is_empty())
+        .map(|fecha| {
+            NaiveDate::parse_from_str(fecha, "%Y-%m-%d")
+                .map_err(|_| ApiError::InvalidInput("Formato de fecha inválido (YYYY-MM-DD)".to_string()))
+        })
+        .transpose()
+}
+
+/// Primer instante del día siguiente, para filtrar con `<` e incluir todo `fecha`
+fn fin_del_dia(fecha: NaiveDate) -> NaiveDateTime {
+    (fecha + Duration::days(1))
+        .and_hms_opt(0, 0, 0)
+        .expect("la medianoche siempre es válida")
+}
+
+fn parsear_agrupacion(agrupar: Option<&str>, periodo: Option<&str>) -> ApiResult<AgrupacionMargen> {
+    let periodo = match periodo.map(|p| p.trim().to_lowercase()).as_deref() {
+        None | Some("") | Some("mes") => PeriodoMargen::Mes,
+        Some("dia") | Some("día") => PeriodoMargen::Dia,
+        Some("semana") => PeriodoMargen::Semana,
+        Some(_) => return Err(ApiError::InvalidInput("Periodo inválido. Valores permitidos: dia, semana, mes".to_string())),
+    };
+
+    match agrupar.map(|a| a.trim().to_lowercase()).as_deref() {
+        None | Some("") | Some("producto") => Ok(AgrupacionMargen::Producto),
+        Some("vendedor") => Ok(AgrupacionMargen::Vendedor),
+        Some("sucursal") => Ok(AgrupacionMargen::Sucursal),
+        Some("periodo") => Ok(AgrupacionMargen::Periodo(periodo)),
+        Some(_) => Err(ApiError::InvalidInput(
+            "Agrupación inválida. Valores permitidos: producto, vendedor, sucursal, periodo".to_string()
+        )),
+    }
+}
+
+fn margen_response(grupo: MargenGrupo) -> MargenResponse {
+    let margen = &grupo.ingresos - &grupo.costo;
+    let margen_porcentaje = (!grupo.ingresos.is_zero())
+        .then(|| costos::redondear_importe(&(&margen * BigDecimal::from(100) / &grupo.ingresos)))
+        .and_then(|porcentaje| porcentaje.to_f64());
+
+    MargenResponse {
+        clave: grupo.clave,
+        nombre: grupo.nombre,
+        unidades: grupo.unidades,
+        ingresos: grupo.ingresos.to_f64().unwrap_or(0.0),
+        costo: grupo.costo.to_f64().unwrap_or(0.0),
+        margen: margen.to_f64().unwrap_or(0.0),
+        margen_porcentaje,
+    }
+}
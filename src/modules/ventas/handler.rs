@@ -1,5 +1,8 @@
 use actix_web::{web, HttpResponse, ResponseError, Result};
-use crate::modules::ventas::model::{CrearVentaRequest, VentasQueryParams, VentaCreadaResponse, VentaResponse};
+use crate::modules::ventas::model::{
+    AnularVentaRequest, CrearVentaRequest, RegistrarPagosRequest, SerieResponse, VentasQueryParams,
+    VentaCreadaResponse, VentaResponse,
+};
 use crate::modules::common::errors::ErrorResponse;
 use crate::state::app_state::AppState;
 
@@ -11,7 +14,7 @@ use crate::state::app_state::AppState;
     request_body = CrearVentaRequest,
     responses(
         (status = 201, description = "Venta creada exitosamente con descuento de inventario automático", body = VentaCreadaResponse),
-        (status = 400, description = "Datos inválidos, cliente inactivo, o stock insuficiente", body = ErrorResponse),
+        (status = 400, description = "Datos inválidos, cliente inactivo, stock insuficiente o pagos que no cubren el total", body = ErrorResponse),
         (status = 404, description = "Cliente o producto no encontrado", body = ErrorResponse),
         (status = 500, description = "Error interno del servidor", body = ErrorResponse)
     )
@@ -38,6 +41,7 @@ pub async fn crear_venta(
     ),
     responses(
         (status = 200, description = "Lista de ventas con filtros aplicados", body = Vec<VentaResponse>),
+        (status = 400, description = "Filtro inválido", body = ErrorResponse),
         (status = 500, description = "Error interno del servidor", body = ErrorResponse)
     )
 )]
@@ -52,6 +56,8 @@ pub async fn listar_ventas(
         query.sucursal.clone(),
         query.fecha_desde.clone(),
         query.fecha_hasta.clone(),
+        query.estado.clone(),
+        query.metodo_pago.clone(),
     ) {
         Ok(ventas) => Ok(HttpResponse::Ok().json(ventas)),
         Err(e) => Ok(e.error_response()),
@@ -85,11 +91,102 @@ pub async fn obtener_venta(
     }
 }
 
+/// POST /v1/ventas/:id/pagos - Cobrar una venta pendiente de pago
+#[utoipa::path(
+    post,
+    path = "/v1/ventas/{id}/pagos",
+    tag = "Ventas",
+    params(
+        ("id" = String, Path, description = "ID de la venta (UUID)")
+    ),
+    request_body = RegistrarPagosRequest,
+    responses(
+        (status = 200, description = "Venta pagada, con sus pagos y el cambio entregado", body = VentaResponse),
+        (status = 400, description = "Pagos inválidos o que no cubren el total, venta ya pagada o anulada, o sesión de caja cerrada", body = ErrorResponse),
+        (status = 404, description = "Venta o sesión de caja no encontrada", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn registrar_pagos(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<RegistrarPagosRequest>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    let service = &state.venta_service;
+
+    match service.registrar_pagos(&id, body.into_inner()) {
+        Ok(venta) => Ok(HttpResponse::Ok().json(venta)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// POST /v1/ventas/:id/anular - Anular una venta devolviendo la mercancía al inventario
+#[utoipa::path(
+    post,
+    path = "/v1/ventas/{id}/anular",
+    tag = "Ventas",
+    params(
+        ("id" = String, Path, description = "ID de la venta (UUID)")
+    ),
+    request_body = AnularVentaRequest,
+    responses(
+        (status = 200, description = "Venta anulada: stock devuelto y pagos reembolsados", body = VentaResponse),
+        (status = 400, description = "Datos inválidos, venta ya anulada o con su sesión de caja cerrada", body = ErrorResponse),
+        (status = 404, description = "Venta o persona no encontrada", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn anular_venta(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<AnularVentaRequest>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    let service = &state.venta_service;
+
+    match service.anular_venta(&id, body.into_inner()) {
+        Ok(venta) => Ok(HttpResponse::Ok().json(venta)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// GET /v1/ventas/series/:numero - Qué venta y qué cliente se llevaron un número de serie
+#[utoipa::path(
+    get,
+    path = "/v1/ventas/series/{numero}",
+    tag = "Ventas",
+    params(
+        ("numero" = String, Path, description = "Número de serie, sin distinguir mayúsculas")
+    ),
+    responses(
+        (status = 200, description = "Unidades con ese número, su estado y las ventas en que salieron", body = Vec<SerieResponse>),
+        (status = 400, description = "Número de serie inválido", body = ErrorResponse),
+        (status = 404, description = "Número de serie no registrado", body = ErrorResponse),
+        (status = 500, description = "Error interno del servidor", body = ErrorResponse)
+    )
+)]
+pub async fn buscar_serie(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+) -> Result<HttpResponse> {
+    let numero = path.into_inner();
+    let service = &state.venta_service;
+
+    match service.buscar_serie(&numero) {
+        Ok(series) => Ok(HttpResponse::Ok().json(series)),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
 pub fn configure(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/ventas")
             .route("", web::post().to(crear_venta))
             .route("", web::get().to(listar_ventas))
+            .route("/series/{numero}", web::get().to(buscar_serie))
             .route("/{id}", web::get().to(obtener_venta))
+            .route("/{id}/pagos", web::post().to(registrar_pagos))
+            .route("/{id}/anular", web::post().to(anular_venta))
     );
 }
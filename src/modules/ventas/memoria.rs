@@ -0,0 +1,460 @@
+use std::cmp::Reverse;
+use bigdecimal::BigDecimal;
+use chrono::{NaiveDate, NaiveDateTime, Utc};
+use uuid::Uuid;
+use crate::modules::cajas::repository as caja_repo;
+use crate::modules::common::errors::{ApiError, ApiResult};
+use crate::modules::common::memoria::{MemoriaRepository, Tablas};
+use crate::modules::common::types::{EstadoCotizacion, EstadoSerie, EstadoVenta, MetodoPago, TipoMovimiento};
+use crate::modules::cotizaciones::repository as cotizacion_repo;
+use crate::modules::cuentas::credito;
+use crate::modules::inventarios::costos;
+use crate::modules::inventarios::lotes;
+use crate::modules::inventarios::model::{NuevoMovimiento, NumeroSerie};
+use crate::modules::inventarios::repository as inventario_repo;
+use crate::modules::pedidos::repository as pedido_repo;
+use crate::modules::personas::repository as persona_repo;
+use crate::modules::ventas::model::{
+    DetalleVenta, LoteDetalleVenta, NuevaSerieDetalleVenta, NuevaVenta, NuevoDetalleVenta, NuevoPago, Pago,
+    SerieDetalleVenta, Venta,
+};
+use crate::modules::ventas::repository::{self as venta_repo, VentaRepository};
+
+impl Tablas {
+    fn insertar_pagos(&mut self, pagos: Vec<NuevoPago>) {
+        let ahora = Utc::now().naive_utc();
+        self.pagos.extend(pagos.into_iter().map(|pago| Pago {
+            id: Uuid::new_v4(),
+            id_venta: pago.id_venta,
+            metodo: pago.metodo,
+            monto: pago.monto,
+            cambio: pago.cambio,
+            referencia: pago.referencia,
+            fecha: pago.fecha,
+            fecha_creacion: ahora,
+            fecha_actualizacion: ahora,
+            activo: true,
+            id_sesion_caja: pago.id_sesion_caja,
+        }));
+    }
+
+    fn saldo_pendiente(&self, id_cliente: Uuid) -> BigDecimal {
+        self.ventas
+            .iter()
+            .filter(|venta| venta.id_persona == id_cliente && venta.activo && venta.estado == EstadoVenta::PendientePago)
+            .map(|venta| {
+                let pagos: Vec<Pago> = self
+                    .pagos
+                    .iter()
+                    .filter(|pago| pago.id_venta == venta.id && pago.activo)
+                    .cloned()
+                    .collect();
+                credito::saldo_de(venta, &pagos)
+            })
+            .sum()
+    }
+
+    fn venta_mut(&mut self, id_venta: Uuid) -> ApiResult<&mut Venta> {
+        self.ventas
+            .iter_mut()
+            .find(|venta| venta.id == id_venta && venta.activo)
+            .ok_or_else(|| venta_repo::venta_no_encontrada(id_venta))
+    }
+}
+
+impl VentaRepository for MemoriaRepository {
+    fn guardar_con_detalles(
+        &self,
+        venta: NuevaVenta,
+        detalles: Vec<NuevoDetalleVenta>,
+        series: Vec<NuevaSerieDetalleVenta>,
+        pagos: Vec<NuevoPago>,
+    ) -> ApiResult<Uuid> {
+        self.transaccion(|tablas| {
+            let ahora = Utc::now().naive_utc();
+
+            if let Some(id_sesion_caja) = venta.id_sesion_caja {
+                caja_repo::validar_abierta(tablas.sesion_caja(id_sesion_caja)?)?;
+            }
+
+            if venta.estado == EstadoVenta::PendientePago {
+                let cliente = tablas
+                    .personas
+                    .iter()
+                    .find(|persona| persona.id == venta.id_persona)
+                    .cloned()
+                    .ok_or_else(|| persona_repo::persona_no_encontrada(venta.id_persona))?;
+                let a_credito = &venta.monto - pagos.iter().map(|pago| &pago.monto).sum::<BigDecimal>();
+                if a_credito > BigDecimal::from(0) {
+                    credito::validar_credito(&cliente, &tablas.saldo_pendiente(venta.id_persona), &a_credito)?;
+                }
+            }
+
+            tablas.ventas.push(Venta {
+                id: venta.id,
+                id_persona: venta.id_persona,
+                fecha: venta.fecha,
+                monto: venta.monto,
+                sucursal: venta.sucursal,
+                fecha_creacion: ahora,
+                fecha_actualizacion: ahora,
+                activo: true,
+                id_vendedor: venta.id_vendedor,
+                estado: venta.estado,
+                credito: venta.credito,
+                id_sesion_caja: venta.id_sesion_caja,
+                id_cotizacion: venta.id_cotizacion,
+                id_pedido: venta.id_pedido,
+            });
+
+            // cotizacion_repo::convertir_en_venta
+            if let Some(id_cotizacion) = venta.id_cotizacion {
+                let cotizacion = tablas.cotizacion_mut(id_cotizacion)?;
+                cotizacion_repo::validar_estado(cotizacion, EstadoCotizacion::Aceptada, venta.fecha.date())?;
+                cotizacion.estado = EstadoCotizacion::Convertida;
+                cotizacion.id_venta = Some(venta.id);
+                cotizacion.fecha_actualizacion = ahora;
+            }
+
+            // La SALIDA queda a cargo del vendedor o, sin él, del operador de la caja
+            let responsable = match (venta.id_vendedor, venta.id_sesion_caja) {
+                (Some(id_vendedor), _) => id_vendedor,
+                (None, Some(id_sesion_caja)) => tablas.sesion_caja(id_sesion_caja)?.id_operador,
+                (None, None) => venta.id_persona,
+            };
+
+            let mut salidas_pedido = match venta.id_pedido {
+                Some(id_pedido) => Some(tablas.entregar_pedido(id_pedido, venta.id, ahora)?.into_iter()),
+                None => None,
+            };
+
+            for detalle in detalles {
+                // trg_detalle_ventas_unidad
+                let factor = match detalle.id_unidad {
+                    Some(id_unidad) => tablas
+                        .unidades_producto
+                        .iter()
+                        .find(|u| u.id == id_unidad && u.id_producto == detalle.id_producto)
+                        .map(|u| u.factor)
+                        .ok_or_else(|| ApiError::DatabaseError(
+                            format!("La unidad {} no pertenece al producto {}", id_unidad, detalle.id_producto)
+                        ))?,
+                    None => 1,
+                };
+                if detalle.cantidad != detalle.cantidad_unidad * factor {
+                    return Err(ApiError::DatabaseError(
+                        "La cantidad del detalle no corresponde a su unidad".to_string()
+                    ));
+                }
+
+                let (asignacion, costo) = match salidas_pedido.as_mut() {
+                    // Lo despachado del pedido ya salió del stock
+                    Some(salidas) => {
+                        let salida = pedido_repo::salida_de_detalle(salidas.next(), &detalle)?;
+                        (salida.lotes, salida.costo)
+                    }
+                    None => {
+                        // inventario_repo::consumir_fefo, con el stock previo a la venta
+                        let stock_actual = tablas.inventario_mut(detalle.id_producto)?.cantidad_disponible;
+                        let asignacion = lotes::asignar_fefo(
+                            detalle.id_producto,
+                            &tablas.lotes_de(detalle.id_producto),
+                            stock_actual,
+                            detalle.cantidad,
+                            ahora.date(),
+                        )?;
+                        for (id_lote, tomada) in &asignacion {
+                            tablas.sumar_a_lote(*id_lote, -tomada)?;
+                        }
+
+                        // inventario_repo::consumir_capas, también con el stock previo
+                        let consumo = tablas.consumir_capas(detalle.id_producto, stock_actual, detalle.cantidad, None);
+                        let costo = costos::redondear_importe(&consumo.costo_total);
+
+                        // trg_validar_stock_detalle_venta + trg_actualizar_inventario_venta; lo
+                        // reservado para pedidos no se vende
+                        let inventario = tablas
+                            .inventario_mut(detalle.id_producto)
+                            .ok()
+                            .filter(|inventario| {
+                                inventario.cantidad_disponible - inventario.cantidad_reservada >= detalle.cantidad
+                            })
+                            .ok_or_else(|| ApiError::DatabaseError(
+                                format!("Stock insuficiente para el producto {}", detalle.id_producto)
+                            ))?;
+                        inventario.cantidad_disponible -= detalle.cantidad;
+                        inventario.fecha_actualizacion = ahora;
+
+                        tablas.insertar_movimiento(NuevoMovimiento {
+                            id_producto: detalle.id_producto,
+                            tipo_movimiento: TipoMovimiento::Salida,
+                            fecha: ahora,
+                            id_persona: responsable,
+                            cantidad: -detalle.cantidad,
+                            observaciones: Some(format!("Venta ID: {}", venta.id)),
+                            id_movimiento_revertido: None,
+                            id_lote: None,
+                            costo_unitario: (detalle.cantidad != 0)
+                                .then(|| costos::redondear_costo(&(&costo / BigDecimal::from(detalle.cantidad)))),
+                            id_detalle_pedido: None,
+                        });
+                        (asignacion, costo)
+                    }
+                };
+                for (id_lote, tomada) in asignacion {
+                    tablas.detalle_ventas_lotes.push(LoteDetalleVenta {
+                        id: Uuid::new_v4(),
+                        id_detalle_venta: detalle.id,
+                        id_lote,
+                        cantidad: tomada,
+                        fecha_creacion: ahora,
+                        fecha_actualizacion: ahora,
+                        activo: true,
+                    });
+                }
+
+                tablas.detalle_ventas.push(DetalleVenta {
+                    id: detalle.id,
+                    id_venta: detalle.id_venta,
+                    id_producto: detalle.id_producto,
+                    cantidad: detalle.cantidad,
+                    monto: detalle.monto,
+                    fecha_creacion: ahora,
+                    fecha_actualizacion: ahora,
+                    activo: true,
+                    id_unidad: detalle.id_unidad,
+                    cantidad_unidad: detalle.cantidad_unidad,
+                    costo,
+                    comision: detalle.comision,
+                    id_regla_comision: detalle.id_regla_comision,
+                    id_lista_precios: detalle.id_lista_precios,
+                    id_promocion: detalle.id_promocion,
+                    descuento: detalle.descuento,
+                });
+            }
+
+            // inventario_repo::vender_series
+            for vendida in series {
+                let serie = tablas
+                    .numeros_serie
+                    .iter_mut()
+                    .find(|serie| serie.id == vendida.id_serie && serie.activo && serie.estado.en_almacen())
+                    .ok_or_else(|| inventario_repo::serie_no_disponible(vendida.id_serie))?;
+                serie.estado = EstadoSerie::Vendido;
+                serie.fecha_actualizacion = ahora;
+
+                tablas.detalle_ventas_series.push(SerieDetalleVenta {
+                    id: Uuid::new_v4(),
+                    id_detalle_venta: vendida.id_detalle_venta,
+                    id_serie: vendida.id_serie,
+                    fecha_creacion: ahora,
+                    fecha_actualizacion: ahora,
+                    activo: true,
+                });
+            }
+
+            tablas.insertar_pagos(pagos);
+
+            Ok(venta.id)
+        })
+    }
+
+    fn buscar_por_id(&self, id: Uuid) -> ApiResult<(Venta, Vec<DetalleVenta>)> {
+        let venta = self
+            .tablas()
+            .ventas
+            .iter()
+            .find(|venta| venta.id == id && venta.activo)
+            .cloned()
+            .ok_or_else(|| venta_repo::venta_no_encontrada(id))?;
+
+        let detalles = self.obtener_detalles(id)?;
+        Ok((venta, detalles))
+    }
+
+    fn listar(
+        &self,
+        id_cliente: Option<Uuid>,
+        sucursal: Option<String>,
+        fecha_desde: Option<NaiveDateTime>,
+        fecha_hasta: Option<NaiveDateTime>,
+        estado: Option<EstadoVenta>,
+        metodo_pago: Option<MetodoPago>,
+    ) -> ApiResult<Vec<Venta>> {
+        let tablas = self.tablas();
+        let mut ventas: Vec<Venta> = tablas
+            .ventas
+            .iter()
+            .filter(|venta| venta.activo)
+            .filter(|venta| estado.is_none_or(|estado| venta.estado == estado))
+            .filter(|venta| {
+                metodo_pago.is_none_or(|metodo| {
+                    tablas.pagos.iter().any(|pago| pago.id_venta == venta.id && pago.activo && pago.metodo == metodo)
+                })
+            })
+            .filter(|venta| id_cliente.is_none_or(|id| venta.id_persona == id))
+            .filter(|venta| sucursal.is_none() || venta.sucursal == sucursal)
+            .filter(|venta| fecha_desde.is_none_or(|desde| venta.fecha >= desde))
+            .filter(|venta| fecha_hasta.is_none_or(|hasta| venta.fecha <= hasta))
+            .cloned()
+            .collect();
+        ventas.sort_by_key(|venta| Reverse(venta.fecha));
+        Ok(ventas)
+    }
+
+    fn obtener_detalles(&self, id_venta: Uuid) -> ApiResult<Vec<DetalleVenta>> {
+        Ok(self
+            .tablas()
+            .detalle_ventas
+            .iter()
+            .filter(|detalle| detalle.id_venta == id_venta && detalle.activo)
+            .cloned()
+            .collect())
+    }
+
+    fn obtener_lotes_detalle(&self, id_detalle_venta: Uuid) -> ApiResult<Vec<LoteDetalleVenta>> {
+        let tablas = self.tablas();
+        let mut consumidos: Vec<(NaiveDate, LoteDetalleVenta)> = tablas
+            .detalle_ventas_lotes
+            .iter()
+            .filter(|consumo| consumo.id_detalle_venta == id_detalle_venta && consumo.activo)
+            .map(|consumo| {
+                let vencimiento = tablas
+                    .lotes
+                    .iter()
+                    .find(|lote| lote.id == consumo.id_lote)
+                    .map(|lote| lote.fecha_vencimiento)
+                    .unwrap_or(NaiveDate::MAX);
+                (vencimiento, consumo.clone())
+            })
+            .collect();
+        consumidos.sort_by_key(|(vencimiento, _)| *vencimiento);
+        Ok(consumidos.into_iter().map(|(_, consumo)| consumo).collect())
+    }
+    fn obtener_series_detalle(&self, id_detalle_venta: Uuid) -> ApiResult<Vec<NumeroSerie>> {
+        let tablas = self.tablas();
+        let mut series: Vec<NumeroSerie> = tablas
+            .detalle_ventas_series
+            .iter()
+            .filter(|vendida| vendida.id_detalle_venta == id_detalle_venta && vendida.activo)
+            .filter_map(|vendida| tablas.numeros_serie.iter().find(|serie| serie.id == vendida.id_serie))
+            .cloned()
+            .collect();
+        series.sort_by(|a, b| a.numero.cmp(&b.numero));
+        Ok(series)
+    }
+
+    fn listar_ventas_de_serie(&self, id_serie: Uuid) -> ApiResult<Vec<Venta>> {
+        let tablas = self.tablas();
+        let mut ventas: Vec<Venta> = tablas
+            .detalle_ventas_series
+            .iter()
+            .filter(|vendida| vendida.id_serie == id_serie && vendida.activo)
+            .filter_map(|vendida| tablas.detalle_ventas.iter().find(|detalle| detalle.id == vendida.id_detalle_venta))
+            .filter_map(|detalle| tablas.ventas.iter().find(|venta| venta.id == detalle.id_venta && venta.activo))
+            .cloned()
+            .collect();
+        ventas.sort_by_key(|venta| Reverse(venta.fecha));
+        Ok(ventas)
+    }
+
+    fn obtener_pagos(&self, id_venta: Uuid) -> ApiResult<Vec<Pago>> {
+        let mut pagos: Vec<Pago> = self
+            .tablas()
+            .pagos
+            .iter()
+            .filter(|pago| pago.id_venta == id_venta && pago.activo)
+            .cloned()
+            .collect();
+        pagos.sort_by_key(|pago| (pago.fecha, pago.fecha_creacion));
+        Ok(pagos)
+    }
+
+    fn registrar_pagos(&self, id_venta: Uuid, pagos: Vec<NuevoPago>) -> ApiResult<()> {
+        self.transaccion(|tablas| {
+            if let Some(id_sesion_caja) = pagos.first().and_then(|pago| pago.id_sesion_caja) {
+                caja_repo::validar_abierta(tablas.sesion_caja(id_sesion_caja)?)?;
+            }
+
+            let pagado: BigDecimal = tablas
+                .pagos
+                .iter()
+                .filter(|pago| pago.id_venta == id_venta && pago.activo)
+                .map(|pago| &pago.monto)
+                .sum();
+            let venta = tablas.venta_mut(id_venta)?;
+            venta_repo::validar_pendiente_de_pago(venta)?;
+            if venta_repo::saldo_tras_pagos(venta, &pagado, &pagos)? == BigDecimal::from(0) {
+                venta.estado = EstadoVenta::Pagada;
+            }
+            venta.fecha_actualizacion = Utc::now().naive_utc();
+
+            tablas.insertar_pagos(pagos);
+            Ok(())
+        })
+    }
+
+    fn saldo_pendiente(&self, id_cliente: Uuid) -> ApiResult<BigDecimal> {
+        Ok(self.tablas().saldo_pendiente(id_cliente))
+    }
+
+    fn anular(&self, id_venta: Uuid, id_persona: Uuid, motivo: Option<String>) -> ApiResult<Vec<Uuid>> {
+        let metodo = self.metodo_valorizacion;
+        self.transaccion(|tablas| {
+            let ahora = Utc::now().naive_utc();
+            let venta = tablas.venta_mut(id_venta)?.clone();
+            venta_repo::validar_anulable(&venta)?;
+            let sesiones_pagos = tablas
+                .pagos
+                .iter()
+                .filter(|pago| pago.id_venta == id_venta && pago.activo)
+                .map(|pago| pago.id_sesion_caja);
+            for id_sesion_caja in venta_repo::sesiones_afectadas(&venta, sesiones_pagos) {
+                caja_repo::validar_abierta(tablas.sesion_caja(id_sesion_caja)?)?;
+            }
+            let venta = tablas.venta_mut(id_venta)?;
+            venta.estado = EstadoVenta::Anulada;
+            venta.fecha_actualizacion = ahora;
+
+            let detalles: Vec<DetalleVenta> = tablas
+                .detalle_ventas
+                .iter()
+                .filter(|detalle| detalle.id_venta == id_venta && detalle.activo)
+                .cloned()
+                .collect();
+
+            let mut entradas = Vec::new();
+            for detalle in &detalles {
+                let lotes_linea: Vec<LoteDetalleVenta> = tablas
+                    .detalle_ventas_lotes
+                    .iter()
+                    .filter(|consumo| consumo.id_detalle_venta == detalle.id && consumo.activo)
+                    .cloned()
+                    .collect();
+                let numeros: Vec<String> = tablas
+                    .detalle_ventas_series
+                    .iter()
+                    .filter(|vendida| vendida.id_detalle_venta == detalle.id && vendida.activo)
+                    .filter_map(|vendida| tablas.numeros_serie.iter().find(|serie| serie.id == vendida.id_serie))
+                    .map(|serie| serie.numero.clone())
+                    .collect();
+
+                let mut ids_linea = Vec::new();
+                for devolucion in venta_repo::devoluciones_de_linea(detalle, &lotes_linea, id_persona, motivo.as_deref()) {
+                    ids_linea.push(tablas.devolver_al_stock(metodo, devolucion)?);
+                }
+                if let Some(id_movimiento) = ids_linea.first().filter(|_| !numeros.is_empty()) {
+                    tablas.aplicar_series(detalle.id_producto, TipoMovimiento::Entrada, &numeros, *id_movimiento)?;
+                }
+                entradas.extend(ids_linea);
+            }
+
+            for pago in tablas.pagos.iter_mut().filter(|pago| pago.id_venta == id_venta && pago.activo) {
+                pago.activo = false;
+                pago.fecha_actualizacion = ahora;
+            }
+
+            Ok(entradas)
+        })
+    }
+}
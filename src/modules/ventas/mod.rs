@@ -1,4 +1,6 @@
 pub mod model;
+pub mod pagos;
 pub mod repository;
 pub mod service;
 pub mod handler;
+mod memoria;
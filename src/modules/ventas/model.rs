@@ -4,7 +4,9 @@ use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use utoipa::{ToSchema, IntoParams};
-use crate::schema::{ventas, detalle_ventas};
+use crate::modules::common::types::{EstadoSerie, EstadoVenta, MetodoPago};
+use crate::modules::precios::model::OrigenPrecio;
+use crate::schema::{ventas, detalle_ventas, detalle_ventas_lotes, detalle_ventas_series, pagos};
 
 // Domain Model for Venta
 #[derive(Debug, Clone, Queryable, Identifiable, Selectable)]
@@ -18,6 +20,17 @@ pub struct Venta {
     pub fecha_creacion: NaiveDateTime,
     pub fecha_actualizacion: NaiveDateTime,
     pub activo: bool,
+    /// Vendedor que atendió la venta; None en las ventas anteriores a registrarlo
+    pub id_vendedor: Option<Uuid>,
+    pub estado: EstadoVenta,
+    /// A crédito: admite abonos parciales y lo que falta es saldo del cliente
+    pub credito: bool,
+    /// Sesión de caja en que se registró; None en las ventas anteriores a las cajas
+    pub id_sesion_caja: Option<Uuid>,
+    /// Cotización de la que salió la venta
+    pub id_cotizacion: Option<Uuid>,
+    /// Pedido entregado con la venta; sus unidades salieron del stock con los despachos
+    pub id_pedido: Option<Uuid>,
 }
 
 // Domain Model for DetalleVenta
@@ -28,11 +41,77 @@ pub struct DetalleVenta {
     pub id: Uuid,
     pub id_venta: Uuid,
     pub id_producto: Uuid,
+    /// Cantidad en la unidad base del producto, la que mueve el inventario
     pub cantidad: i32,
     pub monto: BigDecimal,
     pub fecha_creacion: NaiveDateTime,
     pub fecha_actualizacion: NaiveDateTime,
     pub activo: bool,
+    /// Unidad en que se vendió la línea; None para la unidad base
+    pub id_unidad: Option<Uuid>,
+    /// Cantidad en la unidad vendida
+    pub cantidad_unidad: i32,
+    /// Costo de la mercancía vendida, de las capas de costo que consumió la línea
+    pub costo: BigDecimal,
+    /// Comisión del vendedor, fijada al vender con la regla `id_regla_comision`
+    pub comision: BigDecimal,
+    pub id_regla_comision: Option<Uuid>,
+    /// Lista de precios de la que salió el precio; None si es el de catálogo
+    pub id_lista_precios: Option<Uuid>,
+    /// Promoción que fijó el descuento de la línea
+    pub id_promocion: Option<Uuid>,
+    /// Descuento de la promoción, ya restado del monto
+    pub descuento: BigDecimal,
+}
+
+// Domain Model for the lots a sale line was taken from
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable, Associations)]
+#[diesel(belongs_to(DetalleVenta, foreign_key = id_detalle_venta))]
+#[diesel(table_name = detalle_ventas_lotes)]
+pub struct LoteDetalleVenta {
+    pub id: Uuid,
+    pub id_detalle_venta: Uuid,
+    pub id_lote: Uuid,
+    /// Unidades base tomadas del lote
+    pub cantidad: i32,
+    pub fecha_creacion: NaiveDateTime,
+    pub fecha_actualizacion: NaiveDateTime,
+    pub activo: bool,
+}
+
+// Domain Model for the serial numbers sold in a sale line
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable, Associations)]
+#[diesel(belongs_to(DetalleVenta, foreign_key = id_detalle_venta))]
+#[diesel(table_name = detalle_ventas_series)]
+pub struct SerieDetalleVenta {
+    pub id: Uuid,
+    pub id_detalle_venta: Uuid,
+    pub id_serie: Uuid,
+    pub fecha_creacion: NaiveDateTime,
+    pub fecha_actualizacion: NaiveDateTime,
+    pub activo: bool,
+}
+
+// Domain Model for a payment of a sale
+#[derive(Debug, Clone, Queryable, Identifiable, Selectable, Associations)]
+#[diesel(belongs_to(Venta, foreign_key = id_venta))]
+#[diesel(table_name = pagos)]
+pub struct Pago {
+    pub id: Uuid,
+    pub id_venta: Uuid,
+    pub metodo: MetodoPago,
+    /// Lo que el pago abona a la venta
+    pub monto: BigDecimal,
+    /// Lo que se devolvió al cliente; solo en efectivo
+    pub cambio: BigDecimal,
+    pub referencia: Option<String>,
+    pub fecha: NaiveDateTime,
+    pub fecha_creacion: NaiveDateTime,
+    pub fecha_actualizacion: NaiveDateTime,
+    /// False si la venta se anuló y el pago se reembolsó
+    pub activo: bool,
+    /// Sesión de caja que lo cobró; None si se cobró fuera de caja
+    pub id_sesion_caja: Option<Uuid>,
 }
 
 // DTO for creating a sale
@@ -40,17 +119,73 @@ pub struct DetalleVenta {
 pub struct CrearVentaRequest {
     #[schema(example = "550e8400-e29b-41d4-a716-446655440000")]
     pub id_cliente: String,
+    /// Sesión de caja abierta en la que se registra la venta y se cobran sus pagos
+    #[schema(example = "bb0e8400-e29b-41d4-a716-446655440000")]
+    pub id_sesion_caja: String,
+    /// Si se indica, debe ser la sucursal de la caja
     #[schema(example = "Bogotá Centro")]
     pub sucursal: Option<String>,
+    /// Persona con perfil VENDEDOR que atiende la venta y cobra su comisión; sin ella la venta
+    /// no genera comisión
+    #[schema(example = "440e8400-e29b-41d4-a716-446655440000")]
+    pub id_vendedor: Option<String>,
     pub detalles: Vec<DetalleVentaRequest>,
+    /// Pagos con que se cobra la venta; deben cubrir el total salvo en las ventas a crédito.
+    /// Sin pagos, la venta queda PENDIENTE_PAGO
+    #[serde(default)]
+    pub pagos: Vec<PagoRequest>,
+    /// Venta a crédito: lo que no cubren los pagos queda como saldo del cliente, hasta su
+    /// límite de crédito
+    #[serde(default)]
+    pub credito: bool,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PagoRequest {
+    /// EFECTIVO, TARJETA o TRANSFERENCIA
+    #[schema(example = "EFECTIVO")]
+    pub metodo: String,
+    /// Importe entregado; en efectivo puede superar lo pendiente y la diferencia es el cambio
+    #[schema(example = 50000.0)]
+    pub monto: f64,
+    /// Autorización de la tarjeta, número de la transferencia...
+    #[schema(example = "AUT-004512")]
+    pub referencia: Option<String>,
+}
+
+// DTO for paying a pending sale (or part of a credit sale)
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RegistrarPagosRequest {
+    pub pagos: Vec<PagoRequest>,
+    /// Sesión de caja abierta que cobra los pagos y en cuyo arqueo cuentan
+    #[schema(example = "bb0e8400-e29b-41d4-a716-446655440000")]
+    pub id_sesion_caja: String,
+}
+
+// DTO for voiding a sale
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AnularVentaRequest {
+    /// Persona que registra la devolución de la mercancía al inventario
+    #[schema(example = "440e8400-e29b-41d4-a716-446655440000")]
+    pub id_persona: String,
+    #[schema(example = "El cliente desistió de la compra")]
+    pub motivo: Option<String>,
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct DetalleVentaRequest {
     #[schema(example = "660e8400-e29b-41d4-a716-446655440000")]
     pub id_producto: String,
+    /// Cantidad en la unidad indicada
     #[schema(example = 2)]
     pub cantidad: i32,
+    /// Nombre de la unidad de venta ("Caja x12"); si se omite, la unidad base del producto
+    #[schema(example = "Caja x12")]
+    pub unidad: Option<String>,
+    /// Obligatorios en productos serializados: uno por unidad base vendida
+    #[serde(default)]
+    #[schema(example = json!(["SN-5CD1234XYZ", "SN-5CD1234XZA"]))]
+    pub numeros_serie: Vec<String>,
 }
 
 // DTO for sale response
@@ -66,7 +201,45 @@ pub struct VentaResponse {
     pub total: f64,
     #[schema(example = "Bogotá Centro")]
     pub sucursal: Option<String>,
+    #[schema(example = "440e8400-e29b-41d4-a716-446655440000")]
+    pub id_vendedor: Option<String>,
+    pub estado: EstadoVenta,
+    pub credito: bool,
+    #[schema(example = "bb0e8400-e29b-41d4-a716-446655440000")]
+    pub id_sesion_caja: Option<String>,
+    /// Cotización convertida en esta venta
+    #[schema(example = "cc0e8400-e29b-41d4-a716-446655440000")]
+    pub id_cotizacion: Option<String>,
+    /// Pedido entregado con esta venta
+    #[schema(example = "dd0e8400-e29b-41d4-a716-446655440000")]
+    pub id_pedido: Option<String>,
+    /// Suma de lo que abonan los pagos vigentes
+    #[schema(example = 2400000.0)]
+    pub total_pagado: f64,
+    /// Lo que falta por pagar; 0 si está pagada o anulada
+    #[schema(example = 0.0)]
+    pub saldo: f64,
+    /// Cambio entregado en efectivo
+    #[schema(example = 0.0)]
+    pub cambio: f64,
     pub detalles: Vec<DetalleVentaResponse>,
+    /// Pagos vigentes, del primero al último; vacío si la venta está pendiente o anulada
+    pub pagos: Vec<PagoResponse>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PagoResponse {
+    #[schema(example = "aa0e8400-e29b-41d4-a716-446655440000")]
+    pub id: String,
+    pub metodo: MetodoPago,
+    #[schema(example = 2350000.0)]
+    pub monto: f64,
+    #[schema(example = 0.0)]
+    pub cambio: f64,
+    #[schema(example = "AUT-004512")]
+    pub referencia: Option<String>,
+    #[schema(example = "2025-11-17 10:30:00")]
+    pub fecha: String,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
@@ -75,12 +248,81 @@ pub struct DetalleVentaResponse {
     pub id_producto: String,
     #[schema(example = "Laptop Dell Inspiron 15")]
     pub nombre_producto: String,
+    #[schema(example = "Caja x12")]
+    pub unidad: String,
+    /// Cantidad en la unidad vendida
     #[schema(example = 2)]
     pub cantidad: i32,
+    /// Cantidad descontada del inventario, en la unidad base
+    #[schema(example = 24)]
+    pub cantidad_base: i32,
+    /// Precio de una unidad de la vendida, antes del descuento de la promoción
     #[schema(example = 1200000.0)]
     pub precio_unitario: f64,
     #[schema(example = 2400000.0)]
     pub subtotal: f64,
+    /// Descuento de la promoción aplicada, ya restado del subtotal
+    #[schema(example = 0.0)]
+    pub descuento: f64,
+    /// Regla que fijó el precio de la línea
+    pub origen_precio: OrigenPrecio,
+    #[schema(example = "aa0e8400-e29b-41d4-a716-446655440000")]
+    pub id_lista_precios: Option<String>,
+    #[schema(example = json!(null))]
+    pub id_promocion: Option<String>,
+    /// Costo de la mercancía vendida, fijado al vender con el método de valorización vigente
+    #[schema(example = 1900000.0)]
+    pub costo: f64,
+    /// Comisión del vendedor por la línea; 0 en las ventas sin vendedor o sin regla aplicable
+    #[schema(example = 60000.0)]
+    pub comision: f64,
+    /// Lotes de los que salió la línea, en orden FEFO; vacío si salió de stock sin lote
+    pub lotes: Vec<LoteConsumidoResponse>,
+    /// Números de serie vendidos; vacío si el producto no es serializado
+    #[schema(example = json!(["SN-5CD1234XYZ", "SN-5CD1234XZA"]))]
+    pub numeros_serie: Vec<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LoteConsumidoResponse {
+    #[schema(example = "880e8400-e29b-41d4-a716-446655440000")]
+    pub id_lote: String,
+    #[schema(example = "L2026-101")]
+    pub codigo: String,
+    #[schema(example = "2026-11-05")]
+    pub fecha_vencimiento: String,
+    /// Unidades base tomadas del lote
+    #[schema(example = 12)]
+    pub cantidad: i32,
+}
+
+// DTO for the serial number lookup
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SerieResponse {
+    #[schema(example = "990e8400-e29b-41d4-a716-446655440000")]
+    pub id: String,
+    #[schema(example = "660e8400-e29b-41d4-a716-446655440000")]
+    pub id_producto: String,
+    #[schema(example = "Laptop Dell Inspiron 15")]
+    pub nombre_producto: String,
+    #[schema(example = "SN-5CD1234XYZ")]
+    pub numero: String,
+    pub estado: EstadoSerie,
+    /// Ventas en las que salió la unidad, de la más reciente a la más antigua
+    pub ventas: Vec<VentaSerieResponse>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct VentaSerieResponse {
+    #[schema(example = "770e8400-e29b-41d4-a716-446655440000")]
+    pub id_venta: String,
+    #[schema(example = "2025-11-17 10:30:00")]
+    pub fecha: String,
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000")]
+    pub id_cliente: String,
+    /// None si el cliente fue dado de baja
+    #[schema(example = "María González")]
+    pub nombre_cliente: Option<String>,
 }
 
 // DTO for created sale response
@@ -90,6 +332,13 @@ pub struct VentaCreadaResponse {
     pub id: String,
     #[schema(example = "Venta procesada exitosamente")]
     pub mensaje: String,
+    pub estado: EstadoVenta,
+    /// Cambio a devolver en efectivo
+    #[schema(example = 2500.0)]
+    pub cambio: f64,
+    /// Lo que queda por pagar (a crédito o pendiente de pago)
+    #[schema(example = 0.0)]
+    pub saldo: f64,
 }
 
 // Insertable structs for database
@@ -101,6 +350,12 @@ pub struct NuevaVenta {
     pub fecha: NaiveDateTime,
     pub monto: BigDecimal,
     pub sucursal: Option<String>,
+    pub id_vendedor: Option<Uuid>,
+    pub estado: EstadoVenta,
+    pub credito: bool,
+    pub id_sesion_caja: Option<Uuid>,
+    pub id_cotizacion: Option<Uuid>,
+    pub id_pedido: Option<Uuid>,
 }
 
 #[derive(Debug, Insertable)]
@@ -111,6 +366,42 @@ pub struct NuevoDetalleVenta {
     pub id_producto: Uuid,
     pub cantidad: i32,
     pub monto: BigDecimal,
+    pub id_unidad: Option<Uuid>,
+    pub cantidad_unidad: i32,
+    /// Lo fija el repositorio al consumir las capas de costo
+    pub costo: BigDecimal,
+    pub comision: BigDecimal,
+    pub id_regla_comision: Option<Uuid>,
+    pub id_lista_precios: Option<Uuid>,
+    pub id_promocion: Option<Uuid>,
+    pub descuento: BigDecimal,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = pagos)]
+pub struct NuevoPago {
+    pub id_venta: Uuid,
+    pub metodo: MetodoPago,
+    pub monto: BigDecimal,
+    pub cambio: BigDecimal,
+    pub referencia: Option<String>,
+    pub fecha: NaiveDateTime,
+    pub id_sesion_caja: Option<Uuid>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = detalle_ventas_lotes)]
+pub struct NuevoLoteDetalleVenta {
+    pub id_detalle_venta: Uuid,
+    pub id_lote: Uuid,
+    pub cantidad: i32,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = detalle_ventas_series)]
+pub struct NuevaSerieDetalleVenta {
+    pub id_detalle_venta: Uuid,
+    pub id_serie: Uuid,
 }
 
 // Query parameters for filtering sales
@@ -124,4 +415,10 @@ pub struct VentasQueryParams {
     pub fecha_desde: Option<String>,
     #[schema(example = "2025-11-30")]
     pub fecha_hasta: Option<String>,
+    /// PENDIENTE_PAGO, PAGADA o ANULADA
+    #[schema(example = "PAGADA")]
+    pub estado: Option<String>,
+    /// Ventas con al menos un pago vigente con ese método: EFECTIVO, TARJETA o TRANSFERENCIA
+    #[schema(example = "TARJETA")]
+    pub metodo_pago: Option<String>,
 }
@@ -0,0 +1,133 @@
+//! Cobro de las ventas: una venta se paga con uno o varios pagos (efectivo, tarjeta o
+//! transferencia) que juntos deben cubrir lo pendiente.
+//!
+//! La tarjeta y la transferencia cobran importes exactos, así que no pueden superar lo
+//! pendiente; el efectivo cubre el resto y lo que sobra es el cambio. Las ventas a crédito
+//! admiten además abonos que cubren solo una parte.
+
+use bigdecimal::{BigDecimal, Zero};
+use crate::modules::common::errors::{ApiError, ApiResult};
+use crate::modules::common::types::MetodoPago;
+use crate::modules::inventarios::costos;
+use crate::modules::ventas::model::PagoRequest;
+
+/// Longitud máxima de la referencia de un pago
+pub const LONGITUD_MAXIMA_REFERENCIA: usize = 100;
+
+/// Pago tal como lo entrega el cliente
+#[derive(Debug, Clone, PartialEq)]
+pub struct PagoRecibido {
+    pub metodo: MetodoPago,
+    /// Importe entregado, en centavos
+    pub recibido: BigDecimal,
+    pub referencia: Option<String>,
+}
+
+impl PagoRecibido {
+    pub fn desde_request(request: &PagoRequest) -> ApiResult<PagoRecibido> {
+        let metodo = match request.metodo.trim().to_uppercase().as_str() {
+            "EFECTIVO" => MetodoPago::Efectivo,
+            "TARJETA" => MetodoPago::Tarjeta,
+            "TRANSFERENCIA" => MetodoPago::Transferencia,
+            _ => {
+                return Err(ApiError::InvalidInput(
+                    "Método de pago inválido. Valores permitidos: EFECTIVO, TARJETA, TRANSFERENCIA".to_string(),
+                ))
+            }
+        };
+
+        if !request.monto.is_finite() || request.monto <= 0.0 {
+            return Err(ApiError::InvalidInput("El monto de cada pago debe ser mayor a 0".to_string()));
+        }
+        let recibido = BigDecimal::try_from(request.monto)
+            .map(|monto| costos::redondear_importe(&monto))
+            .map_err(|e| ApiError::InvalidInput(format!("Monto de pago inválido: {}", e)))?;
+        if recibido <= BigDecimal::zero() {
+            return Err(ApiError::InvalidInput("El monto de cada pago debe ser mayor a 0".to_string()));
+        }
+
+        let referencia = request
+            .referencia
+            .as_deref()
+            .map(str::trim)
+            .filter(|referencia| !referencia.is_empty())
+            .map(str::to_string);
+        if referencia.as_ref().is_some_and(|r| r.chars().count() > LONGITUD_MAXIMA_REFERENCIA) {
+            return Err(ApiError::InvalidInput(format!(
+                "La referencia del pago no puede superar los {} caracteres",
+                LONGITUD_MAXIMA_REFERENCIA
+            )));
+        }
+
+        Ok(PagoRecibido { metodo, recibido, referencia })
+    }
+}
+
+/// Parte de un pago que abona la venta y cambio que se devuelve
+#[derive(Debug, Clone, PartialEq)]
+pub struct PagoAplicado {
+    pub metodo: MetodoPago,
+    pub monto: BigDecimal,
+    pub cambio: BigDecimal,
+    pub referencia: Option<String>,
+}
+
+/// Reparte `pagos` sobre lo `pendiente`, en el orden recibido. Falla si no lo cubren, si la
+/// tarjeta y la transferencia lo superan o si sobra un pago en efectivo entero.
+pub fn aplicar_pagos(pendiente: &BigDecimal, pagos: Vec<PagoRecibido>) -> ApiResult<Vec<PagoAplicado>> {
+    let recibido: BigDecimal = pagos.iter().map(|pago| &pago.recibido).sum();
+    if recibido < *pendiente {
+        return Err(ApiError::BusinessRuleViolation(format!(
+            "Los pagos ({}) no cubren el total pendiente ({})",
+            recibido, pendiente
+        )));
+    }
+
+    aplicar_abonos(pendiente, pagos)
+}
+
+/// Como `aplicar_pagos`, pero los pagos pueden cubrir solo una parte de lo `pendiente`: son
+/// abonos a una venta a crédito
+pub fn aplicar_abonos(pendiente: &BigDecimal, pagos: Vec<PagoRecibido>) -> ApiResult<Vec<PagoAplicado>> {
+    let exactos: BigDecimal = pagos
+        .iter()
+        .filter(|pago| pago.metodo != MetodoPago::Efectivo)
+        .map(|pago| &pago.recibido)
+        .sum();
+    if exactos > *pendiente {
+        return Err(ApiError::BusinessRuleViolation(format!(
+            "Los pagos con tarjeta o transferencia ({}) superan el total pendiente ({}); solo el efectivo da cambio",
+            exactos, pendiente
+        )));
+    }
+
+    let mut por_cubrir_en_efectivo = pendiente - exactos;
+    pagos
+        .into_iter()
+        .map(|pago| {
+            if pago.metodo != MetodoPago::Efectivo {
+                return Ok(PagoAplicado {
+                    metodo: pago.metodo,
+                    monto: pago.recibido,
+                    cambio: BigDecimal::zero(),
+                    referencia: pago.referencia,
+                });
+            }
+
+            let monto = pago.recibido.clone().min(por_cubrir_en_efectivo.clone());
+            if monto <= BigDecimal::zero() {
+                return Err(ApiError::BusinessRuleViolation(format!(
+                    "El pago en efectivo de {} sobra: los anteriores ya cubren el total",
+                    pago.recibido
+                )));
+            }
+            por_cubrir_en_efectivo -= &monto;
+            Ok(PagoAplicado {
+                metodo: pago.metodo,
+                cambio: &pago.recibido - &monto,
+                monto,
+                referencia: pago.referencia,
+            })
+        })
+        .collect()
+}
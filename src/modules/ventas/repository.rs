@@ -1,21 +1,97 @@
-use chrono::NaiveDateTime;
+use bigdecimal::BigDecimal;
+use chrono::{NaiveDateTime, Utc};
 use diesel::prelude::*;
 use diesel::r2d2::{ConnectionManager, Pool, PooledConnection};
 use uuid::Uuid;
+use tracing::instrument;
+use crate::modules::auditoria;
+use crate::modules::cajas::repository as caja_repo;
 use crate::modules::common::errors::{ApiError, ApiResult};
-use crate::modules::ventas::model::{Venta, DetalleVenta, NuevaVenta, NuevoDetalleVenta};
-use crate::schema::{ventas, detalle_ventas};
+use crate::modules::common::types::{EstadoVenta, MetodoPago, TipoMovimiento};
+use crate::modules::cotizaciones::repository as cotizacion_repo;
+use crate::modules::cuentas::credito;
+use crate::modules::inventarios::costos::{self, MetodoValorizacion};
+use crate::modules::inventarios::model::{NuevoMovimiento, NumeroSerie};
+use crate::modules::inventarios::repository as inventario_repo;
+use crate::modules::pedidos::repository as pedido_repo;
+use crate::modules::personas::model::Persona;
+use crate::modules::personas::repository::persona_no_encontrada;
+use crate::modules::ventas::model::{
+    Venta, DetalleVenta, LoteDetalleVenta, NuevaSerieDetalleVenta, NuevaVenta, NuevoDetalleVenta,
+    NuevoLoteDetalleVenta, NuevoPago, Pago,
+};
+use crate::schema::{ventas, detalle_ventas, detalle_ventas_lotes, detalle_ventas_series, lotes, numeros_serie, pagos, personas};
 
 pub type DbPool = Pool<ConnectionManager<PgConnection>>;
 pub type DbConnection = PooledConnection<ConnectionManager<PgConnection>>;
 
-pub struct VentaRepository {
+/// Acceso a datos de ventas (ver `PersonaRepository` para las implementaciones)
+pub trait VentaRepository: Send + Sync {
+    /// Guarda la cabecera y los detalles en una transacción. Al insertar cada detalle se valida
+    /// y descuenta el stock, y se registra la SALIDA de inventario (triggers en PostgreSQL).
+    /// Cada detalle consume además los lotes vigentes del producto en orden FEFO, y lo que
+    /// tomó de cada uno queda en `detalle_ventas_lotes`. Su costo sale de las capas de costo que
+    /// consume y se guarda en el detalle. Los números de serie vendidos pasan a VENDIDO; si
+    /// alguno ya no está en el almacén, la venta entera se rechaza. Los pagos se guardan en la
+    /// misma transacción. En las ventas que quedan con saldo se vuelve a comprobar, con el
+    /// cliente bloqueado, que lo que queda sin pagar cabe en su crédito disponible. La sesión de caja de
+    /// la venta debe seguir abierta; su cierre espera a que la venta termine de guardarse. La
+    /// venta de un pedido lo deja ENTREGADO y no vuelve a tocar el stock: cada detalle toma el
+    /// costo y los lotes de los despachos de su línea (ver `pedido_repo::entregar_en_venta`).
+    fn guardar_con_detalles(
+        &self,
+        venta: NuevaVenta,
+        detalles: Vec<NuevoDetalleVenta>,
+        series: Vec<NuevaSerieDetalleVenta>,
+        pagos: Vec<NuevoPago>,
+    ) -> ApiResult<Uuid>;
+
+    fn buscar_por_id(&self, id: Uuid) -> ApiResult<(Venta, Vec<DetalleVenta>)>;
+
+    fn listar(
+        &self,
+        id_cliente: Option<Uuid>,
+        sucursal: Option<String>,
+        fecha_desde: Option<NaiveDateTime>,
+        fecha_hasta: Option<NaiveDateTime>,
+        estado: Option<EstadoVenta>,
+        metodo_pago: Option<MetodoPago>,
+    ) -> ApiResult<Vec<Venta>>;
+
+    fn obtener_detalles(&self, id_venta: Uuid) -> ApiResult<Vec<DetalleVenta>>;
+
+    fn obtener_lotes_detalle(&self, id_detalle_venta: Uuid) -> ApiResult<Vec<LoteDetalleVenta>>;
+
+    /// Números de serie vendidos en la línea, ordenados por número
+    fn obtener_series_detalle(&self, id_detalle_venta: Uuid) -> ApiResult<Vec<NumeroSerie>>;
+
+    /// Ventas activas en las que salió el número de serie, de la más reciente a la más antigua
+    fn listar_ventas_de_serie(&self, id_serie: Uuid) -> ApiResult<Vec<Venta>>;
+
+    /// Pagos vigentes de la venta, del primero al último
+    fn obtener_pagos(&self, id_venta: Uuid) -> ApiResult<Vec<Pago>>;
+
+    /// Guarda pagos de una venta PENDIENTE_PAGO, que no pueden superar su saldo, y la marca
+    /// PAGADA cuando lo saldan. La sesión de caja que los cobra debe seguir abierta.
+    fn registrar_pagos(&self, id_venta: Uuid, pagos: Vec<NuevoPago>) -> ApiResult<()>;
+
+    /// Lo que el cliente debe entre sus ventas PENDIENTE_PAGO
+    fn saldo_pendiente(&self, id_cliente: Uuid) -> ApiResult<BigDecimal>;
+
+    /// Anula la venta en una transacción: devuelve al inventario lo vendido (ver
+    /// `devoluciones_de_linea`), los números de serie vendidos pasan a DEVUELTO y los pagos se
+    /// desactivan. Devuelve los ids de las ENTRADAS registradas.
+    fn anular(&self, id_venta: Uuid, id_persona: Uuid, motivo: Option<String>) -> ApiResult<Vec<Uuid>>;
+}
+
+pub struct PgVentaRepository {
     pool: DbPool,
+    metodo_valorizacion: MetodoValorizacion,
 }
 
-impl VentaRepository {
-    pub fn new(pool: DbPool) -> Self {
-        VentaRepository { pool }
+impl PgVentaRepository {
+    pub fn new(pool: DbPool, metodo_valorizacion: MetodoValorizacion) -> Self {
+        PgVentaRepository { pool, metodo_valorizacion }
     }
 
     fn get_connection(&self) -> ApiResult<DbConnection> {
@@ -23,34 +99,117 @@ impl VentaRepository {
             .get()
             .map_err(|e| ApiError::DatabaseError(e.to_string()))
     }
+}
 
-    pub fn guardar_con_detalles(
+impl VentaRepository for PgVentaRepository {
+    #[instrument(skip(self, venta, detalles, series, pagos))]
+    fn guardar_con_detalles(
         &self,
         venta: NuevaVenta,
         detalles: Vec<NuevoDetalleVenta>,
+        series: Vec<NuevaSerieDetalleVenta>,
+        pagos: Vec<NuevoPago>,
     ) -> ApiResult<Uuid> {
         let mut conn = self.get_connection()?;
 
         // Use a transaction to ensure atomicity
-        conn.transaction(|conn| {
+        conn.transaction::<Uuid, ApiError, _>(|conn| {
+            auditoria::contexto::aplicar(conn)?;
+
+            if let Some(id_sesion_caja) = venta.id_sesion_caja {
+                caja_repo::sesion_abierta_compartida(conn, id_sesion_caja)?;
+            }
+
+            // Bloquear al cliente para que dos ventas con saldo simultáneas no superen su límite
+            if venta.estado == EstadoVenta::PendientePago {
+                let cliente = personas::table
+                    .find(venta.id_persona)
+                    .select(Persona::as_select())
+                    .for_update()
+                    .first(conn)
+                    .map_err(|e| match e {
+                        diesel::result::Error::NotFound => persona_no_encontrada(venta.id_persona),
+                        _ => ApiError::DatabaseError(e.to_string()),
+                    })?;
+                let a_credito = &venta.monto - pagos.iter().map(|pago| &pago.monto).sum::<BigDecimal>();
+                if a_credito > BigDecimal::from(0) {
+                    credito::validar_credito(&cliente, &saldo_pendiente_de(conn, venta.id_persona)?, &a_credito)?;
+                }
+            }
+
             // Insert sale header
             diesel::insert_into(ventas::table)
                 .values(&venta)
-                .execute(conn)?;
+                .execute(conn)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+            // Una cotización se convierte en una sola venta
+            if let Some(id_cotizacion) = venta.id_cotizacion {
+                cotizacion_repo::convertir_en_venta(conn, id_cotizacion, venta.id, venta.fecha.date())?;
+            }
+
+            // Un pedido se entrega con una sola venta, con lo que salió en sus despachos
+            let mut salidas_pedido = match venta.id_pedido {
+                Some(id_pedido) => Some(pedido_repo::entregar_en_venta(conn, id_pedido, venta.id)?.into_iter()),
+                None => None,
+            };
+
+            // Insert sale details, taking the units from the lots that expire first and costing
+            // them with the cost layers they consume
+            for mut detalle in detalles {
+                let asignacion = match salidas_pedido.as_mut() {
+                    Some(salidas) => {
+                        let salida = pedido_repo::salida_de_detalle(salidas.next(), &detalle)?;
+                        detalle.costo = salida.costo;
+                        salida.lotes
+                    }
+                    None => {
+                        let asignacion = inventario_repo::consumir_fefo(conn, detalle.id_producto, detalle.cantidad)?;
+                        let stock_actual = inventario_repo::stock_bloqueado(conn, detalle.id_producto)?;
+                        let consumo =
+                            inventario_repo::consumir_capas(conn, detalle.id_producto, stock_actual, detalle.cantidad, None)?;
+                        detalle.costo = costos::redondear_importe(&consumo.costo_total);
+                        asignacion
+                    }
+                };
 
-            // Insert sale details
-            for detalle in detalles {
                 diesel::insert_into(detalle_ventas::table)
                     .values(&detalle)
-                    .execute(conn)?;
+                    .execute(conn)
+                    .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+                let lotes: Vec<NuevoLoteDetalleVenta> = asignacion
+                    .into_iter()
+                    .map(|(id_lote, cantidad)| NuevoLoteDetalleVenta {
+                        id_detalle_venta: detalle.id,
+                        id_lote,
+                        cantidad,
+                    })
+                    .collect();
+                diesel::insert_into(detalle_ventas_lotes::table)
+                    .values(&lotes)
+                    .execute(conn)
+                    .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
             }
 
+            let ids_serie: Vec<Uuid> = series.iter().map(|serie| serie.id_serie).collect();
+            inventario_repo::vender_series(conn, &ids_serie)?;
+            diesel::insert_into(detalle_ventas_series::table)
+                .values(&series)
+                .execute(conn)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+            diesel::insert_into(pagos::table)
+                .values(&pagos)
+                .execute(conn)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
             Ok(venta.id)
         })
-        .map_err(|e: diesel::result::Error| ApiError::DatabaseError(e.to_string()))
     }
 
-    pub fn buscar_por_id(&self, id: Uuid) -> ApiResult<(Venta, Vec<DetalleVenta>)> {
+    #[instrument(skip(self))]
+    fn buscar_por_id(&self, id: Uuid) -> ApiResult<(Venta, Vec<DetalleVenta>)> {
         let mut conn = self.get_connection()?;
 
         let venta = ventas::table
@@ -59,7 +218,7 @@ impl VentaRepository {
             .select(Venta::as_select())
             .first(&mut conn)
             .map_err(|e| match e {
-                diesel::result::Error::NotFound => ApiError::NotFound(format!("Venta con ID {} no encontrada", id)),
+                diesel::result::Error::NotFound => venta_no_encontrada(id),
                 _ => ApiError::DatabaseError(e.to_string()),
             })?;
 
@@ -73,12 +232,15 @@ impl VentaRepository {
         Ok((venta, detalles))
     }
 
-    pub fn listar(
+    #[instrument(skip(self))]
+    fn listar(
         &self,
         id_cliente: Option<Uuid>,
         sucursal: Option<String>,
         fecha_desde: Option<NaiveDateTime>,
         fecha_hasta: Option<NaiveDateTime>,
+        estado: Option<EstadoVenta>,
+        metodo_pago: Option<MetodoPago>,
     ) -> ApiResult<Vec<Venta>> {
         let mut conn = self.get_connection()?;
 
@@ -102,6 +264,21 @@ impl VentaRepository {
             query = query.filter(ventas::fecha.le(hasta));
         }
 
+        if let Some(estado) = estado {
+            query = query.filter(ventas::estado.eq(estado));
+        }
+
+        if let Some(metodo) = metodo_pago {
+            query = query.filter(
+                ventas::id.eq_any(
+                    pagos::table
+                        .filter(pagos::metodo.eq(metodo))
+                        .filter(pagos::activo.eq(true))
+                        .select(pagos::id_venta),
+                ),
+            );
+        }
+
         query
             .order(ventas::fecha.desc())
             .select(Venta::as_select())
@@ -109,7 +286,8 @@ impl VentaRepository {
             .map_err(|e| ApiError::DatabaseError(e.to_string()))
     }
 
-    pub fn obtener_detalles(&self, id_venta: Uuid) -> ApiResult<Vec<DetalleVenta>> {
+    #[instrument(skip(self))]
+    fn obtener_detalles(&self, id_venta: Uuid) -> ApiResult<Vec<DetalleVenta>> {
         let mut conn = self.get_connection()?;
 
         detalle_ventas::table
@@ -119,4 +297,299 @@ impl VentaRepository {
             .load(&mut conn)
             .map_err(|e| ApiError::DatabaseError(e.to_string()))
     }
+
+    #[instrument(skip(self))]
+    fn obtener_lotes_detalle(&self, id_detalle_venta: Uuid) -> ApiResult<Vec<LoteDetalleVenta>> {
+        let mut conn = self.get_connection()?;
+
+        detalle_ventas_lotes::table
+            .inner_join(lotes::table)
+            .filter(detalle_ventas_lotes::id_detalle_venta.eq(id_detalle_venta))
+            .filter(detalle_ventas_lotes::activo.eq(true))
+            .order((lotes::fecha_vencimiento.asc(), lotes::codigo.asc()))
+            .select(LoteDetalleVenta::as_select())
+            .load(&mut conn)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+    }
+
+    #[instrument(skip(self))]
+    fn obtener_series_detalle(&self, id_detalle_venta: Uuid) -> ApiResult<Vec<NumeroSerie>> {
+        let mut conn = self.get_connection()?;
+
+        detalle_ventas_series::table
+            .inner_join(numeros_serie::table)
+            .filter(detalle_ventas_series::id_detalle_venta.eq(id_detalle_venta))
+            .filter(detalle_ventas_series::activo.eq(true))
+            .order(numeros_serie::numero.asc())
+            .select(NumeroSerie::as_select())
+            .load(&mut conn)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+    }
+
+    #[instrument(skip(self))]
+    fn listar_ventas_de_serie(&self, id_serie: Uuid) -> ApiResult<Vec<Venta>> {
+        let mut conn = self.get_connection()?;
+
+        detalle_ventas_series::table
+            .inner_join(detalle_ventas::table.inner_join(ventas::table))
+            .filter(detalle_ventas_series::id_serie.eq(id_serie))
+            .filter(detalle_ventas_series::activo.eq(true))
+            .filter(ventas::activo.eq(true))
+            .order(ventas::fecha.desc())
+            .select(Venta::as_select())
+            .load(&mut conn)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+    }
+
+    #[instrument(skip(self))]
+    fn obtener_pagos(&self, id_venta: Uuid) -> ApiResult<Vec<Pago>> {
+        let mut conn = self.get_connection()?;
+
+        pagos::table
+            .filter(pagos::id_venta.eq(id_venta))
+            .filter(pagos::activo.eq(true))
+            .order((pagos::fecha.asc(), pagos::fecha_creacion.asc()))
+            .select(Pago::as_select())
+            .load(&mut conn)
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))
+    }
+
+    #[instrument(skip(self, pagos))]
+    fn registrar_pagos(&self, id_venta: Uuid, pagos: Vec<NuevoPago>) -> ApiResult<()> {
+        let mut conn = self.get_connection()?;
+
+        conn.transaction::<(), ApiError, _>(|conn| {
+            auditoria::contexto::aplicar(conn)?;
+
+            // Los pagos de un mismo cobro van todos a la misma sesión
+            if let Some(id_sesion_caja) = pagos.first().and_then(|pago| pago.id_sesion_caja) {
+                caja_repo::sesion_abierta_compartida(conn, id_sesion_caja)?;
+            }
+
+            // Bloquear la venta para que dos cobros simultáneos no la paguen dos veces
+            let venta = venta_bloqueada(conn, id_venta)?;
+            validar_pendiente_de_pago(&venta)?;
+            let pagado: Option<BigDecimal> = pagos::table
+                .filter(pagos::id_venta.eq(id_venta))
+                .filter(pagos::activo.eq(true))
+                .select(diesel::dsl::sum(pagos::monto))
+                .first(conn)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+            let saldo = saldo_tras_pagos(&venta, &pagado.unwrap_or_default(), &pagos)?;
+
+            diesel::insert_into(pagos::table)
+                .values(&pagos)
+                .execute(conn)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+            if saldo == BigDecimal::from(0) {
+                diesel::update(ventas::table.find(id_venta))
+                    .set(ventas::estado.eq(EstadoVenta::Pagada))
+                    .execute(conn)
+                    .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+            }
+
+            Ok(())
+        })
+    }
+
+    #[instrument(skip(self))]
+    fn saldo_pendiente(&self, id_cliente: Uuid) -> ApiResult<BigDecimal> {
+        let mut conn = self.get_connection()?;
+        saldo_pendiente_de(&mut conn, id_cliente)
+    }
+
+    #[instrument(skip(self, motivo))]
+    fn anular(&self, id_venta: Uuid, id_persona: Uuid, motivo: Option<String>) -> ApiResult<Vec<Uuid>> {
+        let mut conn = self.get_connection()?;
+
+        conn.transaction::<Vec<Uuid>, ApiError, _>(|conn| {
+            auditoria::contexto::aplicar(conn)?;
+
+            let venta = venta_bloqueada(conn, id_venta)?;
+            validar_anulable(&venta)?;
+            let sesiones_pagos: Vec<Option<Uuid>> = pagos::table
+                .filter(pagos::id_venta.eq(id_venta))
+                .filter(pagos::activo.eq(true))
+                .select(pagos::id_sesion_caja)
+                .load(conn)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+            for id_sesion_caja in sesiones_afectadas(&venta, sesiones_pagos) {
+                caja_repo::sesion_abierta_compartida(conn, id_sesion_caja)?;
+            }
+
+            let detalles = detalle_ventas::table
+                .filter(detalle_ventas::id_venta.eq(id_venta))
+                .filter(detalle_ventas::activo.eq(true))
+                .select(DetalleVenta::as_select())
+                .load(conn)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+            let mut entradas = Vec::new();
+            for detalle in &detalles {
+                let lotes_linea = detalle_ventas_lotes::table
+                    .filter(detalle_ventas_lotes::id_detalle_venta.eq(detalle.id))
+                    .filter(detalle_ventas_lotes::activo.eq(true))
+                    .select(LoteDetalleVenta::as_select())
+                    .load(conn)
+                    .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+                let numeros: Vec<String> = detalle_ventas_series::table
+                    .inner_join(numeros_serie::table)
+                    .filter(detalle_ventas_series::id_detalle_venta.eq(detalle.id))
+                    .filter(detalle_ventas_series::activo.eq(true))
+                    .select(numeros_serie::numero)
+                    .load(conn)
+                    .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+                let mut ids_linea = Vec::new();
+                for devolucion in devoluciones_de_linea(detalle, &lotes_linea, id_persona, motivo.as_deref()) {
+                    ids_linea.push(inventario_repo::devolver_al_stock(conn, self.metodo_valorizacion, devolucion)?);
+                }
+                // Los números de serie vuelven con la primera ENTRADA de la línea
+                if let Some(id_movimiento) = ids_linea.first().filter(|_| !numeros.is_empty()) {
+                    inventario_repo::aplicar_series(
+                        conn,
+                        detalle.id_producto,
+                        TipoMovimiento::Entrada,
+                        &numeros,
+                        *id_movimiento,
+                    )?;
+                }
+                entradas.extend(ids_linea);
+            }
+
+            diesel::update(pagos::table.filter(pagos::id_venta.eq(id_venta)).filter(pagos::activo.eq(true)))
+                .set(pagos::activo.eq(false))
+                .execute(conn)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+            diesel::update(ventas::table.find(id_venta))
+                .set(ventas::estado.eq(EstadoVenta::Anulada))
+                .execute(conn)
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+            Ok(entradas)
+        })
+    }
+}
+
+/// Venta activa, bloqueada hasta el final de la transacción
+fn venta_bloqueada(conn: &mut PgConnection, id_venta: Uuid) -> ApiResult<Venta> {
+    ventas::table
+        .find(id_venta)
+        .filter(ventas::activo.eq(true))
+        .select(Venta::as_select())
+        .for_update()
+        .first(conn)
+        .map_err(|e| match e {
+            diesel::result::Error::NotFound => venta_no_encontrada(id_venta),
+            _ => ApiError::DatabaseError(e.to_string()),
+        })
+}
+
+/// Lo que el cliente debe entre sus ventas PENDIENTE_PAGO: su total menos sus pagos vigentes
+fn saldo_pendiente_de(conn: &mut PgConnection, id_cliente: Uuid) -> ApiResult<BigDecimal> {
+    let cargos: Option<BigDecimal> = ventas::table
+        .filter(ventas::id_persona.eq(id_cliente))
+        .filter(ventas::activo.eq(true))
+        .filter(ventas::estado.eq(EstadoVenta::PendientePago))
+        .select(diesel::dsl::sum(ventas::monto))
+        .first(conn)
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+    let abonos: Option<BigDecimal> = pagos::table
+        .inner_join(ventas::table)
+        .filter(ventas::id_persona.eq(id_cliente))
+        .filter(ventas::activo.eq(true))
+        .filter(ventas::estado.eq(EstadoVenta::PendientePago))
+        .filter(pagos::activo.eq(true))
+        .select(diesel::dsl::sum(pagos::monto))
+        .first(conn)
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+    Ok(cargos.unwrap_or_default() - abonos.unwrap_or_default())
+}
+
+/// ENTRADAS que devuelven al inventario lo que salió en una línea de venta: una por cada lote
+/// del que tomó unidades y otra por las que salieron sin lote, todas al costo unitario de la
+/// línea
+pub(crate) fn devoluciones_de_linea(
+    detalle: &DetalleVenta,
+    lotes_linea: &[LoteDetalleVenta],
+    id_persona: Uuid,
+    motivo: Option<&str>,
+) -> Vec<NuevoMovimiento> {
+    let costo_unitario = if detalle.cantidad > 0 {
+        costos::redondear_costo(&(&detalle.costo / BigDecimal::from(detalle.cantidad)))
+    } else {
+        BigDecimal::from(0)
+    };
+    let mut observaciones = format!("Anulación de la venta {}", detalle.id_venta);
+    if let Some(motivo) = motivo.map(str::trim).filter(|motivo| !motivo.is_empty()) {
+        observaciones.push_str(": ");
+        observaciones.push_str(motivo);
+    }
+
+    let sin_lote = detalle.cantidad - lotes_linea.iter().map(|consumo| consumo.cantidad).sum::<i32>();
+    lotes_linea
+        .iter()
+        .map(|consumo| (Some(consumo.id_lote), consumo.cantidad))
+        .chain((sin_lote > 0).then_some((None, sin_lote)))
+        .map(|(id_lote, cantidad)| NuevoMovimiento {
+            id_producto: detalle.id_producto,
+            tipo_movimiento: TipoMovimiento::Entrada,
+            fecha: Utc::now().naive_utc(),
+            id_persona,
+            cantidad,
+            observaciones: Some(observaciones.clone()),
+            id_movimiento_revertido: None,
+            id_lote,
+            costo_unitario: Some(costo_unitario.clone()),
+            id_detalle_pedido: None,
+        })
+        .collect()
+}
+
+/// Solo se cobran las ventas que esperan su pago
+pub(crate) fn validar_pendiente_de_pago(venta: &Venta) -> ApiResult<()> {
+    match venta.estado {
+        EstadoVenta::PendientePago => Ok(()),
+        EstadoVenta::Pagada => Err(ApiError::BusinessRuleViolation(format!("La venta {} ya está pagada", venta.id))),
+        EstadoVenta::Anulada => Err(venta_anulada(venta.id)),
+    }
+}
+
+/// Saldo de la venta tras sumar los pagos `nuevos` a lo ya `pagado`; falla si lo superan
+pub(crate) fn saldo_tras_pagos(venta: &Venta, pagado: &BigDecimal, nuevos: &[NuevoPago]) -> ApiResult<BigDecimal> {
+    let saldo = &venta.monto - pagado;
+    let abonado: BigDecimal = nuevos.iter().map(|pago| &pago.monto).sum();
+    if abonado > saldo {
+        return Err(ApiError::BusinessRuleViolation(format!(
+            "Los pagos ({}) superan el saldo de la venta {} ({})",
+            abonado, venta.id, saldo
+        )));
+    }
+    Ok(saldo - abonado)
+}
+
+pub(crate) fn validar_anulable(venta: &Venta) -> ApiResult<()> {
+    match venta.estado {
+        EstadoVenta::Anulada => Err(venta_anulada(venta.id)),
+        EstadoVenta::PendientePago | EstadoVenta::Pagada => Ok(()),
+    }
+}
+
+/// Sesiones de caja que registraron la venta o cobraron sus pagos vigentes. Anularla cambia su
+/// resumen y reembolsa lo cobrado, así que solo se anula mientras todas siguen abiertas.
+pub(crate) fn sesiones_afectadas(venta: &Venta, sesiones_pagos: impl IntoIterator<Item = Option<Uuid>>) -> Vec<Uuid> {
+    let mut sesiones: Vec<Uuid> = sesiones_pagos.into_iter().chain([venta.id_sesion_caja]).flatten().collect();
+    // Siempre en el mismo orden, para bloquearlas sin interbloqueos
+    sesiones.sort();
+    sesiones.dedup();
+    sesiones
+}
+
+pub(crate) fn venta_anulada(id: Uuid) -> ApiError {
+    ApiError::BusinessRuleViolation(format!("La venta {} está anulada", id))
+}
+
+pub(crate) fn venta_no_encontrada(id: Uuid) -> ApiError {
+    ApiError::NotFound(format!("Venta con ID {} no encontrada", id))
 }
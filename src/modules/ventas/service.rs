@@ -1,126 +1,385 @@
-use bigdecimal::{BigDecimal, ToPrimitive};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use bigdecimal::{BigDecimal, ToPrimitive, Zero};
 use chrono::{Utc, NaiveDateTime};
 use uuid::Uuid;
+use tracing::instrument;
+use crate::metrics::metrics;
+use crate::modules::cajas::repository::CajaRepository;
+use crate::modules::cajas::service as caja_service;
 use crate::modules::common::errors::{ApiError, ApiResult};
+use crate::modules::catalogo::repository::CatalogoRepository;
+use crate::modules::comisiones::reglas;
+use crate::modules::comisiones::repository::ComisionRepository;
+use crate::modules::common::types::{EstadoVenta, MetodoPago, TipoMovimiento};
+use crate::modules::cuentas::credito;
 use crate::modules::ventas::model::{
-    CrearVentaRequest, VentaCreadaResponse, VentaResponse, DetalleVentaResponse,
-    NuevaVenta, NuevoDetalleVenta,
+    AnularVentaRequest, CrearVentaRequest, VentaCreadaResponse, VentaResponse, DetalleVenta, DetalleVentaResponse,
+    LoteConsumidoResponse, NuevaSerieDetalleVenta, NuevaVenta, NuevoDetalleVenta, NuevoPago, PagoRequest, PagoResponse,
+    RegistrarPagosRequest, SerieResponse, Venta, VentaSerieResponse,
 };
-use crate::modules::ventas::repository::VentaRepository;
+use crate::modules::ventas::pagos::{self, PagoAplicado, PagoRecibido};
+use crate::modules::ventas::repository::{self as venta_repo, VentaRepository};
 use crate::modules::personas::repository::PersonaRepository;
+use crate::modules::precios::lineas::{self, Encabezado, LineaConPrecio};
+use crate::modules::precios::model::{OrigenPrecio, PrecioLinea};
+use crate::modules::precios::repository::PrecioRepository;
+use crate::modules::productos::model::Producto;
 use crate::modules::productos::repository::ProductoRepository;
 use crate::modules::inventarios::repository::InventarioRepository;
+use crate::modules::inventarios::series;
+
+/// Cotización de la que sale una venta
+pub struct OrigenCotizacion {
+    pub id_cotizacion: Uuid,
+    /// Precio cotizado de cada línea, en el orden de los detalles; None para calcularlos de
+    /// nuevo al vender
+    pub precios: Option<Vec<PrecioLinea>>,
+}
+
+/// Pedido despachado que se entrega con una venta
+pub struct OrigenPedido {
+    pub id_pedido: Uuid,
+    /// Precio fijado al crear el pedido para cada línea, en el orden de los detalles
+    pub precios: Vec<PrecioLinea>,
+}
+
+/// De dónde sale una venta que no se registra directamente en caja
+enum Origen {
+    Cotizacion(OrigenCotizacion),
+    Pedido(OrigenPedido),
+}
 
 pub struct VentaService {
-    venta_repo: VentaRepository,
-    persona_repo: PersonaRepository,
-    producto_repo: ProductoRepository,
-    inventario_repo: InventarioRepository,
+    venta_repo: Arc<dyn VentaRepository>,
+    persona_repo: Arc<dyn PersonaRepository>,
+    producto_repo: Arc<dyn ProductoRepository>,
+    inventario_repo: Arc<dyn InventarioRepository>,
+    comision_repo: Arc<dyn ComisionRepository>,
+    catalogo_repo: Arc<dyn CatalogoRepository>,
+    caja_repo: Arc<dyn CajaRepository>,
+    precio_repo: Arc<dyn PrecioRepository>,
 }
 
 impl VentaService {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
-        venta_repo: VentaRepository,
-        persona_repo: PersonaRepository,
-        producto_repo: ProductoRepository,
-        inventario_repo: InventarioRepository,
+        venta_repo: Arc<dyn VentaRepository>,
+        persona_repo: Arc<dyn PersonaRepository>,
+        producto_repo: Arc<dyn ProductoRepository>,
+        inventario_repo: Arc<dyn InventarioRepository>,
+        comision_repo: Arc<dyn ComisionRepository>,
+        catalogo_repo: Arc<dyn CatalogoRepository>,
+        caja_repo: Arc<dyn CajaRepository>,
+        precio_repo: Arc<dyn PrecioRepository>,
     ) -> Self {
         VentaService {
             venta_repo,
             persona_repo,
             producto_repo,
             inventario_repo,
+            comision_repo,
+            catalogo_repo,
+            caja_repo,
+            precio_repo,
         }
     }
 
     /// RF1: Procesar una venta con descuento automático de inventario
+    #[instrument(skip(self, request), fields(id_cliente = %request.id_cliente))]
     pub fn procesar_venta(&self, request: CrearVentaRequest) -> ApiResult<VentaCreadaResponse> {
-        // 1. Validar que el cliente existe y está activo
-        let id_cliente = Uuid::parse_str(&request.id_cliente)
-            .map_err(|_| ApiError::InvalidInput("ID de cliente inválido".to_string()))?;
+        self.registrar_venta(request, None)
+    }
 
-        let cliente = self.persona_repo.buscar_por_id(id_cliente)?;
-        if !cliente.activo {
-            return Err(ApiError::InactiveClient);
+    /// Venta de una cotización aceptada. Pasa por las mismas validaciones que cualquier venta
+    /// (stock, caja, pagos, crédito) y la cotización queda CONVERTIDA en la misma transacción
+    #[instrument(skip(self, request, origen), fields(id_cotizacion = %origen.id_cotizacion))]
+    pub fn convertir_cotizacion(
+        &self,
+        request: CrearVentaRequest,
+        origen: OrigenCotizacion,
+    ) -> ApiResult<VentaCreadaResponse> {
+        if let Some(precios) = &origen.precios {
+            if precios.len() != request.detalles.len() {
+                return Err(ApiError::InternalError(
+                    "La cotización no tiene un precio por línea".to_string(),
+                ));
+            }
+        }
+        self.registrar_venta(request, Some(Origen::Cotizacion(origen)))
+    }
+
+    /// Venta final de un pedido DESPACHADO, con los precios del pedido. Sus unidades ya
+    /// salieron del stock con los despachos, así que no se vuelve a validar ni a descontar el
+    /// stock: el costo y los lotes de cada línea son los de sus despachos. El pedido queda
+    /// ENTREGADO en la misma transacción
+    #[instrument(skip(self, request, origen), fields(id_pedido = %origen.id_pedido))]
+    pub fn entregar_pedido(&self, request: CrearVentaRequest, origen: OrigenPedido) -> ApiResult<VentaCreadaResponse> {
+        if origen.precios.len() != request.detalles.len() {
+            return Err(ApiError::InternalError("El pedido no tiene un precio por línea".to_string()));
+        }
+        self.registrar_venta(request, Some(Origen::Pedido(origen)))
+    }
+
+    fn registrar_venta(
+        &self,
+        request: CrearVentaRequest,
+        origen: Option<Origen>,
+    ) -> ApiResult<VentaCreadaResponse> {
+        // 1. Validar que el cliente existe y está activo, y el vendedor si se indica
+        let Encabezado { cliente, id_vendedor, sucursal } = lineas::validar_encabezado(
+            self.persona_repo.as_ref(),
+            &request.id_cliente,
+            request.id_vendedor.as_deref(),
+            request.sucursal.as_deref(),
+        )?;
+        let id_cliente = cliente.id;
+
+        // La venta se registra en una caja abierta de su misma sucursal
+        let sesion = caja_service::sesion_para_cobrar(self.caja_repo.as_ref(), &request.id_sesion_caja)?;
+        if let Some(sucursal) = sucursal.as_deref() {
+            if !sucursal.eq_ignore_ascii_case(&sesion.sucursal) {
+                return Err(ApiError::BusinessRuleViolation(format!(
+                    "La venta es de la sucursal '{}' pero la sesión de caja es de '{}'",
+                    sucursal, sesion.sucursal
+                )));
+            }
         }
 
         // 2. Validar que hay detalles
         if request.detalles.is_empty() {
             return Err(ApiError::InvalidInput("La venta debe tener al menos un detalle".to_string()));
         }
+        let pagos_recibidos = pagos_desde_request(&request.pagos)?;
 
-        // 3. Validar stock y calcular total
+        // Reglas de comisión y árbol de categorías, solo si hay vendedor al que pagarla
+        let (reglas_comision, categorias) = match id_vendedor {
+            Some(_) => (self.comision_repo.listar_reglas()?, self.catalogo_repo.listar_categorias()?),
+            None => (Vec::new(), Vec::new()),
+        };
+
+        // 3. Validar stock y calcular total. El stock libre (lo no reservado para pedidos) se
+        // compara en la unidad base, sumando las líneas del mismo producto (p.ej. una caja y
+        // unidades sueltas). Las promociones se evalúan en el momento de la venta, salvo que se
+        // mantengan los precios de la cotización o del pedido
+        let fecha_actual = Utc::now().naive_utc();
         let mut total = BigDecimal::from(0);
         let mut detalles_validados = Vec::new();
+        let mut requerido_por_producto: HashMap<Uuid, i32> = HashMap::new();
+        let mut series_en_venta: HashSet<Uuid> = HashSet::new();
+
+        let (id_cotizacion, id_pedido, precios_fijados) = match origen {
+            Some(Origen::Cotizacion(origen)) => (Some(origen.id_cotizacion), None, origen.precios),
+            Some(Origen::Pedido(origen)) => (None, Some(origen.id_pedido), Some(origen.precios)),
+            None => (None, None, None),
+        };
+        let mut precios_fijados = precios_fijados.map(Vec::into_iter);
 
         for detalle_req in &request.detalles {
-            let id_producto = Uuid::parse_str(&detalle_req.id_producto)
-                .map_err(|_| ApiError::InvalidInput("ID de producto inválido".to_string()))?;
+            // Producto, unidad y precio con la lista del cliente y la mejor promoción vigente
+            let LineaConPrecio { producto, unidad, cantidad, cantidad_base, precio } = lineas::linea_con_precio(
+                self.producto_repo.as_ref(),
+                self.precio_repo.as_ref(),
+                cliente.id_lista_precios,
+                &detalle_req.id_producto,
+                detalle_req.unidad.as_deref(),
+                detalle_req.cantidad,
+                precios_fijados.as_mut().and_then(Iterator::next),
+                fecha_actual,
+            )?;
+            let id_producto = producto.id;
+            let ids_serie = self.series_de_linea(
+                &producto,
+                cantidad_base,
+                &series::normalizar_numeros(&detalle_req.numeros_serie)?,
+                &mut series_en_venta,
+            )?;
 
-            // Verificar que el producto existe
-            let producto = self.producto_repo.buscar_por_id(id_producto)?;
+            let requerido = requerido_por_producto.entry(id_producto).or_insert(0);
+            *requerido = requerido.checked_add(cantidad_base).ok_or_else(|| {
+                ApiError::InvalidInput(format!("La cantidad total de '{}' es demasiado grande", producto.nombre))
+            })?;
 
-            // Validar cantidad positiva
-            if detalle_req.cantidad <= 0 {
-                return Err(ApiError::InvalidInput("La cantidad debe ser mayor a 0".to_string()));
+            // Validar stock suficiente; el de un pedido ya salió con sus despachos
+            if id_pedido.is_none() {
+                let stock_libre = self.inventario_repo.obtener_stock(id_producto)?
+                    - self.inventario_repo.obtener_reservado(id_producto)?;
+                if stock_libre < *requerido {
+                    metrics().rechazos_stock_insuficiente.with_label_values(&["venta"]).inc();
+                    return Err(ApiError::BusinessRuleViolation(
+                        format!("Stock insuficiente para el producto '{}'. Disponible: {} {}, Requerido: {}",
+                                producto.nombre, stock_libre, producto.unidad_venta, *requerido)
+                    ));
+                }
             }
 
-            // Validar stock suficiente
-            let stock_actual = self.inventario_repo.obtener_stock(id_producto)?;
-            if stock_actual < detalle_req.cantidad {
-                return Err(ApiError::BusinessRuleViolation(
-                    format!("Stock insuficiente para el producto '{}'. Disponible: {}, Requerido: {}",
-                            producto.nombre, stock_actual, detalle_req.cantidad)
-                ));
-            }
-
-            // Calcular subtotal
-            let subtotal = &producto.precio_unitario * BigDecimal::from(detalle_req.cantidad);
+            let subtotal = precio.subtotal.clone();
             total += &subtotal;
 
-            detalles_validados.push((id_producto, detalle_req.cantidad, subtotal));
+            let regla = id_vendedor.and_then(|id_vendedor| {
+                let categorias_producto = reglas::categoria_y_ancestros(producto.id_categoria, &categorias);
+                reglas::regla_aplicable(&reglas_comision, id_vendedor, &categorias_producto)
+            });
+            let comision = match regla {
+                Some(regla) => (reglas::calcular_comision(&subtotal, &regla.porcentaje), Some(regla.id)),
+                None => (BigDecimal::from(0), None),
+            };
+
+            detalles_validados.push((id_producto, unidad.id, cantidad, cantidad_base, precio, ids_serie, comision));
         }
 
-        // 4. Crear la venta
+        // 4. Repartir los pagos sobre el total; sin pagos, la venta queda pendiente de cobro. A
+        // crédito basta con abonar una parte. Lo que quede sin pagar, a crédito o pendiente,
+        // debe caber en el crédito del cliente
+        let pagos_aplicados = if request.credito {
+            pagos::aplicar_abonos(&total, pagos_recibidos)?
+        } else if pagos_recibidos.is_empty() {
+            Vec::new()
+        } else {
+            pagos::aplicar_pagos(&total, pagos_recibidos)?
+        };
+        let cambio: BigDecimal = pagos_aplicados.iter().map(|pago| &pago.cambio).sum();
+        let saldo = &total - pagos_aplicados.iter().map(|pago| &pago.monto).sum::<BigDecimal>();
+        if saldo > BigDecimal::zero() {
+            credito::validar_credito(&cliente, &self.venta_repo.saldo_pendiente(id_cliente)?, &saldo)?;
+        }
+        let estado = if saldo > BigDecimal::zero() { EstadoVenta::PendientePago } else { EstadoVenta::Pagada };
+
+        // 5. Crear la venta
         let venta_id = Uuid::new_v4();
-        let fecha_actual = Utc::now().naive_utc();
 
         let nueva_venta = NuevaVenta {
             id: venta_id,
             id_persona: id_cliente,
             fecha: fecha_actual,
             monto: total.clone(),
-            sucursal: request.sucursal.clone(),
+            sucursal,
+            id_vendedor,
+            estado,
+            credito: request.credito,
+            id_sesion_caja: Some(sesion.id),
+            id_cotizacion,
+            id_pedido,
         };
 
-        let nuevos_detalles: Vec<NuevoDetalleVenta> = detalles_validados
-            .iter()
-            .map(|(id_producto, cantidad, monto)| NuevoDetalleVenta {
-                id: Uuid::new_v4(),
+        let mut nuevos_detalles = Vec::with_capacity(detalles_validados.len());
+        let mut series_vendidas = Vec::new();
+        for (id_producto, id_unidad, cantidad_unidad, cantidad_base, precio, ids_serie, (comision, id_regla_comision)) in
+            &detalles_validados
+        {
+            let id_detalle = Uuid::new_v4();
+            series_vendidas.extend(ids_serie.iter().map(|id_serie| NuevaSerieDetalleVenta {
+                id_detalle_venta: id_detalle,
+                id_serie: *id_serie,
+            }));
+            nuevos_detalles.push(NuevoDetalleVenta {
+                id: id_detalle,
                 id_venta: venta_id,
                 id_producto: *id_producto,
-                cantidad: *cantidad,
-                monto: monto.clone(),
-            })
-            .collect();
+                cantidad: *cantidad_base,
+                monto: precio.subtotal.clone(),
+                id_unidad: *id_unidad,
+                cantidad_unidad: *cantidad_unidad,
+                costo: BigDecimal::from(0),
+                comision: comision.clone(),
+                id_regla_comision: *id_regla_comision,
+                id_lista_precios: precio.id_lista_precios,
+                id_promocion: precio.id_promocion,
+                descuento: precio.descuento.clone(),
+            });
+        }
+
+        let pagos_venta = nuevos_pagos(venta_id, pagos_aplicados, Some(sesion.id));
 
-        // 5. Guardar venta en transacción (esto también descuenta el inventario automáticamente
+        // 6. Guardar venta en transacción (esto también descuenta el inventario automáticamente
         // gracias a los triggers de la base de datos)
-        self.venta_repo.guardar_con_detalles(nueva_venta, nuevos_detalles)?;
+        self.venta_repo.guardar_con_detalles(nueva_venta, nuevos_detalles, series_vendidas, pagos_venta)?;
+
+        let metrics = metrics();
+        metrics.ventas_creadas.inc();
+        for (id_producto, _, _, cantidad, _, _, _) in &detalles_validados {
+            metrics.unidades_vendidas
+                .with_label_values(&[id_producto.to_string().as_str()])
+                .inc_by(*cantidad as u64);
+            // El trigger de la base de datos registra una SALIDA por cada detalle, salvo en los
+            // pedidos, que las registraron al despachar
+            if id_pedido.is_none() {
+                metrics.movimientos_inventario
+                    .with_label_values(&[TipoMovimiento::Salida.as_str()])
+                    .inc();
+            }
+        }
 
         Ok(VentaCreadaResponse {
             id: venta_id.to_string(),
             mensaje: format!("Venta creada exitosamente. Total: ${:.2}", total.to_f64().unwrap_or(0.0)),
+            estado,
+            cambio: cambio.to_f64().unwrap_or(0.0),
+            saldo: saldo.to_f64().unwrap_or(0.0),
         })
     }
 
+    /// Cobrar una venta PENDIENTE_PAGO: los pagos deben cubrir su saldo, salvo en las ventas a
+    /// crédito, que admiten abonos parciales
+    #[instrument(skip(self, request))]
+    pub fn registrar_pagos(&self, id_str: &str, request: RegistrarPagosRequest) -> ApiResult<VentaResponse> {
+        let id = Uuid::parse_str(id_str)
+            .map_err(|_| ApiError::InvalidInput("ID de venta inválido".to_string()))?;
+
+        let pagos_recibidos = pagos_desde_request(&request.pagos)?;
+        if pagos_recibidos.is_empty() {
+            return Err(ApiError::InvalidInput("Indique al menos un pago".to_string()));
+        }
+
+        let sesion = caja_service::sesion_para_cobrar(self.caja_repo.as_ref(), &request.id_sesion_caja)?;
+
+        let (venta, _) = self.venta_repo.buscar_por_id(id)?;
+        venta_repo::validar_pendiente_de_pago(&venta)?;
+        let saldo = credito::saldo_de(&venta, &self.venta_repo.obtener_pagos(id)?);
+        let pagos_aplicados = if venta.credito {
+            pagos::aplicar_abonos(&saldo, pagos_recibidos)?
+        } else {
+            pagos::aplicar_pagos(&saldo, pagos_recibidos)?
+        };
+
+        self.venta_repo.registrar_pagos(id, nuevos_pagos(id, pagos_aplicados, Some(sesion.id)))?;
+
+        self.obtener_venta_por_id(id_str)
+    }
+
+    /// Anular una venta: lo vendido vuelve al inventario y los pagos se reembolsan. Se rechaza si
+    /// ya se cerró la sesión de caja que la registró o alguna de las que cobraron sus pagos.
+    #[instrument(skip(self, request))]
+    pub fn anular_venta(&self, id_str: &str, request: AnularVentaRequest) -> ApiResult<VentaResponse> {
+        let id = Uuid::parse_str(id_str)
+            .map_err(|_| ApiError::InvalidInput("ID de venta inválido".to_string()))?;
+        let id_persona = Uuid::parse_str(&request.id_persona)
+            .map_err(|_| ApiError::InvalidInput("ID de persona inválido".to_string()))?;
+
+        // Validar que la persona existe y está activa
+        let persona = self.persona_repo.buscar_por_id(id_persona)?;
+        if !persona.activo {
+            return Err(ApiError::InactiveClient);
+        }
+
+        let entradas = self.venta_repo.anular(id, id_persona, request.motivo)?;
+        metrics().movimientos_inventario
+            .with_label_values(&[TipoMovimiento::Entrada.as_str()])
+            .inc_by(entradas.len() as u64);
+
+        self.obtener_venta_por_id(id_str)
+    }
+
     /// RF2: Obtener ventas con filtros opcionales
+    #[instrument(skip(self))]
     pub fn obtener_ventas(
         &self,
         id_cliente: Option<String>,
         sucursal: Option<String>,
         fecha_desde: Option<String>,
         fecha_hasta: Option<String>,
+        estado: Option<String>,
+        metodo_pago: Option<String>,
     ) -> ApiResult<Vec<VentaResponse>> {
         let id_cliente_uuid = match id_cliente {
             Some(id_str) => Some(Uuid::parse_str(&id_str)
@@ -140,60 +399,162 @@ impl VentaService {
             None => None,
         };
 
+        let estado = match estado {
+            Some(estado) => Some(match estado.to_uppercase().as_str() {
+                "PENDIENTE_PAGO" => EstadoVenta::PendientePago,
+                "PAGADA" => EstadoVenta::Pagada,
+                "ANULADA" => EstadoVenta::Anulada,
+                _ => return Err(ApiError::InvalidInput(
+                    "Estado de venta inválido. Valores permitidos: PENDIENTE_PAGO, PAGADA, ANULADA".to_string()
+                )),
+            }),
+            None => None,
+        };
+
+        let metodo_pago = match metodo_pago {
+            Some(metodo) => Some(match metodo.to_uppercase().as_str() {
+                "EFECTIVO" => MetodoPago::Efectivo,
+                "TARJETA" => MetodoPago::Tarjeta,
+                "TRANSFERENCIA" => MetodoPago::Transferencia,
+                _ => return Err(ApiError::InvalidInput(
+                    "Método de pago inválido. Valores permitidos: EFECTIVO, TARJETA, TRANSFERENCIA".to_string()
+                )),
+            }),
+            None => None,
+        };
+
         let ventas = self.venta_repo.listar(
             id_cliente_uuid,
             sucursal,
             fecha_desde_naive,
             fecha_hasta_naive,
+            estado,
+            metodo_pago,
         )?;
 
         let mut respuestas = Vec::new();
         for venta in ventas {
             let detalles = self.venta_repo.obtener_detalles(venta.id)?;
-
-            let mut detalles_response = Vec::new();
-            for detalle in detalles {
-                let producto = self.producto_repo.buscar_por_id(detalle.id_producto)?;
-                detalles_response.push(DetalleVentaResponse {
-                    id_producto: detalle.id_producto.to_string(),
-                    nombre_producto: producto.nombre,
-                    cantidad: detalle.cantidad,
-                    precio_unitario: producto.precio_unitario.to_f64().unwrap_or(0.0),
-                    subtotal: detalle.monto.to_f64().unwrap_or(0.0),
-                });
-            }
-
-            respuestas.push(VentaResponse {
-                id: venta.id.to_string(),
-                id_cliente: venta.id_persona.to_string(),
-                fecha: venta.fecha.format("%Y-%m-%d %H:%M:%S").to_string(),
-                total: venta.monto.to_f64().unwrap_or(0.0),
-                sucursal: venta.sucursal,
-                detalles: detalles_response,
-            });
+            respuestas.push(self.venta_response(venta, detalles)?);
         }
 
         Ok(respuestas)
     }
 
     /// Obtener una venta específica por ID
+    #[instrument(skip(self))]
     pub fn obtener_venta_por_id(&self, id_str: &str) -> ApiResult<VentaResponse> {
         let id = Uuid::parse_str(id_str)
             .map_err(|_| ApiError::InvalidInput("ID de venta inválido".to_string()))?;
 
         let (venta, detalles) = self.venta_repo.buscar_por_id(id)?;
+        self.venta_response(venta, detalles)
+    }
 
-        let mut detalles_response = Vec::new();
-        for detalle in detalles {
-            let producto = self.producto_repo.buscar_por_id(detalle.id_producto)?;
-            detalles_response.push(DetalleVentaResponse {
-                id_producto: detalle.id_producto.to_string(),
-                nombre_producto: producto.nombre,
-                cantidad: detalle.cantidad,
-                precio_unitario: producto.precio_unitario.to_f64().unwrap_or(0.0),
-                subtotal: detalle.monto.to_f64().unwrap_or(0.0),
-            });
+    /// Qué venta y qué cliente se llevaron la unidad con ese número de serie. Un mismo número
+    /// puede estar registrado en productos distintos.
+    #[instrument(skip(self))]
+    pub fn buscar_serie(&self, numero: &str) -> ApiResult<Vec<SerieResponse>> {
+        let numero = series::normalizar_numero(numero)?;
+        let encontradas = self.inventario_repo.buscar_series_por_numero(&numero)?;
+        if encontradas.is_empty() {
+            return Err(series::serie_no_encontrada(&numero));
+        }
+
+        encontradas
+            .into_iter()
+            .map(|serie| {
+                let producto = self.producto_repo.buscar_por_id(serie.id_producto)?;
+                let ventas = self
+                    .venta_repo
+                    .listar_ventas_de_serie(serie.id)?
+                    .into_iter()
+                    .map(|venta| {
+                        Ok(VentaSerieResponse {
+                            id_venta: venta.id.to_string(),
+                            fecha: venta.fecha.format("%Y-%m-%d %H:%M:%S").to_string(),
+                            id_cliente: venta.id_persona.to_string(),
+                            nombre_cliente: self.nombre_cliente(venta.id_persona)?,
+                        })
+                    })
+                    .collect::<ApiResult<Vec<_>>>()?;
+
+                Ok(SerieResponse {
+                    id: serie.id.to_string(),
+                    id_producto: serie.id_producto.to_string(),
+                    nombre_producto: producto.nombre,
+                    numero: serie.numero,
+                    estado: serie.estado,
+                    ventas,
+                })
+            })
+            .collect()
+    }
+
+    /// Números de serie de una línea: uno por unidad base, en el almacén y sin repetirse en la
+    /// venta. Devuelve sus ids; vacío si el producto no es serializado.
+    fn series_de_linea(
+        &self,
+        producto: &Producto,
+        cantidad_base: i32,
+        numeros: &[String],
+        series_en_venta: &mut HashSet<Uuid>,
+    ) -> ApiResult<Vec<Uuid>> {
+        if !producto.serializado {
+            if !numeros.is_empty() {
+                return Err(ApiError::InvalidInput(format!(
+                    "El producto '{}' no lleva números de serie",
+                    producto.nombre
+                )));
+            }
+            return Ok(Vec::new());
+        }
+        if numeros.len() as i64 != cantidad_base as i64 {
+            return Err(ApiError::InvalidInput(format!(
+                "Indique un número de serie por unidad vendida de '{}': {} unidades, {} números",
+                producto.nombre,
+                cantidad_base,
+                numeros.len()
+            )));
+        }
+
+        numeros
+            .iter()
+            .map(|numero| {
+                let serie = self
+                    .inventario_repo
+                    .buscar_serie(producto.id, numero)?
+                    .ok_or_else(|| series::serie_no_encontrada(numero))?;
+                series::validar_serie_en_almacen(&serie)?;
+                if !series_en_venta.insert(serie.id) {
+                    return Err(ApiError::InvalidInput(format!(
+                        "El número de serie {} está repetido en la venta",
+                        numero
+                    )));
+                }
+                Ok(serie.id)
+            })
+            .collect()
+    }
+
+    /// None si el cliente ya no está activo
+    fn nombre_cliente(&self, id_cliente: Uuid) -> ApiResult<Option<String>> {
+        match self.persona_repo.buscar_por_id(id_cliente) {
+            Ok(cliente) => Ok(Some(cliente.nombre)),
+            Err(ApiError::NotFound(_)) => Ok(None),
+            Err(e) => Err(e),
         }
+    }
+
+    fn venta_response(&self, venta: Venta, detalles: Vec<DetalleVenta>) -> ApiResult<VentaResponse> {
+        let detalles_response = detalles
+            .into_iter()
+            .map(|detalle| self.detalle_response(detalle))
+            .collect::<ApiResult<Vec<_>>>()?;
+        let pagos = self.venta_repo.obtener_pagos(venta.id)?;
+        let total_pagado: BigDecimal = pagos.iter().map(|pago| &pago.monto).sum();
+        let cambio: BigDecimal = pagos.iter().map(|pago| &pago.cambio).sum();
+        let saldo = credito::saldo_de(&venta, &pagos);
 
         Ok(VentaResponse {
             id: venta.id.to_string(),
@@ -201,7 +562,100 @@ impl VentaService {
             fecha: venta.fecha.format("%Y-%m-%d %H:%M:%S").to_string(),
             total: venta.monto.to_f64().unwrap_or(0.0),
             sucursal: venta.sucursal,
+            id_vendedor: venta.id_vendedor.map(|id| id.to_string()),
+            estado: venta.estado,
+            credito: venta.credito,
+            id_sesion_caja: venta.id_sesion_caja.map(|id| id.to_string()),
+            id_cotizacion: venta.id_cotizacion.map(|id| id.to_string()),
+            id_pedido: venta.id_pedido.map(|id| id.to_string()),
+            total_pagado: total_pagado.to_f64().unwrap_or(0.0),
+            saldo: saldo.to_f64().unwrap_or(0.0),
+            cambio: cambio.to_f64().unwrap_or(0.0),
             detalles: detalles_response,
+            pagos: pagos
+                .into_iter()
+                .map(|pago| PagoResponse {
+                    id: pago.id.to_string(),
+                    metodo: pago.metodo,
+                    monto: pago.monto.to_f64().unwrap_or(0.0),
+                    cambio: pago.cambio.to_f64().unwrap_or(0.0),
+                    referencia: pago.referencia,
+                    fecha: pago.fecha.format("%Y-%m-%d %H:%M:%S").to_string(),
+                })
+                .collect(),
         })
     }
+
+    /// El precio unitario es el de lista o catálogo: el monto de la línea antes del descuento de
+    /// la promoción entre la cantidad vendida
+    fn detalle_response(&self, detalle: DetalleVenta) -> ApiResult<DetalleVentaResponse> {
+        let producto = self.producto_repo.buscar_por_id(detalle.id_producto)?;
+        let unidad = match detalle.id_unidad {
+            Some(id_unidad) => self.producto_repo.buscar_unidad(id_unidad)?.nombre,
+            None => producto.unidad_venta,
+        };
+        let precio_unitario = if detalle.cantidad_unidad > 0 {
+            (&detalle.monto + &detalle.descuento) / BigDecimal::from(detalle.cantidad_unidad)
+        } else {
+            BigDecimal::from(0)
+        };
+        let lotes = self
+            .venta_repo
+            .obtener_lotes_detalle(detalle.id)?
+            .into_iter()
+            .map(|consumo| {
+                let lote = self.inventario_repo.buscar_lote(consumo.id_lote)?;
+                Ok(LoteConsumidoResponse {
+                    id_lote: lote.id.to_string(),
+                    codigo: lote.codigo,
+                    fecha_vencimiento: lote.fecha_vencimiento.format("%Y-%m-%d").to_string(),
+                    cantidad: consumo.cantidad,
+                })
+            })
+            .collect::<ApiResult<Vec<_>>>()?;
+        let numeros_serie = self
+            .venta_repo
+            .obtener_series_detalle(detalle.id)?
+            .into_iter()
+            .map(|serie| serie.numero)
+            .collect();
+
+        Ok(DetalleVentaResponse {
+            id_producto: detalle.id_producto.to_string(),
+            nombre_producto: producto.nombre,
+            unidad,
+            cantidad: detalle.cantidad_unidad,
+            cantidad_base: detalle.cantidad,
+            precio_unitario: precio_unitario.to_f64().unwrap_or(0.0),
+            subtotal: detalle.monto.to_f64().unwrap_or(0.0),
+            descuento: detalle.descuento.to_f64().unwrap_or(0.0),
+            origen_precio: OrigenPrecio::de(detalle.id_lista_precios, detalle.id_promocion),
+            id_lista_precios: detalle.id_lista_precios.map(|id| id.to_string()),
+            id_promocion: detalle.id_promocion.map(|id| id.to_string()),
+            costo: detalle.costo.to_f64().unwrap_or(0.0),
+            comision: detalle.comision.to_f64().unwrap_or(0.0),
+            lotes,
+            numeros_serie,
+        })
+    }
+}
+
+fn pagos_desde_request(pagos: &[PagoRequest]) -> ApiResult<Vec<PagoRecibido>> {
+    pagos.iter().map(PagoRecibido::desde_request).collect()
+}
+
+fn nuevos_pagos(id_venta: Uuid, pagos: Vec<PagoAplicado>, id_sesion_caja: Option<Uuid>) -> Vec<NuevoPago> {
+    let fecha = Utc::now().naive_utc();
+    pagos
+        .into_iter()
+        .map(|pago| NuevoPago {
+            id_venta,
+            metodo: pago.metodo,
+            monto: pago.monto,
+            cambio: pago.cambio,
+            referencia: pago.referencia,
+            fecha,
+            id_sesion_caja,
+        })
+        .collect()
 }
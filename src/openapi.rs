@@ -0,0 +1,245 @@
+use utoipa::OpenApi;
+
+use crate::{health, metrics, modules};
+
+/// Documentación OpenAPI de la API, servida en /api-docs/openapi.json
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "PoliMarket API",
+        version = env!("CARGO_PKG_VERSION"),
+        description = "API REST para el sistema de gestión de ventas e inventario del PoliMarket. \
+        Esta API permite gestionar personas (clientes, vendedores, proveedores), productos, \
+        movimientos de inventario y ventas con control automático de stock.",
+        contact(
+            name = "PoliMarket Team",
+            email = "soporte@polimarket.com"
+        )
+    ),
+    tags(
+        (name = "Health", description = "Endpoints de verificación del estado del servicio"),
+        (name = "Personas", description = "Gestión de personas (clientes, vendedores, proveedores)"),
+        (name = "Productos", description = "Gestión de productos y consulta de inventario"),
+        (name = "Catalogo", description = "Categorías jerárquicas y marcas de productos"),
+        (name = "Inventario", description = "Movimientos de inventario y disponibilidad de productos"),
+        (name = "Ventas", description = "Procesamiento y consulta de ventas"),
+        (name = "Cajas", description = "Sesiones de caja por terminal, arqueo y reportes X/Z"),
+        (name = "Precios", description = "Listas de precios por cliente, promociones y cotización de precios"),
+        (name = "Cotizaciones", description = "Cotizaciones a clientes con vencimiento y su conversión en venta"),
+        (name = "Pedidos", description = "Pedidos de clientes con reserva de stock, backorders y despachos parciales"),
+        (name = "Comisiones", description = "Reglas de comisión de los vendedores y su liquidación mensual"),
+        (name = "Cuentas por cobrar", description = "Crédito de los clientes, saldos pendientes y estado de cuenta"),
+        (name = "Auditoria", description = "Registro de cambios: quién modificó qué y cuándo"),
+        (name = "Reportes", description = "Reportes agregados: valorización del inventario y márgenes de venta"),
+        (name = "Observabilidad", description = "Métricas operativas y de negocio en formato Prometheus")
+    ),
+    paths(
+        health::handler::liveness,
+        health::handler::readiness,
+        metrics::handler::exportar_metricas,
+        modules::personas::handler::crear_persona,
+        modules::personas::handler::obtener_persona,
+        modules::personas::handler::listar_personas,
+        modules::productos::handler::crear_producto,
+        modules::productos::handler::obtener_producto,
+        modules::productos::handler::listar_productos,
+        modules::productos::handler::obtener_por_codigo_barras,
+        modules::productos::handler::actualizar_catalogo,
+        modules::productos::handler::agregar_codigo_barras,
+        modules::productos::handler::eliminar_codigo_barras,
+        modules::productos::handler::listar_variantes,
+        modules::productos::handler::agregar_unidad,
+        modules::productos::handler::eliminar_unidad,
+        modules::productos::handler::cambiar_serializado,
+        modules::catalogo::handler::crear_categoria,
+        modules::catalogo::handler::listar_categorias,
+        modules::catalogo::handler::obtener_categoria,
+        modules::catalogo::handler::actualizar_categoria,
+        modules::catalogo::handler::desactivar_categoria,
+        modules::catalogo::handler::crear_marca,
+        modules::catalogo::handler::listar_marcas,
+        modules::catalogo::handler::obtener_marca,
+        modules::catalogo::handler::actualizar_marca,
+        modules::catalogo::handler::desactivar_marca,
+        modules::inventarios::handler::registrar_movimiento,
+        modules::inventarios::handler::obtener_movimiento,
+        modules::inventarios::handler::revertir_movimiento,
+        modules::inventarios::handler::obtener_disponibilidad,
+        modules::inventarios::handler::listar_vencimientos,
+        modules::ventas::handler::crear_venta,
+        modules::ventas::handler::listar_ventas,
+        modules::ventas::handler::obtener_venta,
+        modules::ventas::handler::registrar_pagos,
+        modules::ventas::handler::anular_venta,
+        modules::ventas::handler::buscar_serie,
+        modules::comisiones::handler::liquidar_comisiones,
+        modules::comisiones::handler::crear_regla,
+        modules::comisiones::handler::listar_reglas,
+        modules::comisiones::handler::desactivar_regla,
+        modules::cajas::handler::abrir_sesion,
+        modules::cajas::handler::listar_sesiones,
+        modules::cajas::handler::obtener_sesion,
+        modules::cajas::handler::reporte_x,
+        modules::cajas::handler::cerrar_sesion,
+        modules::cajas::handler::reporte_z,
+        modules::precios::handler::crear_lista,
+        modules::precios::handler::listar_listas,
+        modules::precios::handler::obtener_lista,
+        modules::precios::handler::listar_precios,
+        modules::precios::handler::fijar_precio,
+        modules::precios::handler::quitar_precio,
+        modules::precios::handler::asignar_lista_cliente,
+        modules::precios::handler::crear_promocion,
+        modules::precios::handler::listar_promociones,
+        modules::precios::handler::desactivar_promocion,
+        modules::precios::handler::cotizar,
+        modules::cotizaciones::handler::crear_cotizacion,
+        modules::cotizaciones::handler::listar_cotizaciones,
+        modules::cotizaciones::handler::obtener_cotizacion,
+        modules::cotizaciones::handler::actualizar_cotizacion,
+        modules::cotizaciones::handler::enviar_cotizacion,
+        modules::cotizaciones::handler::aceptar_cotizacion,
+        modules::cotizaciones::handler::convertir_cotizacion,
+        modules::pedidos::handler::crear_pedido,
+        modules::pedidos::handler::listar_pedidos,
+        modules::pedidos::handler::obtener_pedido,
+        modules::pedidos::handler::preparar_pedido,
+        modules::pedidos::handler::despachar_pedido,
+        modules::pedidos::handler::entregar_pedido,
+        modules::cuentas::handler::estado_cuenta,
+        modules::cuentas::handler::asignar_limite_credito,
+        modules::auditoria::handler::listar_auditoria,
+        modules::consistencia::handler::verificar_stock,
+        modules::consistencia::handler::reparar_stock,
+        modules::reportes::handler::valorizacion,
+        modules::reportes::handler::margenes,
+    ),
+    components(
+        schemas(
+            // Health
+            health::model::HealthStatus,
+            health::model::ComponentHealth,
+            health::model::LivenessResponse,
+            health::model::ReadinessResponse,
+            // Common types
+            modules::common::errors::ErrorResponse,
+            modules::common::types::TipoPerfil,
+            modules::common::types::TipoMovimiento,
+            modules::common::types::TipoCodigoBarras,
+            modules::common::types::EstadoSerie,
+            modules::common::types::EstadoVenta,
+            modules::common::types::MetodoPago,
+            // Personas
+            modules::personas::model::CrearPersonaRequest,
+            modules::personas::model::PersonaResponse,
+            modules::personas::model::PersonaCreadaResponse,
+            modules::personas::handler::PersonasQuery,
+            // Productos
+            modules::productos::model::CrearProductoRequest,
+            modules::productos::model::ProductoResponse,
+            modules::productos::model::ProductoCreadoResponse,
+            modules::productos::model::ProductosQueryParams,
+            modules::productos::model::ActualizarCatalogoProductoRequest,
+            modules::productos::model::CodigoBarrasRequest,
+            modules::productos::model::CodigoBarrasResponse,
+            modules::productos::model::UnidadProductoRequest,
+            modules::productos::model::UnidadProductoResponse,
+            modules::productos::model::SerializadoRequest,
+            // Catalogo
+            modules::catalogo::model::CategoriaRequest,
+            modules::catalogo::model::CategoriaResponse,
+            modules::catalogo::model::MarcaRequest,
+            modules::catalogo::model::MarcaResponse,
+            modules::catalogo::model::CatalogoCreadoResponse,
+            // Inventarios
+            modules::inventarios::model::MovimientoRequest,
+            modules::inventarios::model::MovimientoRegistradoResponse,
+            modules::inventarios::model::MovimientoResponse,
+            modules::inventarios::model::RevertirMovimientoRequest,
+            modules::inventarios::model::DisponibilidadResponse,
+            modules::inventarios::model::LoteResponse,
+            modules::inventarios::model::VencimientosQuery,
+            // Ventas
+            modules::ventas::model::CrearVentaRequest,
+            modules::ventas::model::DetalleVentaRequest,
+            modules::ventas::model::PagoRequest,
+            modules::ventas::model::RegistrarPagosRequest,
+            modules::ventas::model::AnularVentaRequest,
+            modules::ventas::model::PagoResponse,
+            modules::ventas::model::VentaResponse,
+            modules::ventas::model::DetalleVentaResponse,
+            modules::ventas::model::LoteConsumidoResponse,
+            modules::ventas::model::VentaCreadaResponse,
+            modules::ventas::model::VentasQueryParams,
+            modules::ventas::model::SerieResponse,
+            modules::ventas::model::VentaSerieResponse,
+            // Cajas
+            modules::common::types::EstadoSesionCaja,
+            modules::cajas::model::AbrirSesionCajaRequest,
+            modules::cajas::model::CerrarSesionCajaRequest,
+            modules::cajas::model::SesionesCajaQuery,
+            modules::cajas::model::SesionCajaResponse,
+            modules::cajas::model::ReporteCajaResponse,
+            modules::cajas::model::TipoReporteCaja,
+            modules::cajas::model::TotalMetodoPagoResponse,
+            // Precios
+            modules::common::types::TipoPromocion,
+            modules::precios::model::OrigenPrecio,
+            modules::precios::model::ListaPreciosRequest,
+            modules::precios::model::ListaPreciosResponse,
+            modules::precios::model::PrecioListaRequest,
+            modules::precios::model::PrecioListaQuery,
+            modules::precios::model::PrecioListaResponse,
+            modules::precios::model::ListaPreciosClienteRequest,
+            modules::precios::model::PromocionRequest,
+            modules::precios::model::PromocionResponse,
+            modules::precios::model::PromocionesQuery,
+            modules::precios::model::CotizarPreciosRequest,
+            modules::precios::model::LineaPrecioRequest,
+            modules::precios::model::CotizacionPreciosResponse,
+            modules::precios::model::PrecioLineaResponse,
+            // Cotizaciones
+            modules::common::types::EstadoCotizacion,
+            modules::cotizaciones::model::CotizacionRequest,
+            modules::cotizaciones::model::ConvertirCotizacionRequest,
+            modules::cotizaciones::model::CotizacionesQuery,
+            modules::cotizaciones::model::CotizacionResponse,
+            modules::cotizaciones::model::DetalleCotizacionResponse,
+            // Pedidos
+            modules::common::types::EstadoPedido,
+            modules::pedidos::model::PedidoRequest,
+            modules::pedidos::model::DespachoRequest,
+            modules::pedidos::model::LineaDespachoRequest,
+            modules::pedidos::model::EntregarPedidoRequest,
+            modules::pedidos::model::PedidosQuery,
+            modules::pedidos::model::PedidoResponse,
+            modules::pedidos::model::DetallePedidoResponse,
+            // Comisiones
+            modules::comisiones::model::ReglaComisionRequest,
+            modules::comisiones::model::ReglaComisionResponse,
+            modules::comisiones::model::LiquidacionResponse,
+            modules::comisiones::model::LiquidacionVendedorResponse,
+            // Cuentas por cobrar
+            modules::cuentas::model::LimiteCreditoRequest,
+            modules::cuentas::model::EstadoCuentaResponse,
+            modules::cuentas::model::AntiguedadSaldosResponse,
+            modules::cuentas::model::DocumentoPendienteResponse,
+            modules::cuentas::model::MovimientoCuentaResponse,
+            modules::cuentas::model::TipoMovimientoCuenta,
+            // Auditoria
+            modules::common::types::AccionAuditoria,
+            modules::auditoria::model::AuditoriaResponse,
+            modules::auditoria::model::AuditoriaQueryParams,
+            // Consistencia
+            modules::consistencia::model::ConsistenciaResponse,
+            modules::consistencia::model::DescuadreStockResponse,
+            modules::consistencia::model::RepararStockRequest,
+            // Reportes
+            modules::reportes::model::ValorizacionResponse,
+            modules::reportes::model::ValorizacionProductoResponse,
+            modules::reportes::model::MargenesResponse,
+            modules::reportes::model::MargenResponse,
+        )
+    )
+)]
+pub struct ApiDoc;
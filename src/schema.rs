@@ -1,6 +1,38 @@
 // @generated automatically by Diesel CLI.
 
 pub mod sql_types {
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "accion_auditoria"))]
+    pub struct AccionAuditoria;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "estado_cotizacion"))]
+    pub struct EstadoCotizacion;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "estado_pedido"))]
+    pub struct EstadoPedido;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "estado_serie"))]
+    pub struct EstadoSerie;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "estado_sesion_caja"))]
+    pub struct EstadoSesionCaja;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "estado_venta"))]
+    pub struct EstadoVenta;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "metodo_pago"))]
+    pub struct MetodoPago;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "tipo_codigo_barras"))]
+    pub struct TipoCodigoBarras;
+
     #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
     #[diesel(postgres_type(name = "tipo_movimiento"))]
     pub struct TipoMovimiento;
@@ -8,6 +40,114 @@ pub mod sql_types {
     #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
     #[diesel(postgres_type(name = "tipo_perfil"))]
     pub struct TipoPerfil;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "tipo_promocion"))]
+    pub struct TipoPromocion;
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::AccionAuditoria;
+
+    auditoria (id) {
+        id -> Uuid,
+        fecha -> Timestamp,
+        id_actor -> Nullable<Uuid>,
+        accion -> AccionAuditoria,
+        #[max_length = 50]
+        entidad -> Varchar,
+        id_entidad -> Uuid,
+        datos_anteriores -> Nullable<Jsonb>,
+        datos_nuevos -> Nullable<Jsonb>,
+        cambios -> Nullable<Jsonb>,
+        #[max_length = 128]
+        request_id -> Nullable<Varchar>,
+    }
+}
+
+diesel::table! {
+    capas_costo (id) {
+        id -> Uuid,
+        id_producto -> Uuid,
+        id_movimiento -> Nullable<Uuid>,
+        costo_unitario -> Numeric,
+        cantidad_inicial -> Int4,
+        cantidad_disponible -> Int4,
+        fecha_creacion -> Timestamp,
+        fecha_actualizacion -> Timestamp,
+        activo -> Bool,
+    }
+}
+
+diesel::table! {
+    categorias (id) {
+        id -> Uuid,
+        #[max_length = 100]
+        nombre -> Varchar,
+        descripcion -> Nullable<Text>,
+        id_padre -> Nullable<Uuid>,
+        fecha_creacion -> Timestamp,
+        fecha_actualizacion -> Timestamp,
+        activo -> Bool,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::TipoCodigoBarras;
+
+    codigos_barra (id) {
+        id -> Uuid,
+        id_producto -> Uuid,
+        #[max_length = 14]
+        codigo -> Varchar,
+        tipo -> TipoCodigoBarras,
+        fecha_creacion -> Timestamp,
+        fecha_actualizacion -> Timestamp,
+        activo -> Bool,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::EstadoCotizacion;
+
+    cotizaciones (id) {
+        id -> Uuid,
+        id_persona -> Uuid,
+        id_vendedor -> Nullable<Uuid>,
+        #[max_length = 100]
+        sucursal -> Nullable<Varchar>,
+        estado -> EstadoCotizacion,
+        fecha_vencimiento -> Date,
+        monto -> Numeric,
+        observaciones -> Nullable<Text>,
+        id_venta -> Nullable<Uuid>,
+        fecha_creacion -> Timestamp,
+        fecha_actualizacion -> Timestamp,
+        activo -> Bool,
+    }
+}
+
+diesel::table! {
+    detalle_cotizaciones (id) {
+        id -> Uuid,
+        id_cotizacion -> Uuid,
+        linea -> Int4,
+        id_producto -> Uuid,
+        id_unidad -> Nullable<Uuid>,
+        cantidad -> Int4,
+        cantidad_unidad -> Int4,
+        precio_unitario -> Numeric,
+        descuento -> Numeric,
+        monto -> Numeric,
+        id_lista_precios -> Nullable<Uuid>,
+        id_promocion -> Nullable<Uuid>,
+        fecha_creacion -> Timestamp,
+        fecha_actualizacion -> Timestamp,
+        activo -> Bool,
+    }
 }
 
 diesel::table! {
@@ -25,6 +165,32 @@ diesel::table! {
         fecha_creacion -> Timestamp,
         fecha_actualizacion -> Timestamp,
         activo -> Bool,
+        id_movimiento_revertido -> Nullable<Uuid>,
+        id_lote -> Nullable<Uuid>,
+        costo_unitario -> Nullable<Numeric>,
+        id_detalle_pedido -> Nullable<Uuid>,
+    }
+}
+
+diesel::table! {
+    detalle_pedidos (id) {
+        id -> Uuid,
+        id_pedido -> Uuid,
+        linea -> Int4,
+        id_producto -> Uuid,
+        id_unidad -> Nullable<Uuid>,
+        cantidad -> Int4,
+        cantidad_unidad -> Int4,
+        cantidad_asignada -> Int4,
+        cantidad_despachada -> Int4,
+        precio_unitario -> Numeric,
+        descuento -> Numeric,
+        monto -> Numeric,
+        id_lista_precios -> Nullable<Uuid>,
+        id_promocion -> Nullable<Uuid>,
+        fecha_creacion -> Timestamp,
+        fecha_actualizacion -> Timestamp,
+        activo -> Bool,
     }
 }
 
@@ -38,6 +204,37 @@ diesel::table! {
         fecha_creacion -> Timestamp,
         fecha_actualizacion -> Timestamp,
         activo -> Bool,
+        id_unidad -> Nullable<Uuid>,
+        cantidad_unidad -> Int4,
+        costo -> Numeric,
+        comision -> Numeric,
+        id_regla_comision -> Nullable<Uuid>,
+        id_lista_precios -> Nullable<Uuid>,
+        id_promocion -> Nullable<Uuid>,
+        descuento -> Numeric,
+    }
+}
+
+diesel::table! {
+    detalle_ventas_lotes (id) {
+        id -> Uuid,
+        id_detalle_venta -> Uuid,
+        id_lote -> Uuid,
+        cantidad -> Int4,
+        fecha_creacion -> Timestamp,
+        fecha_actualizacion -> Timestamp,
+        activo -> Bool,
+    }
+}
+
+diesel::table! {
+    detalle_ventas_series (id) {
+        id -> Uuid,
+        id_detalle_venta -> Uuid,
+        id_serie -> Uuid,
+        fecha_creacion -> Timestamp,
+        fecha_actualizacion -> Timestamp,
+        activo -> Bool,
     }
 }
 
@@ -50,6 +247,101 @@ diesel::table! {
         fecha_creacion -> Timestamp,
         fecha_actualizacion -> Timestamp,
         activo -> Bool,
+        cantidad_reservada -> Int4,
+    }
+}
+
+diesel::table! {
+    listas_precios (id) {
+        id -> Uuid,
+        #[max_length = 100]
+        nombre -> Varchar,
+        descripcion -> Nullable<Text>,
+        fecha_creacion -> Timestamp,
+        fecha_actualizacion -> Timestamp,
+        activo -> Bool,
+    }
+}
+
+diesel::table! {
+    lotes (id) {
+        id -> Uuid,
+        id_producto -> Uuid,
+        #[max_length = 50]
+        codigo -> Varchar,
+        fecha_vencimiento -> Date,
+        cantidad_disponible -> Int4,
+        fecha_creacion -> Timestamp,
+        fecha_actualizacion -> Timestamp,
+        activo -> Bool,
+    }
+}
+
+diesel::table! {
+    marcas (id) {
+        id -> Uuid,
+        #[max_length = 100]
+        nombre -> Varchar,
+        fecha_creacion -> Timestamp,
+        fecha_actualizacion -> Timestamp,
+        activo -> Bool,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::EstadoSerie;
+
+    numeros_serie (id) {
+        id -> Uuid,
+        id_producto -> Uuid,
+        #[max_length = 100]
+        numero -> Varchar,
+        estado -> EstadoSerie,
+        id_movimiento -> Nullable<Uuid>,
+        fecha_creacion -> Timestamp,
+        fecha_actualizacion -> Timestamp,
+        activo -> Bool,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::MetodoPago;
+
+    pagos (id) {
+        id -> Uuid,
+        id_venta -> Uuid,
+        metodo -> MetodoPago,
+        monto -> Numeric,
+        cambio -> Numeric,
+        #[max_length = 100]
+        referencia -> Nullable<Varchar>,
+        fecha -> Timestamp,
+        fecha_creacion -> Timestamp,
+        fecha_actualizacion -> Timestamp,
+        activo -> Bool,
+        id_sesion_caja -> Nullable<Uuid>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::EstadoPedido;
+
+    pedidos (id) {
+        id -> Uuid,
+        id_persona -> Uuid,
+        id_vendedor -> Nullable<Uuid>,
+        #[max_length = 100]
+        sucursal -> Nullable<Varchar>,
+        estado -> EstadoPedido,
+        monto -> Numeric,
+        observaciones -> Nullable<Text>,
+        id_venta -> Nullable<Uuid>,
+        fecha_creacion -> Timestamp,
+        fecha_actualizacion -> Timestamp,
+        activo -> Bool,
     }
 }
 
@@ -71,6 +363,21 @@ diesel::table! {
         fecha_creacion -> Timestamp,
         fecha_actualizacion -> Timestamp,
         activo -> Bool,
+        limite_credito -> Nullable<Numeric>,
+        id_lista_precios -> Nullable<Uuid>,
+    }
+}
+
+diesel::table! {
+    precios_lista (id) {
+        id -> Uuid,
+        id_lista_precios -> Uuid,
+        id_producto -> Uuid,
+        precio_unitario -> Numeric,
+        fecha_creacion -> Timestamp,
+        fecha_actualizacion -> Timestamp,
+        activo -> Bool,
+        id_unidad -> Nullable<Uuid>,
     }
 }
 
@@ -86,10 +393,113 @@ diesel::table! {
         fecha_creacion -> Timestamp,
         fecha_actualizacion -> Timestamp,
         activo -> Bool,
+        #[max_length = 64]
+        sku -> Varchar,
+        id_categoria -> Nullable<Uuid>,
+        id_marca -> Nullable<Uuid>,
+        id_producto_padre -> Nullable<Uuid>,
+        atributos -> Jsonb,
+        serializado -> Bool,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::TipoPromocion;
+
+    promociones (id) {
+        id -> Uuid,
+        #[max_length = 100]
+        nombre -> Varchar,
+        tipo -> TipoPromocion,
+        id_producto -> Uuid,
+        fecha_inicio -> Timestamp,
+        fecha_fin -> Timestamp,
+        porcentaje -> Nullable<Numeric>,
+        cantidad_lleva -> Nullable<Int4>,
+        cantidad_paga -> Nullable<Int4>,
+        precio_paquete -> Nullable<Numeric>,
+        fecha_creacion -> Timestamp,
+        fecha_actualizacion -> Timestamp,
+        activo -> Bool,
+    }
+}
+
+diesel::table! {
+    reglas_comision (id) {
+        id -> Uuid,
+        id_vendedor -> Nullable<Uuid>,
+        id_categoria -> Nullable<Uuid>,
+        porcentaje -> Numeric,
+        fecha_creacion -> Timestamp,
+        fecha_actualizacion -> Timestamp,
+        activo -> Bool,
     }
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::EstadoSesionCaja;
+
+    sesiones_caja (id) {
+        id -> Uuid,
+        #[max_length = 100]
+        sucursal -> Varchar,
+        #[max_length = 50]
+        terminal -> Varchar,
+        id_operador -> Uuid,
+        monto_apertura -> Numeric,
+        fecha_apertura -> Timestamp,
+        estado -> EstadoSesionCaja,
+        fecha_cierre -> Nullable<Timestamp>,
+        efectivo_esperado -> Nullable<Numeric>,
+        efectivo_contado -> Nullable<Numeric>,
+        diferencia -> Nullable<Numeric>,
+        observaciones -> Nullable<Text>,
+        fecha_creacion -> Timestamp,
+        fecha_actualizacion -> Timestamp,
+        activo -> Bool,
+        ventas -> Nullable<Int8>,
+        ventas_anuladas -> Nullable<Int8>,
+        total_vendido -> Nullable<Numeric>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::MetodoPago;
+
+    totales_cierre_caja (id) {
+        id -> Uuid,
+        id_sesion_caja -> Uuid,
+        metodo -> MetodoPago,
+        pagos -> Int8,
+        monto -> Numeric,
+        cambio -> Numeric,
+        fecha_creacion -> Timestamp,
+        fecha_actualizacion -> Timestamp,
+        activo -> Bool,
+    }
+}
+
+diesel::table! {
+    unidades_producto (id) {
+        id -> Uuid,
+        id_producto -> Uuid,
+        #[max_length = 50]
+        nombre -> Varchar,
+        factor -> Int4,
+        precio_unitario -> Nullable<Numeric>,
+        fecha_creacion -> Timestamp,
+        fecha_actualizacion -> Timestamp,
+        activo -> Bool,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::EstadoVenta;
+
     ventas (id) {
         id -> Uuid,
         id_persona -> Uuid,
@@ -100,22 +510,90 @@ diesel::table! {
         fecha_creacion -> Timestamp,
         fecha_actualizacion -> Timestamp,
         activo -> Bool,
+        id_vendedor -> Nullable<Uuid>,
+        estado -> EstadoVenta,
+        credito -> Bool,
+        id_sesion_caja -> Nullable<Uuid>,
+        id_cotizacion -> Nullable<Uuid>,
+        id_pedido -> Nullable<Uuid>,
     }
 }
 
+diesel::joinable!(codigos_barra -> productos (id_producto));
+diesel::joinable!(capas_costo -> detalle_inventarios (id_movimiento));
+diesel::joinable!(capas_costo -> productos (id_producto));
+diesel::joinable!(detalle_cotizaciones -> cotizaciones (id_cotizacion));
+diesel::joinable!(detalle_cotizaciones -> listas_precios (id_lista_precios));
+diesel::joinable!(detalle_cotizaciones -> productos (id_producto));
+diesel::joinable!(detalle_cotizaciones -> promociones (id_promocion));
+diesel::joinable!(detalle_cotizaciones -> unidades_producto (id_unidad));
+diesel::joinable!(detalle_inventarios -> detalle_pedidos (id_detalle_pedido));
+diesel::joinable!(detalle_inventarios -> lotes (id_lote));
 diesel::joinable!(detalle_inventarios -> personas (id_persona));
 diesel::joinable!(detalle_inventarios -> productos (id_producto));
+diesel::joinable!(detalle_pedidos -> listas_precios (id_lista_precios));
+diesel::joinable!(detalle_pedidos -> pedidos (id_pedido));
+diesel::joinable!(detalle_pedidos -> productos (id_producto));
+diesel::joinable!(detalle_pedidos -> promociones (id_promocion));
+diesel::joinable!(detalle_pedidos -> unidades_producto (id_unidad));
+diesel::joinable!(detalle_ventas -> listas_precios (id_lista_precios));
 diesel::joinable!(detalle_ventas -> productos (id_producto));
+diesel::joinable!(detalle_ventas -> promociones (id_promocion));
+diesel::joinable!(detalle_ventas -> reglas_comision (id_regla_comision));
+diesel::joinable!(detalle_ventas -> unidades_producto (id_unidad));
 diesel::joinable!(detalle_ventas -> ventas (id_venta));
+diesel::joinable!(detalle_ventas_lotes -> detalle_ventas (id_detalle_venta));
+diesel::joinable!(detalle_ventas_lotes -> lotes (id_lote));
+diesel::joinable!(detalle_ventas_series -> detalle_ventas (id_detalle_venta));
+diesel::joinable!(detalle_ventas_series -> numeros_serie (id_serie));
 diesel::joinable!(inventarios -> personas (id_persona));
 diesel::joinable!(inventarios -> productos (id_producto));
+diesel::joinable!(lotes -> productos (id_producto));
+diesel::joinable!(numeros_serie -> detalle_inventarios (id_movimiento));
+diesel::joinable!(numeros_serie -> productos (id_producto));
+diesel::joinable!(pagos -> sesiones_caja (id_sesion_caja));
+diesel::joinable!(pagos -> ventas (id_venta));
+diesel::joinable!(personas -> listas_precios (id_lista_precios));
+diesel::joinable!(precios_lista -> listas_precios (id_lista_precios));
+diesel::joinable!(precios_lista -> productos (id_producto));
+diesel::joinable!(precios_lista -> unidades_producto (id_unidad));
+diesel::joinable!(productos -> categorias (id_categoria));
+diesel::joinable!(productos -> marcas (id_marca));
+diesel::joinable!(promociones -> productos (id_producto));
+diesel::joinable!(reglas_comision -> categorias (id_categoria));
+diesel::joinable!(reglas_comision -> personas (id_vendedor));
+diesel::joinable!(sesiones_caja -> personas (id_operador));
+diesel::joinable!(totales_cierre_caja -> sesiones_caja (id_sesion_caja));
+diesel::joinable!(unidades_producto -> productos (id_producto));
 diesel::joinable!(ventas -> personas (id_persona));
+diesel::joinable!(ventas -> sesiones_caja (id_sesion_caja));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    auditoria,
+    capas_costo,
+    categorias,
+    codigos_barra,
+    cotizaciones,
+    detalle_cotizaciones,
     detalle_inventarios,
+    detalle_pedidos,
     detalle_ventas,
+    detalle_ventas_lotes,
+    detalle_ventas_series,
     inventarios,
+    listas_precios,
+    lotes,
+    marcas,
+    numeros_serie,
+    pagos,
+    pedidos,
     personas,
+    precios_lista,
     productos,
+    promociones,
+    reglas_comision,
+    sesiones_caja,
+    totales_cierre_caja,
+    unidades_producto,
     ventas,
 );
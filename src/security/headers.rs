@@ -0,0 +1,23 @@
+use actix_web::http::header;
+use actix_web::middleware::DefaultHeaders;
+
+/// Cabeceras de seguridad añadidas a todas las respuestas (sin sobrescribir las del handler).
+///
+/// `hsts_max_age_seconds = 0` omite Strict-Transport-Security, p.ej. en desarrollo sin TLS.
+pub fn cabeceras_seguridad(hsts_max_age_seconds: u64) -> DefaultHeaders {
+    let cabeceras = DefaultHeaders::new()
+        .add((header::X_CONTENT_TYPE_OPTIONS, "nosniff"))
+        .add((header::X_FRAME_OPTIONS, "DENY"))
+        .add((header::REFERRER_POLICY, "no-referrer"))
+        .add(("Permissions-Policy", "camera=(), microphone=(), geolocation=()"))
+        .add(("Cross-Origin-Opener-Policy", "same-origin"));
+
+    if hsts_max_age_seconds == 0 {
+        return cabeceras;
+    }
+
+    cabeceras.add((
+        header::STRICT_TRANSPORT_SECURITY,
+        format!("max-age={}; includeSubDomains", hsts_max_age_seconds),
+    ))
+}
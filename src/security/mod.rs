@@ -0,0 +1,28 @@
+pub mod headers;
+pub mod rate_limit;
+
+use actix_web::error::JsonPayloadError;
+use actix_web::web;
+
+use crate::modules::common::errors::ApiError;
+
+/// Cabecera con la que el cliente identifica a la persona que opera
+pub const PERSONA_ID_HEADER: &str = "x-persona-id";
+
+/// Configuración de los extractores `web::Json`: límite de tamaño y errores en formato `ErrorResponse`
+pub fn json_config(max_body_bytes: usize) -> web::JsonConfig {
+    web::JsonConfig::default()
+        .limit(max_body_bytes)
+        .error_handler(move |err, _req| {
+            let error = match err {
+                JsonPayloadError::Overflow { .. } | JsonPayloadError::OverflowKnownLength { .. } => {
+                    ApiError::PayloadTooLarge(max_body_bytes)
+                }
+                JsonPayloadError::ContentType => ApiError::InvalidInput(
+                    "Content-Type debe ser application/json".to_string()
+                ),
+                otro => ApiError::InvalidInput(format!("JSON inválido: {}", otro)),
+            };
+            error.into()
+        })
+}
@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::Method;
+use actix_web::middleware::Next;
+use actix_web::{web, Error};
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::metrics::metrics;
+use crate::modules::common::errors::ApiError;
+use crate::security::PERSONA_ID_HEADER;
+
+/// Número de claves a partir del cual se purgan los buckets llenos (clientes inactivos)
+const MAX_BUCKETS: usize = 10_000;
+
+/// Rutas de escritura que mueven dinero o stock y usan los buckets estrictos
+const RUTAS_ESCRITURA: &[&str] = &[
+    "/v1/ventas",
+    "/v1/ventas/{id}/pagos",
+    "/v1/ventas/{id}/anular",
+    "/v1/cajas/sesiones/{id}/cierre",
+    "/v1/cotizaciones/{id}/convertir",
+    "/v1/pedidos",
+    "/v1/pedidos/{id}/despachos",
+    "/v1/pedidos/{id}/entregar",
+    "/v1/inventario/movimientos",
+    "/v1/inventario/movimientos/id/{id}/reversion",
+    "/v1/consistencia/stock/reparar",
+];
+
+/// Token bucket: `capacidad` es la ráfaga máxima y se recargan `por_segundo` tokens
+#[derive(Debug, Clone, Copy)]
+struct Politica {
+    nombre: &'static str,
+    capacidad: f64,
+    por_segundo: f64,
+}
+
+impl Politica {
+    fn new(nombre: &'static str, por_minuto: u32, rafaga: u32) -> Self {
+        Politica {
+            nombre,
+            capacidad: f64::from(rafaga),
+            por_segundo: f64::from(por_minuto) / 60.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    actualizado: Instant,
+}
+
+struct Buckets {
+    politica: Politica,
+    estado: Mutex<HashMap<String, Bucket>>,
+}
+
+impl Buckets {
+    fn new(politica: Politica) -> Self {
+        Buckets {
+            politica,
+            estado: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Consume un token de `clave`; si no hay, devuelve cuánto falta para el siguiente
+    fn consumir(&self, clave: &str, ahora: Instant) -> Result<(), Duration> {
+        let politica = self.politica;
+        let mut estado = self.estado.lock().unwrap_or_else(|e| e.into_inner());
+
+        if estado.len() >= MAX_BUCKETS && !estado.contains_key(clave) {
+            estado.retain(|_, bucket| recargar(bucket, politica, ahora) < politica.capacidad);
+        }
+
+        let bucket = estado.entry(clave.to_string()).or_insert(Bucket {
+            tokens: politica.capacidad,
+            actualizado: ahora,
+        });
+
+        if recargar(bucket, politica, ahora) >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            let faltante = (1.0 - bucket.tokens) / politica.por_segundo;
+            Err(Duration::from_secs_f64(faltante))
+        }
+    }
+}
+
+fn recargar(bucket: &mut Bucket, politica: Politica, ahora: Instant) -> f64 {
+    let transcurrido = ahora.saturating_duration_since(bucket.actualizado).as_secs_f64();
+    bucket.tokens = (bucket.tokens + transcurrido * politica.por_segundo).min(politica.capacidad);
+    bucket.actualizado = ahora;
+    bucket.tokens
+}
+
+/// Límite de peticiones por IP del cliente y por persona (`X-Persona-Id`).
+///
+/// Toda petición a `/v1` consume de los buckets generales; las rutas de `RUTAS_ESCRITURA`
+/// consumen además de los buckets estrictos. Se comparte entre todos los workers.
+pub struct RateLimiter {
+    habilitado: bool,
+    confiar_en_proxy: bool,
+    general: Buckets,
+    escritura: Buckets,
+}
+
+impl RateLimiter {
+    pub fn new(config: &Config) -> Self {
+        RateLimiter {
+            habilitado: config.rate_limit_enabled,
+            confiar_en_proxy: config.trust_proxy_headers,
+            general: Buckets::new(Politica::new(
+                "general",
+                config.rate_limit_per_minute,
+                config.rate_limit_burst,
+            )),
+            escritura: Buckets::new(Politica::new(
+                "escritura",
+                config.rate_limit_write_per_minute,
+                config.rate_limit_write_burst,
+            )),
+        }
+    }
+
+    fn verificar(&self, ip: &str, persona: Option<Uuid>, es_escritura: bool) -> Result<(), ApiError> {
+        let ahora = Instant::now();
+        let persona = persona.map(|id| id.to_string());
+
+        let mut buckets = vec![(&self.general, "ip", ip)];
+        if let Some(persona) = &persona {
+            buckets.push((&self.general, "persona", persona.as_str()));
+        }
+        if es_escritura {
+            buckets.push((&self.escritura, "ip", ip));
+            if let Some(persona) = &persona {
+                buckets.push((&self.escritura, "persona", persona.as_str()));
+            }
+        }
+
+        for (bucket, alcance, clave) in buckets {
+            if let Err(espera) = bucket.consumir(&format!("{}:{}", alcance, clave), ahora) {
+                metrics()
+                    .peticiones_limitadas
+                    .with_label_values(&[bucket.politica.nombre, alcance])
+                    .inc();
+                tracing::warn!(politica = bucket.politica.nombre, alcance, "Rate limit exceeded");
+                return Err(ApiError::TooManyRequests(espera.as_secs_f64().ceil().max(1.0) as u64));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Middleware que aplica el `RateLimiter` registrado en `app_data` a las rutas de `/v1`
+pub async fn limitar_peticiones(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, Error> {
+    let limitador = req.app_data::<web::Data<RateLimiter>>().cloned();
+
+    if let Some(limitador) = limitador.filter(|l| l.habilitado) {
+        let ruta = req.match_pattern();
+        let limitada = ruta
+            .as_deref()
+            .is_some_and(|ruta| ruta.starts_with("/v1/") && !ruta.starts_with("/v1/health"));
+
+        if limitada {
+            let ip = if limitador.confiar_en_proxy {
+                req.connection_info().realip_remote_addr().map(str::to_string)
+            } else {
+                req.peer_addr().map(|addr| addr.ip().to_string())
+            }
+            .unwrap_or_else(|| "desconocida".to_string());
+
+            let persona = req
+                .headers()
+                .get(PERSONA_ID_HEADER)
+                .and_then(|valor| valor.to_str().ok())
+                .and_then(|valor| Uuid::parse_str(valor.trim()).ok());
+
+            let es_escritura = req.method() == Method::POST
+                && ruta.as_deref().is_some_and(|ruta| RUTAS_ESCRITURA.contains(&ruta));
+
+            // Se responde aquí (y no con Err) para que las capas externas añadan sus cabeceras
+            if let Err(error) = limitador.verificar(&ip, persona, es_escritura) {
+                return Ok(req.error_response(error).map_into_right_body());
+            }
+        }
+    }
+
+    next.call(req).await.map(ServiceResponse::map_into_left_body)
+}
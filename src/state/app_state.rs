@@ -1,71 +1,194 @@
-use diesel::r2d2::{ConnectionManager, Pool};
+use std::sync::Arc;
+use std::time::Duration;
+use diesel::r2d2::{ConnectionManager, Pool, PoolError};
 use diesel::PgConnection;
-use log::info;
+use tracing::{debug, info};
 
-use crate::modules::personas::repository::PersonaRepository;
+use crate::config::Config;
+use crate::metrics;
+use crate::modules::auditoria::repository::AuditoriaRepository;
+use crate::modules::auditoria::service::AuditoriaService;
+use crate::modules::cajas::repository::PgCajaRepository;
+use crate::modules::cajas::service::CajaService;
+use crate::modules::catalogo::repository::PgCatalogoRepository;
+use crate::modules::catalogo::service::CatalogoService;
+use crate::modules::comisiones::repository::PgComisionRepository;
+use crate::modules::comisiones::service::ComisionService;
+use crate::modules::consistencia::repository::ConsistenciaRepository;
+use crate::modules::cotizaciones::repository::PgCotizacionRepository;
+use crate::modules::cotizaciones::service::CotizacionService;
+use crate::modules::cuentas::service::CuentaService;
+use crate::modules::pedidos::repository::PgPedidoRepository;
+use crate::modules::pedidos::service::PedidoService;
+use crate::modules::consistencia::service::ConsistenciaService;
+use crate::modules::personas::repository::PgPersonaRepository;
 use crate::modules::personas::service::PersonaService;
-use crate::modules::productos::repository::ProductoRepository;
+use crate::modules::precios::repository::PgPrecioRepository;
+use crate::modules::precios::service::PrecioService;
+use crate::modules::productos::repository::PgProductoRepository;
 use crate::modules::productos::service::ProductoService;
-use crate::modules::inventarios::repository::InventarioRepository;
+use crate::modules::inventarios::repository::PgInventarioRepository;
 use crate::modules::inventarios::service::InventarioService;
-use crate::modules::ventas::repository::VentaRepository;
+use crate::modules::reportes::repository::ReporteRepository;
+use crate::modules::reportes::service::ReporteService;
+use crate::modules::ventas::repository::PgVentaRepository;
 use crate::modules::ventas::service::VentaService;
 
 pub type DbPool = Pool<ConnectionManager<PgConnection>>;
 
+/// Crea el pool de conexiones con los tamaños y timeout configurados y las métricas del pool
+pub fn crear_pool(config: &Config) -> Result<DbPool, PoolError> {
+    let manager = ConnectionManager::<PgConnection>::new(&config.database_url);
+
+    Pool::builder()
+        .max_size(config.pool_max_size)
+        .min_idle(Some(config.pool_min_idle))
+        .connection_timeout(Duration::from_secs(config.pool_timeout_seconds))
+        .event_handler(Box::new(metrics::db::PoolMetrics))
+        .connection_customizer(Box::new(metrics::db::QueryMetrics))
+        .build(manager)
+}
+
 pub struct AppState {
+    pub pool: DbPool,
     pub persona_service: PersonaService,
     pub producto_service: ProductoService,
+    pub catalogo_service: CatalogoService,
     pub inventario_service: InventarioService,
-    pub venta_service: VentaService,
+    pub venta_service: Arc<VentaService>,
+    pub comision_service: ComisionService,
+    pub cuenta_service: CuentaService,
+    pub caja_service: CajaService,
+    pub precio_service: PrecioService,
+    pub cotizacion_service: CotizacionService,
+    pub pedido_service: PedidoService,
+    pub auditoria_service: AuditoriaService,
+    pub consistencia_service: ConsistenciaService,
+    pub reporte_service: ReporteService,
 }
 
 impl AppState {
-    pub fn new(pool: DbPool) -> Self {
-        info!("🔧 Initializing AppState with all services...");
+    pub fn new(pool: DbPool, config: &Config) -> Self {
+        info!("Initializing AppState with all services");
 
-        // Create repositories (unused for now, but kept for future optimization)
-        info!("  → Creating repository instances...");
-        let _persona_repo = PersonaRepository::new(pool.clone());
-        let _producto_repo = ProductoRepository::new(pool.clone());
-        let _inventario_repo = InventarioRepository::new(pool.clone());
-        let _venta_repo = VentaRepository::new(pool.clone());
+        // Un único repositorio por entidad, compartido entre los servicios que lo usan
+        debug!("Creating repository instances");
+        let persona_repo = Arc::new(PgPersonaRepository::new(pool.clone()));
+        let producto_repo = Arc::new(PgProductoRepository::new(pool.clone()));
+        let inventario_repo = Arc::new(PgInventarioRepository::new(pool.clone(), config.valuation_method));
+        let venta_repo = Arc::new(PgVentaRepository::new(pool.clone(), config.valuation_method));
+        let catalogo_repo = Arc::new(PgCatalogoRepository::new(pool.clone()));
+        let comision_repo = Arc::new(PgComisionRepository::new(pool.clone()));
+        let caja_repo = Arc::new(PgCajaRepository::new(pool.clone()));
+        let precio_repo = Arc::new(PgPrecioRepository::new(pool.clone()));
+        let cotizacion_repo = Arc::new(PgCotizacionRepository::new(pool.clone()));
+        let pedido_repo = Arc::new(PgPedidoRepository::new(pool.clone()));
 
         // Create services with their dependencies
-        info!("  → Creating PersonaService...");
-        let persona_service = PersonaService::new(
-            PersonaRepository::new(pool.clone())
-        );
+        debug!("Creating PersonaService");
+        let persona_service = PersonaService::new(persona_repo.clone());
 
-        info!("  → Creating InventarioService...");
+        debug!("Creating InventarioService");
         let inventario_service = InventarioService::new(
-            InventarioRepository::new(pool.clone()),
-            ProductoRepository::new(pool.clone()),
-            PersonaRepository::new(pool.clone()),
+            inventario_repo.clone(),
+            producto_repo.clone(),
+            persona_repo.clone(),
+            config.low_stock_threshold,
         );
 
-        info!("  → Creating ProductoService...");
+        debug!("Creating ProductoService");
         let producto_service = ProductoService::new(
-            ProductoRepository::new(pool.clone()),
-            InventarioRepository::new(pool.clone()),
-            PersonaRepository::new(pool.clone()),
+            producto_repo.clone(),
+            inventario_repo.clone(),
+            persona_repo.clone(),
+            catalogo_repo.clone(),
+        );
+
+        debug!("Creating CatalogoService");
+        let catalogo_service = CatalogoService::new(catalogo_repo.clone());
+
+        debug!("Creating VentaService");
+        let venta_service = Arc::new(VentaService::new(
+            venta_repo.clone(),
+            persona_repo.clone(),
+            producto_repo.clone(),
+            inventario_repo,
+            comision_repo.clone(),
+            catalogo_repo.clone(),
+            caja_repo.clone(),
+            precio_repo.clone(),
+        ));
+
+        debug!("Creating ComisionService");
+        let comision_service = ComisionService::new(
+            comision_repo,
+            persona_repo.clone(),
+            catalogo_repo,
+        );
+
+        debug!("Creating CuentaService");
+        let cuenta_service = CuentaService::new(venta_repo, persona_repo.clone());
+
+        debug!("Creating CajaService");
+        let caja_service = CajaService::new(caja_repo, persona_repo.clone());
+
+        debug!("Creating PrecioService");
+        let precio_service = PrecioService::new(precio_repo.clone(), producto_repo.clone(), persona_repo.clone());
+
+        debug!("Creating CotizacionService");
+        let cotizacion_service = CotizacionService::new(
+            cotizacion_repo,
+            persona_repo.clone(),
+            producto_repo.clone(),
+            precio_repo.clone(),
+            venta_service.clone(),
+            config.quote_price_policy,
+        );
+
+        debug!("Creating PedidoService");
+        let pedido_service = PedidoService::new(
+            pedido_repo,
+            persona_repo.clone(),
+            producto_repo,
+            precio_repo,
+            venta_service.clone(),
+        );
+
+        debug!("Creating AuditoriaService");
+        let auditoria_service = AuditoriaService::new(
+            AuditoriaRepository::new(pool.clone())
+        );
+
+        debug!("Creating ConsistenciaService");
+        let consistencia_service = ConsistenciaService::new(
+            ConsistenciaRepository::new(pool.clone()),
+            persona_repo,
         );
 
-        info!("  → Creating VentaService...");
-        let venta_service = VentaService::new(
-            VentaRepository::new(pool.clone()),
-            PersonaRepository::new(pool.clone()),
-            ProductoRepository::new(pool.clone()),
-            InventarioRepository::new(pool.clone()),
+        debug!("Creating ReporteService");
+        let reporte_service = ReporteService::new(
+            ReporteRepository::new(pool.clone()),
+            config.valuation_method,
         );
 
-        info!("✓ All services initialized successfully");
+        info!("All services initialized successfully");
 
         AppState {
+            pool,
             persona_service,
             producto_service,
+            catalogo_service,
             inventario_service,
             venta_service,
+            comision_service,
+            cuenta_service,
+            caja_service,
+            precio_service,
+            cotizacion_service,
+            pedido_service,
+            auditoria_service,
+            consistencia_service,
+            reporte_service,
         }
     }
 }
@@ -0,0 +1,80 @@
+use std::time::Instant;
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::middleware::Next;
+use actix_web::{Error, HttpMessage};
+use tracing::{field, Instrument};
+use uuid::Uuid;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Request id de la petición en curso, disponible en las extensiones de la petición
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+/// Longitud máxima aceptada para un `X-Request-Id` recibido del cliente
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Middleware que abre un span por petición con su `X-Request-Id`.
+///
+/// Si el cliente envía un `X-Request-Id` válido se propaga; si no, se genera uno nuevo.
+/// El identificador se devuelve en la respuesta y queda en el span raíz, de modo que todos
+/// los eventos de servicios y repositorios de la petición lo incluyen.
+pub async fn trazar_peticion(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let request_id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|valor| valor.to_str().ok())
+        .filter(|valor| es_request_id_valido(valor))
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    let span = tracing::info_span!(
+        "http_request",
+        request_id = %request_id,
+        method = %req.method(),
+        route = %req.match_pattern().unwrap_or_else(|| "unmatched".to_string()),
+        path = %req.path(),
+        status = field::Empty,
+        latency_ms = field::Empty,
+    );
+
+    req.extensions_mut().insert(RequestId(request_id.clone()));
+
+    let inicio = Instant::now();
+    let resultado = next.call(req).instrument(span.clone()).await;
+    let latencia = inicio.elapsed().as_millis() as u64;
+
+    let status = match &resultado {
+        Ok(res) => res.status(),
+        Err(e) => e.as_response_error().status_code(),
+    };
+
+    span.record("status", status.as_u16());
+    span.record("latency_ms", latencia);
+    span.in_scope(|| {
+        if status.is_server_error() {
+            tracing::error!("request failed");
+        } else {
+            tracing::info!("request completed");
+        }
+    });
+
+    let mut res = resultado?;
+    if let Ok(valor) = HeaderValue::from_str(&request_id) {
+        res.headers_mut().insert(HeaderName::from_static(REQUEST_ID_HEADER), valor);
+    }
+
+    Ok(res)
+}
+
+/// Acepta identificadores cortos de ASCII visible para evitar inyección en los logs
+fn es_request_id_valido(valor: &str) -> bool {
+    !valor.is_empty()
+        && valor.len() <= MAX_REQUEST_ID_LEN
+        && valor.bytes().all(|b| b.is_ascii_graphic())
+}
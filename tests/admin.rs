@@ -0,0 +1,147 @@
+//! Tareas de `polimarket-admin` (módulo `admin`)
+mod common;
+
+use bigdecimal::BigDecimal;
+use diesel::prelude::*;
+
+use common::builders::{PersonaBuilder, ProductoBuilder, VentaBuilder};
+use common::TestDb;
+use poli_market_api::admin::{self, PerfilSemilla};
+use poli_market_api::modules::common::types::{TipoMovimiento, TipoPerfil};
+use poli_market_api::schema::{capas_costo, detalle_inventarios, inventarios, personas, productos};
+
+#[test]
+fn semilla_demo_es_idempotente_y_cuadra_con_los_movimientos() {
+    let db = TestDb::new();
+    let mut conn = db.conn();
+
+    admin::sembrar(&mut conn, PerfilSemilla::Demo).unwrap();
+    admin::sembrar(&mut conn, PerfilSemilla::Demo).unwrap();
+
+    let documentos: Vec<(String, TipoPerfil)> = personas::table
+        .order(personas::documento)
+        .select((personas::documento, personas::perfil))
+        .load(&mut conn)
+        .unwrap();
+    assert_eq!(
+        documentos,
+        vec![
+            ("11111111".to_string(), TipoPerfil::Proveedor),
+            ("12345678".to_string(), TipoPerfil::Cliente),
+            ("87654321".to_string(), TipoPerfil::Vendedor),
+        ]
+    );
+
+    // Cada producto semilla tiene inventario igual a su cantidad inicial y una única ENTRADA
+    let stock: Vec<(String, i32, i32)> = productos::table
+        .inner_join(inventarios::table)
+        .order(productos::nombre)
+        .select((productos::nombre, productos::cantidad, inventarios::cantidad_disponible))
+        .load(&mut conn)
+        .unwrap();
+    assert_eq!(stock.len(), 3);
+    assert!(stock.iter().all(|(_, cantidad, disponible)| cantidad == disponible));
+    assert_eq!(stock[0].0, "Laptop Dell");
+
+    let movimientos: i64 = detalle_inventarios::table.count().get_result(&mut conn).unwrap();
+    assert_eq!(movimientos, 3);
+    assert!(db.app_state().consistencia_service.verificar().unwrap().consistente);
+}
+
+#[test]
+fn estado_de_migraciones_tras_migrar_y_revertir() {
+    let db = TestDb::new();
+    let mut conn = db.conn();
+
+    let estado = admin::estado_migraciones(&mut conn).unwrap();
+    assert!(estado.len() >= 3);
+    assert!(estado.iter().all(|migracion| migracion.aplicada));
+    assert!(admin::migrar(&mut conn).unwrap().is_empty());
+
+    let revertidas = admin::revertir(&mut conn, 1).unwrap();
+    assert_eq!(revertidas.len(), 1);
+    let estado = admin::estado_migraciones(&mut conn).unwrap();
+    assert!(!estado.last().unwrap().aplicada);
+    assert_eq!(estado.last().unwrap().version, revertidas[0]);
+
+    assert_eq!(admin::migrar(&mut conn).unwrap(), revertidas);
+}
+
+#[test]
+fn recalcular_stock_corrige_el_descuadre_con_signos_normalizados() {
+    let db = TestDb::new();
+    let mut conn = db.conn();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut conn);
+    let cliente = PersonaBuilder::cliente().crear(&mut conn);
+    // El builder no registra la ENTRADA inicial: el stock (10) no cuadra con los movimientos (0)
+    let producto = ProductoBuilder::new().stock(10).crear(&mut conn, &vendedor);
+    // La venta guarda la SALIDA con cantidad negativa
+    VentaBuilder::new(&cliente).item(&producto, 3).crear(&mut conn).unwrap();
+
+    let verificacion = db.app_state().consistencia_service.verificar().unwrap();
+    assert_eq!(verificacion.descuadres.len(), 1);
+    assert_eq!(verificacion.descuadres[0].cantidad_disponible, 7);
+    assert_eq!(verificacion.descuadres[0].suma_movimientos, -3);
+    assert_eq!(verificacion.descuadres[0].diferencia, 10);
+
+    // Una ENTRADA manual de 20 deja el ledger en 17
+    diesel::insert_into(detalle_inventarios::table)
+        .values((
+            detalle_inventarios::id_producto.eq(producto.id),
+            detalle_inventarios::tipo_movimiento.eq(TipoMovimiento::Entrada),
+            detalle_inventarios::id_persona.eq(vendedor.id),
+            detalle_inventarios::cantidad.eq(20),
+        ))
+        .execute(&mut conn)
+        .unwrap();
+
+    let resultado = admin::recalcular_stock(&mut conn).unwrap();
+    assert!(resultado.rechazados.is_empty());
+    assert_eq!(resultado.corregidos.len(), 1);
+    assert_eq!(resultado.corregidos[0].cantidad_disponible, 7);
+    assert_eq!(db.stock(producto.id), 17);
+    assert!(db.app_state().consistencia_service.verificar().unwrap().consistente);
+}
+
+#[test]
+fn recalcular_stock_no_deja_sin_cubrir_reservas_ni_capas_de_costo() {
+    let db = TestDb::new();
+    let mut conn = db.conn();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut conn);
+    // Ninguno tiene movimientos: el recálculo dejaría su stock en 0
+    let reservado = ProductoBuilder::new().nombre("Reservado").stock(10).crear(&mut conn, &vendedor);
+    let costeado = ProductoBuilder::new().nombre("Costeado").stock(10).crear(&mut conn, &vendedor);
+    let libre = ProductoBuilder::new().nombre("Libre").stock(10).crear(&mut conn, &vendedor);
+
+    diesel::update(inventarios::table.filter(inventarios::id_producto.eq(reservado.id)))
+        .set(inventarios::cantidad_reservada.eq(4))
+        .execute(&mut conn)
+        .unwrap();
+    diesel::insert_into(capas_costo::table)
+        .values((
+            capas_costo::id_producto.eq(costeado.id),
+            capas_costo::costo_unitario.eq(BigDecimal::from(100)),
+            capas_costo::cantidad_inicial.eq(6),
+            capas_costo::cantidad_disponible.eq(6),
+        ))
+        .execute(&mut conn)
+        .unwrap();
+
+    let resultado = admin::recalcular_stock(&mut conn).unwrap();
+    assert_eq!(resultado.corregidos.len(), 1);
+    assert_eq!(resultado.corregidos[0].id_producto, libre.id);
+    assert_eq!(db.stock(libre.id), 0);
+
+    let motivo = |id_producto| {
+        resultado
+            .rechazados
+            .iter()
+            .find(|rechazado| rechazado.descuadre.id_producto == id_producto)
+            .map(|rechazado| rechazado.motivo.as_str())
+    };
+    assert_eq!(resultado.rechazados.len(), 2);
+    assert_eq!(motivo(reservado.id), Some("hay 4 unidades reservadas"));
+    assert_eq!(motivo(costeado.id), Some("sus capas de costo activas suman 6 unidades"));
+    assert_eq!(db.stock(reservado.id), 10);
+    assert_eq!(db.stock(costeado.id), 10);
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test;
+use diesel::prelude::*;
+use serde_json::{json, Value};
+
+use common::builders::{PersonaBuilder, ProductoBuilder};
+use common::TestDb;
+use poli_market_api::schema::personas;
+
+#[actix_web::test]
+async fn movimiento_registra_actor_request_id_y_diferencias() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let producto = ProductoBuilder::new().stock(10).crear(&mut db.conn(), &vendedor);
+    let app = app!(db);
+
+    let req = test::TestRequest::post()
+        .uri("/v1/inventario/movimientos")
+        .insert_header(("X-Persona-Id", vendedor.id.to_string()))
+        .insert_header(("X-Request-Id", "req-auditoria-1"))
+        .set_json(json!({
+            "id_producto": producto.id,
+            "tipo_movimiento": "ENTRADA",
+            "id_persona": vendedor.id,
+            "cantidad": 5
+        }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/v1/auditoria?entidad=inventarios&id_actor={}", vendedor.id))
+        .to_request();
+    let registros: Vec<Value> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(registros.len(), 1);
+
+    let registro = &registros[0];
+    assert_eq!(registro["accion"], "ACTUALIZAR");
+    assert_eq!(registro["request_id"], "req-auditoria-1");
+    assert_eq!(registro["cambios"], json!({"cantidad_disponible": {"antes": 10, "despues": 15}}));
+
+    // El movimiento también queda registrado, con el mismo request id
+    let req = test::TestRequest::get()
+        .uri("/v1/auditoria?entidad=detalle_inventarios")
+        .to_request();
+    let registros: Vec<Value> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(registros.len(), 1);
+    assert_eq!(registros[0]["accion"], "CREAR");
+    assert_eq!(registros[0]["request_id"], "req-auditoria-1");
+    assert_eq!(registros[0]["datos_nuevos"]["cantidad"], 5);
+}
+
+#[actix_web::test]
+async fn desactivar_registra_accion_desactivar() {
+    let db = TestDb::new();
+    let persona = PersonaBuilder::cliente().crear(&mut db.conn());
+    diesel::update(personas::table.find(persona.id))
+        .set(personas::activo.eq(false))
+        .execute(&mut db.conn())
+        .unwrap();
+    let app = app!(db);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/v1/auditoria?entidad=personas&id_entidad={}", persona.id))
+        .to_request();
+    let registros: Vec<Value> = test::call_and_read_body_json(&app, req).await;
+    let acciones: Vec<&str> = registros.iter().map(|r| r["accion"].as_str().unwrap()).collect();
+    assert_eq!(acciones, vec!["DESACTIVAR", "CREAR"]);
+    // Sin petición HTTP no hay actor
+    assert!(registros.iter().all(|r| r["id_actor"].is_null()));
+}
+
+#[actix_web::test]
+async fn filtros_invalidos_se_rechazan() {
+    let db = TestDb::new();
+    let app = app!(db);
+
+    for uri in [
+        "/v1/auditoria?entidad=usuarios",
+        "/v1/auditoria?id_actor=abc",
+        "/v1/auditoria?fecha_desde=01/11/2025",
+        "/v1/auditoria?limite=0",
+    ] {
+        let req = test::TestRequest::get().uri(uri).to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST, "{}", uri);
+    }
+
+    let req = test::TestRequest::get()
+        .uri("/v1/auditoria?fecha_desde=2020-01-01&fecha_hasta=2020-12-31")
+        .to_request();
+    let registros: Vec<Value> = test::call_and_read_body_json(&app, req).await;
+    assert!(registros.is_empty());
+}
@@ -0,0 +1,81 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test;
+use serde_json::Value;
+
+use common::builders::{PersonaBuilder, ProductoBuilder};
+use common::TestDb;
+
+fn nombres(resultado: &Value) -> Vec<&str> {
+    resultado
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|fila| fila["nombre"].as_str().unwrap())
+        .collect()
+}
+
+#[actix_web::test]
+async fn buscar_productos_por_prefijo_sin_acentos_y_con_errores() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    for nombre in ["Teclado mecánico RGB", "Teclado inalámbrico", "Mouse óptico", "Monitor 24 pulgadas"] {
+        ProductoBuilder::new().nombre(nombre).crear(&mut db.conn(), &vendedor);
+    }
+    let app = app!(db);
+
+    let casos: [(&str, &[&str]); 5] = [
+        // Cada palabra es un prefijo; el que coincide con ambas queda primero y el otro
+        // teclado entra por similitud
+        ("teclado%20mec", &["Teclado mecánico RGB", "Teclado inalámbrico"]),
+        ("teclado", &["Teclado inalámbrico", "Teclado mecánico RGB"]),
+        ("OPTICO", &["Mouse óptico"]),
+        // Errores de escritura, por similitud de trigramas
+        ("teclafo%20mecanico", &["Teclado mecánico RGB"]),
+        ("impresora", &[]),
+    ];
+
+    for (q, esperados) in casos {
+        let req = test::TestRequest::get().uri(&format!("/v1/productos?q={}", q)).to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK, "q={}", q);
+        let productos: Value = test::read_body_json(res).await;
+        assert_eq!(nombres(&productos), esperados, "q={}", q);
+    }
+
+    // En blanco equivale a no buscar
+    let req = test::TestRequest::get().uri("/v1/productos?q=%20%20").to_request();
+    let productos: Value = test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(productos.as_array().unwrap().len(), 4);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/v1/productos?q={}", "a".repeat(101)))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn buscar_personas_por_nombre_o_documento() {
+    let db = TestDb::new();
+    PersonaBuilder::cliente().nombre("María González").documento("1032456789").crear(&mut db.conn());
+    PersonaBuilder::cliente().nombre("Mario Gómez").documento("1032999888").crear(&mut db.conn());
+    PersonaBuilder::vendedor().nombre("María Fernanda Ruiz").documento("5200111222").crear(&mut db.conn());
+    let app = app!(db);
+
+    let casos: [(&str, &[&str]); 5] = [
+        ("maria%20gonzalez", &["María González"]),
+        ("gonzales", &["María González"]),
+        ("1032", &["María González", "Mario Gómez"]),
+        ("1032456", &["María González"]),
+        ("maria&tipo=VENDEDOR", &["María Fernanda Ruiz"]),
+    ];
+
+    for (q, esperados) in casos {
+        let req = test::TestRequest::get().uri(&format!("/v1/personas?q={}", q)).to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK, "q={}", q);
+        let personas: Value = test::read_body_json(res).await;
+        assert_eq!(nombres(&personas), esperados, "q={}", q);
+    }
+}
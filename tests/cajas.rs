@@ -0,0 +1,126 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test;
+use diesel::prelude::*;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use common::builders::{PersonaBuilder, ProductoBuilder};
+use common::TestDb;
+use poli_market_api::schema::pagos;
+
+#[actix_web::test]
+async fn sesion_de_caja_se_abre_vende_y_se_cierra_con_arqueo() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let cliente = PersonaBuilder::cliente().crear(&mut db.conn());
+    let producto = ProductoBuilder::new().precio(100).stock(10).crear(&mut db.conn(), &vendedor);
+    let app = app!(db);
+
+    let abrir = |id_operador: Uuid| {
+        test::TestRequest::post()
+            .uri("/v1/cajas/sesiones")
+            .set_json(json!({"sucursal": "Centro", "terminal": "CAJA-01",
+                             "id_operador": id_operador, "monto_apertura": 200.0}))
+            .to_request()
+    };
+    assert_eq!(test::call_service(&app, abrir(cliente.id)).await.status(), StatusCode::BAD_REQUEST);
+    let res = test::call_service(&app, abrir(vendedor.id)).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let sesion: Value = test::read_body_json(res).await;
+    assert_eq!(sesion["estado"], "ABIERTA");
+    assert_eq!(sesion["monto_apertura"], 200.0);
+    let id_sesion = sesion["id"].as_str().unwrap().to_string();
+    // Una sola sesión abierta por terminal
+    assert_eq!(test::call_service(&app, abrir(vendedor.id)).await.status(), StatusCode::BAD_REQUEST);
+
+    let venta = |sucursal: &str, pagos: Value| {
+        test::TestRequest::post()
+            .uri("/v1/ventas")
+            .set_json(json!({"id_cliente": cliente.id, "id_sesion_caja": id_sesion, "sucursal": sucursal,
+                             "detalles": [{"id_producto": producto.id, "cantidad": 2}], "pagos": pagos}))
+            .to_request()
+    };
+    let res = test::call_service(&app, venta("Norte", json!([]))).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let res = test::call_service(&app, venta("Centro", json!([{"metodo": "EFECTIVO", "monto": 250.0}]))).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let creada: Value = test::read_body_json(res).await;
+    assert_eq!(creada["cambio"], 50.0);
+    let res = test::call_service(&app, venta("Centro", json!([{"metodo": "TARJETA", "monto": 200.0}]))).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+
+    let req = test::TestRequest::get().uri(&format!("/v1/ventas/{}", creada["id"].as_str().unwrap())).to_request();
+    let registrada: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(registrada["id_sesion_caja"], id_sesion);
+
+    let reporte = |tipo: &str| {
+        test::TestRequest::get()
+            .uri(&format!("/v1/cajas/sesiones/{}/reporte-{}", id_sesion, tipo))
+            .to_request()
+    };
+    let x: Value = test::call_and_read_body_json(&app, reporte("x")).await;
+    assert_eq!(x["tipo"], "X");
+    assert_eq!(x["ventas"], 2);
+    assert_eq!(x["total_vendido"], 400.0);
+    assert_eq!(
+        x["pagos"],
+        json!([{"metodo": "EFECTIVO", "pagos": 1, "monto": 200.0, "cambio": 50.0},
+               {"metodo": "TARJETA", "pagos": 1, "monto": 200.0, "cambio": 0.0}])
+    );
+    assert_eq!(x["cambio_entregado"], 50.0);
+    assert_eq!(x["efectivo_esperado"], 400.0);
+    assert_eq!(x["efectivo_contado"], Value::Null);
+    assert_eq!(test::call_service(&app, reporte("z")).await.status(), StatusCode::BAD_REQUEST);
+
+    // Cierre con un faltante de 15
+    let cerrar = || {
+        test::TestRequest::post()
+            .uri(&format!("/v1/cajas/sesiones/{}/cierre", id_sesion))
+            .set_json(json!({"efectivo_contado": 385.0, "observaciones": "Faltante"}))
+            .to_request()
+    };
+    let res = test::call_service(&app, cerrar()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let z: Value = test::read_body_json(res).await;
+    assert_eq!(z["tipo"], "Z");
+    assert_eq!(z["efectivo_esperado"], 400.0);
+    assert_eq!(z["efectivo_contado"], 385.0);
+    assert_eq!(z["diferencia"], -15.0);
+    assert_eq!(z["sesion"]["estado"], "CERRADA");
+    assert_eq!(z["sesion"]["diferencia"], -15.0);
+    // El reporte Z es el guardado al cerrar, aunque luego cambien los pagos
+    diesel::update(pagos::table).set(pagos::activo.eq(false)).execute(&mut db.conn()).unwrap();
+    let z: Value = test::call_and_read_body_json(&app, reporte("z")).await;
+    assert_eq!(z["diferencia"], -15.0);
+    assert_eq!(z["ventas"], 2);
+    assert_eq!(z["pagos"], x["pagos"]);
+
+    // La sesión cerrada no admite ventas, anulaciones, otro cierre ni reporte X
+    let res = test::call_service(&app, venta("Centro", json!([]))).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let req = test::TestRequest::post()
+        .uri(&format!("/v1/ventas/{}/anular", creada["id"].as_str().unwrap()))
+        .set_json(json!({"id_persona": vendedor.id}))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(db.stock(producto.id), 6);
+    assert_eq!(test::call_service(&app, cerrar()).await.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(test::call_service(&app, reporte("x")).await.status(), StatusCode::BAD_REQUEST);
+
+    // La terminal queda libre para la siguiente sesión
+    assert_eq!(test::call_service(&app, abrir(vendedor.id)).await.status(), StatusCode::CREATED);
+    let listar = |consulta: &str| test::TestRequest::get().uri(&format!("/v1/cajas/sesiones?{}", consulta)).to_request();
+    let sesiones: Value = test::call_and_read_body_json(&app, listar("sucursal=Centro&estado=cerrada")).await;
+    assert_eq!(sesiones.as_array().unwrap().len(), 1);
+    assert_eq!(sesiones[0]["id"], id_sesion);
+    let sesiones: Value = test::call_and_read_body_json(&app, listar("estado=ABIERTA")).await;
+    assert_eq!(sesiones.as_array().unwrap().len(), 1);
+    assert_eq!(test::call_service(&app, listar("estado=OTRO")).await.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::get().uri(&format!("/v1/cajas/sesiones/{}", Uuid::new_v4())).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::NOT_FOUND);
+    let req = test::TestRequest::get().uri("/v1/cajas/sesiones/no-es-uuid").to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+}
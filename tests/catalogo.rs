@@ -0,0 +1,287 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test;
+use serde_json::{json, Value};
+
+use common::builders::{PersonaBuilder, ProductoBuilder};
+use common::TestDb;
+
+#[actix_web::test]
+async fn filtrar_por_categoria_incluye_subcategorias() {
+    let db = TestDb::new();
+    PersonaBuilder::vendedor().crear(&mut db.conn());
+    let app = app!(db);
+
+    let crear_categoria = |nombre: &'static str, id_padre: Option<String>| {
+        test::TestRequest::post()
+            .uri("/v1/categorias")
+            .set_json(json!({"nombre": nombre, "id_padre": id_padre}))
+            .to_request()
+    };
+    let req = crear_categoria("Periféricos", None);
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let perifericos = test::read_body_json::<Value, _>(res).await["id"].as_str().unwrap().to_string();
+    let req = crear_categoria("Teclados", Some(perifericos.clone()));
+    let teclados = test::read_body_json::<Value, _>(test::call_service(&app, req).await).await["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let req = crear_categoria("Monitores", None);
+    let monitores = test::read_body_json::<Value, _>(test::call_service(&app, req).await).await["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let req = test::TestRequest::post().uri("/v1/marcas").set_json(json!({"nombre": "Logitech"})).to_request();
+    let logitech = test::read_body_json::<Value, _>(test::call_service(&app, req).await).await["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    for (nombre, id_categoria, id_marca) in [
+        ("Teclado K120", &teclados, Some(&logitech)),
+        ("Mouse M90", &perifericos, Some(&logitech)),
+        ("Monitor 24", &monitores, None),
+    ] {
+        let req = test::TestRequest::post()
+            .uri("/v1/productos")
+            .set_json(json!({
+                "nombre": nombre,
+                "cantidad": 5,
+                "unidad_venta": "Unidad",
+                "precio_unitario": 50.0,
+                "id_categoria": id_categoria,
+                "id_marca": id_marca
+            }))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED, "{}", nombre);
+    }
+
+    let nombres = |productos: Value| -> Vec<String> {
+        productos
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["nombre"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/v1/productos?id_categoria={}", perifericos))
+        .to_request();
+    let productos: Value = test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(nombres(productos), ["Mouse M90", "Teclado K120"]);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/v1/productos?id_categoria={}", teclados))
+        .to_request();
+    let productos: Value = test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(nombres(productos), ["Teclado K120"]);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/v1/productos?id_marca={}", logitech))
+        .to_request();
+    let productos: Value = test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(nombres(productos), ["Mouse M90", "Teclado K120"]);
+
+    // Con productos o subcategorías activas no se puede dar de baja
+    for uri in [
+        format!("/v1/categorias/{}", perifericos),
+        format!("/v1/categorias/{}", teclados),
+        format!("/v1/marcas/{}", logitech),
+    ] {
+        let req = test::TestRequest::delete().uri(&uri).to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST, "{}", uri);
+    }
+}
+
+#[actix_web::test]
+async fn categorias_rechazan_ciclos_y_nombres_repetidos() {
+    let db = TestDb::new();
+    let app = app!(db);
+
+    let req = test::TestRequest::post().uri("/v1/categorias").set_json(json!({"nombre": "Audio"})).to_request();
+    let audio = test::read_body_json::<Value, _>(test::call_service(&app, req).await).await["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let req = test::TestRequest::post()
+        .uri("/v1/categorias")
+        .set_json(json!({"nombre": "Audífonos", "id_padre": audio}))
+        .to_request();
+    let audifonos = test::read_body_json::<Value, _>(test::call_service(&app, req).await).await["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let casos = [
+        // Mismo nombre en el mismo nivel, sin distinguir mayúsculas
+        (test::TestRequest::post().uri("/v1/categorias").set_json(json!({"nombre": "AUDIO"})), StatusCode::BAD_REQUEST),
+        // Colgar una categoría de su propia subcategoría
+        (
+            test::TestRequest::put()
+                .uri(&format!("/v1/categorias/{}", audio))
+                .set_json(json!({"nombre": "Audio", "id_padre": audifonos})),
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            test::TestRequest::post()
+                .uri("/v1/categorias")
+                .set_json(json!({"nombre": "Parlantes", "id_padre": uuid::Uuid::new_v4()})),
+            StatusCode::NOT_FOUND,
+        ),
+        (test::TestRequest::post().uri("/v1/categorias").set_json(json!({"nombre": "A"})), StatusCode::BAD_REQUEST),
+    ];
+    for (req, esperado) in casos {
+        assert_eq!(test::call_service(&app, req.to_request()).await.status(), esperado);
+    }
+
+    // El mismo nombre sí se admite bajo otro padre
+    let req = test::TestRequest::post()
+        .uri("/v1/categorias")
+        .set_json(json!({"nombre": "Audio", "id_padre": audifonos}))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+
+    let req = test::TestRequest::post().uri("/v1/marcas").set_json(json!({"nombre": "Sony"})).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+    let req = test::TestRequest::post().uri("/v1/marcas").set_json(json!({"nombre": "sony"})).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn buscar_por_codigo_de_barras_normaliza_upc_y_ean13() {
+    let db = TestDb::new();
+    PersonaBuilder::vendedor().crear(&mut db.conn());
+    let app = app!(db);
+
+    let req = test::TestRequest::post()
+        .uri("/v1/productos")
+        .set_json(json!({
+            "nombre": "Gaseosa 400 ml",
+            "cantidad": 24,
+            "unidad_venta": "Unidad",
+            "precio_unitario": 2.5,
+            "codigos_barra": ["036000291452", "96385074"]
+        }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let id_producto = test::read_body_json::<Value, _>(res).await["id"].as_str().unwrap().to_string();
+
+    // El UPC-A impreso y su EAN-13 (con 0 delante) son el mismo código
+    for codigo in ["036000291452", "0036000291452", "9638-5074"] {
+        let req = test::TestRequest::get().uri(&format!("/v1/productos/codigo/{}", codigo)).to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK, "{}", codigo);
+        let producto: Value = test::read_body_json(res).await;
+        assert_eq!(producto["id"], id_producto);
+        assert_eq!(producto["stock_actual"], 24);
+        assert_eq!(
+            producto["codigos_barra"],
+            json!([{"codigo": "036000291452", "tipo": "UPCA"}, {"codigo": "96385074", "tipo": "EAN8"}])
+        );
+    }
+
+    let casos = [
+        ("/v1/productos/codigo/7702004003509", StatusCode::BAD_REQUEST),
+        ("/v1/productos/codigo/77020040", StatusCode::BAD_REQUEST),
+        ("/v1/productos/codigo/7702004003508", StatusCode::NOT_FOUND),
+    ];
+    for (uri, esperado) in casos {
+        let req = test::TestRequest::get().uri(uri).to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), esperado, "{}", uri);
+    }
+}
+
+#[actix_web::test]
+async fn codigos_de_barras_se_asignan_a_un_solo_producto() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let arroz = ProductoBuilder::new().nombre("Arroz 1 kg").crear(&mut db.conn(), &vendedor);
+    let frijol = ProductoBuilder::new().nombre("Fríjol 500 g").crear(&mut db.conn(), &vendedor);
+    let app = app!(db);
+
+    let agregar = |id_producto: uuid::Uuid, codigo: &str| {
+        test::TestRequest::post()
+            .uri(&format!("/v1/productos/{}/codigos", id_producto))
+            .set_json(json!({"codigo": codigo}))
+            .to_request()
+    };
+
+    let res = test::call_service(&app, agregar(arroz.id, "7702004003508")).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let codigo: Value = test::read_body_json(res).await;
+    assert_eq!(codigo, json!({"codigo": "7702004003508", "tipo": "EAN13"}));
+
+    let casos = [
+        (agregar(frijol.id, "7702004003508"), StatusCode::BAD_REQUEST),
+        (agregar(frijol.id, "7702004003507"), StatusCode::BAD_REQUEST),
+        (agregar(uuid::Uuid::new_v4(), "4006381333931"), StatusCode::NOT_FOUND),
+    ];
+    for (req, esperado) in casos {
+        assert_eq!(test::call_service(&app, req).await.status(), esperado);
+    }
+
+    let eliminar = test::TestRequest::delete()
+        .uri(&format!("/v1/productos/{}/codigos/7702004003508", arroz.id))
+        .to_request();
+    assert_eq!(test::call_service(&app, eliminar).await.status(), StatusCode::NO_CONTENT);
+    let eliminar = test::TestRequest::delete()
+        .uri(&format!("/v1/productos/{}/codigos/7702004003508", arroz.id))
+        .to_request();
+    assert_eq!(test::call_service(&app, eliminar).await.status(), StatusCode::NOT_FOUND);
+
+    // Dado de baja, el código queda libre para otro producto
+    assert_eq!(test::call_service(&app, agregar(frijol.id, "7702004003508")).await.status(), StatusCode::CREATED);
+    let req = test::TestRequest::get().uri("/v1/productos/codigo/7702004003508").to_request();
+    let producto: Value = test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(producto["id"], frijol.id.to_string());
+}
+
+#[actix_web::test]
+async fn sku_se_genera_y_no_se_repite() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let teclado = ProductoBuilder::new().nombre("Teclado").crear(&mut db.conn(), &vendedor);
+    let app = app!(db);
+
+    let req = test::TestRequest::get().uri(&format!("/v1/productos/{}", teclado.id)).to_request();
+    let producto: Value = test::read_body_json(test::call_service(&app, req).await).await;
+    let esperado = format!("PM-{}", &teclado.id.simple().to_string()[..12]).to_uppercase();
+    assert_eq!(producto["sku"], esperado);
+
+    let crear = |sku: &str| {
+        test::TestRequest::post()
+            .uri("/v1/productos")
+            .set_json(json!({
+                "nombre": "Mouse",
+                "cantidad": 1,
+                "unidad_venta": "Unidad",
+                "precio_unitario": 20.0,
+                "sku": sku
+            }))
+            .to_request()
+    };
+    assert_eq!(test::call_service(&app, crear("MOU-001")).await.status(), StatusCode::CREATED);
+    assert_eq!(test::call_service(&app, crear("mou-001")).await.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(test::call_service(&app, crear("MOU 002")).await.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::put()
+        .uri(&format!("/v1/productos/{}/catalogo", teclado.id))
+        .set_json(json!({"sku": "Mou-001"}))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::put()
+        .uri(&format!("/v1/productos/{}/catalogo", teclado.id))
+        .set_json(json!({"sku": "TEC-001"}))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let producto: Value = test::read_body_json(res).await;
+    assert_eq!(producto["sku"], "TEC-001");
+    assert!(producto["id_categoria"].is_null());
+}
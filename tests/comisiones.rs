@@ -0,0 +1,148 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test;
+use chrono::Utc;
+use diesel::prelude::*;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use common::builders::{PersonaBuilder, ProductoBuilder, SesionCajaBuilder};
+use common::TestDb;
+use poli_market_api::modules::common::types::TipoMovimiento;
+use poli_market_api::schema::detalle_inventarios;
+
+#[actix_web::test]
+async fn ventas_registran_comision_y_se_liquidan_por_vendedor() {
+    let db = TestDb::new();
+    let ana = PersonaBuilder::vendedor().nombre("Ana").crear(&mut db.conn());
+    let beto = PersonaBuilder::vendedor().nombre("Beto").crear(&mut db.conn());
+    let cliente = PersonaBuilder::cliente().limite_credito(1_000_000).crear(&mut db.conn());
+    let laptop = ProductoBuilder::new().nombre("Laptop").precio(1000).stock(10).crear(&mut db.conn(), &ana);
+    let caja = SesionCajaBuilder::new(&ana).crear(&mut db.conn());
+    let app = app!(db);
+
+    for regla in [json!({"porcentaje": 2.0}), json!({"id_vendedor": ana.id, "porcentaje": 5.0})] {
+        let req = test::TestRequest::post().uri("/v1/comisiones/reglas").set_json(regla).to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+    }
+
+    let mut comisiones = Vec::new();
+    for vendedor in [ana.id, beto.id, ana.id] {
+        let venta = json!({"id_cliente": cliente.id, "id_sesion_caja": caja, "id_vendedor": vendedor,
+                           "detalles": [{"id_producto": laptop.id, "cantidad": 1}]});
+        let req = test::TestRequest::post().uri("/v1/ventas").set_json(venta).to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+        let creada: Value = test::read_body_json(res).await;
+        let req = test::TestRequest::get()
+            .uri(&format!("/v1/ventas/{}", creada["id"].as_str().unwrap()))
+            .to_request();
+        let venta: Value = test::call_and_read_body_json(&app, req).await;
+        comisiones.push(venta["detalles"][0]["comision"].as_f64().unwrap());
+    }
+    assert_eq!(comisiones, [50.0, 20.0, 50.0]);
+
+    let periodo = Utc::now().format("%Y-%m").to_string();
+    let req = test::TestRequest::get().uri(&format!("/v1/comisiones?periodo={}", periodo)).to_request();
+    let liquidacion: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(liquidacion["periodo"], periodo);
+    assert_eq!(liquidacion["total_comisiones"], 120.0);
+    let vendedores = liquidacion["vendedores"].as_array().unwrap();
+    assert_eq!(vendedores.len(), 2);
+    assert_eq!(vendedores[0]["nombre_vendedor"], "Ana");
+    assert_eq!(vendedores[0]["ventas"], 2);
+    assert_eq!(vendedores[0]["total_vendido"], 2000.0);
+    assert_eq!(vendedores[0]["comision"], 100.0);
+    assert_eq!(vendedores[1]["nombre_vendedor"], "Beto");
+    assert_eq!(vendedores[1]["comision"], 20.0);
+
+    // Un mes sin ventas no liquida nada
+    let req = test::TestRequest::get().uri("/v1/comisiones?periodo=2000-01").to_request();
+    let liquidacion: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(liquidacion["vendedores"], json!([]));
+}
+
+#[actix_web::test]
+async fn reglas_de_comision_invalidas_son_rechazadas() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let cliente = PersonaBuilder::cliente().crear(&mut db.conn());
+    let app = app!(db);
+
+    let crear = |regla: Value| test::TestRequest::post().uri("/v1/comisiones/reglas").set_json(regla).to_request();
+
+    let res = test::call_service(&app, crear(json!({"id_vendedor": vendedor.id, "porcentaje": 3.0}))).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let regla: Value = test::read_body_json(res).await;
+
+    let casos = [
+        json!({"id_vendedor": vendedor.id, "porcentaje": 4.0}),
+        json!({"id_vendedor": cliente.id, "porcentaje": 4.0}),
+        json!({"porcentaje": 120.0}),
+        json!({"porcentaje": -1.0}),
+    ];
+    for caso in casos {
+        let res = test::call_service(&app, crear(caso.clone())).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST, "{}", caso);
+    }
+
+    let req = test::TestRequest::get().uri("/v1/comisiones?periodo=2024-13").to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+
+    // Al desactivar la regla se puede crear otra para el mismo vendedor
+    let req = test::TestRequest::delete()
+        .uri(&format!("/v1/comisiones/reglas/{}", regla["id"].as_str().unwrap()))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::NO_CONTENT);
+    let res = test::call_service(&app, crear(json!({"id_vendedor": vendedor.id, "porcentaje": 4.0}))).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+
+    let req = test::TestRequest::get().uri("/v1/comisiones/reglas").to_request();
+    let reglas: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(reglas.as_array().unwrap().len(), 1);
+    assert_eq!(reglas[0]["porcentaje"], 4.0);
+}
+
+#[actix_web::test]
+async fn venta_sin_vendedor_no_genera_comision_para_nadie() {
+    let db = TestDb::new();
+    let responsable = PersonaBuilder::vendedor().nombre("Responsable").crear(&mut db.conn());
+    let cajero = PersonaBuilder::vendedor().nombre("Cajero").crear(&mut db.conn());
+    let cliente = PersonaBuilder::cliente().crear(&mut db.conn());
+    let laptop = ProductoBuilder::new().precio(1000).stock(5).crear(&mut db.conn(), &responsable);
+    let caja = SesionCajaBuilder::new(&cajero).crear(&mut db.conn());
+    let app = app!(db);
+
+    let req = test::TestRequest::post()
+        .uri("/v1/comisiones/reglas")
+        .set_json(json!({"porcentaje": 5.0}))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+
+    let req = test::TestRequest::post()
+        .uri("/v1/ventas")
+        .set_json(json!({"id_cliente": cliente.id, "id_sesion_caja": caja,
+                         "pagos": [{"metodo": "EFECTIVO", "monto": 1000.0}],
+                         "detalles": [{"id_producto": laptop.id, "cantidad": 1}]}))
+        .to_request();
+    let creada: Value = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get()
+        .uri(&format!("/v1/ventas/{}", creada["id"].as_str().unwrap()))
+        .to_request();
+    let venta: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(venta["detalles"][0]["comision"], 0.0);
+
+    let req = test::TestRequest::get().uri("/v1/comisiones").to_request();
+    let liquidacion: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(liquidacion["vendedores"], json!([]));
+
+    // La SALIDA queda a cargo de quien operaba la caja, no del responsable del inventario
+    let salidas: Vec<Uuid> = detalle_inventarios::table
+        .filter(detalle_inventarios::id_producto.eq(laptop.id))
+        .filter(detalle_inventarios::tipo_movimiento.eq(TipoMovimiento::Salida))
+        .select(detalle_inventarios::id_persona)
+        .load(&mut db.conn())
+        .unwrap();
+    assert_eq!(salidas, [cajero.id]);
+}
@@ -0,0 +1,277 @@
+//! Builders de datos de prueba. Insertan directamente con Diesel, sin pasar por los servicios,
+//! para poder preparar estados que la API no permite crear (p.ej. personas inactivas).
+
+use bigdecimal::BigDecimal;
+use chrono::{NaiveDateTime, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use poli_market_api::modules::common::types::TipoPerfil;
+use poli_market_api::modules::personas::model::Persona;
+use poli_market_api::modules::productos::model::Producto;
+use poli_market_api::schema::{detalle_ventas, inventarios, personas, productos, sesiones_caja, ventas};
+
+/// Documento único de 10 dígitos
+fn documento_unico() -> String {
+    let n = Uuid::new_v4().as_u128() % 10_000_000_000;
+    format!("{:010}", n)
+}
+
+pub struct PersonaBuilder {
+    nombre: String,
+    documento: String,
+    perfil: TipoPerfil,
+    email: Option<String>,
+    limite_credito: Option<BigDecimal>,
+    activo: bool,
+}
+
+impl PersonaBuilder {
+    pub fn new(perfil: TipoPerfil) -> Self {
+        PersonaBuilder {
+            nombre: "Persona de Prueba".to_string(),
+            documento: documento_unico(),
+            perfil,
+            email: None,
+            limite_credito: None,
+            activo: true,
+        }
+    }
+
+    pub fn cliente() -> Self {
+        PersonaBuilder::new(TipoPerfil::Cliente)
+    }
+
+    pub fn vendedor() -> Self {
+        PersonaBuilder::new(TipoPerfil::Vendedor)
+    }
+
+    pub fn nombre(mut self, nombre: &str) -> Self {
+        self.nombre = nombre.to_string();
+        self
+    }
+
+    pub fn documento(mut self, documento: &str) -> Self {
+        self.documento = documento.to_string();
+        self
+    }
+
+    pub fn email(mut self, email: &str) -> Self {
+        self.email = Some(email.to_string());
+        self
+    }
+
+    /// Cliente con crédito aprobado, para ventas que quedan con saldo pendiente
+    pub fn limite_credito(mut self, limite: i64) -> Self {
+        self.limite_credito = Some(BigDecimal::from(limite));
+        self
+    }
+
+    pub fn inactiva(mut self) -> Self {
+        self.activo = false;
+        self
+    }
+
+    pub fn crear(self, conn: &mut PgConnection) -> Persona {
+        diesel::insert_into(personas::table)
+            .values((
+                personas::nombre.eq(self.nombre),
+                personas::documento.eq(self.documento),
+                personas::perfil.eq(self.perfil),
+                personas::email.eq(self.email),
+                personas::limite_credito.eq(self.limite_credito),
+                personas::activo.eq(self.activo),
+            ))
+            .returning(Persona::as_returning())
+            .get_result(conn)
+            .expect("no se pudo crear la persona de prueba")
+    }
+}
+
+pub struct ProductoBuilder {
+    nombre: String,
+    precio_unitario: BigDecimal,
+    unidad_venta: String,
+    stock: i32,
+}
+
+impl Default for ProductoBuilder {
+    fn default() -> Self {
+        ProductoBuilder::new()
+    }
+}
+
+impl ProductoBuilder {
+    pub fn new() -> Self {
+        ProductoBuilder {
+            nombre: "Producto de Prueba".to_string(),
+            precio_unitario: BigDecimal::from(100),
+            unidad_venta: "Unidad".to_string(),
+            stock: 10,
+        }
+    }
+
+    pub fn nombre(mut self, nombre: &str) -> Self {
+        self.nombre = nombre.to_string();
+        self
+    }
+
+    pub fn precio(mut self, precio: i64) -> Self {
+        self.precio_unitario = BigDecimal::from(precio);
+        self
+    }
+
+    pub fn stock(mut self, stock: i32) -> Self {
+        self.stock = stock;
+        self
+    }
+
+    /// Crea el producto y su registro de inventario, a cargo de `responsable`
+    pub fn crear(self, conn: &mut PgConnection, responsable: &Persona) -> Producto {
+        let producto = diesel::insert_into(productos::table)
+            .values((
+                productos::nombre.eq(self.nombre),
+                productos::cantidad.eq(self.stock),
+                productos::unidad_venta.eq(self.unidad_venta),
+                productos::precio_unitario.eq(self.precio_unitario),
+            ))
+            .returning(Producto::as_returning())
+            .get_result(conn)
+            .expect("no se pudo crear el producto de prueba");
+
+        diesel::insert_into(inventarios::table)
+            .values((
+                inventarios::id_producto.eq(producto.id),
+                inventarios::id_persona.eq(responsable.id),
+                inventarios::cantidad_disponible.eq(self.stock),
+            ))
+            .execute(conn)
+            .expect("no se pudo crear el inventario de prueba");
+
+        producto
+    }
+}
+
+pub struct VentaBuilder {
+    id_cliente: Uuid,
+    id_vendedor: Option<Uuid>,
+    sucursal: Option<String>,
+    fecha: NaiveDateTime,
+    credito: bool,
+    items: Vec<(Uuid, i32, BigDecimal)>,
+}
+
+impl VentaBuilder {
+    pub fn new(cliente: &Persona) -> Self {
+        VentaBuilder {
+            id_cliente: cliente.id,
+            id_vendedor: None,
+            sucursal: None,
+            fecha: Utc::now().naive_utc(),
+            credito: false,
+            items: Vec::new(),
+        }
+    }
+
+    pub fn sucursal(mut self, sucursal: &str) -> Self {
+        self.sucursal = Some(sucursal.to_string());
+        self
+    }
+
+    pub fn vendedor(mut self, vendedor: &Persona) -> Self {
+        self.id_vendedor = Some(vendedor.id);
+        self
+    }
+
+    /// Venta a crédito, sin pagos: todo su total queda como saldo del cliente
+    pub fn a_credito(mut self) -> Self {
+        self.credito = true;
+        self
+    }
+
+    pub fn fecha(mut self, fecha: NaiveDateTime) -> Self {
+        self.fecha = fecha;
+        self
+    }
+
+    pub fn item(mut self, producto: &Producto, cantidad: i32) -> Self {
+        let monto = &producto.precio_unitario * BigDecimal::from(cantidad);
+        self.items.push((producto.id, cantidad, monto));
+        self
+    }
+
+    /// Inserta la venta y sus detalles; los triggers descuentan el stock
+    pub fn crear(self, conn: &mut PgConnection) -> QueryResult<Uuid> {
+        conn.transaction(|conn| {
+            let total: BigDecimal = self.items.iter().map(|(_, _, monto)| monto).sum();
+            let id_venta = Uuid::new_v4();
+
+            diesel::insert_into(ventas::table)
+                .values((
+                    ventas::id.eq(id_venta),
+                    ventas::id_persona.eq(self.id_cliente),
+                    ventas::fecha.eq(self.fecha),
+                    ventas::monto.eq(total),
+                    ventas::sucursal.eq(&self.sucursal),
+                    ventas::id_vendedor.eq(self.id_vendedor),
+                    ventas::credito.eq(self.credito),
+                ))
+                .execute(conn)?;
+
+            for (id_producto, cantidad, monto) in &self.items {
+                diesel::insert_into(detalle_ventas::table)
+                    .values((
+                        detalle_ventas::id_venta.eq(id_venta),
+                        detalle_ventas::id_producto.eq(id_producto),
+                        detalle_ventas::cantidad.eq(cantidad),
+                        detalle_ventas::monto.eq(monto),
+                    ))
+                    .execute(conn)?;
+            }
+
+            Ok(id_venta)
+        })
+    }
+}
+
+pub struct SesionCajaBuilder {
+    id_operador: Uuid,
+    sucursal: String,
+    terminal: String,
+    monto_apertura: BigDecimal,
+}
+
+impl SesionCajaBuilder {
+    /// Sesión abierta en una terminal propia de la sucursal "Centro", sin efectivo inicial
+    pub fn new(operador: &Persona) -> Self {
+        SesionCajaBuilder {
+            id_operador: operador.id,
+            sucursal: "Centro".to_string(),
+            terminal: format!("CAJA-{}", &Uuid::new_v4().simple().to_string()[..8]),
+            monto_apertura: BigDecimal::from(0),
+        }
+    }
+
+    pub fn sucursal(mut self, sucursal: &str) -> Self {
+        self.sucursal = sucursal.to_string();
+        self
+    }
+
+    pub fn monto_apertura(mut self, monto: i64) -> Self {
+        self.monto_apertura = BigDecimal::from(monto);
+        self
+    }
+
+    pub fn crear(self, conn: &mut PgConnection) -> Uuid {
+        diesel::insert_into(sesiones_caja::table)
+            .values((
+                sesiones_caja::id_operador.eq(self.id_operador),
+                sesiones_caja::sucursal.eq(self.sucursal),
+                sesiones_caja::terminal.eq(self.terminal),
+                sesiones_caja::monto_apertura.eq(self.monto_apertura),
+            ))
+            .returning(sesiones_caja::id)
+            .get_result(conn)
+            .expect("no se pudo abrir la sesión de caja de prueba")
+    }
+}
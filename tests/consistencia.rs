@@ -0,0 +1,159 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test;
+use diesel::prelude::*;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use common::builders::{PersonaBuilder, ProductoBuilder, SesionCajaBuilder};
+use common::TestDb;
+use poli_market_api::modules::common::types::TipoMovimiento;
+use poli_market_api::schema::{detalle_inventarios, inventarios};
+
+#[actix_web::test]
+async fn operaciones_de_la_api_no_generan_descuadres() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let cliente = PersonaBuilder::cliente().limite_credito(1_000_000).crear(&mut db.conn());
+    let caja = SesionCajaBuilder::new(&vendedor).crear(&mut db.conn());
+    let app = app!(db);
+
+    let req = test::TestRequest::post()
+        .uri("/v1/productos")
+        .set_json(json!({"nombre": "Monitor 27", "cantidad": 10, "unidad_venta": "Unidad", "precio_unitario": 950.5}))
+        .to_request();
+    let creado: Value = test::read_body_json(test::call_service(&app, req).await).await;
+    let id_producto = creado["id"].as_str().unwrap().to_string();
+
+    // SALIDA manual (se guarda en positivo) y venta (se guarda en negativo)
+    let req = test::TestRequest::post()
+        .uri("/v1/inventario/movimientos")
+        .set_json(json!({
+            "id_producto": id_producto,
+            "tipo_movimiento": "SALIDA",
+            "id_persona": vendedor.id,
+            "cantidad": 2
+        }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+    let req = test::TestRequest::post()
+        .uri("/v1/ventas")
+        .set_json(json!({"id_cliente": cliente.id, "id_sesion_caja": caja, "detalles": [{"id_producto": id_producto, "cantidad": 3}]}))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+
+    let req = test::TestRequest::get().uri("/v1/consistencia/stock").to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let verificacion: Value = test::read_body_json(res).await;
+    assert_eq!(verificacion["consistente"], true);
+    assert_eq!(verificacion["descuadres"], json!([]));
+}
+
+#[actix_web::test]
+async fn reparar_registra_un_ajuste_sin_tocar_el_stock() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let auditor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    // El builder no registra la ENTRADA inicial: stock 8 frente a 0 en movimientos
+    let producto = ProductoBuilder::new().stock(8).crear(&mut db.conn(), &vendedor);
+    let app = app!(db);
+
+    let req = test::TestRequest::get().uri("/v1/consistencia/stock").to_request();
+    let verificacion: Value = test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(verificacion["consistente"], false);
+    assert_eq!(verificacion["descuadres"][0]["id_producto"], producto.id.to_string());
+    assert_eq!(verificacion["descuadres"][0]["diferencia"], 8);
+    assert!(verificacion["descuadres"][0]["id_ajuste"].is_null());
+
+    let req = test::TestRequest::post()
+        .uri("/v1/consistencia/stock/reparar")
+        .set_json(json!({"id_persona": auditor.id, "motivo": "Conteo físico"}))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let reparacion: Value = test::read_body_json(res).await;
+    assert_eq!(reparacion["descuadres"].as_array().unwrap().len(), 1);
+    let id_ajuste = Uuid::parse_str(reparacion["descuadres"][0]["id_ajuste"].as_str().unwrap()).unwrap();
+
+    let (tipo, cantidad, id_persona, observaciones): (TipoMovimiento, i32, Uuid, Option<String>) =
+        detalle_inventarios::table
+            .find(id_ajuste)
+            .select((
+                detalle_inventarios::tipo_movimiento,
+                detalle_inventarios::cantidad,
+                detalle_inventarios::id_persona,
+                detalle_inventarios::observaciones,
+            ))
+            .first(&mut db.conn())
+            .unwrap();
+    assert_eq!(tipo, TipoMovimiento::Ajuste);
+    assert_eq!(cantidad, 8);
+    assert_eq!(id_persona, auditor.id);
+    let observaciones = observaciones.unwrap();
+    assert!(observaciones.contains("stock 8, suma de movimientos 0, diferencia +8"), "{}", observaciones);
+    assert!(observaciones.ends_with("Conteo físico"));
+    assert_eq!(db.stock(producto.id), 8);
+
+    let req = test::TestRequest::get().uri("/v1/consistencia/stock").to_request();
+    let verificacion: Value = test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(verificacion["consistente"], true);
+}
+
+#[actix_web::test]
+async fn reparar_un_producto_deja_los_demas_descuadrados() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let teclado = ProductoBuilder::new().nombre("Teclado").stock(5).crear(&mut db.conn(), &vendedor);
+    let mouse = ProductoBuilder::new().nombre("Mouse").stock(4).crear(&mut db.conn(), &vendedor);
+    // Un UPDATE directo desvía el stock del mouse todavía más
+    diesel::update(inventarios::table.filter(inventarios::id_producto.eq(mouse.id)))
+        .set(inventarios::cantidad_disponible.eq(1))
+        .execute(&mut db.conn())
+        .unwrap();
+    let app = app!(db);
+
+    let req = test::TestRequest::post()
+        .uri("/v1/consistencia/stock/reparar")
+        .set_json(json!({"id_producto": teclado.id}))
+        .to_request();
+    let reparacion: Value = test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(reparacion["descuadres"].as_array().unwrap().len(), 1);
+    assert_eq!(reparacion["descuadres"][0]["id_producto"], teclado.id.to_string());
+
+    let req = test::TestRequest::get().uri("/v1/consistencia/stock").to_request();
+    let verificacion: Value = test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(verificacion["descuadres"].as_array().unwrap().len(), 1);
+    assert_eq!(verificacion["descuadres"][0]["id_producto"], mouse.id.to_string());
+    assert_eq!(verificacion["descuadres"][0]["cantidad_disponible"], 1);
+}
+
+#[actix_web::test]
+async fn reparar_con_datos_invalidos_se_rechaza() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let producto = ProductoBuilder::new().stock(3).crear(&mut db.conn(), &vendedor);
+    let app = app!(db);
+
+    let casos = [
+        (json!({"id_persona": "no-es-uuid"}), StatusCode::BAD_REQUEST),
+        (json!({"id_producto": "no-es-uuid"}), StatusCode::BAD_REQUEST),
+        (json!({"id_persona": Uuid::new_v4()}), StatusCode::NOT_FOUND),
+    ];
+
+    for (body, esperado) in casos {
+        let req = test::TestRequest::post()
+            .uri("/v1/consistencia/stock/reparar")
+            .set_json(&body)
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), esperado, "body: {}", body);
+    }
+
+    let movimientos: i64 = detalle_inventarios::table
+        .filter(detalle_inventarios::id_producto.eq(producto.id))
+        .count()
+        .get_result(&mut db.conn())
+        .unwrap();
+    assert_eq!(movimientos, 0);
+}
@@ -0,0 +1,202 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test;
+use chrono::{Days, Utc};
+use diesel::prelude::*;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use common::builders::{PersonaBuilder, ProductoBuilder, SesionCajaBuilder};
+use common::TestDb;
+use poli_market_api::modules::common::types::EstadoCotizacion;
+use poli_market_api::schema::cotizaciones;
+
+#[actix_web::test]
+async fn la_cotizacion_aceptada_se_convierte_en_una_sola_venta() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let cliente = PersonaBuilder::cliente().crear(&mut db.conn());
+    let producto = ProductoBuilder::new().precio(100).stock(10).crear(&mut db.conn(), &vendedor);
+    let caja = SesionCajaBuilder::new(&vendedor).crear(&mut db.conn());
+    let app = app!(db);
+
+    let req = test::TestRequest::post()
+        .uri("/v1/cotizaciones")
+        .set_json(json!({"id_cliente": cliente.id, "id_vendedor": vendedor.id,
+                         "detalles": [{"id_producto": producto.id, "cantidad": 2}]}))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let cotizacion: Value = test::read_body_json(res).await;
+    assert_eq!(cotizacion["estado"], "BORRADOR");
+    assert_eq!(cotizacion["total"], 200.0);
+    assert_eq!(cotizacion["detalles"][0]["origen_precio"], "CATALOGO");
+    let vencimiento = (Utc::now().date_naive() + Days::new(15)).format("%Y-%m-%d").to_string();
+    assert_eq!(cotizacion["fecha_vencimiento"], vencimiento.as_str());
+    let id = cotizacion["id"].as_str().unwrap().to_string();
+
+    // En BORRADOR se edita y se vuelve a cotizar
+    let editar = || {
+        test::TestRequest::put()
+            .uri(&format!("/v1/cotizaciones/{}", id))
+            .set_json(json!({"id_cliente": cliente.id, "id_vendedor": vendedor.id,
+                             "detalles": [{"id_producto": producto.id, "cantidad": 3}]}))
+            .to_request()
+    };
+    let editada: Value = test::call_and_read_body_json(&app, editar()).await;
+    assert_eq!(editada["total"], 300.0);
+    assert_eq!(editada["detalles"].as_array().unwrap().len(), 1);
+
+    let transicion = |accion: &str| {
+        test::TestRequest::post()
+            .uri(&format!("/v1/cotizaciones/{}/{}", id, accion))
+            .to_request()
+    };
+    assert_eq!(test::call_service(&app, transicion("aceptar")).await.status(), StatusCode::BAD_REQUEST);
+    let enviada: Value = test::call_and_read_body_json(&app, transicion("enviar")).await;
+    assert_eq!(enviada["estado"], "ENVIADA");
+    assert_eq!(test::call_service(&app, editar()).await.status(), StatusCode::BAD_REQUEST);
+    let aceptada: Value = test::call_and_read_body_json(&app, transicion("aceptar")).await;
+    assert_eq!(aceptada["estado"], "ACEPTADA");
+
+    let convertir = || {
+        test::TestRequest::post()
+            .uri(&format!("/v1/cotizaciones/{}/convertir", id))
+            .set_json(json!({"id_sesion_caja": caja, "pagos": [{"metodo": "EFECTIVO", "monto": 300.0}]}))
+            .to_request()
+    };
+    let res = test::call_service(&app, convertir()).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let creada: Value = test::read_body_json(res).await;
+    assert_eq!(creada["estado"], "PAGADA");
+    let id_venta = creada["id"].as_str().unwrap();
+
+    let req = test::TestRequest::get().uri(&format!("/v1/ventas/{}", id_venta)).to_request();
+    let venta: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(venta["total"], 300.0);
+    assert_eq!(venta["id_cotizacion"], id.as_str());
+    assert_eq!(venta["id_vendedor"], vendedor.id.to_string());
+
+    let req = test::TestRequest::get().uri(&format!("/v1/cotizaciones/{}", id)).to_request();
+    let convertida: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(convertida["estado"], "CONVERTIDA");
+    assert_eq!(convertida["id_venta"], id_venta);
+
+    // Una segunda conversión no crea otra venta ni descuenta stock
+    assert_eq!(test::call_service(&app, convertir()).await.status(), StatusCode::BAD_REQUEST);
+    let req = test::TestRequest::get()
+        .uri(&format!("/v1/ventas?id_cliente={}", cliente.id))
+        .to_request();
+    let ventas: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(ventas.as_array().unwrap().len(), 1);
+}
+
+#[actix_web::test]
+async fn cotizaciones_vencidas_o_invalidas_se_rechazan() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let cliente = PersonaBuilder::cliente().crear(&mut db.conn());
+    let producto = ProductoBuilder::new().precio(100).stock(10).crear(&mut db.conn(), &vendedor);
+    let caja = SesionCajaBuilder::new(&vendedor).crear(&mut db.conn());
+    let app = app!(db);
+
+    let crear = |cuerpo: Value| test::TestRequest::post().uri("/v1/cotizaciones").set_json(cuerpo).to_request();
+    for cuerpo in [
+        json!({"id_cliente": cliente.id, "detalles": []}),
+        json!({"id_cliente": cliente.id, "fecha_vencimiento": "2020-01-01",
+               "detalles": [{"id_producto": producto.id, "cantidad": 1}]}),
+        json!({"id_cliente": cliente.id, "detalles": [{"id_producto": producto.id, "cantidad": 0}]}),
+    ] {
+        assert_eq!(test::call_service(&app, crear(cuerpo)).await.status(), StatusCode::BAD_REQUEST);
+    }
+    let res = test::call_service(
+        &app,
+        crear(json!({"id_cliente": Uuid::new_v4(), "detalles": [{"id_producto": producto.id, "cantidad": 1}]})),
+    )
+    .await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    let cotizacion: Value = test::call_and_read_body_json(
+        &app,
+        crear(json!({"id_cliente": cliente.id, "detalles": [{"id_producto": producto.id, "cantidad": 1}]})),
+    )
+    .await;
+    let id = cotizacion["id"].as_str().unwrap().to_string();
+    for accion in ["enviar", "aceptar"] {
+        let req = test::TestRequest::post()
+            .uri(&format!("/v1/cotizaciones/{}/{}", id, accion))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+    }
+
+    // Pasada su fecha de vencimiento ya no se puede vender
+    let ayer = Utc::now().date_naive() - Days::new(1);
+    diesel::update(cotizaciones::table.find(Uuid::parse_str(&id).unwrap()))
+        .set(cotizaciones::fecha_vencimiento.eq(ayer))
+        .execute(&mut db.conn())
+        .unwrap();
+    let req = test::TestRequest::post()
+        .uri(&format!("/v1/cotizaciones/{}/convertir", id))
+        .set_json(json!({"id_sesion_caja": caja}))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+
+    let listar = |filtro: &str| test::TestRequest::get().uri(&format!("/v1/cotizaciones?{}", filtro)).to_request();
+    let vencidas: Value = test::call_and_read_body_json(&app, listar("estado=VENCIDA")).await;
+    assert_eq!(vencidas.as_array().unwrap().len(), 1);
+    assert_eq!(vencidas[0]["id"], id.as_str());
+    assert_eq!(test::call_service(&app, listar("estado=PERDIDA")).await.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/v1/cotizaciones/{}", Uuid::new_v4()))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn consultar_una_cotizacion_vencida_no_la_modifica() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let cliente = PersonaBuilder::cliente().crear(&mut db.conn());
+    let producto = ProductoBuilder::new().precio(100).stock(10).crear(&mut db.conn(), &vendedor);
+    let app = app!(db);
+
+    let req = test::TestRequest::post()
+        .uri("/v1/cotizaciones")
+        .set_json(json!({"id_cliente": cliente.id, "detalles": [{"id_producto": producto.id, "cantidad": 1}]}))
+        .to_request();
+    let cotizacion: Value = test::call_and_read_body_json(&app, req).await;
+    let id = Uuid::parse_str(cotizacion["id"].as_str().unwrap()).unwrap();
+    let ayer = Utc::now().date_naive() - Days::new(1);
+    diesel::update(cotizaciones::table.find(id))
+        .set(cotizaciones::fecha_vencimiento.eq(ayer))
+        .execute(&mut db.conn())
+        .unwrap();
+
+    // Las consultas la muestran vencida sin escribir en la base
+    let req = test::TestRequest::get().uri(&format!("/v1/cotizaciones/{}", id)).to_request();
+    let leida: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(leida["estado"], "VENCIDA");
+    let listar = |filtro: &str| test::TestRequest::get().uri(&format!("/v1/cotizaciones?{}", filtro)).to_request();
+    let vencidas: Value = test::call_and_read_body_json(&app, listar("estado=VENCIDA")).await;
+    assert_eq!(vencidas.as_array().unwrap().len(), 1);
+    let borradores: Value = test::call_and_read_body_json(&app, listar("estado=BORRADOR")).await;
+    assert!(borradores.as_array().unwrap().is_empty());
+    let estado: EstadoCotizacion = cotizaciones::table
+        .find(id)
+        .select(cotizaciones::estado)
+        .first(&mut db.conn())
+        .unwrap();
+    assert_eq!(estado, EstadoCotizacion::Borrador);
+
+    // Una operación que cambia el estado sí la persiste
+    let req = test::TestRequest::post().uri(&format!("/v1/cotizaciones/{}/enviar", id)).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+    let estado: EstadoCotizacion = cotizaciones::table
+        .find(id)
+        .select(cotizaciones::estado)
+        .first(&mut db.conn())
+        .unwrap();
+    assert_eq!(estado, EstadoCotizacion::Vencida);
+}
@@ -0,0 +1,134 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test;
+use chrono::{Duration, Utc};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use common::builders::{PersonaBuilder, ProductoBuilder, SesionCajaBuilder, VentaBuilder};
+use common::TestDb;
+
+#[actix_web::test]
+async fn estado_de_cuenta_reparte_el_saldo_por_antiguedad() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let cliente = PersonaBuilder::cliente().crear(&mut db.conn());
+    let producto = ProductoBuilder::new().precio(100).stock(20).crear(&mut db.conn(), &vendedor);
+
+    let mut ventas = Vec::new();
+    for (dias, cantidad) in [(120, 3), (75, 1), (45, 2), (10, 1)] {
+        let id = VentaBuilder::new(&cliente)
+            .a_credito()
+            .fecha(Utc::now().naive_utc() - Duration::days(dias))
+            .item(&producto, cantidad)
+            .crear(&mut db.conn())
+            .unwrap();
+        ventas.push(id);
+    }
+    let caja = SesionCajaBuilder::new(&vendedor).crear(&mut db.conn());
+    let app = app!(db);
+
+    let limite = |id: Uuid, limite: Value| {
+        test::TestRequest::put()
+            .uri(&format!("/v1/personas/{}/credito", id))
+            .set_json(json!({"limite_credito": limite}))
+            .to_request()
+    };
+    let res = test::call_service(&app, limite(cliente.id, json!(1000.0))).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let persona: Value = test::read_body_json(res).await;
+    assert_eq!(persona["limite_credito"], 1000.0);
+    assert_eq!(test::call_service(&app, limite(vendedor.id, json!(1000.0))).await.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(test::call_service(&app, limite(Uuid::new_v4(), json!(1000.0))).await.status(), StatusCode::NOT_FOUND);
+
+    // Debe 700: una venta de 400 solo cabe abonando 100
+    let venta = |pagos: Value| {
+        test::TestRequest::post()
+            .uri("/v1/ventas")
+            .set_json(json!({"id_cliente": cliente.id, "id_sesion_caja": caja, "credito": true, "pagos": pagos,
+                             "detalles": [{"id_producto": producto.id, "cantidad": 4}]}))
+            .to_request()
+    };
+    assert_eq!(test::call_service(&app, venta(json!([]))).await.status(), StatusCode::BAD_REQUEST);
+    let res = test::call_service(&app, venta(json!([{"metodo": "EFECTIVO", "monto": 100.0}]))).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let creada: Value = test::read_body_json(res).await;
+    assert_eq!(creada["estado"], "PENDIENTE_PAGO");
+    assert_eq!(creada["saldo"], 300.0);
+
+    let estado_cuenta = || {
+        test::TestRequest::get()
+            .uri(&format!("/v1/personas/{}/estado-cuenta", cliente.id))
+            .to_request()
+    };
+    let cuenta: Value = test::call_and_read_body_json(&app, estado_cuenta()).await;
+    assert_eq!(cuenta["saldo"], 1000.0);
+    assert_eq!(cuenta["credito_disponible"], 0.0);
+    assert_eq!(
+        cuenta["antiguedad"],
+        json!({"dias_0_30": 400.0, "dias_31_60": 200.0, "dias_61_90": 100.0, "mas_de_90": 300.0})
+    );
+    assert_eq!(cuenta["documentos"].as_array().unwrap().len(), 5);
+    assert_eq!(cuenta["documentos"][0]["id_venta"], ventas[0].to_string());
+    assert_eq!(cuenta["documentos"][0]["dias"], 120);
+    let movimientos = cuenta["movimientos"].as_array().unwrap();
+    assert_eq!(movimientos.len(), 6);
+    assert_eq!(movimientos[4]["tipo"], "CARGO");
+    assert_eq!(movimientos[5]["tipo"], "ABONO");
+    assert_eq!(movimientos[5]["metodo"], "EFECTIVO");
+    assert_eq!(movimientos[5]["saldo"], 1000.0);
+
+    // Un abono parcial a la venta más antigua
+    let req = test::TestRequest::post()
+        .uri(&format!("/v1/ventas/{}/pagos", ventas[0]))
+        .set_json(json!({"pagos": [{"metodo": "TRANSFERENCIA", "monto": 100.0, "referencia": "TRF-1"}],
+                         "id_sesion_caja": caja}))
+        .to_request();
+    let abonada: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(abonada["estado"], "PENDIENTE_PAGO");
+    assert_eq!(abonada["credito"], true);
+    assert_eq!(abonada["saldo"], 200.0);
+
+    let cuenta: Value = test::call_and_read_body_json(&app, estado_cuenta()).await;
+    assert_eq!(cuenta["saldo"], 900.0);
+    assert_eq!(cuenta["credito_disponible"], 100.0);
+    assert_eq!(cuenta["antiguedad"]["mas_de_90"], 200.0);
+
+    // Retirar el crédito no borra lo que debe
+    assert_eq!(test::call_service(&app, limite(cliente.id, Value::Null)).await.status(), StatusCode::OK);
+    let cuenta: Value = test::call_and_read_body_json(&app, estado_cuenta()).await;
+    assert_eq!(cuenta["saldo"], 900.0);
+    assert_eq!(cuenta["credito_disponible"], Value::Null);
+
+    let req = test::TestRequest::get().uri("/v1/personas/no-es-uuid/estado-cuenta").to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+    let req = test::TestRequest::get()
+        .uri(&format!("/v1/personas/{}/estado-cuenta", Uuid::new_v4()))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn cliente_sin_credito_no_deja_ventas_pendientes_de_pago() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let cliente = PersonaBuilder::cliente().limite_credito(0).crear(&mut db.conn());
+    let producto = ProductoBuilder::new().precio(100).stock(5).crear(&mut db.conn(), &vendedor);
+    let caja = SesionCajaBuilder::new(&vendedor).crear(&mut db.conn());
+    let app = app!(db);
+
+    let venta = |pagos: Value| {
+        test::TestRequest::post()
+            .uri("/v1/ventas")
+            .set_json(json!({"id_cliente": cliente.id, "id_sesion_caja": caja, "pagos": pagos,
+                             "detalles": [{"id_producto": producto.id, "cantidad": 1}]}))
+            .to_request()
+    };
+    assert_eq!(test::call_service(&app, venta(json!([]))).await.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(db.stock(producto.id), 5);
+    let res = test::call_service(&app, venta(json!([{"metodo": "EFECTIVO", "monto": 100.0}]))).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let creada: Value = test::read_body_json(res).await;
+    assert_eq!(creada["estado"], "PAGADA");
+}
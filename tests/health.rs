@@ -0,0 +1,40 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test;
+use serde_json::Value;
+
+use common::TestDb;
+
+#[actix_web::test]
+async fn liveness_y_readiness_con_base_migrada() {
+    let db = TestDb::new();
+    let app = app!(db);
+
+    let req = test::TestRequest::get().uri("/v1/health/live").to_request();
+    let live: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(live["status"], "healthy");
+
+    let req = test::TestRequest::get().uri("/v1/health/ready").to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let ready: Value = test::read_body_json(res).await;
+    assert_eq!(ready["components"]["database"]["status"], "healthy");
+    assert_eq!(ready["components"]["migrations"]["status"], "healthy");
+}
+
+#[actix_web::test]
+async fn metrics_expone_formato_prometheus() {
+    let db = TestDb::new();
+    let app = app!(db);
+
+    let req = test::TestRequest::get().uri("/v1/productos").to_request();
+    test::call_service(&app, req).await;
+
+    let req = test::TestRequest::get().uri("/metrics").to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let cuerpo = String::from_utf8(test::read_body(res).await.to_vec()).unwrap();
+    assert!(cuerpo.contains("polimarket_http_request_duration_seconds_bucket"));
+    assert!(cuerpo.contains(r#"route="/v1/productos""#));
+}
@@ -0,0 +1,252 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test;
+use diesel::prelude::*;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use common::builders::{PersonaBuilder, ProductoBuilder, VentaBuilder};
+use common::TestDb;
+use poli_market_api::schema::detalle_inventarios;
+
+fn movimiento(id_producto: Uuid, id_persona: Uuid, tipo: &str, cantidad: i32) -> Value {
+    json!({
+        "id_producto": id_producto,
+        "tipo_movimiento": tipo,
+        "id_persona": id_persona,
+        "cantidad": cantidad,
+        "observaciones": "test"
+    })
+}
+
+#[actix_web::test]
+async fn entrada_y_salida_actualizan_el_stock() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let producto = ProductoBuilder::new().stock(10).crear(&mut db.conn(), &vendedor);
+    let app = app!(db);
+
+    let req = test::TestRequest::post()
+        .uri("/v1/inventario/movimientos")
+        .set_json(movimiento(producto.id, vendedor.id, "ENTRADA", 5))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+    assert_eq!(db.stock(producto.id), 15);
+
+    let req = test::TestRequest::post()
+        .uri("/v1/inventario/movimientos")
+        .set_json(movimiento(producto.id, vendedor.id, "salida", 3))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+    assert_eq!(db.stock(producto.id), 12);
+}
+
+#[actix_web::test]
+async fn salida_sin_stock_suficiente_se_rechaza() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let producto = ProductoBuilder::new().stock(2).crear(&mut db.conn(), &vendedor);
+    let app = app!(db);
+
+    let req = test::TestRequest::post()
+        .uri("/v1/inventario/movimientos")
+        .set_json(movimiento(producto.id, vendedor.id, "SALIDA", 3))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let error: Value = test::read_body_json(res).await;
+    assert_eq!(error["code"], "BUSINESS_RULE_VIOLATION");
+    assert_eq!(db.stock(producto.id), 2);
+}
+
+#[actix_web::test]
+async fn movimiento_invalido_se_rechaza() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let inactiva = PersonaBuilder::vendedor().inactiva().crear(&mut db.conn());
+    let producto = ProductoBuilder::new().crear(&mut db.conn(), &vendedor);
+    let app = app!(db);
+
+    let casos = [
+        (movimiento(producto.id, vendedor.id, "TRASLADO", 1), StatusCode::BAD_REQUEST),
+        (movimiento(producto.id, vendedor.id, "ENTRADA", 0), StatusCode::BAD_REQUEST),
+        (movimiento(Uuid::new_v4(), vendedor.id, "ENTRADA", 1), StatusCode::NOT_FOUND),
+        (movimiento(producto.id, inactiva.id, "ENTRADA", 1), StatusCode::NOT_FOUND),
+    ];
+
+    for (body, esperado) in casos {
+        let req = test::TestRequest::post()
+            .uri("/v1/inventario/movimientos")
+            .set_json(&body)
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), esperado, "body: {}", body);
+    }
+    assert_eq!(db.stock(producto.id), 10);
+}
+
+#[actix_web::test]
+async fn disponibilidad_informa_el_estado_del_stock() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let casos = [(0, "SIN_STOCK"), (3, "STOCK_BAJO"), (50, "STOCK_OK")];
+    let productos: Vec<_> = casos
+        .iter()
+        .map(|(stock, _)| ProductoBuilder::new().stock(*stock).crear(&mut db.conn(), &vendedor))
+        .collect();
+    let app = app!(db);
+
+    for (producto, (stock, estado)) in productos.iter().zip(casos) {
+        let req = test::TestRequest::get()
+            .uri(&format!("/v1/inventario/disponibilidad/{}", producto.id))
+            .to_request();
+        let disponibilidad: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(disponibilidad["cantidad_disponible"], stock);
+        assert_eq!(disponibilidad["estado_stock"], estado);
+    }
+}
+
+#[actix_web::test]
+async fn movimiento_registrado_se_puede_consultar_por_id() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let producto = ProductoBuilder::new().stock(10).crear(&mut db.conn(), &vendedor);
+    let app = app!(db);
+
+    let req = test::TestRequest::post()
+        .uri("/v1/inventario/movimientos")
+        .set_json(movimiento(producto.id, vendedor.id, "SALIDA", 4))
+        .to_request();
+    let registrado: Value = test::call_and_read_body_json(&app, req).await;
+    let id = registrado["id"].as_str().unwrap();
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/v1/inventario/movimientos/id/{}", id))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let consultado: Value = test::read_body_json(res).await;
+    assert_eq!(consultado["id"], id);
+    assert_eq!(consultado["id_producto"], producto.id.to_string());
+    assert_eq!(consultado["tipo_movimiento"], "SALIDA");
+    assert_eq!(consultado["cantidad"], 4);
+    assert_eq!(consultado["efecto_en_stock"], -4);
+    assert!(consultado["id_reversion"].is_null());
+
+    for (uri, esperado) in [
+        (format!("/v1/inventario/movimientos/id/{}", Uuid::new_v4()), StatusCode::NOT_FOUND),
+        ("/v1/inventario/movimientos/id/no-es-uuid".to_string(), StatusCode::BAD_REQUEST),
+    ] {
+        let req = test::TestRequest::get().uri(&uri).to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), esperado, "uri: {}", uri);
+    }
+}
+
+#[actix_web::test]
+async fn revertir_movimiento_registra_el_compensatorio_una_sola_vez() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let producto = ProductoBuilder::new().stock(10).crear(&mut db.conn(), &vendedor);
+    let app = app!(db);
+
+    let req = test::TestRequest::post()
+        .uri("/v1/inventario/movimientos")
+        .set_json(movimiento(producto.id, vendedor.id, "ENTRADA", 5))
+        .to_request();
+    let entrada: Value = test::call_and_read_body_json(&app, req).await;
+    let id_entrada = entrada["id"].as_str().unwrap().to_string();
+    assert_eq!(db.stock(producto.id), 15);
+
+    let revertir = |id: &str| {
+        test::TestRequest::post()
+            .uri(&format!("/v1/inventario/movimientos/id/{}/reversion", id))
+            .set_json(json!({"id_persona": vendedor.id, "observaciones": "Entrada duplicada"}))
+            .to_request()
+    };
+
+    let res = test::call_service(&app, revertir(&id_entrada)).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let reversion: Value = test::read_body_json(res).await;
+    let id_reversion = reversion["id"].as_str().unwrap().to_string();
+    assert_eq!(db.stock(producto.id), 10);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/v1/inventario/movimientos/id/{}", id_reversion))
+        .to_request();
+    let compensatorio: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(compensatorio["tipo_movimiento"], "SALIDA");
+    assert_eq!(compensatorio["efecto_en_stock"], -5);
+    assert_eq!(compensatorio["id_movimiento_revertido"], id_entrada.as_str());
+    assert!(compensatorio["observaciones"].as_str().unwrap().ends_with("Entrada duplicada"));
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/v1/inventario/movimientos/id/{}", id_entrada))
+        .to_request();
+    let original: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(original["id_reversion"], id_reversion.as_str());
+
+    // Ni el original se revierte dos veces ni la reversión se revierte
+    for id in [&id_entrada, &id_reversion] {
+        let res = test::call_service(&app, revertir(id)).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        let error: Value = test::read_body_json(res).await;
+        assert_eq!(error["code"], "BUSINESS_RULE_VIOLATION");
+    }
+    assert_eq!(db.stock(producto.id), 10);
+}
+
+#[actix_web::test]
+async fn revertir_la_salida_de_una_venta_devuelve_las_unidades() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let cliente = PersonaBuilder::cliente().crear(&mut db.conn());
+    let producto = ProductoBuilder::new().stock(6).crear(&mut db.conn(), &vendedor);
+    let id_venta = VentaBuilder::new(&cliente).item(&producto, 2).crear(&mut db.conn()).unwrap();
+    let id_salida: Uuid = detalle_inventarios::table
+        .filter(detalle_inventarios::observaciones.eq(format!("Venta ID: {}", id_venta)))
+        .select(detalle_inventarios::id)
+        .first(&mut db.conn())
+        .unwrap();
+    assert_eq!(db.stock(producto.id), 4);
+    let app = app!(db);
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/v1/inventario/movimientos/id/{}/reversion", id_salida))
+        .set_json(json!({"id_persona": vendedor.id}))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+    assert_eq!(db.stock(producto.id), 6);
+}
+
+#[actix_web::test]
+async fn revertir_una_entrada_ya_vendida_se_rechaza() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let producto = ProductoBuilder::new().stock(0).crear(&mut db.conn(), &vendedor);
+    let app = app!(db);
+
+    let req = test::TestRequest::post()
+        .uri("/v1/inventario/movimientos")
+        .set_json(movimiento(producto.id, vendedor.id, "ENTRADA", 3))
+        .to_request();
+    let entrada: Value = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::post()
+        .uri("/v1/inventario/movimientos")
+        .set_json(movimiento(producto.id, vendedor.id, "SALIDA", 2))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+
+    let casos = [
+        (json!({"id_persona": vendedor.id}), StatusCode::BAD_REQUEST),
+        (json!({"id_persona": "no-es-uuid"}), StatusCode::BAD_REQUEST),
+        (json!({"id_persona": Uuid::new_v4()}), StatusCode::NOT_FOUND),
+    ];
+    for (body, esperado) in casos {
+        let req = test::TestRequest::post()
+            .uri(&format!("/v1/inventario/movimientos/id/{}/reversion", entrada["id"].as_str().unwrap()))
+            .set_json(&body)
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), esperado, "body: {}", body);
+    }
+    assert_eq!(db.stock(producto.id), 1);
+}
@@ -0,0 +1,142 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test;
+use chrono::{Duration, Utc};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use common::builders::{PersonaBuilder, ProductoBuilder, SesionCajaBuilder};
+use common::TestDb;
+
+/// Fecha a `dias` de hoy, como la espera la API
+fn en_dias(dias: i64) -> String {
+    (Utc::now().date_naive() + Duration::days(dias)).format("%Y-%m-%d").to_string()
+}
+
+fn movimiento_lote(id_producto: Uuid, id_persona: Uuid, tipo: &str, cantidad: i32, lote: &str, vence: Option<String>) -> Value {
+    json!({
+        "id_producto": id_producto,
+        "tipo_movimiento": tipo,
+        "id_persona": id_persona,
+        "cantidad": cantidad,
+        "lote": lote,
+        "fecha_vencimiento": vence
+    })
+}
+
+#[actix_web::test]
+async fn venta_consume_primero_el_lote_que_vence_antes() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let cliente = PersonaBuilder::cliente().limite_credito(1_000_000).crear(&mut db.conn());
+    let yogur = ProductoBuilder::new().nombre("Yogur natural 1L").stock(0).crear(&mut db.conn(), &vendedor);
+    let caja = SesionCajaBuilder::new(&vendedor).crear(&mut db.conn());
+    let app = app!(db);
+
+    let entrada = |cantidad: i32, lote: &str, vence: Option<String>| {
+        test::TestRequest::post()
+            .uri("/v1/inventario/movimientos")
+            .set_json(movimiento_lote(yogur.id, vendedor.id, "ENTRADA", cantidad, lote, vence))
+            .to_request()
+    };
+    for (cantidad, lote, vence) in [(10, "l-b", Some(en_dias(20))), (5, "L-A", Some(en_dias(5))), (3, " l-a ", None)] {
+        let res = test::call_service(&app, entrada(cantidad, lote, vence)).await;
+        assert_eq!(res.status(), StatusCode::CREATED, "lote {}", lote);
+    }
+    // Un lote existente no cambia de vencimiento y uno nuevo necesita fecha
+    let res = test::call_service(&app, entrada(1, "L-A", Some(en_dias(9)))).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let res = test::call_service(&app, entrada(1, "L-C", None)).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(db.stock(yogur.id), 18);
+
+    let req = test::TestRequest::post()
+        .uri("/v1/ventas")
+        .set_json(json!({"id_cliente": cliente.id, "id_sesion_caja": caja, "detalles": [{"id_producto": yogur.id, "cantidad": 12}]}))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let creada: Value = test::read_body_json(res).await;
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/v1/ventas/{}", creada["id"].as_str().unwrap()))
+        .to_request();
+    let venta: Value = test::call_and_read_body_json(&app, req).await;
+    let consumidos: Vec<(&str, i64)> = venta["detalles"][0]["lotes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|lote| (lote["codigo"].as_str().unwrap(), lote["cantidad"].as_i64().unwrap()))
+        .collect();
+    assert_eq!(consumidos, [("L-A", 8), ("L-B", 4)]);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/v1/inventario/disponibilidad/{}", yogur.id))
+        .to_request();
+    let disponibilidad: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(disponibilidad["cantidad_disponible"], 6);
+    assert_eq!(disponibilidad["lotes"].as_array().unwrap().len(), 1);
+    assert_eq!(disponibilidad["lotes"][0]["codigo"], "L-B");
+    assert_eq!(disponibilidad["lotes"][0]["cantidad_disponible"], 6);
+}
+
+#[actix_web::test]
+async fn lotes_vencidos_no_se_venden_y_aparecen_en_vencimientos() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let cliente = PersonaBuilder::cliente().limite_credito(1_000_000).crear(&mut db.conn());
+    let leche = ProductoBuilder::new().nombre("Leche entera").stock(0).crear(&mut db.conn(), &vendedor);
+    let caja = SesionCajaBuilder::new(&vendedor).crear(&mut db.conn());
+    let app = app!(db);
+
+    let registrar = |tipo: &str, cantidad: i32, lote: &str, vence: Option<String>| {
+        test::TestRequest::post()
+            .uri("/v1/inventario/movimientos")
+            .set_json(movimiento_lote(leche.id, vendedor.id, tipo, cantidad, lote, vence))
+            .to_request()
+    };
+    let res = test::call_service(&app, registrar("ENTRADA", 4, "VIEJO", Some(en_dias(-1)))).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let res = test::call_service(&app, registrar("ENTRADA", 2, "NUEVO", Some(en_dias(40)))).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+
+    let vencimientos = |dias: i64| test::TestRequest::get().uri(&format!("/v1/inventario/vencimientos?dias={}", dias)).to_request();
+    let lotes: Value = test::call_and_read_body_json(&app, vencimientos(30)).await;
+    assert_eq!(lotes.as_array().unwrap().len(), 1);
+    assert_eq!(lotes[0]["codigo"], "VIEJO");
+    assert_eq!(lotes[0]["nombre_producto"], "Leche entera");
+    assert_eq!(lotes[0]["dias_para_vencer"], -1);
+    assert_eq!(lotes[0]["vencido"], true);
+    let lotes: Value = test::call_and_read_body_json(&app, vencimientos(60)).await;
+    assert_eq!(lotes.as_array().unwrap().len(), 2);
+    assert_eq!(test::call_service(&app, vencimientos(-1)).await.status(), StatusCode::BAD_REQUEST);
+
+    // Hay 6 en stock pero solo 2 sin vencer
+    let req = test::TestRequest::post()
+        .uri("/v1/ventas")
+        .set_json(json!({"id_cliente": cliente.id, "id_sesion_caja": caja, "detalles": [{"id_producto": leche.id, "cantidad": 3}]}))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let error: Value = test::read_body_json(res).await;
+    assert!(error["error"].as_str().unwrap().contains("lotes vencidos"), "{}", error);
+    assert_eq!(db.stock(leche.id), 6);
+
+    // El lote vencido se da de baja con una SALIDA sobre él; sin lote no hay de dónde sacar
+    let res = test::call_service(&app, registrar("SALIDA", 4, "viejo", None)).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let req = test::TestRequest::post()
+        .uri("/v1/inventario/movimientos")
+        .set_json(json!({
+            "id_producto": leche.id,
+            "tipo_movimiento": "SALIDA",
+            "id_persona": vendedor.id,
+            "cantidad": 1
+        }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+    let lotes: Value = test::call_and_read_body_json(&app, vencimientos(30)).await;
+    assert!(lotes.as_array().unwrap().is_empty());
+    assert_eq!(db.stock(leche.id), 2);
+}
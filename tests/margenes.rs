@@ -0,0 +1,153 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test;
+use chrono::{Duration, Utc};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use common::builders::{PersonaBuilder, ProductoBuilder, SesionCajaBuilder};
+use common::TestDb;
+
+fn entrada(id_producto: Uuid, id_persona: Uuid, cantidad: i32, costo_unitario: f64) -> Value {
+    json!({
+        "id_producto": id_producto,
+        "tipo_movimiento": "ENTRADA",
+        "id_persona": id_persona,
+        "cantidad": cantidad,
+        "costo_unitario": costo_unitario
+    })
+}
+
+#[actix_web::test]
+async fn margenes_por_producto_vendedor_sucursal_y_periodo() {
+    let db = TestDb::new();
+    let ana = PersonaBuilder::vendedor().nombre("Ana").crear(&mut db.conn());
+    let beto = PersonaBuilder::vendedor().nombre("Beto").crear(&mut db.conn());
+    let cliente = PersonaBuilder::cliente().limite_credito(1_000_000).crear(&mut db.conn());
+    let laptop = ProductoBuilder::new().nombre("Laptop").precio(1000).stock(0).crear(&mut db.conn(), &ana);
+    let mouse = ProductoBuilder::new().nombre("Mouse").precio(50).stock(0).crear(&mut db.conn(), &ana);
+    let centro = SesionCajaBuilder::new(&ana).crear(&mut db.conn());
+    let norte = SesionCajaBuilder::new(&beto).sucursal("Norte").crear(&mut db.conn());
+    let app = app!(db);
+
+    for (producto, cantidad, costo) in [(laptop.id, 10, 600.0), (mouse.id, 20, 20.0)] {
+        let req = test::TestRequest::post()
+            .uri("/v1/inventario/movimientos")
+            .set_json(entrada(producto, ana.id, cantidad, costo))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+    }
+
+    let ventas = [
+        json!({"id_cliente": cliente.id, "id_sesion_caja": centro, "id_vendedor": ana.id, "sucursal": "Centro",
+               "detalles": [{"id_producto": laptop.id, "cantidad": 2}, {"id_producto": mouse.id, "cantidad": 5}]}),
+        json!({"id_cliente": cliente.id, "id_sesion_caja": norte, "id_vendedor": beto.id, "sucursal": "Norte",
+               "detalles": [{"id_producto": laptop.id, "cantidad": 1}]}),
+        json!({"id_cliente": cliente.id, "id_sesion_caja": centro, "detalles": [{"id_producto": mouse.id, "cantidad": 4}]}),
+    ];
+    let mut ids = Vec::new();
+    for venta in ventas {
+        let req = test::TestRequest::post().uri("/v1/ventas").set_json(venta).to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+        let creada: Value = test::read_body_json(res).await;
+        ids.push(creada["id"].as_str().unwrap().to_string());
+    }
+
+    let req = test::TestRequest::get().uri(&format!("/v1/ventas/{}", ids[0])).to_request();
+    let venta: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(venta["id_vendedor"], ana.id.to_string());
+
+    let margenes = |consulta: &str| test::TestRequest::get().uri(&format!("/v1/reportes/margenes{}", consulta)).to_request();
+    let resumen = |reporte: &Value| -> Vec<(String, f64, f64, f64)> {
+        reporte["grupos"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|g| {
+                (
+                    g["nombre"].as_str().unwrap().to_string(),
+                    g["ingresos"].as_f64().unwrap(),
+                    g["costo"].as_f64().unwrap(),
+                    g["margen"].as_f64().unwrap(),
+                )
+            })
+            .collect()
+    };
+
+    let reporte: Value = test::call_and_read_body_json(&app, margenes("")).await;
+    assert_eq!(reporte["agrupacion"], "PRODUCTO");
+    assert_eq!(
+        resumen(&reporte),
+        [("Laptop".to_string(), 3000.0, 1800.0, 1200.0), ("Mouse".to_string(), 450.0, 180.0, 270.0)]
+    );
+    assert_eq!(reporte["grupos"][0]["clave"], laptop.id.to_string());
+    assert_eq!(reporte["grupos"][0]["unidades"], 3);
+    assert_eq!(reporte["grupos"][0]["margen_porcentaje"], 40.0);
+    assert_eq!(reporte["total"]["ingresos"], 3450.0);
+    assert_eq!(reporte["total"]["margen"], 1470.0);
+    assert_eq!(reporte["total"]["margen_porcentaje"], 42.61);
+
+    let reporte: Value = test::call_and_read_body_json(&app, margenes("?agrupar=vendedor")).await;
+    assert_eq!(
+        resumen(&reporte),
+        [
+            ("Ana".to_string(), 2250.0, 1300.0, 950.0),
+            ("Beto".to_string(), 1000.0, 600.0, 400.0),
+            ("Sin vendedor".to_string(), 200.0, 80.0, 120.0),
+        ]
+    );
+    assert_eq!(reporte["grupos"][2]["clave"], Value::Null);
+
+    let reporte: Value = test::call_and_read_body_json(&app, margenes("?agrupar=sucursal")).await;
+    let sucursales: Vec<String> = resumen(&reporte).into_iter().map(|(nombre, ..)| nombre).collect();
+    assert_eq!(sucursales, ["Centro", "Norte", "Sin sucursal"]);
+
+    let hoy = Utc::now().date_naive();
+    let reporte: Value = test::call_and_read_body_json(&app, margenes("?agrupar=periodo&periodo=dia")).await;
+    assert_eq!(reporte["grupos"].as_array().unwrap().len(), 1);
+    assert_eq!(reporte["grupos"][0]["clave"], hoy.format("%Y-%m-%d").to_string());
+    assert_eq!(reporte["grupos"][0]["margen"], 1470.0);
+
+    let ayer = (hoy - Duration::days(1)).format("%Y-%m-%d");
+    let reporte: Value =
+        test::call_and_read_body_json(&app, margenes(&format!("?fecha_desde={0}&fecha_hasta={0}", ayer))).await;
+    assert!(reporte["grupos"].as_array().unwrap().is_empty());
+    assert_eq!(reporte["total"]["ingresos"], 0.0);
+    assert_eq!(reporte["total"]["margen_porcentaje"], Value::Null);
+
+    for consulta in ["?agrupar=cliente", "?agrupar=periodo&periodo=trimestre", "?fecha_desde=2026-12-01&fecha_hasta=2026-11-01"] {
+        assert_eq!(test::call_service(&app, margenes(consulta)).await.status(), StatusCode::BAD_REQUEST, "{}", consulta);
+    }
+}
+
+#[actix_web::test]
+async fn venta_con_vendedor_que_no_es_vendedor_activo_es_rechazada() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let inactivo = PersonaBuilder::vendedor().inactiva().crear(&mut db.conn());
+    let cliente = PersonaBuilder::cliente().crear(&mut db.conn());
+    let producto = ProductoBuilder::new().stock(5).crear(&mut db.conn(), &vendedor);
+    let caja = SesionCajaBuilder::new(&vendedor).crear(&mut db.conn());
+    let app = app!(db);
+
+    // Las personas dadas de baja no se encuentran
+    for (id_vendedor, estado) in [
+        (cliente.id.to_string(), StatusCode::BAD_REQUEST),
+        (inactivo.id.to_string(), StatusCode::NOT_FOUND),
+        ("no-es-un-uuid".to_string(), StatusCode::BAD_REQUEST),
+    ] {
+        let req = test::TestRequest::post()
+            .uri("/v1/ventas")
+            .set_json(json!({
+                "id_cliente": cliente.id,
+                "id_sesion_caja": caja,
+                "id_vendedor": id_vendedor,
+                "detalles": [{"id_producto": producto.id, "cantidad": 1}]
+            }))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), estado, "{}", id_vendedor);
+    }
+    assert_eq!(db.stock(producto.id), 5);
+}
@@ -0,0 +1,141 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test;
+use serde_json::{json, Value};
+
+use common::builders::{PersonaBuilder, ProductoBuilder, SesionCajaBuilder};
+use common::TestDb;
+
+#[actix_web::test]
+async fn venta_con_pago_combinado_queda_pagada_y_se_filtra_por_metodo() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let cliente = PersonaBuilder::cliente().limite_credito(1_000_000).crear(&mut db.conn());
+    let producto = ProductoBuilder::new().precio(100).stock(10).crear(&mut db.conn(), &vendedor);
+    let caja = SesionCajaBuilder::new(&vendedor).crear(&mut db.conn());
+    let app = app!(db);
+
+    let venta = |pagos: Value| {
+        test::TestRequest::post()
+            .uri("/v1/ventas")
+            .set_json(json!({"id_cliente": cliente.id, "id_sesion_caja": caja,
+                             "detalles": [{"id_producto": producto.id, "cantidad": 3}],
+                             "pagos": pagos}))
+            .to_request()
+    };
+
+    // La tarjeta no da cambio: la venta se rechaza sin tocar el stock
+    let res = test::call_service(&app, venta(json!([{"metodo": "TARJETA", "monto": 350.0}]))).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(db.stock(producto.id), 10);
+
+    let pagos = json!([{"metodo": "TARJETA", "monto": 200.0, "referencia": "AUT-1"},
+                       {"metodo": "EFECTIVO", "monto": 150.0}]);
+    let res = test::call_service(&app, venta(pagos)).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let creada: Value = test::read_body_json(res).await;
+    assert_eq!(creada["estado"], "PAGADA");
+    assert_eq!(creada["cambio"], 50.0);
+
+    let res = test::call_service(&app, venta(json!([]))).await;
+    let pendiente: Value = test::read_body_json(res).await;
+    assert_eq!(pendiente["estado"], "PENDIENTE_PAGO");
+
+    let req = test::TestRequest::get().uri(&format!("/v1/ventas/{}", creada["id"].as_str().unwrap())).to_request();
+    let pagada: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(pagada["total_pagado"], 300.0);
+    assert_eq!(pagada["pagos"][0]["metodo"], "TARJETA");
+    assert_eq!(pagada["pagos"][0]["referencia"], "AUT-1");
+    assert_eq!(pagada["pagos"][1]["monto"], 100.0);
+    assert_eq!(pagada["pagos"][1]["cambio"], 50.0);
+
+    let listar = |consulta: &str| test::TestRequest::get().uri(&format!("/v1/ventas?{}", consulta)).to_request();
+    let ids = |ventas: &Value| -> Vec<String> {
+        ventas.as_array().unwrap().iter().map(|v| v["id"].as_str().unwrap().to_string()).collect()
+    };
+    let ventas: Value = test::call_and_read_body_json(&app, listar("metodo_pago=efectivo")).await;
+    assert_eq!(ids(&ventas), [creada["id"].as_str().unwrap()]);
+    let ventas: Value = test::call_and_read_body_json(&app, listar("estado=PENDIENTE_PAGO")).await;
+    assert_eq!(ids(&ventas), [pendiente["id"].as_str().unwrap()]);
+    let res = test::call_service(&app, listar("metodo_pago=CHEQUE")).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    // Cobrar la pendiente
+    let cobrar = |pagos: Value| {
+        test::TestRequest::post()
+            .uri(&format!("/v1/ventas/{}/pagos", pendiente["id"].as_str().unwrap()))
+            .set_json(json!({"pagos": pagos, "id_sesion_caja": caja}))
+            .to_request()
+    };
+    let res = test::call_service(&app, cobrar(json!([{"metodo": "TRANSFERENCIA", "monto": 100.0}]))).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    // El efectivo siempre entra en el arqueo de una sesión abierta
+    let req = test::TestRequest::post()
+        .uri(&format!("/v1/ventas/{}/pagos", pendiente["id"].as_str().unwrap()))
+        .set_json(json!({"pagos": [{"metodo": "EFECTIVO", "monto": 300.0}]}))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+    let res = test::call_service(&app, cobrar(json!([{"metodo": "TRANSFERENCIA", "monto": 300.0}]))).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let cobrada: Value = test::read_body_json(res).await;
+    assert_eq!(cobrada["estado"], "PAGADA");
+    assert_eq!(cobrada["total_pagado"], 300.0);
+    let res = test::call_service(&app, cobrar(json!([{"metodo": "EFECTIVO", "monto": 300.0}]))).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn anular_venta_devuelve_el_stock_y_la_saca_de_los_reportes() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let cliente = PersonaBuilder::cliente().crear(&mut db.conn());
+    let producto = ProductoBuilder::new().precio(100).stock(0).crear(&mut db.conn(), &vendedor);
+    let caja = SesionCajaBuilder::new(&vendedor).crear(&mut db.conn());
+    let app = app!(db);
+
+    let req = test::TestRequest::post()
+        .uri("/v1/inventario/movimientos")
+        .set_json(json!({"id_producto": producto.id, "tipo_movimiento": "ENTRADA", "id_persona": vendedor.id,
+                         "cantidad": 5, "costo_unitario": 60.0, "lote": "L1", "fecha_vencimiento": "2099-12-31"}))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+
+    let req = test::TestRequest::post()
+        .uri("/v1/ventas")
+        .set_json(json!({"id_cliente": cliente.id, "id_sesion_caja": caja, "id_vendedor": vendedor.id,
+                         "detalles": [{"id_producto": producto.id, "cantidad": 2}],
+                         "pagos": [{"metodo": "EFECTIVO", "monto": 200.0}]}))
+        .to_request();
+    let creada: Value = test::call_and_read_body_json(&app, req).await;
+    let id_venta = creada["id"].as_str().unwrap().to_string();
+    assert_eq!(db.stock(producto.id), 3);
+
+    let anular = || {
+        test::TestRequest::post()
+            .uri(&format!("/v1/ventas/{}/anular", id_venta))
+            .set_json(json!({"id_persona": vendedor.id, "motivo": "Cobro duplicado"}))
+            .to_request()
+    };
+    let res = test::call_service(&app, anular()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let anulada: Value = test::read_body_json(res).await;
+    assert_eq!(anulada["estado"], "ANULADA");
+    assert_eq!(anulada["pagos"], json!([]));
+    assert_eq!(db.stock(producto.id), 5);
+
+    let req = test::TestRequest::get().uri(&format!("/v1/inventario/disponibilidad/{}", producto.id)).to_request();
+    let disponibilidad: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(disponibilidad["lotes"][0]["cantidad_disponible"], 5);
+
+    let req = test::TestRequest::get().uri("/v1/reportes/valorizacion").to_request();
+    let valorizacion: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(valorizacion["valor_total"], 300.0);
+
+    let req = test::TestRequest::get().uri("/v1/reportes/margenes").to_request();
+    let margenes: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(margenes["grupos"], json!([]));
+
+    let res = test::call_service(&app, anular()).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
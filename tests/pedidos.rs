@@ -0,0 +1,197 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test;
+use diesel::prelude::*;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use common::builders::{PersonaBuilder, ProductoBuilder, SesionCajaBuilder};
+use common::TestDb;
+use poli_market_api::schema::{detalle_inventarios, productos};
+
+#[actix_web::test]
+async fn el_pedido_espera_el_stock_que_falta_y_se_entrega_con_una_venta() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let cliente = PersonaBuilder::cliente().crear(&mut db.conn());
+    let otro_cliente = PersonaBuilder::cliente().crear(&mut db.conn());
+    let producto = ProductoBuilder::new().precio(100).stock(5).crear(&mut db.conn(), &vendedor);
+    let caja = SesionCajaBuilder::new(&vendedor).crear(&mut db.conn());
+    let app = app!(db);
+
+    let pedir = |id_cliente: Uuid, cantidad: i32| {
+        test::TestRequest::post()
+            .uri("/v1/pedidos")
+            .set_json(json!({"id_cliente": id_cliente, "detalles": [{"id_producto": producto.id, "cantidad": cantidad}]}))
+            .to_request()
+    };
+    let res = test::call_service(&app, pedir(cliente.id, 8)).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let pedido: Value = test::read_body_json(res).await;
+    assert_eq!(pedido["estado"], "PENDIENTE");
+    assert_eq!(pedido["total"], 800.0);
+    assert_eq!(pedido["detalles"][0]["cantidad_asignada"], 5);
+    assert_eq!(pedido["detalles"][0]["cantidad_pendiente"], 3);
+    let id = pedido["id"].as_str().unwrap().to_string();
+    let posterior: Value = test::call_and_read_body_json(&app, pedir(otro_cliente.id, 2)).await;
+    let id_posterior = posterior["id"].as_str().unwrap().to_string();
+    assert_eq!(posterior["detalles"][0]["cantidad_pendiente"], 2);
+
+    let reservado = || async {
+        let req = test::TestRequest::get()
+            .uri(&format!("/v1/inventario/disponibilidad/{}", producto.id))
+            .to_request();
+        let disponibilidad: Value = test::call_and_read_body_json(&app, req).await;
+        disponibilidad["cantidad_reservada"].as_i64().unwrap()
+    };
+    assert_eq!(reservado().await, 5);
+
+    // Lo reservado no se vende en mostrador
+    let req = test::TestRequest::post()
+        .uri("/v1/ventas")
+        .set_json(json!({"id_cliente": cliente.id, "id_sesion_caja": caja,
+                         "detalles": [{"id_producto": producto.id, "cantidad": 1}]}))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+
+    // La entrada completa primero el pedido más antiguo
+    let req = test::TestRequest::post()
+        .uri("/v1/inventario/movimientos")
+        .set_json(json!({"id_producto": producto.id, "tipo_movimiento": "ENTRADA",
+                         "id_persona": vendedor.id, "cantidad": 4}))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+    let obtener = |id: &str| test::TestRequest::get().uri(&format!("/v1/pedidos/{}", id)).to_request();
+    let completo: Value = test::call_and_read_body_json(&app, obtener(&id)).await;
+    assert_eq!(completo["detalles"][0]["cantidad_asignada"], 8);
+    let parcial: Value = test::call_and_read_body_json(&app, obtener(&id_posterior)).await;
+    assert_eq!(parcial["detalles"][0]["cantidad_asignada"], 1);
+    assert_eq!(reservado().await, 9);
+
+    let despachar = |detalles: Value| {
+        test::TestRequest::post()
+            .uri(&format!("/v1/pedidos/{}/despachos", id))
+            .set_json(json!({"id_persona": vendedor.id, "detalles": detalles}))
+            .to_request()
+    };
+    assert_eq!(test::call_service(&app, despachar(json!([]))).await.status(), StatusCode::BAD_REQUEST);
+    let req = test::TestRequest::post().uri(&format!("/v1/pedidos/{}/preparar", id)).to_request();
+    let preparado: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(preparado["estado"], "EN_PREPARACION");
+
+    let primero: Value = test::call_and_read_body_json(&app, despachar(json!([{"linea": 1, "cantidad": 5}]))).await;
+    assert_eq!(primero["estado"], "EN_PREPARACION");
+    assert_eq!(primero["detalles"][0]["cantidad_despachada"], 5);
+    assert_eq!(db.stock(producto.id), 4);
+    let segundo: Value = test::call_and_read_body_json(&app, despachar(json!([]))).await;
+    assert_eq!(segundo["estado"], "DESPACHADO");
+    assert_eq!(db.stock(producto.id), 1);
+    assert_eq!(reservado().await, 1);
+
+    // Cada despacho deja su SALIDA, que no se revierte
+    let despachos: Vec<(Uuid, i32)> = detalle_inventarios::table
+        .filter(detalle_inventarios::id_detalle_pedido.is_not_null())
+        .order(detalle_inventarios::cantidad.desc())
+        .select((detalle_inventarios::id, detalle_inventarios::cantidad))
+        .load(&mut db.conn())
+        .unwrap();
+    assert_eq!(despachos.iter().map(|(_, cantidad)| *cantidad).collect::<Vec<_>>(), vec![5, 3]);
+    let req = test::TestRequest::post()
+        .uri(&format!("/v1/inventario/movimientos/id/{}/reversion", despachos[0].0))
+        .set_json(json!({"id_persona": vendedor.id}))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+
+    let entregar = || {
+        test::TestRequest::post()
+            .uri(&format!("/v1/pedidos/{}/entregar", id))
+            .set_json(json!({"id_sesion_caja": caja, "pagos": [{"metodo": "EFECTIVO", "monto": 800.0}]}))
+            .to_request()
+    };
+    let res = test::call_service(&app, entregar()).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let creada: Value = test::read_body_json(res).await;
+    assert_eq!(creada["estado"], "PAGADA");
+    let id_venta = creada["id"].as_str().unwrap().to_string();
+    assert_eq!(db.stock(producto.id), 1);
+    let req = test::TestRequest::get().uri(&format!("/v1/ventas/{}", id_venta)).to_request();
+    let venta: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(venta["total"], 800.0);
+    assert_eq!(venta["id_pedido"], id.as_str());
+    let entregado: Value = test::call_and_read_body_json(&app, obtener(&id)).await;
+    assert_eq!(entregado["estado"], "ENTREGADO");
+    assert_eq!(entregado["id_venta"], id_venta.as_str());
+    assert_eq!(test::call_service(&app, entregar()).await.status(), StatusCode::BAD_REQUEST);
+
+    // Anular la venta devuelve lo despachado, que completa el pedido que esperaba
+    let req = test::TestRequest::post()
+        .uri(&format!("/v1/ventas/{}/anular", id_venta))
+        .set_json(json!({"id_persona": vendedor.id}))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+    assert_eq!(db.stock(producto.id), 9);
+    let completado: Value = test::call_and_read_body_json(&app, obtener(&id_posterior)).await;
+    assert_eq!(completado["detalles"][0]["cantidad_pendiente"], 0);
+    assert_eq!(reservado().await, 2);
+}
+
+#[actix_web::test]
+async fn pedidos_invalidos_se_rechazan() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let cliente = PersonaBuilder::cliente().crear(&mut db.conn());
+    let producto = ProductoBuilder::new().stock(3).crear(&mut db.conn(), &vendedor);
+    let serializado = ProductoBuilder::new().stock(0).crear(&mut db.conn(), &vendedor);
+    diesel::update(productos::table.find(serializado.id))
+        .set(productos::serializado.eq(true))
+        .execute(&mut db.conn())
+        .unwrap();
+    let app = app!(db);
+
+    let crear = |cuerpo: Value| test::TestRequest::post().uri("/v1/pedidos").set_json(cuerpo).to_request();
+    for cuerpo in [
+        json!({"id_cliente": cliente.id, "detalles": []}),
+        json!({"id_cliente": cliente.id, "detalles": [{"id_producto": producto.id, "cantidad": 0}]}),
+        json!({"id_cliente": cliente.id, "detalles": [{"id_producto": serializado.id, "cantidad": 1}]}),
+    ] {
+        assert_eq!(test::call_service(&app, crear(cuerpo)).await.status(), StatusCode::BAD_REQUEST);
+    }
+    let res = test::call_service(
+        &app,
+        crear(json!({"id_cliente": Uuid::new_v4(), "detalles": [{"id_producto": producto.id, "cantidad": 1}]})),
+    )
+    .await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    let pedido: Value = test::call_and_read_body_json(
+        &app,
+        crear(json!({"id_cliente": cliente.id, "detalles": [{"id_producto": producto.id, "cantidad": 2}]})),
+    )
+    .await;
+    let id = pedido["id"].as_str().unwrap().to_string();
+    let preparar = || test::TestRequest::post().uri(&format!("/v1/pedidos/{}/preparar", id)).to_request();
+    assert_eq!(test::call_service(&app, preparar()).await.status(), StatusCode::OK);
+    assert_eq!(test::call_service(&app, preparar()).await.status(), StatusCode::BAD_REQUEST);
+
+    for detalles in [
+        json!([{"linea": 2, "cantidad": 1}]),
+        json!([{"linea": 1, "cantidad": 1}, {"linea": 1, "cantidad": 1}]),
+        json!([{"linea": 1, "cantidad": 3}]),
+    ] {
+        let req = test::TestRequest::post()
+            .uri(&format!("/v1/pedidos/{}/despachos", id))
+            .set_json(json!({"id_persona": vendedor.id, "detalles": detalles}))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+    }
+    assert_eq!(db.stock(producto.id), 3);
+
+    let listar = |filtro: &str| test::TestRequest::get().uri(&format!("/v1/pedidos?{}", filtro)).to_request();
+    let en_preparacion: Value = test::call_and_read_body_json(&app, listar("estado=en_preparacion")).await;
+    assert_eq!(en_preparacion.as_array().unwrap().len(), 1);
+    assert_eq!(test::call_service(&app, listar("estado=CANCELADO")).await.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::get().uri(&format!("/v1/pedidos/{}", Uuid::new_v4())).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::NOT_FOUND);
+}
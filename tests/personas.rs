@@ -0,0 +1,104 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test;
+use serde_json::{json, Value};
+
+use common::builders::PersonaBuilder;
+use common::TestDb;
+
+#[actix_web::test]
+async fn crear_persona_y_consultarla() {
+    let db = TestDb::new();
+    let app = app!(db);
+
+    let req = test::TestRequest::post()
+        .uri("/v1/personas")
+        .set_json(json!({
+            "nombre": "Ana Torres",
+            "documento": "1020304050",
+            "perfil": "cliente",
+            "email": "ana@example.com"
+        }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let creada: Value = test::read_body_json(res).await;
+    let id = creada["id"].as_str().unwrap();
+
+    let req = test::TestRequest::get().uri(&format!("/v1/personas/{}", id)).to_request();
+    let persona: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(persona["nombre"], "Ana Torres");
+    assert_eq!(persona["perfil"], "CLIENTE");
+    assert_eq!(persona["email"], "ana@example.com");
+}
+
+#[actix_web::test]
+async fn crear_persona_con_documento_duplicado_falla() {
+    let db = TestDb::new();
+    PersonaBuilder::cliente().documento("5555566666").crear(&mut db.conn());
+    let app = app!(db);
+
+    let req = test::TestRequest::post()
+        .uri("/v1/personas")
+        .set_json(json!({"nombre": "Otra", "documento": "5555566666", "perfil": "CLIENTE"}))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let error: Value = test::read_body_json(res).await;
+    assert_eq!(error["code"], "BUSINESS_RULE_VIOLATION");
+}
+
+#[actix_web::test]
+async fn crear_persona_valida_la_entrada() {
+    let db = TestDb::new();
+    let app = app!(db);
+
+    let invalidas = [
+        json!({"nombre": "Ana", "documento": "1020304050", "perfil": "ADMIN"}),
+        json!({"nombre": "Ana", "documento": "123", "perfil": "CLIENTE"}),
+        json!({"nombre": "", "documento": "1020304050", "perfil": "CLIENTE"}),
+    ];
+
+    for body in invalidas {
+        let req = test::TestRequest::post().uri("/v1/personas").set_json(&body).to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST, "body: {}", body);
+    }
+}
+
+#[actix_web::test]
+async fn obtener_persona_inexistente_o_invalida() {
+    let db = TestDb::new();
+    let inactiva = PersonaBuilder::cliente().inactiva().crear(&mut db.conn());
+    let app = app!(db);
+
+    let req = test::TestRequest::get().uri("/v1/personas/no-es-uuid").to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/v1/personas/{}", uuid::Uuid::new_v4()))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::NOT_FOUND);
+
+    // Las personas desactivadas no se exponen
+    let req = test::TestRequest::get().uri(&format!("/v1/personas/{}", inactiva.id)).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn listar_personas_filtra_por_tipo() {
+    let db = TestDb::new();
+    let proveedor = PersonaBuilder::new(poli_market_api::modules::common::types::TipoPerfil::Proveedor)
+        .nombre("Distribuidora XYZ")
+        .crear(&mut db.conn());
+    let app = app!(db);
+
+    let req = test::TestRequest::get().uri("/v1/personas?tipo=proveedor").to_request();
+    let personas: Vec<Value> = test::call_and_read_body_json(&app, req).await;
+    assert!(personas.iter().all(|p| p["perfil"] == "PROVEEDOR"));
+    assert!(personas.iter().any(|p| p["id"] == proveedor.id.to_string()));
+
+    let req = test::TestRequest::get().uri("/v1/personas?tipo=otro").to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,216 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use common::builders::{PersonaBuilder, ProductoBuilder, SesionCajaBuilder};
+use common::TestDb;
+
+#[actix_web::test]
+async fn la_lista_del_cliente_y_la_promocion_fijan_el_precio_de_la_venta() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let cliente = PersonaBuilder::cliente().crear(&mut db.conn());
+    let producto = ProductoBuilder::new().precio(100).stock(20).crear(&mut db.conn(), &vendedor);
+    let caja = SesionCajaBuilder::new(&vendedor).crear(&mut db.conn());
+    let app = app!(db);
+
+    let crear_lista = |nombre: &str| {
+        test::TestRequest::post()
+            .uri("/v1/precios/listas")
+            .set_json(json!({"nombre": nombre, "descripcion": "Clientes por volumen"}))
+            .to_request()
+    };
+    let res = test::call_service(&app, crear_lista("Mayorista")).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let lista: Value = test::read_body_json(res).await;
+    let id_lista = lista["id"].as_str().unwrap().to_string();
+    assert_eq!(test::call_service(&app, crear_lista("MAYORISTA")).await.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::put()
+        .uri(&format!("/v1/precios/listas/{}/productos/{}", id_lista, producto.id))
+        .set_json(json!({"precio_unitario": 80.0}))
+        .to_request();
+    let precio: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(precio["precio_unitario"], 80.0);
+
+    // Solo los clientes tienen lista de precios
+    let asignar = |id_persona: Uuid| {
+        test::TestRequest::put()
+            .uri(&format!("/v1/personas/{}/lista-precios", id_persona))
+            .set_json(json!({"id_lista_precios": id_lista}))
+            .to_request()
+    };
+    assert_eq!(test::call_service(&app, asignar(vendedor.id)).await.status(), StatusCode::BAD_REQUEST);
+    let persona: Value = test::call_and_read_body_json(&app, asignar(cliente.id)).await;
+    assert_eq!(persona["id_lista_precios"], id_lista.as_str());
+
+    let req = test::TestRequest::post()
+        .uri("/v1/precios/promociones")
+        .set_json(json!({"nombre": "Lleva 3 paga 2", "tipo": "lleva_paga", "id_producto": producto.id,
+                         "fecha_inicio": "2020-01-01 00:00:00", "fecha_fin": "2099-01-01 00:00:00",
+                         "cantidad_lleva": 3, "cantidad_paga": 2}))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let promocion: Value = test::read_body_json(res).await;
+    assert_eq!(promocion["tipo"], "LLEVA_PAGA");
+
+    let cotizar = |cuerpo: Value| test::TestRequest::post().uri("/v1/precios/cotizar").set_json(cuerpo).to_request();
+    let cotizacion: Value = test::call_and_read_body_json(
+        &app,
+        cotizar(json!({"id_cliente": cliente.id, "detalles": [{"id_producto": producto.id, "cantidad": 4}]})),
+    )
+    .await;
+    assert_eq!(cotizacion["id_lista_precios"], id_lista.as_str());
+    assert_eq!(cotizacion["bruto"], 320.0);
+    assert_eq!(cotizacion["descuento"], 80.0);
+    assert_eq!(cotizacion["total"], 240.0);
+    assert_eq!(cotizacion["detalles"][0]["origen"], "PROMOCION");
+    assert_eq!(cotizacion["detalles"][0]["id_promocion"], promocion["id"]);
+
+    // Sin cliente no hay lista, y una sola unidad no completa el grupo de la promoción
+    let cotizacion: Value = test::call_and_read_body_json(
+        &app,
+        cotizar(json!({"detalles": [{"id_producto": producto.id, "cantidad": 1}]})),
+    )
+    .await;
+    assert_eq!(cotizacion["total"], 100.0);
+    assert_eq!(cotizacion["detalles"][0]["origen"], "CATALOGO");
+
+    let req = test::TestRequest::post()
+        .uri("/v1/ventas")
+        .set_json(json!({"id_cliente": cliente.id, "id_sesion_caja": caja,
+                         "detalles": [{"id_producto": producto.id, "cantidad": 4}],
+                         "pagos": [{"metodo": "EFECTIVO", "monto": 240.0}]}))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let creada: Value = test::read_body_json(res).await;
+    assert_eq!(creada["estado"], "PAGADA");
+    let req = test::TestRequest::get()
+        .uri(&format!("/v1/ventas/{}", creada["id"].as_str().unwrap()))
+        .to_request();
+    let venta: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(venta["total"], 240.0);
+    let detalle = &venta["detalles"][0];
+    assert_eq!(detalle["precio_unitario"], 80.0);
+    assert_eq!(detalle["descuento"], 80.0);
+    assert_eq!(detalle["subtotal"], 240.0);
+    assert_eq!(detalle["origen_precio"], "PROMOCION");
+    assert_eq!(detalle["id_lista_precios"], id_lista.as_str());
+
+    // La caja usa su precio en la lista o el base por el factor; el 3x2 cuenta unidades base
+    let req = test::TestRequest::post()
+        .uri(&format!("/v1/productos/{}/unidades", producto.id))
+        .set_json(json!({"nombre": "Caja x12", "factor": 12}))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+    let por_caja = || cotizar(json!({"id_cliente": cliente.id,
+                                     "detalles": [{"id_producto": producto.id, "cantidad": 1, "unidad": "Caja x12"}]}));
+    let cotizacion: Value = test::call_and_read_body_json(&app, por_caja()).await;
+    assert_eq!((cotizacion["bruto"].as_f64(), cotizacion["total"].as_f64()), (Some(960.0), Some(640.0)));
+    let req = test::TestRequest::put()
+        .uri(&format!("/v1/precios/listas/{}/productos/{}", id_lista, producto.id))
+        .set_json(json!({"precio_unitario": 900.0, "unidad": "Caja x12"}))
+        .to_request();
+    let precio: Value = test::call_and_read_body_json(&app, req).await;
+    assert!(precio["id_unidad"].is_string());
+    let cotizacion: Value = test::call_and_read_body_json(&app, por_caja()).await;
+    assert_eq!((cotizacion["descuento"].as_f64(), cotizacion["total"].as_f64()), (Some(300.0), Some(600.0)));
+    let req = test::TestRequest::delete()
+        .uri(&format!("/v1/precios/listas/{}/productos/{}?unidad=Caja%20x12", id_lista, producto.id))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::NO_CONTENT);
+    let cotizacion: Value = test::call_and_read_body_json(&app, por_caja()).await;
+    assert_eq!(cotizacion["total"], 640.0);
+
+    // Sin la promoción la venta vuelve al precio de la lista
+    let req = test::TestRequest::delete()
+        .uri(&format!("/v1/precios/promociones/{}", promocion["id"].as_str().unwrap()))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::NO_CONTENT);
+    let cotizacion: Value = test::call_and_read_body_json(
+        &app,
+        cotizar(json!({"id_cliente": cliente.id, "detalles": [{"id_producto": producto.id, "cantidad": 4}]})),
+    )
+    .await;
+    assert_eq!(cotizacion["total"], 320.0);
+    assert_eq!(cotizacion["detalles"][0]["origen"], "LISTA");
+}
+
+#[actix_web::test]
+async fn precios_y_promociones_invalidos_se_rechazan() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let producto = ProductoBuilder::new().precio(100).crear(&mut db.conn(), &vendedor);
+    let app = app!(db);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/v1/precios/listas/{}", Uuid::new_v4()))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::NOT_FOUND);
+
+    let req = test::TestRequest::post()
+        .uri("/v1/precios/listas")
+        .set_json(json!({"nombre": "Minorista"}))
+        .to_request();
+    let lista: Value = test::call_and_read_body_json(&app, req).await;
+    let id_lista = lista["id"].as_str().unwrap();
+
+    let fijar = |precio: f64| {
+        test::TestRequest::put()
+            .uri(&format!("/v1/precios/listas/{}/productos/{}", id_lista, producto.id))
+            .set_json(json!({"precio_unitario": precio}))
+            .to_request()
+    };
+    assert_eq!(test::call_service(&app, fijar(-1.0)).await.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(test::call_service(&app, fijar(90.0)).await.status(), StatusCode::OK);
+    // Un segundo precio reemplaza al primero
+    assert_eq!(test::call_service(&app, fijar(85.0)).await.status(), StatusCode::OK);
+    let req = test::TestRequest::get()
+        .uri(&format!("/v1/precios/listas/{}/productos", id_lista))
+        .to_request();
+    let precios: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(precios.as_array().unwrap().len(), 1);
+    assert_eq!(precios[0]["precio_unitario"], 85.0);
+
+    let quitar = || {
+        test::TestRequest::delete()
+            .uri(&format!("/v1/precios/listas/{}/productos/{}", id_lista, producto.id))
+            .to_request()
+    };
+    assert_eq!(test::call_service(&app, quitar()).await.status(), StatusCode::NO_CONTENT);
+    assert_eq!(test::call_service(&app, quitar()).await.status(), StatusCode::NOT_FOUND);
+
+    let promocion = |cuerpo: Value| {
+        test::TestRequest::post().uri("/v1/precios/promociones").set_json(cuerpo).to_request()
+    };
+    // Porcentaje fuera de rango, paga más de lo que lleva y vigencia invertida
+    for cuerpo in [
+        json!({"nombre": "Mitad", "tipo": "PORCENTAJE", "id_producto": producto.id, "porcentaje": 150.0,
+               "fecha_inicio": "2020-01-01 00:00:00", "fecha_fin": "2099-01-01 00:00:00"}),
+        json!({"nombre": "2x3", "tipo": "LLEVA_PAGA", "id_producto": producto.id, "cantidad_lleva": 2,
+               "cantidad_paga": 3, "fecha_inicio": "2020-01-01 00:00:00", "fecha_fin": "2099-01-01 00:00:00"}),
+        json!({"nombre": "Paquete", "tipo": "PRECIO_PAQUETE", "id_producto": producto.id, "cantidad_lleva": 2,
+               "precio_paquete": 150.0, "fecha_inicio": "2099-01-01 00:00:00", "fecha_fin": "2020-01-01 00:00:00"}),
+    ] {
+        assert_eq!(test::call_service(&app, promocion(cuerpo)).await.status(), StatusCode::BAD_REQUEST);
+    }
+    let res = test::call_service(
+        &app,
+        promocion(json!({"nombre": "Fantasma", "tipo": "PORCENTAJE", "id_producto": Uuid::new_v4(),
+                         "porcentaje": 10.0, "fecha_inicio": "2020-01-01 00:00:00",
+                         "fecha_fin": "2099-01-01 00:00:00"})),
+    )
+    .await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    let req = test::TestRequest::post()
+        .uri("/v1/precios/cotizar")
+        .set_json(json!({"detalles": [{"id_producto": producto.id, "cantidad": 0}]}))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test;
+use diesel::prelude::*;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use common::builders::{PersonaBuilder, ProductoBuilder};
+use common::TestDb;
+use poli_market_api::modules::common::types::TipoMovimiento;
+use poli_market_api::schema::detalle_inventarios;
+
+#[actix_web::test]
+async fn crear_producto_registra_inventario_inicial() {
+    let db = TestDb::new();
+    PersonaBuilder::vendedor().crear(&mut db.conn());
+    let app = app!(db);
+
+    let req = test::TestRequest::post()
+        .uri("/v1/productos")
+        .set_json(json!({
+            "nombre": "Monitor 27",
+            "cantidad": 7,
+            "unidad_venta": "Unidad",
+            "precio_unitario": 950.5
+        }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let creado: Value = test::read_body_json(res).await;
+    let id = Uuid::parse_str(creado["id"].as_str().unwrap()).unwrap();
+
+    assert_eq!(db.stock(id), 7);
+
+    let movimientos: Vec<(TipoMovimiento, i32)> = detalle_inventarios::table
+        .filter(detalle_inventarios::id_producto.eq(id))
+        .select((detalle_inventarios::tipo_movimiento, detalle_inventarios::cantidad))
+        .load(&mut db.conn())
+        .unwrap();
+    assert_eq!(movimientos, vec![(TipoMovimiento::Entrada, 7)]);
+
+    let req = test::TestRequest::get().uri(&format!("/v1/productos/{}", id)).to_request();
+    let producto: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(producto["nombre"], "Monitor 27");
+    assert_eq!(producto["precio_unitario"], 950.5);
+    assert_eq!(producto["stock_actual"], 7);
+}
+
+#[actix_web::test]
+async fn crear_producto_valida_la_entrada() {
+    let db = TestDb::new();
+    let app = app!(db);
+
+    let invalidos = [
+        json!({"nombre": "", "cantidad": 1, "unidad_venta": "Unidad", "precio_unitario": 10.0}),
+        json!({"nombre": "C", "cantidad": 1, "unidad_venta": "Unidad", "precio_unitario": 10.0}),
+        json!({"nombre": "Cable", "cantidad": -1, "unidad_venta": "Unidad", "precio_unitario": 10.0}),
+        json!({"nombre": "Cable", "cantidad": 1, "unidad_venta": "Unidad", "precio_unitario": 0.0}),
+    ];
+
+    for body in invalidos {
+        let req = test::TestRequest::post().uri("/v1/productos").set_json(&body).to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST, "body: {}", body);
+    }
+}
+
+#[actix_web::test]
+async fn listar_productos_incluye_stock() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let producto = ProductoBuilder::new().nombre("Audífonos").stock(4).crear(&mut db.conn(), &vendedor);
+    let app = app!(db);
+
+    let req = test::TestRequest::get().uri("/v1/productos").to_request();
+    let productos: Vec<Value> = test::call_and_read_body_json(&app, req).await;
+    let listado = productos
+        .iter()
+        .find(|p| p["id"] == producto.id.to_string())
+        .expect("el producto no aparece en el listado");
+    assert_eq!(listado["stock_actual"], 4);
+}
+
+#[actix_web::test]
+async fn obtener_producto_inexistente() {
+    let db = TestDb::new();
+    let app = app!(db);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/v1/productos/{}", Uuid::new_v4()))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::NOT_FOUND);
+
+    let req = test::TestRequest::get().uri("/v1/productos/abc").to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,232 @@
+mod common;
+
+use actix_web::http::{header, StatusCode};
+use actix_web::test;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use common::builders::{PersonaBuilder, ProductoBuilder};
+use common::TestDb;
+use poli_market_api::config::ConfigLayer;
+
+#[actix_web::test]
+async fn respuestas_incluyen_cabeceras_de_seguridad() {
+    let db = TestDb::new();
+    let app = app!(db);
+
+    let req = test::TestRequest::get().uri("/v1/health/live").to_request();
+    let res = test::call_service(&app, req).await;
+    let cabeceras = res.headers();
+
+    assert_eq!(cabeceras.get(header::X_CONTENT_TYPE_OPTIONS).unwrap(), "nosniff");
+    assert_eq!(cabeceras.get(header::X_FRAME_OPTIONS).unwrap(), "DENY");
+    assert!(cabeceras.get(header::STRICT_TRANSPORT_SECURITY).is_some());
+    assert!(cabeceras.get("x-request-id").is_some());
+}
+
+#[actix_web::test]
+async fn preflight_cors_admite_put_y_delete() {
+    let db = TestDb::con_config(ConfigLayer {
+        cors_allowed_origins: Some(vec!["https://app.polimarket.com".to_string()]),
+        ..ConfigLayer::default()
+    });
+    let app = app!(db);
+
+    for metodo in ["PUT", "DELETE"] {
+        let req = test::TestRequest::default()
+            .method(actix_web::http::Method::OPTIONS)
+            .uri("/v1/categorias/550e8400-e29b-41d4-a716-446655440000")
+            .insert_header((header::ORIGIN, "https://app.polimarket.com"))
+            .insert_header((header::ACCESS_CONTROL_REQUEST_METHOD, metodo))
+            .insert_header((header::ACCESS_CONTROL_REQUEST_HEADERS, "content-type"))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let permitidos = res.headers().get(header::ACCESS_CONTROL_ALLOW_METHODS).unwrap().to_str().unwrap();
+        assert!(permitidos.contains(metodo), "{} no está en {}", metodo, permitidos);
+    }
+}
+
+#[actix_web::test]
+async fn cuerpo_demasiado_grande_devuelve_413() {
+    let db = TestDb::con_config(ConfigLayer {
+        max_body_bytes: Some(256),
+        ..ConfigLayer::default()
+    });
+    let app = app!(db);
+
+    let req = test::TestRequest::post()
+        .uri("/v1/personas")
+        .set_json(json!({"nombre": "x".repeat(1024), "documento": "1020304050", "perfil": "CLIENTE"}))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    let error: Value = test::read_body_json(res).await;
+    assert_eq!(error["code"], "PAYLOAD_TOO_LARGE");
+
+    let req = test::TestRequest::post()
+        .uri("/v1/personas")
+        .insert_header((header::CONTENT_TYPE, "application/json"))
+        .set_payload("{no es json")
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let error: Value = test::read_body_json(res).await;
+    assert_eq!(error["code"], "INVALID_INPUT");
+}
+
+#[actix_web::test]
+async fn rutas_de_escritura_tienen_limite_estricto_por_persona() {
+    let db = TestDb::con_config(ConfigLayer {
+        rate_limit_enabled: Some(true),
+        rate_limit_write_per_minute: Some(1),
+        rate_limit_write_burst: Some(2),
+        ..ConfigLayer::default()
+    });
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let producto = ProductoBuilder::new().crear(&mut db.conn(), &vendedor);
+    let app = app!(db);
+
+    let movimiento = json!({
+        "id_producto": producto.id,
+        "tipo_movimiento": "ENTRADA",
+        "id_persona": vendedor.id,
+        "cantidad": 1
+    });
+    let registrar = || {
+        test::TestRequest::post()
+            .uri("/v1/inventario/movimientos")
+            .insert_header(("X-Persona-Id", vendedor.id.to_string()))
+            .peer_addr("10.0.0.1:4000".parse().unwrap())
+            .set_json(&movimiento)
+            .to_request()
+    };
+
+    for _ in 0..2 {
+        assert_eq!(test::call_service(&app, registrar()).await.status(), StatusCode::CREATED);
+    }
+
+    let res = test::call_service(&app, registrar()).await;
+    assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(res.headers().get(header::RETRY_AFTER).is_some());
+    assert_eq!(db.stock(producto.id), 12);
+
+    // Las lecturas usan el límite general y siguen disponibles
+    let req = test::TestRequest::get()
+        .uri(&format!("/v1/productos/{}", producto.id))
+        .peer_addr("10.0.0.1:4000".parse().unwrap())
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn pagos_y_anulaciones_de_ventas_usan_el_limite_de_escritura() {
+    let db = TestDb::con_config(ConfigLayer {
+        rate_limit_enabled: Some(true),
+        rate_limit_write_per_minute: Some(1),
+        rate_limit_write_burst: Some(2),
+        ..ConfigLayer::default()
+    });
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let app = app!(db);
+
+    let escribir = |accion: &str| {
+        test::TestRequest::post()
+            .uri(&format!("/v1/ventas/{}/{}", Uuid::new_v4(), accion))
+            .insert_header(("X-Persona-Id", vendedor.id.to_string()))
+            .peer_addr("10.0.0.1:4000".parse().unwrap())
+            .set_json(json!({"id_persona": vendedor.id, "pagos": []}))
+            .to_request()
+    };
+
+    for accion in ["pagos", "anular"] {
+        assert_ne!(test::call_service(&app, escribir(accion)).await.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+    let res = test::call_service(&app, escribir("pagos")).await;
+    assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(res.headers().get(header::RETRY_AFTER).is_some());
+}
+
+#[actix_web::test]
+async fn convertir_cotizaciones_usa_el_limite_de_escritura() {
+    let db = TestDb::con_config(ConfigLayer {
+        rate_limit_enabled: Some(true),
+        rate_limit_write_per_minute: Some(1),
+        rate_limit_write_burst: Some(1),
+        ..ConfigLayer::default()
+    });
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let app = app!(db);
+
+    let convertir = || {
+        test::TestRequest::post()
+            .uri(&format!("/v1/cotizaciones/{}/convertir", Uuid::new_v4()))
+            .insert_header(("X-Persona-Id", vendedor.id.to_string()))
+            .peer_addr("10.0.0.1:4000".parse().unwrap())
+            .set_json(json!({"id_sesion_caja": Uuid::new_v4()}))
+            .to_request()
+    };
+
+    assert_ne!(test::call_service(&app, convertir()).await.status(), StatusCode::TOO_MANY_REQUESTS);
+    let res = test::call_service(&app, convertir()).await;
+    assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(res.headers().get(header::RETRY_AFTER).is_some());
+}
+
+#[actix_web::test]
+async fn pedidos_despachos_y_entregas_usan_el_limite_de_escritura() {
+    let db = TestDb::con_config(ConfigLayer {
+        rate_limit_enabled: Some(true),
+        rate_limit_write_per_minute: Some(1),
+        rate_limit_write_burst: Some(3),
+        ..ConfigLayer::default()
+    });
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let app = app!(db);
+
+    let escribir = |uri: String| {
+        test::TestRequest::post()
+            .uri(&uri)
+            .insert_header(("X-Persona-Id", vendedor.id.to_string()))
+            .peer_addr("10.0.0.1:4000".parse().unwrap())
+            .set_json(json!({}))
+            .to_request()
+    };
+
+    for uri in [
+        "/v1/pedidos".to_string(),
+        format!("/v1/pedidos/{}/despachos", Uuid::new_v4()),
+        format!("/v1/pedidos/{}/entregar", Uuid::new_v4()),
+    ] {
+        assert_ne!(test::call_service(&app, escribir(uri)).await.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+    let res = test::call_service(&app, escribir("/v1/pedidos".to_string())).await;
+    assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(res.headers().get(header::RETRY_AFTER).is_some());
+}
+
+#[actix_web::test]
+async fn cierre_de_caja_usa_el_limite_de_escritura() {
+    let db = TestDb::con_config(ConfigLayer {
+        rate_limit_enabled: Some(true),
+        rate_limit_write_per_minute: Some(1),
+        rate_limit_write_burst: Some(1),
+        ..ConfigLayer::default()
+    });
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let app = app!(db);
+
+    let cerrar = || {
+        test::TestRequest::post()
+            .uri(&format!("/v1/cajas/sesiones/{}/cierre", Uuid::new_v4()))
+            .insert_header(("X-Persona-Id", vendedor.id.to_string()))
+            .peer_addr("10.0.0.1:4000".parse().unwrap())
+            .set_json(json!({"efectivo_contado": 0.0}))
+            .to_request()
+    };
+
+    assert_ne!(test::call_service(&app, cerrar()).await.status(), StatusCode::TOO_MANY_REQUESTS);
+    let res = test::call_service(&app, cerrar()).await;
+    assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(res.headers().get(header::RETRY_AFTER).is_some());
+}
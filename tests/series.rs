@@ -0,0 +1,184 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use common::builders::{PersonaBuilder, ProductoBuilder, SesionCajaBuilder};
+use common::TestDb;
+
+fn movimiento_series(id_producto: Uuid, id_persona: Uuid, tipo: &str, numeros: &[&str]) -> Value {
+    json!({
+        "id_producto": id_producto,
+        "tipo_movimiento": tipo,
+        "id_persona": id_persona,
+        "cantidad": numeros.len(),
+        "numeros_serie": numeros
+    })
+}
+
+#[actix_web::test]
+async fn venta_de_serializado_registra_las_series_y_se_rastrea_al_cliente() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let cliente = PersonaBuilder::cliente().limite_credito(10_000_000).nombre("María González").crear(&mut db.conn());
+    let caja = SesionCajaBuilder::new(&vendedor).crear(&mut db.conn());
+    let app = app!(db);
+
+    let req = test::TestRequest::post()
+        .uri("/v1/productos")
+        .set_json(json!({
+            "nombre": "Laptop Dell Inspiron 15",
+            "cantidad": 0,
+            "unidad_venta": "Unidad",
+            "precio_unitario": 1200000.0,
+            "serializado": true
+        }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let laptop: Uuid = test::read_body_json::<Value, _>(res).await["id"].as_str().unwrap().parse().unwrap();
+
+    let registrar = |cuerpo: Value| {
+        test::TestRequest::post().uri("/v1/inventario/movimientos").set_json(cuerpo).to_request()
+    };
+    // Una ENTRADA necesita exactamente un número por unidad, y un AJUSTE no se admite
+    let mut sin_completar = movimiento_series(laptop, vendedor.id, "ENTRADA", &["SN-001"]);
+    sin_completar["cantidad"] = json!(2);
+    assert_eq!(test::call_service(&app, registrar(sin_completar)).await.status(), StatusCode::BAD_REQUEST);
+    let repetidos = movimiento_series(laptop, vendedor.id, "ENTRADA", &["SN-001", "sn-001"]);
+    assert_eq!(test::call_service(&app, registrar(repetidos)).await.status(), StatusCode::BAD_REQUEST);
+    let ajuste = movimiento_series(laptop, vendedor.id, "AJUSTE", &["SN-009"]);
+    assert_eq!(test::call_service(&app, registrar(ajuste)).await.status(), StatusCode::BAD_REQUEST);
+
+    let entrada = movimiento_series(laptop, vendedor.id, "ENTRADA", &["sn-001", "SN-002", "SN-003"]);
+    assert_eq!(test::call_service(&app, registrar(entrada)).await.status(), StatusCode::CREATED);
+    let otra_vez = movimiento_series(laptop, vendedor.id, "ENTRADA", &["SN-003"]);
+    assert_eq!(test::call_service(&app, registrar(otra_vez)).await.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(db.stock(laptop), 3);
+
+    let vender = |numeros: Value, cantidad: i32| {
+        test::TestRequest::post()
+            .uri("/v1/ventas")
+            .set_json(json!({
+                "id_cliente": cliente.id,
+                "id_sesion_caja": caja,
+                "detalles": [{"id_producto": laptop, "cantidad": cantidad, "numeros_serie": numeros}]
+            }))
+            .to_request()
+    };
+    for (numeros, cantidad) in [(json!([]), 1), (json!(["SN-001"]), 2), (json!(["SN-404"]), 1)] {
+        let res = test::call_service(&app, vender(numeros.clone(), cantidad)).await;
+        assert!(res.status().is_client_error(), "{} -> {}", numeros, res.status());
+    }
+    let res = test::call_service(&app, vender(json!(["SN-002", "sn-001"]), 2)).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let creada: Value = test::read_body_json(res).await;
+    assert_eq!(db.stock(laptop), 1);
+    let res = test::call_service(&app, vender(json!(["SN-001"]), 1)).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/v1/ventas/{}", creada["id"].as_str().unwrap()))
+        .to_request();
+    let venta: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(venta["detalles"][0]["numeros_serie"], json!(["SN-001", "SN-002"]));
+
+    let req = test::TestRequest::get().uri("/v1/ventas/series/sn-002").to_request();
+    let encontradas: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(encontradas.as_array().unwrap().len(), 1);
+    assert_eq!(encontradas[0]["estado"], "VENDIDO");
+    assert_eq!(encontradas[0]["nombre_producto"], "Laptop Dell Inspiron 15");
+    assert_eq!(encontradas[0]["ventas"][0]["id_venta"], creada["id"]);
+    assert_eq!(encontradas[0]["ventas"][0]["nombre_cliente"], "María González");
+
+    let req = test::TestRequest::get().uri("/v1/ventas/series/SN-404").to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn unidad_devuelta_vuelve_al_stock_y_se_puede_revender() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let primer_cliente = PersonaBuilder::cliente().limite_credito(1_000_000).nombre("Primer cliente").crear(&mut db.conn());
+    let segundo_cliente = PersonaBuilder::cliente().limite_credito(1_000_000).nombre("Segundo cliente").crear(&mut db.conn());
+    let laptop = ProductoBuilder::new().nombre("Laptop Dell").stock(2).crear(&mut db.conn(), &vendedor);
+    let caja = SesionCajaBuilder::new(&vendedor).crear(&mut db.conn());
+    let app = app!(db);
+
+    // Un producto existente se marca con un número por unidad en stock
+    let serializar = |numeros: Value| {
+        test::TestRequest::put()
+            .uri(&format!("/v1/productos/{}/serializado", laptop.id))
+            .set_json(json!({"serializado": true, "numeros_serie": numeros}))
+            .to_request()
+    };
+    assert_eq!(test::call_service(&app, serializar(json!(["DL-1"]))).await.status(), StatusCode::BAD_REQUEST);
+    let res = test::call_service(&app, serializar(json!(["DL-1", "DL-2"]))).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let producto: Value = test::read_body_json(res).await;
+    assert_eq!(producto["serializado"], true);
+
+    let vender = |cliente: Uuid| {
+        test::TestRequest::post()
+            .uri("/v1/ventas")
+            .set_json(json!({
+                "id_cliente": cliente,
+                "id_sesion_caja": caja,
+                "detalles": [{"id_producto": laptop.id, "cantidad": 1, "numeros_serie": ["DL-1"]}]
+            }))
+            .to_request()
+    };
+    assert_eq!(test::call_service(&app, vender(primer_cliente.id)).await.status(), StatusCode::CREATED);
+
+    // Una SALIDA manual no puede llevarse una unidad vendida; la devolución es una ENTRADA
+    let registrar = |tipo: &str, numero: &str| {
+        test::TestRequest::post()
+            .uri("/v1/inventario/movimientos")
+            .set_json(movimiento_series(laptop.id, vendedor.id, tipo, &[numero]))
+            .to_request()
+    };
+    assert_eq!(test::call_service(&app, registrar("SALIDA", "DL-1")).await.status(), StatusCode::BAD_REQUEST);
+    let res = test::call_service(&app, registrar("ENTRADA", "DL-1")).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let devolucion: Value = test::read_body_json(res).await;
+
+    let req = test::TestRequest::get().uri("/v1/ventas/series/DL-1").to_request();
+    let encontradas: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(encontradas[0]["estado"], "DEVUELTO");
+
+    // Los movimientos de un serializado no se revierten
+    let req = test::TestRequest::post()
+        .uri(&format!("/v1/inventario/movimientos/id/{}/reversion", devolucion["id"].as_str().unwrap()))
+        .set_json(json!({"id_persona": vendedor.id}))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+
+    assert_eq!(test::call_service(&app, vender(segundo_cliente.id)).await.status(), StatusCode::CREATED);
+    let req = test::TestRequest::get().uri("/v1/ventas/series/DL-1").to_request();
+    let encontradas: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(encontradas[0]["estado"], "VENDIDO");
+    let clientes: Vec<&str> = encontradas[0]["ventas"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|venta| venta["nombre_cliente"].as_str().unwrap())
+        .collect();
+    assert_eq!(clientes, ["Segundo cliente", "Primer cliente"]);
+
+    // Con stock no se deja de seguir por número de serie
+    let req = test::TestRequest::put()
+        .uri(&format!("/v1/productos/{}/serializado", laptop.id))
+        .set_json(json!({"serializado": false}))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+    let res = test::call_service(&app, registrar("SALIDA", "DL-2")).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    assert_eq!(db.stock(laptop.id), 0);
+    let req = test::TestRequest::put()
+        .uri(&format!("/v1/productos/{}/serializado", laptop.id))
+        .set_json(json!({"serializado": false}))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+}
@@ -0,0 +1,1210 @@
+//! Reglas de negocio de los servicios probadas contra `MemoriaRepository`, sin PostgreSQL.
+
+use std::sync::Arc;
+
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use poli_market_api::modules::cajas::model::{AbrirSesionCajaRequest, CerrarSesionCajaRequest, NuevaSesionCaja, TipoReporteCaja};
+use poli_market_api::modules::cajas::repository::CajaRepository;
+use poli_market_api::modules::cajas::service::CajaService;
+use poli_market_api::modules::catalogo::model::CategoriaRequest;
+use poli_market_api::modules::catalogo::service::CatalogoService;
+use poli_market_api::modules::comisiones::model::ReglaComisionRequest;
+use poli_market_api::modules::comisiones::service::ComisionService;
+use poli_market_api::modules::common::errors::ApiError;
+use poli_market_api::modules::common::memoria::MemoriaRepository;
+use poli_market_api::modules::common::types::{
+    EstadoCotizacion, EstadoPedido, EstadoSerie, EstadoVenta, MetodoPago, TipoMovimiento,
+};
+use poli_market_api::modules::cotizaciones::model::{
+    ConvertirCotizacionRequest, CotizacionRequest, CotizacionesQuery, PoliticaPreciosCotizacion,
+};
+use poli_market_api::modules::cotizaciones::service::CotizacionService;
+use poli_market_api::modules::cuentas::model::{LimiteCreditoRequest, TipoMovimientoCuenta};
+use poli_market_api::modules::cuentas::service::CuentaService;
+use poli_market_api::modules::inventarios::costos::MetodoValorizacion;
+use poli_market_api::modules::inventarios::model::{MovimientoRequest, RevertirMovimientoRequest};
+use poli_market_api::modules::inventarios::repository::InventarioRepository;
+use poli_market_api::modules::inventarios::service::InventarioService;
+use poli_market_api::modules::pedidos::model::{DespachoRequest, EntregarPedidoRequest, LineaDespachoRequest, PedidoRequest};
+use poli_market_api::modules::pedidos::service::PedidoService;
+use poli_market_api::modules::personas::model::CrearPersonaRequest;
+use poli_market_api::modules::personas::service::PersonaService;
+use poli_market_api::modules::precios::model::{
+    CotizarPreciosRequest, LineaPrecioRequest, ListaPreciosClienteRequest, ListaPreciosRequest, OrigenPrecio,
+    PrecioListaQuery, PrecioListaRequest, PromocionRequest, PromocionesQuery,
+};
+use poli_market_api::modules::precios::service::PrecioService;
+use poli_market_api::modules::productos::model::{CrearProductoRequest, SerializadoRequest, UnidadProductoRequest};
+use poli_market_api::modules::productos::service::ProductoService;
+use poli_market_api::modules::ventas::model::{
+    AnularVentaRequest, CrearVentaRequest, DetalleVentaRequest, PagoRequest, RegistrarPagosRequest,
+};
+use poli_market_api::modules::ventas::service::VentaService;
+
+struct Servicios {
+    repo: Arc<MemoriaRepository>,
+    personas: PersonaService,
+    productos: ProductoService,
+    catalogo: CatalogoService,
+    inventario: InventarioService,
+    ventas: Arc<VentaService>,
+    comisiones: ComisionService,
+    cuentas: CuentaService,
+    cajas: CajaService,
+    precios: PrecioService,
+    cotizaciones: CotizacionService,
+    pedidos: PedidoService,
+    /// Sesión de caja abierta en la que se registran las ventas de `venta`
+    caja: Uuid,
+}
+
+impl Servicios {
+    fn new() -> Self {
+        Servicios::con_repo(MemoriaRepository::new())
+    }
+
+    fn con_repo(repo: MemoriaRepository) -> Self {
+        let repo = Arc::new(repo);
+        let caja = repo
+            .abrir_sesion(NuevaSesionCaja {
+                sucursal: "Centro".to_string(),
+                terminal: "CAJA-01".to_string(),
+                id_operador: Uuid::new_v4(),
+                monto_apertura: 0.into(),
+            })
+            .expect("sesión de caja");
+        let ventas = Arc::new(VentaService::new(
+            repo.clone(), repo.clone(), repo.clone(), repo.clone(), repo.clone(), repo.clone(), repo.clone(), repo.clone(),
+        ));
+        Servicios {
+            personas: PersonaService::new(repo.clone()),
+            productos: ProductoService::new(repo.clone(), repo.clone(), repo.clone(), repo.clone()),
+            catalogo: CatalogoService::new(repo.clone()),
+            inventario: InventarioService::new(repo.clone(), repo.clone(), repo.clone(), 10),
+            ventas: ventas.clone(),
+            comisiones: ComisionService::new(repo.clone(), repo.clone(), repo.clone()),
+            cuentas: CuentaService::new(repo.clone(), repo.clone()),
+            cajas: CajaService::new(repo.clone(), repo.clone()),
+            precios: PrecioService::new(repo.clone(), repo.clone(), repo.clone()),
+            cotizaciones: CotizacionService::new(
+                repo.clone(), repo.clone(), repo.clone(), repo.clone(), ventas.clone(), PoliticaPreciosCotizacion::Mantener,
+            ),
+            pedidos: PedidoService::new(repo.clone(), repo.clone(), repo.clone(), repo.clone(), ventas),
+            caja,
+            repo,
+        }
+    }
+
+    fn persona(&self, perfil: &str) -> Uuid {
+        let documento = (Uuid::new_v4().as_u128() % 10_000_000_000).to_string();
+        let creada = self
+            .personas
+            .crear_persona(CrearPersonaRequest {
+                nombre: format!("Persona {}", perfil),
+                documento,
+                perfil: perfil.to_string(),
+                email: None,
+                telefono: None,
+            })
+            .expect("persona");
+        creada.id.parse().unwrap()
+    }
+
+    /// Cliente con crédito aprobado, para ventas que quedan con saldo pendiente
+    fn cliente_con_credito(&self) -> Uuid {
+        let cliente = self.persona("CLIENTE");
+        self.cuentas
+            .asignar_limite_credito(&cliente.to_string(), LimiteCreditoRequest { limite_credito: Some(1_000_000.0) })
+            .expect("límite de crédito");
+        cliente
+    }
+
+    fn producto(&self, cantidad: i32, precio: f64) -> Uuid {
+        self.producto_en_categoria(cantidad, precio, None)
+    }
+
+    fn producto_en_categoria(&self, cantidad: i32, precio: f64, id_categoria: Option<&str>) -> Uuid {
+        let creado = self
+            .productos
+            .crear_producto(CrearProductoRequest {
+                nombre: "Teclado".to_string(),
+                cantidad,
+                unidad_venta: "Unidad".to_string(),
+                precio_unitario: precio,
+                sku: None,
+                id_categoria: id_categoria.map(str::to_string),
+                id_marca: None,
+                codigos_barra: vec![],
+                id_producto_padre: None,
+                atributos: Default::default(),
+                serializado: false,
+                costo_unitario: None,
+            })
+            .expect("producto");
+        creado.id.parse().unwrap()
+    }
+
+    fn categoria(&self, nombre: &str, id_padre: Option<&str>) -> String {
+        self.catalogo
+            .crear_categoria(CategoriaRequest {
+                nombre: nombre.to_string(),
+                descripcion: None,
+                id_padre: id_padre.map(str::to_string),
+            })
+            .expect("categoría")
+            .id
+    }
+
+    fn stock(&self, id_producto: Uuid) -> i32 {
+        self.repo.obtener_stock(id_producto).unwrap()
+    }
+
+    fn venta(&self, id_cliente: Uuid, items: &[(Uuid, i32)]) -> CrearVentaRequest {
+        CrearVentaRequest {
+            id_cliente: id_cliente.to_string(),
+            id_sesion_caja: self.caja.to_string(),
+            sucursal: Some("Centro".to_string()),
+            id_vendedor: None,
+            detalles: items
+                .iter()
+                .map(|(id_producto, cantidad)| DetalleVentaRequest {
+                    id_producto: id_producto.to_string(),
+                    cantidad: *cantidad,
+                    unidad: None,
+                    numeros_serie: vec![],
+                })
+                .collect(),
+            pagos: vec![],
+            credito: false,
+        }
+    }
+}
+
+fn pago(metodo: &str, monto: f64) -> PagoRequest {
+    PagoRequest {
+        metodo: metodo.to_string(),
+        monto,
+        referencia: None,
+    }
+}
+
+#[test]
+fn crear_producto_sin_vendedores_es_rechazado() {
+    let s = Servicios::new();
+    s.persona("CLIENTE");
+
+    let error = s
+        .productos
+        .crear_producto(CrearProductoRequest {
+            nombre: "Teclado".to_string(),
+            cantidad: 5,
+            unidad_venta: "Unidad".to_string(),
+            precio_unitario: 10.0,
+            sku: None,
+            id_categoria: None,
+            id_marca: None,
+            codigos_barra: vec![],
+            id_producto_padre: None,
+            atributos: Default::default(),
+            serializado: false,
+            costo_unitario: None,
+        })
+        .unwrap_err();
+
+    assert!(matches!(error, ApiError::BusinessRuleViolation(ref m) if m.contains("vendedores")));
+}
+
+#[test]
+fn crear_producto_abre_inventario_con_entrada_inicial() {
+    let s = Servicios::new();
+    s.persona("VENDEDOR");
+
+    let id = s.producto(7, 10.0);
+
+    assert_eq!(s.stock(id), 7);
+    let movimientos = s.repo.listar_movimientos(id).unwrap();
+    assert_eq!(movimientos.len(), 1);
+    assert_eq!(movimientos[0].tipo_movimiento, TipoMovimiento::Entrada);
+    assert_eq!(movimientos[0].cantidad, 7);
+}
+
+#[test]
+fn procesar_venta_descuenta_stock_como_los_triggers() {
+    let s = Servicios::new();
+    s.persona("VENDEDOR");
+    let cliente = s.cliente_con_credito();
+    let producto = s.producto(10, 2.5);
+
+    let creada = s.ventas.procesar_venta(s.venta(cliente, &[(producto, 4)])).unwrap();
+
+    assert!(creada.mensaje.contains("10.00"));
+    assert_eq!(s.stock(producto), 6);
+    let salida = s
+        .repo
+        .listar_movimientos(producto)
+        .unwrap()
+        .into_iter()
+        .find(|m| m.tipo_movimiento == TipoMovimiento::Salida)
+        .expect("movimiento de salida");
+    assert_eq!(salida.cantidad, -4);
+    assert_eq!(salida.observaciones, Some(format!("Venta ID: {}", creada.id)));
+}
+
+#[test]
+fn procesar_venta_con_stock_insuficiente_no_modifica_nada() {
+    let s = Servicios::new();
+    s.persona("VENDEDOR");
+    let cliente = s.persona("CLIENTE");
+    let producto = s.producto(3, 10.0);
+
+    let error = s.ventas.procesar_venta(s.venta(cliente, &[(producto, 4)])).unwrap_err();
+
+    assert!(matches!(error, ApiError::BusinessRuleViolation(ref m) if m.contains("Stock insuficiente")));
+    assert_eq!(s.stock(producto), 3);
+    assert!(s.ventas.obtener_ventas(None, None, None, None, None, None).unwrap().is_empty());
+}
+
+#[test]
+fn procesar_venta_de_cliente_inactivo_es_rechazada() {
+    let s = Servicios::new();
+    s.persona("VENDEDOR");
+    let cliente = s.persona("CLIENTE");
+    let producto = s.producto(3, 10.0);
+    s.repo.desactivar_persona(cliente);
+
+    let error = s.ventas.procesar_venta(s.venta(cliente, &[(producto, 1)])).unwrap_err();
+
+    assert!(matches!(error, ApiError::NotFound(_)));
+    assert_eq!(s.stock(producto), 3);
+}
+
+#[test]
+fn movimiento_que_deja_stock_negativo_es_rechazado() {
+    let s = Servicios::new();
+    let vendedor = s.persona("VENDEDOR");
+    let producto = s.producto(5, 10.0);
+
+    let movimiento = |tipo: &str, cantidad: i32| MovimientoRequest {
+        id_producto: producto.to_string(),
+        tipo_movimiento: tipo.to_string(),
+        id_persona: vendedor.to_string(),
+        cantidad,
+        observaciones: None,
+        lote: None,
+        fecha_vencimiento: None,
+        numeros_serie: vec![],
+        costo_unitario: None,
+    };
+
+    let error = s.inventario.registrar_movimiento(movimiento("SALIDA", 6)).unwrap_err();
+    assert!(matches!(error, ApiError::BusinessRuleViolation(_)));
+    assert_eq!(s.stock(producto), 5);
+
+    s.inventario.registrar_movimiento(movimiento("SALIDA", 5)).unwrap();
+    assert_eq!(s.stock(producto), 0);
+    assert_eq!(
+        s.inventario.obtener_disponibilidad(&producto.to_string()).unwrap().estado_stock,
+        "SIN_STOCK"
+    );
+}
+
+#[test]
+fn documento_duplicado_es_rechazado() {
+    let s = Servicios::new();
+    let solicitud = || CrearPersonaRequest {
+        nombre: "Ana Gómez".to_string(),
+        documento: "1234567890".to_string(),
+        perfil: "CLIENTE".to_string(),
+        email: None,
+        telefono: None,
+    };
+
+    s.personas.crear_persona(solicitud()).unwrap();
+    let error = s.personas.crear_persona(solicitud()).unwrap_err();
+
+    assert!(matches!(error, ApiError::BusinessRuleViolation(_)));
+}
+
+#[test]
+fn revertir_ajuste_negativo_devuelve_el_stock() {
+    let s = Servicios::new();
+    let vendedor = s.persona("VENDEDOR");
+    let producto = s.producto(8, 10.0);
+
+    let ajuste = s
+        .inventario
+        .registrar_movimiento(MovimientoRequest {
+            id_producto: producto.to_string(),
+            tipo_movimiento: "AJUSTE".to_string(),
+            id_persona: vendedor.to_string(),
+            cantidad: 3,
+            observaciones: None,
+            lote: None,
+            fecha_vencimiento: None,
+            numeros_serie: vec![],
+            costo_unitario: None,
+        })
+        .unwrap();
+    assert_eq!(s.stock(producto), 11);
+
+    let revertir = || RevertirMovimientoRequest { id_persona: vendedor.to_string(), observaciones: None };
+    let reversion = s.inventario.revertir_movimiento(&ajuste.id, revertir()).unwrap();
+    assert_eq!(s.stock(producto), 8);
+
+    let compensatorio = s.inventario.obtener_movimiento(&reversion.id).unwrap();
+    assert_eq!(compensatorio.tipo_movimiento, "AJUSTE");
+    assert_eq!(compensatorio.cantidad, -3);
+    assert_eq!(compensatorio.id_movimiento_revertido.as_deref(), Some(ajuste.id.as_str()));
+    assert_eq!(s.inventario.obtener_movimiento(&ajuste.id).unwrap().id_reversion, Some(reversion.id));
+
+    let error = s.inventario.revertir_movimiento(&ajuste.id, revertir()).unwrap_err();
+    assert!(matches!(error, ApiError::BusinessRuleViolation(_)));
+    assert_eq!(s.stock(producto), 8);
+}
+
+#[test]
+fn categoria_no_puede_colgar_de_su_subcategoria() {
+    let s = Servicios::new();
+    let perifericos = s.categoria("Periféricos", None);
+    let teclados = s.categoria("Teclados", Some(&perifericos));
+    let mecanicos = s.categoria("Mecánicos", Some(&teclados));
+
+    let mover = |id_padre: &str| CategoriaRequest {
+        nombre: "Periféricos".to_string(),
+        descripcion: None,
+        id_padre: Some(id_padre.to_string()),
+    };
+    for id_padre in [&perifericos, &mecanicos] {
+        let error = s.catalogo.actualizar_categoria(&perifericos, mover(id_padre)).unwrap_err();
+        assert!(matches!(error, ApiError::BusinessRuleViolation(_)), "padre {}", id_padre);
+    }
+
+    let error = s.catalogo.desactivar_categoria(&teclados).unwrap_err();
+    assert!(matches!(error, ApiError::BusinessRuleViolation(ref m) if m.contains("1 subcategorías")));
+}
+
+#[test]
+fn buscar_personas_ignora_acentos_y_admite_prefijo_de_documento() {
+    let s = Servicios::new();
+    for (nombre, documento) in [("María González", "1032456789"), ("Mario Gómez", "5200111222")] {
+        s.personas
+            .crear_persona(CrearPersonaRequest {
+                nombre: nombre.to_string(),
+                documento: documento.to_string(),
+                perfil: "CLIENTE".to_string(),
+                email: None,
+                telefono: None,
+            })
+            .unwrap();
+    }
+
+    let buscar = |q: &str| -> Vec<String> {
+        s.personas
+            .listar_personas(None, Some(q.to_string()))
+            .unwrap()
+            .into_iter()
+            .map(|persona| persona.nombre)
+            .collect()
+    };
+
+    assert_eq!(buscar("maria gonz"), ["María González"]);
+    assert_eq!(buscar("GOMEZ"), ["Mario Gómez"]);
+    assert_eq!(buscar("5200"), ["Mario Gómez"]);
+    assert_eq!(buscar("   ").len(), 2);
+}
+
+#[test]
+fn vender_por_caja_descuenta_unidades_base() {
+    let s = Servicios::new();
+    s.persona("VENDEDOR");
+    let cliente = s.cliente_con_credito();
+    let producto = s.producto(30, 2.0);
+    s.productos
+        .agregar_unidad(
+            &producto.to_string(),
+            UnidadProductoRequest { nombre: "Caja x12".to_string(), factor: 12, precio_unitario: None },
+        )
+        .unwrap();
+
+    let mut request = s.venta(cliente, &[(producto, 2)]);
+    request.detalles[0].unidad = Some("Caja x12".to_string());
+    let creada = s.ventas.procesar_venta(request).unwrap();
+
+    assert_eq!(s.stock(producto), 6);
+    let vendida = s.ventas.obtener_venta_por_id(&creada.id).unwrap();
+    assert_eq!(vendida.total, 48.0);
+    assert_eq!(vendida.detalles[0].cantidad_base, 24);
+}
+
+#[test]
+fn venta_reparte_entre_lotes_y_stock_sin_lote() {
+    let s = Servicios::new();
+    let vendedor = s.persona("VENDEDOR");
+    let cliente = s.cliente_con_credito();
+    // 3 unidades sin lote del inventario inicial
+    let producto = s.producto(3, 1.0);
+    let hoy = Utc::now().date_naive();
+    s.inventario
+        .registrar_movimiento(MovimientoRequest {
+            id_producto: producto.to_string(),
+            tipo_movimiento: "ENTRADA".to_string(),
+            id_persona: vendedor.to_string(),
+            cantidad: 4,
+            observaciones: None,
+            lote: Some("A1".to_string()),
+            fecha_vencimiento: Some((hoy + Duration::days(10)).format("%Y-%m-%d").to_string()),
+            numeros_serie: vec![],
+            costo_unitario: None,
+        })
+        .unwrap();
+
+    let creada = s.ventas.procesar_venta(s.venta(cliente, &[(producto, 6)])).unwrap();
+
+    let vendida = s.ventas.obtener_venta_por_id(&creada.id).unwrap();
+    let lotes = &vendida.detalles[0].lotes;
+    assert_eq!(lotes.len(), 1);
+    assert_eq!((lotes[0].codigo.as_str(), lotes[0].cantidad), ("A1", 4));
+    assert_eq!(s.stock(producto), 1);
+}
+
+#[test]
+fn numero_de_serie_vendido_solo_vuelve_al_stock_como_devuelto() {
+    let s = Servicios::new();
+    let vendedor = s.persona("VENDEDOR");
+    let cliente = s.cliente_con_credito();
+    let producto = s.producto(2, 900.0);
+    let serializado = s
+        .productos
+        .cambiar_serializado(
+            &producto.to_string(),
+            SerializadoRequest { serializado: true, numeros_serie: vec!["sn-1".to_string(), "SN-2".to_string()] },
+        )
+        .unwrap();
+    assert!(serializado.serializado);
+
+    let vender = |numero: &str| {
+        let mut request = s.venta(cliente, &[(producto, 1)]);
+        request.detalles[0].numeros_serie = vec![numero.to_string()];
+        s.ventas.procesar_venta(request)
+    };
+    let creada = vender(" sn-1").unwrap();
+    assert_eq!(s.ventas.obtener_venta_por_id(&creada.id).unwrap().detalles[0].numeros_serie, ["SN-1"]);
+    let error = vender("SN-1").unwrap_err();
+    assert!(matches!(error, ApiError::BusinessRuleViolation(_)), "{:?}", error);
+    assert_eq!(s.stock(producto), 1);
+
+    // Sin número de serie no se vende, y el cliente lo devuelve con una ENTRADA
+    let error = s.ventas.procesar_venta(s.venta(cliente, &[(producto, 1)])).unwrap_err();
+    assert!(matches!(error, ApiError::InvalidInput(_)), "{:?}", error);
+    s.inventario
+        .registrar_movimiento(MovimientoRequest {
+            id_producto: producto.to_string(),
+            tipo_movimiento: "ENTRADA".to_string(),
+            id_persona: vendedor.to_string(),
+            cantidad: 1,
+            observaciones: Some("Devolución".to_string()),
+            lote: None,
+            fecha_vencimiento: None,
+            numeros_serie: vec!["SN-1".to_string()],
+            costo_unitario: None,
+        })
+        .unwrap();
+
+    let encontradas = s.ventas.buscar_serie("sn-1").unwrap();
+    assert_eq!(encontradas.len(), 1);
+    assert_eq!(encontradas[0].estado, EstadoSerie::Devuelto);
+    assert_eq!(encontradas[0].ventas[0].id_venta, creada.id);
+    assert_eq!(s.stock(producto), 2);
+}
+
+#[test]
+fn fifo_revertir_una_entrada_retira_las_unidades_de_su_capa() {
+    let s = Servicios::con_repo(MemoriaRepository::con_metodo_valorizacion(MetodoValorizacion::Fifo));
+    let vendedor = s.persona("VENDEDOR");
+    let cliente = s.cliente_con_credito();
+    let producto = s.producto(0, 200.0);
+
+    let compra = |cantidad: i32, costo: f64| {
+        s.inventario
+            .registrar_movimiento(MovimientoRequest {
+                id_producto: producto.to_string(),
+                tipo_movimiento: "ENTRADA".to_string(),
+                id_persona: vendedor.to_string(),
+                cantidad,
+                observaciones: None,
+                lote: None,
+                fecha_vencimiento: None,
+                numeros_serie: vec![],
+                costo_unitario: Some(costo),
+            })
+            .unwrap()
+    };
+    compra(10, 100.0);
+    let segunda = compra(10, 130.0);
+    compra(10, 160.0);
+
+    let reversion = s
+        .inventario
+        .revertir_movimiento(&segunda.id, RevertirMovimientoRequest { id_persona: vendedor.to_string(), observaciones: None })
+        .unwrap();
+    assert_eq!(s.inventario.obtener_movimiento(&reversion.id).unwrap().costo_unitario, Some(130.0));
+    assert_eq!(s.stock(producto), 20);
+
+    // Quedan 10 a 100 y 10 a 160: la venta de 12 toma las 10 más antiguas y 2 de la última
+    let creada = s.ventas.procesar_venta(s.venta(cliente, &[(producto, 12)])).unwrap();
+    let detalle = &s.ventas.obtener_venta_por_id(&creada.id).unwrap().detalles[0];
+    assert_eq!(detalle.costo, 1320.0);
+
+    let movimientos = s.repo.listar_movimientos(producto).unwrap();
+    let salida = movimientos.iter().find(|m| m.tipo_movimiento == TipoMovimiento::Salida).unwrap();
+    assert_eq!(salida.costo_unitario.as_ref().map(ToString::to_string).as_deref(), Some("110.0000"));
+}
+
+#[test]
+fn la_comision_de_cada_linea_sale_de_la_regla_mas_especifica() {
+    let s = Servicios::new();
+    let ana = s.persona("VENDEDOR");
+    let beto = s.persona("VENDEDOR");
+    let cliente = s.cliente_con_credito();
+    let computo = s.categoria("Cómputo", None);
+    let portatiles = s.categoria("Portátiles", Some(&computo));
+    let laptop = s.producto_en_categoria(10, 1000.0, Some(&portatiles));
+    let cable = s.producto(10, 100.0);
+
+    let regla = |id_vendedor: Option<Uuid>, id_categoria: Option<&str>, porcentaje: f64| {
+        s.comisiones.crear_regla(ReglaComisionRequest {
+            id_vendedor: id_vendedor.map(|id| id.to_string()),
+            id_categoria: id_categoria.map(str::to_string),
+            porcentaje,
+        })
+    };
+    regla(None, None, 1.0).unwrap();
+    regla(None, Some(&computo), 3.0).unwrap();
+    regla(Some(ana), None, 2.0).unwrap();
+    regla(Some(ana), Some(&computo), 5.0).unwrap();
+    // Una sola regla activa por vendedor y categoría, y solo para vendedores
+    assert!(matches!(regla(None, None, 4.0).unwrap_err(), ApiError::BusinessRuleViolation(_)));
+    assert!(matches!(regla(Some(cliente), None, 4.0).unwrap_err(), ApiError::BusinessRuleViolation(_)));
+    assert!(matches!(regla(None, None, 100.5).unwrap_err(), ApiError::InvalidInput(_)));
+
+    let comisiones = |id_vendedor: Option<Uuid>| {
+        let mut request = s.venta(cliente, &[(laptop, 1), (cable, 2)]);
+        request.id_vendedor = id_vendedor.map(|id| id.to_string());
+        let creada = s.ventas.procesar_venta(request).unwrap();
+        let detalles = s.ventas.obtener_venta_por_id(&creada.id).unwrap().detalles;
+        detalles.iter().map(|detalle| detalle.comision).collect::<Vec<_>>()
+    };
+    assert_eq!(comisiones(Some(ana)), [50.0, 4.0]);
+    assert_eq!(comisiones(Some(beto)), [30.0, 2.0]);
+    assert_eq!(comisiones(None), [0.0, 0.0]);
+
+    let liquidacion = s.comisiones.liquidacion(None).unwrap();
+    assert_eq!(liquidacion.total_comisiones, 86.0);
+    let vendedores: Vec<(String, i64, f64, f64)> = liquidacion
+        .vendedores
+        .iter()
+        .map(|v| (v.id_vendedor.clone(), v.ventas, v.total_vendido, v.comision))
+        .collect();
+    assert_eq!(vendedores, [(ana.to_string(), 1, 1200.0, 54.0), (beto.to_string(), 1, 1200.0, 32.0)]);
+}
+
+#[test]
+fn pagos_combinados_cubren_el_total_y_solo_el_efectivo_da_cambio() {
+    let s = Servicios::new();
+    s.persona("VENDEDOR");
+    let cliente = s.cliente_con_credito();
+    let producto = s.producto(10, 100.0);
+
+    let con_pagos = |pagos: Vec<PagoRequest>| {
+        let mut request = s.venta(cliente, &[(producto, 3)]);
+        request.pagos = pagos;
+        s.ventas.procesar_venta(request)
+    };
+
+    // Ninguna venta rechazada descuenta stock
+    let rechazos = [
+        vec![pago("TARJETA", 200.0), pago("EFECTIVO", 50.0)],
+        vec![pago("TARJETA", 250.0), pago("TRANSFERENCIA", 100.0)],
+        vec![pago("EFECTIVO", 300.0), pago("EFECTIVO", 20.0)],
+    ];
+    for pagos in rechazos {
+        assert!(matches!(con_pagos(pagos).unwrap_err(), ApiError::BusinessRuleViolation(_)));
+    }
+    assert!(matches!(con_pagos(vec![pago("CHEQUE", 300.0)]).unwrap_err(), ApiError::InvalidInput(_)));
+    assert!(matches!(con_pagos(vec![pago("EFECTIVO", 0.0)]).unwrap_err(), ApiError::InvalidInput(_)));
+    assert_eq!(s.stock(producto), 10);
+
+    let creada = con_pagos(vec![pago("tarjeta", 200.0), pago("EFECTIVO", 150.0)]).unwrap();
+    assert_eq!(creada.estado, EstadoVenta::Pagada);
+    assert_eq!(creada.cambio, 50.0);
+
+    let pagada = s.ventas.obtener_venta_por_id(&creada.id).unwrap();
+    assert_eq!((pagada.total_pagado, pagada.cambio), (300.0, 50.0));
+    let pagos: Vec<(MetodoPago, f64, f64)> = pagada.pagos.iter().map(|p| (p.metodo, p.monto, p.cambio)).collect();
+    assert_eq!(pagos, [(MetodoPago::Tarjeta, 200.0, 0.0), (MetodoPago::Efectivo, 100.0, 50.0)]);
+
+    // Sin pagos queda pendiente hasta que se cobra, y no se cobra dos veces
+    let pendiente = con_pagos(vec![]).unwrap();
+    assert_eq!(pendiente.estado, EstadoVenta::PendientePago);
+    let cobrar = |pagos: Vec<PagoRequest>| s.ventas.registrar_pagos(&pendiente.id, RegistrarPagosRequest { pagos, id_sesion_caja: s.caja.to_string() });
+    assert!(matches!(cobrar(vec![pago("TRANSFERENCIA", 299.0)]).unwrap_err(), ApiError::BusinessRuleViolation(_)));
+    assert_eq!(cobrar(vec![pago("TRANSFERENCIA", 300.0)]).unwrap().estado, EstadoVenta::Pagada);
+    assert!(matches!(cobrar(vec![pago("EFECTIVO", 300.0)]).unwrap_err(), ApiError::BusinessRuleViolation(_)));
+
+    let filtrar = |estado: Option<&str>, metodo: Option<&str>| {
+        s.ventas
+            .obtener_ventas(None, None, None, None, estado.map(str::to_string), metodo.map(str::to_string))
+            .map(|ventas| ventas.len())
+    };
+    assert_eq!(filtrar(Some("PAGADA"), None).unwrap(), 2);
+    assert_eq!(filtrar(Some("PENDIENTE_PAGO"), None).unwrap(), 0);
+    assert_eq!(filtrar(None, Some("EFECTIVO")).unwrap(), 1);
+    assert_eq!(filtrar(None, Some("TRANSFERENCIA")).unwrap(), 1);
+    assert!(matches!(filtrar(Some("COBRADA"), None).unwrap_err(), ApiError::InvalidInput(_)));
+}
+
+#[test]
+fn anular_venta_devuelve_lotes_y_costo_y_reembolsa_los_pagos() {
+    let s = Servicios::con_repo(MemoriaRepository::con_metodo_valorizacion(MetodoValorizacion::Fifo));
+    let vendedor = s.persona("VENDEDOR");
+    let cliente = s.persona("CLIENTE");
+    // 3 unidades sin lote del inventario inicial, a costo 0
+    let producto = s.producto(3, 10.0);
+    let hoy = Utc::now().date_naive();
+    s.inventario
+        .registrar_movimiento(MovimientoRequest {
+            id_producto: producto.to_string(),
+            tipo_movimiento: "ENTRADA".to_string(),
+            id_persona: vendedor.to_string(),
+            cantidad: 4,
+            observaciones: None,
+            lote: Some("A1".to_string()),
+            fecha_vencimiento: Some((hoy + Duration::days(10)).format("%Y-%m-%d").to_string()),
+            numeros_serie: vec![],
+            costo_unitario: Some(4.0),
+        })
+        .unwrap();
+
+    let mut request = s.venta(cliente, &[(producto, 6)]);
+    request.pagos = vec![pago("EFECTIVO", 100.0)];
+    let creada = s.ventas.procesar_venta(request).unwrap();
+    assert_eq!(creada.cambio, 40.0);
+    assert_eq!(s.stock(producto), 1);
+
+    let anular = || {
+        s.ventas.anular_venta(
+            &creada.id,
+            AnularVentaRequest { id_persona: vendedor.to_string(), motivo: Some("Desistió".to_string()) },
+        )
+    };
+    let anulada = anular().unwrap();
+    assert_eq!(anulada.estado, EstadoVenta::Anulada);
+    assert!(anulada.pagos.is_empty());
+    assert_eq!(anulada.total_pagado, 0.0);
+    assert_eq!(s.stock(producto), 7);
+
+    // Las 4 unidades vuelven al lote y las 2 sin lote, al costo medio con que salió la línea
+    let lotes = s.inventario.obtener_disponibilidad(&producto.to_string()).unwrap().lotes;
+    assert_eq!((lotes[0].codigo.as_str(), lotes[0].cantidad_disponible), ("A1", 4));
+    let mut entradas: Vec<(i32, Option<String>)> = s
+        .repo
+        .listar_movimientos(producto)
+        .unwrap()
+        .into_iter()
+        .filter(|m| m.observaciones.as_deref().is_some_and(|o| o.starts_with("Anulación de la venta")))
+        .map(|m| (m.cantidad, m.costo_unitario.map(|c| c.to_string())))
+        .collect();
+    entradas.sort();
+    assert_eq!(entradas, [(2, Some("2.0000".to_string())), (4, Some("2.0000".to_string()))]);
+
+    assert!(matches!(anular().unwrap_err(), ApiError::BusinessRuleViolation(_)));
+    let cobrar = s.ventas.registrar_pagos(&creada.id, RegistrarPagosRequest { pagos: vec![pago("EFECTIVO", 60.0)], id_sesion_caja: s.caja.to_string() });
+    assert!(matches!(cobrar.unwrap_err(), ApiError::BusinessRuleViolation(_)));
+}
+
+#[test]
+fn venta_a_credito_respeta_el_limite_y_admite_abonos_parciales() {
+    let s = Servicios::new();
+    let vendedor = s.persona("VENDEDOR");
+    let cliente = s.persona("CLIENTE");
+    let producto = s.producto(20, 100.0);
+
+    let a_credito = |cantidad: i32, pagos: Vec<PagoRequest>| {
+        let mut request = s.venta(cliente, &[(producto, cantidad)]);
+        request.credito = true;
+        request.pagos = pagos;
+        s.ventas.procesar_venta(request)
+    };
+    let limite = |id: Uuid, limite_credito: Option<f64>| {
+        s.cuentas.asignar_limite_credito(&id.to_string(), LimiteCreditoRequest { limite_credito })
+    };
+
+    // Sin límite no hay crédito, y solo los clientes lo tienen
+    assert!(matches!(a_credito(1, vec![]).unwrap_err(), ApiError::BusinessRuleViolation(_)));
+    assert!(matches!(limite(vendedor, Some(500.0)).unwrap_err(), ApiError::BusinessRuleViolation(_)));
+    assert!(matches!(limite(cliente, Some(-1.0)).unwrap_err(), ApiError::InvalidInput(_)));
+    // Dejar la venta pendiente de pago también es fiar: sin crédito o con límite cero no se puede
+    let pendiente = || s.ventas.procesar_venta(s.venta(cliente, &[(producto, 1)]));
+    assert!(matches!(pendiente().unwrap_err(), ApiError::BusinessRuleViolation(_)));
+    limite(cliente, Some(0.0)).unwrap();
+    assert!(matches!(pendiente().unwrap_err(), ApiError::BusinessRuleViolation(_)));
+    assert_eq!(s.stock(producto), 20);
+    assert_eq!(limite(cliente, Some(500.0)).unwrap().limite_credito, Some(500.0));
+
+    let primera = a_credito(3, vec![pago("EFECTIVO", 100.0)]).unwrap();
+    assert_eq!((primera.estado, primera.saldo), (EstadoVenta::PendientePago, 200.0));
+    // Una venta pendiente de pago también cuenta en el saldo
+    let en_caja = s.ventas.procesar_venta(s.venta(cliente, &[(producto, 1)])).unwrap();
+    assert!(matches!(a_credito(3, vec![]).unwrap_err(), ApiError::BusinessRuleViolation(_)));
+    assert_eq!(s.stock(producto), 16);
+    let segunda = a_credito(2, vec![]).unwrap();
+
+    let cuenta = s.cuentas.estado_cuenta(&cliente.to_string()).unwrap();
+    assert_eq!((cuenta.saldo, cuenta.credito_disponible), (500.0, Some(0.0)));
+    assert_eq!(cuenta.antiguedad.dias_0_30, 500.0);
+    assert_eq!(cuenta.documentos.len(), 3);
+    let movimientos: Vec<(TipoMovimientoCuenta, f64, f64)> =
+        cuenta.movimientos.iter().map(|m| (m.tipo, m.monto, m.saldo)).collect();
+    assert_eq!(
+        movimientos,
+        [
+            (TipoMovimientoCuenta::Cargo, 300.0, 300.0),
+            (TipoMovimientoCuenta::Abono, 100.0, 200.0),
+            (TipoMovimientoCuenta::Cargo, 100.0, 300.0),
+            (TipoMovimientoCuenta::Cargo, 200.0, 500.0),
+        ]
+    );
+
+    // Abonos parciales solo en las ventas a crédito, y nunca por encima del saldo
+    let abonar = |id: &str, pagos: Vec<PagoRequest>| s.ventas.registrar_pagos(id, RegistrarPagosRequest { pagos, id_sesion_caja: s.caja.to_string() });
+    let abonada = abonar(&primera.id, vec![pago("TRANSFERENCIA", 50.0)]).unwrap();
+    assert_eq!((abonada.estado, abonada.saldo), (EstadoVenta::PendientePago, 150.0));
+    assert!(matches!(abonar(&en_caja.id, vec![pago("EFECTIVO", 50.0)]).unwrap_err(), ApiError::BusinessRuleViolation(_)));
+    assert!(matches!(abonar(&primera.id, vec![pago("TARJETA", 200.0)]).unwrap_err(), ApiError::BusinessRuleViolation(_)));
+    let saldada = abonar(&primera.id, vec![pago("EFECTIVO", 200.0)]).unwrap();
+    assert_eq!((saldada.estado, saldada.saldo, saldada.cambio), (EstadoVenta::Pagada, 0.0, 50.0));
+
+    // Anular una venta a crédito libera su saldo
+    let id_vendedor = vendedor.to_string();
+    s.ventas
+        .anular_venta(&segunda.id, AnularVentaRequest { id_persona: id_vendedor, motivo: None })
+        .unwrap();
+    let cuenta = s.cuentas.estado_cuenta(&cliente.to_string()).unwrap();
+    assert_eq!((cuenta.saldo, cuenta.credito_disponible), (100.0, Some(400.0)));
+    assert_eq!(cuenta.documentos.len(), 1);
+    assert_eq!(cuenta.documentos[0].id_venta, en_caja.id);
+}
+
+#[test]
+fn cierre_de_caja_cuadra_el_efectivo_y_bloquea_la_sesion() {
+    let s = Servicios::new();
+    let vendedor = s.persona("VENDEDOR");
+    let cliente = s.cliente_con_credito();
+    let producto = s.producto(20, 100.0);
+
+    let abrir = |id_operador: Uuid| {
+        s.cajas.abrir_sesion(AbrirSesionCajaRequest {
+            sucursal: " Norte ".to_string(),
+            terminal: "CAJA-02".to_string(),
+            id_operador: id_operador.to_string(),
+            monto_apertura: 100.0,
+        })
+    };
+    assert!(matches!(abrir(cliente).unwrap_err(), ApiError::BusinessRuleViolation(_)));
+    let sesion = abrir(vendedor).unwrap();
+    assert_eq!((sesion.sucursal.as_str(), sesion.monto_apertura), ("Norte", 100.0));
+    assert!(matches!(abrir(vendedor).unwrap_err(), ApiError::BusinessRuleViolation(_)));
+
+    let en_norte = |cantidad: i32, pagos: Vec<PagoRequest>| {
+        let mut request = s.venta(cliente, &[(producto, cantidad)]);
+        request.id_sesion_caja = sesion.id.clone();
+        request.sucursal = Some("norte".to_string());
+        request.pagos = pagos;
+        s.ventas.procesar_venta(request)
+    };
+    // La sucursal de la venta debe ser la de la caja
+    let mut request = s.venta(cliente, &[(producto, 1)]);
+    request.id_sesion_caja = sesion.id.clone();
+    assert!(matches!(s.ventas.procesar_venta(request).unwrap_err(), ApiError::BusinessRuleViolation(_)));
+
+    let primera = en_norte(3, vec![pago("EFECTIVO", 500.0)]).unwrap();
+    assert_eq!(primera.cambio, 200.0);
+    assert_eq!(s.ventas.obtener_venta_por_id(&primera.id).unwrap().id_sesion_caja, Some(sesion.id.clone()));
+    en_norte(1, vec![pago("TARJETA", 100.0)]).unwrap();
+    let pendiente = en_norte(1, vec![]).unwrap();
+    let anulada = en_norte(2, vec![pago("EFECTIVO", 200.0)]).unwrap();
+    s.ventas
+        .anular_venta(&anulada.id, AnularVentaRequest { id_persona: vendedor.to_string(), motivo: None })
+        .unwrap();
+    let cobrar = |id_sesion_caja: &str| {
+        s.ventas.registrar_pagos(
+            &pendiente.id,
+            RegistrarPagosRequest { pagos: vec![pago("EFECTIVO", 100.0)], id_sesion_caja: id_sesion_caja.to_string() },
+        )
+    };
+    assert_eq!(cobrar(&sesion.id).unwrap().estado, EstadoVenta::Pagada);
+
+    // Apertura 100 + 300 + 100 en efectivo; la venta anulada no cuenta
+    let x = s.cajas.reporte_x(&sesion.id).unwrap();
+    assert_eq!(x.tipo, TipoReporteCaja::X);
+    assert_eq!((x.ventas, x.ventas_anuladas, x.total_vendido), (3, 1, 500.0));
+    let pagos: Vec<(MetodoPago, i64, f64)> = x.pagos.iter().map(|p| (p.metodo, p.pagos, p.monto)).collect();
+    assert_eq!(pagos, [(MetodoPago::Efectivo, 2, 400.0), (MetodoPago::Tarjeta, 1, 100.0)]);
+    assert_eq!((x.cambio_entregado, x.efectivo_esperado, x.efectivo_contado), (200.0, 500.0, None));
+    assert!(matches!(s.cajas.reporte_z(&sesion.id).unwrap_err(), ApiError::BusinessRuleViolation(_)));
+
+    let cerrar = |efectivo_contado: f64| {
+        s.cajas.cerrar_sesion(&sesion.id, CerrarSesionCajaRequest { efectivo_contado, observaciones: Some(" Faltante ".to_string()) })
+    };
+    assert!(matches!(cerrar(-1.0).unwrap_err(), ApiError::InvalidInput(_)));
+    let z = cerrar(490.0).unwrap();
+    assert_eq!(z.tipo, TipoReporteCaja::Z);
+    assert_eq!((z.efectivo_esperado, z.efectivo_contado, z.diferencia), (500.0, Some(490.0), Some(-10.0)));
+    assert_eq!(z.sesion.observaciones.as_deref(), Some("Faltante"));
+    assert_eq!(s.cajas.reporte_z(&sesion.id).unwrap().diferencia, Some(-10.0));
+
+    // Una sesión cerrada no vende, no cobra ni se vuelve a cerrar; la terminal queda libre
+    assert!(matches!(en_norte(1, vec![]).unwrap_err(), ApiError::BusinessRuleViolation(_)));
+    assert!(matches!(cobrar(&sesion.id).unwrap_err(), ApiError::BusinessRuleViolation(_)));
+    let anular = s.ventas.anular_venta(&primera.id, AnularVentaRequest { id_persona: vendedor.to_string(), motivo: None });
+    assert!(matches!(anular.unwrap_err(), ApiError::BusinessRuleViolation(_)));
+    assert!(matches!(cerrar(490.0).unwrap_err(), ApiError::BusinessRuleViolation(_)));
+    assert!(matches!(s.cajas.reporte_x(&sesion.id).unwrap_err(), ApiError::BusinessRuleViolation(_)));
+    assert_eq!(s.stock(producto), 15);
+    abrir(vendedor).unwrap();
+
+    let listar = |estado: &str| s.cajas.listar_sesiones(Some("Norte".to_string()), Some(estado.to_string()));
+    assert_eq!(listar("cerrada").unwrap()[0].id, sesion.id);
+    assert_eq!(listar("ABIERTA").unwrap().len(), 1);
+    assert!(matches!(listar("ARQUEADA").unwrap_err(), ApiError::InvalidInput(_)));
+    assert!(matches!(s.cajas.obtener_sesion(&Uuid::new_v4().to_string()).unwrap_err(), ApiError::NotFound(_)));
+}
+
+#[test]
+fn la_mejor_promocion_vigente_fija_el_precio_sobre_la_lista_del_cliente() {
+    let s = Servicios::new();
+    let vendedor = s.persona("VENDEDOR");
+    let mayorista = s.cliente_con_credito();
+    let cliente = s.cliente_con_credito();
+    let producto = s.producto(20, 100.0);
+
+    let lista = s
+        .precios
+        .crear_lista(ListaPreciosRequest { nombre: "Mayorista".to_string(), descripcion: None })
+        .unwrap();
+    assert!(matches!(
+        s.precios.crear_lista(ListaPreciosRequest { nombre: "MAYORISTA".to_string(), descripcion: None }).unwrap_err(),
+        ApiError::BusinessRuleViolation(_)
+    ));
+    s.precios
+        .fijar_precio(&lista.id, &producto.to_string(), PrecioListaRequest { precio_unitario: 80.0, unidad: None })
+        .unwrap();
+    let asignar = |id: Uuid| {
+        s.precios.asignar_lista_cliente(&id.to_string(), ListaPreciosClienteRequest { id_lista_precios: Some(lista.id.clone()) })
+    };
+    assert!(matches!(asignar(vendedor).unwrap_err(), ApiError::BusinessRuleViolation(_)));
+    assert_eq!(asignar(mayorista).unwrap().id_lista_precios, Some(lista.id.clone()));
+
+    let fecha = |dias: i64| (Utc::now() + Duration::days(dias)).format("%Y-%m-%d %H:%M:%S").to_string();
+    let promocion = |tipo: &str, desde: i64, hasta: i64, porcentaje: Option<f64>, lleva_paga: (Option<i32>, Option<i32>), paquete: Option<f64>| {
+        s.precios.crear_promocion(PromocionRequest {
+            nombre: format!("Promo {}", tipo),
+            tipo: tipo.to_string(),
+            id_producto: producto.to_string(),
+            fecha_inicio: fecha(desde),
+            fecha_fin: fecha(hasta),
+            porcentaje,
+            cantidad_lleva: lleva_paga.0,
+            cantidad_paga: lleva_paga.1,
+            precio_paquete: paquete,
+        })
+    };
+    let diez = promocion("PORCENTAJE", -5, 5, Some(10.0), (None, None), None).unwrap();
+    let tres_por_dos = promocion("LLEVA_PAGA", -5, 5, None, (Some(3), Some(2)), None).unwrap();
+    // Mismo descuento que la anterior pero empezó después: no gana el empate
+    promocion("LLEVA_PAGA", -1, 5, None, (Some(3), Some(2)), None).unwrap();
+    promocion("PRECIO_PAQUETE", -5, 5, None, (Some(3), None), Some(250.0)).unwrap();
+    promocion("PORCENTAJE", -10, -5, Some(50.0), (None, None), None).unwrap();
+    assert!(matches!(promocion("LLEVA_PAGA", -5, 5, None, (Some(2), Some(2)), None).unwrap_err(), ApiError::InvalidInput(_)));
+    assert!(matches!(promocion("PORCENTAJE", 5, -5, Some(10.0), (None, None), None).unwrap_err(), ApiError::InvalidInput(_)));
+    assert!(matches!(promocion("REGALO", -5, 5, None, (None, None), None).unwrap_err(), ApiError::InvalidInput(_)));
+
+    let cotizar = |id_cliente: Uuid, cantidad: i32| {
+        s.precios
+            .cotizar(CotizarPreciosRequest {
+                id_cliente: Some(id_cliente.to_string()),
+                detalles: vec![LineaPrecioRequest { id_producto: producto.to_string(), cantidad, unidad: None }],
+            })
+            .unwrap()
+    };
+    // A 80 de lista, el 3x2 (160) supera al 10% (56) y al paquete de 250 (más caro que 3 x 80)
+    let mayoreo = cotizar(mayorista, 7);
+    assert_eq!((mayoreo.bruto, mayoreo.descuento, mayoreo.total), (560.0, 160.0, 400.0));
+    let linea = &mayoreo.detalles[0];
+    assert_eq!((linea.origen, linea.precio_unitario), (OrigenPrecio::Promocion, 80.0));
+    assert_eq!(linea.id_promocion, Some(tres_por_dos.id.clone()));
+    assert_eq!(linea.id_lista_precios, Some(lista.id.clone()));
+    // Una sola unidad no completa ningún grupo: gana el porcentaje sobre el precio de catálogo
+    let suelta = cotizar(cliente, 1);
+    assert_eq!((suelta.total, suelta.detalles[0].id_promocion.clone()), (90.0, Some(diez.id.clone())));
+    assert_eq!(s.stock(producto), 20);
+
+    let creada = s.ventas.procesar_venta(s.venta(mayorista, &[(producto, 7)])).unwrap();
+    assert!(creada.mensaje.contains("400.00"));
+    let detalle = &s.ventas.obtener_venta_por_id(&creada.id).unwrap().detalles[0];
+    assert_eq!((detalle.subtotal, detalle.descuento), (400.0, 160.0));
+    assert_eq!(detalle.origen_precio, OrigenPrecio::Promocion);
+    assert_eq!(detalle.id_promocion, Some(tres_por_dos.id.clone()));
+
+    // Sin promociones queda el precio de lista, y sin lista el de catálogo
+    for vigente in s.precios.listar_promociones(PromocionesQuery { id_producto: None, vigentes: None }).unwrap() {
+        s.precios.desactivar_promocion(&vigente.id).unwrap();
+    }
+    let linea = &cotizar(mayorista, 3).detalles[0];
+    assert_eq!((linea.origen, linea.subtotal), (OrigenPrecio::Lista, 240.0));
+    s.precios.quitar_precio(&lista.id, &producto.to_string(), PrecioListaQuery { unidad: None }).unwrap();
+    let linea = &cotizar(mayorista, 3).detalles[0];
+    assert_eq!((linea.origen, linea.subtotal, linea.id_lista_precios.clone()), (OrigenPrecio::Catalogo, 300.0, None));
+}
+
+#[test]
+fn vender_por_caja_usa_la_lista_del_cliente_y_promociones_en_unidades_base() {
+    let s = Servicios::new();
+    s.persona("VENDEDOR");
+    let mayorista = s.cliente_con_credito();
+    let cliente = s.cliente_con_credito();
+    let producto = s.producto(100, 100.0);
+    s.productos
+        .agregar_unidad(
+            &producto.to_string(),
+            UnidadProductoRequest { nombre: "Caja x12".to_string(), factor: 12, precio_unitario: Some(1000.0) },
+        )
+        .unwrap();
+
+    let lista = s
+        .precios
+        .crear_lista(ListaPreciosRequest { nombre: "Mayorista".to_string(), descripcion: None })
+        .unwrap();
+    let fijar = |precio_unitario: f64, unidad: Option<&str>| {
+        s.precios.fijar_precio(
+            &lista.id,
+            &producto.to_string(),
+            PrecioListaRequest { precio_unitario, unidad: unidad.map(str::to_string) },
+        )
+    };
+    fijar(80.0, None).unwrap();
+    assert!(matches!(fijar(900.0, Some("Pallet")).unwrap_err(), ApiError::InvalidInput(_)));
+    s.precios
+        .asignar_lista_cliente(&mayorista.to_string(), ListaPreciosClienteRequest { id_lista_precios: Some(lista.id.clone()) })
+        .unwrap();
+    let hoy = |dias: i64| (Utc::now() + Duration::days(dias)).format("%Y-%m-%d %H:%M:%S").to_string();
+    let tres_por_dos = s
+        .precios
+        .crear_promocion(PromocionRequest {
+            nombre: "3x2".to_string(),
+            tipo: "LLEVA_PAGA".to_string(),
+            id_producto: producto.to_string(),
+            fecha_inicio: hoy(-1),
+            fecha_fin: hoy(1),
+            porcentaje: None,
+            cantidad_lleva: Some(3),
+            cantidad_paga: Some(2),
+            precio_paquete: None,
+        })
+        .unwrap();
+
+    let cotizar = |id_cliente: Uuid| {
+        let linea = LineaPrecioRequest { id_producto: producto.to_string(), cantidad: 1, unidad: Some("Caja x12".to_string()) };
+        s.precios
+            .cotizar(CotizarPreciosRequest { id_cliente: Some(id_cliente.to_string()), detalles: vec![linea] })
+            .unwrap()
+            .detalles
+            .remove(0)
+    };
+    // Sin precio de la caja en la lista: 12 x 80, y el 3x2 regala 4 de las 12 unidades
+    let linea = cotizar(mayorista);
+    assert_eq!((linea.precio_unitario, linea.descuento, linea.subtotal), (960.0, 320.0, 640.0));
+    assert_eq!((linea.origen, linea.id_lista_precios.clone()), (OrigenPrecio::Promocion, Some(lista.id.clone())));
+    assert_eq!(linea.id_promocion, Some(tres_por_dos.id.clone()));
+    // Sin lista, el precio de catálogo de la caja: 4 unidades de 1000 / 12
+    let linea = cotizar(cliente);
+    assert_eq!((linea.precio_unitario, linea.descuento, linea.subtotal), (1000.0, 333.33, 666.67));
+
+    // Con precio propio de la caja en la lista
+    fijar(900.0, Some("caja x12")).unwrap();
+    let mut request = s.venta(mayorista, &[(producto, 1)]);
+    request.detalles[0].unidad = Some("Caja x12".to_string());
+    let creada = s.ventas.procesar_venta(request).unwrap();
+    let detalle = &s.ventas.obtener_venta_por_id(&creada.id).unwrap().detalles[0];
+    assert_eq!((detalle.subtotal, detalle.descuento, detalle.cantidad_base), (600.0, 300.0, 12));
+    assert_eq!(s.stock(producto), 88);
+
+    // Quitar el precio de la caja vuelve al precio base de la lista por el factor
+    let quitar = |unidad: Option<&str>| {
+        s.precios.quitar_precio(&lista.id, &producto.to_string(), PrecioListaQuery { unidad: unidad.map(str::to_string) })
+    };
+    quitar(Some("Caja x12")).unwrap();
+    assert!(matches!(quitar(Some("Caja x12")).unwrap_err(), ApiError::NotFound(_)));
+    assert_eq!(cotizar(mayorista).subtotal, 640.0);
+    assert_eq!(s.precios.listar_precios(&lista.id).unwrap().len(), 1);
+}
+
+#[test]
+fn la_cotizacion_aceptada_se_vende_una_vez_con_sus_precios_o_los_vigentes() {
+    let s = Servicios::new();
+    s.persona("VENDEDOR");
+    let cliente = s.cliente_con_credito();
+    let producto = s.producto(10, 100.0);
+    let lista = s.precios.crear_lista(ListaPreciosRequest { nombre: "Mayorista".to_string(), descripcion: None }).unwrap();
+    let fijar = |precio: f64| {
+        s.precios
+            .fijar_precio(&lista.id, &producto.to_string(), PrecioListaRequest { precio_unitario: precio, unidad: None })
+            .unwrap()
+    };
+    fijar(80.0);
+    s.precios
+        .asignar_lista_cliente(&cliente.to_string(), ListaPreciosClienteRequest { id_lista_precios: Some(lista.id.clone()) })
+        .unwrap();
+
+    let cotizar = |cantidad: i32| {
+        let cotizacion = s
+            .cotizaciones
+            .crear(CotizacionRequest {
+                id_cliente: cliente.to_string(),
+                id_vendedor: None,
+                sucursal: None,
+                fecha_vencimiento: None,
+                observaciones: None,
+                detalles: vec![LineaPrecioRequest { id_producto: producto.to_string(), cantidad, unidad: None }],
+            })
+            .unwrap();
+        assert_eq!(cotizacion.estado, EstadoCotizacion::Borrador);
+        s.cotizaciones.enviar(&cotizacion.id).unwrap();
+        s.cotizaciones.aceptar(&cotizacion.id).unwrap();
+        cotizacion
+    };
+    let convertir = |servicio: &CotizacionService, id: &str| {
+        servicio.convertir(
+            id,
+            ConvertirCotizacionRequest {
+                id_sesion_caja: s.caja.to_string(),
+                pagos: vec![],
+                credito: false,
+                numeros_serie: vec![],
+            },
+        )
+    };
+
+    // Cotizada a 80, se vende a 80 aunque la lista ya diga 90
+    let mantenida = cotizar(3);
+    assert_eq!(mantenida.total, 240.0);
+    assert_eq!(s.stock(producto), 10);
+    fijar(90.0);
+    let creada = convertir(&s.cotizaciones, &mantenida.id).unwrap();
+    let venta = s.ventas.obtener_venta_por_id(&creada.id).unwrap();
+    assert_eq!((venta.total, venta.id_cotizacion.clone()), (240.0, Some(mantenida.id.clone())));
+    assert_eq!(s.stock(producto), 7);
+    let convertida = s.cotizaciones.obtener(&mantenida.id).unwrap();
+    assert_eq!((convertida.estado, convertida.id_venta), (EstadoCotizacion::Convertida, Some(creada.id)));
+    assert!(matches!(convertir(&s.cotizaciones, &mantenida.id).unwrap_err(), ApiError::BusinessRuleViolation(_)));
+
+    // Con la política de actualizar, la misma conversión toma el precio de lista vigente
+    let actualizar = CotizacionService::new(
+        s.repo.clone(), s.repo.clone(), s.repo.clone(), s.repo.clone(), s.ventas.clone(),
+        PoliticaPreciosCotizacion::Actualizar,
+    );
+    let actualizada = cotizar(2);
+    assert_eq!(actualizada.total, 180.0);
+    fijar(70.0);
+    let creada = convertir(&actualizar, &actualizada.id).unwrap();
+    assert_eq!(s.ventas.obtener_venta_por_id(&creada.id).unwrap().total, 140.0);
+
+    // La conversión vuelve a validar el stock, que la cotización no reserva
+    let sin_stock = cotizar(6);
+    let error = convertir(&s.cotizaciones, &sin_stock.id).unwrap_err();
+    assert!(matches!(error, ApiError::BusinessRuleViolation(ref m) if m.contains("Stock insuficiente")));
+    assert_eq!(s.cotizaciones.obtener(&sin_stock.id).unwrap().estado, EstadoCotizacion::Aceptada);
+    let aceptadas = s
+        .cotizaciones
+        .listar(CotizacionesQuery { id_cliente: Some(cliente.to_string()), estado: Some("aceptada".to_string()) })
+        .unwrap();
+    assert_eq!(aceptadas.len(), 1);
+}
+
+#[test]
+fn el_pedido_reserva_stock_espera_lo_que_falta_y_se_entrega_con_lo_despachado() {
+    let s = Servicios::new();
+    let vendedor = s.persona("VENDEDOR");
+    let cliente = s.persona("CLIENTE");
+    let producto = s.producto(5, 100.0);
+    let movimiento = |tipo: &str, cantidad: i32| MovimientoRequest {
+        id_producto: producto.to_string(),
+        tipo_movimiento: tipo.to_string(),
+        id_persona: vendedor.to_string(),
+        cantidad,
+        observaciones: None,
+        lote: None,
+        fecha_vencimiento: None,
+        numeros_serie: vec![],
+        costo_unitario: None,
+    };
+    let despacho = |detalles: Vec<LineaDespachoRequest>| DespachoRequest { id_persona: vendedor.to_string(), detalles };
+    let entregar = |id: &str| {
+        s.pedidos.entregar(
+            id,
+            EntregarPedidoRequest { id_sesion_caja: s.caja.to_string(), pagos: vec![pago("EFECTIVO", 800.0)], credito: false },
+        )
+    };
+
+    // Se asignan las 5 unidades libres y 3 quedan esperando stock
+    let pedido = s
+        .pedidos
+        .crear(PedidoRequest {
+            id_cliente: cliente.to_string(),
+            id_vendedor: None,
+            sucursal: None,
+            observaciones: None,
+            detalles: vec![LineaPrecioRequest { id_producto: producto.to_string(), cantidad: 8, unidad: None }],
+        })
+        .unwrap();
+    let linea = &pedido.detalles[0];
+    assert_eq!((pedido.estado, pedido.total), (EstadoPedido::Pendiente, 800.0));
+    assert_eq!((linea.cantidad_asignada, linea.cantidad_pendiente), (5, 3));
+    assert_eq!(s.inventario.obtener_disponibilidad(&producto.to_string()).unwrap().cantidad_reservada, 5);
+
+    // Lo reservado no se vende ni sale del stock por otro camino
+    let error = s.ventas.procesar_venta(s.venta(cliente, &[(producto, 1)])).unwrap_err();
+    assert!(matches!(error, ApiError::BusinessRuleViolation(ref m) if m.contains("Stock insuficiente")));
+    assert!(s.inventario.registrar_movimiento(movimiento("SALIDA", 1)).is_err());
+
+    assert!(matches!(s.pedidos.despachar(&pedido.id, despacho(vec![])).unwrap_err(), ApiError::BusinessRuleViolation(_)));
+    s.pedidos.preparar(&pedido.id).unwrap();
+    let error = s.pedidos.despachar(&pedido.id, despacho(vec![LineaDespachoRequest { linea: 1, cantidad: 6 }])).unwrap_err();
+    assert!(matches!(error, ApiError::BusinessRuleViolation(_)));
+    let parcial = s.pedidos.despachar(&pedido.id, despacho(vec![LineaDespachoRequest { linea: 1, cantidad: 4 }])).unwrap();
+    let linea = &parcial.detalles[0];
+    assert_eq!(parcial.estado, EstadoPedido::EnPreparacion);
+    assert_eq!((linea.cantidad_asignada, linea.cantidad_despachada, linea.cantidad_pendiente), (1, 4, 3));
+    assert_eq!(s.stock(producto), 1);
+    assert!(entregar(&pedido.id).is_err());
+
+    // La entrada cubre primero lo que el pedido esperaba
+    s.inventario.registrar_movimiento(movimiento("ENTRADA", 10)).unwrap();
+    let linea = &s.pedidos.obtener(&pedido.id).unwrap().detalles[0];
+    assert_eq!((linea.cantidad_asignada, linea.cantidad_pendiente), (4, 0));
+    let despachado = s.pedidos.despachar(&pedido.id, despacho(vec![])).unwrap();
+    assert_eq!(despachado.estado, EstadoPedido::Despachado);
+    assert_eq!(s.stock(producto), 7);
+    assert_eq!(s.repo.obtener_reservado(producto).unwrap(), 0);
+
+    // La venta final no vuelve a descontar stock
+    let creada = entregar(&pedido.id).unwrap();
+    let venta = s.ventas.obtener_venta_por_id(&creada.id).unwrap();
+    assert_eq!((venta.total, venta.estado, venta.id_pedido.clone()), (800.0, EstadoVenta::Pagada, Some(pedido.id.clone())));
+    assert_eq!(s.stock(producto), 7);
+    let entregado = s.pedidos.obtener(&pedido.id).unwrap();
+    assert_eq!((entregado.estado, entregado.id_venta), (EstadoPedido::Entregado, Some(creada.id)));
+    assert!(matches!(entregar(&pedido.id).unwrap_err(), ApiError::BusinessRuleViolation(_)));
+}
@@ -0,0 +1,123 @@
+//! Lógica de negocio implementada en la base de datos (ver la migración inicial)
+mod common;
+
+use std::time::Duration;
+use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use uuid::Uuid;
+
+use common::builders::{PersonaBuilder, ProductoBuilder, VentaBuilder};
+use common::TestDb;
+use poli_market_api::modules::common::types::TipoMovimiento;
+use poli_market_api::schema::{detalle_inventarios, personas, productos};
+
+#[test]
+fn insertar_detalle_venta_descuenta_stock_y_registra_salida() {
+    let db = TestDb::new();
+    let mut conn = db.conn();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut conn);
+    let cliente = PersonaBuilder::cliente().crear(&mut conn);
+    let producto = ProductoBuilder::new().stock(10).crear(&mut conn, &vendedor);
+
+    let id_venta = VentaBuilder::new(&cliente).item(&producto, 4).crear(&mut conn).unwrap();
+
+    assert_eq!(db.stock(producto.id), 6);
+
+    let (tipo, cantidad, id_persona, observaciones): (TipoMovimiento, i32, Uuid, Option<String>) =
+        detalle_inventarios::table
+            .filter(detalle_inventarios::id_producto.eq(producto.id))
+            .select((
+                detalle_inventarios::tipo_movimiento,
+                detalle_inventarios::cantidad,
+                detalle_inventarios::id_persona,
+                detalle_inventarios::observaciones,
+            ))
+            .first(&mut conn)
+            .unwrap();
+    assert_eq!(tipo, TipoMovimiento::Salida);
+    assert_eq!(cantidad, -4);
+    // Sin vendedor ni sesión de caja en la venta, la salida queda a nombre del cliente
+    assert_eq!(id_persona, cliente.id);
+    assert_eq!(observaciones, Some(format!("Venta ID: {}", id_venta)));
+}
+
+#[test]
+fn salida_de_venta_queda_a_nombre_del_vendedor() {
+    let db = TestDb::new();
+    let mut conn = db.conn();
+    let responsable = PersonaBuilder::vendedor().crear(&mut conn);
+    let vendedor = PersonaBuilder::vendedor().crear(&mut conn);
+    let cliente = PersonaBuilder::cliente().crear(&mut conn);
+    let producto = ProductoBuilder::new().stock(10).crear(&mut conn, &responsable);
+
+    VentaBuilder::new(&cliente)
+        .vendedor(&vendedor)
+        .item(&producto, 2)
+        .crear(&mut conn)
+        .unwrap();
+
+    let id_persona: Uuid = detalle_inventarios::table
+        .filter(detalle_inventarios::id_producto.eq(producto.id))
+        .filter(detalle_inventarios::tipo_movimiento.eq(TipoMovimiento::Salida))
+        .select(detalle_inventarios::id_persona)
+        .first(&mut conn)
+        .unwrap();
+    assert_eq!(id_persona, vendedor.id);
+}
+
+#[test]
+fn validar_stock_venta_rechaza_detalle_sin_stock() {
+    let db = TestDb::new();
+    let mut conn = db.conn();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut conn);
+    let cliente = PersonaBuilder::cliente().crear(&mut conn);
+    let producto = ProductoBuilder::new().stock(3).crear(&mut conn, &vendedor);
+
+    let error = VentaBuilder::new(&cliente)
+        .item(&producto, 4)
+        .crear(&mut conn)
+        .expect_err("el trigger debería rechazar la venta");
+
+    match error {
+        DieselError::DatabaseError(DatabaseErrorKind::Unknown, info) => {
+            assert_eq!(info.message(), format!("Stock insuficiente para el producto {}", producto.id));
+        }
+        otro => panic!("error inesperado: {:?}", otro),
+    }
+
+    // La transacción completa se revierte
+    assert_eq!(db.stock(producto.id), 3);
+    let movimientos: i64 = detalle_inventarios::table
+        .filter(detalle_inventarios::id_producto.eq(producto.id))
+        .count()
+        .get_result(&mut conn)
+        .unwrap();
+    assert_eq!(movimientos, 0);
+}
+
+#[test]
+fn actualizar_registro_renueva_fecha_actualizacion() {
+    let db = TestDb::new();
+    let mut conn = db.conn();
+    let persona = PersonaBuilder::cliente().crear(&mut conn);
+
+    std::thread::sleep(Duration::from_millis(20));
+    let (creacion, actualizacion) = diesel::update(personas::table.find(persona.id))
+        .set(personas::telefono.eq("+57 300 000 0000"))
+        .returning((personas::fecha_creacion, personas::fecha_actualizacion))
+        .get_result::<(chrono::NaiveDateTime, chrono::NaiveDateTime)>(&mut conn)
+        .unwrap();
+
+    assert_eq!(creacion, persona.fecha_creacion);
+    assert!(actualizacion > persona.fecha_actualizacion);
+}
+
+#[test]
+fn migraciones_no_cargan_datos_de_demostracion() {
+    let db = TestDb::new();
+    let mut conn = db.conn();
+
+    let personas: i64 = personas::table.count().get_result(&mut conn).unwrap();
+    let productos: i64 = productos::table.count().get_result(&mut conn).unwrap();
+    assert_eq!((personas, productos), (0, 0));
+}
@@ -0,0 +1,137 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test;
+use chrono::{Duration, Utc};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use poli_market_api::config::ConfigLayer;
+use poli_market_api::modules::inventarios::costos::MetodoValorizacion;
+
+use common::builders::{PersonaBuilder, ProductoBuilder, SesionCajaBuilder};
+use common::TestDb;
+
+fn entrada(id_producto: Uuid, id_persona: Uuid, cantidad: i32, costo_unitario: f64) -> Value {
+    json!({
+        "id_producto": id_producto,
+        "tipo_movimiento": "ENTRADA",
+        "id_persona": id_persona,
+        "cantidad": cantidad,
+        "costo_unitario": costo_unitario
+    })
+}
+
+/// Registra dos compras (10 a 100 y 10 a 130), vende 5 y devuelve el detalle de la venta
+/// y la valorización de hoy
+async fn comprar_y_vender(db: &TestDb) -> (Value, Value) {
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let cliente = PersonaBuilder::cliente().limite_credito(1_000_000).crear(&mut db.conn());
+    let monitor = ProductoBuilder::new().nombre("Monitor 24").stock(0).crear(&mut db.conn(), &vendedor);
+    let caja = SesionCajaBuilder::new(&vendedor).crear(&mut db.conn());
+    let app = app!(db);
+
+    for (cantidad, costo) in [(10, 100.0), (10, 130.0)] {
+        let req = test::TestRequest::post()
+            .uri("/v1/inventario/movimientos")
+            .set_json(entrada(monitor.id, vendedor.id, cantidad, costo))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+        let registrado: Value = test::read_body_json(res).await;
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/v1/inventario/movimientos/id/{}", registrado["id"].as_str().unwrap()))
+            .to_request();
+        let movimiento: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(movimiento["costo_unitario"], costo);
+    }
+
+    let req = test::TestRequest::post()
+        .uri("/v1/ventas")
+        .set_json(json!({"id_cliente": cliente.id, "id_sesion_caja": caja, "detalles": [{"id_producto": monitor.id, "cantidad": 5}]}))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let creada: Value = test::read_body_json(res).await;
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/v1/ventas/{}", creada["id"].as_str().unwrap()))
+        .to_request();
+    let venta: Value = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::get().uri("/v1/reportes/valorizacion").to_request();
+    let valorizacion: Value = test::call_and_read_body_json(&app, req).await;
+
+    (venta["detalles"][0].clone(), valorizacion)
+}
+
+#[actix_web::test]
+async fn promedio_ponderado_costea_la_venta_al_promedio() {
+    let db = TestDb::new();
+    let (detalle, valorizacion) = comprar_y_vender(&db).await;
+
+    assert_eq!(detalle["costo"], 575.0);
+    assert_eq!(valorizacion["metodo_valorizacion"], "PROMEDIO");
+    assert_eq!(valorizacion["valor_total"], 1725.0);
+    assert_eq!(valorizacion["productos"][0]["nombre_producto"], "Monitor 24");
+    assert_eq!(valorizacion["productos"][0]["cantidad"], 15);
+    assert_eq!(valorizacion["productos"][0]["costo_promedio"], 115.0);
+}
+
+#[actix_web::test]
+async fn fifo_costea_la_venta_con_las_capas_mas_antiguas() {
+    let db = TestDb::con_config(ConfigLayer {
+        valuation_method: Some(MetodoValorizacion::Fifo),
+        ..ConfigLayer::default()
+    });
+    let (detalle, valorizacion) = comprar_y_vender(&db).await;
+
+    assert_eq!(detalle["costo"], 500.0);
+    assert_eq!(valorizacion["metodo_valorizacion"], "FIFO");
+    assert_eq!(valorizacion["valor_total"], 1800.0);
+    assert_eq!(valorizacion["productos"][0]["cantidad"], 15);
+    assert_eq!(valorizacion["productos"][0]["costo_promedio"], 120.0);
+}
+
+#[actix_web::test]
+async fn valorizacion_a_una_fecha_pasada_ignora_los_movimientos_posteriores() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let silla = ProductoBuilder::new().nombre("Silla").stock(0).crear(&mut db.conn(), &vendedor);
+    let app = app!(db);
+
+    let req = test::TestRequest::post()
+        .uri("/v1/inventario/movimientos")
+        .set_json(entrada(silla.id, vendedor.id, 4, 25.5))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+
+    // El costo solo se indica en las entradas
+    let req = test::TestRequest::post()
+        .uri("/v1/inventario/movimientos")
+        .set_json(json!({
+            "id_producto": silla.id,
+            "tipo_movimiento": "SALIDA",
+            "id_persona": vendedor.id,
+            "cantidad": 1,
+            "costo_unitario": 10.0
+        }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+
+    let ayer = (Utc::now().date_naive() - Duration::days(1)).format("%Y-%m-%d");
+    let req = test::TestRequest::get()
+        .uri(&format!("/v1/reportes/valorizacion?fecha={}", ayer))
+        .to_request();
+    let valorizacion: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(valorizacion["valor_total"], 0.0);
+    assert!(valorizacion["productos"].as_array().unwrap().is_empty());
+
+    let req = test::TestRequest::get().uri("/v1/reportes/valorizacion").to_request();
+    let valorizacion: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(valorizacion["valor_total"], 102.0);
+
+    let req = test::TestRequest::get().uri("/v1/reportes/valorizacion?fecha=31-12-2026").to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+}
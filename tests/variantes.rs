@@ -0,0 +1,194 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test;
+use serde_json::{json, Value};
+
+use common::builders::{PersonaBuilder, ProductoBuilder, SesionCajaBuilder};
+use common::TestDb;
+
+#[actix_web::test]
+async fn variantes_heredan_el_catalogo_del_producto_padre() {
+    let db = TestDb::new();
+    PersonaBuilder::vendedor().crear(&mut db.conn());
+    let app = app!(db);
+
+    let req = test::TestRequest::post().uri("/v1/categorias").set_json(json!({"nombre": "Ropa"})).to_request();
+    let ropa = test::read_body_json::<Value, _>(test::call_service(&app, req).await).await["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let crear = |cuerpo: Value| test::TestRequest::post().uri("/v1/productos").set_json(cuerpo).to_request();
+    let req = crear(json!({
+        "nombre": "Camiseta básica",
+        "cantidad": 0,
+        "unidad_venta": "Unidad",
+        "precio_unitario": 30.0,
+        "id_categoria": ropa
+    }));
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let padre = test::read_body_json::<Value, _>(res).await["id"].as_str().unwrap().to_string();
+
+    let variante = |nombre: &str, atributos: Value| {
+        crear(json!({
+            "nombre": nombre,
+            "cantidad": 5,
+            "unidad_venta": "Unidad",
+            "precio_unitario": 30.0,
+            "id_producto_padre": padre,
+            "atributos": atributos
+        }))
+    };
+    let res = test::call_service(&app, variante("Camiseta básica M roja", json!({"Talla": "M", "color": " roja "}))).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let id_variante = test::read_body_json::<Value, _>(res).await["id"].as_str().unwrap().to_string();
+    let req = variante("Camiseta básica L roja", json!({"talla": "L", "color": "roja"}));
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+
+    let req = test::TestRequest::get().uri(&format!("/v1/productos/{}", id_variante)).to_request();
+    let consultada: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(consultada["id_producto_padre"], padre.as_str());
+    assert_eq!(consultada["id_categoria"], ropa.as_str());
+    assert_eq!(consultada["atributos"], json!({"color": "roja", "talla": "M"}));
+
+    let req = test::TestRequest::get().uri(&format!("/v1/productos/{}/variantes", padre)).to_request();
+    let variantes: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(variantes.as_array().unwrap().len(), 2);
+
+    // Misma combinación de atributos, variante de una variante y atributos sin padre
+    let res = test::call_service(&app, variante("Otra M roja", json!({"color": "roja", "talla": "M"}))).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let req = crear(json!({
+        "nombre": "Camiseta nieta",
+        "cantidad": 1,
+        "unidad_venta": "Unidad",
+        "precio_unitario": 30.0,
+        "id_producto_padre": id_variante,
+        "atributos": {"talla": "S"}
+    }));
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+    let req = crear(json!({
+        "nombre": "Camiseta suelta",
+        "cantidad": 1,
+        "unidad_venta": "Unidad",
+        "precio_unitario": 30.0,
+        "atributos": {"talla": "S"}
+    }));
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn venta_por_caja_descuenta_el_inventario_en_unidades_base() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let cliente = PersonaBuilder::cliente().limite_credito(1_000_000).crear(&mut db.conn());
+    let gaseosa = ProductoBuilder::new().nombre("Gaseosa 350ml").precio(2).stock(30).crear(&mut db.conn(), &vendedor);
+    let sesion = SesionCajaBuilder::new(&vendedor).crear(&mut db.conn());
+    let app = app!(db);
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/v1/productos/{}/unidades", gaseosa.id))
+        .set_json(json!({"nombre": "Caja x12", "factor": 12, "precio_unitario": 20.0}))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let caja: Value = test::read_body_json(res).await;
+    assert_eq!(caja["factor"], 12);
+
+    let req = test::TestRequest::post()
+        .uri("/v1/ventas")
+        .set_json(json!({
+            "id_cliente": cliente.id,
+            "id_sesion_caja": sesion,
+            "detalles": [
+                {"id_producto": gaseosa.id, "cantidad": 1, "unidad": "caja x12"},
+                {"id_producto": gaseosa.id, "cantidad": 3}
+            ]
+        }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let creada: Value = test::read_body_json(res).await;
+    assert_eq!(db.stock(gaseosa.id), 15);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/v1/ventas/{}", creada["id"].as_str().unwrap()))
+        .to_request();
+    let venta: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(venta["total"], 26.0);
+    let por_caja = venta["detalles"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|detalle| detalle["unidad"] == "Caja x12")
+        .expect("línea por caja");
+    assert_eq!(por_caja["cantidad"], 1);
+    assert_eq!(por_caja["cantidad_base"], 12);
+    assert_eq!(por_caja["precio_unitario"], 20.0);
+
+    // Dos cajas son 24 unidades y solo quedan 15; la validación suma ambas líneas
+    let vender = |detalles: Value| {
+        test::TestRequest::post()
+            .uri("/v1/ventas")
+            .set_json(json!({"id_cliente": cliente.id, "id_sesion_caja": sesion, "detalles": detalles}))
+            .to_request()
+    };
+    let req = vender(json!([{"id_producto": gaseosa.id, "cantidad": 2, "unidad": "Caja x12"}]));
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+    let req = vender(json!([
+        {"id_producto": gaseosa.id, "cantidad": 1, "unidad": "Caja x12"},
+        {"id_producto": gaseosa.id, "cantidad": 4}
+    ]));
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+    let req = vender(json!([{"id_producto": gaseosa.id, "cantidad": 1, "unidad": "Paca x24"}]));
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(db.stock(gaseosa.id), 15);
+}
+
+#[actix_web::test]
+async fn administrar_unidades_de_un_producto() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let arroz = ProductoBuilder::new().nombre("Arroz 500g").precio(3).crear(&mut db.conn(), &vendedor);
+    let app = app!(db);
+
+    let agregar = |cuerpo: Value| {
+        test::TestRequest::post()
+            .uri(&format!("/v1/productos/{}/unidades", arroz.id))
+            .set_json(cuerpo)
+            .to_request()
+    };
+    let res = test::call_service(&app, agregar(json!({"nombre": "Paca x25", "factor": 25}))).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let paca: Value = test::read_body_json(res).await;
+    // Sin precio propio, el de la unidad base por el factor
+    assert_eq!(paca["precio_unitario"], 75.0);
+
+    for invalida in [
+        json!({"nombre": "PACA X25", "factor": 20}),
+        json!({"nombre": "Unidad", "factor": 1}),
+        json!({"nombre": "Bulto", "factor": 0}),
+        json!({"nombre": "Bulto", "factor": 50, "precio_unitario": -1.0}),
+    ] {
+        let res = test::call_service(&app, agregar(invalida.clone())).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST, "{}", invalida);
+    }
+
+    let req = test::TestRequest::get().uri(&format!("/v1/productos/{}", arroz.id)).to_request();
+    let producto: Value = test::call_and_read_body_json(&app, req).await;
+    let unidades: Vec<&str> = producto["unidades"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|unidad| unidad["nombre"].as_str().unwrap())
+        .collect();
+    assert_eq!(unidades, ["Paca x25"]);
+
+    let uri = format!("/v1/productos/{}/unidades/{}", arroz.id, paca["id"].as_str().unwrap());
+    let req = test::TestRequest::delete().uri(&uri).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::NO_CONTENT);
+    let req = test::TestRequest::delete().uri(&uri).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,136 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test;
+use diesel::prelude::*;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use common::builders::{PersonaBuilder, ProductoBuilder, SesionCajaBuilder, VentaBuilder};
+use common::TestDb;
+use poli_market_api::modules::common::types::TipoMovimiento;
+use poli_market_api::schema::detalle_inventarios;
+
+#[actix_web::test]
+async fn crear_venta_descuenta_stock_y_registra_salida() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let cliente = PersonaBuilder::cliente().limite_credito(1_000_000).crear(&mut db.conn());
+    let teclado = ProductoBuilder::new().precio(80).stock(10).crear(&mut db.conn(), &vendedor);
+    let mouse = ProductoBuilder::new().precio(25).stock(5).crear(&mut db.conn(), &vendedor);
+    let caja = SesionCajaBuilder::new(&vendedor).crear(&mut db.conn());
+    let app = app!(db);
+
+    let req = test::TestRequest::post()
+        .uri("/v1/ventas")
+        .set_json(json!({
+            "id_cliente": cliente.id,
+            "id_sesion_caja": caja,
+            "sucursal": "Centro",
+            "detalles": [
+                {"id_producto": teclado.id, "cantidad": 2},
+                {"id_producto": mouse.id, "cantidad": 5}
+            ]
+        }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let creada: Value = test::read_body_json(res).await;
+
+    assert_eq!(db.stock(teclado.id), 8);
+    assert_eq!(db.stock(mouse.id), 0);
+
+    let salidas: Vec<i32> = detalle_inventarios::table
+        .filter(detalle_inventarios::id_producto.eq(teclado.id))
+        .filter(detalle_inventarios::tipo_movimiento.eq(TipoMovimiento::Salida))
+        .select(detalle_inventarios::cantidad)
+        .load(&mut db.conn())
+        .unwrap();
+    assert_eq!(salidas, vec![-2]);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/v1/ventas/{}", creada["id"].as_str().unwrap()))
+        .to_request();
+    let venta: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(venta["total"], 285.0);
+    assert_eq!(venta["sucursal"], "Centro");
+    assert_eq!(venta["detalles"].as_array().unwrap().len(), 2);
+}
+
+#[actix_web::test]
+async fn venta_sin_stock_suficiente_no_modifica_nada() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let cliente = PersonaBuilder::cliente().crear(&mut db.conn());
+    let producto = ProductoBuilder::new().stock(1).crear(&mut db.conn(), &vendedor);
+    let caja = SesionCajaBuilder::new(&vendedor).crear(&mut db.conn());
+    let app = app!(db);
+
+    let req = test::TestRequest::post()
+        .uri("/v1/ventas")
+        .set_json(json!({
+            "id_cliente": cliente.id,
+            "id_sesion_caja": caja,
+            "detalles": [{"id_producto": producto.id, "cantidad": 2}]
+        }))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let error: Value = test::read_body_json(res).await;
+    assert_eq!(error["code"], "BUSINESS_RULE_VIOLATION");
+    assert_eq!(db.stock(producto.id), 1);
+}
+
+#[actix_web::test]
+async fn venta_invalida_se_rechaza() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let cliente = PersonaBuilder::cliente().crear(&mut db.conn());
+    let inactivo = PersonaBuilder::cliente().inactiva().crear(&mut db.conn());
+    let producto = ProductoBuilder::new().crear(&mut db.conn(), &vendedor);
+    let caja = SesionCajaBuilder::new(&vendedor).crear(&mut db.conn());
+    let app = app!(db);
+
+    let casos = [
+        (json!({"id_cliente": cliente.id, "id_sesion_caja": caja, "detalles": []}), StatusCode::BAD_REQUEST),
+        (json!({"id_cliente": "x", "id_sesion_caja": caja, "detalles": [{"id_producto": producto.id, "cantidad": 1}]}), StatusCode::BAD_REQUEST),
+        (json!({"id_cliente": cliente.id, "id_sesion_caja": caja, "detalles": [{"id_producto": producto.id, "cantidad": 0}]}), StatusCode::BAD_REQUEST),
+        (json!({"id_cliente": cliente.id, "id_sesion_caja": caja, "detalles": [{"id_producto": Uuid::new_v4(), "cantidad": 1}]}), StatusCode::NOT_FOUND),
+        (json!({"id_cliente": inactivo.id, "id_sesion_caja": caja, "detalles": [{"id_producto": producto.id, "cantidad": 1}]}), StatusCode::NOT_FOUND),
+    ];
+
+    for (body, esperado) in casos {
+        let req = test::TestRequest::post().uri("/v1/ventas").set_json(&body).to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), esperado, "body: {}", body);
+    }
+    assert_eq!(db.stock(producto.id), 10);
+}
+
+#[actix_web::test]
+async fn listar_ventas_filtra_por_cliente_y_sucursal() {
+    let db = TestDb::new();
+    let vendedor = PersonaBuilder::vendedor().crear(&mut db.conn());
+    let ana = PersonaBuilder::cliente().crear(&mut db.conn());
+    let luis = PersonaBuilder::cliente().crear(&mut db.conn());
+    let producto = ProductoBuilder::new().stock(20).crear(&mut db.conn(), &vendedor);
+    VentaBuilder::new(&ana).sucursal("Norte").item(&producto, 1).crear(&mut db.conn()).unwrap();
+    VentaBuilder::new(&ana).sucursal("Sur").item(&producto, 1).crear(&mut db.conn()).unwrap();
+    VentaBuilder::new(&luis).sucursal("Norte").item(&producto, 1).crear(&mut db.conn()).unwrap();
+    let app = app!(db);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/v1/ventas?id_cliente={}", ana.id))
+        .to_request();
+    let ventas: Vec<Value> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(ventas.len(), 2);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/v1/ventas?id_cliente={}&sucursal=Norte", ana.id))
+        .to_request();
+    let ventas: Vec<Value> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(ventas.len(), 1);
+    assert_eq!(ventas[0]["detalles"][0]["id_producto"], producto.id.to_string());
+
+    let req = test::TestRequest::get().uri("/v1/ventas?fecha_desde=ayer").to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+}